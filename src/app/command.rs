@@ -2,16 +2,18 @@
 
 use std::path::PathBuf;
 
+use crate::notes::HighlightColor;
+
 /// Parsed command from the command line
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Command {
     /// Add a book from path: :add /path/to/book
     Add(PathBuf),
-    /// Remove a book: :remove <book-id>
+    /// Remove a book: :remove <book-id or title>
     Remove(String),
     /// Refresh book cache (re-parse without losing progress): :refresh
     Refresh,
-    /// Open/switch to a book: :open <book-id>
+    /// Open/switch to a book: :open <book-id or title>
     Open(String),
     /// List available books: :list
     List,
@@ -21,16 +23,32 @@ pub enum Command {
     Help,
     /// Search content: /pattern
     Search(String),
+    /// Search every book in the library: :search-all <query>
+    SearchLibrary(String),
     /// Go to specific section: :goto <path>
     Goto(String),
+    /// Jump to where reading last stopped: :resume
+    Resume,
+    /// Show study plan progress: :plan [name]
+    Plan(Option<String>),
+    /// Add a study plan: :plan-add <name>: <book> ch1-10, then <book> ...
+    PlanAdd(String),
+    /// Remove a study plan: :plan-remove <name>
+    PlanRemove(String),
+    /// Show sections due for review: :review
+    Review,
+    /// Tune FSRS to your review history: :review-tune
+    ReviewTune,
     /// Clear message: (empty command)
     Nop,
     /// Start Claude API setup wizard: :claude-setup
     ClaudeSetup,
     /// Set Claude API key: :claude-key <api-key>
     ClaudeKey(String),
-    /// Set Claude model: :claude-model <haiku|sonnet>
+    /// Set Claude model: :claude-model <haiku|sonnet|opus|model-id>
     ClaudeModel(String),
+    /// List the models available to the API key: :claude-models
+    ClaudeModels,
     /// Clear Claude state: :claude-clear
     ClaudeClear,
     /// Ask Claude a question: :ask <question>
@@ -39,6 +57,123 @@ pub enum Command {
     Explain(Option<String>),
     /// Ask Claude about selected text: :sel <question>
     AskSelection(String),
+    /// Ask Claude for a text diagram of selected text: :diagram [flow|memory|state]
+    Diagram(DiagramKind),
+    /// Save the code block under the cursor to a file: :wcode <path>
+    WriteCode(PathBuf),
+    /// Run the code block under the cursor with its language's runner: :run
+    RunCode,
+    /// Open the link under the cursor in the default browser: :open-link
+    OpenLink,
+    /// Compare the open book with another edition, or migrate progress and notes
+    /// from it: :editions <old-book>, :editions migrate <old-book>
+    Editions { book: String, migrate: bool },
+    /// Summarize the current chapter (cached unless refreshed): :summarize [refresh]
+    Summarize { refresh: bool },
+    /// Show the book's glossary, extracting the current chapter's terms if needed:
+    /// :glossary [refresh]
+    Glossary { refresh: bool },
+    /// Ask the current and study buddy models, side by side: :compare <question>
+    Compare(String),
+    /// Chapter office hours: explain the chapter back to Claude: :recap [explanation]
+    Recap(Option<String>),
+    /// Explain the current section back for Claude to grade: :teach [explanation]
+    TeachBack(Option<String>),
+    /// Show or hide the current section's learning objectives, writing them first
+    /// if needed: :objectives [refresh]
+    Objectives { refresh: bool },
+    /// Check off (or uncheck) one of the section's learning objectives: :objective <n>
+    Objective(usize),
+    /// Check terminal capabilities and suggest fixes: :doctor
+    Doctor,
+    /// Show Claude API usage and estimated cost: :usage
+    Usage,
+    /// Show study stats such as words written in notes: :stats
+    Stats,
+    /// Read the current section aloud: :speak [pause|resume|stop]
+    Speak(SpeakAction),
+    /// Open a section beside the content, from this book or another:
+    /// :split [@book] [section] | :split sync | :split close
+    Split(SplitAction),
+    /// Show or set Claude's persona for the current book: :persona [name]
+    Persona(Option<String>),
+    /// Show or override a setting for the current book (theme, textwidth,
+    /// persona, model, mode; "off" clears it): :bookset [key] [value]
+    BookSet { key: Option<String>, value: Option<String> },
+    /// Show or switch the book's reading mode (normal, focus, skim, review): :mode [name]
+    Mode(Option<String>),
+    /// Export annotated chapters as HTML pages: :export-html [dir]
+    ExportHtml(Option<String>),
+    /// Export the book in a registered format: :export <format> [path]
+    Export { format: String, path: Option<String> },
+    /// Jump to a footnote, or back to where you were: :footnote [n]
+    Footnote(Option<String>),
+    /// Show or change a setting: :set <option>[=<value>]
+    Set { option: String, value: Option<String> },
+    /// Go to a note by its ID: :note <id>
+    Note(String),
+    /// Note a thought on the current section without opening the editor: :capture <text>
+    Capture(String),
+    /// Delete a note by its ID: :note-delete <id>
+    NoteDelete(String),
+    /// Search notes in every book, or open the search box: :notes search [query]
+    NotesSearch(Option<String>),
+    /// Replace text across the book's notes, after a preview: :notes s/old/new/[g][i]
+    NotesReplace(String),
+    /// List only annotations on the text in view as it scrolls, or everything again: :notes follow
+    NotesFollow,
+    /// List the book's highlights, or only those of one color: :highlights [color]
+    Highlights(Option<HighlightColor>),
+}
+
+/// Playback control for :speak
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeakAction {
+    /// Start reading, or pause/resume if already reading
+    Toggle,
+    /// Pause reading
+    Pause,
+    /// Resume paused reading
+    Resume,
+    /// Stop reading
+    Stop,
+}
+
+/// What :split does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SplitAction {
+    /// Open (or change) the split pane; a missing book means the open one and
+    /// a missing section the current one
+    Open { book: Option<String>, section: Option<String> },
+    /// Turn synchronized scrolling on or off
+    ToggleSync,
+    /// Close the split pane
+    Close,
+}
+
+/// What kind of diagram :diagram asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagramKind {
+    /// Whichever suits the passage best
+    Auto,
+    /// Flowchart of steps and decisions
+    Flow,
+    /// Memory layout: stack, heap, pointers
+    Memory,
+    /// State machine of states and transitions
+    State,
+}
+
+impl DiagramKind {
+    /// The diagram as a phrase, e.g. "a state machine diagram"
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Auto => "the diagram (flowchart, memory layout or state machine) that best fits",
+            Self::Flow => "a flowchart",
+            Self::Memory => "a memory layout diagram",
+            Self::State => "a state machine diagram",
+        }
+    }
 }
 
 /// Result of parsing a command
@@ -98,6 +233,33 @@ pub fn parse_command(input: &str) -> ParseResult {
                 ParseResult::Ok(Command::Goto(args.to_string()))
             }
         }
+        "resume" => ParseResult::Ok(Command::Resume),
+        "plan" | "plans" => {
+            ParseResult::Ok(Command::Plan((!args.is_empty()).then(|| args.to_string())))
+        }
+        "plan-add" => {
+            if args.is_empty() {
+                ParseResult::MissingArgument("plan-add".to_string())
+            } else {
+                ParseResult::Ok(Command::PlanAdd(args.to_string()))
+            }
+        }
+        "plan-remove" => {
+            if args.is_empty() {
+                ParseResult::MissingArgument("plan-remove".to_string())
+            } else {
+                ParseResult::Ok(Command::PlanRemove(args.to_string()))
+            }
+        }
+        "review" | "reviews" => ParseResult::Ok(Command::Review),
+        "review-tune" => ParseResult::Ok(Command::ReviewTune),
+        "search-all" | "sa" => {
+            if args.is_empty() {
+                ParseResult::MissingArgument("search-all".to_string())
+            } else {
+                ParseResult::Ok(Command::SearchLibrary(args.to_string()))
+            }
+        }
         "claude-setup" | "cs" => ParseResult::Ok(Command::ClaudeSetup),
         "claude-key" | "ck" => {
             if args.is_empty() {
@@ -113,6 +275,7 @@ pub fn parse_command(input: &str) -> ParseResult {
                 ParseResult::Ok(Command::ClaudeModel(args.to_string()))
             }
         }
+        "claude-models" => ParseResult::Ok(Command::ClaudeModels),
         "claude-clear" | "cc" => ParseResult::Ok(Command::ClaudeClear),
         "ask" => {
             if args.is_empty() {
@@ -133,10 +296,180 @@ pub fn parse_command(input: &str) -> ParseResult {
                 ParseResult::Ok(Command::AskSelection(args.to_string()))
             }
         }
+        "diagram" | "draw" => match args.to_lowercase().as_str() {
+            "" => ParseResult::Ok(Command::Diagram(DiagramKind::Auto)),
+            "flow" | "flowchart" => ParseResult::Ok(Command::Diagram(DiagramKind::Flow)),
+            "memory" | "mem" | "layout" => ParseResult::Ok(Command::Diagram(DiagramKind::Memory)),
+            "state" | "states" | "fsm" => ParseResult::Ok(Command::Diagram(DiagramKind::State)),
+            _ => ParseResult::UnknownCommand(format!("diagram {}", args)),
+        },
+        "wcode" => {
+            if args.is_empty() {
+                ParseResult::MissingArgument("wcode".to_string())
+            } else {
+                ParseResult::Ok(Command::WriteCode(PathBuf::from(args)))
+            }
+        }
+        "run" => ParseResult::Ok(Command::RunCode),
+        "open-link" => ParseResult::Ok(Command::OpenLink),
+        "editions" | "edition" => match args.split_once(' ').unwrap_or((args, "")) {
+            ("", _) => ParseResult::MissingArgument("editions".to_string()),
+            ("migrate", "") => ParseResult::MissingArgument("editions migrate".to_string()),
+            ("migrate", book) => {
+                ParseResult::Ok(Command::Editions { book: book.trim().to_string(), migrate: true })
+            }
+            _ => ParseResult::Ok(Command::Editions { book: args.to_string(), migrate: false }),
+        },
+        "usage" | "cost" => ParseResult::Ok(Command::Usage),
+        "stats" | "dashboard" => ParseResult::Ok(Command::Stats),
+        "doctor" | "checkhealth" => ParseResult::Ok(Command::Doctor),
+        "summarize" | "summary" | "sum" => match args.to_lowercase().as_str() {
+            "" => ParseResult::Ok(Command::Summarize { refresh: false }),
+            "refresh" | "!" => ParseResult::Ok(Command::Summarize { refresh: true }),
+            _ => ParseResult::UnknownCommand(format!("summarize {}", args)),
+        },
+        "glossary" | "terms" => match args.to_lowercase().as_str() {
+            "" => ParseResult::Ok(Command::Glossary { refresh: false }),
+            "refresh" | "!" => ParseResult::Ok(Command::Glossary { refresh: true }),
+            _ => ParseResult::UnknownCommand(format!("glossary {}", args)),
+        },
+        "compare" | "buddy" => {
+            if args.is_empty() {
+                ParseResult::MissingArgument("compare".to_string())
+            } else {
+                ParseResult::Ok(Command::Compare(args.to_string()))
+            }
+        }
+        "recap" | "office-hours" | "oh" => {
+            let explanation = if args.is_empty() { None } else { Some(args.to_string()) };
+            ParseResult::Ok(Command::Recap(explanation))
+        }
+        "teach" | "teach-back" => {
+            let explanation = if args.is_empty() { None } else { Some(args.to_string()) };
+            ParseResult::Ok(Command::TeachBack(explanation))
+        }
+        "objectives" | "goals" => match args.to_lowercase().as_str() {
+            "" => ParseResult::Ok(Command::Objectives { refresh: false }),
+            "refresh" | "!" => ParseResult::Ok(Command::Objectives { refresh: true }),
+            _ => ParseResult::UnknownCommand(format!("objectives {}", args)),
+        },
+        "objective" | "goal" => match args.parse() {
+            Ok(number) => ParseResult::Ok(Command::Objective(number)),
+            Err(_) if args.is_empty() => ParseResult::MissingArgument("objective".to_string()),
+            Err(_) => ParseResult::UnknownCommand(format!("objective {}", args)),
+        },
+        "persona" | "p" => {
+            let name = if args.is_empty() { None } else { Some(args.to_lowercase()) };
+            ParseResult::Ok(Command::Persona(name))
+        }
+        "export" => {
+            if args.is_empty() {
+                return ParseResult::MissingArgument("export".to_string());
+            }
+            let (format, path) = match args.split_once(char::is_whitespace) {
+                Some((format, path)) => (format, Some(path.trim().to_string())),
+                None => (args, None),
+            };
+            ParseResult::Ok(Command::Export { format: format.to_lowercase(), path })
+        }
+        "export-html" | "html" => {
+            let dir = if args.is_empty() { None } else { Some(args.to_string()) };
+            ParseResult::Ok(Command::ExportHtml(dir))
+        }
+        "footnote" | "fn" => {
+            let label = if args.is_empty() { None } else { Some(args.to_string()) };
+            ParseResult::Ok(Command::Footnote(label))
+        }
+        "set" | "se" => {
+            if args.is_empty() {
+                return ParseResult::MissingArgument("set".to_string());
+            }
+            let (option, value) = match args.split_once('=') {
+                Some((option, value)) => (option.trim(), Some(value.trim().to_string())),
+                None => (args, None),
+            };
+            ParseResult::Ok(Command::Set { option: option.to_lowercase(), value })
+        }
+        "mode" => ParseResult::Ok(Command::Mode((!args.is_empty()).then(|| args.to_string()))),
+        "bookset" | "bset" => {
+            let (key, value) = match args.split_once([' ', '=']) {
+                Some((key, value)) => (key, Some(value.trim().to_string())),
+                None => (args, None),
+            };
+            let key = (!key.is_empty()).then(|| key.to_lowercase());
+            ParseResult::Ok(Command::BookSet { key, value: value.filter(|v| !v.is_empty()) })
+        }
+        "note" => {
+            if args.is_empty() {
+                ParseResult::MissingArgument("note".to_string())
+            } else {
+                ParseResult::Ok(Command::Note(args.to_string()))
+            }
+        }
+        "capture" | "cap" => {
+            if args.is_empty() {
+                ParseResult::MissingArgument("capture".to_string())
+            } else {
+                ParseResult::Ok(Command::Capture(args.to_string()))
+            }
+        }
+        "note-delete" | "note-rm" => {
+            if args.is_empty() {
+                ParseResult::MissingArgument("note-delete".to_string())
+            } else {
+                ParseResult::Ok(Command::NoteDelete(args.to_string()))
+            }
+        }
+        "notes" => match args.split_once(' ').unwrap_or((args, "")) {
+            _ if args
+                .strip_prefix('s')
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(|c| !c.is_alphanumeric() && !c.is_whitespace()) =>
+            {
+                ParseResult::Ok(Command::NotesReplace(args.to_string()))
+            }
+            ("" | "search", query) => ParseResult::Ok(Command::NotesSearch(
+                (!query.trim().is_empty()).then(|| query.trim().to_string()),
+            )),
+            ("follow", "") => ParseResult::Ok(Command::NotesFollow),
+            _ => ParseResult::UnknownCommand(format!("notes {}", args)),
+        },
+        "highlights" | "hl" if args.is_empty() => ParseResult::Ok(Command::Highlights(None)),
+        "highlights" | "hl" => match HighlightColor::from_name(args) {
+            Some(color) => ParseResult::Ok(Command::Highlights(Some(color))),
+            None => ParseResult::UnknownCommand(format!("highlights {}", args)),
+        },
+        "speak" | "tts" => match args.to_lowercase().as_str() {
+            "" => ParseResult::Ok(Command::Speak(SpeakAction::Toggle)),
+            "pause" => ParseResult::Ok(Command::Speak(SpeakAction::Pause)),
+            "resume" | "play" => ParseResult::Ok(Command::Speak(SpeakAction::Resume)),
+            "stop" => ParseResult::Ok(Command::Speak(SpeakAction::Stop)),
+            _ => ParseResult::UnknownCommand(format!("speak {}", args)),
+        },
+        "split" | "vsplit" | "sp" | "vs" => ParseResult::Ok(Command::Split(parse_split(args))),
+        "only" => ParseResult::Ok(Command::Split(SplitAction::Close)),
         _ => ParseResult::UnknownCommand(cmd.to_string()),
     }
 }
 
+/// Parse the arguments of :split
+fn parse_split(args: &str) -> SplitAction {
+    match args.to_lowercase().as_str() {
+        "sync" | "scrollbind" | "bind" => return SplitAction::ToggleSync,
+        "close" | "off" => return SplitAction::Close,
+        _ => {}
+    }
+    let (book, section) = match args.strip_prefix('@') {
+        Some(rest) => {
+            let (book, section) = rest.split_once(' ').unwrap_or((rest, ""));
+            (Some(book.to_string()), section.trim())
+        }
+        None => (None, args),
+    };
+    let section = (!section.is_empty()).then(|| section.to_string());
+    SplitAction::Open { book, section }
+}
+
 /// Parse a search query (without the leading /)
 pub fn parse_search(input: &str) -> Command {
     Command::Search(input.to_string())
@@ -220,6 +553,310 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_resume_command() {
+        assert!(matches!(parse_command("resume"), ParseResult::Ok(Command::Resume)));
+    }
+
+    #[test]
+    fn parse_plan_commands() {
+        assert!(matches!(
+            parse_command("plan Rust path"),
+            ParseResult::Ok(Command::Plan(Some(name))) if name == "Rust path"
+        ));
+        assert!(matches!(parse_command("plans"), ParseResult::Ok(Command::Plan(None))));
+        assert!(matches!(parse_command("plan-add"), ParseResult::MissingArgument(_)));
+        assert!(matches!(
+            parse_command("plan-add Rust: rust-book ch1-10"),
+            ParseResult::Ok(Command::PlanAdd(spec)) if spec == "Rust: rust-book ch1-10"
+        ));
+    }
+
+    #[test]
+    fn parse_review_commands() {
+        assert!(matches!(parse_command("reviews"), ParseResult::Ok(Command::Review)));
+        assert!(matches!(parse_command("review-tune"), ParseResult::Ok(Command::ReviewTune)));
+    }
+
+    #[test]
+    fn parse_usage_command() {
+        assert!(matches!(parse_command("usage"), ParseResult::Ok(Command::Usage)));
+        assert!(matches!(parse_command("cost"), ParseResult::Ok(Command::Usage)));
+    }
+
+    #[test]
+    fn parse_stats_command() {
+        assert!(matches!(parse_command("stats"), ParseResult::Ok(Command::Stats)));
+        assert!(matches!(parse_command("dashboard"), ParseResult::Ok(Command::Stats)));
+    }
+
+    #[test]
+    fn parse_doctor_command() {
+        assert!(matches!(parse_command("doctor"), ParseResult::Ok(Command::Doctor)));
+        assert!(matches!(parse_command("checkhealth"), ParseResult::Ok(Command::Doctor)));
+    }
+
+    #[test]
+    fn parse_summarize_command() {
+        assert!(matches!(
+            parse_command("summarize"),
+            ParseResult::Ok(Command::Summarize { refresh: false })
+        ));
+        assert!(matches!(
+            parse_command("sum refresh"),
+            ParseResult::Ok(Command::Summarize { refresh: true })
+        ));
+        assert!(matches!(parse_command("summarize now"), ParseResult::UnknownCommand(_)));
+    }
+
+    #[test]
+    fn parse_glossary() {
+        assert!(matches!(
+            parse_command("glossary"),
+            ParseResult::Ok(Command::Glossary { refresh: false })
+        ));
+        assert!(matches!(
+            parse_command("terms refresh"),
+            ParseResult::Ok(Command::Glossary { refresh: true })
+        ));
+        assert!(matches!(parse_command("glossary all"), ParseResult::UnknownCommand(_)));
+    }
+
+    #[test]
+    fn parse_compare_command() {
+        match parse_command("buddy What is a move?") {
+            ParseResult::Ok(Command::Compare(question)) => assert_eq!(question, "What is a move?"),
+            _ => panic!("Expected Compare command"),
+        }
+        assert!(matches!(parse_command("compare"), ParseResult::MissingArgument(_)));
+    }
+
+    #[test]
+    fn parse_recap_command() {
+        assert!(matches!(parse_command("recap"), ParseResult::Ok(Command::Recap(None))));
+        match parse_command("oh Moves transfer ownership") {
+            ParseResult::Ok(Command::Recap(Some(text))) => {
+                assert_eq!(text, "Moves transfer ownership")
+            }
+            _ => panic!("Expected Recap command"),
+        }
+    }
+
+    #[test]
+    fn parse_teach_back_command() {
+        assert!(matches!(parse_command("teach"), ParseResult::Ok(Command::TeachBack(None))));
+        match parse_command("teach-back Borrows are checked at compile time") {
+            ParseResult::Ok(Command::TeachBack(Some(text))) => {
+                assert_eq!(text, "Borrows are checked at compile time")
+            }
+            _ => panic!("Expected TeachBack command"),
+        }
+    }
+
+    #[test]
+    fn parse_objectives_commands() {
+        assert!(matches!(
+            parse_command("objectives"),
+            ParseResult::Ok(Command::Objectives { refresh: false })
+        ));
+        assert!(matches!(
+            parse_command("goals refresh"),
+            ParseResult::Ok(Command::Objectives { refresh: true })
+        ));
+        assert!(matches!(parse_command("objective 2"), ParseResult::Ok(Command::Objective(2))));
+        assert!(matches!(parse_command("objective"), ParseResult::MissingArgument(_)));
+        assert!(matches!(parse_command("objective two"), ParseResult::UnknownCommand(_)));
+    }
+
+    #[test]
+    fn parse_persona_command() {
+        assert!(matches!(parse_command("persona"), ParseResult::Ok(Command::Persona(None))));
+        match parse_command("persona ELI5") {
+            ParseResult::Ok(Command::Persona(Some(name))) => assert_eq!(name, "eli5"),
+            other => panic!("Expected Persona command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_footnote_command() {
+        assert!(matches!(parse_command("fn"), ParseResult::Ok(Command::Footnote(None))));
+        match parse_command("footnote 3") {
+            ParseResult::Ok(Command::Footnote(Some(label))) => assert_eq!(label, "3"),
+            other => panic!("Expected Footnote command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_set_command() {
+        match parse_command("set textwidth=80") {
+            ParseResult::Ok(Command::Set { option, value }) => {
+                assert_eq!(option, "textwidth");
+                assert_eq!(value.as_deref(), Some("80"));
+            }
+            other => panic!("Expected Set command, got {:?}", other),
+        }
+        assert!(matches!(
+            parse_command("se TW"),
+            ParseResult::Ok(Command::Set { ref option, value: None }) if option == "tw"
+        ));
+        assert!(matches!(parse_command("set"), ParseResult::MissingArgument(_)));
+    }
+
+    #[test]
+    fn parse_bookset_command() {
+        let bookset = |input| match parse_command(input) {
+            ParseResult::Ok(Command::BookSet { key, value }) => (key, value),
+            other => panic!("Expected BookSet command, got {:?}", other),
+        };
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(bookset("bookset"), (None, None));
+        assert!(matches!(parse_command("mode"), ParseResult::Ok(Command::Mode(None))));
+        assert!(matches!(
+            parse_command("mode Skim"),
+            ParseResult::Ok(Command::Mode(Some(name))) if name == "Skim"
+        ));
+        assert_eq!(bookset("bookset TW"), (some("tw"), None));
+        assert_eq!(bookset("bookset textwidth 110"), (some("textwidth"), some("110")));
+        assert_eq!(bookset("bset theme=Tokyo Night"), (some("theme"), some("Tokyo Night")));
+    }
+
+    #[test]
+    fn parse_note_commands() {
+        match parse_command("note #a1b2c3") {
+            ParseResult::Ok(Command::Note(id)) => assert_eq!(id, "#a1b2c3"),
+            other => panic!("Expected Note command, got {:?}", other),
+        }
+        assert!(matches!(parse_command("note-rm a1b"), ParseResult::Ok(Command::NoteDelete(_))));
+        assert!(matches!(parse_command("note"), ParseResult::MissingArgument(_)));
+        assert!(matches!(
+            parse_command("cap Check this against ch03"),
+            ParseResult::Ok(Command::Capture(text)) if text == "Check this against ch03"
+        ));
+        assert!(matches!(parse_command("capture"), ParseResult::MissingArgument(_)));
+        assert!(matches!(parse_command("note-delete"), ParseResult::MissingArgument(_)));
+        assert!(matches!(
+            parse_command("notes search borrow checker"),
+            ParseResult::Ok(Command::NotesSearch(Some(q))) if q == "borrow checker"
+        ));
+        assert!(matches!(parse_command("notes"), ParseResult::Ok(Command::NotesSearch(None))));
+        assert!(matches!(
+            parse_command("notes s/Teh/The/g"),
+            ParseResult::Ok(Command::NotesReplace(e)) if e == "s/Teh/The/g"
+        ));
+        assert!(matches!(parse_command("notes follow"), ParseResult::Ok(Command::NotesFollow)));
+        assert!(matches!(parse_command("notes delete"), ParseResult::UnknownCommand(_)));
+        assert!(matches!(parse_command("highlights"), ParseResult::Ok(Command::Highlights(None))));
+        assert!(matches!(
+            parse_command("hl Green"),
+            ParseResult::Ok(Command::Highlights(Some(HighlightColor::Green)))
+        ));
+        assert!(matches!(parse_command("hl red"), ParseResult::UnknownCommand(_)));
+    }
+
+    #[test]
+    fn parse_export_html_command() {
+        assert!(matches!(parse_command("export-html"), ParseResult::Ok(Command::ExportHtml(None))));
+        match parse_command("html ~/notes") {
+            ParseResult::Ok(Command::ExportHtml(Some(dir))) => assert_eq!(dir, "~/notes"),
+            other => panic!("Expected ExportHtml command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_export_command() {
+        assert!(matches!(parse_command("export"), ParseResult::MissingArgument(_)));
+        match parse_command("export HTML ~/notes/book.html") {
+            ParseResult::Ok(Command::Export { format, path }) => {
+                assert_eq!(format, "html");
+                assert_eq!(path.as_deref(), Some("~/notes/book.html"));
+            }
+            other => panic!("Expected Export command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_speak_command() {
+        assert!(matches!(
+            parse_command("speak"),
+            ParseResult::Ok(Command::Speak(SpeakAction::Toggle))
+        ));
+        assert!(matches!(
+            parse_command("speak pause"),
+            ParseResult::Ok(Command::Speak(SpeakAction::Pause))
+        ));
+        assert!(matches!(
+            parse_command("tts stop"),
+            ParseResult::Ok(Command::Speak(SpeakAction::Stop))
+        ));
+        assert!(matches!(parse_command("speak loudly"), ParseResult::UnknownCommand(_)));
+    }
+
+    #[test]
+    fn parse_split_commands() {
+        let open = |book: Option<&str>, section: Option<&str>| {
+            Command::Split(SplitAction::Open {
+                book: book.map(String::from),
+                section: section.map(String::from),
+            })
+        };
+        let parsed = |input| match parse_command(input) {
+            ParseResult::Ok(command) => command,
+            _ => panic!("Expected a command for {}", input),
+        };
+        assert_eq!(parsed("split"), open(None, None));
+        assert_eq!(parsed("vs ch03/s02"), open(None, Some("ch03/s02")));
+        assert_eq!(parsed("split @rust-2018"), open(Some("rust-2018"), None));
+        assert_eq!(
+            parsed("sp @rust-2018 Error handling"),
+            open(Some("rust-2018"), Some("Error handling"))
+        );
+        assert_eq!(parsed("split sync"), Command::Split(SplitAction::ToggleSync));
+        assert_eq!(parsed("split close"), Command::Split(SplitAction::Close));
+        assert_eq!(parsed("only"), Command::Split(SplitAction::Close));
+    }
+
+    #[test]
+    fn test_parse_code_block_commands() {
+        assert!(matches!(
+            parse_command("wcode /tmp/main.rs"),
+            ParseResult::Ok(Command::WriteCode(path)) if path == std::path::Path::new("/tmp/main.rs")
+        ));
+        assert!(matches!(parse_command("wcode"), ParseResult::MissingArgument(_)));
+        assert!(matches!(parse_command("run"), ParseResult::Ok(Command::RunCode)));
+        assert!(matches!(parse_command("open-link"), ParseResult::Ok(Command::OpenLink)));
+    }
+
+    #[test]
+    fn test_parse_editions() {
+        assert!(matches!(
+            parse_command("editions rust-book-1e"),
+            ParseResult::Ok(Command::Editions { book, migrate: false }) if book == "rust-book-1e"
+        ));
+        assert!(matches!(
+            parse_command("editions migrate The Rust Book"),
+            ParseResult::Ok(Command::Editions { book, migrate: true }) if book == "The Rust Book"
+        ));
+        assert!(matches!(parse_command("editions"), ParseResult::MissingArgument(_)));
+        assert!(matches!(parse_command("editions migrate"), ParseResult::MissingArgument(_)));
+    }
+
+    #[test]
+    fn test_parse_diagram() {
+        assert!(matches!(
+            parse_command("diagram"),
+            ParseResult::Ok(Command::Diagram(DiagramKind::Auto))
+        ));
+        assert!(matches!(
+            parse_command("diagram memory"),
+            ParseResult::Ok(Command::Diagram(DiagramKind::Memory))
+        ));
+        assert!(matches!(
+            parse_command("draw fsm"),
+            ParseResult::Ok(Command::Diagram(DiagramKind::State))
+        ));
+        assert!(matches!(parse_command("diagram venn"), ParseResult::UnknownCommand(_)));
+    }
+
     #[test]
     fn parse_unknown_command() {
         assert!(matches!(parse_command("unknown"), ParseResult::UnknownCommand(_)));
@@ -231,6 +868,15 @@ mod tests {
         assert!(matches!(parse_command("   "), ParseResult::Ok(Command::Nop)));
     }
 
+    #[test]
+    fn test_parse_search_library() {
+        let result = parse_command("search-all borrow checker");
+        assert!(
+            matches!(result, ParseResult::Ok(Command::SearchLibrary(q)) if q == "borrow checker")
+        );
+        assert!(matches!(parse_command("sa"), ParseResult::MissingArgument(_)));
+    }
+
     #[test]
     fn test_parse_search() {
         let cmd = super::parse_search("test query");
@@ -0,0 +1,200 @@
+//! Terminal health check
+//!
+//! Detects the terminal capabilities sensei relies on (colors, images,
+//! clipboard, unicode) and suggests fixes for anything missing. Run at startup
+//! and by `:doctor`.
+
+use ratatui_image::picker::ProtocolType;
+
+/// Outcome of a single capability check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// Capability being checked
+    pub name: &'static str,
+    /// Whether the capability is fully available
+    pub ok: bool,
+    /// What was detected
+    pub detail: String,
+    /// Suggested adjustment when the check fails
+    pub suggestion: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, ok: true, detail: detail.into(), suggestion: None }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, suggestion: impl Into<String>) -> Self {
+        Self { name, ok: false, detail: detail.into(), suggestion: Some(suggestion.into()) }
+    }
+}
+
+/// Run all checks
+///
+/// `env` looks up environment variables, `protocol` is the image protocol the
+/// terminal was detected to support and `clipboard` is the result of opening
+/// the system clipboard.
+pub fn run_checks(
+    env: impl Fn(&str) -> Option<String>,
+    protocol: ProtocolType,
+    clipboard: Result<(), String>,
+) -> Vec<Check> {
+    vec![
+        check_truecolor(&env),
+        check_images(&env, protocol),
+        check_clipboard(&env, clipboard),
+        check_unicode(&env),
+    ]
+}
+
+/// Check the process environment and the real clipboard
+pub fn run_system_checks(protocol: ProtocolType) -> Vec<Check> {
+    let clipboard = arboard::Clipboard::new().map(|_| ()).map_err(|e| e.to_string());
+    run_checks(|name| std::env::var(name).ok(), protocol, clipboard)
+}
+
+/// Format checks as a markdown report
+pub fn report(checks: &[Check]) -> String {
+    let mut out = String::from("# Terminal health check\n\n");
+    for check in checks {
+        let mark = if check.ok { "✓" } else { "✗" };
+        out.push_str(&format!("- {} **{}**: {}\n", mark, check.name, check.detail));
+        if let Some(suggestion) = &check.suggestion {
+            out.push_str(&format!("  - {}\n", suggestion));
+        }
+    }
+    if checks.iter().all(|c| c.ok) {
+        out.push_str("\nEverything looks good.\n");
+    }
+    out
+}
+
+/// Themes use 24-bit colors
+fn check_truecolor(env: &impl Fn(&str) -> Option<String>) -> Check {
+    let colorterm = env("COLORTERM").unwrap_or_default().to_lowercase();
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        return Check::ok("Truecolor", format!("COLORTERM={}", colorterm));
+    }
+
+    let detail = if colorterm.is_empty() {
+        "COLORTERM is not set".to_string()
+    } else {
+        format!("COLORTERM={}", colorterm)
+    };
+    Check::warn(
+        "Truecolor",
+        detail,
+        "Theme colors may be approximated. If your terminal supports 24-bit color, \
+         set COLORTERM=truecolor (in tmux, also add `set -as terminal-features ',*:RGB'`).",
+    )
+}
+
+/// Images need a graphics protocol; half-blocks are a low-resolution fallback
+fn check_images(env: &impl Fn(&str) -> Option<String>, protocol: ProtocolType) -> Check {
+    let name = match protocol {
+        ProtocolType::Halfblocks => "half-blocks",
+        ProtocolType::Sixel => "Sixel",
+        ProtocolType::Kitty => "Kitty graphics",
+        ProtocolType::Iterm2 => "iTerm2 inline images",
+    };
+
+    if protocol != ProtocolType::Halfblocks {
+        return Check::ok("Images", name);
+    }
+
+    let suggestion = if env("TMUX").is_some() {
+        "tmux hides graphics support. Run sensei outside tmux, or enable \
+         `set -g allow-passthrough on` in a terminal with Kitty or Sixel graphics."
+    } else {
+        "Images are drawn with half-blocks. Use a terminal with Kitty, Sixel or iTerm2 \
+         graphics (e.g. kitty, WezTerm, Ghostty, foot or iTerm2) for full-resolution images."
+    };
+    Check::warn("Images", name, suggestion)
+}
+
+/// Yanking copies to the system clipboard
+fn check_clipboard(env: &impl Fn(&str) -> Option<String>, clipboard: Result<(), String>) -> Check {
+    match clipboard {
+        Ok(()) => Check::ok("Clipboard", "system clipboard available"),
+        Err(e) => {
+            let suggestion = if env("SSH_CONNECTION").is_some() {
+                "The system clipboard isn't reachable over SSH; yank won't copy to your machine."
+            } else if cfg!(target_os = "linux")
+                && env("WAYLAND_DISPLAY").is_none()
+                && env("DISPLAY").is_none()
+            {
+                "No display server found. Run sensei inside a graphical session \
+                 (DISPLAY or WAYLAND_DISPLAY must be set) to use the clipboard."
+            } else {
+                "Check that a clipboard service is running for your desktop environment."
+            };
+            Check::warn("Clipboard", format!("unavailable ({})", e), suggestion)
+        }
+    }
+}
+
+/// Wide and combining characters render correctly only in a UTF-8 locale
+fn check_unicode(env: &impl Fn(&str) -> Option<String>) -> Check {
+    if env("TERM").is_some_and(|term| term == "linux") {
+        return Check::warn(
+            "Unicode",
+            "TERM=linux (virtual console)",
+            "The Linux console can't draw many symbols used by sensei. \
+             Use a terminal emulator instead.",
+        );
+    }
+
+    // The first of these that is set decides the character encoding
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| env(name).filter(|v| !v.is_empty()).map(|v| (*name, v)));
+    match locale {
+        Some((name, value)) => {
+            let lower = value.to_lowercase();
+            if lower.contains("utf-8") || lower.contains("utf8") {
+                Check::ok("Unicode", format!("{}={}", name, value))
+            } else {
+                Check::warn(
+                    "Unicode",
+                    format!("{}={}", name, value),
+                    "Set a UTF-8 locale (e.g. LANG=en_US.UTF-8) so wide characters line up.",
+                )
+            }
+        }
+        None => Check::warn(
+            "Unicode",
+            "no locale set",
+            "Set a UTF-8 locale (e.g. LANG=en_US.UTF-8) so wide characters line up.",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_from(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |name| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+    }
+
+    #[test]
+    fn healthy_terminal_passes_all_checks() {
+        let env = env_from(&[("COLORTERM", "truecolor"), ("LANG", "en_US.UTF-8")]);
+        let checks = run_checks(env, ProtocolType::Kitty, Ok(()));
+
+        assert!(checks.iter().all(|c| c.ok), "{:?}", checks);
+        assert!(report(&checks).contains("Everything looks good."));
+    }
+
+    #[test]
+    fn limited_terminal_gets_suggestions() {
+        let env = env_from(&[("TERM", "screen"), ("TMUX", "/tmp/tmux"), ("LC_ALL", "C")]);
+        let checks = run_checks(env, ProtocolType::Halfblocks, Err("no provider".into()));
+
+        assert!(checks.iter().all(|c| !c.ok && c.suggestion.is_some()));
+        let images = checks.iter().find(|c| c.name == "Images").unwrap();
+        assert!(images.suggestion.as_deref().unwrap().contains("tmux"));
+        let unicode = checks.iter().find(|c| c.name == "Unicode").unwrap();
+        assert_eq!(unicode.detail, "LC_ALL=C");
+    }
+}
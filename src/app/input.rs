@@ -1,7 +1,13 @@
 //! Event handling utilities
 
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+
 use crossterm::event::{KeyCode, KeyModifiers};
 
+use super::state::TextObject;
+use crate::config::KeyAcceleration;
+
 /// Vim-style key mapping (basic, without modifiers)
 pub fn vim_key_to_action(key: KeyCode) -> Option<Action> {
     match key {
@@ -20,6 +26,7 @@ pub fn vim_key_to_action(key: KeyCode) -> Option<Action> {
         KeyCode::Char('N') => Some(Action::PrevMatch),
         KeyCode::Char('v') => Some(Action::VisualMode),
         KeyCode::Char('?') => Some(Action::Help),
+        KeyCode::Char(':') => Some(Action::Command),
         // Note: 'q' intentionally not mapped - use :q command to quit
         // Panel toggles
         KeyCode::Char('[') | KeyCode::Char('1') => Some(Action::ToggleCurriculum),
@@ -37,8 +44,27 @@ pub fn vim_key_to_action(key: KeyCode) -> Option<Action> {
         KeyCode::Char('w') => Some(Action::WordForward),
         KeyCode::Char('b') => Some(Action::WordBackward),
         KeyCode::Char('E') => Some(Action::WordEnd),
+        KeyCode::Char('s') => Some(Action::Save),
+        KeyCode::Char('o') => Some(Action::FollowLink),
+        KeyCode::Char('F') => Some(Action::FollowView),
+        KeyCode::Char('C') => Some(Action::Capture),
+        KeyCode::Char('H') => Some(Action::Highlight),
+        // Filtering the focused list
+        KeyCode::Char('f') => Some(Action::Filter),
+        // Skim mode
+        KeyCode::Char('Z') => Some(Action::ToggleSkim),
+        KeyCode::Char('z') => Some(Action::ExpandBlock),
+        // Marks (followed by a letter)
+        KeyCode::Char('M') => Some(Action::SetMark),
+        KeyCode::Char('\'') | KeyCode::Char('`') => Some(Action::GotoMark),
+        KeyCode::Char('c') => Some(Action::ToggleResponse),
+        KeyCode::Char('p') => Some(Action::Previous),
         // Clipboard (yank like vim)
         KeyCode::Char('y') => Some(Action::Yank),
+        KeyCode::Char('Y') => Some(Action::YankCode),
+        // Terminals send Ctrl+I as Tab
+        KeyCode::Tab => Some(Action::JumpForward),
+        KeyCode::BackTab => Some(Action::JumpBack),
         _ => None,
     }
 }
@@ -57,6 +83,11 @@ pub fn key_with_modifier_to_action(key: KeyCode, modifiers: KeyModifiers) -> Opt
             KeyCode::Char('k') => Some(Action::LineUp),
             KeyCode::Char('n') => Some(Action::LineDown),
             KeyCode::Char('p') => Some(Action::LineUp),
+            // Jump list
+            KeyCode::Char('o') => Some(Action::JumpBack),
+            KeyCode::Char('i') => Some(Action::JumpForward),
+            KeyCode::Char('c') => Some(Action::Cancel),
+            KeyCode::Char('s') => Some(Action::Submit),
             _ => None,
         }
     } else {
@@ -106,9 +137,47 @@ pub enum Action {
     VisualMode,
     Help,
     Quit,
+    /// Enter a command on the command line
+    Command,
+    /// Stop Claude's answer while it streams
+    Cancel,
+    /// Filter the curriculum or search the notes
+    Filter,
+
+    // Marks (the letter is read next)
+    SetMark,
+    GotoMark,
+
+    // Skim mode
+    ToggleSkim,
+    /// Expand a collapsed block in skim mode
+    ExpandBlock,
+
+    // Claude's answer
+    /// Show or hide the answer (or, on a highlight, change its color)
+    ToggleResponse,
+    /// Save the answer as a note
+    Save,
+
+    // Notes
+    /// Capture a quick note about the section
+    Capture,
+    /// Follow a link in the selected note
+    FollowLink,
+    /// List only the notes on the text in view
+    FollowView,
+    /// Highlight the visual selection
+    Highlight,
+    /// Save what is being written
+    Submit,
+
+    /// The previous quiz question (or, selecting, character-precise selection)
+    Previous,
 
     // Clipboard
     Yank,
+    /// Copy the whole code block under the cursor
+    YankCode,
 
     // Word motions (for visual mode)
     WordForward,
@@ -118,12 +187,464 @@ pub enum Action {
     // Line motions (for cursor mode within blocks)
     LineUp,
     LineDown,
+
+    // Jump list
+    JumpBack,
+    JumpForward,
+}
+
+/// What the reader is doing, for choosing which key hints to show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintContext {
+    /// Curriculum tree focused
+    Curriculum,
+    /// Content focused, scrolling
+    Content,
+    /// Content focused with the text cursor
+    Cursor,
+    /// Selecting text
+    Visual,
+    /// Notes panel focused
+    Notes,
+    /// Taking a quiz
+    Quiz,
+    /// Reading a Claude response
+    ClaudePanel,
+}
+
+/// Actions worth a hint in a context, with what they do there
+fn hinted_actions(context: HintContext) -> &'static [(&'static [Action], &'static str)] {
+    use Action::*;
+
+    match context {
+        HintContext::Curriculum => &[
+            (&[Down, Up], "move"),
+            (&[Select], "open"),
+            (&[Search], "filter"),
+            (&[MarkComplete], "done"),
+            (&[Right], "content"),
+            (&[ToggleCurriculum], "hide"),
+        ],
+        HintContext::Content => &[
+            (&[Down, Up], "scroll"),
+            (&[PageDown, PageUp], "page"),
+            (&[VisualMode], "cursor"),
+            (&[CreateNote], "note"),
+            (&[MarkComplete], "done"),
+            (&[Left, Right], "panels"),
+            (&[Help], "help"),
+        ],
+        HintContext::Cursor => &[
+            (&[Left, Down, Up, Right], "move"),
+            (&[WordForward, WordBackward], "word"),
+            (&[VisualMode], "select"),
+            (&[YankCode], "copy code"),
+            (&[Back], "exit"),
+        ],
+        HintContext::Visual => &[
+            (&[WordForward, WordEnd], "extend"),
+            (&[Select], "annotate"),
+            (&[Yank], "copy"),
+            (&[Back], "cancel"),
+        ],
+        HintContext::Notes => &[
+            (&[Down, Up], "move"),
+            (&[Select], "expand"),
+            (&[CreateNote], "new"),
+            (&[EditNote], "edit"),
+            (&[DeleteNote], "delete"),
+            (&[Search], "search"),
+        ],
+        HintContext::Quiz => &[(&[Down, Up], "choose"), (&[Select], "answer"), (&[Back], "close")],
+        HintContext::ClaudePanel => {
+            &[(&[Down, Up], "scroll"), (&[PageDown, PageUp], "page"), (&[Back], "close")]
+        }
+    }
+}
+
+/// Keys in the order hints prefer them: printable keys, then named keys, then
+/// Ctrl combinations
+fn hint_keys() -> impl Iterator<Item = (KeyCode, KeyModifiers)> {
+    let named = [
+        KeyCode::Enter,
+        KeyCode::Esc,
+        KeyCode::Tab,
+        KeyCode::Left,
+        KeyCode::Down,
+        KeyCode::Up,
+        KeyCode::Right,
+        KeyCode::Home,
+        KeyCode::End,
+        KeyCode::PageUp,
+        KeyCode::PageDown,
+        KeyCode::BackTab,
+    ];
+    ('!'..='~')
+        .map(KeyCode::Char)
+        .chain(named)
+        .map(|key| (key, KeyModifiers::NONE))
+        .chain(('a'..='z').map(|c| (KeyCode::Char(c), KeyModifiers::CONTROL)))
+}
+
+/// The key bound to an action, as shown in hints
+pub fn key_for(action: Action) -> Option<String> {
+    keys_for(action).into_iter().next()
+}
+
+/// Every key bound to an action, in the order hints prefer them
+pub fn keys_for(action: Action) -> Vec<String> {
+    hint_keys()
+        .filter(|&(key, modifiers)| key_with_modifier_to_action(key, modifiers) == Some(action))
+        .map(|(key, modifiers)| match key {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => format!("^{}", c),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            other => format!("{:?}", other),
+        })
+        .collect()
+}
+
+/// Key hints for the idle command line: (keys, what they do), most useful first
+///
+/// Keys come from the keymap, so a hint whose action has lost its key is left
+/// out rather than shown wrong.
+pub fn key_hints(context: HintContext) -> Vec<(String, &'static str)> {
+    let mut hints: Vec<(String, &'static str)> = hinted_actions(context)
+        .iter()
+        .filter_map(|(actions, what)| {
+            let keys: Option<Vec<String>> = actions.iter().map(|&a| key_for(a)).collect();
+            Some((keys?.join("/"), *what))
+        })
+        .collect();
+    // Commands can be entered everywhere but overlays
+    if !matches!(context, HintContext::Quiz | HintContext::ClaudePanel)
+        && let Some(key) = key_for(Action::Command)
+    {
+        hints.push((key, "commands"));
+    }
+    hints
+}
+
+/// Letters marks are named by, read after `SetMark` or `GotoMark`
+pub const MARK_NAMES: RangeInclusive<char> = 'a'..='z';
+
+/// Keys read after the text object key in visual mode, for each object
+pub const TEXT_OBJECT_KEYS: [(char, TextObject); 3] =
+    [('s', TextObject::Sentence), ('l', TextObject::ListItem), ('b', TextObject::Block)];
+
+/// Keys picking each quiz option: a digit, or the letter the option is shown with
+pub const QUIZ_OPTION_KEYS: [[char; 2]; 4] = [['1', 'a'], ['2', 'b'], ['3', 'c'], ['4', 'd']];
+
+/// The quiz option a key picks
+pub fn quiz_option(key: KeyCode) -> Option<usize> {
+    let KeyCode::Char(c) = key else { return None };
+    QUIZ_OPTION_KEYS.iter().position(|keys| keys.contains(&c))
+}
+
+/// The text object a key names after the text object key
+pub fn text_object(key: KeyCode) -> Option<TextObject> {
+    let KeyCode::Char(c) = key else { return None };
+    TEXT_OBJECT_KEYS.iter().find(|(k, _)| *k == c).map(|(_, object)| *object)
+}
+
+/// Keys of an entry on the help screen
+#[derive(Debug, Clone, Copy)]
+enum HelpKeys {
+    /// Whatever the keymap binds to these actions
+    Actions(&'static [Action]),
+    /// An action's key, then the letter of a mark
+    Mark(Action),
+    /// The text object key in visual mode, then the object's key
+    TextObject,
+    /// Keys picking a quiz option
+    QuizOptions,
+}
+
+impl HelpKeys {
+    /// The keys as shown, or None if an action has lost its key
+    fn shown(self) -> Option<String> {
+        match self {
+            HelpKeys::Actions(actions) => {
+                let keys: Vec<String> = actions
+                    .iter()
+                    .map(|&a| Some(keys_for(a)).filter(|k| !k.is_empty()))
+                    .map(|k| k.map(|k| k.join(" ")))
+                    .collect::<Option<_>>()?;
+                Some(keys.join(" / "))
+            }
+            HelpKeys::Mark(action) => {
+                Some(format!("{} {}-{}", key_for(action)?, MARK_NAMES.start(), MARK_NAMES.end()))
+            }
+            HelpKeys::TextObject => {
+                let objects: Vec<String> =
+                    TEXT_OBJECT_KEYS.iter().map(|(key, _)| key.to_string()).collect();
+                Some(format!("{} {}", key_for(Action::CreateNote)?, objects.join("/")))
+            }
+            HelpKeys::QuizOptions => {
+                let (first, last) =
+                    (QUIZ_OPTION_KEYS[0], QUIZ_OPTION_KEYS[QUIZ_OPTION_KEYS.len() - 1]);
+                Some(format!("{}-{} {}-{}", first[0], last[0], first[1], last[1]))
+            }
+        }
+    }
+}
+
+/// Everything on the help screen: each context with its keys and what they
+/// do there
+fn help_table() -> &'static [(&'static str, &'static [(HelpKeys, &'static str)])] {
+    use Action::*;
+    use HelpKeys::{Actions, Mark, QuizOptions, TextObject};
+
+    &[
+        (
+            "Global",
+            &[
+                (Actions(&[Help]), "show or close this help"),
+                (Actions(&[Command]), "enter a command"),
+                (Actions(&[Left, Right]), "focus the panel to the left / right"),
+                (Actions(&[ToggleCurriculum]), "show or hide the curriculum"),
+                (Actions(&[ToggleNotes]), "show or hide the notes"),
+                (Actions(&[DecreasePanelWidth, IncreasePanelWidth]), "narrow / widen the panels"),
+                (Actions(&[Search]), "search the book"),
+                (Actions(&[NextMatch, PrevMatch]), "next / previous match"),
+                (Actions(&[MarkComplete]), "mark the section complete"),
+                (Actions(&[JumpBack, JumpForward]), "jump back / forward"),
+                (Mark(SetMark), "set a mark"),
+                (Mark(GotoMark), "jump to a mark"),
+                (Actions(&[Capture]), "capture a quick note"),
+                (Actions(&[ToggleResponse]), "show or hide Claude's answer"),
+                (Actions(&[Cancel]), "stop Claude's answer"),
+                (Actions(&[Back]), "close or go back"),
+            ],
+        ),
+        (
+            "Curriculum",
+            &[
+                (Actions(&[Down, Up]), "move"),
+                (Actions(&[Select]), "open the section"),
+                (Actions(&[Filter]), "filter the sections"),
+            ],
+        ),
+        (
+            "Content",
+            &[
+                (Actions(&[Down, Up]), "scroll"),
+                (Actions(&[HalfPageDown, HalfPageUp]), "half a page down / up"),
+                (Actions(&[PageDown, PageUp]), "a page down / up"),
+                (Actions(&[Top, Bottom]), "start / end of the section"),
+                (Actions(&[VisualMode]), "enter cursor mode"),
+                (Actions(&[CreateNote]), "add a note to the section"),
+                (Actions(&[ToggleSkim]), "turn skim mode on or off"),
+                (Actions(&[ExpandBlock]), "expand a skimmed block"),
+            ],
+        ),
+        (
+            "Cursor mode",
+            &[
+                (Actions(&[Left, Down, Up, Right]), "move the cursor"),
+                (Actions(&[WordForward, WordBackward, WordEnd]), "next / previous / end of word"),
+                (Actions(&[LineDown, LineUp]), "next / previous line of the block"),
+                (Actions(&[VisualMode]), "start or stop selecting"),
+                (Actions(&[YankCode]), "copy the code block"),
+                (Actions(&[Yank]), "copy the selection"),
+                (Actions(&[Select]), "annotate the selection"),
+                (TextObject, "select the sentence / list item / block"),
+                (Actions(&[Highlight]), "highlight the selection"),
+                (Actions(&[Previous]), "select by character or by word"),
+                (Actions(&[Back]), "leave cursor mode"),
+            ],
+        ),
+        (
+            "Quiz",
+            &[
+                (Actions(&[Down, Up]), "choose an answer"),
+                (QuizOptions, "pick an option"),
+                (Actions(&[Select]), "answer"),
+                (Actions(&[NextMatch, Previous]), "next / previous question"),
+                (Actions(&[EditNote]), "edit the section's questions, once answered"),
+                (Actions(&[Back]), "close the quiz"),
+            ],
+        ),
+        (
+            "Question editor",
+            &[
+                (Actions(&[Down, Up]), "move"),
+                (Actions(&[Select]), "edit the question"),
+                (Actions(&[CreateNote]), "write a new question"),
+                (Actions(&[DeleteNote]), "delete the question"),
+                (Actions(&[Submit]), "save the question being written"),
+                (Actions(&[Back]), "stop writing, or close the editor"),
+            ],
+        ),
+        (
+            "Notes",
+            &[
+                (Actions(&[Down, Up]), "move"),
+                (Actions(&[Select]), "expand the note"),
+                (Actions(&[CreateNote]), "new note"),
+                (Actions(&[EditNote]), "edit the note"),
+                (Actions(&[DeleteNote]), "delete the note"),
+                (Actions(&[Filter]), "search the notes"),
+                (Actions(&[FollowLink]), "follow a link in the note"),
+                (Actions(&[FollowView]), "list only notes on the text in view"),
+                (Actions(&[ToggleResponse]), "change a highlight's color"),
+            ],
+        ),
+        (
+            "Claude's answer",
+            &[
+                (Actions(&[Down, Up]), "scroll"),
+                (Actions(&[PageDown, PageUp]), "a page down / up"),
+                (Actions(&[Top, Bottom]), "start / end of the answer"),
+                (Actions(&[Yank]), "copy the answer"),
+                (Actions(&[Save]), "save the answer as a note"),
+                (Actions(&[CreateNote]), "add the answer to the selected note"),
+                (Actions(&[JumpForward, JumpBack]), "next / previous citation"),
+                (Actions(&[Select]), "go to the cited passage"),
+                (Actions(&[Back, ToggleResponse]), "close"),
+            ],
+        ),
+    ]
+}
+
+/// A context's keys on the help screen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpGroup {
+    /// Where the keys work
+    pub title: &'static str,
+    /// Keys and what they do
+    pub entries: Vec<(String, &'static str)>,
+}
+
+/// The help screen's keys, from the keymap, keeping those matching `query`
+/// (by key, description or context); contexts left empty are dropped
+pub fn help_groups(query: &str) -> Vec<HelpGroup> {
+    let query = query.trim().to_lowercase();
+    help_table()
+        .iter()
+        .map(|(title, entries)| {
+            let title_matches = title.to_lowercase().contains(&query);
+            let entries = entries
+                .iter()
+                .filter_map(|&(keys, what)| {
+                    let keys = keys.shown()?;
+                    let matches = title_matches
+                        || what.to_lowercase().contains(&query)
+                        || keys.to_lowercase().contains(&query);
+                    matches.then_some((keys, what))
+                })
+                .collect();
+            HelpGroup { title, entries }
+        })
+        .filter(|group| !group.entries.is_empty())
+        .collect()
+}
+
+/// Tracks rapid repeats of the same action to accelerate held navigation keys
+#[derive(Debug, Clone, Default)]
+pub struct KeyRepeat {
+    /// Last action seen and when it arrived
+    last: Option<(Action, Instant)>,
+    /// Number of consecutive repeats of the last action
+    repeats: u32,
+}
+
+impl KeyRepeat {
+    /// Register an action press and return the step multiplier to apply
+    pub fn register(&mut self, action: Action, now: Instant, config: &KeyAcceleration) -> u32 {
+        let window = Duration::from_millis(config.repeat_window_ms);
+        let is_repeat = matches!(
+            self.last,
+            Some((last, at)) if last == action && now.saturating_duration_since(at) <= window
+        );
+
+        self.repeats = if is_repeat { self.repeats.saturating_add(1) } else { 0 };
+        self.last = Some((action, now));
+
+        if !config.enabled || config.repeats_per_step == 0 {
+            return 1;
+        }
+        (1 + self.repeats / config.repeats_per_step).min(config.max_multiplier.max(1))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn key_repeat_accelerates_held_key() {
+        let config = KeyAcceleration::default();
+        let mut repeat = KeyRepeat::default();
+        let start = Instant::now();
+        let step = Duration::from_millis(30);
+
+        let multipliers: Vec<u32> =
+            (0..40).map(|i| repeat.register(Action::Down, start + step * i, &config)).collect();
+        assert_eq!(multipliers[0], 1);
+        assert_eq!(multipliers[8], 2);
+        assert_eq!(multipliers[39], 5);
+
+        // A pause resets the speed
+        assert_eq!(repeat.register(Action::Down, start + Duration::from_secs(5), &config), 1);
+    }
+
+    #[test]
+    fn key_repeat_resets_on_other_action_or_disabled() {
+        let mut config = KeyAcceleration { repeats_per_step: 1, ..Default::default() };
+        let mut repeat = KeyRepeat::default();
+        let now = Instant::now();
+
+        repeat.register(Action::Down, now, &config);
+        assert_eq!(repeat.register(Action::Down, now, &config), 2);
+        assert_eq!(repeat.register(Action::Up, now, &config), 1);
+
+        config.enabled = false;
+        repeat.register(Action::Up, now, &config);
+        assert_eq!(repeat.register(Action::Up, now, &config), 1);
+    }
+
+    #[test]
+    fn key_for_finds_bound_keys() {
+        assert_eq!(key_for(Action::Down).as_deref(), Some("j"));
+        assert_eq!(key_for(Action::Select).as_deref(), Some("Enter"));
+        assert_eq!(key_for(Action::HalfPageDown).as_deref(), Some("^d"));
+        assert_eq!(key_for(Action::Quit), None);
+    }
+
+    #[test]
+    fn every_hinted_action_has_a_key() {
+        use HintContext::*;
+        for context in [Curriculum, Content, Cursor, Visual, Notes, Quiz, ClaudePanel] {
+            let hints = key_hints(context);
+            let from_keymap = hints.iter().filter(|(key, _)| key != ":").count();
+            assert_eq!(from_keymap, hinted_actions(context).len(), "{context:?}");
+        }
+        assert_eq!(key_hints(Cursor)[0], ("h/j/k/l".to_string(), "move"));
+    }
+
+    #[test]
+    fn help_lists_every_bound_key() {
+        let groups = help_groups("");
+        let titles: Vec<&str> = groups.iter().map(|g| g.title).collect();
+        assert_eq!(
+            titles[..7],
+            ["Global", "Curriculum", "Content", "Cursor mode", "Quiz", "Question editor", "Notes"]
+        );
+        let content = &groups[2].entries;
+        assert_eq!(content[0], ("j ↓ / k ↑".to_string(), "scroll"));
+        assert!(content.contains(&("^d / ^u".to_string(), "half a page down / up")));
+
+        let found = help_groups("HIGHLIGHT");
+        let entries: Vec<&str> =
+            found.iter().flat_map(|g| g.entries.iter().map(|(_, what)| *what)).collect();
+        assert_eq!(entries, ["highlight the selection", "change a highlight's color"]);
+        assert_eq!(help_groups("quiz")[0].entries.len(), 6);
+    }
+
     #[test]
     fn vim_j_maps_to_down() {
         assert_eq!(vim_key_to_action(KeyCode::Char('j')), Some(Action::Down));
@@ -136,7 +657,7 @@ mod tests {
 
     #[test]
     fn unknown_key_returns_none() {
-        assert_eq!(vim_key_to_action(KeyCode::Char('z')), None);
+        assert_eq!(vim_key_to_action(KeyCode::Char('q')), None);
     }
 
     #[test]
@@ -267,6 +788,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ctrl_o_and_ctrl_i_move_through_jump_list() {
+        assert_eq!(
+            key_with_modifier_to_action(KeyCode::Char('o'), KeyModifiers::CONTROL),
+            Some(Action::JumpBack)
+        );
+        assert_eq!(
+            key_with_modifier_to_action(KeyCode::Char('i'), KeyModifiers::CONTROL),
+            Some(Action::JumpForward)
+        );
+        assert_eq!(vim_key_to_action(KeyCode::Tab), Some(Action::JumpForward));
+    }
+
     #[test]
     fn ctrl_unknown_returns_none() {
         assert_eq!(key_with_modifier_to_action(KeyCode::Char('x'), KeyModifiers::CONTROL), None);
@@ -283,6 +817,11 @@ mod tests {
         assert_eq!(vim_key_to_action(KeyCode::Char('a')), Some(Action::CreateNote));
     }
 
+    #[test]
+    fn shift_y_maps_to_yank_code() {
+        assert_eq!(vim_key_to_action(KeyCode::Char('Y')), Some(Action::YankCode));
+    }
+
     #[test]
     fn e_maps_to_edit_note() {
         assert_eq!(vim_key_to_action(KeyCode::Char('e')), Some(Action::EditNote));
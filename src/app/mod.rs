@@ -1,7 +1,9 @@
 //! Application state and event handling
 
 pub mod command;
+pub mod doctor;
 pub mod input;
+pub mod selftest;
 pub mod state;
 
 use std::io::{self, Stdout};
@@ -9,22 +11,37 @@ use std::io::{self, Stdout};
 use anyhow::Result;
 use crossterm::{
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
-        MouseButton, MouseEventKind,
+        DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEventKind,
     },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use futures_util::{FutureExt, StreamExt};
 use ratatui::{Terminal, backend::CrosstermBackend};
 
-use crate::book::storage;
-use crate::config::{Config, progress::Progress, session::Session};
+use crate::book::excerpt::{chapter_excerpt, numbered_section_excerpt, section_excerpt};
+use crate::book::storage::{self, BookMatch, LibraryEntry};
+use crate::book::{Book, Section};
+use crate::config::{
+    AutoMarkMode, Config,
+    datetime::{self, DateTimeFormat},
+    progress::Progress,
+    session::{LastRead, Session},
+};
+use crate::learning::bank::QuestionBank;
+use crate::learning::objectives::Objectives;
+use crate::learning::plan::{StepProgress, StudyPlan};
 use crate::notes::NotesStore;
+use crate::notes::anchor::{ReanchorReport, block_text};
+use crate::speech::SpeechEvent;
 use crate::ui;
+use crate::ui::content::ScrollAnchor;
+use crate::ui::curriculum::CurriculumItem;
 use crate::ui::image::ImageCache;
-use command::{Command, ParseResult, parse_command};
-use input::{Action, key_with_modifier_to_action};
-use state::{AppState, CommandMode, Panel, Screen};
+use command::{Command, DiagramKind, ParseResult, SpeakAction, SplitAction, parse_command};
+use input::{Action, HintContext, KeyRepeat, key_with_modifier_to_action};
+use state::{AppState, CommandMode, Panel, ReadingMode, Report, Screen};
 
 /// The main application
 pub struct App {
@@ -57,27 +74,255 @@ pub struct App {
 
     /// Channel receiver for quiz generation results
     quiz_rx: Option<tokio::sync::mpsc::Receiver<QuizGenerationResult>>,
+    /// Channel receiver for study buddy answers
+    buddy_rx: Option<tokio::sync::mpsc::Receiver<BuddyResult>>,
+    /// Channel receiver for the model list being fetched
+    models_rx: Option<tokio::sync::mpsc::Receiver<ModelListResult>>,
+    /// Channel receiver for the study plans' next section being worked out
+    next_up_rx: Option<tokio::sync::mpsc::Receiver<Option<String>>>,
+    /// Status bar plan segment for a (book ID, section path), kept until
+    /// progress or the plans change
+    status_goal: Option<((String, String), Option<String>)>,
+    /// Channel receiver for learning objectives being written
+    objectives_rx: Option<tokio::sync::mpsc::Receiver<ObjectivesResult>>,
+    /// Channel receiver for the next section's quiz and summary being prefetched
+    prefetch_rx: Option<tokio::sync::mpsc::Receiver<Prefetched>>,
+    /// Book ID and path of the section last prefetched, so it's fetched once
+    prefetched_for: Option<(String, String)>,
+    /// Quiz questions fetched ahead of time: (book ID, section path, questions)
+    prefetched_quiz: Option<(String, String, Vec<crate::app::state::QuizQuestion>)>,
+    /// Channel receiver for a book being parsed in the background
+    book_rx: Option<tokio::sync::mpsc::Receiver<BookLoadResult>>,
+    /// Channel receiver for the output of a code block being run
+    code_run_rx: Option<tokio::sync::mpsc::Receiver<CodeRunResult>>,
+    /// Search index of the open book (loaded on first search)
+    search_index: Option<crate::book::SearchIndex>,
+
+    /// Recorded API token usage
+    usage: crate::claude::UsageLog,
+    /// Models last listed by the API, besides the built-in ones
+    models: crate::claude::ModelList,
+    /// Words written in notes, for writing stats
+    writing: crate::notes::WritingLog,
+
+    /// Channel for token usage reported by background (non-streaming) requests
+    usage_tx: tokio::sync::mpsc::UnboundedSender<UsageReport>,
+    usage_rx: tokio::sync::mpsc::UnboundedReceiver<UsageReport>,
+    /// Channel for Claude requests being retried after a rate limit or server error
+    retry_tx: tokio::sync::mpsc::UnboundedSender<crate::claude::RetryStatus>,
+    retry_rx: tokio::sync::mpsc::UnboundedReceiver<crate::claude::RetryStatus>,
 
     /// Mouse selection state: (is_dragging, start_block, start_char)
     mouse_selection: Option<(usize, usize)>,
+
+    /// Held-key tracking for navigation acceleration
+    key_repeat: KeyRepeat,
+
+    /// Step multiplier for the navigation action being handled
+    nav_multiplier: u32,
+
+    /// Text-to-speech playback of the current section (if reading aloud)
+    speaker: Option<crate::speech::Speaker>,
+
+    /// Books offered when :open/:remove matched more than one
+    book_choice: Option<BookChoice>,
+
+    /// Note edits previewed by :notes s/old/new/, applied on y
+    pending_note_edits: Option<Vec<crate::notes::replace::NoteEdit>>,
+
+    /// Whether the terminal shows OSC 8 hyperlinks, making links clickable
+    hyperlinks: bool,
+
+    /// Links of the selected note offered by `o`, with the note's book ID
+    note_link_choice: Option<(String, Vec<crate::notes::links::NoteLink>)>,
+
+    /// Mark command waiting for its letter (after `M` or `'`)
+    pending_mark: Option<MarkKey>,
+
+    /// Jump history per book ID
+    jumps: std::collections::HashMap<String, state::JumpList>,
+
+    /// When progress and session state are next written
+    autosave: state::Autosave,
+
+    /// The open markdown book's files, reloaded when they change
+    source_watch: Option<state::SourceWatch>,
+
+    /// The open book's overrides of global settings
+    book_settings: storage::BookSettings,
+
+    /// Whether the curriculum and notes panels were shown before focus mode hid them
+    panels_before_focus: Option<(bool, bool)>,
+
+    /// The open book's quiz questions written or edited by the reader
+    question_bank: QuestionBank,
+
+    /// Why the open book's question bank couldn't be loaded; it isn't saved
+    /// over while set
+    question_bank_error: Option<String>,
+
+    /// The open book's learning objectives, by section
+    objectives: Objectives,
+}
+
+/// Candidate books awaiting a numbered choice from the user
+struct BookChoice {
+    /// Whether the chosen book is opened or removed
+    remove: bool,
+    /// IDs of the candidate books, in the order they were listed
+    ids: Vec<String>,
+}
+
+/// What a mark letter typed next does
+#[derive(Debug, Clone, Copy)]
+enum MarkKey {
+    /// Set a mark at the current scroll position (`M{a-z}`)
+    Set,
+    /// Jump to a mark in the current section (`'{a-z}`)
+    Jump,
+}
+
+/// What woke the event loop
+enum Wake {
+    /// Terminal input; None once the terminal closes
+    Input(Option<io::Result<Event>>),
+    /// A Claude streaming event; None once the stream closes
+    Claude(Option<crate::claude::StreamEvent>),
+    /// A quiz generation result; None once generation closes
+    Quiz(Option<QuizGenerationResult>),
+    /// Periodic work (animations, autosave, file watching) is due
+    Tick,
+}
+
+/// The next message on a channel, waiting forever if there is none
+async fn recv_if_open<T>(rx: &mut Option<tokio::sync::mpsc::Receiver<T>>) -> Option<T> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Number of questions in a section quiz
+const QUIZ_QUESTIONS: usize = 5;
+
+/// Sections whose content is kept in memory once read, most recent first
+const LOADED_SECTIONS: usize = 32;
+
+/// How long the viewport must stay put before the last-read marker moves there
+const LAST_READ_DWELL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Narrowest text width accepted by `:set textwidth`
+const MIN_TEXT_WIDTH: usize = 20;
+
+/// Widest the text runs in focus mode, in columns
+const FOCUS_TEXT_WIDTH: usize = 72;
+
+/// Matching blocks listed by a book search
+const MAX_SEARCH_RESULTS: usize = 50;
+
+/// Matching sections listed per book by a library search
+const MAX_SEARCH_SECTIONS_PER_BOOK: usize = 5;
+
+/// Widest diagram to ask Claude for, so it fits the response panel
+const DIAGRAM_WIDTH: usize = 72;
+
+/// Columns a table scrolls sideways for each h/l
+const TABLE_SCROLL_STEP: usize = 8;
+
+/// Longest the event loop sleeps while something animates or runs in the background
+const ANIMATION_TICK: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Longest the event loop sleeps when idle
+const IDLE_TICK: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Token usage reported by a background request: (model, input tokens, output tokens)
+type UsageReport = (crate::claude::ClaudeModel, u32, u32);
+
+/// A study buddy answer: (index of the model asked, answer text or error)
+type BuddyResult = (usize, Result<String, String>);
+
+/// Models listed by the API, or the error fetching them
+type ModelListResult = Result<Vec<crate::claude::registry::ListedModel>, String>;
+
+/// Learning objectives written in the background: (book ID, section path,
+/// section title, answer text or error)
+type ObjectivesResult = (String, String, String, Result<String, String>);
+
+/// Something fetched in the background for the section after the one being read
+enum Prefetched {
+    /// Quiz questions: (book ID, section path, questions or error)
+    Quiz(String, String, Result<Vec<crate::app::state::QuizQuestion>, String>),
+    /// A chapter summary: (book ID, chapter path, chapter title, summary or error)
+    Summary(String, String, String, Result<String, String>),
+}
+
+/// Why a book is being loaded in the background
+enum BookLoadKind {
+    /// Restore the last session's book at startup
+    Startup,
+    /// Opened with `:open` (restores the saved position)
+    Open,
+    /// Opened to show a note, which is selected once loaded
+    GotoNote(String),
+    /// Newly added with `:add` (starts at the beginning)
+    Add,
+    /// Reparsed with `:refresh` (keeps the current position)
+    Refresh,
+    /// Reparsed because its markdown files changed (follows the current
+    /// section to wherever it now is)
+    SourceChanged,
 }
 
+/// A book parsed by a background task
+type BookLoadResult = (BookLoadKind, Result<crate::book::Book>);
+
+/// A code block's language and what running it printed
+type CodeRunResult = (String, Result<crate::runner::RunOutput>);
+
 /// Result from quiz generation task
 enum QuizGenerationResult {
-    Success(Vec<crate::app::state::QuizQuestion>),
+    /// A question streamed in while the rest of the quiz is generated
+    Question(crate::app::state::QuizQuestion),
+    /// All streamed questions have arrived
+    Generated,
+    /// Follow-up questions replacing the ones missed on the previous attempt
+    Replacements(Vec<crate::app::state::QuizQuestion>),
+    /// Claude's grades for short answers: (question index, correct)
+    Graded(Vec<(usize, bool)>),
+    /// Claude's explanations of the answers: (question index, explanation)
+    Explained(Vec<(usize, String)>),
     Error(String),
 }
 
+/// Loaded state, or empty state if it couldn't be read (nor its backup)
+fn or_default<T: Default>(name: &str, loaded: Result<T>) -> T {
+    loaded.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load {}, starting empty: {:#}", name, e);
+        T::default()
+    })
+}
+
+/// Names of the reading modes, for messages
+fn reading_mode_names() -> String {
+    ReadingMode::ALL.iter().map(|mode| mode.name()).collect::<Vec<_>>().join(", ")
+}
+
 impl App {
     /// Create a new application instance
     pub fn new(config: Config) -> Result<Self> {
         let terminal = Self::setup_terminal()?;
-        let progress = Progress::load().unwrap_or_default();
-        let session = Session::load().unwrap_or_default();
-        let notes_store = NotesStore::load().unwrap_or_default();
+        let progress = or_default("progress", Progress::load());
+        let session = or_default("session", Session::load());
+        let notes_store = or_default("notes", NotesStore::load());
+        let usage = or_default("usage log", crate::claude::UsageLog::load());
+        let models = or_default("model list", crate::claude::ModelList::load());
+        let writing = or_default("writing stats", crate::notes::WritingLog::load());
+        let (usage_tx, usage_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (retry_tx, retry_rx) = tokio::sync::mpsc::unbounded_channel();
 
         // Create image cache after terminal setup for proper protocol detection
-        let image_cache = ImageCache::new();
+        let mut image_cache = ImageCache::new();
+        image_cache.set_fallback(config.image_fallback);
 
         let mut app = Self {
             config,
@@ -90,9 +335,48 @@ impl App {
             claude_rx: None,
             claude_cancel: None,
             quiz_rx: None,
+            buddy_rx: None,
+            objectives_rx: None,
+            prefetch_rx: None,
+            prefetched_for: None,
+            prefetched_quiz: None,
+            book_rx: None,
+            code_run_rx: None,
+            search_index: None,
+            usage,
+            models,
+            models_rx: None,
+            next_up_rx: None,
+            status_goal: None,
+            writing,
+            usage_tx,
+            usage_rx,
+            retry_tx,
+            retry_rx,
             mouse_selection: None,
+            key_repeat: KeyRepeat::default(),
+            nav_multiplier: 1,
+            speaker: None,
+            book_choice: None,
+            pending_note_edits: None,
+            hyperlinks: crate::ui::hyperlink::supported(),
+            note_link_choice: None,
+            pending_mark: None,
+            jumps: std::collections::HashMap::new(),
+            autosave: state::Autosave::default(),
+            source_watch: None,
+            book_settings: storage::BookSettings::default(),
+            panels_before_focus: None,
+            question_bank: QuestionBank::default(),
+            question_bank_error: None,
+            objectives: Objectives::default(),
         };
 
+        app.state.content.max_width = app.config.text_width;
+        app.state.content.typography = app.config.typography;
+        app.state.datetime = DateTimeFormat::from_config(&app.config.datetime);
+        app.refresh_next_up();
+
         // Apply saved panel widths from session
         app.state.panel_visibility.curriculum_width_percent = app.session.curriculum_width_percent;
         app.state.panel_visibility.notes_width_percent = app.session.notes_width_percent;
@@ -102,7 +386,7 @@ impl App {
 
         // Restore Claude model preference from session
         if let Some(model_str) = &app.session.claude_model {
-            if let Some(model) = crate::claude::ClaudeModel::parse(model_str) {
+            if let Some(model) = app.models.resolve(model_str) {
                 app.state.claude.model = model;
             }
         }
@@ -110,6 +394,9 @@ impl App {
         // Auto-load first book from library if available
         app.auto_load_book();
 
+        app.update_usage_status();
+        app.startup_health_check();
+
         Ok(app)
     }
 
@@ -124,26 +411,240 @@ impl App {
             library.entries.first()
         };
 
-        let Some(entry) = entry else { return };
-        let Ok(book) = storage::load_book(entry) else { return };
+        let Some(entry) = entry.cloned() else { return };
+        let title = entry.metadata.title.clone();
+        self.load_book_in_background(title, BookLoadKind::Startup, move || {
+            storage::load_book(&entry)
+        });
+    }
 
-        // Set image cache base path from book source
-        self.set_image_base_path(&book);
+    /// Parse a book on a blocking thread so the UI stays responsive
+    ///
+    /// The result is picked up by `process_book_loads`. Starting another load
+    /// abandons any load still in progress.
+    fn load_book_in_background(
+        &mut self,
+        title: String,
+        kind: BookLoadKind,
+        load: impl FnOnce() -> Result<crate::book::Book> + Send + 'static,
+    ) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.book_rx = Some(rx);
+        self.state.book_loading = Some(state::BookLoading::new(title));
+
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.blocking_send((kind, load()));
+        });
+    }
+
+    /// Show a book once its background load finishes (non-blocking)
+    fn process_book_loads(&mut self) {
+        use tokio::sync::mpsc::error::TryRecvError;
+
+        let Some(rx) = &mut self.book_rx else { return };
+        let (kind, result) = match rx.try_recv() {
+            Ok(loaded) => loaded,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.book_rx = None;
+                self.state.book_loading = None;
+                self.state.command_line.set_error("Book loading stopped unexpectedly");
+                return;
+            }
+        };
+        self.book_rx = None;
+        self.state.book_loading = None;
 
+        let book = match result {
+            Ok(book) => book,
+            Err(e) => {
+                match kind {
+                    BookLoadKind::Startup => tracing::warn!("Failed to load last book: {}", e),
+                    BookLoadKind::Add => {
+                        self.state.command_line.set_error(format!("Failed to add: {}", e));
+                    }
+                    BookLoadKind::Refresh => {
+                        self.state.command_line.set_error(format!("Failed to refresh: {}", e));
+                    }
+                    BookLoadKind::SourceChanged => {
+                        self.state.command_line.set_error(format!("Failed to reload: {}", e));
+                    }
+                    BookLoadKind::Open | BookLoadKind::GotoNote(_) => {
+                        self.state.command_line.set_error(format!("Failed to load: {}", e));
+                    }
+                }
+                return;
+            }
+        };
+
+        self.set_image_base_path(&book);
+        let title = book.metadata.title.clone();
         let book_id = book.metadata.id.clone();
-        self.state.book = Some(book);
+        self.source_watch = match &book.metadata.source {
+            crate::book::BookSource::Markdown(path) => {
+                Some(state::SourceWatch::new(&book_id, path, std::time::Instant::now()))
+            }
+            _ => None,
+        };
+        let previous = self.state.book.replace(book);
+        self.state.content.layout_cache = None;
+        self.load_book_settings(&book_id);
+        (self.question_bank, self.question_bank_error) = match QuestionBank::load(&book_id) {
+            Ok(bank) => (bank, None),
+            Err(e) => {
+                tracing::warn!("Failed to load question bank: {:#}", e);
+                (QuestionBank::default(), Some(format!("{:#}", e)))
+            }
+        };
+        let objectives_error = match Objectives::load(&book_id) {
+            Ok(objectives) => {
+                self.objectives = objectives;
+                None
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load objectives: {:#}", e);
+                self.objectives = Objectives::default();
+                Some(e)
+            }
+        };
+        // A split of the book's own sections only makes sense in that book
+        let same_book = previous.as_ref().is_some_and(|p| p.metadata.id == book_id);
+        if let Some(split) = &mut self.state.split {
+            split.layout_cache = None;
+            if split.book.is_none() && !same_book {
+                self.state.split = None;
+            }
+        }
+        self.state.content.glossary = crate::learning::glossary::Glossary::load(&book_id)
+            .ok()
+            .and_then(|glossary| glossary.matcher());
+        self.search_index = None;
+        let reanchored = self.reanchor_notes();
+
+        match kind {
+            BookLoadKind::Startup => self.apply_book_session(&book_id),
+            BookLoadKind::Open => {
+                self.apply_book_session(&book_id);
+                self.state.command_line.set_message(format!("Opened: {}", title));
+            }
+            BookLoadKind::GotoNote(note_id) => {
+                self.apply_book_session(&book_id);
+                if let Err(e) = self.goto_note(&note_id) {
+                    self.state.command_line.set_error(format!("Failed to open note: {}", e));
+                }
+            }
+            BookLoadKind::Add => {
+                self.state.current_chapter = 0;
+                self.state.current_section = 0;
+                self.state.curriculum.selected_index = 0;
+                self.state.curriculum.expanded_chapters.clear();
+                self.state.content.scroll_offset = 0;
+                self.state.command_line.set_message(format!("Added: {}", title));
+            }
+            BookLoadKind::Refresh => {
+                // Position is kept as is (clamped to valid range when drawn)
+                let lost = match reanchored.lost {
+                    0 => String::new(),
+                    1 => ", 1 note's text not found".to_string(),
+                    n => format!(", {} notes' text not found", n),
+                };
+                self.state
+                    .command_line
+                    .set_message(format!("Refreshed: {} (progress preserved{})", title, lost));
+            }
+            BookLoadKind::SourceChanged => self.follow_reloaded_section(previous.as_ref()),
+        }
+        if let Some(e) = &self.question_bank_error {
+            self.state.command_line.set_error(format!("Failed to load question bank: {}", e));
+        }
+        if let Some(e) = objectives_error {
+            self.state.command_line.set_error(format!("Failed to load objectives: {:#}", e));
+        }
+    }
+
+    /// Reload the open markdown book in the background if its files changed
+    fn reload_changed_source(&mut self) {
+        let Some(watch) = &mut self.source_watch else { return };
+        if self.book_rx.is_some() || !watch.changed(std::time::Instant::now()) {
+            return;
+        }
+        let book_id = watch.book_id.clone();
+        let title = self.state.book.as_ref().map_or_else(String::new, |b| b.metadata.title.clone());
+        self.load_book_in_background(title, BookLoadKind::SourceChanged, move || {
+            storage::reparse_book(&book_id)
+        });
+        // The old text stays up until the new one is ready, without the loading overlay
+        self.state.book_loading = None;
+    }
+
+    /// After reloading an edited book, move to where the section being read
+    /// now is, keeping the scroll and cursor position within it
+    fn follow_reloaded_section(&mut self, previous: Option<&Book>) {
+        let Some(book) = &self.state.book else { return };
+        let Some(previous) = previous else { return };
+        let current = previous
+            .get_section(self.state.current_chapter, self.state.current_section)
+            .map(|s| s.path.clone());
+        let position = current.and_then(|path| {
+            book.chapters.iter().enumerate().find_map(|(chapter_idx, chapter)| {
+                let section_idx = chapter.sections.iter().position(|s| s.path == path)?;
+                Some((chapter_idx, section_idx))
+            })
+        });
+        if let Some((chapter, section)) = position {
+            self.state.current_chapter = chapter;
+            self.state.current_section = section;
+        }
+
+        let changes = crate::book::compare::compare_editions(previous, book);
+        let changed = changes
+            .iter()
+            .filter(|c| {
+                c.is_changed() || !matches!(c, crate::book::compare::SectionChange::Matched { .. })
+            })
+            .count();
+        let message = match changed {
+            0 => format!("Reloaded: {}", book.metadata.title),
+            1 => format!("Reloaded: {} (1 section changed)", book.metadata.title),
+            n => format!("Reloaded: {} ({} sections changed)", book.metadata.title, n),
+        };
+        self.state.command_line.set_message(message);
+    }
+
+    /// Move the open book's text-selection notes to where their text now is
+    fn reanchor_notes(&mut self) -> ReanchorReport {
+        let Some(book) = &self.state.book else {
+            return ReanchorReport::default();
+        };
+        let report = self.notes_store.reanchor_book(book);
+        if report.lost > 0 {
+            tracing::warn!("{} notes in {} lost their anchors", report.lost, book.metadata.id);
+        }
+        if report.changed()
+            && let Err(e) = self.notes_store.save()
+        {
+            tracing::warn!("Failed to save re-anchored notes: {}", e);
+        }
+        report
+    }
 
-        // Apply saved session state for this book
-        if let Some(book_session) = self.session.book(&book_id) {
+    /// Restore the saved reading position for a book, or start at the beginning
+    fn apply_book_session(&mut self, book_id: &str) {
+        if let Some(book_session) = self.session.book(book_id) {
             self.state.current_chapter = book_session.current_chapter;
             self.state.current_section = book_session.current_section;
             self.state.content.scroll_offset = book_session.content_scroll_offset;
             self.state.curriculum.selected_index = book_session.selected_index;
             self.state.curriculum.scroll_offset = book_session.curriculum_scroll_offset;
             self.state.curriculum.expanded_chapters = book_session.expanded_chapters.clone();
+            self.state.content.last_read = book_session.last_read.clone();
         } else {
             self.state.current_chapter = 0;
             self.state.current_section = 0;
+            self.state.curriculum.selected_index = 0;
+            self.state.curriculum.expanded_chapters.clear();
+            self.state.content.scroll_offset = 0;
+            self.state.content.last_read = None;
         }
     }
 
@@ -154,8 +655,14 @@ impl App {
             BookSource::Markdown(path) => {
                 self.image_cache.set_base_path(path.clone());
             }
-            BookSource::Epub(_) => {
-                // EPUB images are embedded, not file-based
+            BookSource::AsciiDoc(path) => {
+                // Image paths are relative to the document
+                if let Some(dir) = path.parent() {
+                    self.image_cache.set_base_path(dir.to_path_buf());
+                }
+            }
+            BookSource::Epub(_) | BookSource::Docx(_) => {
+                // EPUB and DOCX images are embedded, not file-based
                 // Clear the base path so images aren't found
                 self.image_cache.clear();
             }
@@ -207,6 +714,213 @@ impl App {
         if let Err(e) = self.session.save() {
             tracing::warn!("Failed to save session: {}", e);
         }
+        let section = self.current_section_key();
+        let section = section.as_ref().map(|(book, path)| (book.as_str(), path.as_str()));
+        self.autosave.session_saved(section, std::time::Instant::now());
+    }
+
+    /// Book ID and path of the section being read
+    fn current_section_key(&self) -> Option<(String, String)> {
+        let book = self.state.book.as_ref()?;
+        let section = book.get_section(self.state.current_chapter, self.state.current_section)?;
+        Some((book.metadata.id.clone(), section.path.clone()))
+    }
+
+    /// Remember the scroll offset of the section being left, for coming back
+    fn remember_scroll(&mut self) {
+        let Some((book_id, path)) = self.current_section_key() else { return };
+        let offset = self.state.content.scroll_offset;
+        let scrolls = &mut self.session.book_mut(&book_id).section_scroll;
+        if offset == 0 {
+            scrolls.remove(&path);
+        } else {
+            scrolls.insert(path, offset);
+        }
+    }
+
+    /// Scroll offset the current section was last left at
+    fn remembered_scroll(&self) -> usize {
+        let Some((book_id, path)) = self.current_section_key() else { return 0 };
+        self.session.book(&book_id).and_then(|b| b.section_scroll.get(&path)).copied().unwrap_or(0)
+    }
+
+    /// Queue progress to be written once changes settle
+    fn save_progress(&mut self) {
+        self.status_goal = None;
+        self.autosave.progress_changed(std::time::Instant::now());
+    }
+
+    /// Write any queued progress now
+    fn flush_progress(&mut self) {
+        if !self.autosave.progress_pending() {
+            return;
+        }
+        if let Err(e) = self.progress.save() {
+            tracing::warn!("Failed to save progress: {}", e);
+        }
+        self.autosave.progress_saved();
+    }
+
+    /// Write progress once changes settle, and the session on changing section
+    /// and every `autosave_minutes`, so a crash loses little
+    fn autosave(&mut self) {
+        let now = std::time::Instant::now();
+        if self.autosave.progress_due(now) {
+            self.flush_progress();
+        }
+
+        let interval = self.config.autosave_interval();
+        let section = self.current_section_key();
+        let section = section.as_ref().map(|(book, path)| (book.as_str(), path.as_str()));
+        if self.autosave.session_due(section, interval, now) {
+            self.save_session();
+        }
+    }
+
+    /// Draw the UI, updating layout metrics in state
+    fn draw(&mut self) -> Result<()> {
+        self.state.command_line.hints = match self.hint_context() {
+            Some(context) => input::key_hints(context),
+            None => Vec::new(),
+        };
+        self.state.status_bar = self.status_segments();
+
+        // Borrow state mutably for scroll clamping
+        let state = &mut self.state;
+        let config = &self.config;
+        let progress = &self.progress;
+        let notes_store = &self.notes_store;
+        let image_cache = &mut self.image_cache;
+        let section = state.book.as_ref().and_then(|book| {
+            let section = book.get_section(state.current_chapter, state.current_section)?;
+            Some((book.metadata.id.as_str(), section.path.as_str()))
+        });
+        let section_progress =
+            section.and_then(|(book_id, path)| progress.books.get(book_id)?.sections.get(path));
+
+        // The section footer shows the quiz attempts at the current section,
+        // and its header the section's learning objectives
+        let quiz_history = section_progress.map(|s| s.quiz_history.clone()).unwrap_or_default();
+        let objectives = section
+            .and_then(|(_, path)| self.objectives.get(path))
+            .map(|section| {
+                let met = |o| section_progress.is_some_and(|s| s.objectives_met.contains(o));
+                section.objectives.iter().map(|o| (o.clone(), met(o))).collect()
+            })
+            .unwrap_or_default();
+        state.content.quiz_history = quiz_history;
+        state.content.objectives = objectives;
+        self.terminal.draw(|frame| {
+            ui::draw(frame, state, config, progress, notes_store, image_cache);
+        })?;
+        if self.hyperlinks {
+            let links = &self.state.content.link_hits;
+            ui::hyperlink::write(self.terminal.backend_mut(), links)?;
+        }
+        Ok(())
+    }
+
+    /// The status bar's segments, as turned on in `Config.statusbar`; empty
+    /// off the main screen or with the bar turned off
+    fn status_segments(&mut self) -> Vec<String> {
+        let config = self.config.statusbar;
+        let Some(book) = self.state.book.as_ref().filter(|_| config.enabled) else {
+            return Vec::new();
+        };
+        if !matches!(self.state.screen, Screen::Main) {
+            return Vec::new();
+        }
+        let progress = self.progress.books.get(&book.metadata.id);
+        let section = book.get_section(self.state.current_chapter, self.state.current_section);
+
+        let mut segments = Vec::new();
+        if config.section {
+            let chapter = book.chapters.get(self.state.current_chapter);
+            if let (Some(chapter), Some(section)) = (chapter, section) {
+                segments.push(format!("{} › {}", chapter.title, section.title));
+            }
+        }
+        if config.progress {
+            let completed =
+                progress.map_or(0, |p| p.sections.values().filter(|s| s.completed).count());
+            let percent = (completed * 100).checked_div(book.section_count()).unwrap_or(0);
+            segments.push(format!("{}% read", percent.min(100)));
+        }
+        if config.goal {
+            // Working out plan progress walks the book's sections, so not every frame
+            let key =
+                (book.metadata.id.clone(), section.map(|s| s.path.clone()).unwrap_or_default());
+            let goal = match &self.status_goal {
+                Some((cached, goal)) if *cached == key => goal.clone(),
+                _ => {
+                    let goal = goal_segment(&self.config.plans, book, section, progress);
+                    self.status_goal = Some((key, goal.clone()));
+                    goal
+                }
+            };
+            segments.extend(goal);
+        }
+        if config.model {
+            segments.push(self.models.display_name(&self.claude_model()).to_string());
+        }
+        if config.streaming && self.state.claude.streaming {
+            segments.push("Claude is writing…".to_string());
+        }
+        if config.time {
+            let now = crate::config::datetime::local_time(crate::config::datetime::now());
+            segments.push(self.state.datetime.time(now));
+        }
+        segments
+    }
+
+    /// What the reader is doing, for the command line's key hints; None off
+    /// the main screen or under a report, which lists its own keys
+    fn hint_context(&self) -> Option<HintContext> {
+        if !matches!(self.state.screen, Screen::Main) || self.state.report.is_some() {
+            return None;
+        }
+        Some(if self.state.claude.is_response_visible() {
+            HintContext::ClaudePanel
+        } else if self.state.quiz.active {
+            HintContext::Quiz
+        } else {
+            match self.state.focused_panel {
+                Panel::Curriculum => HintContext::Curriculum,
+                Panel::Notes => HintContext::Notes,
+                Panel::Content if self.state.visual_mode.active => HintContext::Visual,
+                Panel::Content if self.state.content.cursor_mode => HintContext::Cursor,
+                Panel::Content => HintContext::Content,
+            }
+        })
+    }
+
+    /// Lay the screen out again for a new terminal size
+    ///
+    /// Drawing reflows the section around the line at the top of the viewport;
+    /// line-indexed state is then carried over to the new layout and the
+    /// cursor kept on screen.
+    fn handle_resize(&mut self) -> Result<()> {
+        let old_total = self.state.content.total_lines;
+        self.draw()?;
+
+        let section_path = self
+            .state
+            .book
+            .as_ref()
+            .and_then(|b| b.get_section(self.state.current_chapter, self.state.current_section))
+            .map(|s| s.path.clone())
+            .unwrap_or_default();
+        self.state.content.rescale_lines(&section_path, old_total);
+        self.state.content.clamp_scroll();
+        if self.state.content.cursor_mode {
+            self.ensure_cursor_visible();
+        }
+        // The offset moved with the layout, not by scrolling away
+        let offset = self.state.content.scroll_offset;
+        if let Some((_, at, _)) = &mut self.state.content.dwell {
+            *at = offset;
+        }
+        Ok(())
     }
 
     /// Run the application main loop
@@ -219,120 +933,102 @@ impl App {
             original_hook(panic_info);
         }));
 
+        let mut events = EventStream::new();
+        let mut redraw = true;
+        let mut last_draw = std::time::Instant::now();
         loop {
-            // Draw UI - need to borrow state mutably for scroll clamping
-            let state = &mut self.state;
-            let config = &self.config;
-            let progress = &self.progress;
-            let notes_store = &self.notes_store;
-            let image_cache = &mut self.image_cache;
-            self.terminal.draw(|frame| {
-                ui::draw(frame, state, config, progress, notes_store, image_cache);
-            })?;
+            // While Claude streams, redraw at the stream cadence unless input arrived
+            if redraw
+                || !self.state.claude.streaming
+                || last_draw.elapsed() >= self.config.claude.stream_interval()
+            {
+                self.draw()?;
+                redraw = false;
+                last_draw = std::time::Instant::now();
+            }
+
+            // Track reading progress now that content metrics are up to date
+            self.update_read_through();
+            self.update_last_read();
+
+            // Write progress and session state that are due
+            self.autosave();
+
+            // Reload a markdown book edited on disk
+            self.reload_changed_source();
 
             // Process Claude streaming events (non-blocking)
-            self.process_claude_events();
+            redraw |= self.process_claude_events();
 
             // Process quiz generation results (non-blocking)
             self.process_quiz_events();
 
-            // Handle all pending events before next redraw (makes scrolling feel faster)
-            let mut should_quit = false;
-            while event::poll(std::time::Duration::from_millis(0))? {
-                let event = event::read()?;
+            // Show study buddy answers as they arrive (non-blocking)
+            self.process_buddy_answers();
 
-                // Handle mouse events for text selection
-                if let Event::Mouse(mouse_event) = event {
-                    self.handle_mouse_event(mouse_event);
-                    continue;
-                }
+            // Show learning objectives once written (non-blocking)
+            self.process_objectives();
 
-                if let Event::Key(key_event) = event {
-                    if key_event.kind == KeyEventKind::Press {
-                        // Ctrl+C to cancel Claude streaming
-                        if key_event.code == KeyCode::Char('c')
-                            && key_event.modifiers.contains(KeyModifiers::CONTROL)
-                            && self.state.claude.streaming
-                        {
-                            self.cancel_claude_stream();
-                            continue;
-                        }
+            // Show the models available once listed (non-blocking)
+            self.process_model_list();
 
-                        // Route to Claude panel if it's visible
-                        if self.state.claude.is_response_visible() {
-                            self.handle_claude_panel_input(key_event.code);
-                        // Route to notes input if editing a note
-                        } else if self.state.notes.is_editing() {
-                            self.handle_notes_input(key_event.code);
-                        // Route to command line if in input mode
-                        } else if self.state.command_line.is_input_mode() {
-                            match self.handle_command_line_input(key_event.code).await {
-                                Ok(true) => {
-                                    should_quit = true;
-                                    break;
-                                }
-                                Ok(false) => {}
-                                Err(e) => {
-                                    self.state.command_line.set_error(format!("Error: {}", e));
-                                }
-                            }
-                        // Special handling for Ctrl+J which terminals often send as different codes
-                        // Works in both cursor mode and visual mode (for extending selection)
-                        // NOTE: Terminals may send Ctrl+J as: '\n', '\r', Enter, or 'j' with CONTROL
-                        } else if self.state.content.cursor_mode
-                            && (key_event.code == KeyCode::Char('\n')
-                                || key_event.code == KeyCode::Char('\r')
-                                || key_event.code == KeyCode::Enter
-                                || (key_event.code == KeyCode::Char('j')
-                                    && key_event.modifiers.contains(KeyModifiers::CONTROL)))
-                        {
-                            // Direct line-down for cursor/visual mode
-                            if let Some(text) = self.get_block_text(self.state.content.cursor_block)
-                            {
-                                self.state.content.cursor_line_down(&text);
-                            }
-                            self.ensure_cursor_visible();
-                            self.update_cursor_message();
-                        } else if let Some(action) =
-                            key_with_modifier_to_action(key_event.code, key_event.modifiers)
-                        {
-                            match self.handle_action(action).await {
-                                Ok(true) => {
-                                    should_quit = true;
-                                    break;
-                                }
-                                Ok(false) => {}
-                                Err(e) => {
-                                    tracing::error!("Error handling action: {}", e);
-                                }
-                            }
-                        } else {
-                            // Handle : and / to enter command modes, 'c' for Claude panel
-                            match key_event.code {
-                                KeyCode::Char(':') => {
-                                    self.state.command_line.enter_command_mode();
-                                }
-                                KeyCode::Char('/') => {
-                                    self.state.command_line.enter_search_mode();
-                                }
-                                KeyCode::Char('c') => {
-                                    // Toggle Claude response panel if there's a response
-                                    if !self.state.claude.response.is_empty() {
-                                        self.state.claude.toggle_response();
-                                    }
-                                }
-                                _ => {}
-                            }
+            // Show the study plans' next section once worked out (non-blocking)
+            self.process_next_up();
+
+            // Fetch the next section's quiz and summary near the end of this one
+            self.prefetch_next_section();
+            self.process_prefetched();
+
+            // Show a book once it has loaded in the background (non-blocking)
+            self.process_book_loads();
+
+            // Record token usage from background requests (non-blocking)
+            self.process_usage_reports();
+
+            // Show Claude requests waiting to retry (non-blocking)
+            self.process_retry_reports();
+
+            // Advance text-to-speech playback (non-blocking)
+            self.process_speech();
+
+            // Show code run output once it finishes (non-blocking)
+            self.process_code_runs();
+
+            // Sleep until there is input, a Claude or quiz event, or work is due
+            match self.wait_for_wake(&mut events).await {
+                Wake::Input(Some(event)) => {
+                    redraw = true;
+                    if self.handle_event(event?).await? {
+                        break;
+                    }
+                    // Handle all pending events before next redraw (makes scrolling feel faster)
+                    let mut should_quit = false;
+                    while let Some(Some(event)) = events.next().now_or_never() {
+                        if self.handle_event(event?).await? {
+                            should_quit = true;
+                            break;
                         }
                     }
+                    if should_quit {
+                        break;
+                    }
                 }
+                // The terminal closed
+                Wake::Input(None) => break,
+                Wake::Claude(Some(event)) => self.handle_claude_event(event),
+                Wake::Claude(None) => {
+                    self.claude_rx = None;
+                    // Closed without MessageStop, so nothing more will arrive
+                    // and the stream won't finish on its own
+                    let claude = &mut self.state.claude;
+                    if claude.streaming && !claude.stream_throttle.finished {
+                        claude.clear_streaming();
+                    }
+                }
+                Wake::Quiz(Some(result)) => self.handle_quiz_result(result),
+                Wake::Quiz(None) => self.quiz_rx = None,
+                Wake::Tick => {}
             }
-            if should_quit {
-                break;
-            }
-
-            // Small sleep to prevent busy-waiting when idle (~120fps)
-            std::thread::sleep(std::time::Duration::from_millis(4));
 
             // Update animations
             if matches!(self.state.screen, Screen::Landing) {
@@ -340,13 +1036,209 @@ impl App {
             }
         }
 
-        // Save session state before exiting
+        // Save progress and session state before exiting
+        self.flush_progress();
         self.save_session();
 
         self.restore_terminal()?;
         Ok(())
     }
 
+    /// Wait for whatever should wake the event loop next
+    async fn wait_for_wake(&mut self, events: &mut EventStream) -> Wake {
+        let tick = self.tick_interval();
+        let (claude_rx, quiz_rx) = (&mut self.claude_rx, &mut self.quiz_rx);
+        tokio::select! {
+            event = events.next() => Wake::Input(event),
+            event = recv_if_open(claude_rx) => Wake::Claude(event),
+            result = recv_if_open(quiz_rx) => Wake::Quiz(result),
+            () = tokio::time::sleep(tick) => Wake::Tick,
+        }
+    }
+
+    /// How long the event loop may sleep without input: the stream cadence
+    /// while Claude streams, a frame while something animates or runs in the
+    /// background, otherwise long enough for autosave and file watching
+    fn tick_interval(&self) -> std::time::Duration {
+        let stream_interval = self.config.claude.stream_interval();
+        if self.state.claude.streaming && !stream_interval.is_zero() {
+            return stream_interval.min(ANIMATION_TICK);
+        }
+        let animating = (matches!(self.state.screen, Screen::Landing)
+            && !self.state.landing_animation.complete)
+            || self.state.claude.streaming
+            || self.state.book_loading.is_some();
+        let background = self.book_rx.is_some()
+            || self.buddy_rx.is_some()
+            || self.objectives_rx.is_some()
+            || self.models_rx.is_some()
+            || self.next_up_rx.is_some()
+            || self.prefetch_rx.is_some()
+            || self.code_run_rx.is_some()
+            || self.source_watch.as_ref().is_some_and(state::SourceWatch::walking)
+            || self.speaker.is_some()
+            || self.image_cache.is_decoding();
+        if animating || background { ANIMATION_TICK } else { IDLE_TICK }
+    }
+
+    /// Handle one terminal event, returns true if should exit
+    async fn handle_event(&mut self, event: Event) -> Result<bool> {
+        if let Event::Resize(..) = event {
+            self.handle_resize()?;
+            return Ok(false);
+        }
+
+        // Handle mouse events for text selection
+        if let Event::Mouse(mouse_event) = event {
+            self.handle_mouse_event(mouse_event);
+            return Ok(false);
+        }
+
+        if let Event::Key(key_event) = event {
+            if key_event.kind == KeyEventKind::Press {
+                let action = key_with_modifier_to_action(key_event.code, key_event.modifiers);
+                // Ctrl+C to cancel Claude streaming
+                if action == Some(Action::Cancel) && self.state.claude.streaming {
+                    self.cancel_claude_stream();
+                    return Ok(false);
+                }
+
+                // Confirm or cancel a previewed :notes s/old/new/
+                if let Some(edits) = self.pending_note_edits.take() {
+                    self.confirm_note_edits(edits, key_event.code);
+                // Scroll or close a report over the main screen
+                } else if self.state.report.is_some() {
+                    self.handle_report_input(key_event.code);
+                // Route to Claude panel if it's visible
+                } else if self.state.claude.is_response_visible() {
+                    self.handle_claude_panel_input(action);
+                // Pick between books matched by :open/:remove
+                } else if self.book_choice.is_some() {
+                    self.handle_book_choice(key_event.code);
+                // Pick between the links of a note
+                } else if let Some((book_id, links)) = self.note_link_choice.take() {
+                    self.handle_note_link_choice(&book_id, &links, key_event.code);
+                // Letter following M or '
+                } else if let Some(kind) = self.pending_mark.take() {
+                    self.handle_mark_key(kind, key_event.code);
+                // Route to the curriculum filter while it is being typed
+                } else if self.state.curriculum.filter_editing {
+                    self.handle_curriculum_filter_input(key_event.code);
+                // Route to the help screen's filter while it is being typed
+                } else if self.state.help.search_editing {
+                    self.handle_help_search_input(key_event.code);
+                // Route to the notes search while it is being typed
+                } else if self.state.notes.search_editing {
+                    self.handle_notes_search_input(key_event.code);
+                // Route to notes input if editing a note
+                } else if self.state.notes.is_editing() {
+                    self.handle_notes_input(key_event.code);
+                // Route to the question editor while a question is written
+                } else if self.is_question_form_active() {
+                    self.handle_question_form_input(key_event);
+                // Route to quiz text input when answering a typed question
+                } else if self.is_quiz_text_input_active() {
+                    self.handle_quiz_text_input(key_event.code);
+                // Quiz option letters and numbers, and moving between questions
+                } else if self.state.quiz_takes_keys()
+                    && self.handle_quiz_key(key_event.code, action)
+                {
+                    // Text objects and precise-mode toggle in visual mode
+                } else if self.state.visual_mode.active
+                    && self.state.content.cursor_mode
+                    && self.handle_visual_key(key_event.code, action)
+                {
+                    // Route to command line if in input mode
+                } else if self.state.command_line.is_input_mode() {
+                    match self.handle_command_line_input(key_event.code).await {
+                        Ok(true) => {
+                            return Ok(true);
+                        }
+                        Ok(false) => {}
+                        Err(e) => {
+                            self.state.command_line.set_error(format!("Error: {}", e));
+                        }
+                    }
+                // Special handling for Ctrl+J which terminals often send as different codes
+                // Works in both cursor mode and visual mode (for extending selection)
+                // NOTE: Terminals may send Ctrl+J as: '\n', '\r', Enter, or 'j' with CONTROL
+                } else if self.state.content.cursor_mode
+                    && (key_event.code == KeyCode::Char('\n')
+                        || key_event.code == KeyCode::Char('\r')
+                        || key_event.code == KeyCode::Enter
+                        || (key_event.code == KeyCode::Char('j')
+                            && key_event.modifiers.contains(KeyModifiers::CONTROL)))
+                {
+                    // Direct line-down for cursor/visual mode
+                    if let Some(text) = self.get_block_text(self.state.content.cursor_block) {
+                        self.state.content.cursor_line_down(&text);
+                    }
+                    self.ensure_cursor_visible();
+                    self.update_cursor_message();
+                } else if let Some(action) = action
+                    && !self.handle_view_action(action)
+                {
+                    match self.handle_action(action).await {
+                        Ok(true) => {
+                            return Ok(true);
+                        }
+                        Ok(false) => {}
+                        Err(e) => {
+                            tracing::error!("Error handling action: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Handle an action that opens something over the panels or changes
+    /// how they show; returns whether the action was used
+    fn handle_view_action(&mut self, action: Action) -> bool {
+        let has_book = self.state.book.is_some();
+        let panel = self.state.focused_panel;
+        match action {
+            Action::Command => self.state.command_line.enter_command_mode(),
+            Action::Filter if panel == Panel::Curriculum && has_book => {
+                self.start_curriculum_filter();
+            }
+            Action::Filter if panel == Panel::Notes => self.search_notes(None),
+            Action::FollowLink if panel == Panel::Notes => self.follow_note_link(),
+            Action::FollowView if panel == Panel::Notes => self.toggle_notes_follow(),
+            // Quick capture: the command line, ready for :capture
+            Action::Capture if has_book => {
+                self.state.command_line.enter_command_with("capture ");
+            }
+            Action::ToggleSkim if panel == Panel::Content && has_book => self.toggle_skim(),
+            Action::ExpandBlock
+                if self.state.content.reading_mode == ReadingMode::Skim
+                    && panel == Panel::Content =>
+            {
+                self.toggle_skim_block();
+            }
+            Action::SetMark if has_book => {
+                self.pending_mark = Some(MarkKey::Set);
+                self.state.command_line.set_message("Set mark: press a-z");
+            }
+            Action::GotoMark if has_book => {
+                self.pending_mark = Some(MarkKey::Jump);
+                self.state.command_line.set_message("Jump to mark: press a-z");
+            }
+            Action::ToggleResponse if panel == Panel::Notes && self.state.notes.highlights => {
+                self.cycle_highlight_color();
+            }
+            Action::ToggleResponse => {
+                // Toggle Claude response panel if there's a response
+                if !self.state.claude.response.is_empty() {
+                    self.state.claude.toggle_response();
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
     /// Handle an action, returns true if should exit
     async fn handle_action(&mut self, action: Action) -> Result<bool> {
         match &self.state.screen {
@@ -357,7 +1249,8 @@ impl App {
             Screen::Main => {
                 return self.handle_main_action(action);
             }
-            Screen::Help | Screen::Quiz | Screen::Notes => {
+            Screen::Help => return Ok(self.handle_help_action(action)),
+            Screen::Notes => {
                 // Escape or quit returns to main
                 match action {
                     Action::Quit => return Ok(true),
@@ -450,6 +1343,11 @@ impl App {
 
             // Vertical navigation depends on focused panel
             Action::Up | Action::Down | Action::Top | Action::Bottom => {
+                self.nav_multiplier = self.key_repeat.register(
+                    action,
+                    std::time::Instant::now(),
+                    &self.config.key_acceleration,
+                );
                 self.handle_vertical_navigation(action);
             }
 
@@ -463,12 +1361,46 @@ impl App {
                 self.handle_select();
             }
 
-            Action::Help => {
-                self.state.screen = Screen::Help;
+            // Filter the curriculum tree
+            Action::Search
+                if self.state.focused_panel == Panel::Curriculum && self.state.book.is_some() =>
+            {
+                self.start_curriculum_filter();
             }
-
-            Action::MarkComplete => {
-                self.toggle_section_complete();
+            Action::Back
+                if self.state.focused_panel == Panel::Curriculum
+                    && self.state.curriculum.is_filtered() =>
+            {
+                self.state.curriculum.clear_filter();
+            }
+
+            // Search notes in every book
+            Action::Search if self.state.focused_panel == Panel::Notes => {
+                self.search_notes(None);
+            }
+            Action::Back
+                if self.state.focused_panel == Panel::Notes && self.state.notes.is_searching() =>
+            {
+                self.state.notes.clear_search();
+            }
+            Action::Back
+                if self.state.focused_panel == Panel::Notes && self.state.notes.highlights =>
+            {
+                self.state.notes.close_highlights();
+                self.state.command_line.clear_message();
+            }
+
+            Action::Help => self.show_help(),
+
+            Action::MarkComplete => {
+                self.toggle_section_complete();
+            }
+
+            Action::JumpBack => {
+                self.jump_back();
+            }
+            Action::JumpForward => {
+                self.jump_forward();
             }
 
             // Note actions
@@ -487,8 +1419,219 @@ impl App {
         Ok(false)
     }
 
+    /// Current reading position
+    fn current_position(&self) -> state::JumpPosition {
+        state::JumpPosition {
+            chapter: self.state.current_chapter,
+            section: self.state.current_section,
+            scroll_offset: self.state.content.scroll_offset,
+        }
+    }
+
+    /// Record the current position in the book's jump list before a jump
+    fn record_jump(&mut self) {
+        let Some(book) = &self.state.book else { return };
+        let position = self.current_position();
+        self.jumps.entry(book.metadata.id.clone()).or_default().push(position);
+    }
+
+    /// Go back to where the last jump started (Ctrl+O)
+    fn jump_back(&mut self) {
+        let Some(book) = &self.state.book else { return };
+        let current = self.current_position();
+        let target = self.jumps.get_mut(&book.metadata.id).and_then(|jumps| jumps.back(current));
+        match target {
+            Some(position) => self.restore_position(position),
+            None => self.state.command_line.set_message("Already at oldest jump"),
+        }
+    }
+
+    /// Redo a jump undone with Ctrl+O (Ctrl+I / Tab)
+    fn jump_forward(&mut self) {
+        let Some(book) = &self.state.book else { return };
+        let target = self.jumps.get_mut(&book.metadata.id).and_then(|jumps| jumps.forward());
+        match target {
+            Some(position) => self.restore_position(position),
+            None => self.state.command_line.set_message("Already at newest jump"),
+        }
+    }
+
+    /// Move to a position from the jump list
+    fn restore_position(&mut self, position: state::JumpPosition) {
+        let Some(section) = self
+            .state
+            .book
+            .as_ref()
+            .and_then(|b| b.get_section(position.chapter, position.section))
+        else {
+            self.state.command_line.set_error("Jump target no longer exists");
+            return;
+        };
+        let title = section.title.clone();
+
+        let same_section = (position.chapter, position.section)
+            == (self.state.current_chapter, self.state.current_section);
+        if !same_section {
+            self.remember_scroll();
+        }
+        self.state.current_chapter = position.chapter;
+        self.state.current_section = position.section;
+        self.state.content.scroll_offset = position.scroll_offset;
+        self.state.curriculum.expanded_chapters.insert(position.chapter);
+        if !same_section {
+            self.mark_section_viewed();
+        }
+        self.state.command_line.set_message(format!("→ {}", title));
+    }
+
+    /// Set or jump to a mark in the current section
+    fn handle_mark_key(&mut self, kind: MarkKey, key: KeyCode) {
+        let letter = match key {
+            KeyCode::Char(c) if input::MARK_NAMES.contains(&c) => c,
+            _ => {
+                self.state.command_line.clear_message();
+                return;
+            }
+        };
+        let Some(book) = &self.state.book else { return };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            self.state.command_line.set_error("No section selected");
+            return;
+        };
+        let book_id = book.metadata.id.clone();
+        let section_path = section.path.clone();
+
+        match kind {
+            MarkKey::Set => {
+                let offset = self.state.content.scroll_offset;
+                self.session
+                    .book_mut(&book_id)
+                    .marks
+                    .entry(section_path)
+                    .or_default()
+                    .insert(letter, offset);
+                self.state.command_line.set_message(format!("Mark '{} set", letter));
+            }
+            MarkKey::Jump => {
+                let offset = self
+                    .session
+                    .book(&book_id)
+                    .and_then(|s| s.marks.get(&section_path))
+                    .and_then(|marks| marks.get(&letter))
+                    .copied();
+                match offset {
+                    Some(offset) => {
+                        self.record_jump();
+                        self.state.content.scroll_offset = offset;
+                        self.state.command_line.set_message(format!("Mark '{}", letter));
+                    }
+                    None => {
+                        self.state
+                            .command_line
+                            .set_error(format!("Mark '{} not set in this section", letter));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Handle visual-mode keys that aren't regular actions.
+    /// Returns true if the key was consumed.
+    fn handle_visual_key(&mut self, key: KeyCode, action: Option<Action>) -> bool {
+        if !self.state.visual_mode.pending_object {
+            match action {
+                Some(Action::CreateNote) => {
+                    self.state.visual_mode.pending_object = true;
+                    self.state.command_line.set_message(
+                        "-- VISUAL -- a: [s]entence  [l]ist item  [b]lock  [a/Enter] annotate",
+                    );
+                }
+                Some(Action::Highlight) => self.highlight_selection(),
+                Some(Action::Previous) => {
+                    let precise = !self.state.visual_mode.precise;
+                    self.state.visual_mode.precise = precise;
+                    self.state.command_line.set_message(if precise {
+                        "-- VISUAL -- character-precise selection"
+                    } else {
+                        "-- VISUAL -- selection snaps to words"
+                    });
+                }
+                _ => return false,
+            }
+            return true;
+        }
+
+        self.state.visual_mode.pending_object = false;
+        if let Some(object) = input::text_object(key) {
+            self.select_text_object(object);
+        } else if matches!(action, Some(Action::CreateNote | Action::Select)) {
+            self.create_note_from_selection();
+        } else {
+            self.update_cursor_message();
+        }
+        true
+    }
+
+    /// Expand the visual selection to the text object around the cursor
+    fn select_text_object(&mut self, object: state::TextObject) {
+        let block = self.state.content.cursor_block;
+        let Some(text) = self.get_block_text(block) else { return };
+
+        let is_list = matches!(
+            self.current_block(block),
+            Some(crate::book::ContentBlock::UnorderedList(_))
+                | Some(crate::book::ContentBlock::OrderedList(_))
+        );
+        if object == state::TextObject::ListItem && !is_list {
+            self.state.command_line.set_error("Cursor is not in a list");
+            return;
+        }
+
+        let Some((start, end)) =
+            state::text_object_bounds(&text, self.state.content.cursor_char, object)
+        else {
+            return;
+        };
+        self.state.content.cursor_char = self.state.visual_mode.select_range(block, start, end);
+        self.ensure_cursor_visible();
+        self.update_cursor_message();
+    }
+
+    /// Get a content block of the current section
+    fn current_block(&self, block_index: usize) -> Option<&crate::book::ContentBlock> {
+        let book = self.state.book.as_ref()?;
+        let section = book.get_section(self.state.current_chapter, self.state.current_section)?;
+        section.content.get(block_index)
+    }
+
+    /// Snap the moving end of a visual selection to a word boundary
+    fn snap_visual_cursor(&mut self, forward: bool) {
+        let visual = &self.state.visual_mode;
+        if !visual.active || visual.precise {
+            return;
+        }
+        let (block, char_pos) = (self.state.content.cursor_block, self.state.content.cursor_char);
+        let is_end = visual.cursor_is_end(block, char_pos);
+        if let Some(text) = self.get_block_text(block) {
+            self.state.content.cursor_char = state::snap_to_word(&text, char_pos, forward, is_end);
+        }
+    }
+
     /// Handle actions when cursor mode is active in content panel
     fn handle_content_cursor_action(&mut self, action: Action) -> Result<bool> {
+        // Direction of cursor motions, for snapping visual selections to words
+        let motion = match action {
+            Action::Right
+            | Action::Down
+            | Action::WordForward
+            | Action::WordEnd
+            | Action::LineDown => Some(true),
+            Action::Left | Action::Up | Action::WordBackward | Action::LineUp => Some(false),
+            _ => None,
+        };
+
         match action {
             Action::Quit => return Ok(true),
 
@@ -510,7 +1653,10 @@ impl App {
                 self.toggle_visual_mode();
             }
 
-            // Cursor movement
+            // Cursor movement (h/l scroll a table sideways instead)
+            Action::Left | Action::Right if self.cursor_table().is_some() => {
+                self.scroll_cursor_table(action == Action::Right);
+            }
             Action::Left => {
                 self.state.content.cursor_left();
                 self.ensure_cursor_visible();
@@ -610,9 +1756,18 @@ impl App {
                     self.yank_selection();
                 }
             }
+            Action::YankCode => self.yank_code_block(),
 
             _ => {}
         }
+
+        if let Some(forward) = motion {
+            if self.state.visual_mode.active && !self.state.visual_mode.precise {
+                self.snap_visual_cursor(forward);
+                self.ensure_cursor_visible();
+                self.update_cursor_message();
+            }
+        }
         Ok(false)
     }
 
@@ -636,12 +1791,12 @@ impl App {
                 .set_message("-- CURSOR -- (h/j/k/l to move, v to select, Esc to exit)");
         } else if self.state.content.cursor_mode {
             // Already in cursor mode, start visual selection
-            self.state
-                .visual_mode
-                .enter(self.state.content.cursor_block, self.state.content.cursor_char);
-            self.state
-                .command_line
-                .set_message("-- VISUAL -- (move to select, a/Enter to annotate, v/Esc to cancel)");
+            let block = self.state.content.cursor_block;
+            let text = self.get_block_text(block).unwrap_or_default();
+            self.state.visual_mode.enter_snapped(block, self.state.content.cursor_char, &text);
+            self.state.command_line.set_message(
+                "-- VISUAL -- (move to select, as/al/ab expand, Enter to annotate, H to highlight, v/Esc to cancel)",
+            );
         } else {
             // Enter cursor mode (navigation)
             self.enter_cursor_mode();
@@ -671,6 +1826,37 @@ impl App {
         }
     }
 
+    /// The table under the cursor, if it is on one
+    fn cursor_table(&self) -> Option<&crate::book::Table> {
+        let book = self.state.book.as_ref()?;
+        let section = book.get_section(self.state.current_chapter, self.state.current_section)?;
+        match section.content.get(self.state.content.cursor_block)? {
+            crate::book::ContentBlock::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+
+    /// Scroll the table under the cursor sideways
+    fn scroll_cursor_table(&mut self, right: bool) {
+        let Some(table) = self.cursor_table() else { return };
+        let limit = ui::content::table_scroll_limit(table, self.state.content.content_width);
+        if limit == 0 {
+            self.state.command_line.set_message("-- CURSOR -- table fits the panel");
+            return;
+        }
+        let block = self.state.content.cursor_block;
+        let current = self.state.content.table_scroll_at(block);
+        let columns = if right {
+            (current + TABLE_SCROLL_STEP).min(limit)
+        } else {
+            current.saturating_sub(TABLE_SCROLL_STEP)
+        };
+        self.state.content.table_scroll = Some((block, columns));
+        self.state
+            .command_line
+            .set_message(format!("-- CURSOR -- table scrolled {} of {} columns", columns, limit));
+    }
+
     /// Ensure the cursor is visible by scrolling if needed
     fn ensure_cursor_visible(&mut self) {
         // Use the actual block line offsets computed during rendering
@@ -743,88 +1929,28 @@ impl App {
         self.get_block_text(block_index).map(|t| t.chars().count()).unwrap_or(0)
     }
 
-    /// Get the text content of a block
+    /// Get the text content of a block in the current section
     fn get_block_text(&self, block_index: usize) -> Option<String> {
         let book = self.state.book.as_ref()?;
         let section = book.get_section(self.state.current_chapter, self.state.current_section)?;
-
-        match section.content.get(block_index) {
-            Some(crate::book::ContentBlock::Paragraph(text)) => Some(text.clone()),
-            Some(crate::book::ContentBlock::Blockquote(text)) => Some(text.clone()),
-            Some(crate::book::ContentBlock::Heading { text, .. }) => Some(text.clone()),
-            Some(crate::book::ContentBlock::UnorderedList(items)) => {
-                // Combine all list items with newlines for navigation
-                Some(items.join("\n"))
-            }
-            Some(crate::book::ContentBlock::OrderedList(items)) => {
-                // Combine all list items with newlines for navigation
-                Some(items.join("\n"))
-            }
-            Some(crate::book::ContentBlock::Code(code)) => {
-                // Return the code content for navigation
-                Some(code.code.clone())
-            }
-            _ => None,
-        }
+        block_text(section.content.get(block_index)?)
     }
 
     /// Create a note from the current visual selection
     fn create_note_from_selection(&mut self) {
         use crate::notes::Note;
 
-        let Some(book) = &self.state.book else {
-            self.state.visual_mode.exit();
-            return;
-        };
-        let Some(section) =
-            book.get_section(self.state.current_chapter, self.state.current_section)
+        let Some((book_id, section_path, block, start_char, selected_text)) = self.take_selection()
         else {
-            self.state.visual_mode.exit();
-            return;
-        };
-
-        let (start_block, start_char, end_block, end_char) = self
-            .state
-            .visual_mode
-            .selection_range(self.state.content.cursor_block, self.state.content.cursor_char);
-
-        // For simplicity, only support single-block selection for now
-        if start_block != end_block {
-            self.state.command_line.set_error("Multi-block selection not yet supported");
-            self.state.visual_mode.exit();
             return;
-        }
-
-        // Extract the selected text
-        let selected_text = match section.content.get(start_block) {
-            Some(crate::book::ContentBlock::Paragraph(text)) => {
-                text.chars().skip(start_char).take(end_char - start_char).collect::<String>()
-            }
-            Some(crate::book::ContentBlock::Blockquote(text)) => {
-                text.chars().skip(start_char).take(end_char - start_char).collect::<String>()
-            }
-            Some(crate::book::ContentBlock::Heading { text, .. }) => {
-                text.chars().skip(start_char).take(end_char - start_char).collect::<String>()
-            }
-            _ => {
-                self.state.command_line.set_error("Cannot annotate this block type");
-                self.state.visual_mode.exit();
-                return;
-            }
         };
 
-        if selected_text.is_empty() {
-            self.state.command_line.set_error("No text selected");
-            self.state.visual_mode.exit();
-            return;
-        }
-
         // Create the note with anchor
         let note = Note::new_selection_note(
-            &book.metadata.id,
-            &section.path,
+            &book_id,
+            &section_path,
             "", // Empty content - user will edit
-            start_block,
+            block,
             start_char,
             &selected_text,
         );
@@ -837,15 +1963,79 @@ impl App {
         self.state.panel_visibility.notes = true;
         self.state.focused_panel = Panel::Notes;
 
-        // Exit visual mode
-        self.state.visual_mode.exit();
         self.state
             .command_line
             .set_message(format!("Annotating: \"{}\"", truncate_str(&selected_text, 30)));
     }
 
-    /// Mark current section as viewed
+    /// Highlight the visual selection in the current color, without a note
+    fn highlight_selection(&mut self) {
+        use crate::notes::Note;
+
+        let Some((book_id, section_path, block, start_char, selected_text)) = self.take_selection()
+        else {
+            return;
+        };
+
+        let color = self.state.notes.highlight_color;
+        let note =
+            Note::new_highlight(&book_id, &section_path, color, block, start_char, &selected_text);
+        self.notes_store.add_note(note);
+        if let Err(e) = self.notes_store.save() {
+            tracing::warn!("Failed to save notes: {}", e);
+        }
+        self.state.command_line.set_message(format!(
+            "Highlighted in {}: \"{}\" (:highlights to list)",
+            color.name(),
+            truncate_str(&selected_text, 30)
+        ));
+    }
+
+    /// End visual mode, returning the selection as (book ID, section path,
+    /// block index, start character, text)
+    ///
+    /// None, with an error shown, if the selection can't be annotated.
+    fn take_selection(&mut self) -> Option<(String, String, usize, usize, String)> {
+        let (start_block, start_char, end_block, end_char) = self
+            .state
+            .visual_mode
+            .selection_range(self.state.content.cursor_block, self.state.content.cursor_char);
+        self.state.visual_mode.exit();
+
+        let book = self.state.book.as_ref()?;
+        let section = book.get_section(self.state.current_chapter, self.state.current_section)?;
+        // For simplicity, only support single-block selection for now
+        let selected_text = match section.content.get(start_block) {
+            _ if start_block != end_block => Err("Multi-block selection not yet supported"),
+            Some(
+                crate::book::ContentBlock::Paragraph(text)
+                | crate::book::ContentBlock::Blockquote(text)
+                | crate::book::ContentBlock::Heading { text, .. },
+            ) => Ok(text.chars().skip(start_char).take(end_char - start_char).collect::<String>()),
+            _ => Err("Cannot annotate this block type"),
+        }
+        .and_then(|text| if text.is_empty() { Err("No text selected") } else { Ok(text) });
+
+        match selected_text {
+            Ok(text) => Some((
+                book.metadata.id.clone(),
+                section.path.clone(),
+                start_block,
+                start_char,
+                text,
+            )),
+            Err(e) => {
+                self.state.command_line.set_error(e);
+                None
+            }
+        }
+    }
+
+    /// Mark current section as viewed, unloading sections read long ago
     fn mark_section_viewed(&mut self) {
+        if let Some(book) = &mut self.state.book {
+            book.unload_sections(LOADED_SECTIONS);
+        }
         let Some(book) = &self.state.book else { return };
         let Some(section) =
             book.get_section(self.state.current_chapter, self.state.current_section)
@@ -858,17 +2048,140 @@ impl App {
 
         if !section_progress.viewed {
             section_progress.viewed = true;
-            section_progress.last_accessed = Some(
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map_or(0, |d| d.as_secs() as i64),
-            );
-            if let Err(e) = self.progress.save() {
-                tracing::warn!("Failed to save progress: {}", e);
+            section_progress.last_accessed = Some(datetime::now());
+            let unwritten = self.objectives.get(&section.path).is_none();
+            self.save_progress();
+
+            // Objectives are written before the reader starts a new section
+            if unwritten && self.config.claude.auto_objectives {
+                if self.over_prefetch_budget() {
+                    tracing::info!("Not writing objectives: today's spending is over budget");
+                } else {
+                    self.write_objectives(true);
+                }
+            }
+        }
+    }
+
+    /// Record which lines have been seen and auto-mark the section once read through
+    fn update_read_through(&mut self) {
+        if self.config.auto_mark == AutoMarkMode::Off || !matches!(self.state.screen, Screen::Main)
+        {
+            return;
+        }
+
+        let Some(book) = &self.state.book else { return };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            return;
+        };
+
+        self.state.content.record_seen_lines(&section.path);
+
+        if self.state.content.auto_marked
+            || !self.state.content.is_read_through(self.config.auto_mark_threshold)
+        {
+            return;
+        }
+        self.state.content.auto_marked = true;
+
+        let already_marked = self
+            .progress
+            .books
+            .get(&book.metadata.id)
+            .and_then(|b| b.sections.get(&section.path))
+            .is_some_and(|s| match self.config.auto_mark {
+                AutoMarkMode::Complete => s.completed,
+                _ => s.viewed,
+            });
+        if already_marked {
+            return;
+        }
+
+        match self.config.auto_mark {
+            AutoMarkMode::Complete => self.mark_section_complete(),
+            AutoMarkMode::Viewed => self.mark_section_viewed(),
+            AutoMarkMode::Off => {}
+        }
+    }
+
+    /// Move the last-read marker to the top of the viewport once it has stayed put
+    fn update_last_read(&mut self) {
+        if !matches!(self.state.screen, Screen::Main) {
+            return;
+        }
+        let Some(book) = &self.state.book else { return };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            return;
+        };
+
+        let content = &mut self.state.content;
+        let offset = content.scroll_offset;
+        let dwelled = match &content.dwell {
+            Some((path, at, since)) if *path == section.path && *at == offset => {
+                since.elapsed() >= LAST_READ_DWELL
+            }
+            _ => {
+                content.dwell = Some((section.path.clone(), offset, std::time::Instant::now()));
+                false
             }
+        };
+        let anchor = content
+            .layout_cache
+            .as_ref()
+            .filter(|cache| cache.key.section_path == section.path)
+            .and_then(|cache| cache.window.anchor(offset));
+        let Some(anchor) = anchor.filter(|_| dwelled) else { return };
+
+        let last_read = LastRead {
+            section_path: section.path.clone(),
+            block: anchor.block,
+            line: anchor.line,
+            block_height: anchor.height,
+            offset,
+        };
+        if content.last_read.as_ref() != Some(&last_read) {
+            content.last_read = Some(last_read.clone());
+            self.session.book_mut(&book.metadata.id).last_read = Some(last_read);
         }
     }
 
+    /// Jump to the last-read marker (`:resume`)
+    fn resume(&mut self) {
+        let Some(last_read) = self.state.content.last_read.clone() else {
+            self.state.command_line.set_error("No last-read position yet");
+            return;
+        };
+        let Some(book) = &self.state.book else { return };
+        let found = book.chapters.iter().enumerate().find_map(|(chapter, c)| {
+            let section = c.sections.iter().position(|s| s.path == last_read.section_path)?;
+            Some((chapter, section))
+        });
+        let Some((chapter, section)) = found else {
+            self.state.command_line.set_error("Last-read section no longer exists");
+            return;
+        };
+
+        // Follow reflow when the section is laid out already
+        let anchor = ScrollAnchor {
+            block: last_read.block,
+            line: last_read.line,
+            height: last_read.block_height,
+        };
+        let scroll_offset = self
+            .state
+            .content
+            .layout_cache
+            .as_ref()
+            .filter(|cache| cache.key.section_path == last_read.section_path)
+            .map_or(last_read.offset, |cache| cache.window.offset_of(anchor));
+        self.record_jump();
+        self.restore_position(state::JumpPosition { chapter, section, scroll_offset });
+    }
+
     /// Toggle current section's complete status
     fn toggle_section_complete(&mut self) {
         let Some(book) = &self.state.book else { return };
@@ -882,11 +2195,15 @@ impl App {
         let section_progress = book_progress.sections.entry(section.path.clone()).or_default();
 
         section_progress.completed = !section_progress.completed;
-        if section_progress.completed {
+        let completed = section_progress.completed;
+        if completed {
             section_progress.viewed = true;
         }
-        if let Err(e) = self.progress.save() {
-            tracing::warn!("Failed to save progress: {}", e);
+        self.save_progress();
+        if completed {
+            self.state
+                .command_line
+                .set_message("Marked complete. Explain it back in your own words with :teach");
         }
     }
 
@@ -913,25 +2230,22 @@ impl App {
 
         section_progress.completed = true;
         section_progress.viewed = true;
-        section_progress.last_accessed = Some(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map_or(0, |d| d.as_secs() as i64),
-        );
-        if let Err(e) = self.progress.save() {
-            tracing::warn!("Failed to save progress: {}", e);
-        }
+        section_progress.last_accessed = Some(datetime::now());
+        self.save_progress();
 
         self.state.command_line.set_message("Section marked complete!");
     }
 
     /// Navigate to the next section in the book
     fn navigate_to_next_section(&mut self) {
+        self.remember_scroll();
         let Some(book) = &self.state.book else { return };
 
         let current_chapter = self.state.current_chapter;
         let current_section = self.state.current_section;
 
+        let mut finished_chapter = None;
+
         // Try next section in current chapter
         if let Some(chapter) = book.chapters.get(current_chapter) {
             if current_section + 1 < chapter.sections.len() {
@@ -943,6 +2257,7 @@ impl App {
                 self.state.current_section = 0;
                 // Expand the new chapter in curriculum
                 self.state.curriculum.expanded_chapters.insert(current_chapter + 1);
+                finished_chapter = Some(chapter.title.clone());
             } else {
                 // End of book
                 self.state.command_line.set_message("Congratulations! You've completed the book!");
@@ -953,17 +2268,24 @@ impl App {
             return;
         }
 
-        // Reset scroll and footer state
-        self.state.content.scroll_offset = 0;
+        // Back to where the section was left, and out of the footer
+        self.state.content.scroll_offset = self.remembered_scroll();
         self.state.content.exit_footer();
 
         // Mark new section as viewed
         self.mark_section_viewed();
 
-        self.state.command_line.set_message("Moving to next section...");
+        match finished_chapter {
+            Some(title) => self.state.command_line.set_message(format!(
+                "Finished {}! Explain it back in your own words with :recap",
+                title
+            )),
+            None => self.state.command_line.set_message("Moving to next section..."),
+        }
     }
 
-    /// Start the quiz for current section
+    /// Start the quiz for current section, asking the reader's own questions
+    /// first and Claude's for the rest
     fn start_quiz(&mut self) {
         let Some(book) = &self.state.book else { return };
         let Some(section) =
@@ -971,6 +2293,28 @@ impl App {
         else {
             return;
         };
+        let own: Vec<_> =
+            self.question_bank.custom(&section.path).iter().take(QUIZ_QUESTIONS).cloned().collect();
+        let count = QUIZ_QUESTIONS - own.len();
+
+        // Enough questions of the reader's own need no Claude, and Claude's
+        // may have been fetched while the previous section was read
+        let book_id = &book.metadata.id;
+        let prefetched: Option<Vec<_>> = self
+            .prefetched_quiz
+            .take_if(|(id, path, _)| id == book_id && *path == section.path)
+            .map(|(_, _, questions)| {
+                let bank = &self.question_bank;
+                questions.into_iter().filter(|q| !bank.is_removed(&section.path, q)).collect()
+            })
+            .filter(|questions: &Vec<_>| questions.len() >= count);
+        if count == 0 || prefetched.is_some() {
+            let questions = own.into_iter().chain(prefetched.into_iter().flatten());
+            self.state.quiz.start_loading(&section.path);
+            self.state.quiz.set_questions(questions.take(QUIZ_QUESTIONS).collect());
+            self.state.command_line.set_message("Quiz ready! Use j/k to select, Enter to confirm.");
+            return;
+        }
 
         // Check for API key
         if self.state.claude.needs_setup {
@@ -989,46 +2333,442 @@ impl App {
 
         // Reset quiz state and set loading
         self.state.quiz.start_loading(&section.path);
+        self.state.quiz.start_generating(QUIZ_QUESTIONS);
         self.state.command_line.set_message("Generating quiz questions...");
 
-        // Get section content for the prompt
-        let section_title = section.title.clone();
-        let section_content = section.plain_text();
-
-        // Truncate content if too long
-        let content = if section_content.len() > 6000 {
-            format!("{}...\n\n[Content truncated]", &section_content[..6000])
-        } else {
-            section_content
+        // Claude writes spares for the questions the reader deleted, so the
+        // quiz still fills up when it repeats one
+        let requested = count + self.question_bank.removed_count(&section.path).min(count);
+        let prompt = self.section_quiz_prompt(book_id, section, &own, requested);
+        if !own.is_empty() {
+            self.state.command_line.set_message("Quiz ready! Use j/k to select, Enter to confirm.");
+        }
+        for question in own {
+            self.state.quiz.push_question(question);
+        }
+
+        // Create channel for questions as they stream in
+        let (tx, rx) = tokio::sync::mpsc::channel(QUIZ_QUESTIONS + 1);
+        self.quiz_rx = Some(rx);
+
+        let client = self.claude_client(api_key);
+        let settings =
+            self.request_settings(self.claude_model(), self.config.claude.quiz_max_tokens);
+        let usage_tx = self.usage_tx.clone();
+
+        // Spawn the quiz generation task
+        tokio::spawn(stream_quiz_questions(client, settings, usage_tx, prompt, requested, tx));
+    }
+
+    /// Prompt asking for `count` questions on a section besides the reader's
+    /// own, pitched at their mastery of the book
+    fn section_quiz_prompt(
+        &self,
+        book_id: &str,
+        section: &crate::book::Section,
+        own: &[crate::app::state::QuizQuestion],
+        count: usize,
+    ) -> String {
+        let ratings = self.progress.books.get(book_id).map(|p| &p.quiz_ratings);
+        let guidance = ratings.and_then(|r| quiz_guidance(r, &section.path));
+        let content = quiz_content(section, self.config.claude.context_chars);
+        let prompt = quiz_prompt(&section.title, &content, count);
+        let prompt = append_instruction(prompt, guidance.as_deref());
+        let prompt = append_instruction(prompt, own_questions_guidance(own).as_deref());
+        append_instruction(prompt, self.config.claude.language_instruction().as_deref())
+    }
+
+    /// Whether today's spending has reached `claude.prefetch_budget`, which
+    /// stops requests the reader didn't ask for
+    fn over_prefetch_budget(&self) -> bool {
+        let budget = self.config.claude.prefetch_budget;
+        budget > 0.0 && self.usage.today().cost >= budget
+    }
+
+    /// Near the end of a section, fetch the next section's quiz and its
+    /// chapter's summary in the background, so they're ready once it's reached
+    ///
+    /// Each section is fetched once, and not at all once today's spending
+    /// reaches `claude.prefetch_budget`.
+    fn prefetch_next_section(&mut self) {
+        use crate::claude::summary::{SummaryCache, summary_prompt};
+
+        if !self.config.claude.prefetch
+            || self.prefetch_rx.is_some()
+            || self.state.claude.needs_setup
+            || !matches!(self.state.screen, Screen::Main)
+            || !self.state.content.near_end()
+        {
+            return;
+        }
+        let Some(book) = &self.state.book else { return };
+        let Some((chapter_idx, section_idx)) =
+            book.next_section(self.state.current_chapter, self.state.current_section)
+        else {
+            return;
+        };
+        let chapter = &book.chapters[chapter_idx];
+        let section = &chapter.sections[section_idx];
+        let book_id = book.metadata.id.clone();
+        let key = (book_id.clone(), section.path.clone());
+        if self.prefetched_for.as_ref() == Some(&key) {
+            return;
+        }
+        self.prefetched_for = Some(key);
+
+        if self.over_prefetch_budget() {
+            tracing::info!("Not prefetching {}: today's spending is over budget", section.path);
+            return;
+        }
+        let Ok(api_key) = crate::claude::ApiKeyManager::get_api_key() else { return };
+
+        let own = self.question_bank.custom(&section.path);
+        let count = QUIZ_QUESTIONS.saturating_sub(own.len());
+        let count = count + self.question_bank.removed_count(&section.path).min(count);
+        let quiz = (count > 0).then(|| {
+            let own: Vec<_> = own.iter().take(QUIZ_QUESTIONS).cloned().collect();
+            (section.path.clone(), self.section_quiz_prompt(&book_id, section, &own, count))
+        });
+        let cached = SummaryCache::load(&book_id).unwrap_or_default().get(&chapter.path).is_some();
+        let summary = (!cached).then(|| {
+            let prompt = summary_prompt(
+                &chapter.title,
+                &chapter_excerpt(chapter, self.config.claude.chapter_context_chars),
+            );
+            (chapter.path.clone(), chapter.title.clone(), prompt)
+        });
+        if quiz.is_none() && summary.is_none() {
+            return;
+        }
+
+        let usage_tx = self.usage_tx.clone();
+        let (tx, rx) = tokio::sync::mpsc::channel(2);
+        self.prefetch_rx = Some(rx);
+
+        if let Some((section_path, prompt)) = quiz {
+            let settings =
+                self.request_settings(self.claude_model(), self.config.claude.quiz_max_tokens);
+            let client = self.claude_client(api_key.clone());
+            let (usage_tx, tx) = (usage_tx.clone(), tx.clone());
+            let book_id = book_id.clone();
+            tokio::spawn(async move {
+                let questions = generate_quiz(client, settings, usage_tx, prompt, count).await;
+                let _ = tx.send(Prefetched::Quiz(book_id, section_path, questions)).await;
+            });
+        }
+        if let Some((chapter_path, title, prompt)) = summary {
+            let settings =
+                self.request_settings(self.claude_model(), self.config.claude.max_tokens);
+            let system = self.system_prompt("Summarize the chapter you are given.");
+            let client = self.claude_client(api_key);
+            tokio::spawn(async move {
+                let text = ask_once(client, settings, usage_tx, Some(system), prompt).await;
+                let _ = tx.send(Prefetched::Summary(book_id, chapter_path, title, text)).await;
+            });
+        }
+    }
+
+    /// Keep what was prefetched for the next section once it arrives
+    fn process_prefetched(&mut self) {
+        use tokio::sync::mpsc::error::TryRecvError;
+
+        let Some(rx) = &mut self.prefetch_rx else { return };
+        let mut arrived = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(prefetched) => arrived.push(prefetched),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.prefetch_rx = None;
+                    break;
+                }
+            }
+        }
+
+        for prefetched in arrived {
+            match prefetched {
+                Prefetched::Quiz(book_id, section_path, Ok(questions)) => {
+                    self.prefetched_quiz = Some((book_id, section_path, questions));
+                }
+                Prefetched::Summary(book_id, chapter_path, title, Ok(text)) => {
+                    let mut cache =
+                        crate::claude::summary::SummaryCache::load(&book_id).unwrap_or_default();
+                    if cache.get(&chapter_path).is_none() {
+                        cache.insert(&chapter_path, &title, &text);
+                        if let Err(e) = cache.save(&book_id) {
+                            tracing::warn!("Failed to save summary: {}", e);
+                        }
+                    }
+                }
+                Prefetched::Quiz(_, path, Err(e)) | Prefetched::Summary(_, path, _, Err(e)) => {
+                    tracing::warn!("Failed to prefetch for {}: {}", path, e);
+                }
+            }
+        }
+    }
+}
+
+impl App {
+    /// Replace missed questions with follow-ups targeting the same concepts
+    fn regenerate_missed_questions(&mut self) {
+        let Some(book) = &self.state.book else { return };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            return;
+        };
+
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(_) => {
+                // Without Claude, retake the same questions
+                self.state.quiz.attempt += 1;
+                self.state.quiz.retry();
+                return;
+            }
         };
 
-        // Create channel for results
+        let quiz = &self.state.quiz;
+        let missed: Vec<MissedQuestion> = quiz
+            .missed()
+            .into_iter()
+            .map(|i| {
+                let question = &quiz.questions[i];
+                MissedQuestion {
+                    question: question.question().to_string(),
+                    correct_answer: question.answer_text(),
+                    given_answer: quiz.answers[i]
+                        .as_ref()
+                        .map(|a| question.describe_answer(a))
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        let section_title = section.title.clone();
+        let content = quiz_content(section, self.config.claude.context_chars);
+
+        self.state.quiz.start_regenerating();
+        self.state.command_line.set_message("Generating follow-up questions on what you missed...");
+
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         self.quiz_rx = Some(rx);
 
-        let model = self.state.claude.model;
+        let client = self.claude_client(api_key);
+        let settings =
+            self.request_settings(self.claude_model(), self.config.claude.quiz_max_tokens);
+        let usage_tx = self.usage_tx.clone();
+        let attempt = self.state.quiz.attempt;
+        let language = self.config.claude.language_instruction();
 
-        // Spawn the quiz generation task
         tokio::spawn(async move {
-            let result = generate_quiz_questions(api_key, model, &section_title, &content).await;
+            let request = RetryRequest { section_title, content, missed, attempt, language };
+            let result = generate_retry_questions(client, settings, usage_tx, request).await;
             let _ = tx.send(result).await;
         });
     }
 }
 
-/// Generate quiz questions using Claude API
-async fn generate_quiz_questions(
-    api_key: String,
+/// Append an instruction (e.g. the configured language) to a prompt, as a
+/// paragraph of its own; None leaves the prompt as it is
+fn append_instruction(mut prompt: String, instruction: Option<&str>) -> String {
+    if let Some(instruction) = instruction {
+        prompt.push_str("\n\n");
+        prompt.push_str(instruction);
+    }
+    prompt
+}
+
+/// Instructions biasing a new quiz towards the reader's level, from how
+/// they've done on this book's questions so far
+///
+/// Questions in the section the reader is more likely than not to miss are
+/// listed so their concepts come up again, asked a different way.
+fn quiz_guidance(
+    ratings: &crate::learning::rating::QuizRatings,
+    section_path: &str,
+) -> Option<String> {
+    use crate::learning::rating::{INITIAL_RATING, expected};
+
+    const HARD_QUESTIONS: usize = 3;
+
+    let mut guidance = Vec::new();
+    if ratings.answered >= QUIZ_QUESTIONS as u32 {
+        if ratings.mastery < INITIAL_RATING - 100.0 {
+            guidance.push(
+                "The learner has found this book's quizzes hard. Favour direct questions on the \
+                 core ideas over tricky edge cases."
+                    .to_string(),
+            );
+        } else if ratings.mastery > INITIAL_RATING + 100.0 {
+            guidance.push(
+                "The learner does well on this book's quizzes. Favour harder questions that \
+                 apply the ideas to new situations."
+                    .to_string(),
+            );
+        }
+    }
+
+    let hard: Vec<String> = ratings
+        .hardest_in(section_path)
+        .into_iter()
+        .filter(|(_, q)| expected(ratings.mastery, q.rating) < 0.5)
+        .take(HARD_QUESTIONS)
+        .map(|(question, _)| format!("- {}", question))
+        .collect();
+    if !hard.is_empty() {
+        guidance.push(format!(
+            "The learner has struggled with these questions before. Test the same concepts \
+             again from a different angle, without repeating them:\n{}",
+            hard.join("\n")
+        ));
+    }
+
+    (!guidance.is_empty()).then(|| guidance.join("\n\n"))
+}
+
+/// Section text used as quiz source material, truncated to `max_chars`
+fn quiz_content(section: &crate::book::Section, max_chars: usize) -> String {
+    crate::book::excerpt::section_excerpt(section, None, max_chars)
+}
+
+/// A question answered incorrectly on a previous attempt
+struct MissedQuestion {
+    question: String,
+    correct_answer: String,
+    given_answer: String,
+}
+
+/// Model, response length and temperature for a request sent from a spawned task
+#[derive(Debug, Clone)]
+struct RequestSettings {
     model: crate::claude::ClaudeModel,
-    section_title: &str,
-    content: &str,
+    max_tokens: u32,
+    temperature: Option<f32>,
+}
+
+impl RequestSettings {
+    /// A streamed request with these settings
+    fn request(
+        &self,
+        messages: Vec<crate::claude::Message>,
+    ) -> crate::claude::CreateMessageRequest {
+        crate::claude::CreateMessageRequest::new(&self.model, messages)
+            .with_max_tokens(self.max_tokens)
+            .with_temperature(self.temperature)
+    }
+}
+
+/// Input for generating follow-up questions after a failed attempt
+struct RetryRequest {
+    section_title: String,
+    content: String,
+    missed: Vec<MissedQuestion>,
+    /// The attempt the follow-up questions are for (2 = first retry)
+    attempt: u32,
+    /// Instruction for the language to write questions in
+    language: Option<String>,
+}
+
+/// Generate follow-up questions targeting the concepts missed on a previous attempt
+async fn generate_retry_questions(
+    client: crate::claude::ClaudeClient,
+    settings: RequestSettings,
+    usage_tx: tokio::sync::mpsc::UnboundedSender<UsageReport>,
+    request: RetryRequest,
 ) -> QuizGenerationResult {
-    use crate::claude::{ClaudeClient, CreateMessageRequest, Message};
+    let missed = request
+        .missed
+        .iter()
+        .map(|m| {
+            format!(
+                "Question: {}\nCorrect answer: {}\nLearner's answer: {}",
+                m.question, m.correct_answer, m.given_answer
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
 
-    let client = ClaudeClient::new(api_key);
+    // Ease off after repeated misses so the learner can rebuild from the core idea
+    let difficulty = if request.attempt <= 2 {
+        "Test the same concepts from a different angle, at a similar difficulty."
+    } else {
+        "The learner has struggled with these concepts several times. Make the questions more \
+         direct, focusing on the single core idea behind each one."
+    };
 
     let prompt = format!(
-        r#"Based on this educational content about "{}", generate exactly 5 multiple-choice quiz questions to test comprehension.
+        r#"A learner is taking a quiz on "{}" (attempt {}). They missed these questions:
+
+{}
+
+{} Do not repeat the original questions.
+
+Content:
+{}
+
+Generate exactly {} new questions, one per missed question, in the same order, as a JSON object with this exact structure:
+{{
+  "questions": [
+    {{
+      "type": "multiple_choice",
+      "question": "The question text",
+      "options": ["Option A", "Option B", "Option C", "Option D"],
+      "correct_index": 0
+    }}
+  ]
+}}
+
+Each question may be "multiple_choice" (4 options, correct_index 0-3), "true_false" (boolean "answer"), "fill_blank" (question with one ____ and a short string "answer") or "short_answer" (string "answer" with a model answer).
+
+Respond with ONLY the JSON object, no other text."#,
+        request.section_title,
+        request.attempt,
+        missed,
+        difficulty,
+        request.content,
+        request.missed.len()
+    );
+    let prompt = append_instruction(prompt, request.language.as_deref());
+
+    let text = match ask_once(client, settings, usage_tx, None, prompt).await {
+        Ok(text) => text,
+        Err(e) => return QuizGenerationResult::Error(e),
+    };
+    match parse_quiz_json(&text, request.missed.len()) {
+        Ok(questions) => QuizGenerationResult::Replacements(questions),
+        Err(e) => QuizGenerationResult::Error(format!("Failed to parse quiz: {}", e)),
+    }
+}
+
+/// Generate a whole quiz in one request, for questions asked ahead of time
+async fn generate_quiz(
+    client: crate::claude::ClaudeClient,
+    settings: RequestSettings,
+    usage_tx: tokio::sync::mpsc::UnboundedSender<UsageReport>,
+    prompt: String,
+    count: usize,
+) -> Result<Vec<crate::app::state::QuizQuestion>, String> {
+    let text = ask_once(client, settings, usage_tx, None, prompt).await?;
+    parse_quiz_json(&text, count).map_err(|e| format!("Failed to parse quiz: {}", e))
+}
+
+/// Instruction not to repeat the questions the reader wrote for the quiz
+fn own_questions_guidance(own: &[crate::app::state::QuizQuestion]) -> Option<String> {
+    if own.is_empty() {
+        return None;
+    }
+    let listed: Vec<String> = own.iter().map(|q| format!("- {}", q.question())).collect();
+    Some(format!(
+        "The quiz already includes these questions written by the reader; don't repeat them:\n{}",
+        listed.join("\n")
+    ))
+}
+
+/// Prompt asking Claude for `count` quiz questions on a section
+fn quiz_prompt(section_title: &str, content: &str, count: usize) -> String {
+    format!(
+        r#"Based on this educational content about "{}", generate exactly {} quiz questions to test comprehension.
 
 Content:
 {}
@@ -1037,1242 +2777,5147 @@ Generate your response as a JSON object with this exact structure:
 {{
   "questions": [
     {{
+      "type": "multiple_choice",
       "question": "The question text",
       "options": ["Option A", "Option B", "Option C", "Option D"],
       "correct_index": 0
+    }},
+    {{
+      "type": "true_false",
+      "question": "A statement that is either true or false",
+      "answer": true
+    }},
+    {{
+      "type": "fill_blank",
+      "question": "A sentence with a ____ to fill in",
+      "answer": "word"
+    }},
+    {{
+      "type": "short_answer",
+      "question": "A question answered in a sentence or two",
+      "answer": "A model answer"
     }}
   ]
 }}
 
 Requirements:
-- Exactly 5 questions
-- Exactly 4 options per question
-- correct_index is 0-3 indicating which option is correct
+- Exactly {} questions, mixing the four question types
+- multiple_choice questions have exactly 4 options and correct_index 0-3
+- fill_blank questions contain exactly one ____ and the answer is a single word or short phrase
 - Questions should test understanding, not just memorization
 - Make questions challenging but fair based on the content provided
 
 Respond with ONLY the JSON object, no other text."#,
-        section_title, content
-    );
+        section_title, count, content, count
+    )
+}
 
-    let messages = vec![Message::user(prompt)];
-    let request =
-        CreateMessageRequest::new(model, messages).with_max_tokens(2000).without_streaming();
+/// Generate quiz questions with the streaming API, sending each question as soon
+/// as its JSON object is complete
+async fn stream_quiz_questions(
+    client: crate::claude::ClaudeClient,
+    settings: RequestSettings,
+    usage_tx: tokio::sync::mpsc::UnboundedSender<UsageReport>,
+    prompt: String,
+    count: usize,
+    tx: tokio::sync::mpsc::Sender<QuizGenerationResult>,
+) {
+    use crate::claude::{Message, StreamEvent};
+
+    let request = settings.request(vec![Message::user(prompt)]);
+
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(100);
+    let cancel_token = tokio_util::sync::CancellationToken::new();
+    let stream_cancel = cancel_token.clone();
+    let stream =
+        tokio::spawn(async move { client.send_streaming(request, event_tx, stream_cancel).await });
+
+    let mut text = String::new();
+    let mut input_tokens = 0;
+    // Question objects read so far, and how many of them were valid questions
+    let mut seen = 0;
+    let mut sent = 0;
+
+    while let Some(event) = event_rx.recv().await {
+        match event {
+            StreamEvent::MessageStart { input_tokens: tokens, .. } => input_tokens = tokens,
+            StreamEvent::ContentBlockDelta { text: delta } => {
+                text.push_str(&delta);
+                let objects = complete_question_objects(&text);
+                for object in &objects[seen..] {
+                    seen += 1;
+                    if sent == count {
+                        break;
+                    }
+                    let question = serde_json::from_str::<QuestionJson>(object)
+                        .map_err(anyhow::Error::from)
+                        .and_then(question_from_json);
+                    match question {
+                        Ok(question) => {
+                            if tx.send(QuizGenerationResult::Question(question)).await.is_err() {
+                                // Quiz was closed
+                                cancel_token.cancel();
+                                return;
+                            }
+                            sent += 1;
+                        }
+                        Err(e) => tracing::warn!("Skipping quiz question: {}", e),
+                    }
+                }
+            }
+            StreamEvent::MessageDelta { output_tokens, .. } => {
+                let _ = usage_tx.send((settings.model.clone(), input_tokens, output_tokens));
+            }
+            StreamEvent::Error { message } => {
+                let _ =
+                    tx.send(QuizGenerationResult::Error(format!("API error: {}", message))).await;
+                return;
+            }
+            _ => {}
+        }
+    }
 
-    match client.send_message(request).await {
-        Ok(response) => {
-            // Extract text from response content blocks
-            let text = response
-                .content
-                .iter()
-                .filter_map(|block| block.text.as_deref())
-                .collect::<Vec<_>>()
-                .join("");
+    let result = match stream.await {
+        Ok(Err(e)) => QuizGenerationResult::Error(format!("API error: {}", e)),
+        _ if sent == 0 => QuizGenerationResult::Error(format!(
+            "Failed to parse quiz: no questions in response: {}",
+            text
+        )),
+        _ => QuizGenerationResult::Generated,
+    };
+    let _ = tx.send(result).await;
+}
 
-            // Parse JSON response
-            match parse_quiz_json(&text) {
-                Ok(questions) => QuizGenerationResult::Success(questions),
-                Err(e) => QuizGenerationResult::Error(format!("Failed to parse quiz: {}", e)),
+/// Complete question objects in a partial `{"questions": [...]}` response
+///
+/// Objects are returned as soon as their closing brace arrives, so questions can
+/// be shown while later ones are still streaming.
+fn complete_question_objects(text: &str) -> Vec<&str> {
+    let Some(key) = text.find("\"questions\"") else { return Vec::new() };
+    let Some(open) = text[key..].find('[') else { return Vec::new() };
+    let array_start = key + open + 1;
+
+    let mut objects = Vec::new();
+    let mut depth = 0usize;
+    let mut object_start = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text[array_start..].char_indices() {
+        let i = array_start + i;
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => {
+                if depth == 0 {
+                    object_start = i;
+                }
+                depth += 1;
+            }
+            '}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    objects.push(&text[object_start..=i]);
+                }
             }
+            ']' if depth == 0 => break,
+            _ => {}
         }
-        Err(e) => QuizGenerationResult::Error(format!("API error: {}", e)),
     }
+    objects
 }
 
-/// Parse quiz questions from Claude's JSON response
-fn parse_quiz_json(text: &str) -> Result<Vec<crate::app::state::QuizQuestion>> {
-    use crate::app::state::QuizQuestion;
-
-    // Try to extract JSON from the response (Claude might add markdown code blocks)
-    let json_str = if text.contains("```json") {
+/// Extract JSON from a Claude response (Claude might add markdown code blocks)
+fn extract_json(text: &str) -> &str {
+    if text.contains("```json") {
         text.split("```json").nth(1).and_then(|s| s.split("```").next()).unwrap_or(text).trim()
     } else if text.contains("```") {
         text.split("```").nth(1).and_then(|s| s.split("```").next()).unwrap_or(text).trim()
     } else {
         text.trim()
+    }
+}
+
+/// A quiz question as Claude writes it in JSON
+#[derive(serde::Deserialize)]
+struct QuestionJson {
+    /// Question type; multiple choice when omitted
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+    question: String,
+    #[serde(default)]
+    options: Vec<String>,
+    #[serde(default)]
+    correct_index: usize,
+    #[serde(default)]
+    answer: serde_json::Value,
+}
+
+/// Convert a JSON question to a quiz question, checking the fields its type needs
+fn question_from_json(q: QuestionJson) -> Result<crate::app::state::QuizQuestion> {
+    use crate::app::state::QuizQuestion;
+
+    let answer_text = || match &q.answer {
+        serde_json::Value::String(s) if !s.trim().is_empty() => Ok(s.clone()),
+        _ => Err(anyhow::anyhow!("Missing answer for question: {}", q.question)),
     };
+    Ok(match q.kind.as_deref().unwrap_or("multiple_choice") {
+        "multiple_choice" => {
+            if q.correct_index >= q.options.len() {
+                return Err(anyhow::anyhow!(
+                    "correct_index {} out of range for question: {}",
+                    q.correct_index,
+                    q.question
+                ));
+            }
+            QuizQuestion::MultipleChoice {
+                question: q.question,
+                options: q.options,
+                correct_index: q.correct_index,
+            }
+        }
+        "true_false" => {
+            let answer = match &q.answer {
+                serde_json::Value::Bool(b) => *b,
+                serde_json::Value::String(s) => s.eq_ignore_ascii_case("true"),
+                _ => {
+                    return Err(anyhow::anyhow!("Missing answer for question: {}", q.question));
+                }
+            };
+            QuizQuestion::TrueFalse { question: q.question, answer }
+        }
+        "fill_blank" => {
+            let answer = answer_text()?;
+            QuizQuestion::FillInBlank { question: q.question, answer }
+        }
+        "short_answer" => {
+            let reference_answer = answer_text()?;
+            QuizQuestion::ShortAnswer { question: q.question, reference_answer }
+        }
+        other => return Err(anyhow::anyhow!("Unknown question type: {}", other)),
+    })
+}
+
+/// Parse the expected number of quiz questions from Claude's JSON response
+fn parse_quiz_json(text: &str, expected: usize) -> Result<Vec<crate::app::state::QuizQuestion>> {
+    let json_str = extract_json(text);
 
     #[derive(serde::Deserialize)]
     struct QuizResponse {
         questions: Vec<QuestionJson>,
     }
 
-    #[derive(serde::Deserialize)]
-    struct QuestionJson {
-        question: String,
-        options: Vec<String>,
-        correct_index: usize,
-    }
-
     let response: QuizResponse = serde_json::from_str(json_str)
         .map_err(|e| anyhow::anyhow!("JSON parse error: {} in text: {}", e, json_str))?;
 
-    if response.questions.len() != 5 {
-        return Err(anyhow::anyhow!("Expected 5 questions, got {}", response.questions.len()));
+    if response.questions.len() != expected {
+        return Err(anyhow::anyhow!(
+            "Expected {} questions, got {}",
+            expected,
+            response.questions.len()
+        ));
     }
 
-    let questions: Vec<QuizQuestion> = response
-        .questions
-        .into_iter()
-        .map(|q| QuizQuestion {
-            question: q.question,
-            options: q.options,
-            correct_index: q.correct_index,
-        })
-        .collect();
-
-    Ok(questions)
+    response.questions.into_iter().map(question_from_json).collect()
 }
 
-impl App {
-    /// Handle actions when quiz overlay is active
-    fn handle_quiz_action(&mut self, action: Action) -> Result<bool> {
-        match action {
-            Action::Quit => return Ok(true),
-
-            Action::Back => {
-                // Escape closes quiz
-                self.state.quiz.close();
-                self.state.command_line.clear_message();
-            }
+/// The status bar's progress through the plan step covering a section, else
+/// through any step of its book; None if no plan includes the book
+fn goal_segment(
+    plans: &[StudyPlan],
+    book: &Book,
+    section: Option<&crate::book::Section>,
+    progress: Option<&crate::config::progress::BookProgress>,
+) -> Option<String> {
+    let steps = plans.iter().flat_map(|plan| {
+        plan.steps.iter().filter(|step| step.book == book.metadata.id).map(move |step| (plan, step))
+    });
+    let covers = |step: &crate::learning::plan::PlanStep| {
+        section.is_some_and(|section| step.sections(book).iter().any(|s| s.path == section.path))
+    };
+    let (plan, step) =
+        steps.clone().find(|(_, step)| covers(step)).or_else(|| steps.clone().next())?;
+    let status = step.progress(book, progress);
+    Some(format!("{} {}/{}", plan.name, status.done, status.total))
+}
 
-            Action::Up => {
-                // In question mode, move to previous option
-                if !self.state.quiz.loading && !self.state.quiz.completed {
-                    self.state.quiz.select_prev();
+/// Progress through each step of a plan, with the book's title
+///
+/// Steps whose book isn't in the library (or fails to load) are None. The
+/// open book, if given, is used rather than loaded again.
+fn plan_progress(
+    plan: &StudyPlan,
+    library: &storage::Library,
+    progress: &crate::config::progress::Progress,
+    open: Option<&Book>,
+) -> Vec<Option<(String, StepProgress)>> {
+    plan.steps
+        .iter()
+        .map(|step| {
+            let loaded;
+            let book = match open {
+                Some(book) if book.metadata.id == step.book => book,
+                _ => {
+                    loaded = storage::load_book(library.find_by_id(&step.book)?).ok()?;
+                    &loaded
                 }
-            }
+            };
+            let status = step.progress(book, progress.books.get(&step.book));
+            Some((book.metadata.title.clone(), status))
+        })
+        .collect()
+}
 
-            Action::Down => {
-                // In question mode, move to next option
-                if !self.state.quiz.loading && !self.state.quiz.completed {
-                    self.state.quiz.select_next();
-                }
-            }
+/// The next section to read in the first unfinished study plan
+fn plans_next_up(
+    plans: &[StudyPlan],
+    progress: &crate::config::progress::Progress,
+) -> Option<String> {
+    let library = storage::Library::load().ok()?;
+    plans.iter().find_map(|plan| {
+        plan_progress(plan, &library, progress, None).into_iter().flatten().find_map(
+            |(title, status)| {
+                let (_, section) = status.next?;
+                Some(format!("{} › {} ({})", title, section, plan.name))
+            },
+        )
+    })
+}
 
-            Action::Select => {
-                if self.state.quiz.loading {
-                    // Ignore while loading
-                } else if self.state.quiz.error.is_some() {
-                    // Retry on error
-                    self.retry_quiz();
-                } else if self.state.quiz.completed {
-                    if self.state.quiz.passed() {
-                        // Passed - complete section and continue
-                        self.state.quiz.close();
-                        self.mark_section_complete();
-                        self.navigate_to_next_section();
-                    } else {
-                        // Failed - retry
-                        self.state.quiz.retry();
-                    }
+/// Load a library book by ID or title
+fn load_library_book(query: &str) -> Result<Book> {
+    storage::Library::load().and_then(|library| match library.resolve(query) {
+        BookMatch::Found(entry) | BookMatch::Likely(entry) => storage::load_book(entry),
+        BookMatch::Ambiguous(matches) => anyhow::bail!(
+            "\"{}\" matches several books: {}",
+            query,
+            matches.iter().map(|e| e.metadata.id.as_str()).collect::<Vec<_>>().join(", ")
+        ),
+        BookMatch::NotFound => anyhow::bail!("Book not found: {}", query),
+    })
+}
+
+/// Markdown report of how a book's new edition differs from an old one
+fn edition_report(
+    old: &Book,
+    new: &Book,
+    changes: &[crate::book::compare::SectionChange],
+    old_progress: Option<&crate::config::progress::BookProgress>,
+    notes: &NotesStore,
+    query: &str,
+) -> String {
+    use crate::book::compare::SectionChange;
+
+    let title = |book: &Book, path: &str| {
+        book.find_section_by_path(path).map_or_else(|| path.to_string(), |s| s.title.clone())
+    };
+    let (mut added, mut changed, mut removed, mut same) = (Vec::new(), Vec::new(), Vec::new(), 0);
+    let mut migratable = (0, 0);
+    for change in changes {
+        match change {
+            SectionChange::Matched { old_path, new_path, similarity } => {
+                if change.is_changed() {
+                    changed.push(format!(
+                        "- {} → {} ({:.0}% of words shared)",
+                        title(old, old_path),
+                        title(new, new_path),
+                        similarity * 100.0
+                    ));
                 } else {
-                    // Confirm current answer
-                    self.state.quiz.confirm_answer();
+                    same += 1;
                 }
+                migratable.0 +=
+                    usize::from(old_progress.is_some_and(|p| p.sections.contains_key(old_path)));
+                migratable.1 += notes.get_section_notes(&old.metadata.id, old_path).len();
+            }
+            SectionChange::Added { new_path } => added.push(format!("- {}", title(new, new_path))),
+            SectionChange::Removed { old_path } => {
+                removed.push(format!("- {}", title(old, old_path)))
             }
+        }
+    }
 
-            _ => {}
+    let mut out = format!(
+        "# {} → {}\n\n- **Unchanged**: {} sections\n- **Changed**: {}\n- **New**: {}\n- **Removed**: {}\n",
+        old.metadata.title,
+        new.metadata.title,
+        same,
+        changed.len(),
+        added.len(),
+        removed.len()
+    );
+    for (heading, lines) in
+        [("New sections", &added), ("Changed sections", &changed), ("Removed sections", &removed)]
+    {
+        if !lines.is_empty() {
+            out.push_str(&format!("\n## {}\n\n{}\n", heading, lines.join("\n")));
         }
-        Ok(false)
     }
+    if migratable != (0, 0) {
+        out.push_str(&format!(
+            "\nProgress on {} section{} and {} note{} can be copied to this edition with \
+             `:editions migrate {}`.\n",
+            migratable.0,
+            if migratable.0 == 1 { "" } else { "s" },
+            migratable.1,
+            if migratable.1 == 1 { "" } else { "s" },
+            query
+        ));
+    }
+    out
+}
 
-    /// Retry quiz generation after error
-    fn retry_quiz(&mut self) {
-        let Some(book) = &self.state.book else { return };
-        let Some(section) =
-            book.get_section(self.state.current_chapter, self.state.current_section)
-        else {
-            return;
-        };
+/// Ask a model one question without streaming, for its whole answer
+async fn ask_once(
+    client: crate::claude::ClaudeClient,
+    settings: RequestSettings,
+    usage_tx: tokio::sync::mpsc::UnboundedSender<UsageReport>,
+    system: Option<String>,
+    prompt: String,
+) -> Result<String, String> {
+    use crate::claude::Message;
+
+    let mut request = settings.request(vec![Message::user(prompt)]).without_streaming();
+    if let Some(system) = system {
+        request = request.with_system(system);
+    }
 
-        self.state.quiz.start_loading(&section.path);
-        self.state.command_line.set_message("Retrying quiz generation...");
+    match client.send_message(request).await {
+        Ok(response) => {
+            let _ = usage_tx.send((
+                settings.model,
+                response.usage.input_tokens,
+                response.usage.output_tokens,
+            ));
+            Ok(response
+                .content
+                .iter()
+                .filter_map(|block| block.text.as_deref())
+                .collect::<Vec<_>>()
+                .join(""))
+        }
+        Err(e) => Err(format!("API error: {}", e)),
     }
+}
 
-    /// Start creating a new note
-    fn start_creating_note(&mut self) {
-        // Only allow if we have a book loaded
-        let Some(book) = &self.state.book else {
-            self.state.command_line.set_error("No book loaded");
-            return;
-        };
+/// Ask Claude to grade short answers against their reference answers
+async fn grade_short_answers(
+    client: crate::claude::ClaudeClient,
+    settings: RequestSettings,
+    usage_tx: tokio::sync::mpsc::UnboundedSender<UsageReport>,
+    answers: Vec<(usize, String, String, String)>,
+) -> QuizGenerationResult {
+    let items = answers
+        .iter()
+        .map(|(index, question, reference, answer)| {
+            format!(
+                "Index: {}\nQuestion: {}\nReference answer: {}\nStudent answer: {}",
+                index, question, reference, answer
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
 
-        // Check if we have a valid section
-        if book.chapters.get(self.state.current_chapter).is_none() {
-            self.state.command_line.set_error("No section selected");
-            return;
-        }
+    let prompt = format!(
+        r#"Grade these short quiz answers. An answer is correct if it captures the key idea of the reference answer, even if worded differently.
 
-        self.state.notes.start_creating();
-        // Show and focus the notes panel
-        self.state.panel_visibility.notes = true;
-        self.state.focused_panel = Panel::Notes;
+{}
+
+Respond with ONLY a JSON object with this exact structure:
+{{
+  "grades": [
+    {{ "index": 0, "correct": true }}
+  ]
+}}"#,
+        items
+    );
+
+    let text = match ask_once(client, settings, usage_tx, None, prompt).await {
+        Ok(text) => text,
+        Err(e) => return QuizGenerationResult::Error(e),
+    };
+    match parse_grades_json(&text) {
+        Ok(grades) => QuizGenerationResult::Graded(grades),
+        Err(e) => QuizGenerationResult::Error(format!("Failed to parse grades: {}", e)),
     }
+}
 
-    /// Start editing the selected note
-    fn start_editing_note(&mut self) {
-        use crate::ui::notes_panel::get_selected_note;
+/// Parse short answer grades from Claude's JSON response
+fn parse_grades_json(text: &str) -> Result<Vec<(usize, bool)>> {
+    #[derive(serde::Deserialize)]
+    struct GradesResponse {
+        grades: Vec<GradeJson>,
+    }
 
-        let Some(note) = get_selected_note(&self.state, &self.notes_store) else {
-            self.state.command_line.set_error("No note selected");
-            return;
-        };
+    #[derive(serde::Deserialize)]
+    struct GradeJson {
+        index: usize,
+        correct: bool,
+    }
 
-        self.state.notes.start_editing(&note.id, &note.content);
-        self.state.panel_visibility.notes = true;
-        self.state.focused_panel = Panel::Notes;
+    let json_str = extract_json(text);
+    let response: GradesResponse = serde_json::from_str(json_str)
+        .map_err(|e| anyhow::anyhow!("JSON parse error: {} in text: {}", e, json_str))?;
+
+    Ok(response.grades.into_iter().map(|g| (g.index, g.correct)).collect())
+}
+
+/// A completed quiz answer to be explained in review
+struct ReviewedAnswer {
+    index: usize,
+    answer: MissedQuestion,
+    correct: bool,
+}
+
+/// Input for explaining the answers to a completed quiz
+struct ExplainRequest {
+    section_title: String,
+    content: String,
+    answers: Vec<ReviewedAnswer>,
+    /// Instruction for the language to write explanations in
+    language: Option<String>,
+}
+
+/// Ask Claude to explain why each answer is right or wrong
+async fn explain_quiz_answers(
+    client: crate::claude::ClaudeClient,
+    settings: RequestSettings,
+    usage_tx: tokio::sync::mpsc::UnboundedSender<UsageReport>,
+    request: ExplainRequest,
+) -> QuizGenerationResult {
+    let items = request
+        .answers
+        .iter()
+        .map(|a| {
+            format!(
+                "Index: {}\nQuestion: {}\nCorrect answer: {}\nLearner's answer: {} ({})",
+                a.index,
+                a.answer.question,
+                a.answer.correct_answer,
+                a.answer.given_answer,
+                if a.correct { "correct" } else { "incorrect" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let prompt = format!(
+        r#"A learner has finished a quiz on "{}". For each question, explain in 2-3 sentences why the correct answer is right, based on the content. If the learner's answer was incorrect, also explain the misunderstanding behind it.
+
+Content:
+{}
+
+Answers:
+{}
+
+Respond with ONLY a JSON object with this exact structure:
+{{
+  "explanations": [
+    {{ "index": 0, "explanation": "Why the answer is correct" }}
+  ]
+}}"#,
+        request.section_title, request.content, items
+    );
+    let prompt = append_instruction(prompt, request.language.as_deref());
+
+    let text = match ask_once(client, settings, usage_tx, None, prompt).await {
+        Ok(text) => text,
+        Err(e) => return QuizGenerationResult::Error(e),
+    };
+    match parse_explanations_json(&text) {
+        Ok(explanations) => QuizGenerationResult::Explained(explanations),
+        Err(e) => QuizGenerationResult::Error(format!("Failed to parse explanations: {}", e)),
     }
+}
 
-    /// Delete the selected note
-    fn delete_selected_note(&mut self) {
-        use crate::ui::notes_panel::get_selected_note;
+/// Parse answer explanations from Claude's JSON response
+fn parse_explanations_json(text: &str) -> Result<Vec<(usize, String)>> {
+    #[derive(serde::Deserialize)]
+    struct ExplanationsResponse {
+        explanations: Vec<ExplanationJson>,
+    }
 
-        let Some(note) = get_selected_note(&self.state, &self.notes_store) else {
-            self.state.command_line.set_error("No note selected");
-            return;
-        };
+    #[derive(serde::Deserialize)]
+    struct ExplanationJson {
+        index: usize,
+        explanation: String,
+    }
 
-        let note_id = note.id.clone();
-        if self.notes_store.delete_note(&note_id) {
-            if let Err(e) = self.notes_store.save() {
-                tracing::warn!("Failed to save notes: {}", e);
+    let json_str = extract_json(text);
+    let response: ExplanationsResponse = serde_json::from_str(json_str)
+        .map_err(|e| anyhow::anyhow!("JSON parse error: {} in text: {}", e, json_str))?;
+
+    Ok(response.explanations.into_iter().map(|e| (e.index, e.explanation)).collect())
+}
+
+impl App {
+    /// Handle actions when quiz overlay is active
+    fn handle_quiz_action(&mut self, action: Action) -> Result<bool> {
+        if self.state.quiz.editor.is_some() {
+            self.handle_question_editor_action(action);
+            return Ok(action == Action::Quit);
+        }
+        match action {
+            Action::Quit => return Ok(true),
+
+            Action::EditNote if self.state.quiz.can_review() => self.open_question_editor(),
+
+            Action::Back => {
+                if self.state.quiz.reviewing {
+                    // Escape leaves the review for the results summary
+                    self.state.quiz.end_review();
+                } else {
+                    // Escape closes quiz, abandoning any request in flight
+                    self.state.quiz.close();
+                    self.quiz_rx = None;
+                    self.state.command_line.clear_message();
+                }
             }
-            self.state.command_line.set_message("Note deleted");
-            // Reset selection if needed
-            let total = crate::ui::notes_panel::get_note_count(&self.state, &self.notes_store);
-            if self.state.notes.selected_index >= total && total > 0 {
-                self.state.notes.selected_index = total - 1;
+
+            Action::Up => {
+                // In question mode, move to previous option
+                if !self.state.quiz.loading && !self.state.quiz.completed {
+                    self.state.quiz.select_prev();
+                } else if self.state.quiz.reviewing {
+                    self.state.quiz.review_prev();
+                } else if self.state.quiz.can_review() {
+                    self.review_quiz_answers();
+                }
+            }
+
+            Action::Down => {
+                // In question mode, move to next option
+                if !self.state.quiz.loading && !self.state.quiz.completed {
+                    self.state.quiz.select_next();
+                } else if self.state.quiz.reviewing {
+                    self.state.quiz.review_next();
+                } else if self.state.quiz.can_review() {
+                    self.review_quiz_answers();
+                }
+            }
+
+            Action::Select => {
+                if self.state.quiz.loading || self.state.quiz.grading {
+                    // Ignore while loading or grading
+                } else if self.state.quiz.error.is_some() {
+                    // Retry on error
+                    self.retry_quiz();
+                } else if self.state.quiz.completed {
+                    if self.state.quiz.passed() {
+                        // Passed - complete section and continue
+                        self.state.quiz.close();
+                        self.mark_section_complete();
+                        self.navigate_to_next_section();
+                    } else {
+                        // Failed - retry with follow-ups on the missed questions
+                        self.regenerate_missed_questions();
+                    }
+                } else {
+                    // Confirm current answer
+                    self.confirm_quiz_answer();
+                }
             }
+
+            _ => {}
         }
+        Ok(false)
     }
 
-    /// Save the current note being created or edited
-    fn save_note(&mut self) {
-        use crate::notes::Note;
+    /// Open the question editor on the quiz's questions and the reader's
+    /// others for the section
+    fn open_question_editor(&mut self) {
+        use crate::app::state::{BankedQuestion, QuestionEditor};
 
-        let content = self.state.notes.input.clone();
-        if content.trim().is_empty() {
-            self.state.notes.cancel_edit();
-            return;
+        let quiz = &self.state.quiz;
+        let Some(section_path) = quiz.section_path.clone() else { return };
+        let own = self.question_bank.custom(&section_path);
+        let mut questions: Vec<BankedQuestion> = quiz
+            .questions
+            .iter()
+            .map(|q| BankedQuestion { question: q.clone(), custom: own.contains(q) })
+            .collect();
+        let others = own.iter().filter(|q| !quiz.questions.contains(q));
+        questions.extend(others.map(|q| BankedQuestion { question: q.clone(), custom: true }));
+        let selected = if quiz.reviewing { quiz.review_index } else { 0 };
+        self.state.quiz.editor =
+            Some(QuestionEditor { section_path, questions, selected, ..Default::default() });
+        self.state.command_line.clear_message();
+    }
+
+    /// Handle actions while choosing a question in the question editor
+    fn handle_question_editor_action(&mut self, action: Action) {
+        use crate::app::state::QuestionForm;
+        use crate::learning::bank::question_to_text;
+
+        let Some(editor) = self.state.quiz.editor.as_mut() else { return };
+        match action {
+            Action::Back => self.state.quiz.editor = None,
+            Action::Up => editor.select_prev(),
+            Action::Down => editor.select_next(),
+            Action::Select | Action::EditNote => {
+                if let Some(banked) = editor.selected() {
+                    let text = question_to_text(&banked.question);
+                    editor.form = Some(QuestionForm::new(Some(editor.selected), &text));
+                }
+            }
+            Action::CreateNote => editor.form = Some(QuestionForm::new(None, "")),
+            Action::DeleteNote => self.delete_banked_question(),
+            _ => {}
         }
+    }
 
-        if self.state.notes.creating {
-            // Create new note
-            let Some(book) = &self.state.book else {
-                self.state.notes.cancel_edit();
-                return;
-            };
-            let Some(chapter) = book.chapters.get(self.state.current_chapter) else {
-                self.state.notes.cancel_edit();
-                return;
-            };
-            let Some(section) = chapter.sections.get(self.state.current_section) else {
-                self.state.notes.cancel_edit();
-                return;
-            };
+    /// Whether keys should be typed into a question in the question editor
+    fn is_question_form_active(&self) -> bool {
+        self.state.quiz.editor.as_ref().is_some_and(|editor| editor.form.is_some())
+    }
 
-            let note = Note::new_section_note(&book.metadata.id, &section.path, &content);
-            self.notes_store.add_note(note);
+    /// Handle keyboard input while writing a question
+    fn handle_question_form_input(&mut self, key: crossterm::event::KeyEvent) {
+        let Some(editor) = self.state.quiz.editor.as_mut() else { return };
+        let Some(form) = editor.form.as_mut() else { return };
+        match (key_with_modifier_to_action(key.code, key.modifiers), key.code) {
+            (Some(Action::Submit), _) => self.save_banked_question(),
+            (Some(Action::Back), _) => {
+                editor.form = None;
+                editor.error = None;
+            }
+            (_, KeyCode::Enter) => form.new_line(),
+            (_, KeyCode::Backspace) => form.delete_char(),
+            (_, KeyCode::Left) => form.move_left(),
+            (_, KeyCode::Right) => form.move_right(),
+            (_, KeyCode::Up) => form.move_up(),
+            (_, KeyCode::Down) => form.move_down(),
+            (_, KeyCode::Char(c)) => form.insert_char(c),
+            _ => {}
+        }
+    }
 
-            if let Err(e) = self.notes_store.save() {
-                tracing::warn!("Failed to save notes: {}", e);
+    /// Save the question being written to the book's question bank
+    fn save_banked_question(&mut self) {
+        use crate::app::state::BankedQuestion;
+        use crate::learning::bank::parse_question;
+
+        let Some(book_id) = self.state.book.as_ref().map(|b| b.metadata.id.clone()) else { return };
+        let Some(editor) = self.state.quiz.editor.as_mut() else { return };
+        let Some(form) = &editor.form else { return };
+        if let Some(e) = &self.question_bank_error {
+            editor.error = Some(format!("Question bank failed to load, so it isn't saved: {}", e));
+            return;
+        }
+        let question = match parse_question(&form.text()) {
+            Ok(question) => question,
+            Err(e) => {
+                editor.error = Some(e);
+                return;
             }
-            self.state.command_line.set_message("Note created");
-        } else if let Some(note_id) = &self.state.notes.editing.clone() {
-            // Update existing note
-            if self.notes_store.update_note(note_id, &content) {
-                if let Err(e) = self.notes_store.save() {
-                    tracing::warn!("Failed to save notes: {}", e);
-                }
-                self.state.command_line.set_message("Note updated");
+        };
+
+        let index = form.index;
+        let original = index.and_then(|i| editor.questions.get(i)).map(|b| &b.question);
+        self.question_bank.save_question(&editor.section_path, original, question.clone());
+        if let Err(e) = self.question_bank.save(&book_id) {
+            editor.error = Some(format!("Failed to save question: {}", e));
+            return;
+        }
+        let banked = BankedQuestion { question, custom: true };
+        match index {
+            Some(i) => editor.questions[i] = banked,
+            None => {
+                editor.questions.push(banked);
+                editor.selected = editor.questions.len() - 1;
             }
         }
+        editor.form = None;
+        editor.error = None;
+        self.state
+            .command_line
+            .set_message("Question saved; it will be asked in this section's quizzes");
+    }
 
-        self.state.notes.cancel_edit();
+    /// Delete the selected question from the section's quizzes
+    fn delete_banked_question(&mut self) {
+        let Some(book_id) = self.state.book.as_ref().map(|b| b.metadata.id.clone()) else { return };
+        let Some(editor) = self.state.quiz.editor.as_mut() else { return };
+        if editor.selected >= editor.questions.len() {
+            return;
+        }
+        if let Some(e) = &self.question_bank_error {
+            self.state
+                .command_line
+                .set_error(format!("Question bank failed to load, so it isn't saved: {}", e));
+            return;
+        }
+        let banked = editor.questions.remove(editor.selected);
+        editor.selected = editor.selected.min(editor.questions.len().saturating_sub(1));
+        self.question_bank.delete_question(&editor.section_path, &banked.question);
+        if let Err(e) = self.question_bank.save(&book_id) {
+            self.state.command_line.set_error(format!("Failed to save question bank: {}", e));
+            return;
+        }
+        self.state.command_line.set_message("Question deleted");
     }
 
-    /// Handle input while editing a note
-    fn handle_notes_input(&mut self, key: KeyCode) {
+    /// Whether a quiz question is waiting for an answer
+    fn is_quiz_answering(&self) -> bool {
+        self.state.quiz.is_answering()
+    }
+
+    /// Whether keys should be typed into the quiz answer box
+    fn is_quiz_text_input_active(&self) -> bool {
+        self.is_quiz_answering() && self.state.quiz.is_text_question()
+    }
+
+    /// Handle a quiz shortcut: an option's key picks it, and the next and
+    /// previous keys move between questions; returns whether the key was used
+    fn handle_quiz_key(&mut self, key: KeyCode, action: Option<Action>) -> bool {
+        let quiz = &mut self.state.quiz;
+        if let Some(option) = input::quiz_option(key) {
+            return quiz.select_option(option);
+        }
+        match action {
+            Some(Action::NextMatch) => quiz.next_question(),
+            Some(Action::Previous) => quiz.prev_question(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Handle keyboard input while typing a quiz answer
+    fn handle_quiz_text_input(&mut self, key: KeyCode) {
         match key {
             KeyCode::Esc => {
-                self.state.notes.cancel_edit();
+                self.state.quiz.close();
+                self.quiz_rx = None;
+                self.state.command_line.clear_message();
             }
             KeyCode::Enter => {
-                self.save_note();
+                self.confirm_quiz_answer();
+            }
+            KeyCode::Up => {
+                self.state.quiz.prev_question();
+            }
+            KeyCode::Down => {
+                self.state.quiz.next_question();
             }
             KeyCode::Backspace => {
-                self.state.notes.delete_char();
+                self.state.quiz.delete_char();
             }
             KeyCode::Left => {
-                self.state.notes.move_left();
+                self.state.quiz.move_left();
             }
             KeyCode::Right => {
-                self.state.notes.move_right();
+                self.state.quiz.move_right();
             }
             KeyCode::Char(c) => {
-                self.state.notes.insert_char(c);
+                self.state.quiz.insert_char(c);
             }
             _ => {}
         }
     }
 
-    /// Move panel focus left
-    fn move_panel_focus_left(&mut self) {
-        match self.state.focused_panel {
-            Panel::Content => {
-                if self.state.panel_visibility.curriculum {
-                    self.state.focused_panel = Panel::Curriculum;
-                }
-            }
-            Panel::Notes => {
-                self.state.focused_panel = Panel::Content;
-            }
-            Panel::Curriculum => {}
+    /// Confirm the current quiz answer, grading short answers once the quiz is finished
+    fn confirm_quiz_answer(&mut self) {
+        self.state.quiz.confirm_answer();
+        if self.state.quiz.completed {
+            self.start_quiz_grading();
         }
     }
 
-    /// Move panel focus right
-    fn move_panel_focus_right(&mut self) {
-        match self.state.focused_panel {
-            Panel::Curriculum => {
-                self.state.focused_panel = Panel::Content;
-            }
-            Panel::Content => {
-                if self.state.panel_visibility.notes {
-                    self.state.focused_panel = Panel::Notes;
-                }
+    /// Send answered short answer questions to Claude for grading
+    fn start_quiz_grading(&mut self) {
+        use crate::app::state::{QuizAnswer, QuizQuestion};
+
+        let pending: Vec<(usize, String, String, String)> = self
+            .state
+            .quiz
+            .ungraded()
+            .into_iter()
+            .filter_map(|i| {
+                let QuizQuestion::ShortAnswer { question, reference_answer } =
+                    &self.state.quiz.questions[i]
+                else {
+                    return None;
+                };
+                let Some(QuizAnswer::Text(answer)) = &self.state.quiz.answers[i] else {
+                    return None;
+                };
+                Some((i, question.clone(), reference_answer.clone(), answer.clone()))
+            })
+            .collect();
+
+        if pending.is_empty() {
+            self.record_quiz_result();
+            return;
+        }
+
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => {
+                // Leave the quiz results up; ungraded answers count as incorrect
+                self.state.quiz.fail_ungraded();
+                self.record_quiz_result();
+                self.state
+                    .command_line
+                    .set_error(format!("Can't grade short answers: Failed to get API key: {}", e));
+                return;
             }
-            Panel::Notes => {}
+        };
+
+        self.state.quiz.grading = true;
+        self.state.command_line.set_message("Grading short answers...");
+
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.quiz_rx = Some(rx);
+
+        let client = self.claude_client(api_key);
+        let settings = self.request_settings(self.claude_model(), 500);
+        let usage_tx = self.usage_tx.clone();
+
+        tokio::spawn(async move {
+            let result = grade_short_answers(client, settings, usage_tx, pending).await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Rate the quiz's newly graded answers, and record a graded first
+    /// attempt as a review of its section
+    ///
+    /// Retries answer follow-up questions straight after a miss, so they say
+    /// nothing about how well the section was remembered and aren't reviews.
+    fn record_quiz_result(&mut self) {
+        use crate::learning::schedule::{Grade, Review};
+
+        let answers = self.state.quiz.take_unrated();
+        let quiz = &self.state.quiz;
+        let (Some(book), Some(path)) = (&self.state.book, &quiz.section_path) else { return };
+        let book_progress = self.progress.book_mut(&book.metadata.id);
+        for (question, correct) in answers {
+            book_progress.quiz_ratings.record(path, &question, correct);
         }
+        if quiz.attempt == 1 {
+            let (correct, total) = quiz.score();
+            let section = book_progress.sections.entry(path.clone()).or_default();
+            section.quiz_score = Some((correct * 100 / total.max(1)) as u8);
+            section
+                .reviews
+                .push(Review { at: datetime::now(), grade: Grade::from_score(correct, total) });
+        }
+        let attempt = quiz.attempt_record(datetime::now());
+        book_progress.sections.entry(path.clone()).or_default().quiz_history.push(attempt);
+        self.save_progress();
     }
 
-    /// Handle mouse events for text selection
-    fn handle_mouse_event(&mut self, mouse_event: crossterm::event::MouseEvent) {
-        // Only handle mouse events on the main screen in content panel
-        if !matches!(self.state.screen, Screen::Main) {
+    /// Review each answer, asking Claude to explain them the first time
+    fn review_quiz_answers(&mut self) {
+        self.state.quiz.start_review();
+        if !self.state.quiz.explanations.is_empty() || self.state.quiz.explaining {
             return;
         }
 
-        // Use the stored content area from rendering (much more accurate than calculating)
-        let (content_x, content_y, content_w, content_h) = self.state.content.content_area;
+        let Some(book) = &self.state.book else { return };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            return;
+        };
 
-        // If content area hasn't been set yet, skip
-        if content_w == 0 || content_h == 0 {
+        // Without Claude, the review still shows answers, just without explanations
+        let Ok(api_key) = crate::claude::ApiKeyManager::get_api_key() else { return };
+
+        let quiz = &self.state.quiz;
+        let answers: Vec<ReviewedAnswer> = (0..quiz.questions.len())
+            .map(|i| {
+                let question = &quiz.questions[i];
+                ReviewedAnswer {
+                    index: i,
+                    answer: MissedQuestion {
+                        question: question.question().to_string(),
+                        correct_answer: question.answer_text(),
+                        given_answer: quiz.answers[i]
+                            .as_ref()
+                            .map(|a| question.describe_answer(a))
+                            .unwrap_or_default(),
+                    },
+                    correct: quiz.grades[i] == Some(true),
+                }
+            })
+            .collect();
+
+        let section_title = section.title.clone();
+        let content = quiz_content(section, self.config.claude.context_chars);
+
+        self.state.quiz.explanations = vec![None; answers.len()];
+        self.state.quiz.explaining = true;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.quiz_rx = Some(rx);
+
+        let client = self.claude_client(api_key);
+        let settings =
+            self.request_settings(self.claude_model(), self.config.claude.quiz_max_tokens);
+        let usage_tx = self.usage_tx.clone();
+        let language = self.config.claude.language_instruction();
+
+        tokio::spawn(async move {
+            let request = ExplainRequest { section_title, content, answers, language };
+            let result = explain_quiz_answers(client, settings, usage_tx, request).await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Retry quiz generation after error
+    fn retry_quiz(&mut self) {
+        let Some(book) = &self.state.book else { return };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
             return;
-        }
+        };
 
-        let col = mouse_event.column;
-        let row = mouse_event.row;
+        self.state.quiz.start_loading(&section.path);
+        self.state.command_line.set_message("Retrying quiz generation...");
+    }
 
-        // Check if mouse is within content panel bounds
-        if col < content_x
-            || col >= content_x + content_w
-            || row < content_y
-            || row >= content_y + content_h
-        {
+    /// Start creating a new note
+    fn start_creating_note(&mut self) {
+        // Only allow if we have a book loaded
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+
+        // Check if we have a valid section
+        if book.chapters.get(self.state.current_chapter).is_none() {
+            self.state.command_line.set_error("No section selected");
             return;
         }
 
-        // Calculate position relative to content area
-        let rel_col = col - content_x;
-        let rel_row = row - content_y;
+        self.state.notes.start_creating();
+        // Show and focus the notes panel
+        self.state.panel_visibility.notes = true;
+        self.state.focused_panel = Panel::Notes;
+    }
 
-        match mouse_event.kind {
-            MouseEventKind::Down(MouseButton::Left) => {
-                // Start selection - convert screen position to block/char
-                if let Some((block, char_pos)) = self.screen_to_text_position(rel_col, rel_row) {
-                    // Enter cursor mode and start visual selection
-                    self.state.content.cursor_block = block;
-                    self.state.content.cursor_char = char_pos;
-                    self.state.content.cursor_mode = true;
+    /// Start editing the selected note
+    fn start_editing_note(&mut self) {
+        use crate::ui::notes_panel::get_selected_note;
 
-                    // Start visual mode with anchor at this position
-                    self.state.visual_mode.active = true;
-                    self.state.visual_mode.anchor_block = block;
-                    self.state.visual_mode.anchor_char = char_pos;
+        let Some(note) = get_selected_note(&self.state, &self.notes_store) else {
+            self.state.command_line.set_error("No note selected");
+            return;
+        };
 
-                    // Track mouse selection start
-                    self.mouse_selection = Some((block, char_pos));
+        self.state.notes.start_editing(&note.id, &note.content);
+        self.state.panel_visibility.notes = true;
+        self.state.focused_panel = Panel::Notes;
+    }
 
-                    self.state.focused_panel = Panel::Content;
-                }
+    /// Delete the selected note
+    fn delete_selected_note(&mut self) {
+        use crate::ui::notes_panel::get_selected_note;
+
+        let Some(note) = get_selected_note(&self.state, &self.notes_store) else {
+            self.state.command_line.set_error("No note selected");
+            return;
+        };
+
+        let note_id = note.id.clone();
+        if self.notes_store.delete_note(&note_id) {
+            if let Err(e) = self.notes_store.save() {
+                tracing::warn!("Failed to save notes: {}", e);
             }
-            MouseEventKind::Drag(MouseButton::Left) => {
-                // Continue selection if we started one
-                if self.mouse_selection.is_some() {
-                    if let Some((block, char_pos)) = self.screen_to_text_position(rel_col, rel_row)
-                    {
-                        // Update cursor position (selection end)
-                        self.state.content.cursor_block = block;
-                        self.state.content.cursor_char = char_pos;
-                    }
-                }
+            self.state.command_line.set_message("Note deleted");
+            // Reset selection if needed
+            let total = crate::ui::notes_panel::get_note_count(&self.state, &self.notes_store);
+            if self.state.notes.selected_index >= total && total > 0 {
+                self.state.notes.selected_index = total - 1;
             }
-            MouseEventKind::Up(MouseButton::Left) => {
-                // End selection
-                if self.mouse_selection.is_some() {
-                    if let Some((block, char_pos)) = self.screen_to_text_position(rel_col, rel_row)
-                    {
-                        self.state.content.cursor_block = block;
-                        self.state.content.cursor_char = char_pos;
-                    }
+        }
+    }
 
-                    // Check if it was just a click (no drag) - clear selection
-                    if let Some((start_block, start_char)) = self.mouse_selection {
-                        if start_block == self.state.content.cursor_block
-                            && start_char == self.state.content.cursor_char
-                        {
-                            // Just a click, exit visual mode but keep cursor
-                            self.state.visual_mode.exit();
-                        }
-                    }
+    /// Show or change a setting: `:set option` or `:set option=value`
+    fn set_option(&mut self, option: &str, value: Option<&str>) {
+        match option {
+            "textwidth" | "tw" => {
+                let Some(value) = value else {
+                    let current = self
+                        .config
+                        .text_width
+                        .map_or_else(|| "0 (fill panel)".to_string(), |w| w.to_string());
+                    self.state.command_line.set_message(format!("textwidth={}", current));
+                    return;
+                };
+                let Ok(width) = value.parse::<usize>() else {
+                    self.state.command_line.set_error(format!("Invalid textwidth: {}", value));
+                    return;
+                };
+                if width != 0 && width < MIN_TEXT_WIDTH {
+                    self.state
+                        .command_line
+                        .set_error(format!("textwidth must be 0 or at least {}", MIN_TEXT_WIDTH));
+                    return;
+                }
 
-                    self.mouse_selection = None;
+                // 0 turns the limit off, as in vim
+                self.config.text_width = (width > 0).then_some(width);
+                self.apply_book_settings();
+                if let Err(e) = self.config.save() {
+                    self.state.command_line.set_error(format!("Failed to save config: {}", e));
+                    return;
                 }
+                self.state.command_line.set_message(format!("textwidth={}", width));
             }
-            MouseEventKind::ScrollUp => {
-                // Scroll content up
-                self.state.content.scroll_offset =
-                    self.state.content.scroll_offset.saturating_sub(3);
-            }
-            MouseEventKind::ScrollDown => {
-                // Scroll content down
-                self.state.content.scroll_offset += 3;
-                self.state.content.clamp_scroll();
-            }
-            _ => {}
+            "language" | "lang" => {
+                let Some(value) = value else {
+                    let current = self.config.claude.language.as_deref().unwrap_or("off");
+                    self.state.command_line.set_message(format!("language={}", current));
+                    return;
+                };
+
+                // Empty or "off" goes back to following the book's language
+                let language = value.trim();
+                self.config.claude.language = (!language.is_empty()
+                    && !language.eq_ignore_ascii_case("off"))
+                .then(|| language.to_string());
+                if let Err(e) = self.config.save() {
+                    self.state.command_line.set_error(format!("Failed to save config: {}", e));
+                    return;
+                }
+                let current = self.config.claude.language.as_deref().unwrap_or("off");
+                self.state.command_line.set_message(format!("language={}", current));
+            }
+            "encrypt" => self.set_encrypt_notes(value),
+            "justify" | "hyphenate" => self.set_typography(option, value),
+            "autosave" => self.set_autosave(value),
+            "maxtokens" | "quiztokens" | "context" | "chaptercontext" => {
+                self.set_claude_limit(option, value)
+            }
+            "temperature" | "temp" => self.set_temperature(value),
+            "uilang" | "uilanguage" => self.set_ui_language(value),
+            _ => self.state.command_line.set_error(format!("Unknown option: {}", option)),
         }
     }
 
-    /// Convert screen coordinates (relative to content panel) to block index and character position
-    fn screen_to_text_position(&self, col: u16, row: u16) -> Option<(usize, usize)> {
-        use crate::book::ContentBlock;
-
-        let book = self.state.book.as_ref()?;
-        let section = book.get_section(self.state.current_chapter, self.state.current_section)?;
+    /// Show or set the interface language (`:set uilang=eo`)
+    fn set_ui_language(&mut self, value: Option<&str>) {
+        use crate::ui::locale::{self, Language};
+
+        if let Some(value) = value {
+            let Some(language) = Language::from_code(value) else {
+                let codes: Vec<&str> = Language::ALL.iter().map(|l| l.code()).collect();
+                self.state.command_line.set_error(format!(
+                    "Unknown interface language: {} (one of {})",
+                    value,
+                    codes.join(", ")
+                ));
+                return;
+            };
+            locale::set_language(language);
+            self.config.ui_language = Some(language.code().to_string());
+            if let Err(e) = self.config.save() {
+                self.state.command_line.set_error(format!("Failed to save config: {}", e));
+                return;
+            }
+        }
+        self.state.command_line.set_message(format!("uilang={}", locale::language().code()));
+    }
 
-        // Calculate which line we're on (accounting for scroll)
-        let target_line = self.state.content.scroll_offset + row as usize;
+    /// Show or set the minutes between automatic session saves (0 turns them off)
+    fn set_autosave(&mut self, value: Option<&str>) {
+        if let Some(value) = value {
+            let Ok(minutes) = value.trim().parse::<u32>() else {
+                self.state.command_line.set_error(format!("Invalid autosave: {}", value));
+                return;
+            };
+            self.config.autosave_minutes = minutes;
+            if let Err(e) = self.config.save() {
+                self.state.command_line.set_error(format!("Failed to save config: {}", e));
+                return;
+            }
+        }
+        let message = match self.config.autosave_minutes {
+            0 => "autosave=0 (on section change and exit only)".to_string(),
+            minutes => format!("autosave={} (minutes)", minutes),
+        };
+        self.state.command_line.set_message(message);
+    }
 
-        // Find which block this line belongs to using block_line_offsets
-        let offsets = &self.state.content.block_line_offsets;
+    /// Show or set a limit on Claude requests: response tokens (`maxtokens`,
+    /// `quiztokens`) or characters of book text sent (`context`, `chaptercontext`)
+    fn set_claude_limit(&mut self, option: &str, value: Option<&str>) {
+        if let Some(value) = value {
+            let Some(limit) = value.trim().parse::<u32>().ok().filter(|&n| n > 0) else {
+                self.state
+                    .command_line
+                    .set_error(format!("Invalid {}: {} (a positive number)", option, value));
+                return;
+            };
+            let claude = &mut self.config.claude;
+            match option {
+                "maxtokens" => claude.max_tokens = limit,
+                "quiztokens" => claude.quiz_max_tokens = limit,
+                "context" => claude.context_chars = limit as usize,
+                _ => claude.chapter_context_chars = limit as usize,
+            }
+            if let Err(e) = self.config.save() {
+                self.state.command_line.set_error(format!("Failed to save config: {}", e));
+                return;
+            }
+        }
+        let claude = &self.config.claude;
+        let message = match option {
+            "maxtokens" => format!("maxtokens={}", claude.max_tokens),
+            "quiztokens" => format!("quiztokens={}", claude.quiz_max_tokens),
+            "context" => format!("context={} (characters)", claude.context_chars),
+            _ => format!("chaptercontext={} (characters)", claude.chapter_context_chars),
+        };
+        self.state.command_line.set_message(message);
+    }
 
-        let mut block_idx = 0;
-        for (idx, &offset) in offsets.iter().enumerate() {
-            if offset <= target_line {
-                block_idx = idx;
+    /// Show or set Claude's sampling temperature (`off` uses the API default)
+    fn set_temperature(&mut self, value: Option<&str>) {
+        if let Some(value) = value.map(str::trim) {
+            let temperature = if value.is_empty() || value.eq_ignore_ascii_case("off") {
+                None
             } else {
-                break;
+                match value.parse::<f32>() {
+                    Ok(t) if (0.0..=1.0).contains(&t) => Some(t),
+                    _ => {
+                        self.state
+                            .command_line
+                            .set_error(format!("Invalid temperature: {} (0-1 or off)", value));
+                        return;
+                    }
+                }
+            };
+            self.config.claude.temperature = temperature;
+            if let Err(e) = self.config.save() {
+                self.state.command_line.set_error(format!("Failed to save config: {}", e));
+                return;
             }
         }
+        let current =
+            self.config.claude.temperature.map_or_else(|| "off".to_string(), |t| t.to_string());
+        self.state.command_line.set_message(format!("temperature={}", current));
+    }
 
-        // Get the block and calculate character position
-        let block = section.content.get(block_idx)?;
-        let block_start_line = offsets.get(block_idx).copied().unwrap_or(0);
-        let line_within_block = target_line.saturating_sub(block_start_line);
-        let content_width = self.state.content.content_width;
-        let col = col as usize;
+    /// Show or set a typography switch (`justify` or `hyphenate`)
+    fn set_typography(&mut self, option: &str, value: Option<&str>) {
+        let typography = &mut self.config.typography;
+        let setting =
+            if option == "justify" { &mut typography.justify } else { &mut typography.hyphenate };
+        match value {
+            None => {}
+            Some("on" | "true" | "yes") => *setting = true,
+            Some("off" | "false" | "no") => *setting = false,
+            Some(value) => {
+                self.state
+                    .command_line
+                    .set_error(format!("Invalid {}: {} (on/off)", option, value));
+                return;
+            }
+        }
+        let current = if *setting { "on" } else { "off" };
+        let message = format!("{}={}", option, current);
 
-        let char_pos = match block {
-            ContentBlock::Paragraph(text) => {
-                // Paragraphs: 2 spaces padding, wrapped at content_width - 4
-                let padding = 2;
-                let wrap_width = content_width.saturating_sub(4).max(1);
-                let col_in_text = col.saturating_sub(padding);
+        if value.is_some() {
+            self.state.content.typography = self.config.typography;
+            if let Err(e) = self.config.save() {
+                self.state.command_line.set_error(format!("Failed to save config: {}", e));
+                return;
+            }
+        }
+        self.state.command_line.set_message(message);
+    }
 
-                // Calculate character position by simulating word wrap
-                self.calculate_wrapped_char_pos(text, wrap_width, line_within_block, col_in_text)
+    /// Show or set whether notes and summaries are encrypted at rest
+    ///
+    /// Existing files are rewritten straight away to match the new setting.
+    fn set_encrypt_notes(&mut self, value: Option<&str>) {
+        let enabled = match value {
+            None => {
+                let current = if self.config.encrypt_notes { "on" } else { "off" };
+                self.state.command_line.set_message(format!("encrypt={}", current));
+                return;
             }
-            ContentBlock::Heading { text, level } => {
-                // Headings: variable padding based on level, single line (usually)
-                let padding = match level {
-                    1..=3 => 2,
-                    4 => 4,
-                    _ => 6,
-                };
-                let col_in_text = col.saturating_sub(padding);
-                col_in_text.min(text.chars().count().saturating_sub(1))
+            Some("on" | "true" | "yes") => true,
+            Some("off" | "false" | "no") => false,
+            Some(value) => {
+                self.state.command_line.set_error(format!("Invalid encrypt: {} (on/off)", value));
+                return;
             }
-            ContentBlock::Code(code) => {
-                // Code blocks: "│  N│ " prefix (about 6 chars), then code
-                // First line is header "┌─ rust ─────", skip it
-                if line_within_block == 0 {
-                    return Some((block_idx, 0));
-                }
-
-                let code_lines: Vec<&str> = code.code.lines().collect();
-                let line_in_code = line_within_block.saturating_sub(1); // Account for header
-
-                if line_in_code >= code_lines.len() {
-                    // On closing line or beyond
-                    return Some((block_idx, code.code.chars().count().saturating_sub(1)));
-                }
+        };
 
-                // Calculate character position in the code
-                // Prefix is "│  N│ " where N is line number
-                let prefix_width = 6; // Approximate
-                let col_in_code = col.saturating_sub(prefix_width);
+        crate::config::encryption::set_enabled(enabled);
+        let rewritten = NotesStore::notes_path().and_then(|notes| {
+            let summaries = crate::claude::summary::SummaryCache::cache_dir()?;
+            // Only the caches themselves: backups are rewritten along with them
+            let summary_files = std::fs::read_dir(&summaries)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"));
+            for path in std::iter::once(notes).chain(summary_files) {
+                crate::config::encryption::rewrite(&path)?;
+            }
+            Ok(())
+        });
+        if let Err(e) = rewritten {
+            crate::config::encryption::set_enabled(self.config.encrypt_notes);
+            self.state.command_line.set_error(format!("Failed to change encryption: {}", e));
+            return;
+        }
 
-                // Sum characters from previous lines + column in current line
-                let chars_before: usize = code_lines
-                    .iter()
-                    .take(line_in_code)
-                    .map(|l| l.chars().count() + 1) // +1 for newline
-                    .sum();
-                let current_line_len =
-                    code_lines.get(line_in_code).map(|l| l.chars().count()).unwrap_or(0);
-                let col_in_line = col_in_code.min(current_line_len);
+        self.config.encrypt_notes = enabled;
+        if let Err(e) = self.config.save() {
+            self.state.command_line.set_error(format!("Failed to save config: {}", e));
+            return;
+        }
+        let current = if enabled { "on" } else { "off" };
+        self.state.command_line.set_message(format!("encrypt={}", current));
+    }
 
-                chars_before + col_in_line
+    /// Resolve a full or short note ID, reporting unknown or ambiguous IDs
+    fn resolve_note_id(&mut self, query: &str) -> Option<crate::notes::Note> {
+        let matches = self.notes_store.find_by_id(query);
+        match matches.as_slice() {
+            [note] => Some((*note).clone()),
+            [] => {
+                self.state.command_line.set_error(format!("No note with ID {}", query));
+                None
             }
-            ContentBlock::Blockquote(text) => {
-                // Blockquotes: "│ " prefix (2 chars), wrapped at content_width - 4
-                let padding = 4; // "│ " + extra padding
-                let wrap_width = content_width.saturating_sub(4).max(1);
-                let col_in_text = col.saturating_sub(padding);
-
-                self.calculate_wrapped_char_pos(text, wrap_width, line_within_block, col_in_text)
+            _ => {
+                self.state.command_line.set_error(format!(
+                    "Note ID {} is ambiguous ({} matches)",
+                    query,
+                    matches.len()
+                ));
+                None
             }
-            ContentBlock::UnorderedList(items) => {
-                // Lists: "  • " prefix (4 chars), items may wrap
-                let padding = 4;
-                let wrap_width = content_width.saturating_sub(4).max(1);
-                let col_in_text = col.saturating_sub(padding);
-                let full_text = items.join("\n");
+        }
+    }
 
-                self.calculate_wrapped_char_pos(
-                    &full_text,
-                    wrap_width,
-                    line_within_block,
-                    col_in_text,
-                )
-            }
-            ContentBlock::OrderedList(items) => {
-                // Ordered lists: "  N. " prefix (about 5-6 chars)
-                let padding = 6;
-                let wrap_width = content_width.saturating_sub(6).max(1);
-                let col_in_text = col.saturating_sub(padding);
-                let full_text = items.join("\n");
+    /// Open the section containing a note and select it in the notes panel
+    fn goto_note(&mut self, query: &str) -> Result<()> {
+        let Some(note) = self.resolve_note_id(query) else { return Ok(()) };
 
-                self.calculate_wrapped_char_pos(
-                    &full_text,
-                    wrap_width,
-                    line_within_block,
-                    col_in_text,
-                )
-            }
-            _ => 0, // Images, tables, horizontal rules - just position at start
+        if self.state.book.as_ref().is_none_or(|b| b.metadata.id != note.book_id) {
+            // Continue once the note's book has loaded
+            return self.load_library_book(&note.book_id, BookLoadKind::GotoNote(note.id));
+        }
+        let Some(book) = &self.state.book else { return Ok(()) };
+
+        let position = book.chapters.iter().enumerate().find_map(|(chapter_idx, chapter)| {
+            chapter
+                .sections
+                .iter()
+                .position(|s| s.path == note.section_path)
+                .map(|section_idx| (chapter_idx, section_idx))
+        });
+        let Some((chapter_idx, section_idx)) = position else {
+            self.state
+                .command_line
+                .set_error(format!("Section {} no longer exists", note.section_path));
+            return Ok(());
         };
 
-        Some((block_idx, char_pos))
+        let same_section =
+            (chapter_idx, section_idx) == (self.state.current_chapter, self.state.current_section);
+        if !same_section {
+            self.record_jump();
+            self.remember_scroll();
+            self.state.current_chapter = chapter_idx;
+            self.state.current_section = section_idx;
+            self.state.content.scroll_offset = 0;
+            self.state.curriculum.expanded_chapters.insert(chapter_idx);
+            self.mark_section_viewed();
+        }
+
+        // Panel order is section-level notes, then annotations
+        let section_notes =
+            self.notes_store.get_section_level_notes(&note.book_id, &note.section_path);
+        let selection_notes =
+            self.notes_store.get_selection_notes(&note.book_id, &note.section_path);
+        let index = section_notes
+            .iter()
+            .chain(selection_notes.iter())
+            .position(|n| n.id == note.id)
+            .unwrap_or(0);
+
+        self.state.notes.selected_index = index;
+        self.state.panel_visibility.notes = true;
+        self.state.focused_panel = Panel::Notes;
+        // Block offsets are only known for the section last rendered
+        if let Some(block) = note.anchor.block_index().filter(|_| same_section) {
+            self.state.content.ensure_block_visible(block);
+        }
+
+        let first_line = note.content.lines().next().unwrap_or("");
+        self.state.command_line.set_message(format!("#{} {}", note.short_id(), first_line));
+        Ok(())
     }
 
-    /// Calculate character position in wrapped text given the wrap width and screen position
-    fn calculate_wrapped_char_pos(
-        &self,
-        text: &str,
-        wrap_width: usize,
-        line_within_block: usize,
-        col_in_text: usize,
-    ) -> usize {
-        if wrap_width == 0 || text.is_empty() {
-            return 0;
+    /// Delete a note by its full or short ID
+    fn delete_note_by_id(&mut self, query: &str) {
+        let Some(note) = self.resolve_note_id(query) else { return };
+
+        self.notes_store.delete_note(&note.id);
+        if let Err(e) = self.notes_store.save() {
+            tracing::warn!("Failed to save notes: {}", e);
+        }
+
+        let total = crate::ui::notes_panel::get_note_count(&self.state, &self.notes_store);
+        if self.state.notes.selected_index >= total {
+            self.state.notes.selected_index = total.saturating_sub(1);
+        }
+        self.state.command_line.set_message(format!("Deleted note #{}", note.short_id()));
+    }
+
+    /// Save the current note being created or edited
+    fn save_note(&mut self) {
+        use crate::notes::Note;
+        use crate::notes::stats::word_count;
+
+        let content = self.state.notes.input.clone();
+        if content.trim().is_empty() {
+            self.state.notes.cancel_edit();
+            return;
         }
 
-        // Build wrapped lines to find exact character positions
-        // This mirrors the wrap_spans logic used in rendering
-        let mut lines: Vec<(usize, usize)> = Vec::new(); // (start_char, end_char) for each line
-        let mut current_width = 0;
-        let mut line_start = 0;
-        let chars: Vec<char> = text.chars().collect();
+        if self.state.notes.creating {
+            // Create new note
+            let Some(book) = &self.state.book else {
+                self.state.notes.cancel_edit();
+                return;
+            };
+            let Some(chapter) = book.chapters.get(self.state.current_chapter) else {
+                self.state.notes.cancel_edit();
+                return;
+            };
+            let Some(section) = chapter.sections.get(self.state.current_section) else {
+                self.state.notes.cancel_edit();
+                return;
+            };
+
+            let note = Note::new_section_note(&book.metadata.id, &section.path, &content);
+            let book_id = book.metadata.id.clone();
+            self.notes_store.add_note(note);
 
-        // Split by whitespace, keeping track of positions
-        let mut i = 0;
-        while i < chars.len() {
-            // Find word boundaries (word + trailing whitespace)
-            let word_start = i;
-            while i < chars.len() && !chars[i].is_whitespace() {
-                i += 1;
+            if let Err(e) = self.notes_store.save() {
+                tracing::warn!("Failed to save notes: {}", e);
             }
-            while i < chars.len() && chars[i].is_whitespace() {
-                i += 1;
+            self.record_writing(&book_id, word_count(&content));
+            self.state.command_line.set_message("Note created");
+        } else if let Some(note_id) = &self.state.notes.editing.clone() {
+            // Update existing note, counting only the words added
+            let previous = self
+                .notes_store
+                .get_note(note_id)
+                .map(|n| (n.book_id.clone(), word_count(&n.content)));
+            if self.notes_store.update_note(note_id, &content) {
+                if let Err(e) = self.notes_store.save() {
+                    tracing::warn!("Failed to save notes: {}", e);
+                }
+                if let Some((book_id, old_words)) = previous {
+                    self.record_writing(&book_id, word_count(&content).saturating_sub(old_words));
+                }
+                self.state.command_line.set_message("Note updated");
             }
-            let word_len = i - word_start;
+        }
 
-            // Check if we need to wrap before this word
-            if current_width + word_len > wrap_width && current_width > 0 {
-                // End current line
-                lines.push((line_start, word_start));
-                line_start = word_start;
-                current_width = 0;
-            }
+        self.state.notes.cancel_edit();
+    }
+
+    /// Add a note on the current section without opening the notes editor
+    fn capture_note(&mut self, text: &str) {
+        use crate::notes::Note;
+        use crate::notes::stats::word_count;
 
-            current_width += word_len;
+        let Some((book_id, section_path)) = self.current_section_key() else {
+            self.state.command_line.set_error("No section to attach the note to");
+            return;
+        };
+        let note = Note::new_section_note(&book_id, &section_path, text);
+        let short_id = note.short_id();
+        self.notes_store.add_note(note);
+        if let Err(e) = self.notes_store.save() {
+            tracing::warn!("Failed to save notes: {}", e);
         }
+        self.record_writing(&book_id, word_count(text));
+        self.state.command_line.set_message(format!("Captured note {}", short_id));
+    }
 
-        // Don't forget the last line
-        if line_start < chars.len() || lines.is_empty() {
-            lines.push((line_start, chars.len()));
+    /// Follow the selected note's `[[note:ID]]` or `[[section:path]]` link,
+    /// offering a choice when it has several (`o` in the notes panel)
+    fn follow_note_link(&mut self) {
+        use crate::notes::links;
+
+        let note = crate::ui::notes_panel::get_selected_note(&self.state, &self.notes_store);
+        let Some(note) = note else { return };
+        let book_id = note.book_id.clone();
+        let links: Vec<_> = links::find(&note.content).into_iter().map(|(_, link)| link).collect();
+        match links.as_slice() {
+            [] => self
+                .state
+                .command_line
+                .set_error("No links in this note (add [[note:ID]] or [[section:path]])"),
+            [link] => self.follow_link(&book_id, link),
+            _ => {
+                let listed: Vec<String> = links
+                    .iter()
+                    .enumerate()
+                    .map(|(i, l)| format!("{} {}", i + 1, l.label()))
+                    .collect();
+                self.state.command_line.set_message(format!(
+                    "Follow link: {} (1-{}, Esc to cancel)",
+                    listed.join(", "),
+                    links.len()
+                ));
+                self.note_link_choice = Some((book_id, links));
+            }
         }
+    }
 
-        // Now find the character at the target line and column
-        if line_within_block >= lines.len() {
-            // Beyond last line, return end of text
-            return chars.len().saturating_sub(1);
+    /// Handle a key while choosing between a note's links
+    fn handle_note_link_choice(
+        &mut self,
+        book_id: &str,
+        links: &[crate::notes::links::NoteLink],
+        key: KeyCode,
+    ) {
+        let picked = match key {
+            KeyCode::Char(c) => {
+                c.to_digit(10).and_then(|n| (n as usize).checked_sub(1)).and_then(|i| links.get(i))
+            }
+            _ => None,
+        };
+        match picked {
+            Some(link) => self.follow_link(book_id, link),
+            None => self.state.command_line.clear_message(),
         }
+    }
 
-        let (line_start_char, line_end_char) = lines[line_within_block];
-        let line_len = line_end_char - line_start_char;
+    /// Go to a note link's note, or to a section link's section of `book_id`
+    fn follow_link(&mut self, book_id: &str, link: &crate::notes::links::NoteLink) {
+        use crate::notes::links::NoteLink;
 
-        // Return character position at (line_start + column), clamped to line bounds
-        let char_pos = line_start_char + col_in_text.min(line_len.saturating_sub(1));
-        char_pos.min(chars.len().saturating_sub(1))
+        self.state.notes.clear_search();
+        self.state.notes.close_highlights();
+        match link {
+            NoteLink::Note(id) => {
+                if let Err(e) = self.goto_note(id) {
+                    self.state.command_line.set_error(format!("Failed to open note: {}", e));
+                }
+            }
+            NoteLink::Section(_)
+                if self.state.book.as_ref().is_none_or(|b| b.metadata.id != book_id) =>
+            {
+                self.state
+                    .command_line
+                    .set_error(format!("Open {} to follow its section links", book_id));
+            }
+            NoteLink::Section(path) => self.goto_section(path),
+        }
     }
 
-    /// Handle vertical navigation based on focused panel
-    fn handle_vertical_navigation(&mut self, action: Action) {
-        match self.state.focused_panel {
-            Panel::Curriculum => self.navigate_curriculum(action),
-            Panel::Content => self.navigate_content(action),
-            Panel::Notes => self.navigate_notes(action),
+    /// Search notes in every book for a query, or start typing one
+    fn search_notes(&mut self, query: Option<String>) {
+        self.state.notes.close_highlights();
+        self.state.notes.clear_search();
+        self.state.panel_visibility.notes = true;
+        self.state.focused_panel = Panel::Notes;
+        match query {
+            Some(query) => {
+                self.state.notes.search = query;
+                let count = crate::ui::notes_panel::get_note_count(&self.state, &self.notes_store);
+                self.state.command_line.set_message(format!(
+                    "{} note{} match (Enter to open, Esc to clear)",
+                    count,
+                    if count == 1 { "" } else { "s" }
+                ));
+            }
+            None => {
+                self.state.notes.search_editing = true;
+                self.state
+                    .command_line
+                    .set_message("Search notes in every book (Enter to keep, Esc to clear)");
+            }
         }
     }
 
-    /// Navigate the notes panel
-    fn navigate_notes(&mut self, action: Action) {
-        use crate::ui::notes_panel::get_note_count;
+    /// List the book's highlights in the notes panel, only those of `color` if given
+    fn show_highlights(&mut self, color: Option<crate::notes::HighlightColor>) {
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book open");
+            return;
+        };
+        self.state.notes.show_highlights(color);
+        self.state.panel_visibility.notes = true;
+        self.state.focused_panel = Panel::Notes;
+        let count = crate::ui::notes_panel::get_note_count(&self.state, &self.notes_store);
+        self.state.command_line.set_message(format!(
+            "{} highlight{} in {} (Enter to open, c to change color, Esc to go back)",
+            count,
+            if count == 1 { "" } else { "s" },
+            book.metadata.title
+        ));
+    }
 
-        let total_notes = get_note_count(&self.state, &self.notes_store);
-        if total_notes == 0 {
+    /// Preview replacing text across the book's notes, to be confirmed with y
+    fn preview_note_replace(&mut self, expr: &str) {
+        use crate::notes::replace::{Substitution, plan, preview};
+
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book open");
+            return;
+        };
+        let substitution = match Substitution::parse(expr) {
+            Ok(substitution) => substitution,
+            Err(e) => {
+                self.state.command_line.set_error(e);
+                return;
+            }
+        };
+        let edits = plan(&self.notes_store, &book.metadata.id, &substitution);
+        if edits.is_empty() {
+            self.state
+                .command_line
+                .set_error(format!("\"{}\" not found in this book's notes", substitution.from));
             return;
         }
 
-        match action {
-            Action::Up => {
-                if self.state.notes.selected_index > 0 {
-                    self.state.notes.selected_index -= 1;
-                }
+        self.state.report = Some(Report::new("Replace in notes", preview(&substitution, &edits)));
+        self.state
+            .command_line
+            .set_message("Replace in notes? (y to apply, any other key to cancel)");
+        self.pending_note_edits = Some(edits);
+    }
+
+    /// Apply previewed note edits on y, otherwise drop them
+    fn confirm_note_edits(&mut self, edits: Vec<crate::notes::replace::NoteEdit>, key: KeyCode) {
+        self.state.report = None;
+        if key != KeyCode::Char('y') {
+            self.state.command_line.set_message("Replace cancelled");
+            return;
+        }
+
+        let mut count = 0;
+        let mut notes = 0;
+        for edit in &edits {
+            // Skip notes changed since the preview
+            let unchanged =
+                self.notes_store.get_note(&edit.note_id).is_some_and(|n| n.content == edit.before);
+            if unchanged && self.notes_store.update_note(&edit.note_id, &edit.after) {
+                count += edit.count;
+                notes += 1;
             }
-            Action::Down => {
-                if self.state.notes.selected_index < total_notes - 1 {
-                    self.state.notes.selected_index += 1;
-                }
+        }
+        if let Err(e) = self.notes_store.save() {
+            tracing::warn!("Failed to save notes: {}", e);
+        }
+        self.state.command_line.set_message(format!(
+            "Replaced {} match{} in {} note{}",
+            count,
+            if count == 1 { "" } else { "es" },
+            notes,
+            if notes == 1 { "" } else { "s" }
+        ));
+    }
+
+    /// Give the selected highlight the next color, which new highlights then use
+    fn cycle_highlight_color(&mut self) {
+        let note = crate::ui::notes_panel::get_selected_note(&self.state, &self.notes_store);
+        let Some((id, color)) = note.and_then(|n| Some((n.id.clone(), n.highlight?.next()))) else {
+            return;
+        };
+        if self.notes_store.set_highlight_color(&id, color) {
+            if let Err(e) = self.notes_store.save() {
+                tracing::warn!("Failed to save notes: {}", e);
+            }
+            self.state.notes.highlight_color = color;
+            self.state.command_line.set_message(format!("Highlight is now {}", color.name()));
+        }
+    }
+
+    /// Handle keys while typing the notes search
+    fn handle_notes_search_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => {
+                self.state.notes.clear_search();
+                self.state.command_line.clear_message();
+                return;
+            }
+            KeyCode::Enter => {
+                self.state.notes.search_editing = false;
+                self.state.command_line.clear_message();
+                return;
+            }
+            KeyCode::Backspace => {
+                self.state.notes.search.pop();
+            }
+            KeyCode::Char(c) => self.state.notes.search.push(c),
+            _ => return,
+        }
+        self.state.notes.selected_index = 0;
+        self.state.notes.scroll_offset = 0;
+    }
+
+    /// Handle input while editing a note
+    fn handle_notes_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => {
+                self.state.notes.cancel_edit();
+            }
+            KeyCode::Enter => {
+                self.save_note();
+            }
+            KeyCode::Backspace => {
+                self.state.notes.delete_char();
+            }
+            KeyCode::Left => {
+                self.state.notes.move_left();
+            }
+            KeyCode::Right => {
+                self.state.notes.move_right();
+            }
+            KeyCode::Char(c) => {
+                self.state.notes.insert_char(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// Move panel focus left
+    fn move_panel_focus_left(&mut self) {
+        let split = self.state.split.as_mut();
+        match self.state.focused_panel {
+            Panel::Content => match split {
+                Some(split) if split.focused => split.focused = false,
+                _ => {
+                    if self.state.panel_visibility.curriculum {
+                        self.state.focused_panel = Panel::Curriculum;
+                    }
+                }
+            },
+            Panel::Notes => {
+                self.state.focused_panel = Panel::Content;
+                if let Some(split) = split {
+                    split.focused = true;
+                }
+            }
+            Panel::Curriculum => {}
+        }
+    }
+
+    /// Move panel focus right
+    fn move_panel_focus_right(&mut self) {
+        let split = self.state.split.as_mut();
+        match self.state.focused_panel {
+            Panel::Curriculum => {
+                self.state.focused_panel = Panel::Content;
+                if let Some(split) = split {
+                    split.focused = false;
+                }
+            }
+            Panel::Content => match split {
+                Some(split) if !split.focused => split.focused = true,
+                _ => {
+                    if self.state.panel_visibility.notes {
+                        self.state.focused_panel = Panel::Notes;
+                    }
+                }
+            },
+            Panel::Notes => {}
+        }
+    }
+
+    /// Handle mouse events for text selection
+    fn handle_mouse_event(&mut self, mouse_event: crossterm::event::MouseEvent) {
+        // Only handle mouse events on the main screen in content panel
+        if !matches!(self.state.screen, Screen::Main) {
+            return;
+        }
+
+        // Clicking a citation in the response panel jumps to its passage
+        if self.state.claude.is_response_visible()
+            && matches!(mouse_event.kind, MouseEventKind::Down(MouseButton::Left))
+        {
+            let (col, row) = (mouse_event.column, mouse_event.row);
+            let hit = self
+                .state
+                .claude
+                .citation_hits
+                .iter()
+                .find(|&&(x, y, width, _)| row == y && col >= x && col < x + width);
+            if let Some(&(_, _, _, index)) = hit {
+                self.goto_citation(index);
+                return;
+            }
+        }
+
+        // Use the stored content area from rendering (much more accurate than calculating)
+        let (content_x, content_y, content_w, content_h) = self.state.content.content_area;
+
+        // If content area hasn't been set yet, skip
+        if content_w == 0 || content_h == 0 {
+            return;
+        }
+
+        let col = mouse_event.column;
+        let row = mouse_event.row;
+
+        // Check if mouse is within content panel bounds
+        if col < content_x
+            || col >= content_x + content_w
+            || row < content_y
+            || row >= content_y + content_h
+        {
+            return;
+        }
+
+        // Calculate position relative to content area
+        let rel_col = col - content_x;
+        let rel_row = row - content_y;
+
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // Start selection - convert screen position to block/char
+                if let Some((block, char_pos)) = self.screen_to_text_position(rel_col, rel_row) {
+                    // Enter cursor mode and start visual selection
+                    self.state.content.cursor_block = block;
+                    self.state.content.cursor_char = char_pos;
+                    self.state.content.cursor_mode = true;
+
+                    // Start visual mode with anchor at this position
+                    self.state.visual_mode.enter(block, char_pos);
+
+                    // Track mouse selection start
+                    self.mouse_selection = Some((block, char_pos));
+
+                    self.state.focused_panel = Panel::Content;
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                // Continue selection if we started one
+                if self.mouse_selection.is_some() {
+                    if let Some((block, char_pos)) = self.screen_to_text_position(rel_col, rel_row)
+                    {
+                        // Update cursor position (selection end)
+                        self.state.content.cursor_block = block;
+                        self.state.content.cursor_char = char_pos;
+                    }
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                // End selection
+                if self.mouse_selection.is_some() {
+                    if let Some((block, char_pos)) = self.screen_to_text_position(rel_col, rel_row)
+                    {
+                        self.state.content.cursor_block = block;
+                        self.state.content.cursor_char = char_pos;
+                    }
+
+                    // Check if it was just a click (no drag) - clear selection
+                    if let Some((start_block, start_char)) = self.mouse_selection {
+                        if start_block == self.state.content.cursor_block
+                            && start_char == self.state.content.cursor_char
+                        {
+                            // Just a click, exit visual mode but keep cursor
+                            self.state.visual_mode.exit();
+                        }
+                    }
+
+                    self.mouse_selection = None;
+                }
+            }
+            MouseEventKind::ScrollUp => {
+                // Scroll content up
+                self.state.content.scroll_offset =
+                    self.state.content.scroll_offset.saturating_sub(3);
+            }
+            MouseEventKind::ScrollDown => {
+                // Scroll content down
+                self.state.content.scroll_offset += 3;
+                self.state.content.clamp_scroll();
+            }
+            _ => {}
+        }
+    }
+
+    /// Convert screen coordinates (relative to content panel) to block index and character position
+    fn screen_to_text_position(&self, col: u16, row: u16) -> Option<(usize, usize)> {
+        use crate::book::ContentBlock;
+
+        let book = self.state.book.as_ref()?;
+        let section = book.get_section(self.state.current_chapter, self.state.current_section)?;
+
+        // Calculate which line we're on (accounting for scroll)
+        let target_line = self.state.content.scroll_offset + row as usize;
+
+        // Find which block this line belongs to using block_line_offsets
+        let offsets = &self.state.content.block_line_offsets;
+
+        let mut block_idx = 0;
+        for (idx, &offset) in offsets.iter().enumerate() {
+            if offset <= target_line {
+                block_idx = idx;
+            } else {
+                break;
+            }
+        }
+
+        // Get the block and calculate character position
+        let block = section.content.get(block_idx)?;
+        let block_start_line = offsets.get(block_idx).copied().unwrap_or(0);
+        let line_within_block = target_line.saturating_sub(block_start_line);
+        let content_width = self.state.content.content_width;
+        let col = col as usize;
+
+        let char_pos = match block {
+            ContentBlock::Paragraph(text) => {
+                // Paragraphs: 2 spaces padding, wrapped at content_width - 4
+                let padding = 2;
+                let wrap_width = content_width.saturating_sub(4).max(1);
+                let col_in_text = col.saturating_sub(padding);
+
+                // Calculate character position by simulating word wrap
+                self.calculate_wrapped_char_pos(text, wrap_width, line_within_block, col_in_text)
+            }
+            ContentBlock::Heading { text, level } => {
+                // Headings: variable padding based on level, single line (usually)
+                let padding = match level {
+                    1..=3 => 2,
+                    4 => 4,
+                    _ => 6,
+                };
+                let col_in_text = col.saturating_sub(padding);
+                col_in_text.min(text.chars().count().saturating_sub(1))
+            }
+            ContentBlock::Code(code) => {
+                // Code blocks: "│  N│ " prefix (about 6 chars), then code
+                // First line is header "┌─ rust ─────", skip it
+                if line_within_block == 0 {
+                    return Some((block_idx, 0));
+                }
+
+                let code_lines: Vec<&str> = code.code.lines().collect();
+                let line_in_code = line_within_block.saturating_sub(1); // Account for header
+
+                if line_in_code >= code_lines.len() {
+                    // On closing line or beyond
+                    return Some((block_idx, code.code.chars().count().saturating_sub(1)));
+                }
+
+                // Calculate character position in the code
+                // Prefix is "│  N│ " where N is line number
+                let prefix_width = 6; // Approximate
+                let col_in_code = col.saturating_sub(prefix_width);
+
+                // Sum characters from previous lines + column in current line
+                let chars_before: usize = code_lines
+                    .iter()
+                    .take(line_in_code)
+                    .map(|l| l.chars().count() + 1) // +1 for newline
+                    .sum();
+                let current_line_len =
+                    code_lines.get(line_in_code).map(|l| l.chars().count()).unwrap_or(0);
+                let col_in_line = col_in_code.min(current_line_len);
+
+                chars_before + col_in_line
+            }
+            ContentBlock::Blockquote(text) => {
+                // Blockquotes: "│ " prefix (2 chars), wrapped at content_width - 4
+                let padding = 4; // "│ " + extra padding
+                let wrap_width = content_width.saturating_sub(4).max(1);
+                let col_in_text = col.saturating_sub(padding);
+
+                self.calculate_wrapped_char_pos(text, wrap_width, line_within_block, col_in_text)
+            }
+            ContentBlock::UnorderedList(items) => {
+                // Lists: "  • " prefix (4 chars), items may wrap
+                let padding = 4;
+                let wrap_width = content_width.saturating_sub(4).max(1);
+                let col_in_text = col.saturating_sub(padding);
+                let full_text = items.join("\n");
+
+                self.calculate_wrapped_char_pos(
+                    &full_text,
+                    wrap_width,
+                    line_within_block,
+                    col_in_text,
+                )
+            }
+            ContentBlock::OrderedList(items) => {
+                // Ordered lists: "  N. " prefix (about 5-6 chars)
+                let padding = 6;
+                let wrap_width = content_width.saturating_sub(6).max(1);
+                let col_in_text = col.saturating_sub(padding);
+                let full_text = items.join("\n");
+
+                self.calculate_wrapped_char_pos(
+                    &full_text,
+                    wrap_width,
+                    line_within_block,
+                    col_in_text,
+                )
+            }
+            _ => 0, // Images, tables, horizontal rules - just position at start
+        };
+
+        Some((block_idx, char_pos))
+    }
+
+    /// Character position in wrapped text at a screen position, wrapped as the
+    /// renderer wraps it with the reader's typography
+    fn calculate_wrapped_char_pos(
+        &self,
+        text: &str,
+        wrap_width: usize,
+        line_within_block: usize,
+        col_in_text: usize,
+    ) -> usize {
+        if wrap_width == 0 || text.is_empty() {
+            return 0;
+        }
+        crate::ui::content::wrapped_char_at(
+            text,
+            wrap_width,
+            self.state.content.typography,
+            line_within_block,
+            col_in_text,
+        )
+    }
+
+    /// Handle vertical navigation based on focused panel
+    fn handle_vertical_navigation(&mut self, action: Action) {
+        match self.state.focused_panel {
+            Panel::Curriculum => self.navigate_curriculum(action),
+            Panel::Content => self.navigate_content(action),
+            Panel::Notes => self.navigate_notes(action),
+        }
+    }
+
+    /// Navigate the notes panel
+    fn navigate_notes(&mut self, action: Action) {
+        use crate::ui::notes_panel::get_note_count;
+
+        let total_notes = get_note_count(&self.state, &self.notes_store);
+        if total_notes == 0 {
+            return;
+        }
+
+        match action {
+            Action::Up => {
+                if self.state.notes.selected_index > 0 {
+                    self.state.notes.selected_index -= 1;
+                }
+            }
+            Action::Down => {
+                if self.state.notes.selected_index < total_notes - 1 {
+                    self.state.notes.selected_index += 1;
+                }
+            }
+            Action::Top => {
+                self.state.notes.selected_index = 0;
+            }
+            Action::Bottom => {
+                self.state.notes.selected_index = total_notes.saturating_sub(1);
+            }
+            _ => {}
+        }
+    }
+
+    /// Navigate the curriculum tree
+    fn navigate_curriculum(&mut self, action: Action) {
+        if self.state.book.is_none() {
+            return;
+        }
+
+        // Calculate total items (chapters + visible sections)
+        let total_items = self.calculate_curriculum_items();
+        if total_items == 0 {
+            return;
+        }
+
+        match action {
+            Action::Up => {
+                if self.state.curriculum.selected_index > 0 {
+                    self.state.curriculum.selected_index -= 1;
+                }
+            }
+            Action::Down => {
+                if self.state.curriculum.selected_index < total_items - 1 {
+                    self.state.curriculum.selected_index += 1;
+                }
+            }
+            Action::Top => {
+                self.state.curriculum.selected_index = 0;
+            }
+            Action::Bottom => {
+                self.state.curriculum.selected_index = total_items.saturating_sub(1);
+            }
+            _ => {}
+        }
+
+        // Keep selection in view
+        self.state.curriculum.ensure_selection_visible();
+    }
+
+    /// Calculate total visible items in curriculum
+    fn calculate_curriculum_items(&self) -> usize {
+        crate::ui::curriculum::calculate_visible_items(&self.state)
+    }
+
+    /// Start typing a filter for the curriculum tree
+    fn start_curriculum_filter(&mut self) {
+        self.state.curriculum.filter_editing = true;
+        self.state
+            .command_line
+            .set_message("Filter chapters and sections (Enter to keep, Esc to clear)");
+    }
+
+    /// List only the annotations on the text in view, or all the section's
+    /// notes again
+    fn toggle_notes_follow(&mut self) {
+        let notes = &mut self.state.notes;
+        notes.follow = !notes.follow;
+        notes.selected_index = 0;
+        notes.scroll_offset = 0;
+        if notes.follow {
+            self.state.panel_visibility.notes = true;
+        }
+        self.state.command_line.set_message(if notes.follow {
+            "Notes follow the text in view"
+        } else {
+            "Notes show the whole section"
+        });
+    }
+
+    /// Open the help screen at the top, unfiltered
+    fn show_help(&mut self) {
+        self.state.help = state::HelpState::default();
+        self.state.screen = Screen::Help;
+    }
+
+    /// Handle an action on the help screen, returns true if should exit
+    fn handle_help_action(&mut self, action: Action) -> bool {
+        let help = &mut self.state.help;
+        let page = help.height.max(1);
+        match action {
+            Action::Quit => return true,
+            Action::Down | Action::LineDown => help.scroll += 1,
+            Action::Up | Action::LineUp => help.scroll = help.scroll.saturating_sub(1),
+            Action::PageDown => help.scroll += page,
+            Action::PageUp => help.scroll = help.scroll.saturating_sub(page),
+            Action::HalfPageDown => help.scroll += page / 2,
+            Action::HalfPageUp => help.scroll = help.scroll.saturating_sub(page / 2),
+            Action::Top => help.scroll = 0,
+            // Drawing keeps the scroll within the lines
+            Action::Bottom => help.scroll = usize::MAX,
+            Action::Search => {
+                help.search.clear();
+                help.search_editing = true;
+                help.scroll = 0;
+            }
+            Action::Back if !help.search.is_empty() => {
+                help.search.clear();
+                help.scroll = 0;
+            }
+            Action::Back | Action::Help => self.state.screen = Screen::Main,
+            _ => {}
+        }
+        false
+    }
+
+    /// Handle keys while typing the help screen's filter
+    fn handle_help_search_input(&mut self, key: KeyCode) {
+        let help = &mut self.state.help;
+        match key {
+            KeyCode::Esc => help.search.clear(),
+            KeyCode::Enter => {}
+            KeyCode::Backspace => {
+                help.search.pop();
+                help.scroll = 0;
+                return;
+            }
+            KeyCode::Char(c) => {
+                help.search.push(c);
+                help.scroll = 0;
+                return;
+            }
+            _ => return,
+        }
+        help.search_editing = false;
+    }
+
+    /// Handle keys while typing the curriculum filter
+    fn handle_curriculum_filter_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => {
+                self.state.curriculum.clear_filter();
+                self.state.command_line.clear_message();
+                return;
+            }
+            KeyCode::Enter => {
+                self.state.curriculum.filter_editing = false;
+                self.state.command_line.clear_message();
+                return;
+            }
+            KeyCode::Backspace => {
+                self.state.curriculum.filter.pop();
+            }
+            KeyCode::Char(c) => self.state.curriculum.filter.push(c),
+            _ => return,
+        }
+
+        // Select the first matching section, or the first item
+        let items = crate::ui::curriculum::visible_items(&self.state);
+        let curriculum = &mut self.state.curriculum;
+        curriculum.selected_index = items
+            .iter()
+            .position(|item| match item {
+                CurriculumItem::Section(c, s) => self.state.book.as_ref().is_some_and(|book| {
+                    curriculum.matches_filter(&book.chapters[*c].sections[*s].title)
+                }),
+                CurriculumItem::Chapter(_) => false,
+            })
+            .unwrap_or(0);
+        curriculum.scroll_offset = 0;
+        curriculum.ensure_selection_visible();
+    }
+
+    /// Navigate content (scrolling)
+    fn navigate_content(&mut self, action: Action) {
+        if self.scroll_split(action) {
+            return;
+        }
+        // Handle footer-focused state
+        if self.state.content.footer_focused {
+            match action {
+                Action::Up => {
+                    // Exit footer, return to content
+                    self.state.content.exit_footer();
+                    self.state.command_line.clear_message();
+                }
+                Action::Left => {
+                    // Switch to previous button
+                    self.state.content.footer_prev();
+                }
+                Action::Right => {
+                    // Switch to next button
+                    self.state.content.footer_next();
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        // Normal content scrolling
+        match action {
+            Action::Up => {
+                let step = 2 * self.nav_multiplier as usize;
+                self.state.content.scroll_offset =
+                    self.state.content.scroll_offset.saturating_sub(step);
+            }
+            Action::Down => {
+                // Check if we're at max scroll and should enter footer
+                let max_scroll = self.state.content.max_scroll();
+                if self.state.content.scroll_offset >= max_scroll {
+                    // At bottom of content, move focus to footer
+                    self.state.content.enter_footer();
+                    self.state.command_line.set_message("[h/l] switch  [Enter] select  [k] back");
+                } else {
+                    self.state.content.scroll_offset += 2 * self.nav_multiplier as usize;
+                }
+            }
+            Action::Top => {
+                self.state.content.scroll_offset = 0;
+            }
+            Action::Bottom => {
+                // Jump to max scroll position
+                self.state.content.scroll_offset = self.state.content.max_scroll();
+            }
+            _ => {}
+        }
+        // Clamp to valid range
+        self.state.content.clamp_scroll();
+    }
+
+    /// Scroll the split pane when it has focus and scrolls on its own;
+    /// returns whether it did (a bound pane follows the main content instead)
+    fn scroll_split(&mut self, action: Action) -> bool {
+        let Some(split) = self.state.split.as_mut() else { return false };
+        if self.state.focused_panel != Panel::Content
+            || !split.focused
+            || split.scroll_bind.is_some()
+        {
+            return false;
+        }
+        let step = 2 * self.nav_multiplier as isize;
+        match action {
+            Action::Up => split.scroll_by(-step),
+            Action::Down => split.scroll_by(step),
+            Action::PageUp => split.scroll_by(-20),
+            Action::PageDown => split.scroll_by(20),
+            Action::HalfPageUp => split.scroll_by(-10),
+            Action::HalfPageDown => split.scroll_by(10),
+            Action::Top => split.scroll_offset = 0,
+            Action::Bottom => split.scroll_offset = split.max_scroll,
+            _ => {}
+        }
+        true
+    }
+
+    /// Handle page/half-page scrolling based on focused panel
+    fn handle_scroll(&mut self, action: Action) {
+        let scroll_amount = match action {
+            Action::PageUp | Action::PageDown => 20,
+            Action::HalfPageUp | Action::HalfPageDown => 10,
+            _ => return,
+        };
+
+        match self.state.focused_panel {
+            Panel::Curriculum => {
+                let total_items = self.calculate_curriculum_items();
+                match action {
+                    Action::PageUp | Action::HalfPageUp => {
+                        self.state.curriculum.selected_index =
+                            self.state.curriculum.selected_index.saturating_sub(scroll_amount);
+                    }
+                    Action::PageDown | Action::HalfPageDown => {
+                        self.state.curriculum.selected_index =
+                            (self.state.curriculum.selected_index + scroll_amount)
+                                .min(total_items.saturating_sub(1));
+                    }
+                    _ => {}
+                }
+                // Keep selection in view
+                self.state.curriculum.ensure_selection_visible();
+            }
+            Panel::Content => {
+                if self.scroll_split(action) {
+                    return;
+                }
+                match action {
+                    Action::PageUp | Action::HalfPageUp => {
+                        self.state.content.scroll_offset =
+                            self.state.content.scroll_offset.saturating_sub(scroll_amount);
+                    }
+                    Action::PageDown | Action::HalfPageDown => {
+                        self.state.content.scroll_offset += scroll_amount;
+                    }
+                    _ => {}
+                }
+                // Clamp to valid range
+                self.state.content.clamp_scroll();
+            }
+            Panel::Notes => {}
+        }
+    }
+
+    /// Handle selection (Enter key)
+    fn handle_select(&mut self) {
+        // Handle Content panel with footer focused
+        if self.state.focused_panel == Panel::Content && self.state.content.footer_focused {
+            match self.state.content.footer_button_index {
+                0 => {
+                    // Take Quiz button
+                    self.start_quiz();
+                }
+                1 => {
+                    // Complete & Next button
+                    self.complete_section_and_next();
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if self.state.focused_panel == Panel::Notes {
+            let note = crate::ui::notes_panel::get_selected_note(&self.state, &self.notes_store);
+            let Some(id) = note.map(|n| n.id.clone()) else { return };
+            let backlink =
+                crate::ui::notes_panel::backlink_selected(&self.state, &self.notes_store);
+            if !self.state.notes.is_searching() && !self.state.notes.highlights && !backlink {
+                self.state.notes.toggle_expanded(&id);
+                return;
+            }
+            // Jump to a search result's, highlight's or linking note's section
+            self.state.notes.clear_search();
+            self.state.notes.close_highlights();
+            if let Err(e) = self.goto_note(&id) {
+                self.state.command_line.set_error(format!("Failed to open note: {}", e));
+            }
+            return;
+        }
+
+        if self.state.focused_panel != Panel::Curriculum {
+            return;
+        }
+
+        // Toggle chapter expansion or select section
+        let selected = self.state.curriculum.selected_index;
+        match crate::ui::curriculum::get_item_at_index(&self.state, selected) {
+            Some(CurriculumItem::Chapter(chapter_idx)) => {
+                self.state.curriculum.toggle_chapter(chapter_idx);
+            }
+            Some(CurriculumItem::Section(chapter_idx, section_idx)) => {
+                // Select this section, back where it was left
+                self.remember_scroll();
+                self.state.current_chapter = chapter_idx;
+                self.state.current_section = section_idx;
+                self.state.content.scroll_offset = self.remembered_scroll();
+                // Move focus to content
+                self.state.focused_panel = Panel::Content;
+                // Mark as viewed
+                self.mark_section_viewed();
+            }
+            None => {}
+        }
+    }
+
+    /// Handle command line input, returns true if should exit
+    async fn handle_command_line_input(&mut self, key: KeyCode) -> Result<bool> {
+        match key {
+            KeyCode::Esc => {
+                self.state.command_line.exit_input_mode();
+            }
+            KeyCode::Enter => {
+                let input = self.state.command_line.input.clone();
+                let mode = self.state.command_line.mode;
+
+                // Add to history before clearing
+                self.state.command_line.add_to_history(input.clone());
+                self.state.command_line.exit_input_mode();
+
+                // Parse and execute
+                match mode {
+                    CommandMode::Command => {
+                        return self.execute_command(&input);
+                    }
+                    CommandMode::Search => {
+                        self.execute_search(&input);
+                    }
+                    CommandMode::Normal => {}
+                }
+            }
+            KeyCode::Backspace => {
+                self.state.command_line.delete_char();
+            }
+            KeyCode::Delete => {
+                self.state.command_line.delete_char_forward();
+            }
+            KeyCode::Left => {
+                self.state.command_line.move_left();
+            }
+            KeyCode::Right => {
+                self.state.command_line.move_right();
+            }
+            KeyCode::Home => {
+                self.state.command_line.move_start();
+            }
+            KeyCode::End => {
+                self.state.command_line.move_end();
+            }
+            KeyCode::Up => {
+                self.state.command_line.history_up();
+            }
+            KeyCode::Down => {
+                self.state.command_line.history_down();
+            }
+            KeyCode::Char(c) => {
+                self.state.command_line.insert_char(c);
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Execute a parsed command, returns true if should exit
+    fn execute_command(&mut self, input: &str) -> Result<bool> {
+        match parse_command(input) {
+            ParseResult::Ok(cmd) => self.run_command(cmd),
+            ParseResult::UnknownCommand(cmd) => {
+                self.state.command_line.set_error(format!("Unknown command: {}", cmd));
+                Ok(false)
+            }
+            ParseResult::MissingArgument(cmd) => {
+                self.state.command_line.set_error(format!("Missing argument for: {}", cmd));
+                Ok(false)
+            }
+        }
+    }
+
+    /// Run a command, returns true if should exit
+    fn run_command(&mut self, cmd: Command) -> Result<bool> {
+        match cmd {
+            Command::Quit => Ok(true),
+            Command::Help => {
+                self.show_help();
+                Ok(false)
+            }
+            Command::Add(path) => {
+                self.add_book(&path)?;
+                Ok(false)
+            }
+            Command::Open(book_id) => {
+                self.open_book(&book_id)?;
+                Ok(false)
+            }
+            Command::Remove(book_id) => {
+                self.remove_book(&book_id)?;
+                Ok(false)
+            }
+            Command::Refresh => {
+                self.refresh_current_book()?;
+                Ok(false)
+            }
+            Command::List => {
+                self.list_books();
+                Ok(false)
+            }
+            Command::Search(query) => {
+                self.execute_search(&query);
+                Ok(false)
+            }
+            Command::SearchLibrary(query) => {
+                self.search_library(&query);
+                Ok(false)
+            }
+            Command::Goto(path) => {
+                self.goto_section(&path);
+                Ok(false)
+            }
+            Command::Resume => {
+                self.resume();
+                Ok(false)
+            }
+            Command::Plan(name) => {
+                self.show_plans(name.as_deref());
+                Ok(false)
+            }
+            Command::PlanAdd(spec) => {
+                self.add_plan(&spec);
+                Ok(false)
+            }
+            Command::PlanRemove(name) => {
+                self.remove_plan(&name);
+                Ok(false)
+            }
+            Command::Review => {
+                self.show_reviews();
+                Ok(false)
+            }
+            Command::ReviewTune => {
+                self.tune_reviews();
+                Ok(false)
+            }
+            Command::Nop => Ok(false),
+            Command::ClaudeSetup => {
+                self.start_claude_setup();
+                Ok(false)
+            }
+            Command::ClaudeKey(key) => {
+                match crate::claude::ApiKeyManager::set_api_key(&key) {
+                    Ok(()) => {
+                        self.state.claude.needs_setup = false;
+                        self.state.command_line.set_message("API key saved to keyring");
+                    }
+                    Err(e) => {
+                        self.state.command_line.set_error(e.to_string());
+                    }
+                }
+                Ok(false)
+            }
+            Command::ClaudeModel(model_str) => {
+                if let Some(model) = self.models.resolve(&model_str) {
+                    self.set_claude_model(model);
+                } else {
+                    self.state.command_line.set_error(format!(
+                        "Unknown model: {}. Options: haiku, haiku3, sonnet4, sonnet, opus, or an \
+                         ID listed by :claude-models",
+                        model_str
+                    ));
+                }
+                Ok(false)
+            }
+            Command::ClaudeModels => {
+                self.list_models();
+                Ok(false)
+            }
+            Command::ClaudeClear => {
+                self.state.claude.clear_streaming();
+                self.state.claude.clear_error();
+                self.state.command_line.set_message("Claude state cleared");
+                Ok(false)
+            }
+            Command::Ask(question) => {
+                self.ask_claude(&question);
+                Ok(false)
+            }
+            Command::Explain(topic) => {
+                self.explain_section(topic.as_deref());
+                Ok(false)
+            }
+            Command::AskSelection(question) => {
+                self.ask_about_selection(&question);
+                Ok(false)
+            }
+            Command::Diagram(kind) => {
+                self.diagram_selection(kind);
+                Ok(false)
+            }
+            Command::WriteCode(path) => {
+                self.write_code_block(&path);
+                Ok(false)
+            }
+            Command::RunCode => {
+                self.run_code_block();
+                Ok(false)
+            }
+            Command::OpenLink => {
+                self.open_link();
+                Ok(false)
+            }
+            Command::Editions { book, migrate } => {
+                self.compare_editions(&book, migrate);
+                Ok(false)
+            }
+            Command::Summarize { refresh } => {
+                self.summarize_chapter(refresh);
+                Ok(false)
+            }
+            Command::Glossary { refresh } => {
+                self.show_glossary(refresh);
+                Ok(false)
+            }
+            Command::Compare(question) => {
+                self.compare_answers(&question);
+                Ok(false)
+            }
+            Command::Recap(explanation) => {
+                self.recap_chapter(explanation.as_deref());
+                Ok(false)
+            }
+            Command::TeachBack(explanation) => {
+                self.teach_back(explanation.as_deref());
+                Ok(false)
+            }
+            Command::Objectives { refresh } => {
+                self.show_objectives(refresh);
+                Ok(false)
+            }
+            Command::Objective(number) => {
+                self.toggle_objective(number);
+                Ok(false)
+            }
+            Command::Usage => {
+                self.show_usage();
+                Ok(false)
+            }
+            Command::Doctor => {
+                self.show_doctor();
+                Ok(false)
+            }
+            Command::Stats => {
+                self.show_stats();
+                Ok(false)
+            }
+            Command::Speak(action) => {
+                self.control_speech(action);
+                Ok(false)
+            }
+            Command::Split(action) => {
+                match action {
+                    SplitAction::Open { book, section } => {
+                        self.open_split(book.as_deref(), section.as_deref())
+                    }
+                    SplitAction::ToggleSync => self.toggle_split_sync(),
+                    SplitAction::Close => self.close_split(),
+                }
+                Ok(false)
+            }
+            Command::Persona(name) => {
+                self.set_persona(name.as_deref());
+                Ok(false)
+            }
+            Command::BookSet { key, value } => {
+                self.book_set(key.as_deref(), value.as_deref());
+                Ok(false)
+            }
+            Command::Mode(name) => {
+                self.set_reading_mode(name.as_deref());
+                Ok(false)
+            }
+            Command::ExportHtml(dir) => {
+                self.export_html(dir.as_deref());
+                Ok(false)
+            }
+            Command::Export { format, path } => {
+                self.export(&format, path.as_deref());
+                Ok(false)
+            }
+            Command::Footnote(label) => {
+                self.goto_footnote(label.as_deref());
+                Ok(false)
+            }
+            Command::Set { option, value } => {
+                self.set_option(&option, value.as_deref());
+                Ok(false)
+            }
+            Command::Note(id) => {
+                self.goto_note(&id)?;
+                Ok(false)
+            }
+            Command::NotesSearch(query) => {
+                self.search_notes(query);
+                Ok(false)
+            }
+            Command::Highlights(color) => {
+                self.show_highlights(color);
+                Ok(false)
+            }
+            Command::NotesFollow => {
+                self.toggle_notes_follow();
+                Ok(false)
+            }
+            Command::NotesReplace(expr) => {
+                self.preview_note_replace(&expr);
+                Ok(false)
+            }
+            Command::NoteDelete(id) => {
+                self.delete_note_by_id(&id);
+                Ok(false)
+            }
+            Command::Capture(text) => {
+                self.capture_note(&text);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Send a question to Claude and start streaming the response
+    fn ask_claude(&mut self, question: &str) {
+        // Check if already streaming
+        if self.state.claude.streaming {
+            self.state.command_line.set_error("Already waiting for Claude response");
+            return;
+        }
+
+        // Check for API key
+        if self.state.claude.needs_setup {
+            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
+            return;
+        }
+
+        // Get API key
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
+                return;
+            }
+        };
+
+        // Clear previous response and set streaming state
+        self.state.claude.clear_streaming();
+        self.state.claude.streaming = true;
+        self.state.command_line.set_message("Asking Claude...");
+
+        // Create the client and message
+        let client = self.claude_client(api_key);
+        let messages = vec![crate::claude::Message::user(question)];
+        let request = self
+            .claude_request(messages)
+            .with_system(self.system_prompt("Answer the reader's questions concisely."));
+
+        // Create channel and cancellation token
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let cancel_token = tokio_util::sync::CancellationToken::new();
+
+        self.claude_rx = Some(rx);
+        self.claude_cancel = Some(cancel_token.clone());
+
+        // Spawn the streaming task
+        tokio::spawn(async move {
+            if let Err(e) = client.send_streaming(request, tx, cancel_token).await {
+                tracing::error!("Claude API error: {}", e);
+            }
+        });
+    }
+
+    /// Explain the current section using Claude
+    fn explain_section(&mut self, topic: Option<&str>) {
+        // Check if already streaming
+        if self.state.claude.streaming {
+            self.state.command_line.set_error("Already waiting for Claude response");
+            return;
+        }
+
+        // Check for API key
+        if self.state.claude.needs_setup {
+            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
+            return;
+        }
+
+        // Get current section content
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            self.state.command_line.set_error("No section selected");
+            return;
+        };
+
+        let section_title = section.title.clone();
+        let section_path = section.path.clone();
+
+        // Keep the section's structure if it's too long (Claude has context limits)
+        let focus = self.state.content.cursor_mode.then_some(self.state.content.cursor_block);
+        let content = numbered_section_excerpt(section, focus, self.config.claude.context_chars);
+
+        // Get API key
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
+                return;
+            }
+        };
+
+        // Build the prompt; the section text is cached so follow-up questions are cheaper
+        let context =
+            format!("Here is a section from a book titled \"{}\":\n\n{}", section_title, content);
+        let question = if let Some(focus) = topic {
+            format!(
+                "Please explain {} in this context. Be concise.\n\n{}",
+                focus,
+                crate::claude::citations::CITATION_INSTRUCTION
+            )
+        } else {
+            format!(
+                "Please provide a brief explanation of the key concepts in this section. Be concise.\n\n{}",
+                crate::claude::citations::CITATION_INSTRUCTION
+            )
+        };
+
+        // Clear previous response and set streaming state
+        self.state.claude.clear_streaming();
+        self.state.claude.streaming = true;
+        self.state.claude.cited_section = Some(section_path);
+        self.state.command_line.set_message("Asking Claude to explain...");
+
+        // Create the client and message
+        let client = self.claude_client(api_key);
+        let messages = vec![crate::claude::Message::user_with_cached_context(context, question)];
+        let request = self
+            .claude_request(messages)
+            .with_system(self.system_prompt("Explain the concepts in the section you are given."));
+
+        // Create channel and cancellation token
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let cancel_token = tokio_util::sync::CancellationToken::new();
+
+        self.claude_rx = Some(rx);
+        self.claude_cancel = Some(cancel_token.clone());
+
+        // Spawn the streaming task
+        tokio::spawn(async move {
+            if let Err(e) = client.send_streaming(request, tx, cancel_token).await {
+                tracing::error!("Claude API error: {}", e);
+            }
+        });
+    }
+
+    /// Show the current chapter's summary, asking Claude for one if not cached
+    fn summarize_chapter(&mut self, refresh: bool) {
+        use crate::claude::summary::{SummaryCache, summary_prompt};
+
+        if self.state.claude.streaming {
+            self.state.command_line.set_error("Already waiting for Claude response");
+            return;
+        }
+
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+        let Some(chapter) = book.chapters.get(self.state.current_chapter) else {
+            self.state.command_line.set_error("No chapter selected");
+            return;
+        };
+        let book_id = book.metadata.id.clone();
+
+        if !refresh {
+            let cache = SummaryCache::load(&book_id).unwrap_or_default();
+            if let Some(summary) = cache.get(&chapter.path) {
+                self.state.claude.response = summary.text.clone();
+                self.state.claude.show_response = true;
+                self.state.claude.response_scroll = 0;
+                self.state.command_line.set_message(format!(
+                    "Summary of {} (cached; :summarize refresh to regenerate)",
+                    chapter.title
+                ));
+                return;
+            }
+        }
+
+        if self.state.claude.needs_setup {
+            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
+            return;
+        }
+
+        let prompt = summary_prompt(
+            &chapter.title,
+            &chapter_excerpt(chapter, self.config.claude.chapter_context_chars),
+        );
+        let chapter_path = chapter.path.clone();
+        let chapter_title = chapter.title.clone();
+
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
+                return;
+            }
+        };
+
+        self.state.claude.clear_streaming();
+        self.state.claude.streaming = true;
+        self.state.claude.pending_summary = Some((book_id, chapter_path, chapter_title.clone()));
+        self.state.command_line.set_message(format!("Summarizing {}...", chapter_title));
+
+        let client = self.claude_client(api_key);
+        let messages = vec![crate::claude::Message::user(&prompt)];
+        let request = self
+            .claude_request(messages)
+            .with_system(self.system_prompt("Summarize the chapter you are given."));
+
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let cancel_token = tokio_util::sync::CancellationToken::new();
+
+        self.claude_rx = Some(rx);
+        self.claude_cancel = Some(cancel_token.clone());
+
+        tokio::spawn(async move {
+            if let Err(e) = client.send_streaming(request, tx, cancel_token).await {
+                tracing::error!("Claude API error: {}", e);
+            }
+        });
+    }
+
+    /// Cache a just-completed chapter summary
+    fn save_chapter_summary(&mut self) {
+        let Some((book_id, chapter_path, title)) = self.state.claude.pending_summary.take() else {
+            return;
+        };
+
+        let mut cache = crate::claude::summary::SummaryCache::load(&book_id).unwrap_or_default();
+        cache.insert(&chapter_path, &title, &self.state.claude.response);
+        if let Err(e) = cache.save(&book_id) {
+            tracing::warn!("Failed to save summary: {}", e);
+        }
+    }
+
+    /// Show the book's glossary, asking Claude for the current chapter's terms
+    /// first if they haven't been extracted
+    fn show_glossary(&mut self, refresh: bool) {
+        use crate::learning::glossary::{Glossary, glossary_prompt};
+
+        if self.state.claude.streaming {
+            self.state.command_line.set_error("Already waiting for Claude response");
+            return;
+        }
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+        let Some(chapter) = book.chapters.get(self.state.current_chapter) else {
+            self.state.command_line.set_error("No chapter selected");
+            return;
+        };
+        let book_id = book.metadata.id.clone();
+
+        let glossary = Glossary::load(&book_id).unwrap_or_default();
+        if !refresh && glossary.chapters.contains_key(&chapter.path) {
+            self.state.claude.response = glossary.to_markdown(&book.metadata.title);
+            self.state.claude.show_response = true;
+            self.state.claude.response_scroll = 0;
+            self.state.command_line.set_message(format!(
+                "Glossary (:glossary refresh to extract the terms of {} again)",
+                chapter.title
+            ));
+            return;
+        }
+
+        if self.state.claude.needs_setup {
+            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
+            return;
+        }
+
+        let prompt = glossary_prompt(
+            &chapter.title,
+            &chapter_excerpt(chapter, self.config.claude.chapter_context_chars),
+        );
+        let chapter_path = chapter.path.clone();
+        let chapter_title = chapter.title.clone();
+
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
+                return;
+            }
+        };
+
+        self.state.claude.clear_streaming();
+        self.state.claude.streaming = true;
+        self.state.claude.pending_glossary = Some((book_id, chapter_path, chapter_title.clone()));
+        self.state.command_line.set_message(format!("Finding key terms in {}...", chapter_title));
+
+        let client = self.claude_client(api_key);
+        let messages = vec![crate::claude::Message::user(&prompt)];
+        let request = self
+            .claude_request(messages)
+            .with_system(self.system_prompt("List the key terms of the chapter you are given."));
+
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let cancel_token = tokio_util::sync::CancellationToken::new();
+
+        self.claude_rx = Some(rx);
+        self.claude_cancel = Some(cancel_token.clone());
+
+        tokio::spawn(async move {
+            if let Err(e) = client.send_streaming(request, tx, cancel_token).await {
+                tracing::error!("Claude API error: {}", e);
+            }
+        });
+    }
+
+    /// Add a just-extracted chapter's terms to the glossary and show it
+    fn save_chapter_glossary(&mut self) {
+        use crate::learning::glossary::{Glossary, parse_terms};
+
+        let Some((book_id, chapter_path, title)) = self.state.claude.pending_glossary.take() else {
+            return;
+        };
+        let terms = parse_terms(&self.state.claude.response);
+        if terms.is_empty() {
+            self.state.command_line.set_error("No terms found in Claude's answer");
+            return;
+        }
+
+        let mut glossary = Glossary::load(&book_id).unwrap_or_default();
+        glossary.insert(&chapter_path, &title, terms);
+        if let Err(e) = glossary.save(&book_id) {
+            tracing::warn!("Failed to save glossary: {}", e);
+        }
+        if self.state.book.as_ref().is_some_and(|b| b.metadata.id == book_id) {
+            self.state.content.glossary = glossary.matcher();
+        }
+        let book_title = self.state.book.as_ref().map_or(book_id.as_str(), |b| &b.metadata.title);
+        self.state.claude.response = glossary.to_markdown(book_title);
+        self.state.claude.response_scroll = 0;
+    }
+
+    /// Ask the current model and the study buddy model the same question
+    fn compare_answers(&mut self, question: &str) {
+        use crate::claude::buddy::{Comparison, answer_prompt};
+
+        if self.state.claude.streaming || self.buddy_rx.is_some() {
+            self.state.command_line.set_error("Already waiting for Claude response");
+            return;
+        }
+        if self.state.claude.needs_setup {
+            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
+            return;
+        }
+
+        let model = self.claude_model();
+        let Some(buddy) = self.models.resolve(&self.config.claude.buddy_model) else {
+            self.state.command_line.set_error(format!(
+                "Unknown buddy model '{}'. Set claude.buddy_model to haiku, sonnet or opus",
+                self.config.claude.buddy_model
+            ));
+            return;
+        };
+        if buddy == model {
+            self.state.command_line.set_error(format!(
+                "Buddy model is the current model ({}). Switch with :claude-model or set claude.buddy_model",
+                self.models.display_name(&model)
+            ));
+            return;
+        }
+
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            self.state.command_line.set_error("No section selected");
+            return;
+        };
+        let prompt = answer_prompt(
+            &section.title,
+            &quiz_content(section, self.config.claude.context_chars),
+            question,
+        );
+
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
+                return;
+            }
+        };
+
+        let comparison = Comparison::new(question, [model.clone(), buddy.clone()]);
+        self.state.claude.clear_streaming();
+        self.state.claude.response = comparison.to_markdown();
+        self.state.claude.comparison = Some(comparison);
+        self.state.claude.show_response = true;
+        self.state.claude.response_scroll = 0;
+        self.state.command_line.set_message(format!(
+            "Asking {} and {}...",
+            self.models.display_name(&model),
+            self.models.display_name(&buddy)
+        ));
+
+        let system = self.system_prompt("Answer the reader's question about the section.");
+        let (tx, rx) = tokio::sync::mpsc::channel(2);
+        self.buddy_rx = Some(rx);
+        for (index, model) in [model, buddy].into_iter().enumerate() {
+            let client = self.claude_client(api_key.clone());
+            let settings = self.request_settings(model, self.config.claude.max_tokens);
+            let (usage_tx, tx) = (self.usage_tx.clone(), tx.clone());
+            let (system, prompt) = (system.clone(), prompt.clone());
+            tokio::spawn(async move {
+                let result = ask_once(client, settings, usage_tx, Some(system), prompt).await;
+                let _ = tx.send((index, result)).await;
+            });
+        }
+    }
+
+    /// Show study buddy answers as they arrive
+    fn process_buddy_answers(&mut self) {
+        let Some(rx) = &mut self.buddy_rx else { return };
+        while let Ok((index, result)) = rx.try_recv() {
+            let Some(comparison) = &mut self.state.claude.comparison else { continue };
+            comparison.answers[index].result = Some(result);
+            self.state.claude.response = comparison.to_markdown();
+        }
+
+        let Some(comparison) = &self.state.claude.comparison else {
+            // Replaced by another response; drop the remaining answer
+            self.buddy_rx = None;
+            return;
+        };
+        if comparison.is_complete() {
+            let diff = comparison.diff();
+            self.state.command_line.set_message(format!(
+                "Answers compared: {} shared claims, {} and {} differing",
+                diff.shared.len(),
+                diff.only_first.len(),
+                diff.only_second.len()
+            ));
+            self.buddy_rx = None;
+        }
+    }
+
+    /// Chapter office hours: ask for an explanation of the chapter, or critique one
+    ///
+    /// Without an explanation this starts a session on the chapter just read:
+    /// the current one, or the previous one when the reader has just moved on
+    /// to the first section of a new chapter.
+    fn recap_chapter(&mut self, explanation: Option<&str>) {
+        use crate::claude::recap::{RecapSession, critique_prompt, opening_prompt};
+
+        if self.state.claude.streaming {
+            self.state.command_line.set_error("Already waiting for Claude response");
+            return;
+        }
+        if self.state.claude.needs_setup {
+            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
+            return;
+        }
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+
+        let prompt = match explanation {
+            None => {
+                let index = if self.state.current_section == 0 {
+                    self.state.current_chapter.saturating_sub(1)
+                } else {
+                    self.state.current_chapter
+                };
+                let Some(chapter) = book.chapters.get(index) else {
+                    self.state.command_line.set_error("No chapter selected");
+                    return;
+                };
+                let Some(first_section) = chapter.sections.first() else {
+                    self.state.command_line.set_error("Chapter has no sections");
+                    return;
+                };
+                self.state.claude.recap = Some(RecapSession {
+                    book_id: book.metadata.id.clone(),
+                    chapter_path: chapter.path.clone(),
+                    chapter_title: chapter.title.clone(),
+                    note_section_path: first_section.path.clone(),
+                    ..Default::default()
+                });
+                self.state
+                    .command_line
+                    .set_message(format!("Starting office hours for {}...", chapter.title));
+                opening_prompt(
+                    &chapter.title,
+                    &chapter_excerpt(chapter, self.config.claude.chapter_context_chars),
+                )
+            }
+            Some(explanation) => {
+                let Some(session) = self
+                    .state
+                    .claude
+                    .recap
+                    .as_mut()
+                    .filter(|s| s.book_id == book.metadata.id && !s.questions.is_empty())
+                else {
+                    self.state.command_line.set_error("Start office hours with :recap first");
+                    return;
+                };
+                let Some(chapter) = book.chapters.iter().find(|c| c.path == session.chapter_path)
+                else {
+                    self.state.command_line.set_error("Chapter no longer in book");
+                    return;
+                };
+                session.explanation = Some(explanation.to_string());
+                self.state.command_line.set_message("Critiquing your explanation...");
+                critique_prompt(
+                    &chapter.title,
+                    &chapter_excerpt(chapter, self.config.claude.chapter_context_chars),
+                    &session.questions,
+                    explanation,
+                )
+            }
+        };
+
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => {
+                self.reset_recap_step();
+                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
+                return;
+            }
+        };
+
+        self.state.claude.clear_streaming();
+        self.state.claude.streaming = true;
+
+        let client = self.claude_client(api_key);
+        let messages = vec![crate::claude::Message::user(&prompt)];
+        let request = self.claude_request(messages).with_system(self.system_prompt(
+            "You are holding office hours with a reader who has just finished a chapter.",
+        ));
+
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let cancel_token = tokio_util::sync::CancellationToken::new();
+
+        self.claude_rx = Some(rx);
+        self.claude_cancel = Some(cancel_token.clone());
+
+        tokio::spawn(async move {
+            if let Err(e) = client.send_streaming(request, tx, cancel_token).await {
+                tracing::error!("Claude API error: {}", e);
+            }
+        });
+    }
+
+    /// Move an office hours session on once a response completes
+    ///
+    /// Returns whether the response belonged to the session.
+    fn advance_recap(&mut self) -> bool {
+        let Some(session) = &mut self.state.claude.recap else { return false };
+
+        if session.questions.is_empty() {
+            session.questions = self.state.claude.response.clone();
+            self.state.command_line.set_message("Explain it back with :recap <your explanation>");
+            return true;
+        }
+        let Some(explanation) = session.explanation.take() else { return false };
+
+        let Some(session) = self.state.claude.recap.take() else { return false };
+        let content = crate::claude::recap::recap_note(
+            &session.chapter_title,
+            &session.questions,
+            &explanation,
+            &self.state.claude.response,
+        );
+        let mut note = crate::notes::Note::new_section_note(
+            &session.book_id,
+            &session.note_section_path,
+            &content,
+        );
+        note.source = crate::notes::NoteSource::Claude;
+        self.notes_store.add_note(note);
+        if let Err(e) = self.notes_store.save() {
+            tracing::warn!("Failed to save office hours note: {}", e);
+        }
+        self.state.panel_visibility.notes = true;
+        self.state
+            .command_line
+            .set_message(format!("Office hours for {} saved as a note", session.chapter_title));
+        true
+    }
+
+    /// Explain the current section back for Claude to grade; without an
+    /// explanation this opens the command line to type one
+    fn teach_back(&mut self, explanation: Option<&str>) {
+        use crate::claude::teachback::{TeachBack, grading_prompt};
+
+        let Some(explanation) = explanation else {
+            self.state.command_line.enter_command_with("teach ");
+            self.state.command_line.set_message("Explain the section in your own words");
+            return;
+        };
+        if self.state.claude.streaming {
+            self.state.command_line.set_error("Already waiting for Claude response");
+            return;
+        }
+        if self.state.claude.needs_setup {
+            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
+            return;
+        }
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            self.state.command_line.set_error("No section selected");
+            return;
+        };
+
+        let prompt = grading_prompt(
+            &section.title,
+            &quiz_content(section, self.config.claude.context_chars),
+            explanation,
+        );
+        let teach_back = TeachBack {
+            book_id: book.metadata.id.clone(),
+            section_path: section.path.clone(),
+            section_title: section.title.clone(),
+            explanation: explanation.to_string(),
+        };
+
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
+                return;
+            }
+        };
+
+        self.state.claude.clear_streaming();
+        self.state.claude.streaming = true;
+        self.state.command_line.set_message("Grading your explanation...");
+        self.state.claude.pending_teach_back = Some(teach_back);
+
+        let client = self.claude_client(api_key);
+        let messages = vec![crate::claude::Message::user(&prompt)];
+        let request = self.claude_request(messages).with_system(self.system_prompt(
+            "You are grading a reader's explanation of a section they just finished.",
+        ));
+
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let cancel_token = tokio_util::sync::CancellationToken::new();
+
+        self.claude_rx = Some(rx);
+        self.claude_cancel = Some(cancel_token.clone());
+
+        tokio::spawn(async move {
+            if let Err(e) = client.send_streaming(request, tx, cancel_token).await {
+                tracing::error!("Claude API error: {}", e);
+            }
+        });
+    }
+
+    /// Save a graded teach-back once its response completes: the feedback as
+    /// a note, the grade in the section's progress, and a passing grade
+    /// toward the book's mastery rating
+    ///
+    /// Returns whether the response graded a teach-back.
+    fn finish_teach_back(&mut self) -> bool {
+        use crate::claude::teachback::{PASS_GRADE, grade, teach_back_note};
+        use crate::config::progress::TeachBackGrade;
+
+        let Some(teach_back) = self.state.claude.pending_teach_back.take() else { return false };
+        let feedback = &self.state.claude.response;
+
+        let content = teach_back_note(&teach_back.section_title, &teach_back.explanation, feedback);
+        let mut note = crate::notes::Note::new_section_note(
+            &teach_back.book_id,
+            &teach_back.section_path,
+            &content,
+        );
+        note.source = crate::notes::NoteSource::Claude;
+        self.notes_store.add_note(note);
+        if let Err(e) = self.notes_store.save() {
+            tracing::warn!("Failed to save teach-back note: {}", e);
+        }
+        self.state.panel_visibility.notes = true;
+
+        let Some(grade) = grade(feedback) else {
+            self.state.command_line.set_message("Teach-back feedback saved as a note (no grade)");
+            return true;
+        };
+        let at = datetime::now();
+        let book_progress = self.progress.book_mut(&teach_back.book_id);
+        book_progress
+            .sections
+            .entry(teach_back.section_path)
+            .or_default()
+            .teach_backs
+            .push(TeachBackGrade { at, grade });
+        let passed = grade >= PASS_GRADE;
+        if passed {
+            book_progress.quiz_ratings.record_teach_back();
+        }
+        self.save_progress();
+
+        self.state.command_line.set_message(format!(
+            "Teach-back graded {}/5, saved as a note{}",
+            grade,
+            if passed { " (counts toward mastery)" } else { "; try :teach again" }
+        ));
+        true
+    }
+
+    /// Show or fold the current section's learning objectives, asking Claude
+    /// to write them first if the section has none (or on `refresh`)
+    fn show_objectives(&mut self, refresh: bool) {
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            self.state.command_line.set_error("No section selected");
+            return;
+        };
+        if refresh || self.objectives.get(&section.path).is_none() {
+            self.write_objectives(false);
+            return;
+        }
+        let collapsed = !self.state.content.objectives_collapsed;
+        self.state.content.objectives_collapsed = collapsed;
+        self.state.command_line.set_message(if collapsed {
+            "Objectives folded (:objectives to show them)"
+        } else {
+            "Check objectives off with :objective <n> (:objectives refresh to rewrite them)"
+        });
+    }
+
+    /// Ask Claude in the background for the current section's learning
+    /// objectives; `auto` requests (on opening a section) fail quietly
+    fn write_objectives(&mut self, auto: bool) {
+        use crate::learning::objectives::objectives_prompt;
+
+        let error = |app: &mut Self, message: &str| {
+            if !auto {
+                app.state.command_line.set_error(message);
+            }
+        };
+        if self.objectives_rx.is_some() {
+            return error(self, "Already writing learning objectives");
+        }
+        if self.state.claude.needs_setup {
+            return error(self, "API key not set. Use :claude-key <key>");
+        }
+        let Some(book) = &self.state.book else { return };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            return;
+        };
+        let prompt = objectives_prompt(
+            &section.title,
+            &quiz_content(section, self.config.claude.context_chars),
+        );
+        let (book_id, section_path, section_title) =
+            (book.metadata.id.clone(), section.path.clone(), section.title.clone());
+
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => return error(self, &format!("Failed to get API key: {}", e)),
+        };
+        if !auto {
+            self.state
+                .command_line
+                .set_message(format!("Writing learning objectives for {}...", section_title));
+        }
+
+        let client = self.claude_client(api_key);
+        let settings = self.request_settings(self.claude_model(), self.config.claude.max_tokens);
+        let usage_tx = self.usage_tx.clone();
+        let system = self.system_prompt("Write learning objectives for the section you are given.");
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.objectives_rx = Some(rx);
+        tokio::spawn(async move {
+            let result = ask_once(client, settings, usage_tx, Some(system), prompt).await;
+            let _ = tx.send((book_id, section_path, section_title, result)).await;
+        });
+    }
+
+    /// Keep and show learning objectives once Claude has written them
+    fn process_objectives(&mut self) {
+        use crate::learning::objectives::parse_objectives;
+
+        let Some(rx) = &mut self.objectives_rx else { return };
+        let Ok((book_id, section_path, title, result)) = rx.try_recv() else { return };
+        self.objectives_rx = None;
+
+        let objectives = match result.map(|text| parse_objectives(&text)) {
+            Ok(objectives) if !objectives.is_empty() => objectives,
+            Ok(_) => {
+                self.state.command_line.set_error("No objectives found in Claude's answer");
+                return;
+            }
+            Err(e) => {
+                self.state.command_line.set_error(format!("Objectives error: {}", e));
+                return;
+            }
+        };
+        let count = objectives.len();
+        let written = objectives.clone();
+        let open = self.state.book.as_ref().is_some_and(|b| b.metadata.id == book_id);
+        match Objectives::load(&book_id) {
+            Ok(mut saved) => {
+                saved.insert(&section_path, &title, objectives);
+                if let Err(e) = saved.save(&book_id) {
+                    tracing::warn!("Failed to save objectives: {}", e);
+                }
+                if open {
+                    self.objectives = saved;
+                }
+            }
+            Err(e) => {
+                // Shown for now, but the file that failed to load isn't replaced
+                tracing::warn!("Failed to load objectives: {:#}", e);
+                if open {
+                    self.objectives.insert(&section_path, &title, objectives);
+                    self.state.content.objectives_collapsed = false;
+                }
+                self.state.command_line.set_error(format!(
+                    "Objectives not saved, as the saved ones failed to load: {:#}",
+                    e
+                ));
+                return;
+            }
+        }
+
+        // Checks carry over only to objectives worded the same
+        let section = self.progress.book_mut(&book_id).sections.entry(section_path).or_default();
+        let checked = section.objectives_met.len();
+        section.objectives_met.retain(|objective| written.contains(objective));
+        if section.objectives_met.len() != checked {
+            self.save_progress();
+        }
+        self.state.content.objectives_collapsed = false;
+        self.state.command_line.set_message(format!(
+            "{} learning objectives for {} (check them off with :objective <n>)",
+            count, title
+        ));
+    }
+
+    /// Check one of the current section's learning objectives off, or
+    /// uncheck it, moving the reader's mastery either way
+    fn toggle_objective(&mut self, number: usize) {
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            self.state.command_line.set_error("No section selected");
+            return;
+        };
+        let objectives = self.objectives.get(&section.path).map_or(&[][..], |s| &s.objectives);
+        let count = objectives.len();
+        if count == 0 {
+            self.state.command_line.set_error("No objectives for this section; see :objectives");
+            return;
+        }
+        if !(1..=count).contains(&number) {
+            self.state.command_line.set_error(format!("Objectives are numbered 1-{}", count));
+            return;
+        }
+
+        let book_progress = self.progress.book_mut(&book.metadata.id);
+        let met_set =
+            &mut book_progress.sections.entry(section.path.clone()).or_default().objectives_met;
+        let objective = &objectives[number - 1];
+        let met = met_set.insert(objective.clone());
+        if !met {
+            met_set.remove(objective);
+        }
+        let met_count = objectives.iter().filter(|o| met_set.contains(*o)).count();
+        book_progress.quiz_ratings.record_objective(met);
+        self.save_progress();
+
+        self.state.command_line.set_message(format!(
+            "Objective {} {} ({}/{} met)",
+            number,
+            if met { "met" } else { "unchecked" },
+            met_count,
+            count
+        ));
+    }
+
+    /// Undo the office hours step whose request failed or was cancelled
+    fn reset_recap_step(&mut self) {
+        let Some(session) = &mut self.state.claude.recap else { return };
+        if session.explanation.take().is_none() && session.questions.is_empty() {
+            self.state.claude.recap = None;
+        }
+    }
+
+    /// Get currently selected text (if in visual mode with selection)
+    fn get_selected_text(&self) -> Option<String> {
+        if !self.state.visual_mode.active {
+            return None;
+        }
+
+        let book = self.state.book.as_ref()?;
+        let section = book.get_section(self.state.current_chapter, self.state.current_section)?;
+
+        let (start_block, start_char, end_block, end_char) = self
+            .state
+            .visual_mode
+            .selection_range(self.state.content.cursor_block, self.state.content.cursor_char);
+
+        // For single-block selection
+        // end_char is inclusive (the cursor position), so we need +1 to include it
+        if start_block == end_block {
+            let text = block_text(section.content.get(start_block)?)?;
+            Some(text.chars().skip(start_char).take(end_char - start_char + 1).collect())
+        } else {
+            // Multi-block selection: collect text from all blocks
+            let mut result = String::new();
+            for block_idx in start_block..=end_block {
+                let Some(block) = section.content.get(block_idx) else {
+                    continue;
+                };
+                let Some(text) = block_text(block) else {
+                    continue;
+                };
+
+                if block_idx == start_block {
+                    result.push_str(&text.chars().skip(start_char).collect::<String>());
+                } else if block_idx == end_block {
+                    // end_char is inclusive, so take end_char + 1 characters
+                    result.push_str(&text.chars().take(end_char + 1).collect::<String>());
+                } else {
+                    result.push_str(&text);
+                }
+                result.push('\n');
+            }
+            Some(result.trim().to_string())
+        }
+    }
+
+    /// Copy selected text to clipboard (yank)
+    fn yank_selection(&mut self) {
+        let Some(text) = self.get_selected_text() else {
+            self.state.command_line.set_error("No text selected");
+            return;
+        };
+
+        if text.is_empty() {
+            self.state.command_line.set_error("No text selected");
+            return;
+        }
+
+        if self.copy_to_clipboard(&text) {
+            let char_count = text.chars().count();
+            self.state.command_line.set_message(format!("Yanked {} characters", char_count));
+            // Exit visual mode after yanking
+            self.state.visual_mode.exit();
+        }
+    }
+
+    /// Put text on the system clipboard, showing an error if that fails
+    fn copy_to_clipboard(&mut self, text: &str) -> bool {
+        let result = arboard::Clipboard::new()
+            .map_err(|e| format!("Clipboard unavailable: {}", e))
+            .and_then(|mut clipboard| {
+                clipboard.set_text(text).map_err(|e| format!("Failed to copy: {}", e))
+            });
+        match result {
+            Ok(()) => true,
+            Err(message) => {
+                self.state.command_line.set_error(message);
+                false
+            }
+        }
+    }
+
+    /// The code block under the cursor, showing an error if there isn't one
+    fn code_block_at_cursor(&mut self) -> Option<crate::book::CodeBlock> {
+        let code = self
+            .state
+            .book
+            .as_ref()
+            .and_then(|book| {
+                book.get_section(self.state.current_chapter, self.state.current_section)
+            })
+            .and_then(|section| section.content.get(self.state.content.cursor_block))
+            .filter(|_| self.state.content.cursor_mode)
+            .and_then(|block| match block {
+                crate::book::ContentBlock::Code(code) => Some(code.clone()),
+                _ => None,
+            });
+        if code.is_none() {
+            self.state
+                .command_line
+                .set_error("Move the cursor onto a code block first (v enters cursor mode)");
+        }
+        code
+    }
+
+    /// Copy the whole code block under the cursor (`Y`)
+    fn yank_code_block(&mut self) {
+        let Some(code) = self.code_block_at_cursor() else { return };
+        if self.copy_to_clipboard(&code.code) {
+            let lines = code.code.lines().count();
+            self.state.command_line.set_message(format!(
+                "Yanked code block ({} line{})",
+                lines,
+                if lines == 1 { "" } else { "s" }
+            ));
+        }
+    }
+
+    /// Save the code block under the cursor to a file (`:wcode`)
+    fn write_code_block(&mut self, path: &std::path::Path) {
+        let Some(code) = self.code_block_at_cursor() else { return };
+        let path = Self::expand_path(path);
+        let mut contents = code.code;
+        if !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        match std::fs::write(&path, contents) {
+            Ok(()) => {
+                self.state.command_line.set_message(format!("Saved code to {}", path.display()))
+            }
+            Err(e) => self.state.command_line.set_error(format!(
+                "Failed to write {}: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    /// Run the code block under the cursor with its language's runner (`:run`)
+    fn run_code_block(&mut self) {
+        if self.code_run_rx.is_some() {
+            self.state.command_line.set_error("Already running code");
+            return;
+        }
+        let Some(code) = self.code_block_at_cursor() else { return };
+        let language = code.language_name().unwrap_or("code").to_string();
+        let Some(runner) = crate::runner::runner_for(&self.config.code_runners, &code).cloned()
+        else {
+            self.state.command_line.set_error(format!(
+                "No runner for {} (add one under [code_runners.{}] in the config)",
+                language, language
+            ));
+            return;
+        };
+
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.code_run_rx = Some(rx);
+        self.state.command_line.set_message(format!("Running {}...", language));
+        tokio::spawn(async move {
+            let output = crate::runner::run(&runner, &code.code).await;
+            let _ = tx.send((language, output)).await;
+        });
+    }
+
+    /// Open the link under the cursor, or the next one in its block, in the
+    /// default browser (`:open-link`)
+    fn open_link(&mut self) {
+        if !self.state.content.cursor_mode {
+            self.state
+                .command_line
+                .set_error("Move the cursor onto a link first (v enters cursor mode)");
+            return;
+        }
+        let text = self.get_block_text(self.state.content.cursor_block).unwrap_or_default();
+        let offset = text
+            .char_indices()
+            .nth(self.state.content.cursor_char)
+            .map_or(text.len(), |(offset, _)| offset);
+        let Some(link) = crate::ui::hyperlink::link_at(&text, offset) else {
+            self.state.command_line.set_error("No link at the cursor");
+            return;
+        };
+        match crate::ui::hyperlink::open(&link.url) {
+            Ok(()) => self.state.command_line.set_message(format!("Opened {}", link.url)),
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to open {}: {}", link.url, e))
+            }
+        }
+    }
+
+    /// Show the output of a finished code run (non-blocking)
+    fn process_code_runs(&mut self) {
+        let Some(rx) = &mut self.code_run_rx else { return };
+        let Ok((language, output)) = rx.try_recv() else { return };
+        self.code_run_rx = None;
+
+        match output {
+            Ok(output) => {
+                self.state.claude.response = output.to_markdown(&language);
+                self.state.claude.show_response = true;
+                self.state.claude.response_scroll = 0;
+                self.state.command_line.clear_message();
+            }
+            Err(e) => self.state.command_line.set_error(format!("Run failed: {:#}", e)),
+        }
+    }
+
+    /// Ask Claude about selected text (includes full section context)
+    fn ask_about_selection(&mut self, question: &str) {
+        let instructions = format!(
+            "Please provide a clear, concise answer that considers both the highlighted passage and its surrounding context.\n\n{}",
+            crate::claude::citations::CITATION_INSTRUCTION
+        );
+        self.query_selection(
+            question,
+            &instructions,
+            "Answer questions about the selected passage, using the surrounding context to provide more complete explanations when relevant.",
+        );
+    }
+
+    /// Ask Claude to draw the selected passage as a text diagram
+    fn diagram_selection(&mut self, kind: DiagramKind) {
+        let question = format!("Draw {} of this passage", kind.describe());
+        let instructions = format!(
+            "Draw {} using ASCII or Unicode box-drawing characters. Put the diagram alone \
+             in a single fenced code block no wider than {} columns, then explain it in two or \
+             three sentences. Label parts with the passage's own names.",
+            kind.describe(),
+            DIAGRAM_WIDTH
+        );
+        self.query_selection(
+            &question,
+            &instructions,
+            "Illustrate the selected passage with a clear, accurate text diagram.",
+        );
+    }
+
+    /// Send a request about the selected text, saving the exchange as a note
+    fn query_selection(&mut self, question: &str, instructions: &str, system: &str) {
+        // Check if already streaming
+        if self.state.claude.streaming {
+            self.state.command_line.set_error("Already waiting for Claude response");
+            return;
+        }
+
+        // Check for API key
+        if self.state.claude.needs_setup {
+            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
+            return;
+        }
+
+        // Get book and section info for context
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+
+        let Some(section) =
+            book.get_section(self.state.current_chapter, self.state.current_section)
+        else {
+            self.state.command_line.set_error("No section selected");
+            return;
+        };
+
+        let section_title = section.title.clone();
+        let section_path = section.path.clone();
+        let book_id = book.metadata.id.clone();
+
+        // Get selected text and selection info
+        let (selected_text, selection_block, selection_char) = if self.state.visual_mode.active {
+            let (start_block, start_char, _, _) = self
+                .state
+                .visual_mode
+                .selection_range(self.state.content.cursor_block, self.state.content.cursor_char);
+            match self.get_selected_text() {
+                Some(text) if !text.is_empty() => (text, Some(start_block), Some(start_char)),
+                _ => {
+                    self.state.command_line.set_error(
+                        "No text selected. Use 'v' to enter visual mode and select text.",
+                    );
+                    return;
+                }
+            }
+        } else {
+            self.state
+                .command_line
+                .set_error("No text selected. Use 'v' to enter visual mode and select text.");
+            return;
+        };
+
+        // Get API key
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
+                return;
+            }
+        };
+
+        // Fit the section around the selected block (the selection itself is sent whole),
+        // numbering its paragraphs only when the answer is asked to cite them
+        let cited = instructions.contains(crate::claude::citations::CITATION_INSTRUCTION);
+        let max_chars = self.config.claude.context_chars;
+        let context = if cited {
+            numbered_section_excerpt(section, selection_block, max_chars)
+        } else {
+            section_excerpt(section, selection_block, max_chars)
+        };
+
+        // Truncate selection display if too long
+        let selection_display = crate::book::excerpt::truncate(&selected_text, 2000);
+
+        // Build the prompt with full context and highlighted selection; the section
+        // text is cached so further questions about the same section are cheaper
+        let context = format!(
+            "Here is a section from the book titled \"{}\":\n\n---\n{}\n---",
+            section_title, context
+        );
+        let prompt = format!(
+            "The reader has highlighted this specific passage:\n\n\"\"\"\n{}\n\"\"\"\n\n\
+             Their question about this passage: {}\n\n{}",
+            selection_display, question, instructions
+        );
+
+        // Store pending note info for saving Q&A after response
+        self.state.claude.set_pending_note(
+            question,
+            &book_id,
+            &section_path,
+            Some(&selected_text),
+            selection_block,
+            selection_char,
+        );
+
+        // Exit visual mode, showing the answer arrive in the notes panel
+        self.state.visual_mode.exit();
+        self.state.panel_visibility.notes = true;
+
+        // Clear previous response and set streaming state
+        self.state.claude.clear_streaming();
+        self.state.claude.streaming = true;
+        self.state.claude.cited_section = cited.then(|| section_path.clone());
+        self.state
+            .command_line
+            .set_message(format!("Asking about selection ({} chars)...", selected_text.len()));
+
+        // Create the client and message
+        let client = self.claude_client(api_key);
+        let messages = vec![crate::claude::Message::user_with_cached_context(context, prompt)];
+        let request = self.claude_request(messages).with_system(self.system_prompt(system));
+
+        // Create channel and cancellation token
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let cancel_token = tokio_util::sync::CancellationToken::new();
+
+        self.claude_rx = Some(rx);
+        self.claude_cancel = Some(cancel_token.clone());
+
+        // Spawn the streaming task
+        tokio::spawn(async move {
+            if let Err(e) = client.send_streaming(request, tx, cancel_token).await {
+                tracing::error!("Claude API error: {}", e);
             }
-            Action::Top => {
-                self.state.notes.selected_index = 0;
+        });
+    }
+
+    /// Compare the open book with an older edition of it, or copy progress and
+    /// notes from that edition to the matching sections (`:editions`)
+    fn compare_editions(&mut self, query: &str, migrate: bool) {
+        use crate::book::compare::compare_editions;
+
+        let Some(new) = &self.state.book else {
+            self.state.command_line.set_error("Open the new edition first");
+            return;
+        };
+        let old = load_library_book(query);
+        let old = match old {
+            Ok(old) if old.metadata.id == new.metadata.id => {
+                self.state.command_line.set_error("That's the open book; name the other edition");
+                return;
             }
-            Action::Bottom => {
-                self.state.notes.selected_index = total_notes.saturating_sub(1);
+            Ok(old) => old,
+            Err(e) => {
+                self.state.command_line.set_error(e.to_string());
+                return;
             }
-            _ => {}
+        };
+
+        let changes = compare_editions(&old, new);
+        if migrate {
+            self.migrate_edition(&old, &changes);
+            return;
         }
+        let old_progress = self.progress.books.get(&old.metadata.id);
+        let report = edition_report(&old, new, &changes, old_progress, &self.notes_store, query);
+        self.state.claude.response = report;
+        self.state.claude.show_response = true;
+        self.state.claude.response_scroll = 0;
     }
 
-    /// Navigate the curriculum tree
-    fn navigate_curriculum(&mut self, action: Action) {
-        if self.state.book.is_none() {
+    /// Open a section in the split pane: `section` of `book`, defaulting to
+    /// the open book and, in it, the current section or one with the same path
+    fn open_split(&mut self, book: Option<&str>, section: Option<&str>) {
+        let Some(current) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
             return;
-        }
+        };
+        let other = match book.map(load_library_book) {
+            Some(Ok(other)) if other.metadata.id != current.metadata.id => Some(other),
+            Some(Ok(_)) | None => None,
+            Some(Err(e)) => {
+                self.state.command_line.set_error(e.to_string());
+                return;
+            }
+        };
+        let shown = other.as_ref().unwrap_or(current);
+        let current_path = current
+            .get_section(self.state.current_chapter, self.state.current_section)
+            .map(|s| s.path.as_str());
+        let found = match section {
+            Some(query) => shown.find_section(query),
+            None if other.is_none() => {
+                Some((self.state.current_chapter, self.state.current_section))
+            }
+            None => current_path.and_then(|path| shown.find_section(path)).or(Some((0, 0))),
+        };
+        let Some(title) = found.and_then(|(c, s)| shown.get_section(c, s)).map(|s| s.title.clone())
+        else {
+            self.state
+                .command_line
+                .set_error(format!("Section not found: {}", section.unwrap_or_default()));
+            return;
+        };
+        let (chapter, section) = found.unwrap_or_default();
+        let message = match &other {
+            Some(other) => format!("Split: {} · {}", other.metadata.title, title),
+            None => format!("Split: {}", title),
+        };
+        self.state.split = Some(state::SplitPane::new(other, chapter, section));
+        self.state.focused_panel = Panel::Content;
+        self.state.command_line.set_message(message);
+    }
 
-        // Calculate total items (chapters + visible sections)
-        let total_items = self.calculate_curriculum_items();
-        if total_items == 0 {
+    /// Turn synchronized scrolling of the split pane on or off
+    fn toggle_split_sync(&mut self) {
+        let Some(split) = self.state.split.as_mut() else {
+            self.state.command_line.set_error("No split open; use :split [section]");
             return;
+        };
+        let bound = split.toggle_scroll_bind(self.state.content.scroll_offset);
+        self.state.command_line.set_message(if bound {
+            "Scrolling both panes together"
+        } else {
+            "Scrolling panes separately"
+        });
+    }
+
+    /// Close the split pane
+    fn close_split(&mut self) {
+        if self.state.split.take().is_some() {
+            self.state.command_line.set_message("Split closed");
         }
+    }
 
-        match action {
-            Action::Up => {
-                if self.state.curriculum.selected_index > 0 {
-                    self.state.curriculum.selected_index -= 1;
+    /// Copy progress and notes from an older edition to the open book's
+    /// matching sections
+    fn migrate_edition(&mut self, old: &Book, changes: &[crate::book::compare::SectionChange]) {
+        use crate::book::compare::SectionChange;
+
+        let Some(new) = &self.state.book else { return };
+        let new_id = new.metadata.id.clone();
+        let paths: std::collections::HashMap<&str, &str> = changes
+            .iter()
+            .filter_map(|change| match change {
+                SectionChange::Matched { old_path, new_path, .. } => {
+                    Some((old_path.as_str(), new_path.as_str()))
                 }
-            }
-            Action::Down => {
-                if self.state.curriculum.selected_index < total_items - 1 {
-                    self.state.curriculum.selected_index += 1;
+                _ => None,
+            })
+            .collect();
+
+        let mut sections = 0;
+        if let Some(old_progress) = self.progress.books.get(&old.metadata.id).cloned() {
+            let book_progress = self.progress.book_mut(&new_id);
+            for (old_path, section) in &old_progress.sections {
+                if let Some(new_path) = paths.get(old_path.as_str()) {
+                    book_progress.sections.entry(new_path.to_string()).or_default().merge(section);
+                    sections += 1;
                 }
             }
-            Action::Top => {
-                self.state.curriculum.selected_index = 0;
+            self.save_progress();
+        }
+
+        // Notes already copied by an earlier migration aren't copied again
+        let existing: std::collections::HashSet<(String, i64)> = self
+            .notes_store
+            .get_book_notes(&new_id)
+            .iter()
+            .map(|n| (n.content.clone(), n.created_at))
+            .collect();
+        let copies: Vec<_> = self
+            .notes_store
+            .get_book_notes(&old.metadata.id)
+            .iter()
+            .filter(|n| !existing.contains(&(n.content.clone(), n.created_at)))
+            .filter_map(|n| paths.get(n.section_path.as_str()).map(|path| n.copy_to(&new_id, path)))
+            .collect();
+        let notes = copies.len();
+        for note in copies {
+            self.notes_store.add_note(note);
+        }
+        if notes > 0 {
+            if let Some(book) = &self.state.book {
+                self.notes_store.reanchor_book(book);
             }
-            Action::Bottom => {
-                self.state.curriculum.selected_index = total_items.saturating_sub(1);
+            if let Err(e) = self.notes_store.save() {
+                self.state.command_line.set_error(format!("Failed to save notes: {}", e));
+                return;
             }
-            _ => {}
         }
 
-        // Keep selection in view
-        self.state.curriculum.ensure_selection_visible();
+        self.state.command_line.set_message(format!(
+            "Migrated progress on {} section{} and {} note{} from {}",
+            sections,
+            if sections == 1 { "" } else { "s" },
+            notes,
+            if notes == 1 { "" } else { "s" },
+            old.metadata.title
+        ));
     }
 
-    /// Calculate total visible items in curriculum
-    fn calculate_curriculum_items(&self) -> usize {
-        let Some(book) = &self.state.book else { return 0 };
-
-        let mut count = 0;
-        for (chapter_idx, chapter) in book.chapters.iter().enumerate() {
-            count += 1; // Chapter itself
-            if self.state.curriculum.expanded_chapters.contains(&chapter_idx) {
-                count += chapter.sections.len();
+    /// Expand tilde in path to home directory
+    fn expand_path(path: &std::path::Path) -> std::path::PathBuf {
+        let path_str = path.to_string_lossy();
+        if let Some(stripped) = path_str.strip_prefix("~/") {
+            if let Ok(home) = std::env::var("HOME") {
+                return std::path::PathBuf::from(home).join(stripped);
+            }
+        } else if path_str == "~" {
+            if let Ok(home) = std::env::var("HOME") {
+                return std::path::PathBuf::from(home);
             }
         }
-        count
+        path.to_path_buf()
     }
 
-    /// Navigate content (scrolling)
-    fn navigate_content(&mut self, action: Action) {
-        // Handle footer-focused state
-        if self.state.content.footer_focused {
-            match action {
-                Action::Up => {
-                    // Exit footer, return to content
-                    self.state.content.exit_footer();
-                    self.state.command_line.clear_message();
-                }
-                Action::Left => {
-                    // Switch to previous button
-                    self.state.content.footer_prev();
-                }
-                Action::Right => {
-                    // Switch to next button
-                    self.state.content.footer_next();
+    /// Add a book from path
+    fn add_book(&mut self, path: &std::path::Path) -> Result<()> {
+        let expanded_path = Self::expand_path(path);
+        let title = expanded_path
+            .file_name()
+            .map_or_else(|| expanded_path.display().to_string(), |n| n.to_string_lossy().into());
+
+        // Parsing happens while adding, so both run in the background
+        self.load_book_in_background(title, BookLoadKind::Add, move || {
+            match storage::add_book(&expanded_path)? {
+                storage::Added::New(entry) => storage::load_book(&entry),
+                // Added before from this path, so just open it
+                storage::Added::Duplicate(existing)
+                    if expanded_path
+                        .canonicalize()
+                        .is_ok_and(|p| p == existing.metadata.source.path()) =>
+                {
+                    storage::load_book(&existing)
                 }
-                _ => {}
+                storage::Added::Duplicate(existing) => anyhow::bail!(
+                    "This is the same book as {} ({}); run `sensei add --update {}` to read it from this file",
+                    existing.metadata.title,
+                    existing.metadata.id,
+                    expanded_path.display()
+                ),
             }
-            return;
-        }
+        });
+        Ok(())
+    }
 
-        // Normal content scrolling
-        match action {
-            Action::Up => {
-                self.state.content.scroll_offset =
-                    self.state.content.scroll_offset.saturating_sub(2);
+    /// Remove a book from the library
+    fn remove_book(&mut self, book_id: &str) -> Result<()> {
+        // Load library
+        let mut library = match storage::Library::load() {
+            Ok(lib) => lib,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to load library: {}", e));
+                return Ok(());
             }
-            Action::Down => {
-                // Check if we're at max scroll and should enter footer
-                let max_scroll = self.state.content.max_scroll();
-                if self.state.content.scroll_offset >= max_scroll {
-                    // At bottom of content, move focus to footer
-                    self.state.content.enter_footer();
-                    self.state.command_line.set_message("[h/l] switch  [Enter] select  [k] back");
-                } else {
-                    self.state.content.scroll_offset += 2;
-                }
+        };
+
+        // Find the book by ID or (fuzzy) title
+        let entry_idx = match library.resolve(book_id) {
+            BookMatch::Found(entry) => {
+                library.entries.iter().position(|e| e.metadata.id == entry.metadata.id)
             }
-            Action::Top => {
-                self.state.content.scroll_offset = 0;
+            // Only a loose match, so ask before removing it
+            BookMatch::Likely(entry) => {
+                self.offer_book_choice(book_id, &[entry], true);
+                return Ok(());
             }
-            Action::Bottom => {
-                // Jump to max scroll position
-                self.state.content.scroll_offset = self.state.content.max_scroll();
+            BookMatch::Ambiguous(matches) => {
+                self.offer_book_choice(book_id, &matches, true);
+                return Ok(());
             }
-            _ => {}
-        }
-        // Clamp to valid range
-        self.state.content.clamp_scroll();
-    }
-
-    /// Handle page/half-page scrolling based on focused panel
-    fn handle_scroll(&mut self, action: Action) {
-        let scroll_amount = match action {
-            Action::PageUp | Action::PageDown => 20,
-            Action::HalfPageUp | Action::HalfPageDown => 10,
-            _ => return,
+            BookMatch::NotFound => None,
         };
 
-        match self.state.focused_panel {
-            Panel::Curriculum => {
-                let total_items = self.calculate_curriculum_items();
-                match action {
-                    Action::PageUp | Action::HalfPageUp => {
-                        self.state.curriculum.selected_index =
-                            self.state.curriculum.selected_index.saturating_sub(scroll_amount);
-                    }
-                    Action::PageDown | Action::HalfPageDown => {
-                        self.state.curriculum.selected_index =
-                            (self.state.curriculum.selected_index + scroll_amount)
-                                .min(total_items.saturating_sub(1));
-                    }
-                    _ => {}
-                }
-                // Keep selection in view
-                self.state.curriculum.ensure_selection_visible();
+        match entry_idx {
+            Some(idx) if library.entries[idx].shared => {
+                self.state.command_line.set_error(format!(
+                    "{} is in the shared library and can't be removed",
+                    library.entries[idx].metadata.title
+                ));
             }
-            Panel::Content => {
-                match action {
-                    Action::PageUp | Action::HalfPageUp => {
-                        self.state.content.scroll_offset =
-                            self.state.content.scroll_offset.saturating_sub(scroll_amount);
-                    }
-                    Action::PageDown | Action::HalfPageDown => {
-                        self.state.content.scroll_offset += scroll_amount;
+            Some(idx) => {
+                let removed_title = library.entries[idx].metadata.title.clone();
+                let removed_id = library.entries[idx].metadata.id.clone();
+
+                // Remove from library
+                library.entries.remove(idx);
+                if let Err(e) = library.save() {
+                    self.state.command_line.set_error(format!("Failed to save library: {}", e));
+                    return Ok(());
+                }
+
+                // If we removed the currently loaded book, clear it
+                if let Some(book) = &self.state.book {
+                    if book.metadata.id == removed_id {
+                        self.state.book = None;
+                        self.source_watch = None;
+                        self.state.split = None;
+                        self.book_settings = storage::BookSettings::default();
+                        self.apply_book_settings();
+                        self.state.current_chapter = 0;
+                        self.state.current_section = 0;
+                        self.state.curriculum.selected_index = 0;
+                        self.state.curriculum.expanded_chapters.clear();
+                        self.state.content.scroll_offset = 0;
                     }
-                    _ => {}
                 }
-                // Clamp to valid range
-                self.state.content.clamp_scroll();
+
+                self.state.command_line.set_message(format!("Removed: {}", removed_title));
+            }
+            None => {
+                self.state.command_line.set_error(format!("Book not found: {}", book_id));
             }
-            Panel::Notes => {}
         }
+        Ok(())
     }
 
-    /// Handle selection (Enter key)
-    fn handle_select(&mut self) {
-        // Handle Content panel with footer focused
-        if self.state.focused_panel == Panel::Content && self.state.content.footer_focused {
-            match self.state.content.footer_button_index {
-                0 => {
-                    // Take Quiz button
-                    self.start_quiz();
-                }
-                1 => {
-                    // Complete & Next button
-                    self.complete_section_and_next();
-                }
-                _ => {}
+    /// Refresh the currently loaded book (clear cache and reload)
+    fn refresh_current_book(&mut self) -> Result<()> {
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book currently loaded");
+            return Ok(());
+        };
+
+        let book_id = book.metadata.id.clone();
+        let book_title = book.metadata.title.clone();
+
+        // Clear the cache, then reparse in the background
+        self.load_book_in_background(book_title, BookLoadKind::Refresh, move || {
+            storage::reparse_book(&book_id)
+        });
+        Ok(())
+    }
+
+    /// Open a book by ID
+    fn open_book(&mut self, book_id: &str) -> Result<()> {
+        self.load_library_book(book_id, BookLoadKind::Open)
+    }
+
+    /// Find a book in the library by ID or (fuzzy) title and load it in the background
+    fn load_library_book(&mut self, book_id: &str, kind: BookLoadKind) -> Result<()> {
+        // Load library and find the entry
+        let library = match storage::Library::load() {
+            Ok(lib) => lib,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to load library: {}", e));
+                return Ok(());
+            }
+        };
+
+        // Try to find by ID first, then by (fuzzy) title
+        let entry = match library.resolve(book_id) {
+            BookMatch::Found(entry) | BookMatch::Likely(entry) => entry.clone(),
+            BookMatch::Ambiguous(matches) => {
+                self.offer_book_choice(book_id, &matches, false);
+                return Ok(());
+            }
+            BookMatch::NotFound => {
+                self.state.command_line.set_error(format!("Book not found: {}", book_id));
+                return Ok(());
             }
+        };
+
+        let title = entry.metadata.title.clone();
+        self.load_book_in_background(title, kind, move || storage::load_book(&entry));
+        Ok(())
+    }
+
+    /// List books matching an ambiguous query and wait for a numbered choice
+    fn offer_book_choice(&mut self, query: &str, matches: &[&LibraryEntry], remove: bool) {
+        if let [only] = matches {
+            self.state.command_line.set_message(format!(
+                "Remove {}? (y to confirm, Esc to cancel)",
+                only.metadata.title
+            ));
+            self.book_choice = Some(BookChoice { remove, ids: vec![only.metadata.id.clone()] });
             return;
         }
 
-        if self.state.focused_panel != Panel::Curriculum {
+        // Only digits 1-9 can be picked with a single key
+        let matches = &matches[..matches.len().min(9)];
+        let listed: Vec<String> = matches
+            .iter()
+            .enumerate()
+            .map(|(i, e)| format!("{} {}", i + 1, e.metadata.title))
+            .collect();
+        self.state.command_line.set_message(format!(
+            "Multiple books match \"{}\": {} (1-{}, Esc to cancel)",
+            query,
+            listed.join(", "),
+            matches.len()
+        ));
+        self.book_choice = Some(BookChoice {
+            remove,
+            ids: matches.iter().map(|e| e.metadata.id.clone()).collect(),
+        });
+    }
+
+    /// Handle a key while choosing between matched books
+    fn handle_book_choice(&mut self, key: KeyCode) {
+        let Some(choice) = self.book_choice.take() else { return };
+
+        let picked = match key {
+            KeyCode::Char('y') if choice.ids.len() == 1 => choice.ids.first(),
+            KeyCode::Char(c) => c
+                .to_digit(10)
+                .and_then(|n| (n as usize).checked_sub(1))
+                .and_then(|i| choice.ids.get(i)),
+            _ => None,
+        };
+        let Some(id) = picked else {
+            self.state.command_line.clear_message();
             return;
-        }
+        };
 
-        // Toggle chapter expansion or select section
-        let Some(book) = &self.state.book else { return };
+        let result = if choice.remove { self.remove_book(id) } else { self.open_book(id) };
+        if let Err(e) = result {
+            self.state.command_line.set_error(format!("Error: {}", e));
+        }
+    }
 
-        let mut current_idx = 0;
-        for (chapter_idx, chapter) in book.chapters.iter().enumerate() {
-            if current_idx == self.state.curriculum.selected_index {
-                // Toggle chapter expansion
-                if self.state.curriculum.expanded_chapters.contains(&chapter_idx) {
-                    self.state.curriculum.expanded_chapters.remove(&chapter_idx);
+    /// List available books
+    fn list_books(&mut self) {
+        match storage::Library::load() {
+            Ok(library) => {
+                if library.entries.is_empty() {
+                    self.state.command_line.set_message("No books. Use :add <path> to add one.");
                 } else {
-                    self.state.curriculum.expanded_chapters.insert(chapter_idx);
+                    let names: Vec<_> =
+                        library.entries.iter().map(|e| e.metadata.id.as_str()).collect();
+                    self.state.command_line.set_message(format!("Books: {}", names.join(", ")));
                 }
-                return;
             }
-            current_idx += 1;
-
-            if self.state.curriculum.expanded_chapters.contains(&chapter_idx) {
-                for (section_idx, _section) in chapter.sections.iter().enumerate() {
-                    if current_idx == self.state.curriculum.selected_index {
-                        // Select this section
-                        self.state.current_chapter = chapter_idx;
-                        self.state.current_section = section_idx;
-                        self.state.content.scroll_offset = 0;
-                        // Move focus to content
-                        self.state.focused_panel = Panel::Content;
-                        // Mark as viewed
-                        self.mark_section_viewed();
-                        return;
-                    }
-                    current_idx += 1;
-                }
+            Err(_) => {
+                self.state.command_line.set_message("No books. Use :add <path> to add one.");
             }
         }
     }
 
-    /// Handle command line input, returns true if should exit
-    async fn handle_command_line_input(&mut self, key: KeyCode) -> Result<bool> {
-        match key {
-            KeyCode::Esc => {
-                self.state.command_line.exit_input_mode();
-            }
-            KeyCode::Enter => {
-                let input = self.state.command_line.input.clone();
-                let mode = self.state.command_line.mode;
-
-                // Add to history before clearing
-                self.state.command_line.add_to_history(input.clone());
-                self.state.command_line.exit_input_mode();
+    /// Execute a search
+    fn execute_search(&mut self, query: &str) {
+        if query.is_empty() {
+            self.state.search.active = false;
+            self.state.search.query.clear();
+            self.state.command_line.clear_message();
+            return;
+        }
+        self.state.search.active = true;
+        self.state.search.query = query.to_string();
 
-                // Parse and execute
-                match mode {
-                    CommandMode::Command => {
-                        return self.execute_command(&input);
-                    }
-                    CommandMode::Search => {
-                        self.execute_search(&input);
-                    }
-                    CommandMode::Normal => {}
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+        if self.search_index.is_none() {
+            match crate::book::SearchIndex::load_or_build(book) {
+                Ok(index) => self.search_index = Some(index),
+                Err(e) => {
+                    self.state
+                        .command_line
+                        .set_error(format!("Failed to load search index: {}", e));
+                    return;
                 }
             }
-            KeyCode::Backspace => {
-                self.state.command_line.delete_char();
-            }
-            KeyCode::Delete => {
-                self.state.command_line.delete_char_forward();
-            }
-            KeyCode::Left => {
-                self.state.command_line.move_left();
-            }
-            KeyCode::Right => {
-                self.state.command_line.move_right();
-            }
-            KeyCode::Home => {
-                self.state.command_line.move_start();
-            }
-            KeyCode::End => {
-                self.state.command_line.move_end();
-            }
-            KeyCode::Up => {
-                self.state.command_line.history_up();
-            }
-            KeyCode::Down => {
-                self.state.command_line.history_down();
-            }
-            KeyCode::Char(c) => {
-                self.state.command_line.insert_char(c);
-            }
-            _ => {}
         }
-        Ok(false)
-    }
+        let Some(index) = &self.search_index else { return };
 
-    /// Execute a parsed command, returns true if should exit
-    fn execute_command(&mut self, input: &str) -> Result<bool> {
-        match parse_command(input) {
-            ParseResult::Ok(cmd) => self.run_command(cmd),
-            ParseResult::UnknownCommand(cmd) => {
-                self.state.command_line.set_error(format!("Unknown command: {}", cmd));
-                Ok(false)
-            }
-            ParseResult::MissingArgument(cmd) => {
-                self.state.command_line.set_error(format!("Missing argument for: {}", cmd));
-                Ok(false)
+        let hits = index.search(query);
+        if hits.is_empty() {
+            self.state.command_line.set_message(format!("No matches for: {}", query));
+            return;
+        }
+
+        let sections: Vec<&crate::book::Section> =
+            book.chapters.iter().flat_map(|c| &c.sections).collect();
+        let section_count = {
+            let mut ids: Vec<usize> = hits.iter().map(|hit| hit.section).collect();
+            ids.dedup();
+            ids.len()
+        };
+        let mut out = format!(
+            "# Search: {}\n\n{} match{} in {} section{}\n",
+            query,
+            hits.len(),
+            if hits.len() == 1 { "" } else { "es" },
+            section_count,
+            if section_count == 1 { "" } else { "s" }
+        );
+        let mut current_section = None;
+        for hit in hits.iter().take(MAX_SEARCH_RESULTS) {
+            let Some(indexed) = index.sections.get(hit.section) else { continue };
+            if current_section != Some(hit.section) {
+                current_section = Some(hit.section);
+                out.push_str(&format!("\n## {} (`{}`)\n\n", indexed.title, indexed.path));
+            }
+            // The index may lag behind the open book until it is refreshed
+            let text = sections
+                .get(hit.section)
+                .filter(|s| s.path == indexed.path)
+                .and_then(|s| s.content.get(hit.block))
+                .and_then(|block| block.plain_text());
+            if let Some(text) = text {
+                out.push_str(&format!("- {}\n", crate::book::search::snippet(&text, query, 100)));
             }
         }
+        if hits.len() > MAX_SEARCH_RESULTS {
+            out.push_str(&format!("\n…and {} more\n", hits.len() - MAX_SEARCH_RESULTS));
+        }
+        out.push_str("\nUse `:goto <path>` to jump to a section.\n");
+
+        self.state.claude.response = out;
+        self.state.claude.show_response = true;
+        self.state.claude.response_scroll = 0;
+        self.state.command_line.set_message(format!("{} matches for: {}", hits.len(), query));
     }
 
-    /// Run a command, returns true if should exit
-    fn run_command(&mut self, cmd: Command) -> Result<bool> {
-        match cmd {
-            Command::Quit => Ok(true),
-            Command::Help => {
-                self.state.screen = Screen::Help;
-                Ok(false)
-            }
-            Command::Add(path) => {
-                self.add_book(&path)?;
-                Ok(false)
-            }
-            Command::Open(book_id) => {
-                self.open_book(&book_id)?;
-                Ok(false)
-            }
-            Command::Remove(book_id) => {
-                self.remove_book(&book_id)?;
-                Ok(false)
-            }
-            Command::Refresh => {
-                self.refresh_current_book()?;
-                Ok(false)
-            }
-            Command::List => {
-                self.list_books();
-                Ok(false)
-            }
-            Command::Search(query) => {
-                self.execute_search(&query);
-                Ok(false)
-            }
-            Command::Goto(path) => {
-                self.goto_section(&path);
-                Ok(false)
-            }
-            Command::Nop => Ok(false),
-            Command::ClaudeSetup => {
-                self.start_claude_setup();
-                Ok(false)
+    /// Search every book in the library
+    fn search_library(&mut self, query: &str) {
+        let library = match storage::Library::load() {
+            Ok(library) => library,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to load library: {}", e));
+                return;
             }
-            Command::ClaudeKey(key) => {
-                match crate::claude::ApiKeyManager::set_api_key(&key) {
-                    Ok(()) => {
-                        self.state.claude.needs_setup = false;
-                        self.state.command_line.set_message("API key saved to keyring");
-                    }
+        };
+
+        let mut out = format!("# Library search: {}\n", query);
+        let mut total = 0;
+        for entry in &library.entries {
+            // Books added before indexing existed get their index built now
+            let index = match crate::book::SearchIndex::load(&entry.metadata.id) {
+                Ok(Some(index)) => index,
+                _ => match storage::load_book(entry)
+                    .and_then(|book| crate::book::SearchIndex::load_or_build(&book))
+                {
+                    Ok(index) => index,
                     Err(e) => {
-                        self.state.command_line.set_error(e.to_string());
+                        tracing::warn!("Skipping {} in search: {}", entry.metadata.id, e);
+                        continue;
                     }
+                },
+            };
+
+            let hits = index.search(query);
+            if hits.is_empty() {
+                continue;
+            }
+            total += hits.len();
+
+            let mut sections: Vec<(usize, usize)> = Vec::new();
+            for hit in &hits {
+                match sections.last_mut() {
+                    Some((section, count)) if *section == hit.section => *count += 1,
+                    _ => sections.push((hit.section, 1)),
                 }
-                Ok(false)
             }
-            Command::ClaudeModel(model_str) => {
-                if let Some(model) = crate::claude::ClaudeModel::parse(&model_str) {
-                    self.set_claude_model(model);
-                } else {
-                    self.state.command_line.set_error(format!(
-                        "Unknown model: {}. Options: haiku, sonnet35, sonnet, opus",
-                        model_str
+            out.push_str(&format!("\n## {} (`{}`)\n\n", entry.metadata.title, entry.metadata.id));
+            for (section, count) in sections.iter().take(MAX_SEARCH_SECTIONS_PER_BOOK) {
+                if let Some(indexed) = index.sections.get(*section) {
+                    out.push_str(&format!(
+                        "- {} (`{}`): {} match{}\n",
+                        indexed.title,
+                        indexed.path,
+                        count,
+                        if *count == 1 { "" } else { "es" }
                     ));
                 }
-                Ok(false)
-            }
-            Command::ClaudeClear => {
-                self.state.claude.clear_streaming();
-                self.state.claude.clear_error();
-                self.state.command_line.set_message("Claude state cleared");
-                Ok(false)
-            }
-            Command::Ask(question) => {
-                self.ask_claude(&question);
-                Ok(false)
-            }
-            Command::Explain(topic) => {
-                self.explain_section(topic.as_deref());
-                Ok(false)
             }
-            Command::AskSelection(question) => {
-                self.ask_about_selection(&question);
-                Ok(false)
+            if sections.len() > MAX_SEARCH_SECTIONS_PER_BOOK {
+                out.push_str(&format!(
+                    "- …and {} more sections\n",
+                    sections.len() - MAX_SEARCH_SECTIONS_PER_BOOK
+                ));
             }
         }
+
+        if total == 0 {
+            self.state
+                .command_line
+                .set_message(format!("No matches in the library for: {}", query));
+            return;
+        }
+        out.push_str("\nUse `:open <book>` then `/` to search within a book.\n");
+
+        self.state.claude.response = out;
+        self.state.claude.show_response = true;
+        self.state.claude.response_scroll = 0;
+        self.state.command_line.set_message(format!("{} matches in the library", total));
     }
 
-    /// Send a question to Claude and start streaming the response
-    fn ask_claude(&mut self, question: &str) {
-        // Check if already streaming
-        if self.state.claude.streaming {
-            self.state.command_line.set_error("Already waiting for Claude response");
+    /// Go to a specific section
+    fn goto_section(&mut self, path: &str) {
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        };
+
+        // Try to find section by path
+        let Some((chapter_idx, section_idx)) = book.find_section(path) else {
+            self.state.command_line.set_error(format!("Section not found: {}", path));
             return;
+        };
+        let title = book.chapters[chapter_idx].sections[section_idx].title.clone();
+        self.record_jump();
+        self.remember_scroll();
+        self.state.current_chapter = chapter_idx;
+        self.state.current_section = section_idx;
+        self.state.content.scroll_offset = self.remembered_scroll();
+        self.state.curriculum.expanded_chapters.insert(chapter_idx);
+        self.state.command_line.set_message(format!("→ {}", title));
+        self.mark_section_viewed();
+    }
+
+    // ==================== Claude Integration ====================
+
+    /// Process pending Claude streaming events (non-blocking)
+    ///
+    /// Returns whether the response panel changed.
+    fn process_claude_events(&mut self) -> bool {
+        // Collect events first to avoid borrow conflict
+        let events: Vec<_> = if let Some(ref mut rx) = self.claude_rx {
+            let mut collected = Vec::new();
+            while let Ok(event) = rx.try_recv() {
+                collected.push(event);
+            }
+            collected
+        } else {
+            Vec::new()
+        };
+
+        // Now process collected events
+        for event in events {
+            self.handle_claude_event(event);
+        }
+
+        self.release_streamed_text()
+    }
+
+    /// Show streamed text at the configured cadence, completing the response
+    /// once the stream has ended and all of its text is shown
+    fn release_streamed_text(&mut self) -> bool {
+        let claude = &mut self.state.claude;
+        if !claude.streaming {
+            return false;
+        }
+
+        let interval = self.config.claude.stream_interval();
+        let released = claude.stream_throttle.release(std::time::Instant::now(), interval);
+        let changed = released.is_some();
+        if let Some(text) = released {
+            claude.stream_buffer.push_str(&text);
+        }
+
+        if claude.stream_throttle.finished && claude.stream_throttle.is_empty() {
+            self.complete_claude_response();
+            return true;
         }
+        changed
+    }
 
-        // Check for API key
-        if self.state.claude.needs_setup {
-            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
-            return;
+    /// Finish a streamed response once all of its text is shown
+    fn complete_claude_response(&mut self) {
+        self.state.claude.finalize_response();
+
+        // Create note from Q&A if pending info exists
+        if self.state.claude.has_pending_note() {
+            self.save_claude_qa_as_note();
         }
+        self.save_chapter_summary();
+        self.save_chapter_glossary();
 
-        // Get API key
-        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
-            Ok(key) => key,
-            Err(e) => {
-                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
-                return;
-            }
-        };
+        if !self.advance_recap() && !self.finish_teach_back() {
+            self.state
+                .command_line
+                .set_message("Response ready (press 'c' to toggle, Esc to close)");
+        }
+        self.claude_rx = None;
+        self.claude_cancel = None;
+    }
 
-        // Clear previous response and set streaming state
-        self.state.claude.clear_streaming();
-        self.state.claude.streaming = true;
-        self.state.command_line.set_message("Asking Claude...");
+    /// Claude client with the configured retries, reporting them to the UI
+    fn claude_client(&self, api_key: String) -> crate::claude::ClaudeClient {
+        crate::claude::ClaudeClient::new(api_key)
+            .with_max_retries(self.config.claude.max_retries)
+            .with_retry_notifier(self.retry_tx.clone())
+    }
 
-        // Create the client and message
-        let client = crate::claude::ClaudeClient::new(api_key);
-        let messages = vec![crate::claude::Message::user(question)];
-        let request = crate::claude::CreateMessageRequest::new(self.state.claude.model, messages)
-            .with_system("You are a helpful assistant for a book reader application. Answer questions concisely.");
+    /// A streamed request to the current model, with the configured response
+    /// length and temperature
+    fn claude_request(
+        &self,
+        messages: Vec<crate::claude::Message>,
+    ) -> crate::claude::CreateMessageRequest {
+        self.request_settings(self.claude_model(), self.config.claude.max_tokens).request(messages)
+    }
 
-        // Create channel and cancellation token
-        let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let cancel_token = tokio_util::sync::CancellationToken::new();
+    /// Settings for a request made off the main task, capped at `max_tokens`
+    fn request_settings(
+        &self,
+        model: crate::claude::ClaudeModel,
+        max_tokens: u32,
+    ) -> RequestSettings {
+        RequestSettings { model, max_tokens, temperature: self.config.claude.temperature }
+    }
 
-        self.claude_rx = Some(rx);
-        self.claude_cancel = Some(cancel_token.clone());
+    /// The model requests use: the open book's, or the one chosen with
+    /// `:claude-model`
+    fn claude_model(&self) -> crate::claude::ClaudeModel {
+        self.book_settings
+            .model
+            .as_deref()
+            .and_then(|name| self.models.resolve(name))
+            .unwrap_or_else(|| self.state.claude.model.clone())
+    }
 
-        // Spawn the streaming task
-        tokio::spawn(async move {
-            if let Err(e) = client.send_streaming(request, tx, cancel_token).await {
-                tracing::error!("Claude API error: {}", e);
-            }
-        });
+    /// Name of the persona used with the open book
+    fn persona_name(&self) -> &str {
+        let book_id = self.state.book.as_ref().map(|b| b.metadata.id.as_str());
+        match &self.book_settings.persona {
+            Some(persona) => persona,
+            None => self.config.claude.persona_name(book_id),
+        }
     }
 
-    /// Explain the current section using Claude
-    fn explain_section(&mut self, topic: Option<&str>) {
-        // Check if already streaming
-        if self.state.claude.streaming {
-            self.state.command_line.set_error("Already waiting for Claude response");
+    /// System prompt for the active persona, followed by task-specific instructions
+    fn system_prompt(&self, task: &str) -> String {
+        let persona = self.config.claude.persona_prompt(self.persona_name());
+        let prompt = format!("{}\n\n{}", persona, task);
+        append_instruction(prompt, self.config.claude.language_instruction().as_deref())
+    }
+
+    /// Show the current persona, or set the persona for the current book
+    fn set_persona(&mut self, name: Option<&str>) {
+        let claude = &self.config.claude;
+        let available = claude.system_prompts.keys().cloned().collect::<Vec<_>>().join(", ");
+
+        let Some(name) = name else {
+            let current = self.persona_name();
+            self.state
+                .command_line
+                .set_message(format!("Persona: {} (available: {})", current, available));
             return;
-        }
+        };
 
-        // Check for API key
-        if self.state.claude.needs_setup {
-            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
+        if !claude.system_prompts.contains_key(name) {
+            self.state
+                .command_line
+                .set_error(format!("Unknown persona: {}. Options: {}", name, available));
             return;
         }
 
-        // Get current section content
-        let Some(book) = &self.state.book else {
-            self.state.command_line.set_error("No book loaded");
+        // Persona changes apply to the open book; with no book open, change the default
+        if self.state.book.is_some() {
+            self.set_book_setting("persona", Some(name.to_string()));
+            return;
+        }
+        self.config.claude.persona = name.to_string();
+        if let Err(e) = self.config.save() {
+            self.state.command_line.set_error(format!("Failed to save config: {}", e));
             return;
+        }
+        self.state.command_line.set_message(format!("Persona set to {} for all books", name));
+    }
+
+    /// Read the open book's settings overrides from its library entry
+    fn load_book_settings(&mut self, book_id: &str) {
+        self.book_settings = storage::Library::load()
+            .ok()
+            .and_then(|library| library.find_by_id(book_id).map(|e| e.settings.clone()))
+            .unwrap_or_default();
+        self.apply_book_settings();
+    }
+
+    /// Apply the open book's overrides on top of the global config
+    fn apply_book_settings(&mut self) {
+        let settings = &self.book_settings;
+        let mode = settings.mode.as_deref().and_then(ReadingMode::from_name).unwrap_or_default();
+        let max_width = match settings.text_width {
+            Some(width) => (width > 0).then_some(width),
+            None => self.config.text_width,
+        };
+        self.state.content.max_width = match mode {
+            ReadingMode::Focus => {
+                Some(max_width.map_or(FOCUS_TEXT_WIDTH, |w| w.min(FOCUS_TEXT_WIDTH)))
+            }
+            _ => max_width,
+        };
+        self.state.book_theme = settings.theme.as_deref().and_then(|t| self.config.theme_named(t));
+
+        let previous = std::mem::replace(&mut self.state.content.reading_mode, mode);
+        if mode != previous {
+            self.switch_panels_for_mode(previous, mode);
+        }
+    }
+
+    /// Show or hide panels on moving between reading modes
+    fn switch_panels_for_mode(&mut self, previous: ReadingMode, mode: ReadingMode) {
+        let panels = &mut self.state.panel_visibility;
+        if previous == ReadingMode::Focus
+            && let Some((curriculum, notes)) = self.panels_before_focus.take()
+        {
+            (panels.curriculum, panels.notes) = (curriculum, notes);
+        }
+        match mode {
+            ReadingMode::Focus => {
+                self.panels_before_focus = Some((panels.curriculum, panels.notes));
+                panels.curriculum = false;
+                panels.notes = false;
+                self.state.focused_panel = Panel::Content;
+            }
+            ReadingMode::Review => panels.notes = true,
+            ReadingMode::Normal | ReadingMode::Skim => {}
+        }
+        // Skimming shortens blocks, so cursor positions don't carry over
+        if self.state.content.cursor_mode
+            && (mode == ReadingMode::Skim || previous == ReadingMode::Skim)
+        {
+            self.state.visual_mode.exit();
+            self.state.content.exit_cursor_mode();
+        }
+    }
+
+    /// Collapse the section to an outline of headings and first sentences,
+    /// or go back to the book's reading mode
+    fn toggle_skim(&mut self) {
+        let previous = self.state.content.reading_mode;
+        let saved = self.book_settings.mode.as_deref().and_then(ReadingMode::from_name);
+        let mode = match saved {
+            _ if previous != ReadingMode::Skim => ReadingMode::Skim,
+            Some(mode) if mode != ReadingMode::Skim => mode,
+            _ => ReadingMode::Normal,
         };
+        self.state.content.reading_mode = mode;
+        self.switch_panels_for_mode(previous, mode);
+        self.state.command_line.set_message(if mode == ReadingMode::Skim {
+            "Skimming (z expands or collapses a block, Z reads in full)".to_string()
+        } else {
+            format!("{} mode", mode.name())
+        });
+    }
 
+    /// Expand or collapse a block in skim view: the one under the cursor, or
+    /// else the next collapsed block on screen, collapsing them all again once
+    /// every one is expanded
+    fn toggle_skim_block(&mut self) {
+        let Some(book) = &self.state.book else { return };
         let Some(section) =
             book.get_section(self.state.current_chapter, self.state.current_section)
         else {
-            self.state.command_line.set_error("No section selected");
             return;
         };
+        let content = &mut self.state.content;
+        let key = (book.metadata.id.clone(), section.path.clone());
+        let expanded = content.skim_expanded.entry(key).or_default();
+        if content.cursor_mode {
+            let block = content.cursor_block;
+            let message = if expanded.remove(&block) {
+                "Block collapsed"
+            } else {
+                expanded.insert(block);
+                "Block expanded"
+            };
+            self.state.command_line.set_message(message);
+            return;
+        }
 
-        let section_title = section.title.clone();
-        let section_content = section.plain_text();
-
-        // Truncate content if too long (Claude has context limits)
-        let content = if section_content.len() > 8000 {
-            format!("{}...\n\n[Content truncated]", &section_content[..8000])
-        } else {
-            section_content
-        };
-
-        // Get API key
-        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
-            Ok(key) => key,
-            Err(e) => {
-                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
-                return;
+        let offsets = &content.block_line_offsets;
+        let top = offsets.partition_point(|&o| o <= content.scroll_offset).saturating_sub(1);
+        let bottom =
+            offsets.partition_point(|&o| o < content.scroll_offset + content.visible_height);
+        let on_screen: Vec<usize> = (top..bottom.min(section.content.len()))
+            .filter(|&i| {
+                expanded.contains(&i) || crate::ui::content::skim_shortens(&section.content[i])
+            })
+            .collect();
+        let message = match on_screen.iter().find(|i| !expanded.contains(i)) {
+            Some(&block) => {
+                expanded.insert(block);
+                "Block expanded (z again expands the next)"
+            }
+            None if on_screen.is_empty() => "Nothing to expand on screen",
+            None => {
+                on_screen.iter().for_each(|i| {
+                    expanded.remove(i);
+                });
+                "Blocks on screen collapsed"
             }
         };
+        self.state.command_line.set_message(message);
+    }
 
-        // Build the prompt
-        let prompt = if let Some(focus) = topic {
-            format!(
-                "Here is a section from a book titled \"{}\":\n\n{}\n\nPlease explain {} in this context. Be concise.",
-                section_title, content, focus
-            )
-        } else {
-            format!(
-                "Here is a section from a book titled \"{}\":\n\n{}\n\nPlease provide a brief explanation of the key concepts in this section. Be concise.",
-                section_title, content
-            )
+    /// Show or switch the open book's reading mode (`:mode skim`)
+    fn set_reading_mode(&mut self, name: Option<&str>) {
+        if self.state.book.is_none() {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        }
+        let Some(name) = name else {
+            self.state.command_line.set_message(format!(
+                "mode={} ({})",
+                self.state.content.reading_mode.name(),
+                reading_mode_names()
+            ));
+            return;
         };
+        match self.book_setting_value("mode", name) {
+            Ok(value) => {
+                self.set_book_setting("mode", value);
+                let mode = self.state.content.reading_mode;
+                if !self.state.command_line.is_error {
+                    self.state
+                        .command_line
+                        .set_message(format!("{} mode for this book", mode.name()));
+                }
+            }
+            Err(e) => self.state.command_line.set_error(e),
+        }
+    }
 
-        // Clear previous response and set streaming state
-        self.state.claude.clear_streaming();
-        self.state.claude.streaming = true;
-        self.state.command_line.set_message("Asking Claude to explain...");
-
-        // Create the client and message
-        let client = crate::claude::ClaudeClient::new(api_key);
-        let messages = vec![crate::claude::Message::user(&prompt)];
-        let request = crate::claude::CreateMessageRequest::new(self.state.claude.model, messages)
-            .with_system("You are an expert tutor helping someone understand technical content from a book. Explain concepts clearly and concisely.");
-
-        // Create channel and cancellation token
-        let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let cancel_token = tokio_util::sync::CancellationToken::new();
-
-        self.claude_rx = Some(rx);
-        self.claude_cancel = Some(cancel_token.clone());
+    /// Show or override a setting for the open book: `:bookset [key] [value]`
+    fn book_set(&mut self, key: Option<&str>, value: Option<&str>) {
+        if self.state.book.is_none() {
+            self.state.command_line.set_error("No book loaded");
+            return;
+        }
+        let Some(key) = key else {
+            let set: Vec<String> = storage::BookSettings::KEYS
+                .iter()
+                .filter_map(|key| self.book_settings.get(key).map(|v| format!("{}={}", key, v)))
+                .collect();
+            self.state.command_line.set_message(if set.is_empty() {
+                format!(
+                    "No settings for this book. Use :bookset <key> <value> ({})",
+                    storage::BookSettings::KEYS.join(", ")
+                )
+            } else {
+                format!("Book settings: {}", set.join(", "))
+            });
+            return;
+        };
+        let Some(key) = storage::BookSettings::key(key) else {
+            self.state.command_line.set_error(format!(
+                "Unknown book setting: {}. Options: {}",
+                key,
+                storage::BookSettings::KEYS.join(", ")
+            ));
+            return;
+        };
+        let Some(value) = value else {
+            let current = self.book_settings.get(key);
+            self.state.command_line.set_message(match current {
+                Some(value) => format!("{}={} (this book)", key, value),
+                None => format!("{} is not set for this book", key),
+            });
+            return;
+        };
+        match self.book_setting_value(key, value) {
+            Ok(value) => self.set_book_setting(key, value),
+            Err(e) => self.state.command_line.set_error(e),
+        }
+    }
 
-        // Spawn the streaming task
-        tokio::spawn(async move {
-            if let Err(e) = client.send_streaming(request, tx, cancel_token).await {
-                tracing::error!("Claude API error: {}", e);
-            }
-        });
+    /// Check a `:bookset` value, normalizing it; None clears the setting
+    fn book_setting_value(&self, key: &str, value: &str) -> Result<Option<String>, String> {
+        if matches!(value.to_lowercase().as_str(), "off" | "default" | "none") {
+            return Ok(None);
+        }
+        match key {
+            "theme" => match self.config.theme_named(value) {
+                Some(theme) => Ok(Some(theme.name)),
+                None => Err(format!("Unknown theme: {}", value)),
+            },
+            "textwidth" => match value.parse::<usize>() {
+                Ok(width) if width == 0 || width >= MIN_TEXT_WIDTH => Ok(Some(width.to_string())),
+                _ => Err(format!("textwidth must be 0 or at least {}", MIN_TEXT_WIDTH)),
+            },
+            "persona" if self.config.claude.system_prompts.contains_key(value) => {
+                Ok(Some(value.to_string()))
+            }
+            "persona" => Err(format!("Unknown persona: {}", value)),
+            "model" => match self.models.resolve(value) {
+                Some(crate::claude::ClaudeModel::Listed(id)) => Ok(Some(id.to_string())),
+                Some(_) => Ok(Some(value.to_lowercase())),
+                None => Err(format!(
+                    "Unknown model: {}. Options: haiku, sonnet, opus, or an ID listed by \
+                     :claude-models",
+                    value
+                )),
+            },
+            "mode" => match ReadingMode::from_name(value) {
+                Some(ReadingMode::Normal) => Ok(None),
+                Some(mode) => Ok(Some(mode.name().to_string())),
+                None => Err(format!("Unknown mode: {}. Options: {}", value, reading_mode_names())),
+            },
+            _ => Err(format!("Unknown book setting: {}", key)),
+        }
     }
 
-    /// Get currently selected text (if in visual mode with selection)
-    fn get_selected_text(&self) -> Option<String> {
-        if !self.state.visual_mode.active {
-            return None;
+    /// Store a setting for the open book in its library entry and apply it
+    fn set_book_setting(&mut self, key: &str, value: Option<String>) {
+        let Some(book_id) = self.state.book.as_ref().map(|b| b.metadata.id.clone()) else { return };
+        let saved = storage::Library::load().and_then(|mut library| {
+            let entry = library.entries.iter_mut().find(|e| e.metadata.id == book_id);
+            let Some(entry) = entry else { anyhow::bail!("Book is not in the library") };
+            entry.settings.set(key, value.clone());
+            library.save()
+        });
+        if let Err(e) = saved {
+            self.state.command_line.set_error(format!("Failed to save book settings: {}", e));
+            return;
         }
+        self.book_settings.set(key, value.clone());
+        self.apply_book_settings();
+        self.state.command_line.set_message(match value {
+            Some(value) => format!("{}={} for this book", key, value),
+            None => format!("{} follows the global setting for this book", key),
+        });
+    }
 
-        let book = self.state.book.as_ref()?;
-        let section = book.get_section(self.state.current_chapter, self.state.current_section)?;
+    /// Jump to a footnote in the current section, or back from the last jump
+    ///
+    /// With no label, returns to where the last jump started, or jumps to the
+    /// first footnote if there is nothing to return to.
+    fn goto_footnote(&mut self, label: Option<&str>) {
+        let Some(section) =
+            self.state.book.as_ref().and_then(|b| {
+                b.get_section(self.state.current_chapter, self.state.current_section)
+            })
+        else {
+            self.state.command_line.set_error("No section selected");
+            return;
+        };
 
-        let (start_block, start_char, end_block, end_char) = self
-            .state
-            .visual_mode
-            .selection_range(self.state.content.cursor_block, self.state.content.cursor_char);
+        if label.is_none() && self.state.content.return_from_footnote(&section.path) {
+            self.state.command_line.set_message("Returned from footnote");
+            return;
+        }
 
-        // Helper to extract text from a block
-        fn block_text(block: &crate::book::ContentBlock) -> Option<String> {
-            match block {
-                crate::book::ContentBlock::Paragraph(text) => Some(text.clone()),
-                crate::book::ContentBlock::Blockquote(text) => Some(text.clone()),
-                crate::book::ContentBlock::Heading { text, .. } => Some(text.clone()),
-                crate::book::ContentBlock::Code(code_block) => Some(code_block.code.clone()),
-                crate::book::ContentBlock::UnorderedList(items) => Some(items.join("\n")),
-                crate::book::ContentBlock::OrderedList(items) => Some(items.join("\n")),
-                _ => None,
+        let target = section.content.iter().enumerate().find_map(|(index, block)| match block {
+            crate::book::ContentBlock::Footnote { label: l, text }
+                if label.is_none_or(|label| label == l) =>
+            {
+                Some((index, l.clone(), text.clone()))
             }
+            _ => None,
+        });
+
+        match target {
+            Some((index, label, text)) => {
+                let path = section.path.clone();
+                self.record_jump();
+                self.state.content.jump_to_footnote(&path, index);
+                self.state.command_line.set_message(format!(
+                    "{} {} (:footnote to return)",
+                    crate::book::model::footnote_marker(&label),
+                    text
+                ));
+            }
+            None => match label {
+                Some(label) => {
+                    self.state.command_line.set_error(format!("No footnote {} here", label))
+                }
+                None => self.state.command_line.set_error("No footnotes in this section"),
+            },
         }
+    }
 
-        // For single-block selection
-        // end_char is inclusive (the cursor position), so we need +1 to include it
-        if start_block == end_block {
-            let text = block_text(section.content.get(start_block)?)?;
-            Some(text.chars().skip(start_char).take(end_char - start_char + 1).collect())
-        } else {
-            // Multi-block selection: collect text from all blocks
-            let mut result = String::new();
-            for block_idx in start_block..=end_block {
-                let Some(block) = section.content.get(block_idx) else {
-                    continue;
-                };
-                let Some(text) = block_text(block) else {
-                    continue;
-                };
+    /// Export the current book's annotations as HTML pages
+    fn export_html(&mut self, dir: Option<&str>) {
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book open");
+            return;
+        };
 
-                if block_idx == start_block {
-                    result.push_str(&text.chars().skip(start_char).collect::<String>());
-                } else if block_idx == end_block {
-                    // end_char is inclusive, so take end_char + 1 characters
-                    result.push_str(&text.chars().take(end_char + 1).collect::<String>());
-                } else {
-                    result.push_str(&text);
+        let out_dir = match dir {
+            Some(dir) => Self::expand_path(std::path::Path::new(dir)),
+            None => match Config::data_dir() {
+                Ok(data_dir) => data_dir.join("exports").join(&book.metadata.id),
+                Err(e) => {
+                    self.state.command_line.set_error(format!("Export failed: {}", e));
+                    return;
                 }
-                result.push('\n');
-            }
-            Some(result.trim().to_string())
+            },
+        };
+
+        let notes = self.notes_store.get_book_notes(&book.metadata.id);
+        match crate::export::html::export_book(book, notes, &self.config.export, &out_dir) {
+            Ok(index) => self.state.command_line.set_message(format!(
+                "Exported {} notes to {}",
+                notes.len(),
+                index.display()
+            )),
+            Err(e) => self.state.command_line.set_error(format!("Export failed: {}", e)),
         }
     }
 
-    /// Copy selected text to clipboard (yank)
-    fn yank_selection(&mut self) {
-        let Some(text) = self.get_selected_text() else {
-            self.state.command_line.set_error("No text selected");
+    /// Export the current book in a registered format
+    ///
+    /// Without a path, the file goes in the book's export directory.
+    fn export(&mut self, format: &str, path: Option<&str>) {
+        let Some(book) = &self.state.book else {
+            self.state.command_line.set_error("No book open");
             return;
         };
 
-        if text.is_empty() {
-            self.state.command_line.set_error("No text selected");
+        let registry = crate::export::ExporterRegistry::with_builtin();
+        let Some(exporter) = registry.get(format) else {
+            self.state.command_line.set_error(format!(
+                "Unknown export format: {} (available: {})",
+                format,
+                registry.names()
+            ));
             return;
+        };
+
+        let book_id = &book.metadata.id;
+        let summaries = crate::claude::summary::SummaryCache::load(book_id).unwrap_or_default();
+        let theme = self.state.book_theme.clone().unwrap_or_else(|| self.config.active_theme());
+        let ctx = crate::export::ExportContext {
+            book,
+            notes: self.notes_store.get_book_notes(book_id),
+            questions: &self.question_bank,
+            progress: self.progress.books.get(book_id),
+            summaries: &summaries,
+            styles: &self.config.export,
+            theme: &theme,
+        };
+
+        let result = match path {
+            Some(path) => Ok(Self::expand_path(std::path::Path::new(path))),
+            None => Config::data_dir().and_then(|data_dir| {
+                let dir = data_dir.join("exports").join(book_id);
+                std::fs::create_dir_all(&dir)?;
+                Ok(dir.join(exporter.default_file_name(&ctx)))
+            }),
+        }
+        .and_then(|path| exporter.export(&ctx, &path).map(|()| path));
+
+        match result {
+            Ok(path) => self.state.command_line.set_message(format!(
+                "Exported {} to {}",
+                exporter.name(),
+                path.display()
+            )),
+            Err(e) => self.state.command_line.set_error(format!("Export failed: {}", e)),
         }
+    }
 
-        match arboard::Clipboard::new() {
-            Ok(mut clipboard) => match clipboard.set_text(&text) {
-                Ok(()) => {
-                    let char_count = text.chars().count();
-                    self.state
-                        .command_line
-                        .set_message(format!("Yanked {} characters", char_count));
-                    // Exit visual mode after yanking
-                    self.state.visual_mode.exit();
+    /// Start, pause, resume or stop reading the current section aloud
+    fn control_speech(&mut self, action: SpeakAction) {
+        let action = match (action, &self.speaker) {
+            (SpeakAction::Toggle, Some(speaker)) if speaker.is_paused() => SpeakAction::Resume,
+            (SpeakAction::Toggle, Some(_)) => SpeakAction::Pause,
+            (action, _) => action,
+        };
+
+        match action {
+            SpeakAction::Toggle => self.start_speech(),
+            SpeakAction::Pause => {
+                if let Some(speaker) = &mut self.speaker {
+                    speaker.pause();
+                    self.state.command_line.set_message("Reading paused (:speak to resume)");
+                }
+            }
+            SpeakAction::Resume => {
+                let Some(speaker) = &mut self.speaker else {
+                    self.start_speech();
+                    return;
+                };
+                match speaker.play() {
+                    Ok(_) => self.update_speech_message(),
+                    Err(e) => {
+                        self.speaker = None;
+                        self.state.command_line.set_error(format!("Text-to-speech failed: {}", e));
+                    }
                 }
-                Err(e) => {
-                    self.state.command_line.set_error(format!("Failed to copy: {}", e));
+            }
+            SpeakAction::Stop => {
+                if self.speaker.take().is_some() {
+                    self.state.command_line.set_message("Reading stopped");
                 }
-            },
-            Err(e) => {
-                self.state.command_line.set_error(format!("Clipboard unavailable: {}", e));
             }
         }
     }
 
-    /// Ask Claude about selected text (includes full section context)
-    fn ask_about_selection(&mut self, question: &str) {
-        // Check if already streaming
-        if self.state.claude.streaming {
-            self.state.command_line.set_error("Already waiting for Claude response");
-            return;
-        }
-
-        // Check for API key
-        if self.state.claude.needs_setup {
-            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
-            return;
-        }
-
-        // Get book and section info for context
+    /// Start reading the current section aloud from the top
+    fn start_speech(&mut self) {
         let Some(book) = &self.state.book else {
             self.state.command_line.set_error("No book loaded");
             return;
         };
-
         let Some(section) =
             book.get_section(self.state.current_chapter, self.state.current_section)
         else {
@@ -2280,403 +7925,687 @@ impl App {
             return;
         };
 
-        // Get full section content for context
-        let section_content = section.plain_text();
-        let section_title = section.title.clone();
-        let section_path = section.path.clone();
-        let book_id = book.metadata.id.clone();
+        let mut speaker = crate::speech::Speaker::new(&self.config.tts, section);
+        if speaker.is_empty() {
+            self.state.command_line.set_error("Nothing to read in this section");
+            return;
+        }
 
-        // Get selected text and selection info
-        let (selected_text, selection_block, selection_char) = if self.state.visual_mode.active {
-            let (start_block, start_char, _, _) = self
-                .state
-                .visual_mode
-                .selection_range(self.state.content.cursor_block, self.state.content.cursor_char);
-            match self.get_selected_text() {
-                Some(text) if !text.is_empty() => (text, Some(start_block), Some(start_char)),
-                _ => {
-                    self.state.command_line.set_error(
-                        "No text selected. Use 'v' to enter visual mode and select text.",
-                    );
-                    return;
-                }
+        match speaker.play() {
+            Ok(_) => {
+                self.speaker = Some(speaker);
+                self.follow_speech();
             }
-        } else {
-            self.state
-                .command_line
-                .set_error("No text selected. Use 'v' to enter visual mode and select text.");
+            Err(e) => {
+                self.state.command_line.set_error(format!(
+                    "Text-to-speech failed ({}): {}",
+                    self.config.tts.command, e
+                ));
+            }
+        }
+    }
+
+    /// Advance text-to-speech playback and keep the spoken sentence on screen
+    fn process_speech(&mut self) {
+        let Some(speaker) = &mut self.speaker else { return };
+
+        // Stop reading if the user moved to another section
+        let current_path = self.state.book.as_ref().and_then(|book| {
+            book.get_section(self.state.current_chapter, self.state.current_section)
+                .map(|s| s.path.as_str())
+        });
+        if current_path != Some(speaker.section_path()) {
+            self.speaker = None;
             return;
-        };
+        }
 
-        // Get API key
-        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
-            Ok(key) => key,
+        match speaker.poll() {
+            Ok(SpeechEvent::Idle) => {}
+            Ok(SpeechEvent::Started(_)) => self.follow_speech(),
+            Ok(SpeechEvent::Finished) => {
+                self.speaker = None;
+                self.state.command_line.set_message("Finished reading section");
+            }
             Err(e) => {
-                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
-                return;
+                self.speaker = None;
+                self.state.command_line.set_error(format!("Text-to-speech failed: {}", e));
             }
-        };
+        }
+    }
 
-        // Truncate section content if too long (but preserve full selection)
-        let context = if section_content.len() > 6000 {
-            format!("{}...\n\n[Section truncated for context]", &section_content[..6000])
-        } else {
-            section_content
+    /// Scroll to the sentence being spoken
+    fn follow_speech(&mut self) {
+        let Some(block) = self.speaker.as_ref().and_then(|s| s.current()).map(|u| u.block_index)
+        else {
+            return;
         };
+        self.state.content.ensure_block_visible(block);
+        self.update_speech_message();
+    }
 
-        // Truncate selection display if too long
-        let selection_display = if selected_text.len() > 2000 {
-            format!("{}...", &selected_text[..2000])
-        } else {
-            selected_text.clone()
-        };
+    /// Show reading progress in the command line
+    fn update_speech_message(&mut self) {
+        let Some(speaker) = &self.speaker else { return };
+        let (position, total) = speaker.progress();
+        self.state.command_line.set_message(format!(
+            "Reading aloud {}/{} (:speak pause, :speak stop)",
+            position + 1,
+            total
+        ));
+    }
 
-        // Build the prompt with full context and highlighted selection
-        let prompt = format!(
-            "Here is a section from the book titled \"{}\":\n\n---\n{}\n---\n\n\
-             The reader has highlighted this specific passage:\n\n\"\"\"\n{}\n\"\"\"\n\n\
-             Their question about this passage: {}\n\n\
-             Please provide a clear, concise answer that considers both the highlighted passage and its surrounding context.",
-            section_title, context, selection_display, question
-        );
+    /// Record token usage from background requests (non-blocking)
+    fn process_usage_reports(&mut self) {
+        while let Ok((model, input_tokens, output_tokens)) = self.usage_rx.try_recv() {
+            self.record_usage(model, input_tokens, output_tokens, (0, 0));
+        }
+    }
 
-        // Store pending note info for saving Q&A after response
-        self.state.claude.set_pending_note(
-            question,
-            &book_id,
-            &section_path,
-            Some(&selected_text),
-            selection_block,
-            selection_char,
-        );
+    /// Show the latest retry of a rate-limited or failed Claude request
+    fn process_retry_reports(&mut self) {
+        while let Ok(status) = self.retry_rx.try_recv() {
+            self.state.command_line.set_message(status.message());
+        }
+    }
 
-        // Exit visual mode
-        self.state.visual_mode.exit();
+    /// Record token usage for a completed request and refresh the status line
+    ///
+    /// `cache` holds the input tokens (written to, read from) the prompt cache.
+    fn record_usage(
+        &mut self,
+        model: crate::claude::ClaudeModel,
+        input: u32,
+        output: u32,
+        cache: (u32, u32),
+    ) {
+        let book_id = self.state.book.as_ref().map(|b| b.metadata.id.as_str());
+        self.usage.record_cached(model, book_id, input, output, cache);
+        if let Err(e) = self.usage.save() {
+            tracing::warn!("Failed to save usage: {}", e);
+        }
+        self.update_usage_status();
+    }
 
-        // Clear previous response and set streaming state
-        self.state.claude.clear_streaming();
-        self.state.claude.streaming = true;
-        self.state
-            .command_line
-            .set_message(format!("Asking about selection ({} chars)...", selected_text.len()));
+    /// Show today's estimated API cost in the status line
+    fn update_usage_status(&mut self) {
+        let today = self.usage.today();
+        self.state.command_line.status = (today.requests > 0)
+            .then(|| format!("{} today", crate::claude::usage::format_cost(today.cost)));
+    }
 
-        // Create the client and message
-        let client = crate::claude::ClaudeClient::new(api_key);
-        let messages = vec![crate::claude::Message::user(&prompt)];
-        let request = crate::claude::CreateMessageRequest::new(self.state.claude.model, messages)
-            .with_system("You are an expert tutor helping someone understand content from a book. Answer questions about the selected passage clearly and concisely, using the surrounding context to provide more complete explanations when relevant.");
+    /// Show the terminal health check report
+    fn show_doctor(&mut self) {
+        let checks = doctor::run_system_checks(self.image_cache.protocol_type());
+        self.state.claude.response = doctor::report(&checks);
+        self.state.claude.show_response = true;
+        self.state.claude.response_scroll = 0;
+    }
 
-        // Create channel and cancellation token
-        let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let cancel_token = tokio_util::sync::CancellationToken::new();
+    /// Point out missing terminal capabilities once at startup
+    fn startup_health_check(&mut self) {
+        let checks = doctor::run_system_checks(self.image_cache.protocol_type());
+        let failing: Vec<_> = checks.iter().filter(|c| !c.ok).map(|c| c.name).collect();
+        let restored: Vec<String> = crate::config::persist::take_restored()
+            .iter()
+            .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+            .collect();
+        if !restored.is_empty() {
+            self.state.command_line.set_message(format!(
+                "Restored {} from backup after a damaged save (see the log)",
+                restored.join(", ")
+            ));
+        } else if !failing.is_empty() {
+            self.state.command_line.set_message(format!(
+                "Limited terminal support: {} (:doctor for suggestions)",
+                failing.join(", ")
+            ));
+        }
+    }
 
-        self.claude_rx = Some(rx);
-        self.claude_cancel = Some(cancel_token.clone());
+    /// Record words written in a note
+    fn record_writing(&mut self, book_id: &str, words: usize) {
+        if words == 0 {
+            return;
+        }
+        self.writing.record(book_id, words);
+        if let Err(e) = self.writing.save() {
+            tracing::warn!("Failed to save writing stats: {}", e);
+        }
+    }
 
-        // Spawn the streaming task
-        tokio::spawn(async move {
-            if let Err(e) = client.send_streaming(request, tx, cancel_token).await {
-                tracing::error!("Claude API error: {}", e);
-            }
+    /// Progress through each step of a plan, with the book's title
+    fn plan_progress(
+        &self,
+        plan: &StudyPlan,
+        library: &storage::Library,
+    ) -> Vec<Option<(String, StepProgress)>> {
+        plan_progress(plan, library, &self.progress, self.state.book.as_ref())
+    }
+
+    /// Work out the next section to read in the study plans in the
+    /// background, as it may mean loading each plan's books
+    fn refresh_next_up(&mut self) {
+        self.status_goal = None;
+        if self.config.plans.is_empty() {
+            self.state.next_up = None;
+            self.next_up_rx = None;
+            return;
+        }
+        let plans = self.config.plans.clone();
+        let progress = self.progress.clone();
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.next_up_rx = Some(rx);
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.blocking_send(plans_next_up(&plans, &progress));
         });
     }
 
-    /// Expand tilde in path to home directory
-    fn expand_path(path: &std::path::Path) -> std::path::PathBuf {
-        let path_str = path.to_string_lossy();
-        if let Some(stripped) = path_str.strip_prefix("~/") {
-            if let Ok(home) = std::env::var("HOME") {
-                return std::path::PathBuf::from(home).join(stripped);
+    /// Show the study plans' next section once worked out
+    fn process_next_up(&mut self) {
+        let Some(rx) = &mut self.next_up_rx else { return };
+        let Ok(next_up) = rx.try_recv() else { return };
+        self.next_up_rx = None;
+        self.state.next_up = next_up;
+    }
+
+    /// Show progress through the study plans, or the one named
+    fn show_plans(&mut self, name: Option<&str>) {
+        let plans: Vec<&StudyPlan> = self
+            .config
+            .plans
+            .iter()
+            .filter(|p| name.is_none_or(|n| p.name.eq_ignore_ascii_case(n)))
+            .collect();
+        match (plans.is_empty(), name) {
+            (true, Some(name)) => {
+                self.state.command_line.set_error(format!("No study plan named {}", name));
+                return;
             }
-        } else if path_str == "~" {
-            if let Ok(home) = std::env::var("HOME") {
-                return std::path::PathBuf::from(home);
+            (true, None) => {
+                self.state.command_line.set_message(
+                    "No study plans. Add one with :plan-add <name>: <book> ch1-10, then <book> ...",
+                );
+                return;
             }
+            _ => {}
         }
-        path.to_path_buf()
+        let library = match storage::Library::load() {
+            Ok(library) => library,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to load library: {}", e));
+                return;
+            }
+        };
+
+        let mut out = String::from("# Study plans\n");
+        for plan in plans {
+            out.push_str(&self.plan_report(plan, &library));
+        }
+        self.state.claude.response = out;
+        self.state.claude.show_response = true;
+        self.state.claude.response_scroll = 0;
     }
 
-    /// Add a book from path
-    fn add_book(&mut self, path: &std::path::Path) -> Result<()> {
-        let expanded_path = Self::expand_path(path);
-        match storage::add_book(&expanded_path) {
-            Ok(entry) => {
-                // Load the book using the entry we just created
-                match storage::load_book(&entry) {
-                    Ok(book) => {
-                        let title = book.metadata.title.clone();
-                        self.state.book = Some(book);
-                        self.state.current_chapter = 0;
-                        self.state.current_section = 0;
-                        self.state.curriculum.selected_index = 0;
-                        self.state.curriculum.expanded_chapters.clear();
-                        self.state.content.scroll_offset = 0;
-                        self.state.command_line.set_message(format!("Added: {}", title));
-                    }
-                    Err(e) => {
-                        self.state.command_line.set_error(format!("Failed to load: {}", e));
-                    }
-                }
-            }
-            Err(e) => {
-                self.state.command_line.set_error(format!("Failed to add: {}", e));
+    /// One plan's steps and progress, for `:plan`
+    fn plan_report(&self, plan: &StudyPlan, library: &storage::Library) -> String {
+        let steps = self.plan_progress(plan, library);
+        let (done, total) =
+            steps.iter().flatten().fold((0, 0), |(d, t), (_, s)| (d + s.done, t + s.total));
+
+        let mut out = format!("\n## {} ({}/{} sections)\n\n", plan.name, done, total);
+        let mut next_shown = false;
+        for (i, (step, status)) in plan.steps.iter().zip(steps).enumerate() {
+            let Some((title, status)) = status else {
+                out.push_str(&format!("{}. ? {} (not in library)\n", i + 1, step.label()));
+                continue;
+            };
+            let mark = if status.is_complete() { "✓" } else { "·" };
+            out.push_str(&format!(
+                "{}. {} {} ({}/{})\n",
+                i + 1,
+                mark,
+                step.describe(&title),
+                status.done,
+                status.total
+            ));
+            if let Some((path, section)) = status.next.filter(|_| !next_shown) {
+                next_shown = true;
+                out.push_str(&format!(
+                    "   - **Next up**: {} (`:open {}`, then `:goto {}`)\n",
+                    section, step.book, path
+                ));
             }
         }
-        Ok(())
+        out
     }
 
-    /// Remove a book from the library
-    fn remove_book(&mut self, book_id: &str) -> Result<()> {
-        // Load library
-        let mut library = match storage::Library::load() {
-            Ok(lib) => lib,
+    /// Add or replace a study plan from "<name>: <book> ch1-10, then <book> ..."
+    fn add_plan(&mut self, spec: &str) {
+        let Some((name, steps)) = spec.split_once(':') else {
+            self.state
+                .command_line
+                .set_error("Usage: :plan-add <name>: <book> ch1-10, then <book> ...");
+            return;
+        };
+        let plan = StudyPlan::parse(name, steps).and_then(|mut plan| {
+            let library = storage::Library::load()?;
+            for step in &mut plan.steps {
+                step.book = match library.resolve(&step.book) {
+                    BookMatch::Found(entry) | BookMatch::Likely(entry) => entry.metadata.id.clone(),
+                    BookMatch::Ambiguous(matches) => anyhow::bail!(
+                        "\"{}\" matches several books: {}",
+                        step.book,
+                        matches
+                            .iter()
+                            .map(|e| e.metadata.id.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    BookMatch::NotFound => anyhow::bail!("Book not found: {}", step.book),
+                };
+            }
+            Ok(plan)
+        });
+        let plan = match plan {
+            Ok(plan) => plan,
             Err(e) => {
-                self.state.command_line.set_error(format!("Failed to load library: {}", e));
-                return Ok(());
+                self.state.command_line.set_error(e.to_string());
+                return;
             }
         };
 
-        // Find the book by ID or title
-        let entry_idx = library
-            .entries
-            .iter()
-            .position(|e| e.metadata.id == book_id || e.metadata.title == book_id);
-
-        match entry_idx {
-            Some(idx) => {
-                let removed_title = library.entries[idx].metadata.title.clone();
-                let removed_id = library.entries[idx].metadata.id.clone();
-
-                // Remove from library
-                library.entries.remove(idx);
-                if let Err(e) = library.save() {
-                    self.state.command_line.set_error(format!("Failed to save library: {}", e));
-                    return Ok(());
-                }
+        let message = format!("Saved plan {} ({} steps)", plan.name, plan.steps.len());
+        self.config.plans.retain(|p| !p.name.eq_ignore_ascii_case(&plan.name));
+        self.config.plans.push(plan);
+        self.save_plans(message);
+    }
 
-                // If we removed the currently loaded book, clear it
-                if let Some(book) = &self.state.book {
-                    if book.metadata.id == removed_id {
-                        self.state.book = None;
-                        self.state.current_chapter = 0;
-                        self.state.current_section = 0;
-                        self.state.curriculum.selected_index = 0;
-                        self.state.curriculum.expanded_chapters.clear();
-                        self.state.content.scroll_offset = 0;
-                    }
-                }
+    /// Remove a study plan by name
+    fn remove_plan(&mut self, name: &str) {
+        let before = self.config.plans.len();
+        self.config.plans.retain(|p| !p.name.eq_ignore_ascii_case(name.trim()));
+        if self.config.plans.len() == before {
+            self.state.command_line.set_error(format!("No study plan named {}", name));
+            return;
+        }
+        self.save_plans(format!("Removed plan {}", name.trim()));
+    }
 
-                self.state.command_line.set_message(format!("Removed: {}", removed_title));
-            }
-            None => {
-                self.state.command_line.set_error(format!("Book not found: {}", book_id));
-            }
+    /// Save the config after a plan change, reporting `message` on success
+    fn save_plans(&mut self, message: String) {
+        self.refresh_next_up();
+        match self.config.save() {
+            Ok(()) => self.state.command_line.set_message(message),
+            Err(e) => self.state.command_line.set_error(format!("Failed to save plans: {}", e)),
         }
-        Ok(())
     }
 
-    /// Refresh the currently loaded book (clear cache and reload)
-    fn refresh_current_book(&mut self) -> Result<()> {
+    /// Show the current book's sections due for review, and those coming up
+    fn show_reviews(&mut self) {
+        use crate::config::datetime::{day_date, day_number};
+
         let Some(book) = &self.state.book else {
-            self.state.command_line.set_error("No book currently loaded");
-            return Ok(());
+            self.state.command_line.set_error("No book loaded");
+            return;
         };
-
-        let book_id = book.metadata.id.clone();
-        let book_title = book.metadata.title.clone();
-
-        // Clear the cache
-        match storage::refresh_book(&book_id) {
-            Ok(true) => {
-                // Reload the book
-                let library = storage::Library::load()?;
-                if let Some(entry) = library.find_by_id(&book_id) {
-                    match storage::load_book(entry) {
-                        Ok(reloaded_book) => {
-                            // Preserve current position
-                            let chapter = self.state.current_chapter;
-                            let section = self.state.current_section;
-                            let scroll = self.state.content.scroll_offset;
-
-                            self.state.book = Some(reloaded_book);
-
-                            // Restore position (clamped to valid range)
-                            self.state.current_chapter = chapter;
-                            self.state.current_section = section;
-                            self.state.content.scroll_offset = scroll;
-
-                            self.state.command_line.set_message(format!(
-                                "Refreshed: {} (progress preserved)",
-                                book_title
-                            ));
-                        }
-                        Err(e) => {
-                            self.state.command_line.set_error(format!("Failed to reload: {}", e));
-                        }
-                    }
-                }
-            }
-            Ok(false) => {
-                self.state.command_line.set_error("Book not found in library");
-            }
-            Err(e) => {
-                self.state.command_line.set_error(format!("Failed to refresh: {}", e));
+        let scheduler = self.config.review.scheduler();
+        let book_progress = self.progress.books.get(&book.metadata.id);
+        let mut scheduled: Vec<(i64, &Section)> = book
+            .chapters
+            .iter()
+            .flat_map(|c| &c.sections)
+            .filter_map(|section| {
+                let reviews = &book_progress?.sections.get(&section.path)?.reviews;
+                Some((scheduler.due(reviews)?, section))
+            })
+            .collect();
+        if scheduled.is_empty() {
+            self.state.command_line.set_message(
+                "No reviews scheduled. Sections are scheduled once you take their quiz (Q).",
+            );
+            return;
+        }
+        scheduled.sort_by_key(|&(due, _)| due);
+
+        let now = datetime::now();
+        let dates = self.state.datetime;
+        let due_count = scheduled.iter().filter(|&&(due, _)| due <= now).count();
+        let mut out = format!(
+            "# Reviews ({})\n\n{} of {} quizzed section{} due now.\n",
+            scheduler.name(),
+            due_count,
+            scheduled.len(),
+            if scheduled.len() == 1 { "" } else { "s" }
+        );
+        for (heading, sections) in
+            [("Due", &scheduled[..due_count]), ("Coming up", &scheduled[due_count..])]
+        {
+            if sections.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n## {}\n\n", heading));
+            for (due, section) in sections {
+                out.push_str(&format!(
+                    "- **{}** (`:goto {}`): {}\n",
+                    section.title,
+                    section.path,
+                    dates.weekday_date(day_date(day_number(*due)))
+                ));
             }
         }
 
-        Ok(())
+        self.state.claude.response = out;
+        self.state.claude.show_response = true;
+        self.state.claude.response_scroll = 0;
     }
 
-    /// Open a book by ID
-    fn open_book(&mut self, book_id: &str) -> Result<()> {
-        // Load library and find the entry
-        let library = match storage::Library::load() {
-            Ok(lib) => lib,
-            Err(e) => {
-                self.state.command_line.set_error(format!("Failed to load library: {}", e));
-                return Ok(());
-            }
+    /// Fit FSRS to the review history across all books and save its weights
+    fn tune_reviews(&mut self) {
+        let histories = self
+            .progress
+            .books
+            .values()
+            .flat_map(|b| b.sections.values())
+            .map(|s| s.reviews.as_slice());
+        let (tuned, used) = self.config.review.fsrs().tune(histories);
+        if used == 0 {
+            self.state.command_line.set_error(
+                "Not enough review history to tune yet: sections need a second quiz first",
+            );
+            return;
+        }
+
+        self.config.review.fsrs_weights = Some(tuned.weights.to_vec());
+        let message = match self.config.save() {
+            Ok(()) => format!("Tuned FSRS from {} reviews", used),
+            Err(e) => format!("Tuned FSRS from {} reviews, but failed to save: {}", used, e),
         };
+        self.state.command_line.set_message(message);
+    }
 
-        // Try to find by ID first, then by title
-        let entry = library.find_by_id(book_id).or_else(|| library.find_by_title(book_id));
+    /// Show the study stats dashboard
+    fn show_stats(&mut self) {
+        use crate::config::datetime::{date_day, day_date, today};
 
-        match entry {
-            Some(entry) => match storage::load_book(entry) {
-                Ok(book) => {
-                    let title = book.metadata.title.clone();
-                    let loaded_book_id = book.metadata.id.clone();
-                    self.state.book = Some(book);
+        let dates = self.state.datetime;
+        let week_start = date_day(dates.start_of_week(day_date(today())));
 
-                    // Apply saved session state if available
-                    if let Some(book_session) = self.session.book(&loaded_book_id) {
-                        self.state.current_chapter = book_session.current_chapter;
-                        self.state.current_section = book_session.current_section;
-                        self.state.content.scroll_offset = book_session.content_scroll_offset;
-                        self.state.curriculum.selected_index = book_session.selected_index;
-                        self.state.curriculum.scroll_offset = book_session.curriculum_scroll_offset;
-                        self.state.curriculum.expanded_chapters =
-                            book_session.expanded_chapters.clone();
-                    } else {
-                        self.state.current_chapter = 0;
-                        self.state.current_section = 0;
-                        self.state.curriculum.selected_index = 0;
-                        self.state.curriculum.expanded_chapters.clear();
-                        self.state.content.scroll_offset = 0;
-                    }
-                    self.state.command_line.set_message(format!("Opened: {}", title));
-                }
-                Err(e) => {
-                    self.state.command_line.set_error(format!("Failed to load: {}", e));
-                }
-            },
-            None => {
-                self.state.command_line.set_error(format!("Book not found: {}", book_id));
-            }
+        let mut out = String::from("# Study stats\n\n## Writing\n\n");
+        out.push_str(&format!("- **Today**: {} words\n", self.writing.today()));
+        out.push_str(&format!("- **This week**: {} words\n", self.writing.since_day(week_start)));
+        if let Some(book) = &self.state.book {
+            out.push_str(&format!(
+                "- **{}**: {} words\n",
+                book.metadata.title,
+                self.writing.for_book(&book.metadata.id)
+            ));
+        }
+        out.push_str(&format!("- **All time**: {} words\n", self.writing.total()));
+        let streak = self.writing.current_streak();
+        out.push_str(&format!(
+            "- **Writing streak**: {} day{} (longest {})\n",
+            streak,
+            if streak == 1 { "" } else { "s" },
+            self.writing.longest_streak()
+        ));
+
+        out.push_str("\n### Last 7 days\n\n");
+        for (day, words) in self.writing.recent_days(7) {
+            out.push_str(&format!("- {}: {} words\n", dates.weekday_date(day_date(day)), words));
         }
-        Ok(())
-    }
 
-    /// List available books
-    fn list_books(&mut self) {
-        match storage::Library::load() {
-            Ok(library) => {
-                if library.entries.is_empty() {
-                    self.state.command_line.set_message("No books. Use :add <path> to add one.");
-                } else {
-                    let names: Vec<_> =
-                        library.entries.iter().map(|e| e.metadata.id.as_str()).collect();
-                    self.state.command_line.set_message(format!("Books: {}", names.join(", ")));
-                }
-            }
-            Err(_) => {
-                self.state.command_line.set_message("No books. Use :add <path> to add one.");
-            }
+        if let Some(book) = &self.state.book {
+            out.push_str(&self.reading_time_stats(book));
+            out.push_str(&self.mastery_stats(book));
+            out.push_str(&self.weakest_section_stats(book));
         }
-    }
 
-    /// Execute a search
-    fn execute_search(&mut self, query: &str) {
-        if query.is_empty() {
-            self.state.search.active = false;
-            self.state.search.query.clear();
-            self.state.command_line.clear_message();
-        } else {
-            self.state.search.active = true;
-            self.state.search.query = query.to_string();
-            // TODO: Implement actual search highlighting
-            self.state.command_line.set_message(format!("Search: {}", query));
+        if self.writing.total() == 0 {
+            out.push_str(
+                "\nNo notes written yet. Press `a` to jot down what you've just read \
+                 in your own words.\n",
+            );
         }
+
+        self.state.claude.response = out;
+        self.state.claude.show_response = true;
+        self.state.claude.response_scroll = 0;
     }
 
-    /// Go to a specific section
-    fn goto_section(&mut self, path: &str) {
-        let Some(book) = &self.state.book else {
-            self.state.command_line.set_error("No book loaded");
-            return;
+    /// The reader's quiz mastery rating for a book and its hardest questions,
+    /// for the stats dashboard
+    fn mastery_stats(&self, book: &Book) -> String {
+        const HARDEST_SHOWN: usize = 5;
+
+        let Some(ratings) = self
+            .progress
+            .books
+            .get(&book.metadata.id)
+            .map(|p| &p.quiz_ratings)
+            .filter(|r| !r.is_empty())
+        else {
+            return String::new();
         };
 
-        // Try to find section by path
-        for (chapter_idx, chapter) in book.chapters.iter().enumerate() {
-            for (section_idx, section) in chapter.sections.iter().enumerate() {
-                if section.path.contains(path)
-                    || section.title.to_lowercase().contains(&path.to_lowercase())
-                {
-                    self.state.current_chapter = chapter_idx;
-                    self.state.current_section = section_idx;
-                    self.state.content.scroll_offset = 0;
-                    self.state.curriculum.expanded_chapters.insert(chapter_idx);
-                    self.state.command_line.set_message(format!("→ {}", section.title));
-                    self.mark_section_viewed();
-                    return;
-                }
-            }
+        let mut out = format!(
+            "\n## Quiz mastery\n\n- **Rating**: {:.0} from {} answer{}\n",
+            ratings.mastery,
+            ratings.answered,
+            if ratings.answered == 1 { "" } else { "s" }
+        );
+        if ratings.teach_backs > 0 {
+            out.push_str(&format!("- **Teach-backs passed**: {}\n", ratings.teach_backs));
+        }
+        if ratings.objectives_met > 0 {
+            out.push_str(&format!("- **Objectives met**: {}\n", ratings.objectives_met));
+        }
+        let hardest = ratings.hardest();
+        if !hardest.is_empty() {
+            out.push_str("\n### Hardest questions\n\n");
+        }
+        for (question, rating) in hardest.into_iter().take(HARDEST_SHOWN) {
+            let section = book
+                .find_section_by_path(&rating.section_path)
+                .map_or(rating.section_path.as_str(), |s| s.title.as_str());
+            out.push_str(&format!(
+                "- {} ({:.0}, {}/{} right, {})\n",
+                question, rating.rating, rating.correct, rating.attempts, section
+            ));
+        }
+        out
+    }
+
+    /// Sections with the lowest average quiz scores, for the stats dashboard
+    fn weakest_section_stats(&self, book: &Book) -> String {
+        const WEAKEST_SHOWN: usize = 5;
+
+        let Some(book_progress) = self.progress.books.get(&book.metadata.id) else {
+            return String::new();
+        };
+        let weakest = book_progress.weakest_sections();
+        if weakest.is_empty() {
+            return String::new();
         }
 
-        self.state.command_line.set_error(format!("Section not found: {}", path));
+        let mut out = String::from("\n### Weakest sections\n\n");
+        for (path, section) in weakest.into_iter().take(WEAKEST_SHOWN) {
+            let title = book.find_section_by_path(path).map_or(path, |s| s.title.as_str());
+            let attempts = section.quiz_history.len();
+            let last = section.quiz_history.last().map_or(0, |a| a.percent());
+            out.push_str(&format!(
+                "- {}: {:.0}% average over {} attempt{} (last {}%)\n",
+                title,
+                section.average_quiz_percent().unwrap_or_default(),
+                attempts,
+                if attempts == 1 { "" } else { "s" },
+                last
+            ));
+        }
+        out
     }
 
-    // ==================== Claude Integration ====================
+    /// Remaining and total reading time for a book, for the stats dashboard
+    fn reading_time_stats(&self, book: &Book) -> String {
+        use crate::book::model::format_reading_time;
 
-    /// Process pending Claude streaming events (non-blocking)
-    fn process_claude_events(&mut self) {
-        // Collect events first to avoid borrow conflict
-        let events: Vec<_> = if let Some(ref mut rx) = self.claude_rx {
-            let mut collected = Vec::new();
-            while let Ok(event) = rx.try_recv() {
-                collected.push(event);
-            }
-            collected
-        } else {
-            Vec::new()
+        let book_progress = self.progress.books.get(&book.metadata.id);
+        let completed = |section: &Section| {
+            book_progress.and_then(|p| p.sections.get(&section.path)).is_some_and(|s| s.completed)
         };
+        let remaining = book.reading_minutes(|s| !completed(s));
+        let sections_left =
+            book.chapters.iter().flat_map(|c| &c.sections).filter(|s| !completed(s)).count();
+
+        format!(
+            "\n## Reading\n\n- **Remaining**: {} across {} section{}\n- **Whole book**: {}\n",
+            format_reading_time(remaining),
+            sections_left,
+            if sections_left == 1 { "" } else { "s" },
+            format_reading_time(book.reading_minutes(|_| true))
+        )
+    }
 
-        // Now process collected events
-        for event in events {
-            self.handle_claude_event(event);
+    /// Show a breakdown of API usage and estimated cost
+    fn show_usage(&mut self) {
+        use crate::claude::usage::format_cost;
+
+        let total = self.usage.total();
+        if total.requests == 0 {
+            self.state.command_line.set_message("No Claude API usage recorded yet");
+            return;
+        }
+
+        let mut parts = vec![format!("Today {}", format_cost(self.usage.today().cost))];
+        if let Some(book) = &self.state.book {
+            let book_total = self.usage.for_book(&book.metadata.id);
+            parts.push(format!("This book {}", format_cost(book_total.cost)));
         }
+        parts.push(format!(
+            "All time {} ({} requests, {}k in / {}k out, {}k cached)",
+            format_cost(total.cost),
+            total.requests,
+            total.input_tokens / 1000,
+            total.output_tokens / 1000,
+            total.cache_read_tokens / 1000
+        ));
+        let models: Vec<_> = self
+            .usage
+            .by_model()
+            .iter()
+            .map(|(model, totals)| {
+                // Listed models are priced as their family, so may be off
+                let estimate = if model.has_own_pricing() { "" } else { " (est. price)" };
+                let name = self.models.display_name(model);
+                format!("{} {}{}", name, format_cost(totals.cost), estimate)
+            })
+            .collect();
+        parts.push(models.join(", "));
+
+        self.state.command_line.set_message(parts.join(" | "));
     }
 
     /// Process pending quiz generation results (non-blocking)
     fn process_quiz_events(&mut self) {
         // Check for quiz generation result
-        if let Some(ref mut rx) = self.quiz_rx {
-            if let Ok(result) = rx.try_recv() {
-                match result {
-                    QuizGenerationResult::Success(questions) => {
-                        self.state.quiz.set_questions(questions);
-                        self.state
-                            .command_line
-                            .set_message("Quiz ready! Use j/k to select, Enter to confirm.");
-                    }
-                    QuizGenerationResult::Error(message) => {
-                        self.state.quiz.set_error(&message);
-                        self.state.command_line.set_error(format!("Quiz error: {}", message));
-                    }
+        let Some(rx) = &mut self.quiz_rx else { return };
+        if let Ok(result) = rx.try_recv() {
+            self.handle_quiz_result(result);
+        }
+    }
+
+    /// Apply a quiz generation result
+    fn handle_quiz_result(&mut self, result: QuizGenerationResult) {
+        match result {
+            QuizGenerationResult::Question(question) => {
+                // Deleted questions are skipped, and spares once the quiz is full
+                let section = self.state.quiz.section_path.as_deref().unwrap_or_default();
+                if self.question_bank.is_removed(section, &question)
+                    || self.state.quiz.questions.len() >= QUIZ_QUESTIONS
+                {
+                    return;
+                }
+                let first = self.state.quiz.questions.is_empty();
+                self.state.quiz.push_question(question);
+                if first {
+                    self.state
+                        .command_line
+                        .set_message("Quiz ready! Use j/k to select, Enter to confirm.");
+                }
+                // More questions are on the way
+                return;
+            }
+            QuizGenerationResult::Generated => {
+                self.state.quiz.finish_generating();
+                self.quiz_rx = None;
+                if self.state.quiz.completed {
+                    self.start_quiz_grading();
                 }
+                return;
+            }
+            QuizGenerationResult::Error(message)
+                if self.state.quiz.generating && !self.state.quiz.questions.is_empty() =>
+            {
+                // Keep the questions that arrived
+                self.state.quiz.finish_generating();
                 self.quiz_rx = None;
+                self.state.command_line.set_error(format!(
+                    "Quiz generation stopped early ({}); continuing with {} questions",
+                    message,
+                    self.state.quiz.questions.len()
+                ));
+                if self.state.quiz.completed {
+                    self.start_quiz_grading();
+                }
+                return;
+            }
+            QuizGenerationResult::Replacements(questions) => {
+                self.state.quiz.replace_missed(questions);
+                self.state
+                    .command_line
+                    .set_message("Follow-up questions ready for the ones you missed.");
+            }
+            QuizGenerationResult::Error(message) if self.state.quiz.is_regenerating() => {
+                // Fall back to retaking the same questions
+                self.state.quiz.loading = false;
+                self.state.quiz.retry();
+                self.state.command_line.set_error(format!(
+                    "Couldn't generate follow-up questions ({}); retaking the same quiz",
+                    message
+                ));
+            }
+            QuizGenerationResult::Graded(grades) => {
+                for (index, correct) in grades {
+                    self.state.quiz.set_grade(index, correct);
+                }
+                self.state.quiz.grading = false;
+                self.state.command_line.clear_message();
+                self.record_quiz_result();
+            }
+            QuizGenerationResult::Explained(explanations) => {
+                for (index, explanation) in explanations {
+                    self.state.quiz.set_explanation(index, explanation);
+                }
+                self.state.quiz.explaining = false;
+            }
+            QuizGenerationResult::Error(message) if self.state.quiz.explaining => {
+                // The review still shows answers without explanations
+                self.state.quiz.explaining = false;
+                self.state.quiz.explanations.clear();
+                self.state.command_line.set_error(format!("Couldn't explain answers: {}", message));
+            }
+            QuizGenerationResult::Error(message) if self.state.quiz.grading => {
+                // Leave the quiz results up; ungraded answers count as incorrect
+                self.state.quiz.grading = false;
+                self.state.quiz.fail_ungraded();
+                self.record_quiz_result();
+                self.state.command_line.set_error(format!("Grading error: {}", message));
+            }
+            QuizGenerationResult::Error(message) => {
+                self.state.quiz.set_error(&message);
+                self.state.command_line.set_error(format!("Quiz error: {}", message));
             }
         }
+        self.quiz_rx = None;
     }
 
     /// Handle a single Claude streaming event
@@ -2685,36 +8614,43 @@ impl App {
 
         match event {
             StreamEvent::ContentBlockDelta { text } => {
-                self.state.claude.stream_buffer.push_str(&text);
+                self.state.claude.stream_throttle.push(&text);
             }
             StreamEvent::MessageStop => {
-                // Response complete - finalize and show the response panel
-                self.state.claude.finalize_response();
-
-                // Create note from Q&A if pending info exists
-                if self.state.claude.has_pending_note() {
-                    self.save_claude_qa_as_note();
-                }
-
-                self.state
-                    .command_line
-                    .set_message("Response ready (press 'c' to toggle, Esc to close)");
-                self.claude_rx = None;
-                self.claude_cancel = None;
+                // Response complete - finalized once the remaining text is shown
+                self.state.claude.stream_throttle.finished = true;
             }
             StreamEvent::Error { message } => {
                 self.state.claude.set_error(&message);
                 self.state.claude.streaming = false;
+                self.state.claude.stream_throttle.clear();
                 self.state.claude.clear_pending_note(); // Clear pending on error
+                self.state.claude.pending_summary = None;
+                self.state.claude.pending_glossary = None;
+                self.state.claude.pending_teach_back = None;
+                self.reset_recap_step();
                 self.claude_rx = None;
                 self.claude_cancel = None;
             }
-            StreamEvent::MessageStart { .. } => {
+            StreamEvent::MessageStart {
+                input_tokens,
+                cache_creation_input_tokens,
+                cache_read_input_tokens,
+                ..
+            } => {
                 // Response started
                 self.state.claude.clear_error();
+                self.state.claude.input_tokens = input_tokens;
+                self.state.claude.cache_tokens =
+                    (cache_creation_input_tokens, cache_read_input_tokens);
+            }
+            StreamEvent::MessageDelta { output_tokens, .. } => {
+                let input_tokens = std::mem::take(&mut self.state.claude.input_tokens);
+                let cache_tokens = std::mem::take(&mut self.state.claude.cache_tokens);
+                self.record_usage(self.claude_model(), input_tokens, output_tokens, cache_tokens);
             }
             _ => {
-                // Ignore other events (Ping, ContentBlockStart/Stop, MessageDelta)
+                // Ignore other events (Ping, ContentBlockStart/Stop)
             }
         }
     }
@@ -2772,43 +8708,213 @@ impl App {
         if let Some(token) = self.claude_cancel.take() {
             token.cancel();
         }
-        self.state.claude.streaming = false;
-        self.state.claude.stream_buffer.clear();
+        self.state.claude.clear_streaming();
+        self.state.claude.pending_summary = None;
+        self.state.claude.pending_glossary = None;
+        self.state.claude.pending_teach_back = None;
+        self.reset_recap_step();
         self.state.command_line.set_message("Request cancelled");
         self.claude_rx = None;
     }
 
     /// Handle input when Claude response panel is visible
-    fn handle_claude_panel_input(&mut self, key: KeyCode) {
+    fn handle_report_input(&mut self, key: KeyCode) {
+        let Some(report) = &mut self.state.report else { return };
+        // Scrolling past the end is clamped when drawn
         match key {
-            KeyCode::Esc | KeyCode::Char('q') => {
+            KeyCode::Esc | KeyCode::Char('q') => self.state.report = None,
+            KeyCode::Char('j') | KeyCode::Down => report.scroll = report.scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => report.scroll = report.scroll.saturating_sub(1),
+            KeyCode::Char('d') | KeyCode::PageDown => {
+                report.scroll = report.scroll.saturating_add(10);
+            }
+            KeyCode::Char('u') | KeyCode::PageUp => {
+                report.scroll = report.scroll.saturating_sub(10)
+            }
+            KeyCode::Char('g') | KeyCode::Home => report.scroll = 0,
+            KeyCode::Char('G') | KeyCode::End => report.scroll = u16::MAX,
+            _ => {}
+        }
+    }
+
+    fn handle_claude_panel_input(&mut self, action: Option<Action>) {
+        let Some(action) = action else { return };
+        match action {
+            Action::Back => {
                 self.state.claude.hide_response();
             }
-            KeyCode::Char('c') => {
+            Action::ToggleResponse => {
                 self.state.claude.toggle_response();
             }
-            KeyCode::Char('j') | KeyCode::Down => {
+            Action::Down | Action::LineDown => {
                 self.state.claude.scroll_response_down(1, 1000);
             }
-            KeyCode::Char('k') | KeyCode::Up => {
+            Action::Up | Action::LineUp => {
                 self.state.claude.scroll_response_up(1);
             }
-            KeyCode::Char('d') | KeyCode::PageDown => {
+            Action::PageDown | Action::HalfPageDown => {
                 self.state.claude.scroll_response_down(10, 1000);
             }
-            KeyCode::Char('u') | KeyCode::PageUp => {
+            Action::PageUp | Action::HalfPageUp => {
                 self.state.claude.scroll_response_up(10);
             }
-            KeyCode::Char('g') | KeyCode::Home => {
+            Action::Top => {
                 self.state.claude.response_scroll = 0;
             }
-            KeyCode::Char('G') | KeyCode::End => {
+            Action::Bottom => {
                 self.state.claude.scroll_response_down(10000, 10000);
             }
+            Action::Yank => self.copy_response(),
+            Action::Save => self.save_response_as_note(),
+            Action::CreateNote => self.append_response_to_note(),
+            Action::JumpForward => self.select_citation(true),
+            Action::JumpBack => self.select_citation(false),
+            Action::Select => {
+                if let Some(index) = self.state.claude.selected_citation {
+                    self.goto_citation(index);
+                }
+            }
             _ => {}
         }
     }
 
+    /// The finished response, or None (with an error shown) while there is none
+    fn finished_response(&mut self) -> Option<String> {
+        let claude = &self.state.claude;
+        let error = if claude.streaming {
+            "Wait for the response to finish"
+        } else if claude.response.trim().is_empty() {
+            "No response to use"
+        } else {
+            return Some(claude.response.clone());
+        };
+        self.state.command_line.set_error(error);
+        None
+    }
+
+    /// Copy the whole response to the clipboard
+    fn copy_response(&mut self) {
+        let Some(response) = self.finished_response() else { return };
+        if self.copy_to_clipboard(&response) {
+            self.state
+                .command_line
+                .set_message(format!("Copied response ({} characters)", response.chars().count()));
+        }
+    }
+
+    /// Save the response as a note on the current section, unless it is
+    /// already going to be saved as a Q&A note
+    fn save_response_as_note(&mut self) {
+        use crate::notes::{Note, NoteSource};
+
+        if self.state.claude.has_pending_note() {
+            self.state.command_line.set_message("Response will be saved as a Q&A note");
+            return;
+        }
+        let Some(response) = self.finished_response() else { return };
+        let Some((book_id, section_path)) = self.current_section_key() else {
+            self.state.command_line.set_error("No section to attach the note to");
+            return;
+        };
+
+        let mut note = Note::new_section_note(&book_id, &section_path, &response);
+        note.source = NoteSource::Claude;
+        let short_id = note.short_id();
+        self.notes_store.add_note(note);
+        if let Err(e) = self.notes_store.save() {
+            tracing::warn!("Failed to save notes: {}", e);
+        }
+        self.state.panel_visibility.notes = true;
+        self.state.command_line.set_message(format!("Response saved as note {}", short_id));
+    }
+
+    /// Add the response to the end of the note selected in the notes panel
+    fn append_response_to_note(&mut self) {
+        use crate::ui::notes_panel::get_selected_note;
+
+        let Some(note) = get_selected_note(&self.state, &self.notes_store) else {
+            self.state.command_line.set_error("No note selected");
+            return;
+        };
+        let (note_id, short_id) = (note.id.clone(), note.short_id());
+        let Some(response) = self.finished_response() else { return };
+
+        self.notes_store.append_to_note(&note_id, &response);
+        if let Err(e) = self.notes_store.save() {
+            tracing::warn!("Failed to save notes: {}", e);
+        }
+        self.state.command_line.set_message(format!("Response added to note {}", short_id));
+    }
+
+    /// Pick the next or previous citation in the response, scrolling to it
+    fn select_citation(&mut self, forward: bool) {
+        let claude = &mut self.state.claude;
+        let count = claude.citation_rows.len();
+        if count == 0 || claude.streaming {
+            return;
+        }
+        let index = match (claude.selected_citation, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+        };
+        claude.selected_citation = Some(index);
+        claude.response_scroll = claude.citation_rows[index].saturating_sub(2) as u16;
+    }
+
+    /// Close the response and scroll the content to a citation's passage
+    fn goto_citation(&mut self, index: usize) {
+        let quotes = crate::claude::citations::quotes(&self.state.claude.response);
+        let Some(quote) = quotes.get(index) else { return };
+        let Some(section_path) = self.state.claude.cited_section.clone() else { return };
+        let Some(book) = &self.state.book else { return };
+
+        let position = book.chapters.iter().enumerate().find_map(|(chapter_idx, chapter)| {
+            chapter
+                .sections
+                .iter()
+                .position(|s| s.path == section_path)
+                .map(|section_idx| (chapter_idx, section_idx, &chapter.sections[section_idx]))
+        });
+        let Some((chapter_idx, section_idx, section)) = position else {
+            self.state.command_line.set_error(format!("Section {} no longer exists", section_path));
+            return;
+        };
+        let Some(block) = crate::claude::citations::locate(quote, section) else {
+            self.state
+                .command_line
+                .set_error(format!("Quote [{}] not found in the section", index + 1));
+            return;
+        };
+
+        self.record_jump();
+        let same_section =
+            (chapter_idx, section_idx) == (self.state.current_chapter, self.state.current_section);
+        if same_section {
+            self.state.content.ensure_block_visible(block);
+        } else {
+            // Block offsets are only known for the section last rendered
+            self.remember_scroll();
+            self.state.current_chapter = chapter_idx;
+            self.state.current_section = section_idx;
+            self.state.content.scroll_offset = 0;
+            self.state.curriculum.expanded_chapters.insert(chapter_idx);
+            self.mark_section_viewed();
+        }
+
+        self.state.claude.selected_citation = Some(index);
+        self.state.claude.hide_response();
+        // Paragraph citations like ¶3 are shown as they are, quotes in quote marks
+        let cited =
+            if quote.starts_with('¶') { quote.clone() } else { format!("❝{}❞", quote) };
+        self.state.command_line.set_message(format!(
+            "[{}] {} (press c to return to the answer)",
+            index + 1,
+            cited
+        ));
+    }
+
     /// Start Claude setup wizard
     fn start_claude_setup(&mut self) {
         self.state.claude.start_setup();
@@ -2835,12 +8941,78 @@ impl App {
         }
     }
 
+    /// Fetch the models available to the API key, to be listed once they arrive
+    fn list_models(&mut self) {
+        if self.models_rx.is_some() {
+            self.state.command_line.set_error("Already listing models");
+            return;
+        }
+        if self.state.claude.needs_setup {
+            self.state.command_line.set_error("API key not set. Use :claude-key <key>");
+            return;
+        }
+        let api_key = match crate::claude::ApiKeyManager::get_api_key() {
+            Ok(key) => key,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to get API key: {}", e));
+                return;
+            }
+        };
+        self.state.command_line.set_message("Listing models...");
+
+        let client = self.claude_client(api_key);
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.models_rx = Some(rx);
+        tokio::spawn(async move {
+            let result = client.list_models().await.map_err(|e| e.to_string());
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Cache and show the models available once the API has listed them
+    fn process_model_list(&mut self) {
+        let Some(rx) = &mut self.models_rx else { return };
+        let Ok(result) = rx.try_recv() else { return };
+        self.models_rx = None;
+
+        let models = match result {
+            Ok(models) => models,
+            Err(e) => {
+                self.state.command_line.set_error(format!("Failed to list models: {}", e));
+                return;
+            }
+        };
+        self.models = crate::claude::ModelList::fetched(models);
+        if let Err(e) = self.models.save() {
+            tracing::warn!("Failed to save model list: {}", e);
+        }
+
+        let model = self.claude_model();
+        let current = model.model_id();
+        let mut text = String::new();
+        for model in &self.models.models {
+            let marker = if model.id == current { " (in use)" } else { "" };
+            text.push_str(&format!("- `{}` {}{}\n", model.id, model.display_name, marker));
+        }
+        self.state.report = Some(Report::new("Models", text));
+        self.state.command_line.set_message(format!(
+            "{} models available; choose one with :claude-model <id>",
+            self.models.models.len()
+        ));
+    }
+
     /// Set the Claude model
     fn set_claude_model(&mut self, model: crate::claude::ClaudeModel) {
-        self.state.claude.model = model;
-        self.state
-            .command_line
-            .set_message(format!("Claude model set to {}", model.display_name()));
+        self.state.claude.model = model.clone();
+        let mut message = format!("Claude model set to {}", self.models.display_name(&model));
+        let active = self.claude_model();
+        if active != model {
+            message.push_str(&format!(
+                " (this book uses {}; :bookset model off to follow)",
+                self.models.display_name(&active)
+            ));
+        }
+        self.state.command_line.set_message(message);
     }
 }
 
@@ -2860,3 +9032,93 @@ impl Drop for App {
         let _ = self.restore_terminal();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::state::QuizQuestion;
+
+    #[test]
+    fn quiz_guidance_follows_mastery_and_hard_questions() {
+        use crate::learning::rating::QuizRatings;
+
+        let mut ratings = QuizRatings::default();
+        assert_eq!(quiz_guidance(&ratings, "ch01/s01"), None);
+
+        for _ in 0..5 {
+            ratings.record("ch01/s01", "Why do moves invalidate?", false);
+            ratings.record("ch01/s01", "What is a borrow?", false);
+        }
+        ratings.record("ch02/s01", "Elsewhere", false);
+
+        let guidance = quiz_guidance(&ratings, "ch01/s01").unwrap();
+        assert!(guidance.starts_with("The learner has found this book's quizzes hard."));
+        assert!(guidance.contains("\n- Why do moves invalidate?"));
+        assert!(!guidance.contains("Elsewhere"));
+    }
+
+    #[test]
+    fn parse_quiz_json_mixed_types() {
+        let text = r#"```json
+{"questions": [
+  {"type": "multiple_choice", "question": "Q1", "options": ["a", "b", "c", "d"], "correct_index": 2},
+  {"type": "true_false", "question": "Q2", "answer": false},
+  {"type": "fill_blank", "question": "The ____ checker", "answer": "borrow"},
+  {"type": "short_answer", "question": "Q4", "answer": "Because"},
+  {"question": "Q5", "options": ["a", "b"], "correct_index": 0}
+]}
+```"#;
+        let questions = parse_quiz_json(text, 5).unwrap();
+        assert!(matches!(questions[0], QuizQuestion::MultipleChoice { correct_index: 2, .. }));
+        assert!(matches!(questions[1], QuizQuestion::TrueFalse { answer: false, .. }));
+        assert!(
+            matches!(&questions[2], QuizQuestion::FillInBlank { answer, .. } if answer == "borrow")
+        );
+        assert!(matches!(questions[3], QuizQuestion::ShortAnswer { .. }));
+        assert!(matches!(questions[4], QuizQuestion::MultipleChoice { .. }));
+    }
+
+    #[test]
+    fn parse_quiz_json_rejects_unknown_type() {
+        let question = r#"{"type": "essay", "question": "Q"}"#;
+        let text = format!(r#"{{"questions": [{}]}}"#, [question; 5].join(","));
+        assert!(parse_quiz_json(&text, 5).is_err());
+    }
+
+    #[test]
+    fn parse_quiz_json_checks_expected_count() {
+        let text = r#"{"questions": [{"type": "true_false", "question": "Q", "answer": true}]}"#;
+        assert_eq!(parse_quiz_json(text, 1).unwrap().len(), 1);
+        assert!(parse_quiz_json(text, 5).is_err());
+    }
+
+    #[test]
+    fn complete_question_objects_skips_partial_object() {
+        let text = r#"```json
+{"questions": [
+  {"type": "true_false", "question": "Braces {} and \"quotes\"", "answer": true},
+  {"type": "fill_blank", "question": "The ____"#;
+        let objects = complete_question_objects(text);
+        assert_eq!(objects.len(), 1);
+        let question = serde_json::from_str::<QuestionJson>(objects[0]).unwrap();
+        assert!(matches!(question_from_json(question).unwrap(), QuizQuestion::TrueFalse { .. }));
+
+        let done = format!(r#"{}", "answer": "x"}}]}}"#, text);
+        assert_eq!(complete_question_objects(&done).len(), 2);
+    }
+
+    #[test]
+    fn parse_grades_json_reads_grades() {
+        let grades = parse_grades_json(r#"{"grades": [{"index": 3, "correct": true}]}"#).unwrap();
+        assert_eq!(grades, vec![(3, true)]);
+    }
+
+    #[test]
+    fn parse_explanations_json_reads_explanations() {
+        let text = r#"```json
+{"explanations": [{"index": 1, "explanation": "Because ownership moves."}]}
+```"#;
+        let explanations = parse_explanations_json(text).unwrap();
+        assert_eq!(explanations, vec![(1, "Because ownership moves.".to_string())]);
+    }
+}
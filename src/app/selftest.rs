@@ -0,0 +1,269 @@
+//! Installation self-test
+//!
+//! `sensei selftest` exercises markdown and command parsing, rendering into an
+//! in-memory terminal, storage round-trips and, when an API key is stored, a
+//! minimal Claude request. It needs no terminal or book, so packagers and
+//! users can verify an install on platforms the TUI hasn't been tried on.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail, ensure};
+use ratatui::{Terminal, backend::TestBackend};
+
+use super::command::{Command, ParseResult, parse_command};
+use super::state::{AppState, Screen};
+use crate::book::markdown::parse_markdown_content;
+use crate::book::{Book, BookMetadata, BookSource, Chapter, ContentBlock, Section};
+use crate::claude::{ApiKeyManager, ClaudeClient};
+use crate::config::progress::{Progress, SectionProgress};
+use crate::config::{Config, encryption};
+use crate::notes::{Note, NotesStore};
+use crate::ui::image::ImageCache;
+
+/// Markdown covering the common block types
+const SAMPLE_MARKDOWN: &str = "# Ownership\n\n\
+    Each value has a single *owner*.\n\n\
+    ```rust\nlet s = String::from(\"hi\");\n```\n\n\
+    - moves\n- borrows\n\n\
+    > References must always be valid.\n";
+
+/// Result of one self-test step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The step passed
+    Pass,
+    /// The step failed, with the error
+    Fail(String),
+    /// The step didn't run, with the reason
+    Skip(String),
+}
+
+/// A named self-test step and its outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    /// What was tested
+    pub name: &'static str,
+    /// How it went
+    pub outcome: Outcome,
+}
+
+impl Step {
+    fn from_result(name: &'static str, result: Result<()>) -> Self {
+        let outcome = match result {
+            Ok(()) => Outcome::Pass,
+            Err(e) => Outcome::Fail(format!("{:#}", e)),
+        };
+        Self { name, outcome }
+    }
+}
+
+/// Run every step; the API step runs only with `api` set and a stored key
+pub async fn run(config: &Config, api: bool) -> Vec<Step> {
+    let mut steps = run_offline(config);
+    steps.push(check_api(api).await);
+    steps
+}
+
+/// Run the steps that need no network
+pub fn run_offline(config: &Config) -> Vec<Step> {
+    vec![
+        Step::from_result("Markdown parsing", check_markdown()),
+        Step::from_result("Command parsing", check_commands()),
+        Step::from_result("Rendering", check_rendering(config)),
+        Step::from_result("Storage", check_storage()),
+    ]
+}
+
+/// Format steps as a plain-text report with a pass/fail summary
+pub fn report(steps: &[Step]) -> String {
+    let mut out = String::from("sensei self-test\n\n");
+    for step in steps {
+        let line = match &step.outcome {
+            Outcome::Pass => format!("  PASS  {}\n", step.name),
+            Outcome::Fail(e) => format!("  FAIL  {}: {}\n", step.name, e),
+            Outcome::Skip(reason) => format!("  SKIP  {} ({})\n", step.name, reason),
+        };
+        out.push_str(&line);
+    }
+
+    let count = |f: fn(&Outcome) -> bool| steps.iter().filter(|s| f(&s.outcome)).count();
+    out.push_str(&format!(
+        "\n{} passed, {} failed, {} skipped\n",
+        count(|o| *o == Outcome::Pass),
+        count(|o| matches!(o, Outcome::Fail(_))),
+        count(|o| matches!(o, Outcome::Skip(_)))
+    ));
+    out
+}
+
+/// Whether no step failed
+pub fn passed(steps: &[Step]) -> bool {
+    !steps.iter().any(|s| matches!(s.outcome, Outcome::Fail(_)))
+}
+
+/// Markdown is split into the expected blocks
+fn check_markdown() -> Result<()> {
+    let blocks = parse_markdown_content(SAMPLE_MARKDOWN);
+    let has = |f: fn(&ContentBlock) -> bool| blocks.iter().any(f);
+
+    ensure!(has(|b| matches!(b, ContentBlock::Heading { level: 1, .. })), "no heading");
+    ensure!(has(|b| matches!(b, ContentBlock::Paragraph(_))), "no paragraph");
+    ensure!(
+        has(|b| matches!(b, ContentBlock::Code(code) if code.code.contains("String::from"))),
+        "no code block"
+    );
+    ensure!(
+        has(|b| matches!(b, ContentBlock::UnorderedList(items) if items.len() == 2)),
+        "no list"
+    );
+    ensure!(has(|b| matches!(b, ContentBlock::Blockquote(_))), "no blockquote");
+    Ok(())
+}
+
+/// Commands parse to what they name
+fn check_commands() -> Result<()> {
+    let cases = [
+        ("q", Command::Quit),
+        ("goto ch01/s02", Command::Goto("ch01/s02".to_string())),
+        ("ask what moves?", Command::Ask("what moves?".to_string())),
+    ];
+    for (input, expected) in cases {
+        match parse_command(input) {
+            ParseResult::Ok(command) if command == expected => {}
+            other => bail!(":{} parsed as {:?}", input, other),
+        }
+    }
+    ensure!(
+        matches!(parse_command("not-a-command"), ParseResult::UnknownCommand(_)),
+        "unknown commands are accepted"
+    );
+    Ok(())
+}
+
+/// The reading screen draws a book into an in-memory terminal
+fn check_rendering(config: &Config) -> Result<()> {
+    let mut section = Section::new("Moves", 1, "ch01/s01");
+    section.content = parse_markdown_content(SAMPLE_MARKDOWN).into();
+    let mut chapter = Chapter::new("Ownership", 1, "ch01");
+    chapter.sections.push(section);
+    let mut book = Book::new(sample_metadata());
+    book.chapters.push(chapter);
+
+    let mut state = AppState { screen: Screen::Main, book: Some(book), ..Default::default() };
+    let progress = Progress::default();
+    let notes = NotesStore::default();
+    let mut images = ImageCache::new_halfblocks();
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 40))?;
+    terminal
+        .draw(|frame| crate::ui::draw(frame, &mut state, config, &progress, &notes, &mut images))?;
+
+    let text: String =
+        terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+    ensure!(text.contains("Ownership"), "chapter title not drawn");
+    ensure!(text.contains("single"), "section text not drawn");
+    Ok(())
+}
+
+/// Notes and progress survive a write and read through a scratch directory
+fn check_storage() -> Result<()> {
+    let dir = ScratchDir::new()?;
+
+    let mut notes = NotesStore::default();
+    notes.add_note(Note::new_section_note("selftest", "ch01/s01", "Moves transfer ownership"));
+    let path = dir.0.join("notes.json");
+    encryption::write(&path, &serde_json::to_string(&notes)?)?;
+    let loaded: NotesStore = serde_json::from_str(&encryption::read(&path)?)?;
+    ensure!(
+        loaded.get_book_notes("selftest") == notes.get_book_notes("selftest"),
+        "notes changed after a round-trip"
+    );
+
+    let mut progress = Progress::default();
+    progress
+        .book_mut("selftest")
+        .sections
+        .insert("ch01/s01".into(), SectionProgress { completed: true, ..Default::default() });
+    let path = dir.0.join("progress.json");
+    std::fs::write(&path, serde_json::to_string(&progress)?)
+        .with_context(|| format!("Failed to write {:?}", path))?;
+    let loaded: Progress = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    ensure!(
+        loaded
+            .books
+            .get("selftest")
+            .and_then(|b| b.sections.get("ch01/s01"))
+            .is_some_and(|s| s.completed),
+        "progress changed after a round-trip"
+    );
+    Ok(())
+}
+
+/// A minimal request reaches the Claude API
+async fn check_api(enabled: bool) -> Step {
+    const NAME: &str = "Claude API";
+    if !enabled {
+        return Step { name: NAME, outcome: Outcome::Skip("--offline".to_string()) };
+    }
+    let Ok(key) = ApiKeyManager::get_api_key() else {
+        return Step { name: NAME, outcome: Outcome::Skip("no API key stored".to_string()) };
+    };
+    let result = ClaudeClient::new(key).with_max_retries(0).test_connection().await;
+    Step::from_result(NAME, result.map_err(anyhow::Error::from))
+}
+
+fn sample_metadata() -> BookMetadata {
+    BookMetadata {
+        id: "selftest".into(),
+        title: "Self-test".into(),
+        author: None,
+        source: BookSource::Markdown(PathBuf::new()),
+        language: None,
+        description: None,
+        cover_image: None,
+        added_at: 0,
+        last_accessed: None,
+    }
+}
+
+/// A temporary directory removed on drop
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new() -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("sensei-selftest-{}", std::process::id()));
+        std::fs::create_dir_all(&path).with_context(|| format!("Failed to create {:?}", path))?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_steps_pass() {
+        let steps = run_offline(&Config::default());
+        assert!(passed(&steps), "{}", report(&steps));
+    }
+
+    #[test]
+    fn report_counts_outcomes() {
+        let steps = vec![
+            Step { name: "Parsing", outcome: Outcome::Pass },
+            Step { name: "Storage", outcome: Outcome::Fail("disk full".into()) },
+            Step { name: "Claude API", outcome: Outcome::Skip("--offline".into()) },
+        ];
+        let out = report(&steps);
+
+        assert!(out.contains("  FAIL  Storage: disk full\n"));
+        assert!(out.ends_with("1 passed, 1 failed, 1 skipped\n"));
+        assert!(!passed(&steps));
+    }
+}
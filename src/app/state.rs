@@ -1,9 +1,19 @@
 //! Application state definitions
 
-use std::collections::HashSet;
-use std::time::Instant;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 use crate::book::Book;
+use crate::claude::buddy::Comparison;
+use crate::claude::recap::RecapSession;
+use crate::config::Typography;
+use crate::config::datetime::DateTimeFormat;
+use crate::config::progress::QuizAttempt;
+use crate::config::session::LastRead;
+use crate::learning::glossary::TermMatcher;
+use crate::notes::HighlightColor;
+use crate::ui::content::LayoutCache;
+use crate::ui::hyperlink::LinkHit;
 
 /// Which screen is currently displayed
 #[derive(Debug, Clone, Default)]
@@ -11,7 +21,6 @@ pub enum Screen {
     #[default]
     Landing,
     Main,
-    Quiz,
     Notes,
     Help,
 }
@@ -82,9 +91,39 @@ pub struct CurriculumState {
     pub scroll_offset: usize,
     /// Visible height in items (updated on render)
     pub visible_height: usize,
+    /// Text narrowing the tree to matching titles (empty when not filtering)
+    pub filter: String,
+    /// Whether the filter is being typed
+    pub filter_editing: bool,
 }
 
 impl CurriculumState {
+    /// Whether the tree is narrowed by a filter
+    pub fn is_filtered(&self) -> bool {
+        !self.filter.is_empty()
+    }
+
+    /// Whether a chapter or section title matches the filter (ignoring case and backticks)
+    pub fn matches_filter(&self, title: &str) -> bool {
+        let title = title.replace('`', "").to_lowercase();
+        title.contains(&self.filter.to_lowercase())
+    }
+
+    /// Expand a collapsed chapter, or collapse an expanded one
+    pub fn toggle_chapter(&mut self, chapter_idx: usize) {
+        if !self.expanded_chapters.remove(&chapter_idx) {
+            self.expanded_chapters.insert(chapter_idx);
+        }
+    }
+
+    /// Stop filtering and show the whole tree
+    pub fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.filter_editing = false;
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+    }
+
     /// Ensure the selected item is visible by adjusting scroll offset
     pub fn ensure_selection_visible(&mut self) {
         // Don't scroll past the selection (top)
@@ -99,6 +138,40 @@ impl CurriculumState {
     }
 }
 
+/// Preset for how a section is read, changing several settings at once
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadingMode {
+    /// Settings as configured
+    #[default]
+    Normal,
+    /// Side panels hidden and the text in a narrow centered column
+    Focus,
+    /// Only headings and the first sentence of each paragraph
+    Skim,
+    /// Note anchors and highlights emphasized, with the notes panel open
+    Review,
+}
+
+impl ReadingMode {
+    /// Every mode, in the order `:mode` lists them
+    pub const ALL: [ReadingMode; 4] = [Self::Normal, Self::Focus, Self::Skim, Self::Review];
+
+    /// Name used by `:mode` and in book settings
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Focus => "focus",
+            Self::Skim => "skim",
+            Self::Review => "review",
+        }
+    }
+
+    /// Mode by name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
 /// State for content rendering
 #[derive(Debug, Clone, Default)]
 pub struct ContentState {
@@ -118,6 +191,8 @@ pub struct ContentState {
     pub cursor_char: usize,
     /// Whether cursor mode is active (showing cursor in content)
     pub cursor_mode: bool,
+    /// Table block scrolled sideways with h/l, and by how many columns
+    pub table_scroll: Option<(usize, usize)>,
     /// Frame counter for cursor blinking (toggles every N frames)
     pub cursor_blink_frame: usize,
     /// Starting line number for each content block (computed during render)
@@ -130,6 +205,39 @@ pub struct ContentState {
     pub footer_focused: bool,
     /// Which footer button is selected (0 = Quiz, 1 = Next)
     pub footer_button_index: usize,
+    /// Quiz attempts at the current section, oldest first, shown in the footer
+    pub quiz_history: Vec<QuizAttempt>,
+    /// The current section's learning objectives and whether each is met,
+    /// shown above the text
+    pub objectives: Vec<(String, bool)>,
+    /// Whether the objectives are folded to a single line
+    pub objectives_collapsed: bool,
+    /// Terms from the open book's glossary, highlighted in the text
+    pub glossary: Option<TermMatcher>,
+    /// Section path that `seen_lines` belongs to
+    pub seen_section: Option<String>,
+    /// Which rendered lines have been on screen in the current section
+    pub seen_lines: Vec<bool>,
+    /// Whether the current section has already been auto-marked this visit
+    pub auto_marked: bool,
+    /// Section path and scroll offset to return to after jumping to a footnote
+    pub footnote_return: Option<(String, usize)>,
+    /// Maximum text width in columns (from `Config::text_width`); None fills the panel
+    pub max_width: Option<usize>,
+    /// Justification and hyphenation (from `Config::typography`)
+    pub typography: Typography,
+    /// Reading mode preset (from the book's settings, or toggled with Z)
+    pub reading_mode: ReadingMode,
+    /// Blocks expanded in skim view, by book ID and section path
+    pub skim_expanded: HashMap<(String, String), BTreeSet<usize>>,
+    /// Lines laid out for the current section on the last render
+    pub layout_cache: Option<LayoutCache>,
+    /// External links on screen, written as terminal hyperlinks after drawing
+    pub link_hits: Vec<LinkHit>,
+    /// Where reading last stopped in the open book
+    pub last_read: Option<LastRead>,
+    /// Section path and scroll offset of the viewport, and since when it has been there
+    pub dwell: Option<(String, usize, Instant)>,
 }
 
 impl ContentState {
@@ -138,6 +246,11 @@ impl ContentState {
         self.total_lines.saturating_sub(self.visible_height / 2)
     }
 
+    /// Whether the last fifth of the section is on screen
+    pub fn near_end(&self) -> bool {
+        self.total_lines > 0 && self.scroll_offset + self.visible_height >= self.total_lines * 4 / 5
+    }
+
     /// Clamp scroll offset to valid range
     pub fn clamp_scroll(&mut self) {
         let max = self.max_scroll();
@@ -146,6 +259,69 @@ impl ContentState {
         }
     }
 
+    /// Record the currently visible lines as seen, resetting when the section changes
+    pub fn record_seen_lines(&mut self, section_path: &str) {
+        if self.seen_section.as_deref() != Some(section_path) {
+            self.seen_section = Some(section_path.to_string());
+            self.seen_lines.clear();
+            self.auto_marked = false;
+        }
+
+        if self.seen_lines.len() != self.total_lines {
+            self.seen_lines.resize(self.total_lines, false);
+        }
+
+        let end = (self.scroll_offset + self.visible_height).min(self.total_lines);
+        for seen in self.seen_lines.iter_mut().take(end).skip(self.scroll_offset) {
+            *seen = true;
+        }
+    }
+
+    /// Carry line-indexed state over to a reflow of the same section, from
+    /// `old_total` lines to the current `total_lines`
+    ///
+    /// A footnote return point in another section keeps its offset, as that
+    /// section wasn't laid out again.
+    pub fn rescale_lines(&mut self, section_path: &str, old_total: usize) {
+        let total = self.total_lines;
+        if old_total == 0 || total == 0 || old_total == total {
+            return;
+        }
+        let seen = std::mem::take(&mut self.seen_lines);
+        if !seen.is_empty() {
+            self.seen_lines = (0..total).map(|i| seen[i * seen.len() / total]).collect();
+        }
+        if let Some((path, offset)) = &mut self.footnote_return {
+            if path == section_path {
+                *offset = *offset * total / old_total;
+            }
+        }
+    }
+
+    /// Percentage (0-100) of the section's lines that have been on screen
+    pub fn seen_percent(&self) -> u8 {
+        if self.seen_lines.is_empty() {
+            return 0;
+        }
+        let seen = self.seen_lines.iter().filter(|s| **s).count();
+        (seen * 100 / self.seen_lines.len()) as u8
+    }
+
+    /// Whether the section was scrolled to the end with enough of it seen on the way
+    pub fn is_read_through(&self, threshold_percent: u8) -> bool {
+        self.total_lines > 0
+            && self.scroll_offset >= self.max_scroll()
+            && self.seen_percent() >= threshold_percent.min(100)
+    }
+
+    /// Blocks at least partly on screen
+    pub fn visible_blocks(&self) -> std::ops::Range<usize> {
+        let offsets = &self.block_line_offsets;
+        let bottom = self.scroll_offset + self.visible_height.max(1);
+        let first = offsets.partition_point(|&o| o <= self.scroll_offset).saturating_sub(1);
+        first..offsets.partition_point(|&o| o < bottom).max(first)
+    }
+
     /// Get the starting line number for a given block index
     pub fn get_block_line(&self, block_index: usize) -> usize {
         self.block_line_offsets.get(block_index).copied().unwrap_or(0)
@@ -167,6 +343,26 @@ impl ContentState {
         self.clamp_scroll();
     }
 
+    /// Scroll to a footnote block, remembering the position to jump back to
+    pub fn jump_to_footnote(&mut self, section_path: &str, block_index: usize) {
+        self.footnote_return = Some((section_path.to_string(), self.scroll_offset));
+        self.ensure_block_visible(block_index);
+    }
+
+    /// Jump back to where the last footnote jump in this section started
+    ///
+    /// Returns false if there is nothing to return to.
+    pub fn return_from_footnote(&mut self, section_path: &str) -> bool {
+        match self.footnote_return.take() {
+            Some((path, offset)) if path == section_path => {
+                self.scroll_offset = offset;
+                self.clamp_scroll();
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Enter cursor mode at the top of visible content
     pub fn enter_cursor_mode(&mut self, first_visible_block: usize) {
         self.cursor_mode = true;
@@ -178,6 +374,12 @@ impl ContentState {
     /// Exit cursor mode
     pub fn exit_cursor_mode(&mut self) {
         self.cursor_mode = false;
+        self.table_scroll = None;
+    }
+
+    /// Columns the table at `block` is scrolled sideways
+    pub fn table_scroll_at(&self, block: usize) -> usize {
+        self.table_scroll.filter(|&(b, _)| b == block).map_or(0, |(_, columns)| columns)
     }
 
     /// Move cursor left
@@ -444,6 +646,37 @@ impl ContentState {
     }
 }
 
+/// State of the help screen
+#[derive(Debug, Clone, Default)]
+pub struct HelpState {
+    /// First line shown
+    pub scroll: usize,
+    /// Lines that fit on the screen, as last drawn
+    pub height: usize,
+    /// Text the keys are filtered by
+    pub search: String,
+    /// Whether the filter is being typed
+    pub search_editing: bool,
+}
+
+/// Text shown over the main screen until dismissed, kept apart from Claude's
+/// answer so that showing it doesn't lose the answer
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Panel title
+    pub title: String,
+    /// Markdown text
+    pub text: String,
+    /// First line shown
+    pub scroll: u16,
+}
+
+impl Report {
+    pub fn new(title: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { title: title.into(), text: text.into(), scroll: 0 }
+    }
+}
+
 /// State for search mode
 #[derive(Debug, Clone, Default)]
 pub struct SearchState {
@@ -453,15 +686,415 @@ pub struct SearchState {
     pub query: String,
 }
 
-/// A single quiz question
+/// Maximum number of entries kept in a jump list
+const MAX_JUMPS: usize = 100;
+
+/// A reading position recorded in the jump list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JumpPosition {
+    /// Chapter index
+    pub chapter: usize,
+    /// Section index within the chapter
+    pub section: usize,
+    /// Content scroll offset
+    pub scroll_offset: usize,
+}
+
+/// Vim-style jump list for moving back and forward between jump origins
+///
+/// `index` equals the number of entries while at the newest position, and
+/// points at the entry being visited after moving back.
+#[derive(Debug, Clone, Default)]
+pub struct JumpList {
+    entries: Vec<JumpPosition>,
+    index: usize,
+}
+
+impl JumpList {
+    /// Record the position a jump starts from
+    ///
+    /// Jumping after moving back discards the newer entries, like a browser.
+    pub fn push(&mut self, from: JumpPosition) {
+        self.entries.truncate(self.index);
+        if self.entries.last() != Some(&from) {
+            self.entries.push(from);
+        }
+        if self.entries.len() > MAX_JUMPS {
+            self.entries.remove(0);
+        }
+        self.index = self.entries.len();
+    }
+
+    /// Move back from `current`, returning the position to go to
+    pub fn back(&mut self, current: JumpPosition) -> Option<JumpPosition> {
+        if self.index == 0 {
+            return None;
+        }
+        // Remember where we are so forward can return here
+        if self.index == self.entries.len() {
+            if self.entries.last() == Some(&current) {
+                self.index -= 1;
+                if self.index == 0 {
+                    return None;
+                }
+            } else {
+                self.entries.push(current);
+            }
+        }
+        self.index -= 1;
+        self.entries.get(self.index).copied()
+    }
+
+    /// Move forward again after moving back
+    pub fn forward(&mut self) -> Option<JumpPosition> {
+        if self.index + 1 >= self.entries.len() {
+            return None;
+        }
+        self.index += 1;
+        self.entries.get(self.index).copied()
+    }
+}
+
+/// How long progress changes are held before being written, so a burst of
+/// changes is saved once
+pub const PROGRESS_SAVE_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// When progress and session state are next due to be written to disk
+#[derive(Debug, Clone)]
+pub struct Autosave {
+    /// When progress first changed since it was last written
+    progress_changed: Option<Instant>,
+    /// When the session was last written
+    session_saved: Instant,
+    /// Book ID and section path the session was last written at
+    session_section: Option<(String, String)>,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self { progress_changed: None, session_saved: Instant::now(), session_section: None }
+    }
+}
+
+impl Autosave {
+    /// Note that progress has changed and needs writing
+    pub fn progress_changed(&mut self, now: Instant) {
+        self.progress_changed.get_or_insert(now);
+    }
+
+    /// Whether unsaved progress has waited long enough to be written
+    pub fn progress_due(&self, now: Instant) -> bool {
+        self.progress_changed.is_some_and(|at| now.duration_since(at) >= PROGRESS_SAVE_DELAY)
+    }
+
+    /// Whether any progress is waiting to be written
+    pub fn progress_pending(&self) -> bool {
+        self.progress_changed.is_some()
+    }
+
+    /// Note that progress has been written
+    pub fn progress_saved(&mut self) {
+        self.progress_changed = None;
+    }
+
+    /// Whether the session should be written: the reader has moved to another
+    /// section, or `interval` has passed since it was last written
+    pub fn session_due(
+        &self,
+        section: Option<(&str, &str)>,
+        interval: Option<std::time::Duration>,
+        now: Instant,
+    ) -> bool {
+        let moved = section.is_some_and(|(book, path)| {
+            self.session_section.as_ref().is_none_or(|(b, p)| b != book || p != path)
+        });
+        moved || interval.is_some_and(|i| now.duration_since(self.session_saved) >= i)
+    }
+
+    /// Note that the session has been written at a section
+    pub fn session_saved(&mut self, section: Option<(&str, &str)>, now: Instant) {
+        self.session_saved = now;
+        if let Some((book, path)) = section {
+            self.session_section = Some((book.to_string(), path.to_string()));
+        }
+    }
+}
+
+/// How often a markdown book's files are checked for changes
+pub const SOURCE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Watches the files of an open markdown book, so edits made while reading
+/// show up without a restart
+///
+/// The files are walked on a background thread, so a large book directory
+/// doesn't hold up drawing.
+#[derive(Debug)]
+pub struct SourceWatch {
+    /// ID of the book being watched
+    pub book_id: String,
+    /// The book's source directory
+    path: std::path::PathBuf,
+    /// Newest modification time seen among its files
+    modified: Option<std::time::SystemTime>,
+    /// When the files were last checked
+    checked: Instant,
+    /// Newest modification time found by the walk in progress
+    walk: Option<std::sync::mpsc::Receiver<Option<std::time::SystemTime>>>,
+}
+
+impl SourceWatch {
+    /// Start watching a book's source directory
+    pub fn new(book_id: &str, path: &std::path::Path, now: Instant) -> Self {
+        Self {
+            book_id: book_id.to_string(),
+            path: path.to_path_buf(),
+            modified: crate::book::storage::markdown_modified(path),
+            checked: now,
+            walk: None,
+        }
+    }
+
+    /// Whether a walk found the files changed since last seen, starting a
+    /// new walk at most every `SOURCE_CHECK_INTERVAL`
+    pub fn changed(&mut self, now: Instant) -> bool {
+        use std::sync::mpsc::TryRecvError;
+
+        if let Some(walk) = &self.walk {
+            match walk.try_recv() {
+                Ok(modified) => {
+                    self.walk = None;
+                    let changed = modified != self.modified;
+                    self.modified = modified;
+                    return changed;
+                }
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => self.walk = None,
+            }
+        }
+        if now.duration_since(self.checked) >= SOURCE_CHECK_INTERVAL {
+            self.checked = now;
+            let (tx, rx) = std::sync::mpsc::channel();
+            let path = self.path.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send(crate::book::storage::markdown_modified(&path));
+            });
+            self.walk = Some(rx);
+        }
+        false
+    }
+
+    /// Whether the files are being walked
+    pub fn walking(&self) -> bool {
+        self.walk.is_some()
+    }
+}
+
+/// A second section shown beside the main content, for comparing passages
 #[derive(Debug, Clone, Default)]
-pub struct QuizQuestion {
-    /// The question text
-    pub question: String,
-    /// Answer options (typically 4)
-    pub options: Vec<String>,
-    /// Index of the correct answer (0-3)
-    pub correct_index: usize,
+pub struct SplitPane {
+    /// Another book open in the pane; None shows the main book
+    pub book: Option<Book>,
+    /// Chapter index of the section shown
+    pub chapter: usize,
+    /// Section index of the section shown
+    pub section: usize,
+    /// Scroll position (lines from top)
+    pub scroll_offset: usize,
+    /// Lines the pane can scroll (updated on render)
+    pub max_scroll: usize,
+    /// When scrolling in step with the main content, how far the pane is
+    /// scrolled past it
+    pub scroll_bind: Option<isize>,
+    /// Whether the pane, rather than the main content, has focus
+    pub focused: bool,
+    /// Lines laid out on the last render
+    pub layout_cache: Option<LayoutCache>,
+}
+
+impl SplitPane {
+    /// Open a section of `book` (None for the main book) in the pane
+    pub fn new(book: Option<Book>, chapter: usize, section: usize) -> Self {
+        Self { book, chapter, section, ..Default::default() }
+    }
+
+    /// Start or stop scrolling in step with the main content at `main_offset`,
+    /// keeping the current distance between the two; returns whether bound
+    pub fn toggle_scroll_bind(&mut self, main_offset: usize) -> bool {
+        self.scroll_offset = self.offset_for(main_offset);
+        self.scroll_bind = match self.scroll_bind {
+            Some(_) => None,
+            None => Some(self.scroll_offset as isize - main_offset as isize),
+        };
+        self.scroll_bind.is_some()
+    }
+
+    /// Scroll position for the main content at `main_offset`
+    pub fn offset_for(&self, main_offset: usize) -> usize {
+        let offset = match self.scroll_bind {
+            Some(bind) => (main_offset as isize + bind).max(0) as usize,
+            None => self.scroll_offset,
+        };
+        offset.min(self.max_scroll)
+    }
+
+    /// Scroll the pane by `delta` lines
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.scroll_offset =
+            (self.scroll_offset as isize + delta).clamp(0, self.max_scroll as isize) as usize;
+    }
+}
+
+/// A single quiz question
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuizQuestion {
+    /// Pick the correct option from a list
+    MultipleChoice {
+        /// The question text
+        question: String,
+        /// Answer options (typically 4)
+        options: Vec<String>,
+        /// Index of the correct answer (0-3)
+        correct_index: usize,
+    },
+    /// Decide whether a statement is true or false
+    TrueFalse {
+        /// The statement to judge
+        question: String,
+        /// Whether the statement is true
+        answer: bool,
+    },
+    /// Type the word or phrase that fills the blank (`____`) in the question
+    FillInBlank {
+        /// The question text containing a blank
+        question: String,
+        /// The expected word or phrase
+        answer: String,
+    },
+    /// Free-text answer graded by Claude against a reference answer
+    ShortAnswer {
+        /// The question text
+        question: String,
+        /// Model answer used when grading
+        reference_answer: String,
+    },
+}
+
+impl QuizQuestion {
+    /// Identity of the question, which rewording its case, punctuation or
+    /// spacing doesn't change (see `question_id`)
+    pub fn id(&self) -> String {
+        question_id(self.question())
+    }
+
+    /// Get the question text
+    pub fn question(&self) -> &str {
+        match self {
+            Self::MultipleChoice { question, .. }
+            | Self::TrueFalse { question, .. }
+            | Self::FillInBlank { question, .. }
+            | Self::ShortAnswer { question, .. } => question,
+        }
+    }
+
+    /// Options to choose from (empty for typed answers)
+    pub fn options(&self) -> Vec<&str> {
+        match self {
+            Self::MultipleChoice { options, .. } => options.iter().map(String::as_str).collect(),
+            Self::TrueFalse { .. } => vec!["True", "False"],
+            Self::FillInBlank { .. } | Self::ShortAnswer { .. } => Vec::new(),
+        }
+    }
+
+    /// Whether the question is answered by typing text
+    pub fn takes_text(&self) -> bool {
+        matches!(self, Self::FillInBlank { .. } | Self::ShortAnswer { .. })
+    }
+
+    /// Index of the correct option, for questions answered by choosing one
+    pub fn correct_option(&self) -> Option<usize> {
+        match self {
+            Self::MultipleChoice { correct_index, .. } => Some(*correct_index),
+            // Option 0 is "True", option 1 is "False"
+            Self::TrueFalse { answer, .. } => Some(if *answer { 0 } else { 1 }),
+            Self::FillInBlank { .. } | Self::ShortAnswer { .. } => None,
+        }
+    }
+
+    /// Short label describing the question type
+    pub fn kind_label(&self) -> &'static str {
+        match self {
+            Self::MultipleChoice { .. } => "Multiple choice",
+            Self::TrueFalse { .. } => "True or false",
+            Self::FillInBlank { .. } => "Fill in the blank",
+            Self::ShortAnswer { .. } => "Short answer",
+        }
+    }
+
+    /// The correct answer as display text
+    pub fn answer_text(&self) -> String {
+        match self {
+            Self::MultipleChoice { options, correct_index, .. } => {
+                options.get(*correct_index).cloned().unwrap_or_default()
+            }
+            Self::TrueFalse { answer, .. } => if *answer { "True" } else { "False" }.to_string(),
+            Self::FillInBlank { answer, .. } => answer.clone(),
+            Self::ShortAnswer { reference_answer, .. } => reference_answer.clone(),
+        }
+    }
+
+    /// Describe a user's answer to this question as display text
+    pub fn describe_answer(&self, answer: &QuizAnswer) -> String {
+        match answer {
+            QuizAnswer::Choice(i) => self.options().get(*i).copied().unwrap_or("").to_string(),
+            QuizAnswer::Text(text) => text.clone(),
+        }
+    }
+
+    /// Grade an answer locally. Returns None when Claude needs to grade it.
+    pub fn grade(&self, answer: &QuizAnswer) -> Option<bool> {
+        match (self, answer) {
+            (Self::MultipleChoice { correct_index, .. }, QuizAnswer::Choice(i)) => {
+                Some(i == correct_index)
+            }
+            // Option 0 is "True", option 1 is "False"
+            (Self::TrueFalse { answer, .. }, QuizAnswer::Choice(i)) => Some((*i == 0) == *answer),
+            (Self::FillInBlank { answer, .. }, QuizAnswer::Text(text)) => {
+                Some(normalize_answer(text) == normalize_answer(answer))
+            }
+            (Self::ShortAnswer { .. }, QuizAnswer::Text(_)) => None,
+            // Mismatched answer kind can never be correct
+            _ => Some(false),
+        }
+    }
+}
+
+/// Identity of a question's text: its words, lowercased, without punctuation
+pub fn question_id(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Normalize a typed answer for lenient comparison
+fn normalize_answer(text: &str) -> String {
+    text.trim()
+        .trim_matches(|c: char| c.is_ascii_punctuation() && c != '_')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// A user's answer to a quiz question
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuizAnswer {
+    /// Index of the chosen option
+    Choice(usize),
+    /// Typed answer text
+    Text(String),
 }
 
 /// State for section quiz
@@ -474,17 +1107,47 @@ pub struct QuizState {
     /// Current question index (0-4)
     pub current_question: usize,
     /// User's answers (None = not answered yet)
-    pub answers: Vec<Option<usize>>,
+    pub answers: Vec<Option<QuizAnswer>>,
+    /// Whether each answer was correct (None = not graded yet)
+    pub grades: Vec<Option<bool>>,
     /// Currently selected answer option (0-3)
     pub selected_option: usize,
+    /// Text typed for fill-in-the-blank and short answer questions
+    pub text_input: String,
+    /// Cursor position in text input (character index)
+    pub text_cursor: usize,
     /// Quiz completed (showing results)
     pub completed: bool,
     /// Loading state (waiting for Claude to generate questions)
     pub loading: bool,
+    /// More questions are still streaming in
+    pub generating: bool,
+    /// Number of questions requested while generating
+    pub expected: usize,
+    /// Waiting for Claude to grade short answers
+    pub grading: bool,
     /// Error message if generation failed
     pub error: Option<String>,
     /// Section path this quiz is for
     pub section_path: Option<String>,
+    /// Attempt number for this section's quiz (1 = first attempt)
+    pub attempt: u32,
+    /// Reviewing answers one question at a time after completing the quiz
+    pub reviewing: bool,
+    /// Question shown in review
+    pub review_index: usize,
+    /// Claude's explanation for each question (empty until requested)
+    pub explanations: Vec<Option<String>>,
+    /// Waiting for Claude to explain the answers
+    pub explaining: bool,
+    /// Whether each graded answer has updated the question and mastery ratings
+    pub rated: Vec<bool>,
+    /// When the first question of this attempt appeared
+    pub started: Option<Instant>,
+    /// How long the attempt took, fixed once it finished
+    pub duration: Option<Duration>,
+    /// Question bank editor, opened from the results
+    pub editor: Option<QuestionEditor>,
 }
 
 impl QuizState {
@@ -492,28 +1155,166 @@ impl QuizState {
     pub fn start_loading(&mut self, section_path: &str) {
         self.active = true;
         self.loading = true;
+        self.generating = false;
+        self.expected = 0;
+        self.grading = false;
         self.completed = false;
         self.questions.clear();
         self.answers.clear();
+        self.grades.clear();
+        self.rated.clear();
         self.current_question = 0;
         self.selected_option = 0;
+        self.clear_text_input();
         self.error = None;
         self.section_path = Some(section_path.to_string());
+        self.attempt = 1;
+        self.started = None;
+        self.clear_review();
+    }
+
+    /// Start loading follow-up questions to replace the ones missed
+    pub fn start_regenerating(&mut self) {
+        self.loading = true;
+        self.completed = false;
+        self.grading = false;
+        self.error = None;
+        self.attempt += 1;
+        self.started = None;
+        self.clear_review();
+    }
+
+    /// Whether follow-up questions are being generated for a retry
+    pub fn is_regenerating(&self) -> bool {
+        self.loading && !self.questions.is_empty()
+    }
+
+    /// The attempt just finished, for the quiz history
+    pub fn attempt_record(&self, at: i64) -> QuizAttempt {
+        QuizAttempt {
+            at,
+            attempt: self.attempt,
+            duration_secs: self.elapsed().map_or(0, |elapsed| elapsed.as_secs()),
+            results: self.grades.iter().map(|g| *g == Some(true)).collect(),
+        }
+    }
+
+    /// Indices of questions that were not answered correctly
+    pub fn missed(&self) -> Vec<usize> {
+        (0..self.questions.len()).filter(|&i| self.grades[i] != Some(true)).collect()
+    }
+
+    /// Replace missed questions with follow-ups, keeping correct answers
+    pub fn replace_missed(&mut self, questions: Vec<QuizQuestion>) {
+        for (index, question) in self.missed().into_iter().zip(questions) {
+            self.questions[index] = question;
+            self.answers[index] = None;
+            self.grades[index] = None;
+            if let Some(rated) = self.rated.get_mut(index) {
+                *rated = false;
+            }
+        }
+        self.loading = false;
+        self.started = Some(Instant::now());
+        self.current_question = self.answers.iter().position(Option::is_none).unwrap_or(0);
+        self.selected_option = 0;
+        self.clear_text_input();
+    }
+
+    /// Expect `count` questions to stream in one at a time
+    pub fn start_generating(&mut self, count: usize) {
+        self.generating = true;
+        self.expected = count;
+    }
+
+    /// Add a question as it streams in; the first one ends loading
+    pub fn push_question(&mut self, question: QuizQuestion) {
+        let waiting = self.awaiting_question();
+        self.questions.push(question);
+        self.answers.push(None);
+        self.grades.push(None);
+        self.rated.push(false);
+        if self.loading {
+            self.started = Some(Instant::now());
+        }
+        if self.loading || waiting {
+            self.loading = false;
+            self.current_question = self.questions.len() - 1;
+            self.selected_option = 0;
+            self.clear_text_input();
+        }
+    }
+
+    /// Stop expecting questions, finishing the quiz if all have been answered
+    pub fn finish_generating(&mut self) {
+        let waiting = self.awaiting_question();
+        self.generating = false;
+        if waiting && !self.questions.is_empty() {
+            self.finish();
+        }
+    }
+
+    /// Finish the attempt, stopping its clock
+    fn finish(&mut self) {
+        self.completed = true;
+        self.duration = self.started.map(|started| started.elapsed());
+    }
+
+    /// Time spent on this attempt: until it finished, or so far
+    pub fn elapsed(&self) -> Option<Duration> {
+        if self.completed { self.duration } else { self.started.map(|s| s.elapsed()) }
+    }
+
+    /// Whether every question so far is answered and the next is still generating
+    pub fn awaiting_question(&self) -> bool {
+        self.generating && !self.loading && self.current_question >= self.questions.len()
+    }
+
+    /// Number of questions in the quiz, including those still generating
+    pub fn total(&self) -> usize {
+        if self.generating { self.expected.max(self.questions.len()) } else { self.questions.len() }
     }
 
     /// Set questions after Claude generates them
     pub fn set_questions(&mut self, questions: Vec<QuizQuestion>) {
         self.questions = questions;
         self.answers = vec![None; self.questions.len()];
+        self.grades = vec![None; self.questions.len()];
+        self.rated = vec![false; self.questions.len()];
         self.loading = false;
+        self.generating = false;
+        self.started = Some(Instant::now());
         self.current_question = 0;
         self.selected_option = 0;
+        self.clear_text_input();
     }
 
     /// Set error state
     pub fn set_error(&mut self, message: &str) {
         self.error = Some(message.to_string());
         self.loading = false;
+        self.generating = false;
+        self.grading = false;
+        self.explaining = false;
+    }
+
+    /// Get the question currently being answered
+    pub fn current(&self) -> Option<&QuizQuestion> {
+        self.questions.get(self.current_question)
+    }
+
+    /// Whether a question is on screen waiting to be answered
+    pub fn is_answering(&self) -> bool {
+        self.active
+            && !self.loading
+            && !self.completed
+            && self.error.is_none()
+            && self.editor.is_none()
+    }
+
+    /// Whether the current question is answered by typing
+    pub fn is_text_question(&self) -> bool {
+        self.current().is_some_and(QuizQuestion::takes_text)
     }
 
     /// Select previous answer option
@@ -525,35 +1326,163 @@ impl QuizState {
 
     /// Select next answer option
     pub fn select_next(&mut self) {
-        let max_options =
-            self.questions.get(self.current_question).map(|q| q.options.len()).unwrap_or(4);
+        let max_options = self.current().map(|q| q.options().len()).unwrap_or(4);
         if self.selected_option + 1 < max_options {
             self.selected_option += 1;
         }
     }
 
+    /// Select an answer option directly; false if the question has no such option
+    pub fn select_option(&mut self, index: usize) -> bool {
+        let options = self.current().map_or(0, |q| q.options().len());
+        if index >= options {
+            return false;
+        }
+        self.selected_option = index;
+        true
+    }
+
+    /// Go back to the previous question, showing its answer to change
+    pub fn prev_question(&mut self) {
+        if self.current_question > 0 {
+            self.show_question(self.current_question - 1);
+        }
+    }
+
+    /// Go on to the next question, if the current one has been answered
+    pub fn next_question(&mut self) {
+        let answered = self.answers.get(self.current_question).is_some_and(Option::is_some);
+        if answered && self.current_question + 1 < self.questions.len() {
+            self.show_question(self.current_question + 1);
+        }
+    }
+
+    /// Show a question with its answer so far selected or typed
+    fn show_question(&mut self, index: usize) {
+        self.current_question = index;
+        self.selected_option = 0;
+        self.clear_text_input();
+        match self.answers.get(index).cloned().flatten() {
+            Some(QuizAnswer::Choice(option)) => self.selected_option = option,
+            Some(QuizAnswer::Text(text)) => {
+                self.text_cursor = text.chars().count();
+                self.text_input = text;
+            }
+            None => {}
+        }
+    }
+
+    /// Insert a character into the typed answer
+    pub fn insert_char(&mut self, c: char) {
+        let byte_pos = self
+            .text_input
+            .char_indices()
+            .nth(self.text_cursor)
+            .map_or(self.text_input.len(), |(pos, _)| pos);
+        self.text_input.insert(byte_pos, c);
+        self.text_cursor += 1;
+    }
+
+    /// Delete the character before the cursor in the typed answer
+    pub fn delete_char(&mut self) {
+        if self.text_cursor > 0 {
+            self.text_cursor -= 1;
+            if let Some((pos, _)) = self.text_input.char_indices().nth(self.text_cursor) {
+                self.text_input.remove(pos);
+            }
+        }
+    }
+
+    /// Move the typed answer cursor left
+    pub fn move_left(&mut self) {
+        self.text_cursor = self.text_cursor.saturating_sub(1);
+    }
+
+    /// Move the typed answer cursor right
+    pub fn move_right(&mut self) {
+        if self.text_cursor < self.text_input.chars().count() {
+            self.text_cursor += 1;
+        }
+    }
+
+    /// Clear the typed answer
+    fn clear_text_input(&mut self) {
+        self.text_input.clear();
+        self.text_cursor = 0;
+    }
+
     /// Confirm current answer and move to next question
     pub fn confirm_answer(&mut self) {
-        if self.current_question < self.questions.len() {
-            self.answers[self.current_question] = Some(self.selected_option);
+        let Some(question) = self.current() else { return };
 
-            if self.current_question + 1 < self.questions.len() {
-                self.current_question += 1;
-                self.selected_option = 0;
-            } else {
-                self.completed = true;
+        let answer = if question.takes_text() {
+            let text = self.text_input.trim().to_string();
+            if text.is_empty() {
+                return;
             }
+            QuizAnswer::Text(text)
+        } else {
+            QuizAnswer::Choice(self.selected_option)
+        };
+
+        self.grades[self.current_question] = question.grade(&answer);
+        self.answers[self.current_question] = Some(answer);
+
+        // Move to the next unanswered question (earlier ones may be kept from a retry)
+        let next =
+            (self.current_question + 1..self.questions.len()).find(|&i| self.answers[i].is_none());
+        if let Some(next) = next {
+            self.current_question = next;
+            self.selected_option = 0;
+            self.clear_text_input();
+        } else if self.generating {
+            // Wait for the next question to stream in
+            self.current_question = self.questions.len();
+            self.selected_option = 0;
+            self.clear_text_input();
+        } else {
+            self.finish();
         }
     }
 
+    /// Answered questions still waiting for Claude to grade them
+    pub fn ungraded(&self) -> Vec<usize> {
+        (0..self.questions.len())
+            .filter(|&i| self.answers[i].is_some() && self.grades[i].is_none())
+            .collect()
+    }
+
+    /// Count answers Claude couldn't grade as incorrect
+    pub fn fail_ungraded(&mut self) {
+        for index in self.ungraded() {
+            self.set_grade(index, false);
+        }
+    }
+
+    /// Record Claude's grade for a short answer question
+    pub fn set_grade(&mut self, index: usize, correct: bool) {
+        if let Some(grade) = self.grades.get_mut(index) {
+            *grade = Some(correct);
+        }
+    }
+
+    /// Graded answers not yet rated, as (question, correct), marking them rated
+    pub fn take_unrated(&mut self) -> Vec<(String, bool)> {
+        let mut unrated = Vec::new();
+        for (i, rated) in self.rated.iter_mut().enumerate() {
+            if let Some(correct) = self.grades[i]
+                && !*rated
+            {
+                *rated = true;
+                unrated.push((self.questions[i].question().to_string(), correct));
+            }
+        }
+        unrated
+    }
+
     /// Calculate score (number correct)
     pub fn score(&self) -> (usize, usize) {
-        let correct = self
-            .questions
-            .iter()
-            .enumerate()
-            .filter(|(i, q)| self.answers.get(*i).copied().flatten() == Some(q.correct_index))
-            .count();
+        let correct = self.grades.iter().filter(|g| **g == Some(true)).count();
         (correct, self.questions.len())
     }
 
@@ -563,23 +1492,229 @@ impl QuizState {
         total > 0 && correct == total
     }
 
-    /// Reset for retry
-    pub fn retry(&mut self) {
-        self.answers = vec![None; self.questions.len()];
-        self.current_question = 0;
-        self.selected_option = 0;
-        self.completed = false;
+    /// Reset for retry
+    pub fn retry(&mut self) {
+        self.answers = vec![None; self.questions.len()];
+        self.grades = vec![None; self.questions.len()];
+        self.rated = vec![false; self.questions.len()];
+        self.current_question = 0;
+        self.selected_option = 0;
+        self.clear_text_input();
+        self.completed = false;
+        self.grading = false;
+        self.started = Some(Instant::now());
+        self.clear_review();
+    }
+
+    /// Whether the finished quiz can be reviewed (results are in)
+    pub fn can_review(&self) -> bool {
+        self.completed && !self.grading && !self.loading && !self.questions.is_empty()
+    }
+
+    /// Start reviewing answers, beginning at the first missed question
+    pub fn start_review(&mut self) {
+        self.reviewing = true;
+        self.review_index = self.missed().first().copied().unwrap_or(0);
+    }
+
+    /// Return from the review to the results summary
+    pub fn end_review(&mut self) {
+        self.reviewing = false;
+    }
+
+    /// Show the previous question in review
+    pub fn review_prev(&mut self) {
+        self.review_index = self.review_index.saturating_sub(1);
+    }
+
+    /// Show the next question in review
+    pub fn review_next(&mut self) {
+        if self.review_index + 1 < self.questions.len() {
+            self.review_index += 1;
+        }
+    }
+
+    /// Record Claude's explanation for a question
+    pub fn set_explanation(&mut self, index: usize, explanation: String) {
+        if let Some(slot) = self.explanations.get_mut(index) {
+            *slot = Some(explanation);
+        }
+    }
+
+    /// Explanation for a question, if Claude has provided one
+    pub fn explanation(&self, index: usize) -> Option<&str> {
+        self.explanations.get(index).and_then(Option::as_deref)
+    }
+
+    /// Leave review and forget explanations (answers are about to change)
+    fn clear_review(&mut self) {
+        self.reviewing = false;
+        self.review_index = 0;
+        self.explanations.clear();
+        self.explaining = false;
+    }
+
+    /// Close the quiz
+    pub fn close(&mut self) {
+        self.active = false;
+        self.loading = false;
+        self.generating = false;
+        self.grading = false;
+        self.completed = false;
+        self.questions.clear();
+        self.answers.clear();
+        self.grades.clear();
+        self.rated.clear();
+        self.clear_text_input();
+        self.error = None;
+        self.section_path = None;
+        self.editor = None;
+        self.clear_review();
+    }
+}
+
+/// A question listed in the question bank editor
+#[derive(Debug, Clone, PartialEq)]
+pub struct BankedQuestion {
+    /// The question
+    pub question: QuizQuestion,
+    /// Whether the reader wrote or edited it, rather than Claude
+    pub custom: bool,
+}
+
+/// Editing a section's quiz questions
+#[derive(Debug, Clone, Default)]
+pub struct QuestionEditor {
+    /// Section the questions belong to
+    pub section_path: String,
+    /// The quiz's questions followed by the reader's others
+    pub questions: Vec<BankedQuestion>,
+    /// Selected question
+    pub selected: usize,
+    /// Question being written (None while choosing one)
+    pub form: Option<QuestionForm>,
+    /// Why the question couldn't be saved
+    pub error: Option<String>,
+}
+
+impl QuestionEditor {
+    /// Select the previous question
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Select the next question
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.questions.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// The selected question
+    pub fn selected(&self) -> Option<&BankedQuestion> {
+        self.questions.get(self.selected)
+    }
+}
+
+/// Multi-line text of a question being written, with a cursor
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuestionForm {
+    /// Index of the question being edited; None adds one
+    pub index: Option<usize>,
+    /// Lines of text
+    pub lines: Vec<String>,
+    /// Cursor line
+    pub line: usize,
+    /// Cursor character offset within the line
+    pub col: usize,
+}
+
+impl QuestionForm {
+    /// Start editing `text`, with the cursor at the end of the first line
+    pub fn new(index: Option<usize>, text: &str) -> Self {
+        let mut lines: Vec<String> = text.lines().map(String::from).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let col = lines[0].chars().count();
+        Self { index, lines, line: 0, col }
+    }
+
+    /// The text written
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Byte offset of the cursor in its line
+    fn byte_offset(&self) -> usize {
+        let line = &self.lines[self.line];
+        line.char_indices().nth(self.col).map_or(line.len(), |(i, _)| i)
+    }
+
+    /// Insert a character at the cursor
+    pub fn insert_char(&mut self, c: char) {
+        let at = self.byte_offset();
+        self.lines[self.line].insert(at, c);
+        self.col += 1;
+    }
+
+    /// Split the line at the cursor
+    pub fn new_line(&mut self) {
+        let at = self.byte_offset();
+        let rest = self.lines[self.line].split_off(at);
+        self.line += 1;
+        self.lines.insert(self.line, rest);
+        self.col = 0;
+    }
+
+    /// Delete the character before the cursor, joining lines at the start of one
+    pub fn delete_char(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let at = self.byte_offset();
+            self.lines[self.line].remove(at);
+        } else if self.line > 0 {
+            let rest = self.lines.remove(self.line);
+            self.line -= 1;
+            self.col = self.lines[self.line].chars().count();
+            self.lines[self.line].push_str(&rest);
+        }
+    }
+
+    /// Move the cursor left, to the end of the previous line at the start of one
+    pub fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.line > 0 {
+            self.line -= 1;
+            self.col = self.lines[self.line].chars().count();
+        }
+    }
+
+    /// Move the cursor right, to the start of the next line at the end of one
+    pub fn move_right(&mut self) {
+        if self.col < self.lines[self.line].chars().count() {
+            self.col += 1;
+        } else if self.line + 1 < self.lines.len() {
+            self.line += 1;
+            self.col = 0;
+        }
     }
 
-    /// Close the quiz
-    pub fn close(&mut self) {
-        self.active = false;
-        self.loading = false;
-        self.completed = false;
-        self.questions.clear();
-        self.answers.clear();
-        self.error = None;
-        self.section_path = None;
+    /// Move the cursor up a line
+    pub fn move_up(&mut self) {
+        if self.line > 0 {
+            self.line -= 1;
+            self.col = self.col.min(self.lines[self.line].chars().count());
+        }
+    }
+
+    /// Move the cursor down a line
+    pub fn move_down(&mut self) {
+        if self.line + 1 < self.lines.len() {
+            self.line += 1;
+            self.col = self.col.min(self.lines[self.line].chars().count());
+        }
     }
 }
 
@@ -612,6 +1747,10 @@ pub struct CommandLineState {
     pub history: Vec<String>,
     /// Current history index when navigating
     pub history_index: Option<usize>,
+    /// Persistent status shown at the right of the command line (e.g. API cost)
+    pub status: Option<String>,
+    /// Key hints for what the reader is doing, shown when there's no message
+    pub hints: Vec<(String, &'static str)>,
 }
 
 impl CommandLineState {
@@ -624,6 +1763,13 @@ impl CommandLineState {
         self.history_index = None;
     }
 
+    /// Start command mode with the start of a command typed
+    pub fn enter_command_with(&mut self, input: &str) {
+        self.enter_command_mode();
+        self.input = input.to_string();
+        self.cursor = input.chars().count();
+    }
+
     /// Start search mode
     pub fn enter_search_mode(&mut self) {
         self.mode = CommandMode::Search;
@@ -778,6 +1924,32 @@ impl CommandLineState {
     }
 }
 
+/// A book being parsed in the background
+#[derive(Debug, Clone)]
+pub struct BookLoading {
+    /// Title (or file name) of the book being loaded
+    pub title: String,
+    /// When loading started, for the spinner
+    pub start_time: Instant,
+}
+
+impl BookLoading {
+    /// Spinner frames, advanced every `MS_PER_FRAME`
+    const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    const MS_PER_FRAME: u128 = 80;
+
+    /// Start loading a book
+    pub fn new(title: impl Into<String>) -> Self {
+        Self { title: title.into(), start_time: Instant::now() }
+    }
+
+    /// Current spinner character
+    pub fn spinner(&self) -> char {
+        let frame = self.start_time.elapsed().as_millis() / Self::MS_PER_FRAME;
+        Self::FRAMES[frame as usize % Self::FRAMES.len()]
+    }
+}
+
 /// State for the landing animation
 #[derive(Debug, Clone)]
 pub struct LandingAnimation {
@@ -868,9 +2040,59 @@ pub struct NotesState {
     pub input: String,
     /// Cursor position in input
     pub cursor: usize,
+    /// IDs of notes expanded to show their full text
+    pub expanded: HashSet<String>,
+    /// Query searching notes in every book (empty when not searching)
+    pub search: String,
+    /// Whether the search query is being typed
+    pub search_editing: bool,
+    /// Whether the panel lists the book's highlights instead of the section's notes
+    pub highlights: bool,
+    /// Color the highlights list is limited to
+    pub highlight_filter: Option<HighlightColor>,
+    /// Color given to new highlights (the last one picked)
+    pub highlight_color: HighlightColor,
+    /// Whether the panel lists only annotations on the text in view,
+    /// following the scroll
+    pub follow: bool,
 }
 
 impl NotesState {
+    /// List the book's highlights, only those of `color` if given
+    pub fn show_highlights(&mut self, color: Option<HighlightColor>) {
+        self.clear_search();
+        self.highlights = true;
+        self.highlight_filter = color;
+    }
+
+    /// Stop listing highlights and show the section's notes again
+    pub fn close_highlights(&mut self) {
+        self.highlights = false;
+        self.highlight_filter = None;
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+    }
+
+    /// Whether the panel lists search results instead of the section's notes
+    pub fn is_searching(&self) -> bool {
+        self.search_editing || !self.search.trim().is_empty()
+    }
+
+    /// Stop searching and show the section's notes again
+    pub fn clear_search(&mut self) {
+        self.search.clear();
+        self.search_editing = false;
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+    }
+
+    /// Expand a note to show its full text, or collapse it again
+    pub fn toggle_expanded(&mut self, note_id: &str) {
+        if !self.expanded.remove(note_id) {
+            self.expanded.insert(note_id.to_string());
+        }
+    }
+
     /// Start creating a new note
     pub fn start_creating(&mut self) {
         self.creating = true;
@@ -938,7 +2160,7 @@ impl NotesState {
 
 /// State for visual mode (text selection)
 /// The anchor is where selection started; cursor position comes from ContentState
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct VisualModeState {
     /// Whether visual mode (selection) is active
     pub active: bool,
@@ -946,6 +2168,23 @@ pub struct VisualModeState {
     pub anchor_block: usize,
     /// Character offset within anchor block
     pub anchor_char: usize,
+    /// Last character of the anchor word, used when selecting backwards
+    pub anchor_end: usize,
+    /// Select character by character instead of snapping to word boundaries
+    pub precise: bool,
+    /// Waiting for the second key of a text object (`as`, `al`, `ab`)
+    pub pending_object: bool,
+}
+
+/// Text object that a visual selection can be expanded to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextObject {
+    /// The sentence around the cursor
+    Sentence,
+    /// The list item (line) around the cursor
+    ListItem,
+    /// The whole block
+    Block,
 }
 
 impl VisualModeState {
@@ -954,6 +2193,33 @@ impl VisualModeState {
         self.active = true;
         self.anchor_block = block_index;
         self.anchor_char = char_offset;
+        self.anchor_end = char_offset;
+        self.pending_object = false;
+    }
+
+    /// Enter visual mode, snapping the anchor to the word under the cursor
+    /// unless precise mode is on
+    pub fn enter_snapped(&mut self, block_index: usize, char_offset: usize, text: &str) {
+        self.enter(block_index, char_offset);
+        if !self.precise {
+            if let Some((start, end)) = word_bounds(text, char_offset) {
+                self.anchor_char = start;
+                self.anchor_end = end;
+            }
+        }
+    }
+
+    /// Select an exact range within one block, returning the new cursor position
+    pub fn select_range(&mut self, block_index: usize, start: usize, end: usize) -> usize {
+        self.enter(block_index, start);
+        self.anchor_end = end;
+        end
+    }
+
+    /// Whether the cursor is at or after the anchor (i.e. it is the selection end)
+    pub fn cursor_is_end(&self, cursor_block: usize, cursor_char: usize) -> bool {
+        cursor_block > self.anchor_block
+            || (cursor_block == self.anchor_block && cursor_char >= self.anchor_char)
     }
 
     /// Exit visual mode
@@ -969,12 +2235,11 @@ impl VisualModeState {
         cursor_char: usize,
     ) -> (usize, usize, usize, usize) {
         // Compare positions to determine order
-        if self.anchor_block < cursor_block
-            || (self.anchor_block == cursor_block && self.anchor_char <= cursor_char)
-        {
+        if self.cursor_is_end(cursor_block, cursor_char) {
             (self.anchor_block, self.anchor_char, cursor_block, cursor_char)
         } else {
-            (cursor_block, cursor_char, self.anchor_block, self.anchor_char)
+            // Selecting backwards keeps the whole anchor word selected
+            (cursor_block, cursor_char, self.anchor_block, self.anchor_end.max(self.anchor_char))
         }
     }
 
@@ -1013,6 +2278,122 @@ impl VisualModeState {
     }
 }
 
+/// Bounds (start, inclusive end) of the word containing `pos`, if any
+pub fn word_bounds(text: &str, pos: usize) -> Option<(usize, usize)> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.get(pos).is_none_or(|c| c.is_whitespace()) {
+        return None;
+    }
+    let mut start = pos;
+    while start > 0 && !chars[start - 1].is_whitespace() {
+        start -= 1;
+    }
+    let mut end = pos;
+    while end + 1 < chars.len() && !chars[end + 1].is_whitespace() {
+        end += 1;
+    }
+    Some((start, end))
+}
+
+/// Snap a selection edge at `pos` to a word boundary after moving in the
+/// given direction. The selection end snaps to the last character of a word,
+/// the selection start to the first.
+pub fn snap_to_word(text: &str, pos: usize, forward: bool, is_end: bool) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let len = chars.len();
+    if pos >= len {
+        return pos;
+    }
+    let ws = |i: usize| chars[i].is_whitespace();
+
+    match (is_end, forward) {
+        // Extend the end to the end of the next word
+        (true, true) => {
+            let mut p = pos;
+            while p < len && ws(p) {
+                p += 1;
+            }
+            if p == len {
+                return pos;
+            }
+            word_bounds(text, p).map_or(pos, |(_, end)| end)
+        }
+        // Shrink the end back to the end of the previous word
+        (true, false) => {
+            let mut p = word_bounds(text, pos).map_or(pos, |(start, _)| start);
+            while p > 0 && ws(p - 1) {
+                p -= 1;
+            }
+            p.saturating_sub(1)
+        }
+        // Extend the start back to the start of the previous word
+        (false, false) => {
+            let mut p = pos;
+            while p > 0 && ws(p) {
+                p -= 1;
+            }
+            word_bounds(text, p).map_or(pos, |(start, _)| start)
+        }
+        // Shrink the start forward to the start of the next word
+        (false, true) => {
+            let mut p = pos;
+            if p > 0 && !ws(p) && !ws(p - 1) {
+                p = word_bounds(text, p).map_or(p, |(_, end)| end + 1);
+            }
+            while p < len && ws(p) {
+                p += 1;
+            }
+            if p >= len { pos } else { p }
+        }
+    }
+}
+
+/// Bounds (start, inclusive end) of a text object around `pos`
+///
+/// Sentences end at `.`, `!` or `?` followed by whitespace and never span lines;
+/// list items are the lines of a list block.
+pub fn text_object_bounds(text: &str, pos: usize, object: TextObject) -> Option<(usize, usize)> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return None;
+    }
+    let pos = pos.min(chars.len() - 1);
+
+    let (mut start, mut end) = match object {
+        TextObject::Block => (0, chars.len() - 1),
+        TextObject::ListItem | TextObject::Sentence => {
+            let is_boundary = |i: usize| {
+                chars[i] == '\n'
+                    || (object == TextObject::Sentence
+                        && matches!(chars[i], '.' | '!' | '?')
+                        && chars.get(i + 1).is_none_or(|c| c.is_whitespace()))
+            };
+            let mut start = pos;
+            while start > 0 && !is_boundary(start - 1) {
+                start -= 1;
+            }
+            let mut end = pos;
+            while end + 1 < chars.len() && !is_boundary(end) {
+                end += 1;
+            }
+            // A newline is a separator, not part of the object
+            if chars[end] == '\n' && end > start {
+                end -= 1;
+            }
+            (start, end)
+        }
+    };
+
+    // Trim surrounding whitespace
+    while start < end && chars[start].is_whitespace() {
+        start += 1;
+    }
+    while end > start && chars[end].is_whitespace() {
+        end -= 1;
+    }
+    Some((start, end))
+}
+
 /// Setup wizard step for Claude configuration
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum SetupStep {
@@ -1027,6 +2408,72 @@ pub enum SetupStep {
     Complete,
 }
 
+/// Remaining text below which a finished stream's tail is shown at once
+const STREAM_TAIL_CHARS: usize = 200;
+
+/// Batches streamed text so the response panel updates at a steady cadence
+///
+/// Deltas are held back and released once per interval, up to the last word
+/// boundary so words never appear half-written. Once the stream has finished,
+/// the backlog drains in a few shrinking steps rather than all at once.
+#[derive(Debug, Clone, Default)]
+pub struct StreamThrottle {
+    /// Text received but not shown yet
+    pending: String,
+    /// When text was last released
+    last_release: Option<Instant>,
+    /// Whether the stream has ended (only the tail remains)
+    pub finished: bool,
+}
+
+impl StreamThrottle {
+    /// Queue a delta of streamed text
+    pub fn push(&mut self, text: &str) {
+        self.pending.push_str(text);
+    }
+
+    /// Whether all received text has been released
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Discard queued text and start over
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Text to show now, if `interval` has passed since the last release
+    pub fn release(&mut self, now: Instant, interval: std::time::Duration) -> Option<String> {
+        if self.pending.is_empty()
+            || self.last_release.is_some_and(|last| now.duration_since(last) < interval)
+        {
+            return None;
+        }
+
+        let end =
+            if interval.is_zero() || (self.finished && self.pending.len() <= STREAM_TAIL_CHARS) {
+                self.pending.len()
+            } else if self.finished {
+                // Drain about half of the tail per step, ending on a word boundary
+                let half = (self.pending.len() / 2..self.pending.len())
+                    .find(|&i| self.pending.is_char_boundary(i))
+                    .unwrap_or(self.pending.len());
+                self.pending[half..]
+                    .find(char::is_whitespace)
+                    .map_or(self.pending.len(), |i| half + i + 1)
+            } else {
+                // Hold back a partial word until the rest of it arrives
+                match self.pending.rfind(char::is_whitespace) {
+                    Some(i) => i + self.pending[i..].chars().next().map_or(1, char::len_utf8),
+                    None => return None,
+                }
+            };
+
+        self.last_release = Some(now);
+        Some(self.pending.drain(..end).collect())
+    }
+}
+
 /// State for Claude AI integration
 #[derive(Debug, Clone, Default)]
 pub struct ClaudeState {
@@ -1034,6 +2481,8 @@ pub struct ClaudeState {
     pub streaming: bool,
     /// Accumulated text from streaming response
     pub stream_buffer: String,
+    /// Streamed text waiting for the next panel update
+    pub stream_throttle: StreamThrottle,
     /// Completed response for display
     pub response: String,
     /// Whether to show the response panel
@@ -1064,6 +2513,28 @@ pub struct ClaudeState {
     pub pending_selection_block: Option<usize>,
     /// Pending note info: selection start char
     pub pending_selection_char: Option<usize>,
+    /// Input tokens reported at the start of the current response
+    pub input_tokens: u32,
+    /// Prompt cache tokens (written, read) reported at the start of the current response
+    pub cache_tokens: (u32, u32),
+    /// Chapter being summarized by the current response: (book ID, chapter path, title)
+    pub pending_summary: Option<(String, String, String)>,
+    /// Chapter whose glossary terms the current response lists: (book ID, chapter path, title)
+    pub pending_glossary: Option<(String, String, String)>,
+    /// Explanation the current response grades (see `:teach`)
+    pub pending_teach_back: Option<crate::claude::teachback::TeachBack>,
+    /// Chapter office hours in progress (see `:recap`)
+    pub recap: Option<RecapSession>,
+    /// Study buddy answers shown side by side (see `:compare`)
+    pub comparison: Option<Comparison>,
+    /// Section the response's citations quote from
+    pub cited_section: Option<String>,
+    /// Citation picked with Tab, if any
+    pub selected_citation: Option<usize>,
+    /// Panel line where each citation first appears (updated while drawing)
+    pub citation_rows: Vec<usize>,
+    /// Screen areas of visible citations: (x, y, width, citation)
+    pub citation_hits: Vec<(u16, u16, u16, usize)>,
 }
 
 impl ClaudeState {
@@ -1123,7 +2594,11 @@ impl ClaudeState {
     /// Clear streaming state
     pub fn clear_streaming(&mut self) {
         self.streaming = false;
+        self.comparison = None;
+        self.cited_section = None;
+        self.selected_citation = None;
         self.stream_buffer.clear();
+        self.stream_throttle.clear();
     }
 
     /// Finalize the response (called when streaming completes)
@@ -1205,6 +2680,9 @@ pub struct AppState {
     /// Currently loaded book (if any)
     pub book: Option<Book>,
 
+    /// Book being parsed in the background (if any)
+    pub book_loading: Option<BookLoading>,
+
     /// Currently selected chapter index
     pub current_chapter: usize,
 
@@ -1223,6 +2701,9 @@ pub struct AppState {
     /// Content rendering state
     pub content: ContentState,
 
+    /// Second section shown beside the content (if open)
+    pub split: Option<SplitPane>,
+
     /// Search state
     pub search: SearchState,
 
@@ -1240,6 +2721,32 @@ pub struct AppState {
 
     /// Quiz state
     pub quiz: QuizState,
+
+    /// Help screen state
+    pub help: HelpState,
+
+    /// Date and time display settings
+    pub datetime: DateTimeFormat,
+
+    /// Theme the open book overrides the configured one with (if any)
+    pub book_theme: Option<crate::theme::Theme>,
+
+    /// Next section in the study plans, shown on the landing screen
+    pub next_up: Option<String>,
+
+    /// Status bar segments, left to right (empty hides the bar)
+    pub status_bar: Vec<String>,
+
+    /// Report shown over the main screen (if any)
+    pub report: Option<Report>,
+}
+
+impl AppState {
+    /// Whether option letters, numbers and n/p answer the quiz, rather than
+    /// being typed into a command
+    pub fn quiz_takes_keys(&self) -> bool {
+        self.quiz.is_answering() && !self.command_line.is_input_mode()
+    }
 }
 
 #[cfg(test)]
@@ -1247,6 +2754,125 @@ pub struct AppState {
 mod tests {
     use super::*;
 
+    fn pos(chapter: usize, scroll_offset: usize) -> JumpPosition {
+        JumpPosition { chapter, section: 0, scroll_offset }
+    }
+
+    #[test]
+    fn jump_list_back_and_forward() {
+        let mut jumps = JumpList::default();
+        assert_eq!(jumps.back(pos(0, 0)), None);
+
+        jumps.push(pos(0, 5));
+        jumps.push(pos(1, 0));
+
+        // At (2, 0): back twice, then forward to where we started
+        assert_eq!(jumps.back(pos(2, 0)), Some(pos(1, 0)));
+        assert_eq!(jumps.back(pos(1, 0)), Some(pos(0, 5)));
+        assert_eq!(jumps.back(pos(0, 5)), None);
+        assert_eq!(jumps.forward(), Some(pos(1, 0)));
+        assert_eq!(jumps.forward(), Some(pos(2, 0)));
+        assert_eq!(jumps.forward(), None);
+    }
+
+    #[test]
+    fn jump_list_push_after_back_drops_newer_entries() {
+        let mut jumps = JumpList::default();
+        jumps.push(pos(0, 0));
+        jumps.push(pos(1, 0));
+        assert_eq!(jumps.back(pos(2, 0)), Some(pos(1, 0)));
+
+        jumps.push(pos(1, 0));
+        assert_eq!(jumps.forward(), None);
+        assert_eq!(jumps.back(pos(3, 0)), Some(pos(1, 0)));
+        assert_eq!(jumps.back(pos(1, 0)), Some(pos(0, 0)));
+    }
+
+    #[test]
+    fn autosave_debounces_progress_and_saves_session_on_move() {
+        let start = Instant::now();
+        let later = |secs| start + std::time::Duration::from_secs(secs);
+        let mut autosave = Autosave::default();
+
+        // Changes in a burst are written once, after the delay from the first
+        autosave.progress_changed(start);
+        autosave.progress_changed(later(1));
+        assert!(!autosave.progress_due(later(1)));
+        assert!(autosave.progress_due(later(2)));
+        autosave.progress_saved();
+        assert!(!autosave.progress_pending());
+
+        let minute = Some(std::time::Duration::from_secs(60));
+        autosave.session_saved(Some(("rust-book", "ch01/s01")), start);
+        assert!(!autosave.session_due(Some(("rust-book", "ch01/s01")), minute, later(30)));
+        assert!(autosave.session_due(Some(("rust-book", "ch01/s02")), minute, later(30)));
+        assert!(autosave.session_due(Some(("rust-book", "ch01/s01")), minute, later(60)));
+        assert!(!autosave.session_due(None, None, later(600)));
+    }
+
+    #[test]
+    fn source_watch_notices_edits_after_the_interval() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("intro.md");
+        std::fs::write(&file, "# Intro").unwrap();
+        let start = Instant::now();
+        let mut watch = SourceWatch::new("notes", dir.path(), start);
+
+        // Poll until the walk started (if any) is done
+        let settle = |watch: &mut SourceWatch, now: Instant| {
+            let mut changed = watch.changed(now);
+            while watch.walking() {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+                changed |= watch.changed(now);
+            }
+            changed
+        };
+
+        let edited = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        std::fs::File::options().write(true).open(&file).unwrap().set_modified(edited).unwrap();
+        assert!(!settle(&mut watch, start + std::time::Duration::from_secs(1)));
+        assert!(settle(&mut watch, start + SOURCE_CHECK_INTERVAL));
+        assert!(!settle(&mut watch, start + SOURCE_CHECK_INTERVAL * 2));
+    }
+
+    #[test]
+    fn split_pane_scroll_bind_keeps_its_distance() {
+        let mut split = SplitPane::new(None, 0, 1);
+        split.max_scroll = 100;
+        split.scroll_offset = 30;
+
+        assert!(split.toggle_scroll_bind(10));
+        assert_eq!(split.offset_for(10), 30);
+        assert_eq!(split.offset_for(50), 70);
+        assert_eq!(split.offset_for(500), 100);
+
+        split.scroll_bind = Some(-20);
+        assert_eq!(split.offset_for(5), 0);
+        assert!(!split.toggle_scroll_bind(45));
+        assert_eq!(split.offset_for(5), 25);
+    }
+
+    #[test]
+    fn question_form_edits_across_lines() {
+        let mut form = QuestionForm::new(None, "Pick one\n* yes");
+        assert_eq!((form.line, form.col), (0, 8));
+        form.new_line();
+        for c in "- nö".chars() {
+            form.insert_char(c);
+        }
+        assert_eq!(form.text(), "Pick one\n- nö\n* yes");
+
+        form.move_up();
+        form.move_down();
+        assert_eq!((form.line, form.col), (1, 4));
+        form.move_right();
+        assert_eq!((form.line, form.col), (2, 0));
+        form.delete_char();
+        assert_eq!(form.text(), "Pick one\n- nö* yes");
+        form.delete_char();
+        assert_eq!(form.text(), "Pick one\n- n* yes");
+    }
+
     #[test]
     fn command_line_enter_command_mode() {
         let mut state = CommandLineState::default();
@@ -1601,6 +3227,54 @@ mod tests {
         assert_eq!(state.max_scroll(), 90);
     }
 
+    #[test]
+    fn content_state_footnote_jump_and_return() {
+        let mut state = ContentState {
+            total_lines: 200,
+            visible_height: 20,
+            scroll_offset: 10,
+            block_line_offsets: vec![0, 10, 150],
+            ..Default::default()
+        };
+
+        state.jump_to_footnote("ch01/s01", 2);
+        assert!(state.scroll_offset > 100);
+
+        // Returning from another section does nothing
+        assert!(!state.return_from_footnote("ch01/s02"));
+
+        state.scroll_offset = 10;
+        state.jump_to_footnote("ch01/s01", 2);
+        assert!(state.return_from_footnote("ch01/s01"));
+        assert_eq!(state.scroll_offset, 10);
+        assert!(!state.return_from_footnote("ch01/s01"));
+    }
+
+    #[test]
+    fn visible_blocks_overlap_the_screen() {
+        let mut state = ContentState {
+            visible_height: 20,
+            scroll_offset: 5,
+            block_line_offsets: vec![0, 10, 25, 40],
+            ..Default::default()
+        };
+        assert_eq!(state.visible_blocks(), 0..2);
+        state.scroll_offset = 30;
+        assert_eq!(state.visible_blocks(), 2..4);
+        state.block_line_offsets.clear();
+        assert!(state.visible_blocks().is_empty());
+    }
+
+    #[test]
+    fn near_end_once_the_last_fifth_is_in_view() {
+        let mut state = ContentState { total_lines: 100, visible_height: 20, ..Default::default() };
+        assert!(!state.near_end());
+        state.scroll_offset = 60;
+        assert!(state.near_end());
+        state.total_lines = 0;
+        assert!(!state.near_end());
+    }
+
     #[test]
     fn content_state_clamp_scroll() {
         let mut state = ContentState::default();
@@ -1621,6 +3295,436 @@ mod tests {
         assert_eq!(state.scroll_offset, 0);
     }
 
+    #[test]
+    fn content_state_record_seen_lines() {
+        let mut state = ContentState::default();
+        state.total_lines = 100;
+        state.visible_height = 20;
+        state.record_seen_lines("ch01/s01");
+        assert_eq!(state.seen_percent(), 20);
+
+        state.scroll_offset = 10;
+        state.record_seen_lines("ch01/s01");
+        assert_eq!(state.seen_percent(), 30);
+
+        // Switching sections starts tracking over
+        state.auto_marked = true;
+        state.record_seen_lines("ch01/s02");
+        assert_eq!(state.seen_percent(), 20);
+        assert!(!state.auto_marked);
+    }
+
+    #[test]
+    fn content_state_rescale_lines_keeps_seen_share() {
+        let mut state = ContentState::default();
+        state.total_lines = 100;
+        state.visible_height = 20;
+        state.record_seen_lines("ch01/s01");
+        state.footnote_return = Some(("ch01/s01".into(), 50));
+
+        // Narrower panel: the same text takes twice the lines
+        state.total_lines = 200;
+        state.rescale_lines("ch01/s01", 100);
+
+        assert_eq!(state.seen_lines.len(), 200);
+        assert_eq!(state.seen_percent(), 20);
+        assert!(state.seen_lines[39] && !state.seen_lines[40]);
+        assert_eq!(state.footnote_return, Some(("ch01/s01".into(), 100)));
+
+        // Another section's return point wasn't reflowed
+        state.footnote_return = Some(("ch01/s02".into(), 50));
+        state.total_lines = 400;
+        state.rescale_lines("ch01/s01", 200);
+        assert_eq!(state.footnote_return, Some(("ch01/s02".into(), 50)));
+    }
+
+    #[test]
+    fn content_state_read_through_requires_end_and_threshold() {
+        let mut state = ContentState::default();
+        state.total_lines = 100;
+        state.visible_height = 20;
+
+        // Jumping straight to the bottom doesn't count as reading
+        state.scroll_offset = state.max_scroll();
+        state.record_seen_lines("ch01/s01");
+        assert!(!state.is_read_through(80));
+
+        // Scrolling through the whole section does
+        for offset in (0..=90).step_by(10) {
+            state.scroll_offset = offset;
+            state.record_seen_lines("ch01/s01");
+        }
+        assert!(state.is_read_through(80));
+
+        // Not at the end yet
+        state.scroll_offset = 50;
+        assert!(!state.is_read_through(80));
+    }
+
+    #[test]
+    fn content_state_read_through_empty_section() {
+        let state = ContentState::default();
+        assert!(!state.is_read_through(0));
+    }
+
+    // StreamThrottle tests
+
+    #[test]
+    fn stream_throttle_releases_whole_words_per_interval() {
+        let interval = std::time::Duration::from_millis(100);
+        let start = Instant::now();
+        let mut throttle = StreamThrottle::default();
+
+        throttle.push("Hello wor");
+        assert_eq!(throttle.release(start, interval).as_deref(), Some("Hello "));
+
+        throttle.push("ld and more ");
+        assert_eq!(throttle.release(start + interval / 2, interval), None);
+        assert_eq!(
+            throttle.release(start + interval, interval).as_deref(),
+            Some("world and more ")
+        );
+        assert!(throttle.is_empty());
+    }
+
+    #[test]
+    fn stream_throttle_drains_tail_after_finish() {
+        let interval = std::time::Duration::from_millis(100);
+        let mut now = Instant::now();
+        let mut throttle = StreamThrottle::default();
+        throttle.push(&"word ".repeat(200));
+        throttle.push("end");
+        throttle.release(now, interval);
+        assert_eq!(throttle.pending, "end");
+
+        throttle.push(&" tail".repeat(100));
+        throttle.finished = true;
+        let mut steps = 0;
+        while !throttle.is_empty() {
+            now += interval;
+            assert!(throttle.release(now, interval).is_some());
+            steps += 1;
+        }
+        assert!(steps > 1 && steps < 5, "{} steps", steps);
+    }
+
+    #[test]
+    fn stream_throttle_zero_interval_releases_everything() {
+        let mut throttle = StreamThrottle::default();
+        throttle.push("partial wo");
+        let released = throttle.release(Instant::now(), std::time::Duration::ZERO);
+        assert_eq!(released.as_deref(), Some("partial wo"));
+    }
+
+    // QuizState tests
+
+    #[test]
+    fn commands_typed_during_a_quiz_keep_their_letters() {
+        let mut state = AppState { quiz: sample_quiz(), ..Default::default() };
+        assert!(state.quiz_takes_keys());
+
+        state.command_line.enter_command_mode();
+        for c in "note".chars() {
+            assert!(!state.quiz_takes_keys());
+            state.command_line.insert_char(c);
+        }
+        assert_eq!(state.command_line.display_text(), ":note");
+        state.command_line.exit_input_mode();
+        assert!(state.quiz_takes_keys());
+    }
+
+    fn sample_quiz() -> QuizState {
+        let mut quiz = QuizState::default();
+        quiz.start_loading("ch01/s01");
+        quiz.set_questions(vec![
+            QuizQuestion::MultipleChoice {
+                question: "Pick B".into(),
+                options: vec!["A".into(), "B".into(), "C".into(), "D".into()],
+                correct_index: 1,
+            },
+            QuizQuestion::TrueFalse { question: "Rust is memory safe".into(), answer: true },
+            QuizQuestion::FillInBlank {
+                question: "The ____ checker enforces borrowing rules".into(),
+                answer: "borrow".into(),
+            },
+            QuizQuestion::ShortAnswer {
+                question: "Why use Rc?".into(),
+                reference_answer: "Shared ownership".into(),
+            },
+        ]);
+        quiz
+    }
+
+    #[test]
+    fn quiz_true_false_has_two_options() {
+        let mut quiz = sample_quiz();
+        quiz.current_question = 1;
+        quiz.select_next();
+        quiz.select_next();
+        assert_eq!(quiz.selected_option, 1);
+    }
+
+    #[test]
+    fn quiz_grades_each_question_type() {
+        let mut quiz = sample_quiz();
+
+        quiz.selected_option = 1;
+        quiz.confirm_answer();
+        quiz.selected_option = 0;
+        quiz.confirm_answer();
+        assert!(quiz.is_text_question());
+        for c in " Borrow. ".chars() {
+            quiz.insert_char(c);
+        }
+        quiz.confirm_answer();
+        for c in "It allows multiple owners".chars() {
+            quiz.insert_char(c);
+        }
+        quiz.confirm_answer();
+
+        assert!(quiz.completed);
+        assert_eq!(quiz.grades[..3], [Some(true), Some(true), Some(true)]);
+        assert_eq!(quiz.ungraded(), vec![3]);
+        assert!(!quiz.passed());
+
+        quiz.set_grade(3, true);
+        assert!(quiz.ungraded().is_empty());
+        assert_eq!(quiz.score(), (4, 4));
+        assert!(quiz.passed());
+    }
+
+    #[test]
+    fn quiz_counts_answers_left_ungraded_as_incorrect() {
+        let mut quiz = sample_quiz();
+        quiz.selected_option = 1;
+        quiz.confirm_answer();
+        quiz.selected_option = 0;
+        quiz.confirm_answer();
+        quiz.insert_char('x');
+        quiz.confirm_answer();
+        quiz.insert_char('y');
+        quiz.confirm_answer();
+        assert_eq!(quiz.ungraded(), vec![3]);
+
+        quiz.fail_ungraded();
+
+        assert!(quiz.ungraded().is_empty());
+        assert_eq!(quiz.score(), (2, 4));
+        let rated: Vec<bool> = quiz.take_unrated().into_iter().map(|(_, c)| c).collect();
+        assert_eq!(rated, [true, true, false, false]);
+        assert_eq!(quiz.attempt_record(0).results, [true, true, false, false]);
+    }
+
+    #[test]
+    fn quiz_moves_between_answered_questions() {
+        let mut quiz = sample_quiz();
+        assert!(quiz.select_option(2));
+        assert!(!quiz.select_option(4));
+        quiz.confirm_answer();
+        for c in "yes".chars() {
+            quiz.insert_char(c);
+        }
+        quiz.current_question = 2;
+        quiz.confirm_answer();
+
+        // Can't move on past an unanswered question
+        quiz.current_question = 1;
+        quiz.next_question();
+        assert_eq!(quiz.current_question, 1);
+
+        quiz.prev_question();
+        assert_eq!((quiz.current_question, quiz.selected_option), (0, 2));
+        quiz.select_option(1);
+        quiz.confirm_answer();
+        assert_eq!(quiz.grades[0], Some(true));
+        assert_eq!(quiz.current_question, 1);
+
+        quiz.select_option(0);
+        quiz.confirm_answer();
+        quiz.prev_question();
+        assert_eq!((quiz.current_question, quiz.text_input.as_str()), (2, "yes"));
+        assert!(!quiz.completed);
+    }
+
+    #[test]
+    fn quiz_empty_text_answer_is_not_confirmed() {
+        let mut quiz = sample_quiz();
+        quiz.current_question = 2;
+        quiz.confirm_answer();
+        assert_eq!(quiz.current_question, 2);
+        assert!(quiz.answers[2].is_none());
+    }
+
+    #[test]
+    fn quiz_text_input_editing() {
+        let mut quiz = sample_quiz();
+        quiz.insert_char('a');
+        quiz.insert_char('c');
+        quiz.move_left();
+        quiz.insert_char('b');
+        assert_eq!(quiz.text_input, "abc");
+        quiz.move_right();
+        quiz.delete_char();
+        assert_eq!(quiz.text_input, "ab");
+        assert_eq!(quiz.text_cursor, 2);
+    }
+
+    #[test]
+    fn quiz_replace_missed_keeps_correct_answers() {
+        let mut quiz = sample_quiz();
+        quiz.selected_option = 1;
+        quiz.confirm_answer(); // correct
+        quiz.selected_option = 1;
+        quiz.confirm_answer(); // wrong ("False")
+        quiz.insert_char('x');
+        quiz.confirm_answer(); // wrong
+        quiz.insert_char('y');
+        quiz.confirm_answer(); // ungraded short answer
+        quiz.set_grade(3, true);
+        assert_eq!(quiz.missed(), vec![1, 2]);
+        let record = quiz.attempt_record(100);
+        assert_eq!((record.attempt, record.percent()), (1, 50));
+        assert_eq!(record.results, [true, false, false, true]);
+
+        quiz.start_regenerating();
+        assert!(quiz.is_regenerating());
+        assert_eq!(quiz.attempt, 2);
+
+        quiz.replace_missed(vec![
+            QuizQuestion::TrueFalse { question: "New 1".into(), answer: false },
+            QuizQuestion::TrueFalse { question: "New 2".into(), answer: true },
+        ]);
+        assert!(!quiz.loading);
+        assert_eq!(quiz.current_question, 1);
+        assert_eq!(quiz.questions[1].question(), "New 1");
+        assert_eq!(quiz.grades[0], Some(true));
+
+        quiz.selected_option = 1;
+        quiz.confirm_answer();
+        assert_eq!(quiz.current_question, 2);
+        quiz.selected_option = 0;
+        quiz.confirm_answer();
+        assert!(quiz.completed);
+        assert!(quiz.passed());
+    }
+
+    #[test]
+    fn quiz_review_starts_at_first_miss() {
+        let mut quiz = sample_quiz();
+        quiz.selected_option = 1;
+        quiz.confirm_answer(); // correct
+        quiz.selected_option = 1;
+        quiz.confirm_answer(); // wrong ("False")
+        quiz.insert_char('x');
+        quiz.confirm_answer(); // wrong
+        quiz.insert_char('y');
+        quiz.confirm_answer();
+        quiz.set_grade(3, true);
+        assert!(quiz.can_review());
+
+        quiz.start_review();
+        assert_eq!(quiz.review_index, 1);
+        quiz.review_next();
+        quiz.review_next();
+        quiz.review_next();
+        assert_eq!(quiz.review_index, 3);
+
+        quiz.explanations = vec![None; 4];
+        quiz.set_explanation(3, "Rc counts references".into());
+        assert_eq!(quiz.explanation(3), Some("Rc counts references"));
+        assert_eq!(quiz.explanation(0), None);
+
+        quiz.start_regenerating();
+        assert!(!quiz.reviewing);
+        assert!(quiz.explanations.is_empty());
+    }
+
+    #[test]
+    fn quiz_streams_questions_while_answering() {
+        let mut quiz = QuizState::default();
+        quiz.start_loading("ch01/s01");
+        quiz.start_generating(2);
+        assert_eq!(quiz.total(), 2);
+
+        quiz.push_question(QuizQuestion::TrueFalse { question: "Q1".into(), answer: true });
+        assert!(!quiz.loading);
+        assert_eq!(quiz.current().unwrap().question(), "Q1");
+
+        // Answering before the next question arrives waits for it
+        quiz.confirm_answer();
+        assert!(quiz.awaiting_question());
+        assert!(!quiz.completed);
+
+        quiz.selected_option = 1;
+        quiz.push_question(QuizQuestion::TrueFalse { question: "Q2".into(), answer: false });
+        assert_eq!(quiz.current_question, 1);
+        assert_eq!(quiz.selected_option, 0);
+
+        quiz.selected_option = 1;
+        quiz.confirm_answer();
+        assert!(!quiz.completed);
+        quiz.finish_generating();
+        assert!(quiz.completed);
+        assert!(quiz.passed());
+
+        // The clock stops at the last answer
+        let took = quiz.elapsed().unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(quiz.elapsed(), Some(took));
+    }
+
+    #[test]
+    fn quiz_retry_clears_grades() {
+        let mut quiz = sample_quiz();
+        quiz.confirm_answer();
+        quiz.retry();
+        assert!(quiz.grades.iter().all(Option::is_none));
+        assert!(quiz.answers.iter().all(Option::is_none));
+    }
+
+    // Smart selection tests
+
+    #[test]
+    fn visual_mode_enter_snapped_selects_anchor_word() {
+        let mut state = VisualModeState::default();
+        state.enter_snapped(0, 8, "Borrow checker rules");
+        assert_eq!((state.anchor_char, state.anchor_end), (7, 13));
+        // Selecting backwards keeps the whole anchor word
+        assert_eq!(state.selection_range(0, 0), (0, 0, 0, 13));
+
+        state.precise = true;
+        state.enter_snapped(0, 8, "Borrow checker rules");
+        assert_eq!((state.anchor_char, state.anchor_end), (8, 8));
+    }
+
+    #[test]
+    fn snap_to_word_moves_by_words() {
+        let text = "hello big world";
+        // End moving right snaps to end of next word
+        assert_eq!(snap_to_word(text, 5, true, true), 8);
+        assert_eq!(snap_to_word(text, 10, true, true), 14);
+        // End moving left drops back to end of previous word
+        assert_eq!(snap_to_word(text, 13, false, true), 8);
+        assert_eq!(snap_to_word(text, 7, false, true), 4);
+        // Start moving left extends to start of previous word
+        assert_eq!(snap_to_word(text, 9, false, false), 6);
+        // Start moving right shrinks to start of next word
+        assert_eq!(snap_to_word(text, 1, true, false), 6);
+    }
+
+    #[test]
+    fn text_object_sentence_and_list_item() {
+        let text = "First one. Second one! Third";
+        assert_eq!(text_object_bounds(text, 14, TextObject::Sentence), Some((11, 21)));
+        assert_eq!(text_object_bounds(text, 24, TextObject::Sentence), Some((23, 27)));
+        assert_eq!(text_object_bounds(text, 3, TextObject::Sentence), Some((0, 9)));
+
+        let list = "alpha\nbeta item\ngamma";
+        assert_eq!(text_object_bounds(list, 8, TextObject::ListItem), Some((6, 14)));
+        assert_eq!(text_object_bounds(list, 8, TextObject::Block), Some((0, 20)));
+        assert_eq!(text_object_bounds("", 0, TextObject::Block), None);
+    }
+
     // NotesState tests
 
     #[test]
@@ -0,0 +1,378 @@
+//! AsciiDoc parser
+//!
+//! Converts the commonly used subset of AsciiDoc (section titles, lists,
+//! listing/literal/quote blocks, tables, admonitions and links) to markdown,
+//! which is then split into chapters and sections by heading level.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::markdown::book_from_markdown_document;
+use super::model::{Book, BookMetadata, BookSource};
+
+/// Constrained bold (`*text*`), which is emphasis-free in markdown
+static BOLD_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(^|[^*\w])\*([^*\s](?:[^*]*[^*\s])?)\*([^*\w]|$)").unwrap());
+
+/// Links with text: `https://example.com[text]` or `link:path[text]`
+static LINK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:link:([^\s\[\]]+)|(https?://[^\s\[\]]+))\[([^\]]*)\]").unwrap());
+
+/// Admonition labels that start a paragraph (`NOTE: text`)
+const ADMONITIONS: &[(&str, &str)] = &[
+    ("NOTE: ", "Note"),
+    ("TIP: ", "Tip"),
+    ("IMPORTANT: ", "Important"),
+    ("WARNING: ", "Warning"),
+    ("CAUTION: ", "Caution"),
+];
+
+/// Parse an AsciiDoc file into a Book
+pub fn parse_asciidoc_file(path: &Path) -> Result<Book> {
+    let path = path.canonicalize().with_context(|| format!("Invalid path: {}", path.display()))?;
+    let source = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read AsciiDoc file: {}", path.display()))?;
+
+    let doc = asciidoc_to_markdown(&source);
+
+    let book_id = path.file_stem().map_or_else(
+        || "unknown".to_string(),
+        |s| s.to_string_lossy().to_string().to_lowercase().replace(' ', "-"),
+    );
+    let title = doc.title.unwrap_or_else(|| {
+        path.file_stem().map_or_else(|| "Unknown".into(), |s| s.to_string_lossy().to_string())
+    });
+
+    let metadata = BookMetadata {
+        id: book_id,
+        title,
+        author: doc.author,
+        source: BookSource::AsciiDoc(path.clone()),
+        language: doc.language,
+        description: doc.description,
+        cover_image: None,
+        added_at: crate::config::datetime::now(),
+        last_accessed: None,
+    };
+
+    Ok(book_from_markdown_document(metadata, &doc.markdown))
+}
+
+/// An AsciiDoc document converted to markdown
+#[derive(Debug, Default)]
+struct Converted {
+    /// Document title (`= Title`)
+    title: Option<String>,
+    /// Author line after the title, or the `:author:` attribute
+    author: Option<String>,
+    /// `:lang:` attribute
+    language: Option<String>,
+    /// `:description:` attribute
+    description: Option<String>,
+    /// Document body as markdown
+    markdown: String,
+}
+
+/// Delimited block being converted
+#[derive(Debug, PartialEq, Eq)]
+enum Block {
+    /// Listing or literal block, emitted as a fenced code block
+    Code(&'static str),
+    /// Quote block, emitted with `> ` prefixes
+    Quote,
+    /// Comment block, dropped
+    Comment,
+    /// Table, collected as cells
+    Table,
+}
+
+/// Convert AsciiDoc source to markdown
+fn asciidoc_to_markdown(source: &str) -> Converted {
+    let mut doc = Converted::default();
+    let mut out = String::new();
+    let mut block: Option<Block> = None;
+    let mut source_lang = String::new();
+    let mut table: Vec<String> = Vec::new();
+    let mut table_columns = 0;
+    let mut in_header = true;
+
+    for line in source.lines() {
+        // Inside a delimited block, only its closing delimiter is special
+        match &block {
+            Some(Block::Code(delimiter)) => {
+                if line == *delimiter {
+                    out.push_str("```\n\n");
+                    block = None;
+                } else {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+                continue;
+            }
+            Some(Block::Comment) => {
+                if line == "////" {
+                    block = None;
+                }
+                continue;
+            }
+            Some(Block::Table) => {
+                if line == "|===" {
+                    out.push_str(&table_to_markdown(&table, table_columns));
+                    out.push('\n');
+                    table.clear();
+                    block = None;
+                } else if line.trim().is_empty() {
+                    // A blank line after the first row ends the header row
+                    if table_columns == 0 {
+                        table_columns = table.len();
+                    }
+                } else {
+                    let cells = line.trim().strip_prefix('|').unwrap_or(line).split('|');
+                    table.extend(cells.map(|c| inline_to_markdown(c.trim())));
+                }
+                continue;
+            }
+            Some(Block::Quote) if line == "____" => {
+                out.push('\n');
+                block = None;
+                continue;
+            }
+            _ => {}
+        }
+
+        // Document header: title, author line and attributes before the first blank line
+        if let Some(title) = line.strip_prefix("= ") {
+            if doc.title.is_none() {
+                doc.title = Some(title.trim().to_string());
+                continue;
+            }
+        }
+        if in_header {
+            if line.trim().is_empty() {
+                in_header = false;
+                continue;
+            }
+            if doc.title.is_some() && doc.author.is_none() && !line.starts_with(':') {
+                doc.author = Some(line.split('<').next().unwrap_or(line).trim().to_string());
+                continue;
+            }
+        }
+
+        if let Some((name, value)) = attribute_entry(line) {
+            match name {
+                "author" => doc.author = Some(value.to_string()),
+                "lang" => doc.language = Some(value.to_string()),
+                "description" => doc.description = Some(value.to_string()),
+                _ => {}
+            }
+            continue;
+        }
+
+        let prefix = if block == Some(Block::Quote) { "> " } else { "" };
+
+        match line {
+            "----" | "...." => {
+                out.push_str(&format!("```{}\n", source_lang));
+                source_lang.clear();
+                block = Some(Block::Code(if line == "----" { "----" } else { "...." }));
+                continue;
+            }
+            "____" => {
+                block = Some(Block::Quote);
+                continue;
+            }
+            "////" => {
+                block = Some(Block::Comment);
+                continue;
+            }
+            "|===" => {
+                table_columns = 0;
+                block = Some(Block::Table);
+                continue;
+            }
+            // Example, sidebar and open blocks: keep the content, drop the delimiters
+            "====" | "****" | "--" => continue,
+            _ => {}
+        }
+
+        if line.starts_with("//") {
+            continue;
+        }
+
+        // Block attributes: remember a listing's language, drop the rest
+        if line.starts_with('[') && line.ends_with(']') {
+            let attrs: Vec<&str> = line[1..line.len() - 1].split(',').map(str::trim).collect();
+            if attrs.first() == Some(&"source") {
+                source_lang = attrs.get(1).copied().unwrap_or("").to_string();
+            }
+            continue;
+        }
+
+        // Section titles (== Title) map to markdown headings one level up
+        let level = line.chars().take_while(|&c| c == '=').count();
+        if level >= 2 && line[level..].starts_with(' ') {
+            let hashes = "#".repeat((level - 1).min(6));
+            out.push_str(&format!("{} {}\n", hashes, line[level..].trim()));
+            continue;
+        }
+
+        // Block titles (.Title)
+        if let Some(title) = line.strip_prefix('.') {
+            if title.chars().next().is_some_and(|c| c.is_alphanumeric()) {
+                out.push_str(&format!("{}**{}**\n\n", prefix, inline_to_markdown(title)));
+                continue;
+            }
+        }
+
+        // Lists: `*`/`**` bullets and `.`/`..` numbered items
+        if let Some((marker, depth, text)) = list_item(line) {
+            let indent = "  ".repeat(depth.saturating_sub(1));
+            let marker = if marker == '.' { "1." } else { "-" };
+            out.push_str(&format!("{}{}{} {}\n", prefix, indent, marker, inline_to_markdown(text)));
+            continue;
+        }
+
+        if let Some((label, rest)) =
+            ADMONITIONS.iter().find_map(|(tag, label)| line.strip_prefix(tag).map(|r| (label, r)))
+        {
+            out.push_str(&format!("> **{}:** {}\n", label, inline_to_markdown(rest)));
+            continue;
+        }
+
+        if line.trim() == "+" {
+            continue;
+        }
+
+        out.push_str(prefix);
+        out.push_str(&inline_to_markdown(line));
+        out.push('\n');
+    }
+
+    // Close anything left open at the end of the file
+    match block {
+        Some(Block::Code(_)) => out.push_str("```\n"),
+        Some(Block::Table) => out.push_str(&table_to_markdown(&table, table_columns)),
+        _ => {}
+    }
+
+    doc.markdown = out;
+    doc
+}
+
+/// Name and value of an attribute entry line (`:name: value`)
+fn attribute_entry(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix(':')?;
+    let (name, value) = rest.split_once(':')?;
+    if name.is_empty() || name.contains(' ') {
+        return None;
+    }
+    Some((name, value.trim()))
+}
+
+/// Marker character, nesting depth and text of a list item line
+fn list_item(line: &str) -> Option<(char, usize, &str)> {
+    let line = line.trim_start();
+    let marker = line.chars().next().filter(|c| matches!(c, '*' | '.' | '-'))?;
+    let depth = line.chars().take_while(|&c| c == marker).count();
+    let text = line[depth..].strip_prefix(' ')?;
+    // `-` only nests in markdown style, and `--` is an open block delimiter
+    if marker == '-' && depth > 1 {
+        return None;
+    }
+    Some((marker, depth, text.trim()))
+}
+
+/// Convert inline markup: constrained bold and links
+fn inline_to_markdown(text: &str) -> String {
+    let text = BOLD_RE.replace_all(text, "$1**$2**$3");
+    LINK_RE
+        .replace_all(&text, |caps: &regex::Captures| {
+            let target = caps.get(1).or_else(|| caps.get(2)).map_or("", |m| m.as_str());
+            match &caps[3] {
+                "" => format!("<{}>", target),
+                label => format!("[{}]({})", label, target),
+            }
+        })
+        .into_owned()
+}
+
+/// Lay out table cells as a markdown table with the first row as the header
+///
+/// `columns` is the header row's cell count when a blank line separated it;
+/// otherwise every row is assumed to have as many cells as the first line.
+fn table_to_markdown(cells: &[String], columns: usize) -> String {
+    let columns = if columns > 0 { columns } else { cells.len() };
+    if columns == 0 {
+        return String::new();
+    }
+
+    let mut out = String::new();
+    for (i, row) in cells.chunks(columns).enumerate() {
+        let row: Vec<String> = row.iter().map(|c| c.replace('|', "\\|")).collect();
+        out.push_str(&format!("| {} |\n", row.join(" | ")));
+        if i == 0 {
+            out.push_str(&format!("|{}\n", " --- |".repeat(columns)));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_header_and_sections() {
+        let doc = asciidoc_to_markdown(
+            "= Onboarding Guide\nJane Doe <jane@example.com>\n:lang: de\n\n\
+             == Getting started\n\nRead *this* first.\n\n=== Tools\n\n* git\n** hooks\n. Clone\n",
+        );
+
+        assert_eq!(doc.title.as_deref(), Some("Onboarding Guide"));
+        assert_eq!(doc.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(doc.language.as_deref(), Some("de"));
+        assert_eq!(
+            doc.markdown,
+            "# Getting started\n\nRead **this** first.\n\n## Tools\n\n\
+             - git\n  - hooks\n1. Clone\n"
+        );
+    }
+
+    #[test]
+    fn converts_blocks() {
+        let doc = asciidoc_to_markdown(
+            "= T\n\n[source,rust]\n----\n== not a title\n----\n\n\
+             NOTE: Mind the gap.\n\n////\nhidden\n////\n\n\
+             ____\nQuoted\n____\n\nSee https://example.com[the site].\n",
+        );
+
+        assert_eq!(
+            doc.markdown,
+            "```rust\n== not a title\n```\n\n\n> **Note:** Mind the gap.\n\n\n\
+             > Quoted\n\n\nSee [the site](https://example.com).\n"
+        );
+    }
+
+    #[test]
+    fn converts_tables() {
+        let doc = asciidoc_to_markdown("= T\n\n|===\n|Name |Role\n\n|Ann\n|Lead\n|===\n");
+        assert!(doc.markdown.contains("| Name | Role |\n| --- | --- |\n| Ann | Lead |\n"));
+    }
+
+    #[test]
+    fn parses_asciidoc_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("guide.adoc");
+        fs::write(&path, "= Guide\n\n== One\n\nText.\n\n== Two\n\nMore.\n").unwrap();
+
+        let book = parse_asciidoc_file(&path).unwrap();
+
+        assert_eq!(book.metadata.title, "Guide");
+        let chapters: Vec<_> = book.chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(chapters, ["One", "Two"]);
+    }
+}
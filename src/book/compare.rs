@@ -0,0 +1,201 @@
+//! Comparing two editions of a book
+//!
+//! Sections are aligned by title first, ignoring numbering and case, since
+//! editions mostly keep their headings even as chapters move. Sections left
+//! over are matched by how many words they share. A matched section whose
+//! text differs is reported as changed.
+
+use std::collections::HashSet;
+
+use super::model::{Book, Section};
+
+/// Share of words two sections must have in common to match without a title
+const MIN_CONTENT_SIMILARITY: f64 = 0.6;
+
+/// How a section of one edition corresponds to the other
+#[derive(Debug, Clone, PartialEq)]
+pub enum SectionChange {
+    /// In both editions; `similarity` is the share of words in common (0-1)
+    Matched { old_path: String, new_path: String, similarity: f64 },
+    /// Only in the new edition
+    Added { new_path: String },
+    /// Only in the old edition
+    Removed { old_path: String },
+}
+
+impl SectionChange {
+    /// Whether the section is in both editions with different text
+    pub fn is_changed(&self) -> bool {
+        matches!(self, Self::Matched { similarity, .. } if *similarity < 1.0)
+    }
+}
+
+/// A section's title and words, for matching
+struct Entry<'a> {
+    section: &'a Section,
+    title: String,
+    words: HashSet<String>,
+}
+
+impl<'a> Entry<'a> {
+    fn new(section: &'a Section) -> Self {
+        let words = section
+            .plain_text()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        Self { section, title: normalize_title(&section.title), words }
+    }
+
+    fn similarity(&self, other: &Entry) -> f64 {
+        let union = self.words.union(&other.words).count();
+        if union == 0 {
+            return 1.0;
+        }
+        self.words.intersection(&other.words).count() as f64 / union as f64
+    }
+}
+
+/// Title without numbering, punctuation or case, e.g. "3.2 Traits!" -> "traits"
+fn normalize_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .filter(|w| !w.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ':'))
+        .filter(|w| !w.eq_ignore_ascii_case("chapter") && !w.eq_ignore_ascii_case("section"))
+        .map(|w| w.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Matching entries for a book's sections, in reading order
+fn entries(book: &Book) -> Vec<Entry<'_>> {
+    book.chapters.iter().flat_map(|c| &c.sections).map(Entry::new).collect()
+}
+
+/// Align the sections of two editions, in the new edition's order, followed by
+/// the sections that were removed
+pub fn compare_editions(old: &Book, new: &Book) -> Vec<SectionChange> {
+    let (old_entries, new_entries) = (entries(old), entries(new));
+
+    // By title first, then by content for the sections left over
+    let mut matches: Vec<Option<usize>> = vec![None; new_entries.len()];
+    let mut used = vec![false; old_entries.len()];
+    for (new_index, entry) in new_entries.iter().enumerate() {
+        let found = old_entries
+            .iter()
+            .enumerate()
+            .find(|(i, old)| !used[*i] && !entry.title.is_empty() && old.title == entry.title);
+        if let Some((old_index, _)) = found {
+            used[old_index] = true;
+            matches[new_index] = Some(old_index);
+        }
+    }
+    for (new_index, entry) in new_entries.iter().enumerate() {
+        if matches[new_index].is_some() {
+            continue;
+        }
+        let best = old_entries
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !used[*i])
+            .map(|(i, old)| (i, entry.similarity(old)))
+            .filter(|&(_, similarity)| similarity >= MIN_CONTENT_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((old_index, _)) = best {
+            used[old_index] = true;
+            matches[new_index] = Some(old_index);
+        }
+    }
+
+    let mut changes: Vec<SectionChange> = new_entries
+        .iter()
+        .zip(&matches)
+        .map(|(entry, found)| match found {
+            Some(old_index) => {
+                let old = &old_entries[*old_index];
+                let similarity = if old.section.plain_text() == entry.section.plain_text() {
+                    1.0
+                } else {
+                    entry.similarity(old).min(0.99)
+                };
+                SectionChange::Matched {
+                    old_path: old.section.path.clone(),
+                    new_path: entry.section.path.clone(),
+                    similarity,
+                }
+            }
+            None => SectionChange::Added { new_path: entry.section.path.clone() },
+        })
+        .collect();
+    changes.extend(
+        old_entries
+            .iter()
+            .zip(&used)
+            .filter(|(_, used)| !**used)
+            .map(|(old, _)| SectionChange::Removed { old_path: old.section.path.clone() }),
+    );
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::{BookMetadata, Chapter, ContentBlock};
+
+    fn book(id: &str, sections: &[(&str, &str)]) -> Book {
+        let mut book = Book::new(BookMetadata::for_test(id, id));
+        let mut chapter = Chapter::new("Chapter 1", 1, "ch01");
+        for (i, (title, text)) in sections.iter().enumerate() {
+            let mut section = Section::new(*title, i + 1, format!("ch01/s{:02}", i + 1));
+            section.content.push(ContentBlock::Paragraph(text.to_string()));
+            chapter.sections.push(section);
+        }
+        book.chapters.push(chapter);
+        book
+    }
+
+    #[test]
+    fn aligns_by_title_then_content() {
+        let old = book(
+            "old",
+            &[
+                ("1.1 Ownership", "Each value has one owner at a time"),
+                ("1.2 Borrowing rules", "References borrow a value without taking ownership of it"),
+                ("1.3 Unsafe", "Raw pointers"),
+            ],
+        );
+        let new = book(
+            "new",
+            &[
+                ("2.1 Ownership", "Each value has one owner at a time"),
+                ("2.2 References", "References borrow a value without taking ownership of it here"),
+                ("2.3 Async", "Futures are lazy"),
+            ],
+        );
+
+        let changes = compare_editions(&old, &new);
+
+        assert_eq!(
+            changes[0],
+            SectionChange::Matched {
+                old_path: "ch01/s01".into(),
+                new_path: "ch01/s01".into(),
+                similarity: 1.0
+            }
+        );
+        assert!(
+            matches!(&changes[1], SectionChange::Matched { old_path, .. } if old_path == "ch01/s02")
+        );
+        assert!(changes[1].is_changed());
+        assert_eq!(changes[2], SectionChange::Added { new_path: "ch01/s03".into() });
+        assert_eq!(changes[3], SectionChange::Removed { old_path: "ch01/s03".into() });
+    }
+
+    #[test]
+    fn normalize_title_drops_numbering() {
+        assert_eq!(normalize_title("Chapter 3: Traits & Generics!"), "traits generics");
+        assert_eq!(normalize_title("4.2.1 Error handling"), "error handling");
+    }
+}
@@ -0,0 +1,477 @@
+//! DOCX parser
+//!
+//! Reads a Word document's `word/document.xml` and converts its paragraphs,
+//! lists, code and tables to markdown, which is then split into chapters and
+//! sections by heading level.
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+
+use super::markdown::book_from_markdown_document;
+use super::model::{Book, BookMetadata, BookSource};
+
+/// WordprocessingML namespace
+const W_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+/// Dublin Core namespace used by `docProps/core.xml`
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+
+/// Parse a DOCX file into a Book
+pub fn parse_docx_file(path: &Path) -> Result<Book> {
+    let path = path.canonicalize().with_context(|| format!("Invalid path: {}", path.display()))?;
+
+    let file = std::fs::File::open(&path)
+        .with_context(|| format!("Failed to open DOCX file: {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("Failed to read DOCX archive: {}", path.display()))?;
+
+    let document = read_archive_file(&mut archive, "word/document.xml")
+        .with_context(|| format!("Not a Word document: {}", path.display()))?;
+    let (doc_title, markdown) = document_to_markdown(&document)?;
+
+    // Title and author from the document properties, if present
+    let core = read_archive_file(&mut archive, "docProps/core.xml").ok();
+    let core_field = |name: &str| core.as_deref().and_then(|xml| dublin_core_field(xml, name));
+
+    let book_id = path.file_stem().map_or_else(
+        || "unknown".to_string(),
+        |s| s.to_string_lossy().to_string().to_lowercase().replace(' ', "-"),
+    );
+    let title = core_field("title").or(doc_title).unwrap_or_else(|| {
+        path.file_stem().map_or_else(|| "Unknown".into(), |s| s.to_string_lossy().to_string())
+    });
+
+    let metadata = BookMetadata {
+        id: book_id,
+        title,
+        author: core_field("creator"),
+        source: BookSource::Docx(path.clone()),
+        language: core_field("language"),
+        description: core_field("description"),
+        cover_image: None,
+        added_at: crate::config::datetime::now(),
+        last_accessed: None,
+    };
+
+    Ok(book_from_markdown_document(metadata, &markdown))
+}
+
+/// Read a file from the archive as a string
+fn read_archive_file(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> Result<String> {
+    let mut file = archive.by_name(name).with_context(|| format!("Missing {}", name))?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).with_context(|| format!("Failed to read {}", name))?;
+    Ok(contents)
+}
+
+/// A Dublin Core field from `docProps/core.xml`
+fn dublin_core_field(xml: &str, name: &str) -> Option<String> {
+    let doc = Document::parse(xml).ok()?;
+    doc.descendants()
+        .find(|n| n.tag_name().namespace() == Some(DC_NS) && n.tag_name().name() == name)
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+/// Convert `word/document.xml` to markdown
+///
+/// Returns the text of the first `Title`-styled paragraph separately, since
+/// it names the document rather than starting a chapter.
+fn document_to_markdown(xml: &str) -> Result<(Option<String>, String)> {
+    let doc = Document::parse(xml).context("Failed to parse document.xml")?;
+    let body = doc.descendants().find(|n| is_w(n, "body")).context("document.xml has no body")?;
+
+    let mut title = None;
+    let mut out = String::new();
+    let mut code = String::new();
+
+    for node in body.children().filter(Node::is_element) {
+        if is_w(&node, "tbl") {
+            flush_code(&mut out, &mut code);
+            out.push_str(&table_to_markdown(&node));
+            out.push('\n');
+            continue;
+        }
+        if !is_w(&node, "p") {
+            continue;
+        }
+
+        let style = paragraph_style(&node).unwrap_or_default().to_lowercase();
+        let is_code = style.contains("code") || style.contains("preformatted");
+        if is_code {
+            code.push_str(&paragraph_text(&node, false));
+            code.push('\n');
+            continue;
+        }
+        flush_code(&mut out, &mut code);
+
+        let text = paragraph_text(&node, true);
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        if style == "title" {
+            if title.is_none() {
+                title = Some(text.trim().to_string());
+            }
+        } else if let Some(level) = heading_level(&style) {
+            // Headings carry their own emphasis; drop run formatting
+            let plain = escape_inline(paragraph_text(&node, false).trim());
+            out.push_str(&format!("{} {}\n\n", "#".repeat(level), plain));
+        } else if let Some(depth) = list_depth(&node) {
+            let text = escape_block_start(text.trim());
+            out.push_str(&format!("{}- {}\n\n", "  ".repeat(depth), text));
+        } else if style.contains("quote") {
+            out.push_str(&format!("> {}\n\n", escape_block_start(text.trim())));
+        } else {
+            out.push_str(&escape_block_start(text.trim()));
+            out.push_str("\n\n");
+        }
+    }
+    flush_code(&mut out, &mut code);
+
+    Ok((title, out))
+}
+
+/// Emit pending code-styled paragraphs as a fenced block
+fn flush_code(out: &mut String, code: &mut String) {
+    if !code.is_empty() {
+        // The fence must be longer than any run of backticks in the code
+        let fence = "`".repeat(longest_backtick_run(code).max(2) + 1);
+        out.push_str(&format!("{}\n{}{}\n\n", fence, code, fence));
+        code.clear();
+    }
+}
+
+/// Length of the longest run of backticks in some text
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// Escape characters that markdown would read as inline formatting
+fn escape_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escape the start of a paragraph that markdown would read as a heading,
+/// quote or list item
+fn escape_block_start(text: &str) -> String {
+    if text.starts_with(['#', '>', '-', '+']) {
+        return format!("\\{}", text);
+    }
+    // "1. " or "1) " starts an ordered list
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 && text[digits..].starts_with(['.', ')']) {
+        return format!("{}\\{}", &text[..digits], &text[digits..]);
+    }
+    text.to_string()
+}
+
+/// A code span around some text, with more backticks than the text contains
+fn code_span(text: &str) -> String {
+    let ticks = "`".repeat(longest_backtick_run(text) + 1);
+    // Padding keeps backticks at either end apart from the delimiters
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{} {} {}", ticks, text, ticks)
+    } else {
+        format!("{}{}{}", ticks, text, ticks)
+    }
+}
+
+/// Whether a node is the WordprocessingML element `name`
+fn is_w(node: &Node, name: &str) -> bool {
+    node.tag_name().namespace() == Some(W_NS) && node.tag_name().name() == name
+}
+
+/// A `w:` attribute of a node
+fn w_attr<'a>(node: &Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attribute((W_NS, name))
+}
+
+/// First child element named `name`
+fn w_child<'a, 'input>(node: &Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| is_w(n, name))
+}
+
+/// Style ID of a paragraph (e.g. "Heading1")
+fn paragraph_style<'a>(paragraph: &Node<'a, '_>) -> Option<&'a str> {
+    let props = w_child(paragraph, "pPr")?;
+    w_attr(&w_child(&props, "pStyle")?, "val")
+}
+
+/// Heading level for a paragraph style ("heading1", "heading 2", ...)
+fn heading_level(style: &str) -> Option<usize> {
+    let level = style.strip_prefix("heading")?.trim().parse::<usize>().ok()?;
+    (1..=6).contains(&level).then_some(level)
+}
+
+/// Nesting depth of a list paragraph
+fn list_depth(paragraph: &Node) -> Option<usize> {
+    let numbering = w_child(&w_child(paragraph, "pPr")?, "numPr")?;
+    let depth = w_child(&numbering, "ilvl")
+        .and_then(|l| w_attr(&l, "val"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    Some(depth)
+}
+
+/// Text of a paragraph's runs, optionally with bold/italic/monospace as markdown
+fn paragraph_text(paragraph: &Node, formatted: bool) -> String {
+    let mut out = String::new();
+    for run in paragraph.descendants().filter(|n| is_w(n, "r")) {
+        let mut text = String::new();
+        for child in run.children() {
+            if is_w(&child, "t") {
+                text.push_str(child.text().unwrap_or(""));
+            } else if is_w(&child, "tab") {
+                text.push('\t');
+            } else if is_w(&child, "br") {
+                text.push('\n');
+            }
+        }
+        if text.is_empty() {
+            continue;
+        }
+
+        let props = w_child(&run, "rPr");
+        let flag = |name: &str| {
+            props
+                .and_then(|p| w_child(&p, name))
+                .is_some_and(|n| !matches!(w_attr(&n, "val"), Some("0" | "false")))
+        };
+        let monospace = props
+            .and_then(|p| w_child(&p, "rFonts"))
+            .and_then(|f| w_attr(&f, "ascii"))
+            .is_some_and(|font| {
+                let font = font.to_lowercase();
+                font.contains("courier") || font.contains("consolas") || font.contains("mono")
+            });
+
+        if !formatted {
+            out.push_str(&text);
+            continue;
+        }
+        // Emphasis markers only work against the text, so spaces stay outside
+        let inner = text.trim();
+        let start = text.len() - text.trim_start().len();
+        let (leading, trailing) = (&text[..start], &text[start + inner.len()..]);
+        let inner = if inner.is_empty() {
+            String::new()
+        } else if monospace {
+            code_span(inner)
+        } else {
+            let marker = match (flag("b"), flag("i")) {
+                (true, true) => "***",
+                (true, false) => "**",
+                (false, true) => "*",
+                (false, false) => "",
+            };
+            format!("{}{}{}", marker, escape_inline(inner), marker)
+        };
+        out.push_str(&format!("{}{}{}", leading, inner, trailing));
+    }
+    out
+}
+
+/// Convert a table to a markdown table, using the first row as the header
+fn table_to_markdown(table: &Node) -> String {
+    let rows: Vec<Vec<String>> = table
+        .children()
+        .filter(|n| is_w(n, "tr"))
+        .map(|row| {
+            row.children()
+                .filter(|n| is_w(n, "tc"))
+                .map(|cell| {
+                    let paragraphs: Vec<String> = cell
+                        .children()
+                        .filter(|n| is_w(n, "p"))
+                        .map(|p| paragraph_text(&p, true).trim().replace('|', "\\|"))
+                        .filter(|t| !t.is_empty())
+                        .collect();
+                    paragraphs.join(" ")
+                })
+                .collect()
+        })
+        .collect();
+
+    let Some(columns) = rows.iter().map(Vec::len).max().filter(|&n| n > 0) else {
+        return String::new();
+    };
+
+    let mut out = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<&str> =
+            (0..columns).map(|c| row.get(c).map_or("", String::as_str)).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        if i == 0 {
+            out.push_str(&format!("|{}\n", " --- |".repeat(columns)));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::book::ContentBlock;
+
+    fn document(body: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="{}"><w:body>{}</w:body></w:document>"#,
+            W_NS, body
+        )
+    }
+
+    fn paragraph(style: &str, text: &str) -> String {
+        format!(
+            r#"<w:p><w:pPr><w:pStyle w:val="{}"/></w:pPr><w:r><w:t>{}</w:t></w:r></w:p>"#,
+            style, text
+        )
+    }
+
+    #[test]
+    fn converts_headings_lists_code_and_formatting() {
+        let body = [
+            paragraph("Title", "Handbook"),
+            paragraph("Heading1", "Setup"),
+            r#"<w:p><w:r><w:t xml:space="preserve">Run </w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>this</w:t></w:r></w:p>"#.to_string(),
+            r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="1"/></w:numPr></w:pPr><w:r><w:t>Nested</w:t></w:r></w:p>"#.to_string(),
+            paragraph("SourceCode", "cargo build"),
+            paragraph("SourceCode", "cargo test"),
+        ]
+        .concat();
+
+        let (title, md) = document_to_markdown(&document(&body)).unwrap();
+
+        assert_eq!(title.as_deref(), Some("Handbook"));
+        assert_eq!(
+            md,
+            "# Setup\n\nRun **this**\n\n  - Nested\n\n```\ncargo build\ncargo test\n```\n\n"
+        );
+    }
+
+    /// A paragraph of one run with some run properties
+    fn run(props: &str, text: &str) -> String {
+        format!(
+            r#"<w:p><w:r><w:rPr>{}</w:rPr><w:t xml:space="preserve">{}</w:t></w:r></w:p>"#,
+            props, text
+        )
+    }
+
+    #[test]
+    fn keeps_spaces_outside_emphasis() {
+        let body = format!(
+            r#"<w:p><w:r><w:t>Run</w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t xml:space="preserve"> this </w:t></w:r><w:r><w:t>now</w:t></w:r></w:p>{}"#,
+            run("<w:i/>", "slanted ")
+        );
+
+        let (_, md) = document_to_markdown(&document(&body)).unwrap();
+
+        assert_eq!(md, "Run **this** now\n\n*slanted*\n\n");
+    }
+
+    #[test]
+    fn escapes_markdown_in_text() {
+        let body = [
+            paragraph("Normal", "# not a heading"),
+            paragraph("Normal", "- not a list"),
+            paragraph("Normal", "1. not a list either"),
+            paragraph("Normal", "2*3 and [brackets] and snake_case"),
+            paragraph("Heading1", "C* &lt;tips&gt;"),
+        ]
+        .concat();
+
+        let (_, md) = document_to_markdown(&document(&body)).unwrap();
+
+        assert_eq!(
+            md,
+            "\\# not a heading\n\n\\- not a list\n\n1\\. not a list either\n\n\
+             2\\*3 and \\[brackets\\] and snake\\_case\n\n# C\\* \\<tips>\n\n"
+        );
+        let book = book_from_markdown_document(BookMetadata::for_test("t", "T"), &md);
+        let blocks = &book.chapters[0].sections[0].content;
+        assert!(matches!(&blocks[0], ContentBlock::Paragraph(t) if t == "# not a heading"));
+    }
+
+    #[test]
+    fn fences_code_containing_backticks() {
+        let body = [
+            paragraph("SourceCode", "```"),
+            paragraph("SourceCode", "quoted"),
+            run(r#"<w:rFonts w:ascii="Consolas"/>"#, "a`b"),
+        ]
+        .concat();
+
+        let (_, md) = document_to_markdown(&document(&body)).unwrap();
+
+        assert_eq!(md, "````\n```\nquoted\n````\n\n``a`b``\n\n");
+    }
+
+    #[test]
+    fn converts_tables() {
+        let cell = |t: &str| format!("<w:tc><w:p><w:r><w:t>{}</w:t></w:r></w:p></w:tc>", t);
+        let body = format!(
+            "<w:tbl><w:tr>{}{}</w:tr><w:tr>{}{}</w:tr></w:tbl>",
+            cell("Key"),
+            cell("Value"),
+            cell("a"),
+            cell("1")
+        );
+
+        let (_, md) = document_to_markdown(&document(&body)).unwrap();
+
+        assert_eq!(md, "| Key | Value |\n| --- | --- |\n| a | 1 |\n\n");
+    }
+
+    #[test]
+    fn parses_docx_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("Team Guide.docx");
+        let body = [
+            paragraph("Heading1", "Intro"),
+            paragraph("Normal", "Welcome."),
+            paragraph("Heading2", "Goals"),
+            paragraph("Normal", "Learn."),
+        ]
+        .concat();
+        let core = format!(
+            r#"<cp:coreProperties xmlns:cp="urn:cp" xmlns:dc="{}"><dc:title>Guide</dc:title><dc:creator>Ops</dc:creator></cp:coreProperties>"#,
+            DC_NS
+        );
+
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("word/document.xml", options).unwrap();
+        zip.write_all(document(&body).as_bytes()).unwrap();
+        zip.start_file("docProps/core.xml", options).unwrap();
+        zip.write_all(core.as_bytes()).unwrap();
+        zip.finish().unwrap();
+
+        let book = parse_docx_file(&path).unwrap();
+
+        assert_eq!(book.metadata.id, "team-guide");
+        assert_eq!(book.metadata.title, "Guide");
+        assert_eq!(book.metadata.author.as_deref(), Some("Ops"));
+        assert_eq!(book.chapters.len(), 1);
+        let sections: Vec<_> = book.chapters[0].sections.iter().map(|s| &s.title).collect();
+        assert_eq!(sections, ["Intro", "Goals"]);
+        assert!(matches!(
+            &book.chapters[0].sections[1].content[0],
+            ContentBlock::Paragraph(text) if text == "Learn."
+        ));
+    }
+}
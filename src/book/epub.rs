@@ -2,13 +2,16 @@
 //!
 //! Parses EPUB files into the unified content model.
 
+use std::collections::{HashMap, HashSet};
 use std::io::BufReader;
 use std::path::Path;
 
 use anyhow::{Context, Result};
 use epub::doc::EpubDoc;
+use once_cell::sync::Lazy;
+use regex::Regex;
 
-use super::markdown::parse_markdown_content;
+use super::markdown::{extract_html_attribute, parse_markdown_with_emphasis};
 use super::model::{Book, BookMetadata, BookSource, Chapter, ContentBlock, Section};
 
 /// Parse an EPUB file into a Book
@@ -46,14 +49,26 @@ pub fn parse_epub_file(path: &Path) -> Result<Book> {
         language,
         description,
         cover_image: None,
-        added_at: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_secs() as i64),
+        added_at: crate::config::datetime::now(),
         last_accessed: None,
     };
 
     let mut book = Book::new(metadata);
 
+    // Inline styles set by the book's stylesheets, by class
+    let stylesheets: Vec<String> = doc
+        .resources
+        .iter()
+        .filter(|(_, resource)| resource.mime == "text/css")
+        .map(|(id, _)| id.clone())
+        .collect();
+    let mut styles = ClassStyles::new();
+    for id in stylesheets {
+        if let Some((css, _mime)) = doc.get_resource(&id) {
+            merge_styles(&mut styles, parse_stylesheet(&String::from_utf8_lossy(&css)));
+        }
+    }
+
     // Get the spine (reading order) - spine is Vec<SpineItem>, we need idref
     let spine: Vec<String> = doc.spine.iter().map(|s| s.idref.clone()).collect();
 
@@ -98,7 +113,7 @@ pub fn parse_epub_file(path: &Path) -> Result<Book> {
             section_num += 1;
 
             // Parse XHTML content to ContentBlocks
-            let blocks = parse_xhtml_content(&content_str);
+            let blocks = parse_xhtml_content(&content_str, &styles);
 
             let section_path = format!(
                 "ch{:02}/s{:02}",
@@ -107,7 +122,7 @@ pub fn parse_epub_file(path: &Path) -> Result<Book> {
             );
 
             let mut section = Section::new(&section_title, section_num, section_path);
-            section.content = blocks;
+            section.content = blocks.into();
             section.calculate_reading_time();
 
             if let Some(ref mut ch) = current_chapter {
@@ -191,15 +206,21 @@ fn strip_html_tags(html: &str) -> String {
 }
 
 /// Parse XHTML content into ContentBlocks
-fn parse_xhtml_content(xhtml: &str) -> Vec<ContentBlock> {
+fn parse_xhtml_content(xhtml: &str, styles: &ClassStyles) -> Vec<ContentBlock> {
     // Extract the body content
     let body_content = extract_body_content(xhtml).unwrap_or_else(|| xhtml.to_string());
 
+    // Add the document's own <style> rules to the book's
+    let mut styles = styles.clone();
+    if let Some(css) = extract_tag_content(xhtml, "style") {
+        merge_styles(&mut styles, parse_stylesheet(&css));
+    }
+
     // Convert XHTML to a markdown-like representation for parsing
-    let markdown = xhtml_to_markdown(&body_content);
+    let markdown = xhtml_to_markdown(&body_content, &styles);
 
     // Use the markdown parser
-    parse_markdown_content(&markdown)
+    parse_markdown_with_emphasis(&markdown)
 }
 
 /// Extract content between body tags
@@ -210,9 +231,205 @@ fn extract_body_content(xhtml: &str) -> Option<String> {
     if start < end { Some(xhtml[start + 1..end].to_string()) } else { None }
 }
 
+/// Tag stack entry for an element holding a footnote definition
+const FOOTNOTE_TAG: &str = "#footnote";
+
+/// Tag stack entry for a footnote link whose text is replaced by a marker
+const NOTEREF_TAG: &str = "#noteref";
+
+/// Tag stack entry prefix for an element drawn with inline markers, followed
+/// by its tag name, `:` the styles it opened (see `InlineStyle::letters`) and
+/// `@` the byte in the output where its markers start
+const INLINE_TAG: &str = "#inline:";
+
+/// Elements that never have content or a closing tag
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Inline styles the renderer draws from markdown markers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct InlineStyle {
+    bold: bool,
+    italic: bool,
+    code: bool,
+}
+
+/// Inline styles set by stylesheet rules, by class name
+type ClassStyles = HashMap<String, InlineStyle>;
+
+/// CSS comments, removed before reading rules
+static CSS_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)/\*.*?\*/").unwrap());
+
+impl InlineStyle {
+    const BOLD: Self = Self { bold: true, italic: false, code: false };
+    const ITALIC: Self = Self { bold: false, italic: true, code: false };
+    const CODE: Self = Self { bold: false, italic: false, code: true };
+
+    fn is_empty(self) -> bool {
+        !self.bold && !self.italic && !self.code
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            bold: self.bold || other.bold,
+            italic: self.italic || other.italic,
+            code: self.code || other.code,
+        }
+    }
+
+    /// Styles not already applied by `active`; none inside code, where
+    /// markers would show literally
+    fn without(self, active: Self) -> Self {
+        if active.code {
+            return Self::default();
+        }
+        Self {
+            bold: self.bold && !active.bold,
+            italic: self.italic && !active.italic,
+            code: self.code,
+        }
+    }
+
+    /// Markers opening the styles; they close in reverse
+    fn markers(self) -> String {
+        let mut markers = String::new();
+        for (on, marker) in [(self.bold, "**"), (self.italic, "*"), (self.code, "`")] {
+            if on {
+                markers.push_str(marker);
+            }
+        }
+        markers
+    }
+
+    /// The styles as letters, for the tag stack
+    fn letters(self) -> String {
+        [(self.bold, 'b'), (self.italic, 'i'), (self.code, 'c')]
+            .into_iter()
+            .filter_map(|(on, letter)| on.then_some(letter))
+            .collect()
+    }
+
+    fn from_letters(letters: &str) -> Self {
+        Self {
+            bold: letters.contains('b'),
+            italic: letters.contains('i'),
+            code: letters.contains('c'),
+        }
+    }
+
+    /// Styles set by CSS declarations, e.g. `font-style: italic`
+    fn from_declarations(declarations: &str) -> Self {
+        let mut style = Self::default();
+        for declaration in declarations.split(';') {
+            let Some((property, value)) = declaration.split_once(':') else { continue };
+            let value = value.trim().to_lowercase();
+            let bold = value.split_whitespace().any(|v| {
+                matches!(v, "bold" | "bolder") || v.parse::<u32>().is_ok_and(|weight| weight >= 600)
+            });
+            let italic = value.split_whitespace().any(|v| matches!(v, "italic" | "oblique"));
+            let code = ["monospace", "courier", "consolas", "menlo", "monaco"]
+                .iter()
+                .any(|font| value.contains(font));
+            match property.trim().to_lowercase().as_str() {
+                "font-weight" => style.bold |= bold,
+                "font-style" => style.italic |= italic,
+                "font-family" => style.code |= code,
+                "font" => style = style.union(Self { bold, italic, code }),
+                _ => {}
+            }
+        }
+        style
+    }
+
+    /// Styles implied by a conventional class name, as used by mdBook,
+    /// DocBook and many EPUB converters
+    fn from_class_name(class: &str) -> Self {
+        match class.to_lowercase().as_str() {
+            "bold" | "b" | "strong" => Self::BOLD,
+            "italic" | "italics" | "i" | "em" | "emphasis" | "emph" | "cite" | "foreign" => {
+                Self::ITALIC
+            }
+            "code" | "literal" | "monospace" | "mono" | "tt" | "kbd" | "keycap" | "filename"
+            | "command" | "userinput" | "computeroutput" | "varname" | "function" | "hljs" => {
+                Self::CODE
+            }
+            _ => Self::default(),
+        }
+    }
+}
+
+/// Add one set of class styles to another
+fn merge_styles(styles: &mut ClassStyles, more: ClassStyles) {
+    for (class, style) in more {
+        let entry = styles.entry(class).or_default();
+        *entry = entry.union(style);
+    }
+}
+
+/// Inline styles set by a stylesheet's class rules (`.name` or `span.name`)
+fn parse_stylesheet(css: &str) -> ClassStyles {
+    let css = CSS_COMMENT.replace_all(css, "");
+    let mut styles = ClassStyles::new();
+    for rule in css.split('}') {
+        let Some((selectors, declarations)) = rule.rsplit_once('{') else { continue };
+        let style = InlineStyle::from_declarations(declarations);
+        if style.is_empty() {
+            continue;
+        }
+        // Rules nested in @media blocks keep only their own selectors
+        let selectors = selectors.rsplit('{').next().unwrap_or(selectors);
+        for selector in selectors.split(',') {
+            let Some((element, class)) = selector.trim().split_once('.') else { continue };
+            let simple = element.chars().all(|c| c.is_ascii_alphanumeric())
+                && !class.is_empty()
+                && class.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+            if simple {
+                let entry = styles.entry(class.to_string()).or_default();
+                *entry = entry.union(style);
+            }
+        }
+    }
+    styles
+}
+
+/// Inline styles an element asks for through its tag, classes and `style`
+/// attribute; block elements never get inline markers
+fn element_style(tag_name: &str, tag: &str, styles: &ClassStyles) -> InlineStyle {
+    let mut style = match tag_name {
+        "em" | "i" | "cite" | "dfn" | "var" => InlineStyle::ITALIC,
+        "strong" | "b" => InlineStyle::BOLD,
+        "code" | "kbd" | "samp" | "tt" => InlineStyle::CODE,
+        "span" => InlineStyle::default(),
+        _ => return InlineStyle::default(),
+    };
+    if let Some(classes) = extract_html_attribute(tag, "class") {
+        for class in classes.split_whitespace() {
+            let from_css = styles.get(class).copied().unwrap_or_default();
+            style = style.union(from_css).union(InlineStyle::from_class_name(class));
+        }
+    }
+    if let Some(declarations) = extract_html_attribute(tag, "style") {
+        style = style.union(InlineStyle::from_declarations(&declarations));
+    }
+    style
+}
+
+/// Inline styles already applied by elements on the tag stack
+fn active_style(tag_stack: &[String]) -> InlineStyle {
+    let in_pre = tag_stack.iter().any(|t| t == "pre");
+    tag_stack
+        .iter()
+        .filter_map(|t| t.strip_prefix(INLINE_TAG)?.split_once(':'))
+        .map(|(_, t)| InlineStyle::from_letters(t.split('@').next().unwrap_or("")))
+        .fold(InlineStyle { code: in_pre, ..InlineStyle::default() }, InlineStyle::union)
+}
+
 /// Convert XHTML to markdown-like format
-fn xhtml_to_markdown(xhtml: &str) -> String {
+fn xhtml_to_markdown(xhtml: &str, styles: &ClassStyles) -> String {
     let mut result = String::with_capacity(xhtml.len());
+    let footnote_ids = collect_footnote_ids(xhtml);
 
     // Simple state machine for conversion
     let mut in_tag = false;
@@ -228,14 +445,14 @@ fn xhtml_to_markdown(xhtml: &str) -> String {
 
         if c == '>' {
             in_tag = false;
-            process_tag(&current_tag, &mut result, &mut tag_stack);
+            process_tag(&current_tag, &mut result, &mut tag_stack, &footnote_ids, styles);
             continue;
         }
 
         if in_tag {
             current_tag.push(c);
-        } else {
-            // Handle text content
+        } else if !tag_stack.iter().any(|t| t == NOTEREF_TAG) {
+            // Handle text content (footnote link text is replaced by a marker)
             result.push(c);
         }
     }
@@ -243,10 +460,47 @@ fn xhtml_to_markdown(xhtml: &str) -> String {
     result
 }
 
+/// Collect the ids of footnote definitions in the document
+fn collect_footnote_ids(xhtml: &str) -> HashSet<String> {
+    xhtml
+        .split('<')
+        .filter_map(|chunk| chunk.split('>').next())
+        .filter_map(footnote_definition_id)
+        .collect()
+}
+
+/// If the tag opens a footnote/endnote definition, return its id
+fn footnote_definition_id(tag: &str) -> Option<String> {
+    let lower = tag.to_lowercase();
+    let is_note = ["epub:type", "role"].iter().any(|attr| {
+        extract_html_attribute(&lower, attr).is_some_and(|value| {
+            value.split_whitespace().any(|v| {
+                matches!(v, "footnote" | "endnote" | "rearnote" | "doc-footnote" | "doc-endnote")
+            })
+        })
+    }) || extract_html_attribute(&lower, "class")
+        .is_some_and(|value| value.split_whitespace().any(|v| matches!(v, "footnote" | "endnote")));
+
+    if is_note { extract_html_attribute(tag, " id").filter(|id| !id.is_empty()) } else { None }
+}
+
+/// Footnote id referenced by a link tag (`href="#id"`)
+fn footnote_link_target(tag: &str) -> Option<String> {
+    let href = extract_html_attribute(tag, "href")?;
+    let (_, fragment) = href.split_once('#')?;
+    Some(fragment.to_string())
+}
+
 /// Process an HTML tag and convert to markdown
 // skipcq: RS-R1000 - HTML tag processing inherently has high cyclomatic complexity
 #[allow(clippy::cognitive_complexity)]
-fn process_tag(tag: &str, output: &mut String, tag_stack: &mut Vec<String>) {
+fn process_tag(
+    tag: &str,
+    output: &mut String,
+    tag_stack: &mut Vec<String>,
+    footnote_ids: &HashSet<String>,
+    styles: &ClassStyles,
+) {
     let tag_lower = tag.to_lowercase();
     let is_closing = tag_lower.starts_with('/');
     let tag_name = if is_closing {
@@ -254,6 +508,87 @@ fn process_tag(tag: &str, output: &mut String, tag_stack: &mut Vec<String>) {
     } else {
         tag_lower.split_whitespace().next().unwrap_or("")
     };
+    let tag_name = tag_name.trim_end_matches('/');
+
+    // Footnote definitions become markdown footnotes (`[^id]: text`), so block
+    // structure inside them is flattened onto one line
+    let in_footnote = tag_stack.iter().any(|t| t == FOOTNOTE_TAG);
+    let is_block = matches!(
+        tag_name,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "div" | "li" | "blockquote" | "br"
+    );
+
+    // Elements without content are never left open on the stack
+    if !is_closing && (VOID_TAGS.contains(&tag_name) || tag_lower.trim_end().ends_with('/')) {
+        match tag_name {
+            "br" if in_footnote => output.push(' '),
+            "br" => output.push('\n'),
+            "hr" => output.push_str("\n---\n"),
+            _ => {}
+        }
+        return;
+    }
+
+    if is_closing && tag_stack.last().is_some_and(|t| t == FOOTNOTE_TAG) {
+        output.push_str("\n\n");
+        tag_stack.pop();
+        return;
+    }
+    if !is_closing && !in_footnote {
+        if let Some(id) = footnote_definition_id(tag) {
+            output.push_str(&format!("\n\n[^{}]: ", id));
+            tag_stack.push(FOOTNOTE_TAG.to_string());
+            return;
+        }
+    }
+    if in_footnote && is_block {
+        output.push(' ');
+        if is_closing {
+            tag_stack.pop();
+        } else {
+            tag_stack.push(tag_name.to_string());
+        }
+        return;
+    }
+    if !is_closing && tag_name == "a" {
+        match footnote_link_target(tag) {
+            // Reference to a footnote: emit a markdown footnote reference
+            Some(id) if !in_footnote && footnote_ids.contains(&id) => {
+                output.push_str(&format!("[^{}]", id));
+                tag_stack.push(NOTEREF_TAG.to_string());
+                return;
+            }
+            // Back-link from a footnote to its reference: drop it
+            Some(_) if in_footnote && !tag_lower.contains("://") => {
+                tag_stack.push(NOTEREF_TAG.to_string());
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    if is_closing && is_inline_tag(tag_name) {
+        // Close the element, and any left open inside it, innermost first
+        let Some(open) = tag_stack.iter().rposition(|t| element_name(t) == tag_name) else {
+            return;
+        };
+        for entry in tag_stack.split_off(open).iter().rev() {
+            let opened = entry.strip_prefix(INLINE_TAG).and_then(|t| t.split_once(':'));
+            if let Some((letters, start)) = opened.and_then(|(_, t)| t.split_once('@')) {
+                let markers = InlineStyle::from_letters(letters).markers();
+                close_inline(output, start.parse().unwrap_or(output.len()), &markers);
+            }
+        }
+        return;
+    } else if !is_closing {
+        let style = element_style(tag_name, tag, styles).without(active_style(tag_stack));
+        if !style.is_empty() {
+            let letters = style.letters();
+            tag_stack.push(format!("{}{}:{}@{}", INLINE_TAG, tag_name, letters, output.len()));
+            output.push_str(&style.markers());
+            return;
+        }
+    }
 
     if is_closing {
         // Closing tag
@@ -280,14 +615,6 @@ fn process_tag(tag: &str, output: &mut String, tag_stack: &mut Vec<String>) {
                 output.push('\n');
                 tag_stack.pop();
             }
-            "em" | "i" => {
-                output.push('*');
-                tag_stack.pop();
-            }
-            "strong" | "b" => {
-                output.push_str("**");
-                tag_stack.pop();
-            }
             _ => {
                 tag_stack.pop();
             }
@@ -323,12 +650,6 @@ fn process_tag(tag: &str, output: &mut String, tag_stack: &mut Vec<String>) {
                 output.push_str("\n\n");
                 tag_stack.push(tag_name.to_string());
             }
-            "br" => {
-                output.push('\n');
-            }
-            "hr" => {
-                output.push_str("\n---\n");
-            }
             "li" => {
                 output.push_str("\n- ");
                 tag_stack.push(tag_name.to_string());
@@ -343,25 +664,10 @@ fn process_tag(tag: &str, output: &mut String, tag_stack: &mut Vec<String>) {
                 }
                 tag_stack.push(tag_name.to_string());
             }
-            "code" => {
-                // Only add backticks if not inside a pre tag
-                if !tag_stack.iter().any(|t| t == "pre") {
-                    output.push('`');
-                }
-                tag_stack.push(tag_name.to_string());
-            }
             "blockquote" => {
                 output.push_str("\n> ");
                 tag_stack.push(tag_name.to_string());
             }
-            "em" | "i" => {
-                output.push('*');
-                tag_stack.push(tag_name.to_string());
-            }
-            "strong" | "b" => {
-                output.push_str("**");
-                tag_stack.push(tag_name.to_string());
-            }
             _ => {
                 tag_stack.push(tag_name.to_string());
             }
@@ -369,6 +675,52 @@ fn process_tag(tag: &str, output: &mut String, tag_stack: &mut Vec<String>) {
     }
 }
 
+/// Tag name of an element on the tag stack
+fn element_name(entry: &str) -> &str {
+    match entry.strip_prefix(INLINE_TAG) {
+        Some(inline) => inline.split(':').next().unwrap_or(""),
+        None => entry,
+    }
+}
+
+/// Whether a tag can carry inline markers
+fn is_inline_tag(tag_name: &str) -> bool {
+    matches!(
+        tag_name,
+        "em" | "i"
+            | "cite"
+            | "dfn"
+            | "var"
+            | "strong"
+            | "b"
+            | "code"
+            | "kbd"
+            | "samp"
+            | "tt"
+            | "span"
+    )
+}
+
+/// Close the inline markers opened at byte `start` of the output, keeping
+/// them next to the text inside so they still count as markers; an element
+/// with no text drops its markers
+fn close_inline(output: &mut String, start: usize, opening: &str) {
+    let text_start = start + opening.len();
+    let end = output.trim_end().len();
+    if end <= text_start {
+        output.replace_range(start..text_start, "");
+        return;
+    }
+    let closing: String = opening.chars().rev().collect();
+    output.insert_str(end, &closing);
+
+    let leading = output[text_start..].len() - output[text_start..].trim_start().len();
+    if leading > 0 {
+        let space = output[text_start..text_start + leading].to_string();
+        output.replace_range(start..text_start + leading, &format!("{}{}", space, opening));
+    }
+}
+
 /// Extract code language from tag attributes
 fn extract_code_language(tag: &str) -> Option<String> {
     // Look for class="language-xxx" or class="xxx"
@@ -453,21 +805,21 @@ mod tests {
     #[test]
     fn xhtml_to_markdown_heading() {
         let xhtml = "<h1>Title</h1>";
-        let md = xhtml_to_markdown(xhtml);
+        let md = xhtml_to_markdown(xhtml, &ClassStyles::new());
         assert!(md.contains("# Title"));
     }
 
     #[test]
     fn xhtml_to_markdown_paragraph() {
         let xhtml = "<p>This is a paragraph.</p>";
-        let md = xhtml_to_markdown(xhtml);
+        let md = xhtml_to_markdown(xhtml, &ClassStyles::new());
         assert!(md.contains("This is a paragraph."));
     }
 
     #[test]
     fn xhtml_to_markdown_code() {
         let xhtml = "<pre class=\"language-rust\"><code>fn main() {}</code></pre>";
-        let md = xhtml_to_markdown(xhtml);
+        let md = xhtml_to_markdown(xhtml, &ClassStyles::new());
         assert!(md.contains("```rust"));
         assert!(md.contains("fn main()"));
     }
@@ -475,11 +827,64 @@ mod tests {
     #[test]
     fn xhtml_to_markdown_list() {
         let xhtml = "<ul><li>Item 1</li><li>Item 2</li></ul>";
-        let md = xhtml_to_markdown(xhtml);
+        let md = xhtml_to_markdown(xhtml, &ClassStyles::new());
         assert!(md.contains("- Item 1"));
         assert!(md.contains("- Item 2"));
     }
 
+    #[test]
+    fn xhtml_footnotes_become_footnote_blocks() {
+        let xhtml = r##"<p>Lifetimes<sup><a href="#fn1" epub:type="noteref">1</a></sup> matter.</p>
+<aside epub:type="footnote" id="fn1"><p><a href="#ref1">1</a> Also called <em>regions</em>.</p></aside>"##;
+        let blocks = parse_markdown_with_emphasis(&xhtml_to_markdown(xhtml, &ClassStyles::new()));
+
+        assert!(matches!(&blocks[0], ContentBlock::Paragraph(t) if t == "Lifetimes¹ matter."));
+        assert!(matches!(
+            &blocks[1],
+            ContentBlock::Footnote { label, text } if label == "1" && text.contains("Also called *regions*.")
+        ));
+    }
+
+    #[test]
+    fn links_without_definitions_keep_their_text() {
+        let md = xhtml_to_markdown(
+            r##"<p>See <a href="#intro">the intro</a>.</p>"##,
+            &ClassStyles::new(),
+        );
+        assert!(md.contains("See the intro."));
+    }
+
+    #[test]
+    fn inline_styles_come_from_tags_classes_and_css() {
+        let styles = parse_stylesheet(
+            "/* calibre */ .calibre5 { font-style: italic } \
+             span.calibre7, .x { font-weight: 700; } \
+             @media print { .mono { font-family: \"Courier New\", monospace } }",
+        );
+        let xhtml = r#"<p><span class="calibre5">Ownership</span> is <b><span class="bold">key</span></b>,
+            <span class="calibre7"> see </span><span class="mono">Box</span>
+            <code>Rc</code> and <span class="literal"><em>x</em></span><span class="italic"></span>.</p>
+            <pre><code>let <b>x</b> = 1;</code></pre>"#;
+
+        let blocks = parse_markdown_with_emphasis(&xhtml_to_markdown(xhtml, &styles));
+
+        assert!(matches!(
+            &blocks[0],
+            ContentBlock::Paragraph(t)
+                if t == "*Ownership* is **key**, **see** `Box` `Rc` and `x`."
+        ));
+        assert!(matches!(&blocks[1], ContentBlock::Code(code) if code.code.trim() == "let x = 1;"));
+    }
+
+    #[test]
+    fn empty_elements_inside_emphasis_leave_it_closed() {
+        let xhtml = r#"<p><em>one<span epub:type="pagebreak" id="p12"/>two<br/></em> after
+            <i>three</i></p>"#;
+        let blocks = parse_markdown_with_emphasis(&xhtml_to_markdown(xhtml, &ClassStyles::new()));
+
+        assert!(matches!(&blocks[0], ContentBlock::Paragraph(t) if t == "*onetwo* after *three*"));
+    }
+
     #[test]
     fn extract_code_language_from_class() {
         assert_eq!(extract_code_language("pre class=\"language-rust\""), Some("rust".into()));
@@ -0,0 +1,262 @@
+//! Fitting book text into a prompt
+//!
+//! Cutting a long section at a character count loses its end, and with it
+//! whatever the reader was asking about. Instead whole blocks are kept in
+//! order of importance: the block the reader is focused on, every heading, the
+//! blocks either side of the focus and the first and last paragraphs, then as
+//! many of the rest as fit, nearest the focus first. Runs of blocks left out
+//! are marked with `[...]`.
+
+use super::model::{Chapter, ContentBlock, Section};
+
+/// Marks where text was left out
+const GAP: &str = "[...]";
+/// Separates blocks, as in `Section::plain_text`
+const SEPARATOR: &str = "\n\n";
+/// Fewest characters worth keeping of a block that has to be cut short
+const MIN_CUT: usize = 80;
+
+/// Cut `text` to at most `max_chars` characters, at a word boundary where
+/// there is one, marking the cut with `[...]`
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let keep = max_chars.saturating_sub(GAP.len() + 1);
+    let end = text.char_indices().nth(keep).map_or(text.len(), |(i, _)| i);
+    let head = &text[..end];
+    let head = match head.rfind(char::is_whitespace) {
+        Some(space) if space > end / 2 => &head[..space],
+        _ => head,
+    };
+    format!("{} {}", head.trim_end(), GAP)
+}
+
+/// A section's text in at most `max_chars` characters; `focus` is the index
+/// of the content block the reader is looking at
+pub fn section_excerpt(section: &Section, focus: Option<usize>, max_chars: usize) -> String {
+    excerpt(section, focus, max_chars, false)
+}
+
+/// `section_excerpt` with each block marked by its number in the section,
+/// like `[¶3]`, so Claude can cite the blocks it used
+pub fn numbered_section_excerpt(
+    section: &Section,
+    focus: Option<usize>,
+    max_chars: usize,
+) -> String {
+    excerpt(section, focus, max_chars, true)
+}
+
+fn excerpt(section: &Section, focus: Option<usize>, max_chars: usize, numbered: bool) -> String {
+    let blocks: Vec<(&ContentBlock, String)> = section
+        .content
+        .iter()
+        .enumerate()
+        .filter_map(|(i, b)| {
+            let text = b.plain_text()?;
+            Some((b, if numbered { format!("[¶{}] {}", i + 1, text) } else { text }))
+        })
+        .collect();
+    let texts: Vec<&str> = blocks.iter().map(|(_, text)| text.as_str()).collect();
+    let full = texts.join(SEPARATOR);
+    if full.chars().count() <= max_chars {
+        return full;
+    }
+
+    // Position of the focused block among the blocks with text
+    let focus = focus.and_then(|focus| {
+        let before = section.content.iter().take(focus).filter(|b| b.plain_text().is_some());
+        Some(before.count()).filter(|&pos| pos < blocks.len())
+    });
+    let prose: Vec<usize> = (0..blocks.len())
+        .filter(|&pos| !matches!(blocks[pos].0, ContentBlock::Heading { .. }))
+        .collect();
+    let headings = (0..blocks.len()).filter(|pos| !prose.contains(pos));
+    let neighbours =
+        focus.into_iter().flat_map(|pos| [pos.checked_sub(1), Some(pos + 1)]).flatten();
+    let ends = [prose.first().copied(), prose.last().copied()].into_iter().flatten();
+    let essential: Vec<usize> = focus.into_iter().chain(ends.clone()).collect();
+
+    let mut excerpt = Excerpt::new(&texts, max_chars);
+    for pos in focus.into_iter().chain(headings).chain(neighbours).chain(ends) {
+        excerpt.keep(pos);
+    }
+    excerpt.cut_short(&essential);
+    let mut rest: Vec<usize> = (0..blocks.len()).collect();
+    rest.sort_by_key(|&pos| focus.map_or(pos, |focus| pos.abs_diff(focus)));
+    for pos in rest {
+        excerpt.keep(pos);
+    }
+    excerpt.text()
+}
+
+/// A chapter's text with its section headings in at most `max_chars`
+/// characters, sharing the space between sections by their length
+pub fn chapter_excerpt(chapter: &Chapter, max_chars: usize) -> String {
+    let sections: Vec<(String, &Section, usize)> = chapter
+        .sections
+        .iter()
+        .map(|s| (format!("## {}{}", s.title, SEPARATOR), s, s.plain_text().chars().count()))
+        .collect();
+    let framing: usize =
+        sections.iter().map(|(heading, _, _)| heading.chars().count() + SEPARATOR.len()).sum();
+    let total: usize = sections.iter().map(|(_, _, chars)| chars).sum();
+    let budget = max_chars.saturating_sub(framing);
+
+    let mut out = String::new();
+    for (heading, section, chars) in sections {
+        let share = if total <= budget { chars } else { budget * chars / total };
+        out.push_str(&heading);
+        out.push_str(&section_excerpt(section, None, share));
+        out.push_str(SEPARATOR);
+    }
+    out
+}
+
+/// Blocks chosen for an excerpt, within a character budget
+struct Excerpt<'a> {
+    texts: &'a [&'a str],
+    kept: Vec<Option<String>>,
+    /// Characters left, after reserving room for a gap at the end
+    budget: usize,
+}
+
+impl<'a> Excerpt<'a> {
+    fn new(texts: &'a [&'a str], max_chars: usize) -> Self {
+        let budget = max_chars.saturating_sub(GAP.len() + SEPARATOR.len());
+        Self { texts, kept: vec![None; texts.len()], budget }
+    }
+
+    /// Room a block of `chars` characters takes, with its separator and a
+    /// possible gap before it
+    fn cost(chars: usize) -> usize {
+        chars + GAP.len() + 2 * SEPARATOR.len()
+    }
+
+    /// Keep a whole block if it fits
+    fn keep(&mut self, pos: usize) {
+        let cost = Self::cost(self.texts[pos].chars().count());
+        if self.kept[pos].is_none() && cost <= self.budget {
+            self.kept[pos] = Some(self.texts[pos].to_string());
+            self.budget -= cost;
+        }
+    }
+
+    /// Keep the start of each of `positions` that didn't fit whole, sharing
+    /// the room left between them
+    fn cut_short(&mut self, positions: &[usize]) {
+        let mut missing: Vec<usize> =
+            positions.iter().copied().filter(|&pos| self.kept[pos].is_none()).collect();
+        missing.sort_unstable();
+        missing.dedup();
+        for (i, &pos) in missing.iter().enumerate() {
+            let share = self.budget / (missing.len() - i);
+            let chars = share.saturating_sub(Self::cost(0));
+            if chars >= MIN_CUT {
+                let text = truncate(self.texts[pos], chars);
+                self.budget -= Self::cost(text.chars().count());
+                self.kept[pos] = Some(text);
+            }
+        }
+    }
+
+    /// The kept blocks in order, with gaps where blocks were left out
+    fn text(self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        let mut skipped = false;
+        for text in self.kept {
+            match text {
+                Some(text) => {
+                    if skipped {
+                        parts.push(GAP.to_string());
+                    }
+                    parts.push(text);
+                    skipped = false;
+                }
+                None => skipped = true,
+            }
+        }
+        if skipped {
+            parts.push(GAP.to_string());
+        }
+        parts.join(SEPARATOR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(blocks: Vec<ContentBlock>) -> Section {
+        let mut section = Section::new("Traits", 1, "ch01/s01");
+        section.content = blocks.into();
+        section
+    }
+
+    fn paragraph(label: &str) -> ContentBlock {
+        ContentBlock::Paragraph(format!("{} {}", label, "word ".repeat(40).trim_end()))
+    }
+
+    #[test]
+    fn short_sections_are_kept_whole() {
+        let section = section(vec![paragraph("one"), paragraph("two")]);
+        assert_eq!(section_excerpt(&section, None, 10_000), section.plain_text());
+    }
+
+    #[test]
+    fn keeps_headings_focus_and_ends() {
+        let mut blocks = vec![ContentBlock::Heading { level: 2, text: "Overview".into() }];
+        blocks.extend((0..10).map(|i| paragraph(&format!("p{}", i))));
+        blocks.insert(6, ContentBlock::Heading { level: 2, text: "Details".into() });
+        let section = section(blocks);
+
+        let excerpt = section_excerpt(&section, Some(8), 1200);
+
+        assert!(excerpt.chars().count() <= 1200);
+        for kept in ["Overview", "Details", "p0 ", "p9 ", "p5 ", "p6 ", "p7 "] {
+            assert!(excerpt.contains(kept), "{} missing from {}", kept, excerpt);
+        }
+        assert!(!excerpt.contains("p2 "));
+        assert!(excerpt.contains("\n\n[...]\n\n"));
+    }
+
+    #[test]
+    fn numbered_excerpts_mark_blocks_by_position() {
+        let mut blocks = vec![paragraph("one"), ContentBlock::HorizontalRule, paragraph("three")];
+        blocks.push(ContentBlock::Paragraph("four".into()));
+        let section = section(blocks);
+
+        let excerpt = numbered_section_excerpt(&section, None, 10_000);
+
+        assert!(excerpt.starts_with("[¶1] one word"));
+        assert!(excerpt.contains("\n\n[¶3] three word"));
+        assert!(excerpt.ends_with("\n\n[¶4] four"));
+    }
+
+    #[test]
+    fn cuts_a_long_block_at_a_char_boundary() {
+        let section = section(vec![ContentBlock::Paragraph("héllo wörld ".repeat(200))]);
+        let excerpt = section_excerpt(&section, None, 500);
+        assert!(excerpt.chars().count() <= 500);
+        assert!(excerpt.starts_with("héllo wörld"));
+        assert!(excerpt.ends_with("[...]"));
+    }
+
+    #[test]
+    fn chapter_excerpt_keeps_every_section_heading() {
+        let mut chapter = Chapter::new("Chapter 1", 1, "ch01");
+        for title in ["Intro", "Middle", "End"] {
+            let mut section = Section::new(title, 1, "ch01/s");
+            section.content = (0..20).map(|i| paragraph(&format!("{}{}", title, i))).collect();
+            chapter.sections.push(section);
+        }
+
+        let excerpt = chapter_excerpt(&chapter, 3000);
+
+        assert!(excerpt.chars().count() <= 3000);
+        for heading in ["## Intro", "## Middle", "## End", "End19 "] {
+            assert!(excerpt.contains(heading), "{} missing", heading);
+        }
+    }
+}
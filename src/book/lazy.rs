@@ -0,0 +1,332 @@
+//! Section content read on first access
+//!
+//! A cached book keeps its sections' content in a file of its own, one JSON
+//! line per section, so opening even a 1000-page book reads only its outline.
+//! A section's blocks are read the first time they're used, and
+//! `Book::unload_sections` lets go of those used longest ago.
+//!
+//! Parsing the book again replaces its content file under any copy of the
+//! book still reading from it. Each line names its section, so such a copy
+//! reads nothing (with a warning) rather than another section's content;
+//! it should be reloaded from the new cache.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::model::{Book, ContentBlock};
+
+/// Counter ordering section reads, for unloading the least recently used
+static CLOCK: AtomicU64 = AtomicU64::new(1);
+
+/// Where a section's content is in a book's content file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct StoredAt {
+    offset: u64,
+    len: u64,
+}
+
+/// A line of the content file
+#[derive(Serialize, Deserialize)]
+struct StoredLine<S, B> {
+    /// Path of the section the blocks belong to
+    section: S,
+    blocks: B,
+}
+
+/// How section content is cached: inline, or as a place in the content file
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Cached {
+    Blocks(Vec<ContentBlock>),
+    Stored(StoredAt),
+}
+
+/// A section's content blocks, read from the book's content file when first
+/// used
+#[derive(Debug, Default)]
+pub struct SectionContent {
+    blocks: OnceLock<Vec<ContentBlock>>,
+    /// Where the blocks can be read again; None once they've been changed
+    stored: Option<StoredAt>,
+    file: Option<Arc<PathBuf>>,
+    /// Path of the section, to check lines read from the file against
+    section: String,
+    last_used: AtomicU64,
+}
+
+impl SectionContent {
+    /// Whether the blocks are in memory
+    pub fn is_loaded(&self) -> bool {
+        self.blocks.get().is_some()
+    }
+
+    /// When the blocks were last used, if they can be read again once dropped
+    fn unloadable_since(&self) -> Option<u64> {
+        (self.is_loaded() && self.stored.is_some() && self.file.is_some())
+            .then(|| self.last_used.load(Ordering::Relaxed))
+    }
+
+    /// Drop the blocks, to be read again when next used
+    fn unload(&mut self) {
+        if self.unloadable_since().is_some() {
+            self.blocks.take();
+        }
+    }
+
+    fn blocks(&self) -> &Vec<ContentBlock> {
+        self.last_used.store(CLOCK.fetch_add(1, Ordering::Relaxed), Ordering::Relaxed);
+        self.blocks.get_or_init(|| {
+            self.read().unwrap_or_else(|e| {
+                tracing::warn!("Failed to read section content: {:#}", e);
+                Vec::new()
+            })
+        })
+    }
+
+    fn read(&self) -> Result<Vec<ContentBlock>> {
+        let (Some(at), Some(file)) = (self.stored, &self.file) else {
+            return Ok(Vec::new());
+        };
+        let mut reader =
+            File::open(file.as_path()).with_context(|| format!("Failed to open {:?}", file))?;
+        reader.seek(SeekFrom::Start(at.offset))?;
+        let mut bytes = vec![0; at.len as usize];
+        reader.read_exact(&mut bytes)?;
+        let line: StoredLine<String, Vec<ContentBlock>> = serde_json::from_slice(&bytes)
+            .with_context(|| format!("{:?} was replaced or is corrupt", file))?;
+        if line.section != self.section {
+            anyhow::bail!("{:?} was replaced; {} is no longer there", file, self.section);
+        }
+        Ok(line.blocks)
+    }
+}
+
+impl Deref for SectionContent {
+    type Target = Vec<ContentBlock>;
+
+    fn deref(&self) -> &Self::Target {
+        self.blocks()
+    }
+}
+
+impl DerefMut for SectionContent {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.blocks();
+        // Changed blocks no longer match the stored ones
+        self.stored = None;
+        self.blocks.get_mut().expect("blocks were just loaded")
+    }
+}
+
+impl From<Vec<ContentBlock>> for SectionContent {
+    fn from(blocks: Vec<ContentBlock>) -> Self {
+        Self { blocks: OnceLock::from(blocks), ..Self::default() }
+    }
+}
+
+impl FromIterator<ContentBlock> for SectionContent {
+    fn from_iter<I: IntoIterator<Item = ContentBlock>>(blocks: I) -> Self {
+        blocks.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl<'a> IntoIterator for &'a SectionContent {
+    type Item = &'a ContentBlock;
+    type IntoIter = std::slice::Iter<'a, ContentBlock>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Clone for SectionContent {
+    fn clone(&self) -> Self {
+        let blocks = OnceLock::new();
+        if let Some(loaded) = self.blocks.get() {
+            let _ = blocks.set(loaded.clone());
+        }
+        Self {
+            blocks,
+            stored: self.stored,
+            file: self.file.clone(),
+            section: self.section.clone(),
+            last_used: AtomicU64::new(self.last_used.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Serialize for SectionContent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.stored {
+            Some(at) => at.serialize(serializer),
+            None => self.blocks().serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SectionContent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Cached::deserialize(deserializer)? {
+            Cached::Blocks(blocks) => blocks.into(),
+            Cached::Stored(at) => Self { stored: Some(at), ..Self::default() },
+        })
+    }
+}
+
+/// Write every section's content to `path`, pointing the sections at it
+///
+/// The file is replaced only once complete, since sections may still be
+/// reading from the one it replaces.
+pub fn store(book: &mut Book, path: &Path) -> Result<()> {
+    let partial = path.with_extension("partial");
+    let mut out = BufWriter::new(
+        File::create(&partial).with_context(|| format!("Failed to create {:?}", partial))?,
+    );
+    let mut places = Vec::new();
+    let mut offset = 0;
+    for section in book.chapters.iter().flat_map(|c| &c.sections) {
+        let line = StoredLine { section: &section.path, blocks: section.content.blocks() };
+        let line = serde_json::to_vec(&line)?;
+        out.write_all(&line)?;
+        out.write_all(b"\n")?;
+        places.push(StoredAt { offset, len: line.len() as u64 });
+        offset += line.len() as u64 + 1;
+    }
+    out.flush()?;
+    drop(out);
+    fs::rename(&partial, path).with_context(|| format!("Failed to write {:?}", path))?;
+
+    let file = Arc::new(path.to_path_buf());
+    let sections = book.chapters.iter_mut().flat_map(|c| &mut c.sections);
+    for (section, at) in sections.zip(places) {
+        section.content.stored = Some(at);
+        section.content.file = Some(file.clone());
+        section.content.section = section.path.clone();
+    }
+    Ok(())
+}
+
+/// Point a cached book's sections at its content file
+pub fn attach(book: &mut Book, path: &Path) {
+    let file = Arc::new(path.to_path_buf());
+    for section in book.chapters.iter_mut().flat_map(|c| &mut c.sections) {
+        section.content.file = Some(file.clone());
+        section.content.section = section.path.clone();
+    }
+}
+
+/// Read every section's content into memory for good, as before its content
+/// file is replaced
+pub fn detach(book: &mut Book) {
+    for section in book.chapters.iter_mut().flat_map(|c| &mut c.sections) {
+        section.content.blocks();
+        section.content.stored = None;
+        section.content.file = None;
+    }
+}
+
+/// Drop the content of all but the `keep` sections used most recently;
+/// returns how many were dropped
+pub fn unload_stale(book: &mut Book, keep: usize) -> usize {
+    let mut loaded: Vec<(u64, usize, usize)> = Vec::new();
+    for (c, chapter) in book.chapters.iter().enumerate() {
+        for (s, section) in chapter.sections.iter().enumerate() {
+            if let Some(used) = section.content.unloadable_since() {
+                loaded.push((used, c, s));
+            }
+        }
+    }
+    if loaded.len() <= keep {
+        return 0;
+    }
+    loaded.sort_unstable_by_key(|&(used, ..)| std::cmp::Reverse(used));
+    let stale = loaded.split_off(keep);
+    for &(_, c, s) in &stale {
+        book.chapters[c].sections[s].content.unload();
+    }
+    stale.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::{BookMetadata, Chapter, Section};
+
+    fn book(sections: usize) -> Book {
+        let mut book = Book::new(BookMetadata::for_test("big", "Big"));
+        let mut chapter = Chapter::new("One", 1, "ch01");
+        for i in 0..sections {
+            let mut section = Section::new(format!("S{}", i), i + 1, format!("ch01/s{:02}", i));
+            section.content = vec![ContentBlock::Paragraph(format!("Section {} text", i))].into();
+            chapter.sections.push(section);
+        }
+        book.chapters.push(chapter);
+        book
+    }
+
+    #[test]
+    fn stored_sections_are_read_back_on_first_use() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sections.jsonl");
+        let mut original = book(3);
+        store(&mut original, &path).unwrap();
+
+        let json = serde_json::to_string(&original).unwrap();
+        assert!(!json.contains("Section 1 text"));
+        let mut cached: Book = serde_json::from_str(&json).unwrap();
+        attach(&mut cached, &path);
+
+        let section = &cached.chapters[0].sections[1];
+        assert!(!section.content.is_loaded());
+        assert_eq!(section.plain_text(), "Section 1 text");
+        assert!(section.content.is_loaded());
+        assert!(!cached.chapters[0].sections[0].content.is_loaded());
+    }
+
+    #[test]
+    fn sections_of_a_replaced_file_read_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sections.jsonl");
+        let mut old = book(3);
+        store(&mut old, &path).unwrap();
+        let mut cached: Book = serde_json::from_str(&serde_json::to_string(&old).unwrap()).unwrap();
+        attach(&mut cached, &path);
+
+        // Parsed again with the first section gone, moving the others up
+        let mut new = book(3);
+        new.chapters[0].sections.remove(0);
+        store(&mut new, &path).unwrap();
+
+        assert!(cached.chapters[0].sections[0].content.is_empty());
+        assert_eq!(new.chapters[0].sections[0].plain_text(), "Section 1 text");
+    }
+
+    #[test]
+    fn unload_stale_keeps_the_most_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sections.jsonl");
+        let mut book = book(4);
+        store(&mut book, &path).unwrap();
+        for i in [2, 0, 3] {
+            let _ = book.chapters[0].sections[i].content.len();
+        }
+
+        assert_eq!(unload_stale(&mut book, 2), 2);
+        let loaded: Vec<bool> =
+            book.chapters[0].sections.iter().map(|s| s.content.is_loaded()).collect();
+        assert_eq!(loaded, [true, false, false, true]);
+        assert_eq!(book.chapters[0].sections[2].plain_text(), "Section 2 text");
+
+        // Changed content stays until it's stored again
+        book.chapters[0].sections[1].content.push(ContentBlock::HorizontalRule);
+        assert_eq!(unload_stale(&mut book, 0), 3);
+        assert!(book.chapters[0].sections[1].content.is_loaded());
+    }
+}
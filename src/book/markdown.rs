@@ -12,6 +12,7 @@ use regex::Regex;
 
 use super::model::{
     Alignment, Book, BookMetadata, BookSource, Chapter, CodeBlock, ContentBlock, Section, Table,
+    footnote_marker,
 };
 
 /// Regex for matching mdBook include directives (compiled once)
@@ -19,15 +20,41 @@ static INCLUDE_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"\{\{#(include|rustdoc_include)\s+([^}]+)\}\}").unwrap());
 
 /// Parse a markdown string into content blocks
+pub fn parse_markdown_content(markdown: &str) -> Vec<ContentBlock> {
+    parse_markdown(markdown, false)
+}
+
+/// Parse a markdown string into content blocks, keeping emphasis and strong
+/// text as the `*` and `**` markers the renderer styles
+pub fn parse_markdown_with_emphasis(markdown: &str) -> Vec<ContentBlock> {
+    parse_markdown(markdown, true)
+}
+
 // skipcq: RS-R1000 - Parser functions inherently have high cyclomatic complexity
 #[allow(clippy::cognitive_complexity)]
-pub fn parse_markdown_content(markdown: &str) -> Vec<ContentBlock> {
+fn parse_markdown(markdown: &str, keep_emphasis: bool) -> Vec<ContentBlock> {
     let options = Options::ENABLE_TABLES
         | Options::ENABLE_STRIKETHROUGH
         | Options::ENABLE_TASKLISTS
-        | Options::ENABLE_HEADING_ATTRIBUTES;
-
-    let parser = Parser::new_ext(markdown, options);
+        | Options::ENABLE_HEADING_ATTRIBUTES
+        | Options::ENABLE_FOOTNOTES;
+
+    // Emphasis markers become text, except in headings and tables, which are
+    // drawn without inline formatting
+    let mut plain = false;
+    let parser = Parser::new_ext(markdown, options).map(|event| {
+        match &event {
+            Event::Start(Tag::Heading { .. } | Tag::Table(_)) => plain = true,
+            Event::End(TagEnd::Heading(_) | TagEnd::Table) => plain = false,
+            _ => {}
+        }
+        let marker = match &event {
+            Event::Start(Tag::Emphasis) | Event::End(TagEnd::Emphasis) => "*",
+            Event::Start(Tag::Strong) | Event::End(TagEnd::Strong) => "**",
+            _ => return event,
+        };
+        if keep_emphasis && !plain { Event::Text(marker.into()) } else { event }
+    });
     let mut blocks = Vec::new();
 
     let mut current_text = String::default();
@@ -55,6 +82,11 @@ pub fn parse_markdown_content(markdown: &str) -> Vec<ContentBlock> {
     let mut in_caption = false; // Track when inside <span class="caption">
     let mut caption_content = String::new(); // Accumulate caption text
 
+    // Footnote labels in order of first reference, and collected definitions
+    let mut footnote_order: Vec<String> = Vec::new();
+    let mut footnote_defs: Vec<(String, String)> = Vec::new();
+    let mut footnote_start: Option<(String, usize)> = None;
+
     for event in parser {
         match event {
             Event::Start(Tag::Heading { level, .. }) => {
@@ -291,6 +323,44 @@ pub fn parse_markdown_content(markdown: &str) -> Vec<ContentBlock> {
                 }
             }
 
+            Event::FootnoteReference(label) => {
+                // Number footnotes by order of first reference
+                let number = match footnote_order.iter().position(|l| l == label.as_ref()) {
+                    Some(index) => index + 1,
+                    None => {
+                        footnote_order.push(label.to_string());
+                        footnote_order.len()
+                    }
+                };
+                let marker = footnote_marker(&number.to_string());
+                if in_table {
+                    current_cell.push_str(&marker);
+                } else if in_list {
+                    current_list_item.push_str(&marker);
+                } else if in_blockquote {
+                    blockquote_content.push_str(&marker);
+                } else {
+                    current_text.push_str(&marker);
+                }
+            }
+
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                flush_text(&mut current_text, &mut blocks);
+                footnote_start = Some((label.to_string(), blocks.len()));
+            }
+            Event::End(TagEnd::FootnoteDefinition) => {
+                flush_text(&mut current_text, &mut blocks);
+                if let Some((label, start)) = footnote_start.take() {
+                    // Collapse the definition's blocks into a single line of text
+                    let text = blocks
+                        .drain(start.min(blocks.len())..)
+                        .filter_map(|block| block.plain_text())
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    footnote_defs.push((label, text));
+                }
+            }
+
             Event::SoftBreak | Event::HardBreak => {
                 if in_code_block {
                     code_content.push('\n');
@@ -400,6 +470,21 @@ pub fn parse_markdown_content(markdown: &str) -> Vec<ContentBlock> {
     // Flush any remaining text
     flush_text(&mut current_text, &mut blocks);
 
+    // Footnotes go at the end of the section, in reference order
+    for (label, _) in &footnote_defs {
+        if !footnote_order.contains(label) {
+            footnote_order.push(label.clone());
+        }
+    }
+    for (index, label) in footnote_order.iter().enumerate() {
+        if let Some((_, text)) = footnote_defs.iter().find(|(l, _)| l == label) {
+            blocks.push(ContentBlock::Footnote {
+                label: (index + 1).to_string(),
+                text: text.clone(),
+            });
+        }
+    }
+
     blocks
 }
 
@@ -428,7 +513,7 @@ fn parse_html_img_tag(html: &str) -> Option<ContentBlock> {
 }
 
 /// Extract an attribute value from an HTML tag
-fn extract_html_attribute(html: &str, attr_name: &str) -> Option<String> {
+pub(super) fn extract_html_attribute(html: &str, attr_name: &str) -> Option<String> {
     // Try double quotes: attr="value"
     let pattern_double = format!("{}=\"", attr_name);
     if let Some(start_idx) = html.find(&pattern_double) {
@@ -494,7 +579,7 @@ pub fn parse_markdown_file(path: &Path, section_number: usize) -> Result<Section
         .map_or_else(|| format!("section{}", section_number), |s| s.to_string_lossy().to_string());
 
     let mut section = Section::new(title, section_number, section_path);
-    section.content = blocks;
+    section.content = blocks.into();
     section.calculate_reading_time();
 
     Ok(section)
@@ -739,9 +824,7 @@ pub fn parse_markdown_directory(path: &Path) -> Result<Book> {
         language: Some("en".to_string()),
         description: None,
         cover_image: None,
-        added_at: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_secs() as i64),
+        added_at: crate::config::datetime::now(),
         last_accessed: None,
     };
 
@@ -838,9 +921,7 @@ fn parse_mdbook_directory(path: &Path, summary_path: &Path) -> Result<Book> {
         language: Some("en".to_string()),
         description: None,
         cover_image: None,
-        added_at: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_secs() as i64),
+        added_at: crate::config::datetime::now(),
         last_accessed: None,
     };
 
@@ -961,10 +1042,143 @@ fn extract_title_from_file(path: &Path) -> Option<String> {
     })
 }
 
+/// Build a book from a single markdown document
+///
+/// The document's top heading level becomes chapters and the next level
+/// becomes sections. Text before a chapter's first section becomes a section
+/// of its own, named after the chapter. Used by importers (DOCX, AsciiDoc)
+/// that convert a whole document to markdown.
+pub(super) fn book_from_markdown_document(metadata: BookMetadata, markdown: &str) -> Book {
+    let lines: Vec<(Option<(usize, String)>, &str)> = {
+        let mut in_fence = false;
+        markdown
+            .lines()
+            .map(|line| {
+                if line.trim_start().starts_with("```") {
+                    in_fence = !in_fence;
+                }
+                let heading = if in_fence { None } else { markdown_heading(line) };
+                (heading, line)
+            })
+            .collect()
+    };
+
+    let mut levels: Vec<usize> =
+        lines.iter().filter_map(|(h, _)| h.as_ref().map(|h| h.0)).collect();
+    levels.sort_unstable();
+    levels.dedup();
+    let chapter_level = levels.first().copied();
+    let section_level = levels.get(1).copied();
+
+    // (chapter title, [(section title, body)])
+    let mut chapters: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    for (heading, line) in lines {
+        match heading {
+            Some((level, title)) if Some(level) == chapter_level => {
+                chapters.push((title.clone(), vec![(title, String::new())]));
+            }
+            Some((level, title)) if Some(level) == section_level => {
+                if chapters.is_empty() {
+                    chapters.push((metadata.title.clone(), Vec::new()));
+                }
+                if let Some((_, sections)) = chapters.last_mut() {
+                    sections.push((title, String::new()));
+                }
+            }
+            _ => {
+                if chapters.is_empty() {
+                    chapters.push((metadata.title.clone(), Vec::new()));
+                }
+                if let Some((title, sections)) = chapters.last_mut() {
+                    if sections.is_empty() {
+                        sections.push((title.clone(), String::new()));
+                    }
+                    if let Some((_, body)) = sections.last_mut() {
+                        body.push_str(line);
+                        body.push('\n');
+                    }
+                }
+            }
+        }
+    }
+
+    let mut book = Book::new(metadata);
+    for (title, sections) in chapters {
+        let number = book.chapters.len() + 1;
+        let mut chapter = Chapter::new(title, number, format!("ch{:02}", number));
+
+        for (index, (title, body)) in sections.into_iter().enumerate() {
+            // Skip an empty intro before the first section
+            if index == 0 && body.trim().is_empty() {
+                continue;
+            }
+            let section_number = chapter.sections.len() + 1;
+            let mut section = Section::new(
+                title,
+                section_number,
+                format!("ch{:02}/s{:02}", number, section_number),
+            );
+            section.content = parse_markdown_content(&body).into();
+            section.calculate_reading_time();
+            chapter.sections.push(section);
+        }
+
+        if !chapter.sections.is_empty() {
+            book.chapters.push(chapter);
+        }
+    }
+
+    if book.chapters.is_empty() {
+        book.chapters.push(Chapter::new("Content", 1, "content"));
+    }
+    book
+}
+
+/// Level and text of an ATX heading line (`## Title`)
+fn markdown_heading(line: &str) -> Option<(usize, String)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')) {
+        Some((level, rest.trim().trim_end_matches('#').trim().to_string()))
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn document_metadata() -> BookMetadata {
+        BookMetadata::for_test("doc", "Doc")
+    }
+
+    #[test]
+    fn document_headings_become_chapters_and_sections() {
+        let md = "# One\n\nIntro.\n\n## A\n\nText.\n\n```\n# not a heading\n```\n\n\
+                  ### Detail\n\n# Two\n\n## B\n\nMore.\n";
+        let book = book_from_markdown_document(document_metadata(), md);
+
+        assert_eq!(book.chapters.len(), 2);
+        let one = &book.chapters[0];
+        assert_eq!(one.title, "One");
+        let titles: Vec<_> = one.sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["One", "A"]);
+        assert_eq!(one.sections[1].path, "ch01/s02");
+        assert!(one.sections[1].content.iter().any(|b| matches!(b, ContentBlock::Code(_))));
+        // The empty intro of chapter two is dropped
+        assert_eq!(book.chapters[1].sections.len(), 1);
+        assert_eq!(book.chapters[1].sections[0].title, "B");
+    }
+
+    #[test]
+    fn document_without_headings_is_one_section() {
+        let book = book_from_markdown_document(document_metadata(), "Just text.");
+        assert_eq!(book.chapters.len(), 1);
+        assert_eq!(book.chapters[0].title, "Doc");
+        assert_eq!(book.chapters[0].sections[0].title, "Doc");
+    }
+
     #[test]
     fn parse_heading() {
         let blocks = parse_markdown_content("# Hello World");
@@ -1096,6 +1310,24 @@ fn main() {}
         assert!(matches!(&blocks[3], ContentBlock::UnorderedList(_)));
     }
 
+    #[test]
+    fn parse_footnotes() {
+        let md =
+            "Borrowing[^b] and moving[^m].\n\n[^m]: Ownership moves.\n\n[^b]: See *chapter 4*.";
+        let blocks = parse_markdown_content(md);
+
+        assert!(matches!(&blocks[0], ContentBlock::Paragraph(t) if t == "Borrowing¹ and moving²."));
+        assert!(matches!(
+            &blocks[1],
+            ContentBlock::Footnote { label, text } if label == "1" && text == "See chapter 4."
+        ));
+        assert!(matches!(
+            &blocks[2],
+            ContentBlock::Footnote { label, text } if label == "2" && text == "Ownership moves."
+        ));
+        assert_eq!(blocks.len(), 3);
+    }
+
     #[test]
     fn parse_markdown_link_valid() {
         let result = parse_markdown_link("[Title](./path.md)");
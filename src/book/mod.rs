@@ -1,14 +1,25 @@
 //! Book handling and parsing
 //!
 //! This module provides functionality for parsing and managing technical books
-//! from EPUB and Markdown sources.
+//! from EPUB, Markdown, DOCX and AsciiDoc sources.
 
+pub mod asciidoc;
+pub mod compare;
+pub mod docx;
 pub mod epub;
+pub mod excerpt;
+pub mod lazy;
 pub mod markdown;
 pub mod model;
+pub mod search;
 pub mod storage;
+pub mod transform;
 
 pub use model::{
     Alignment, Book, BookMetadata, BookSource, Chapter, CodeBlock, ContentBlock, Section, Table,
 };
-pub use storage::{Library, LibraryEntry, add_book, load_book, remove_book};
+pub use search::SearchIndex;
+pub use storage::{
+    Added, BookMatch, BookUpdate, Library, LibraryEntry, add_book, load_book, remove_book,
+    sync_shared_library, update_book,
+};
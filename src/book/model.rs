@@ -1,12 +1,15 @@
 //! Content model for books
 //!
 //! This module defines the core data structures for representing technical books.
-//! The model supports both EPUB and Markdown sources with a unified representation.
+//! The model supports EPUB, Markdown, DOCX and AsciiDoc sources with a unified
+//! representation.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use super::lazy::{self, SectionContent};
+
 /// Source type for a book
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BookSource {
@@ -14,6 +17,19 @@ pub enum BookSource {
     Epub(PathBuf),
     /// Directory of Markdown files
     Markdown(PathBuf),
+    /// Word document
+    Docx(PathBuf),
+    /// AsciiDoc file
+    AsciiDoc(PathBuf),
+}
+
+impl BookSource {
+    /// The file or directory the book is read from
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Epub(p) | Self::Markdown(p) | Self::Docx(p) | Self::AsciiDoc(p) => p,
+        }
+    }
 }
 
 /// Metadata about a book
@@ -39,6 +55,24 @@ pub struct BookMetadata {
     pub last_accessed: Option<i64>,
 }
 
+#[cfg(test)]
+impl BookMetadata {
+    /// Metadata for a markdown book with just an ID and title
+    pub fn for_test(id: &str, title: &str) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            author: None,
+            source: BookSource::Markdown(PathBuf::from("/test")),
+            language: None,
+            description: None,
+            cover_image: None,
+            added_at: 0,
+            last_accessed: None,
+        }
+    }
+}
+
 /// A complete parsed book
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Book {
@@ -59,11 +93,51 @@ impl Book {
         self.chapters.iter().map(|c| c.sections.len()).sum()
     }
 
+    /// Estimated minutes to read the sections matching `include`
+    pub fn reading_minutes(&self, include: impl Fn(&Section) -> bool) -> u32 {
+        self.chapters
+            .iter()
+            .flat_map(|c| &c.sections)
+            .filter(|s| include(s))
+            .filter_map(|s| s.reading_time_minutes)
+            .sum()
+    }
+
+    /// Drop the content of all but the `keep` sections read most recently,
+    /// to be read again from the cache when next used
+    pub fn unload_sections(&mut self, keep: usize) -> usize {
+        lazy::unload_stale(self, keep)
+    }
+
     /// Get a section by chapter and section index
     pub fn get_section(&self, chapter_idx: usize, section_idx: usize) -> Option<&Section> {
         self.chapters.get(chapter_idx).and_then(|c| c.sections.get(section_idx))
     }
 
+    /// Chapter and section index of the section after the given one, moving
+    /// on to the next chapter at the end of one
+    pub fn next_section(&self, chapter_idx: usize, section_idx: usize) -> Option<(usize, usize)> {
+        let chapter = self.chapters.get(chapter_idx)?;
+        if section_idx + 1 < chapter.sections.len() {
+            return Some((chapter_idx, section_idx + 1));
+        }
+        (chapter_idx + 1..self.chapters.len())
+            .find(|&c| !self.chapters[c].sections.is_empty())
+            .map(|c| (c, 0))
+    }
+
+    /// Chapter and section index of the first section whose path contains
+    /// `query` or whose title contains it, ignoring case
+    pub fn find_section(&self, query: &str) -> Option<(usize, usize)> {
+        let query_lower = query.to_lowercase();
+        self.chapters.iter().enumerate().find_map(|(chapter_idx, chapter)| {
+            let section_idx = chapter.sections.iter().position(|section| {
+                section.path.contains(query) || section.title.to_lowercase().contains(&query_lower)
+            })?;
+            Some((chapter_idx, section_idx))
+        })
+    }
+
     /// Find a section by its path (e.g., "ch01/section02")
     pub fn find_section_by_path(&self, path: &str) -> Option<&Section> {
         for chapter in &self.chapters {
@@ -77,6 +151,21 @@ impl Book {
     }
 }
 
+#[cfg(test)]
+impl Book {
+    /// A book with one chapter, "Ownership", whose one section "Moves"
+    /// (`ch01/s01`) holds `content`
+    pub fn for_test(content: Vec<ContentBlock>) -> Self {
+        let mut book = Book::new(BookMetadata::for_test("test", "Test Book"));
+        let mut chapter = Chapter::new("Ownership", 1, "ch01");
+        let mut section = Section::new("Moves", 1, "ch01/s01");
+        section.content = content.into();
+        chapter.sections.push(section);
+        book.chapters.push(chapter);
+        book
+    }
+}
+
 /// A chapter in a book
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chapter {
@@ -111,8 +200,11 @@ pub struct Section {
     pub number: usize,
     /// Full path identifier (e.g., "ch01/section02")
     pub path: String,
-    /// Content blocks
-    pub content: Vec<ContentBlock>,
+    /// Content blocks, read from the book's cache when first used
+    pub content: SectionContent,
+    /// Words in the content, counted at parse time
+    #[serde(default)]
+    pub word_count: usize,
     /// Estimated reading time in minutes
     pub reading_time_minutes: Option<u32>,
 }
@@ -124,16 +216,17 @@ impl Section {
             title: title.into(),
             number,
             path: path.into(),
-            content: Vec::new(),
+            content: SectionContent::default(),
+            word_count: 0,
             reading_time_minutes: None,
         }
     }
 
-    /// Calculate estimated reading time based on word count
+    /// Count words and calculate estimated reading time
     pub fn calculate_reading_time(&mut self) {
-        let word_count: usize = self.content.iter().map(|block| block.word_count()).sum();
+        self.word_count = self.content.iter().map(|block| block.word_count()).sum();
         // Average reading speed: 200 words per minute for technical content
-        self.reading_time_minutes = Some((word_count / 200).max(1) as u32);
+        self.reading_time_minutes = Some((self.word_count / 200).max(1) as u32);
     }
 
     /// Get plain text content for search/quiz generation
@@ -163,6 +256,8 @@ pub enum ContentBlock {
     HorizontalRule,
     /// A table
     Table(Table),
+    /// A footnote or endnote, referenced from the text by its marker
+    Footnote { label: String, text: String },
     // Note: Inline code within text is handled in Paragraph with markdown.
     // This enum is for standalone code blocks only.
 }
@@ -181,6 +276,7 @@ impl ContentBlock {
             ContentBlock::Image { .. } => 0,
             ContentBlock::HorizontalRule => 0,
             ContentBlock::Table(table) => table.word_count(),
+            ContentBlock::Footnote { text, .. } => text.split_whitespace().count(),
         }
     }
 
@@ -203,10 +299,34 @@ impl ContentBlock {
             }
             ContentBlock::HorizontalRule => None,
             ContentBlock::Table(table) => Some(table.plain_text()),
+            ContentBlock::Footnote { label, text } => {
+                Some(format!("{} {}", footnote_marker(label), text))
+            }
         }
     }
 }
 
+/// Marker shown in the text where a footnote is referenced
+///
+/// Numeric labels become superscript digits; anything else is bracketed.
+pub fn footnote_marker(label: &str) -> String {
+    const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    if !label.is_empty() && label.chars().all(|c| c.is_ascii_digit()) {
+        label.chars().map(|c| SUPERSCRIPTS[(c as u8 - b'0') as usize]).collect()
+    } else {
+        format!("[{}]", label)
+    }
+}
+
+/// Compact reading time, e.g. "5m" or "1h 20m"
+pub fn format_reading_time(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
 /// A code block with language annotation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeBlock {
@@ -237,6 +357,12 @@ impl CodeBlock {
         self.filename = Some(filename.into());
         self
     }
+
+    /// Language name without attributes, e.g. "rust" for "rust,ignore"
+    pub fn language_name(&self) -> Option<&str> {
+        let language = self.language.as_deref()?.split(',').next()?.split_whitespace().next()?;
+        (!language.is_empty()).then_some(language)
+    }
 }
 
 /// A table structure
@@ -287,19 +413,39 @@ pub enum Alignment {
 mod tests {
     use super::*;
 
+    #[test]
+    fn reading_time_counts_words_and_sums_sections() {
+        let mut book = Book::new(BookMetadata::for_test("test", "Test Book"));
+        let mut chapter = Chapter::new("Chapter 1", 1, "ch01");
+        for (i, words) in [450, 10].into_iter().enumerate() {
+            let mut section = Section::new("Section", i + 1, format!("ch01/s0{}", i + 1));
+            section.content.push(ContentBlock::Paragraph("word ".repeat(words)));
+            section.calculate_reading_time();
+            chapter.sections.push(section);
+        }
+        book.chapters.push(chapter);
+
+        assert_eq!(book.chapters[0].sections[0].word_count, 450);
+        assert_eq!(book.chapters[0].sections[1].reading_time_minutes, Some(1));
+        assert_eq!(book.reading_minutes(|_| true), 3);
+        assert_eq!(book.reading_minutes(|s| s.path == "ch01/s02"), 1);
+        assert_eq!(format_reading_time(3), "3m");
+        assert_eq!(format_reading_time(120), "2h");
+        assert_eq!(format_reading_time(95), "1h 35m");
+    }
+
+    #[test]
+    fn code_language_name_drops_attributes() {
+        let code = |language: &str| CodeBlock::new("").with_language(language);
+        assert_eq!(code("rust,ignore").language_name(), Some("rust"));
+        assert_eq!(code("python title=x").language_name(), Some("python"));
+        assert_eq!(code("").language_name(), None);
+        assert_eq!(CodeBlock::new("").language_name(), None);
+    }
+
     #[test]
     fn book_section_count() {
-        let mut book = Book::new(BookMetadata {
-            id: "test".into(),
-            title: "Test Book".into(),
-            author: None,
-            source: BookSource::Markdown(PathBuf::from("/test")),
-            language: None,
-            description: None,
-            cover_image: None,
-            added_at: 0,
-            last_accessed: None,
-        });
+        let mut book = Book::new(BookMetadata::for_test("test", "Test Book"));
 
         let mut ch1 = Chapter::new("Chapter 1", 1, "ch01");
         ch1.sections.push(Section::new("Section 1", 1, "ch01/s01"));
@@ -315,17 +461,7 @@ mod tests {
 
     #[test]
     fn find_section_by_path() {
-        let mut book = Book::new(BookMetadata {
-            id: "test".into(),
-            title: "Test Book".into(),
-            author: None,
-            source: BookSource::Markdown(PathBuf::from("/test")),
-            language: None,
-            description: None,
-            cover_image: None,
-            added_at: 0,
-            last_accessed: None,
-        });
+        let mut book = Book::new(BookMetadata::for_test("test", "Test Book"));
 
         let mut ch1 = Chapter::new("Chapter 1", 1, "ch01");
         ch1.sections.push(Section::new("Target Section", 1, "ch01/target"));
@@ -366,6 +502,12 @@ mod tests {
         assert!(text.contains("World"));
     }
 
+    #[test]
+    fn footnote_marker_superscripts_numbers() {
+        assert_eq!(footnote_marker("12"), "¹²");
+        assert_eq!(footnote_marker("a"), "[a]");
+    }
+
     #[test]
     fn table_word_count() {
         let mut table = Table::new(vec!["Name".into(), "Value".into()]);
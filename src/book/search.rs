@@ -0,0 +1,294 @@
+//! Full-text search index
+//!
+//! An inverted index from words to the blocks containing them, built when a
+//! book is added or refreshed and stored next to its parsed cache, so searching
+//! a book or the whole library doesn't scan every section. Refreshing a book
+//! only re-indexes the sections whose text changed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::model::{Book, Section};
+use super::storage::book_cache_dir;
+use crate::config::persist;
+
+/// A block within an indexed section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BlockRef {
+    /// Index into `SearchIndex::sections`
+    pub section: usize,
+    /// Block index within the section's content
+    pub block: usize,
+}
+
+/// A section covered by the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedSection {
+    /// Section path (e.g. "ch01/section02")
+    pub path: String,
+    /// Section title
+    pub title: String,
+    /// Hash of the section's text, to spot changes on refresh
+    pub fingerprint: u64,
+}
+
+/// Inverted index of a book's words
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    /// Indexed sections, in reading order
+    pub sections: Vec<IndexedSection>,
+    /// Lowercase word -> blocks containing it, sorted
+    pub words: BTreeMap<String, Vec<BlockRef>>,
+}
+
+impl SearchIndex {
+    /// Build an index of a book
+    pub fn build(book: &Book) -> Self {
+        let mut index = Self::default();
+        index.update(book);
+        index
+    }
+
+    /// Bring the index in line with a (re-parsed) book
+    ///
+    /// Sections whose path and text are unchanged keep their entries; the rest
+    /// are re-indexed. Returns the number of sections re-indexed.
+    pub fn update(&mut self, book: &Book) -> usize {
+        let old_positions: HashMap<(&str, u64), usize> = self
+            .sections
+            .iter()
+            .enumerate()
+            .map(|(i, s)| ((s.path.as_str(), s.fingerprint), i))
+            .collect();
+
+        let mut sections = Vec::new();
+        let mut moved: HashMap<usize, usize> = HashMap::new();
+        let mut changed: Vec<(usize, &Section)> = Vec::new();
+        for section in book.chapters.iter().flat_map(|c| &c.sections) {
+            let new_index = sections.len();
+            let fingerprint = fingerprint(section);
+            match old_positions.get(&(section.path.as_str(), fingerprint)) {
+                Some(&old_index) if !moved.contains_key(&old_index) => {
+                    moved.insert(old_index, new_index);
+                }
+                _ => changed.push((new_index, section)),
+            }
+            sections.push(IndexedSection {
+                path: section.path.clone(),
+                title: section.title.clone(),
+                fingerprint,
+            });
+        }
+
+        // Keep entries for unchanged sections, renumbered to their new positions
+        let mut words = std::mem::take(&mut self.words);
+        for refs in words.values_mut() {
+            refs.retain_mut(|r| match moved.get(&r.section) {
+                Some(&section) => {
+                    r.section = section;
+                    true
+                }
+                None => false,
+            });
+        }
+        words.retain(|_, refs| !refs.is_empty());
+
+        for &(section_index, section) in &changed {
+            for (block_index, block) in section.content.iter().enumerate() {
+                let Some(text) = block.plain_text() else { continue };
+                let block_ref = BlockRef { section: section_index, block: block_index };
+                for word in tokenize(&text) {
+                    let refs = words.entry(word).or_default();
+                    if refs.last() != Some(&block_ref) {
+                        refs.push(block_ref);
+                    }
+                }
+            }
+        }
+        for refs in words.values_mut() {
+            refs.sort_unstable();
+            refs.dedup();
+        }
+
+        self.sections = sections;
+        self.words = words;
+        changed.len()
+    }
+
+    /// Blocks containing every word of a query, in reading order
+    ///
+    /// Query words match any indexed word they are a prefix of, so partial
+    /// words find their completions.
+    pub fn search(&self, query: &str) -> Vec<BlockRef> {
+        let mut result: Option<Vec<BlockRef>> = None;
+        for term in tokenize(query) {
+            let mut matches: Vec<BlockRef> = self
+                .words
+                .range(term.clone()..)
+                .take_while(|(word, _)| word.starts_with(&term))
+                .flat_map(|(_, refs)| refs.iter().copied())
+                .collect();
+            matches.sort_unstable();
+            matches.dedup();
+
+            result = Some(match result {
+                Some(previous) => {
+                    previous.into_iter().filter(|r| matches.binary_search(r).is_ok()).collect()
+                }
+                None => matches,
+            });
+        }
+        result.unwrap_or_default()
+    }
+
+    /// Load a book's index from disk, if it has been built
+    pub fn load(book_id: &str) -> Result<Option<Self>> {
+        let path = index_path(book_id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read search index from {:?}", path))?;
+        serde_json::from_str(&contents).map(Some).with_context(|| "Failed to parse search.json")
+    }
+
+    /// Save a book's index to disk
+    pub fn save(&self, book_id: &str) -> Result<()> {
+        let path = index_path(book_id)?;
+        let contents =
+            serde_json::to_string(self).with_context(|| "Failed to serialize search index")?;
+        persist::write_atomic(&path, contents)
+            .with_context(|| format!("Failed to write search index to {:?}", path))
+    }
+
+    /// Load a book's index, building and saving it if it's missing
+    pub fn load_or_build(book: &Book) -> Result<Self> {
+        if let Some(index) = Self::load(&book.metadata.id)? {
+            return Ok(index);
+        }
+        let index = Self::build(book);
+        index.save(&book.metadata.id)?;
+        Ok(index)
+    }
+}
+
+/// Update a book's saved index after it has been (re-)parsed
+pub fn update_index(book_id: &str, book: &Book) -> Result<()> {
+    // A missing or unreadable index is simply rebuilt
+    let mut index = SearchIndex::load(book_id).ok().flatten().unwrap_or_default();
+    index.update(book);
+    index.save(book_id)
+}
+
+/// Lowercase words of a text, splitting on anything but letters, digits and `_`
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Short excerpt of a block's text around the first query word it contains
+pub fn snippet(text: &str, query: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let lower = text.to_lowercase();
+    let start = tokenize(query)
+        .filter_map(|term| lower.find(&term))
+        .min()
+        .map(|byte| lower[..byte].chars().count())
+        .unwrap_or(0);
+
+    // Show some context before the match
+    let start = start.saturating_sub(max_chars / 4);
+    let excerpt: String = text.chars().skip(start).take(max_chars).collect();
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if start + max_chars < text.chars().count() { "…" } else { "" };
+    format!("{}{}{}", prefix, excerpt, suffix)
+}
+
+/// Path of a book's search index
+fn index_path(book_id: &str) -> Result<PathBuf> {
+    Ok(book_cache_dir(book_id)?.join("search.json"))
+}
+
+/// Stable hash of a section's title and text (FNV-1a)
+fn fingerprint(section: &Section) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let text = section.plain_text();
+    for byte in section.title.bytes().chain([0]).chain(text.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::{BookMetadata, Chapter, ContentBlock};
+
+    fn book(sections: &[(&str, &[&str])]) -> Book {
+        let mut chapter = Chapter::new("Chapter", 1, "ch01");
+        for (i, (path, paragraphs)) in sections.iter().enumerate() {
+            let mut section = Section::new(*path, i + 1, *path);
+            section.content =
+                paragraphs.iter().map(|p| ContentBlock::Paragraph(p.to_string())).collect();
+            chapter.sections.push(section);
+        }
+        Book { metadata: BookMetadata::for_test("test", "Test"), chapters: vec![chapter] }
+    }
+
+    #[test]
+    fn search_matches_all_words_by_prefix() {
+        let index = SearchIndex::build(&book(&[
+            ("ch01/a", &["Ownership and borrowing", "The borrow checker"]),
+            ("ch01/b", &["Borrowed references are checked at compile time"]),
+        ]));
+
+        assert_eq!(
+            index.search("borrow"),
+            vec![
+                BlockRef { section: 0, block: 0 },
+                BlockRef { section: 0, block: 1 },
+                BlockRef { section: 1, block: 0 },
+            ]
+        );
+        assert_eq!(
+            index.search("BORROW check"),
+            vec![BlockRef { section: 0, block: 1 }, BlockRef { section: 1, block: 0 },]
+        );
+        assert!(index.search("lifetimes").is_empty());
+        assert!(index.search("  ").is_empty());
+    }
+
+    #[test]
+    fn update_only_reindexes_changed_sections() {
+        let mut index = SearchIndex::build(&book(&[
+            ("ch01/a", &["Traits define shared behavior"]),
+            ("ch01/b", &["Generics reduce duplication"]),
+        ]));
+
+        let reindexed = index.update(&book(&[
+            ("ch01/new", &["Closures capture their environment"]),
+            ("ch01/a", &["Traits define shared behavior"]),
+            ("ch01/b", &["Generics and lifetimes"]),
+        ]));
+
+        assert_eq!(reindexed, 2);
+        assert_eq!(index.search("traits"), vec![BlockRef { section: 1, block: 0 }]);
+        assert_eq!(index.search("closures"), vec![BlockRef { section: 0, block: 0 }]);
+        assert_eq!(index.search("lifetimes"), vec![BlockRef { section: 2, block: 0 }]);
+        assert!(index.search("duplication").is_empty());
+        assert!(!index.words.contains_key("duplication"));
+    }
+
+    #[test]
+    fn snippet_centers_on_first_match() {
+        let text = "Rust has no garbage collector. Instead, memory is managed through ownership.";
+        assert_eq!(snippet(text, "ownership", 30), "…hrough ownership.");
+        assert_eq!(snippet("Short text", "missing", 30), "Short text");
+    }
+}
@@ -1,6 +1,10 @@
 //! Book storage and caching
 //!
 //! Handles persisting parsed books and managing the book library.
+//!
+//! A parsed book's outline is cached as MessagePack behind a header naming the
+//! cache format's version and a hash of the source, so it's parsed again only
+//! when the format or the source's content changes.
 
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -9,10 +13,16 @@ use std::time::SystemTime;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+use super::asciidoc::parse_asciidoc_file;
+use super::compare::{SectionChange, compare_editions};
+use super::docx::parse_docx_file;
 use super::epub::parse_epub_file;
+use super::lazy;
 use super::markdown::parse_markdown_directory;
 use super::model::{Book, BookMetadata, BookSource};
-use crate::config::Config;
+use super::search::update_index;
+use super::transform::Transforms;
+use crate::config::{Config, datetime, persist};
 
 /// Library entry with cache metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +33,95 @@ pub struct LibraryEntry {
     pub cached_at: i64,
     /// File modification time of the source (for invalidation)
     pub source_mtime: Option<i64>,
+    /// Whether the book comes from the read-only shared library
+    #[serde(default)]
+    pub shared: bool,
+    /// Settings that override the global config for this book
+    #[serde(default, skip_serializing_if = "BookSettings::is_empty")]
+    pub settings: BookSettings,
+    /// Hash of the source's content, to spot the same book added twice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+}
+
+/// Per-book overrides of global settings, set with `:bookset`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BookSettings {
+    /// Theme name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Maximum width of the content text in columns (0 fills the panel)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_width: Option<usize>,
+    /// Claude persona name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persona: Option<String>,
+    /// Claude model (as accepted by `:claude-model`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Reading mode preset (as accepted by `:mode`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+}
+
+impl BookSettings {
+    /// Keys `:bookset` accepts
+    pub const KEYS: [&str; 5] = ["theme", "textwidth", "persona", "model", "mode"];
+
+    /// Whether nothing is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Canonical name of a setting key, accepting the short forms of `:set`
+    pub fn key(name: &str) -> Option<&'static str> {
+        match name.to_lowercase().as_str() {
+            "theme" => Some("theme"),
+            "textwidth" | "tw" | "text_width" => Some("textwidth"),
+            "persona" => Some("persona"),
+            "model" | "claude-model" => Some("model"),
+            "mode" => Some("mode"),
+            _ => None,
+        }
+    }
+
+    /// The override for a key, if set
+    pub fn get(&self, key: &str) -> Option<String> {
+        match Self::key(key)? {
+            "theme" => self.theme.clone(),
+            "textwidth" => self.text_width.map(|w| w.to_string()),
+            "persona" => self.persona.clone(),
+            "model" => self.model.clone(),
+            "mode" => self.mode.clone(),
+            _ => None,
+        }
+    }
+
+    /// Set or, with None, clear the override for a key (already validated)
+    pub fn set(&mut self, key: &str, value: Option<String>) {
+        match Self::key(key) {
+            Some("theme") => self.theme = value,
+            Some("textwidth") => self.text_width = value.and_then(|v| v.parse().ok()),
+            Some("persona") => self.persona = value,
+            Some("model") => self.model = value,
+            Some("mode") => self.mode = value,
+            _ => {}
+        }
+    }
+}
+
+/// Result of resolving a book name against the library
+#[derive(Debug)]
+pub enum BookMatch<'a> {
+    /// A book's ID or title matched exactly
+    Found(&'a LibraryEntry),
+    /// Only one book matched, but not exactly
+    Likely(&'a LibraryEntry),
+    /// Several books matched equally well
+    Ambiguous(Vec<&'a LibraryEntry>),
+    /// Nothing matched
+    NotFound,
 }
 
 /// The book library
@@ -37,13 +136,12 @@ impl Library {
     pub fn load() -> Result<Self> {
         let path = Self::library_path()?;
 
-        if path.exists() {
-            let contents = fs::read_to_string(&path)
+        let loaded = persist::load(&path, |path| {
+            let contents = fs::read_to_string(path)
                 .with_context(|| format!("Failed to read library from {:?}", path))?;
             serde_json::from_str(&contents).with_context(|| "Failed to parse library.json")
-        } else {
-            Ok(Self::default())
-        }
+        })?;
+        Ok(loaded.unwrap_or_default())
     }
 
     /// Save library to disk
@@ -58,7 +156,7 @@ impl Library {
         let contents =
             serde_json::to_string_pretty(self).with_context(|| "Failed to serialize library")?;
 
-        fs::write(&path, contents)
+        persist::write(&path, contents)
             .with_context(|| format!("Failed to write library to {:?}", path))?;
 
         Ok(())
@@ -74,16 +172,61 @@ impl Library {
         self.entries.iter().find(|e| e.metadata.id == id)
     }
 
+    /// Find a book with the same content as a source, by content hash
+    pub fn find_by_hash(&self, hash: &str) -> Option<&LibraryEntry> {
+        self.entries.iter().find(|e| e.content_hash.as_deref() == Some(hash))
+    }
+
     /// Find a book by title (case-insensitive partial match)
     pub fn find_by_title(&self, query: &str) -> Option<&LibraryEntry> {
         let query_lower = query.to_lowercase();
         self.entries.iter().find(|e| e.metadata.title.to_lowercase().contains(&query_lower))
     }
 
-    /// Add or update a book in the library
-    pub fn upsert(&mut self, entry: LibraryEntry) {
+    /// Resolve a book by ID, exact title, or fuzzy title match
+    ///
+    /// Fuzzy matches are tiered (prefix, substring, all words, subsequence) and
+    /// only the best tier that matches anything is considered, so a query is
+    /// ambiguous only when several books match it equally well. A single fuzzy
+    /// match is only `Likely`, so callers can confirm it before acting on it.
+    pub fn resolve(&self, query: &str) -> BookMatch<'_> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return BookMatch::NotFound;
+        }
+
+        if let Some(entry) = self.entries.iter().find(|e| {
+            e.metadata.id.to_lowercase() == query || e.metadata.title.to_lowercase() == query
+        }) {
+            return BookMatch::Found(entry);
+        }
+
+        let scored: Vec<(u8, &LibraryEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|e| match_tier(&query, &e.metadata).map(|tier| (tier, e)))
+            .collect();
+        let Some(best) = scored.iter().map(|(tier, _)| *tier).max() else {
+            return BookMatch::NotFound;
+        };
+
+        let mut matches: Vec<&LibraryEntry> =
+            scored.into_iter().filter(|(tier, _)| *tier == best).map(|(_, e)| e).collect();
+        if matches.len() == 1 {
+            BookMatch::Likely(matches[0])
+        } else {
+            matches.sort_by_key(|e| e.metadata.title.len());
+            BookMatch::Ambiguous(matches)
+        }
+    }
+
+    /// Add or update a book in the library, keeping its settings
+    pub fn upsert(&mut self, mut entry: LibraryEntry) {
         if let Some(existing) = self.entries.iter_mut().find(|e| e.metadata.id == entry.metadata.id)
         {
+            if entry.settings.is_empty() {
+                entry.settings = std::mem::take(&mut existing.settings);
+            }
             *existing = entry;
         } else {
             self.entries.push(entry);
@@ -103,6 +246,34 @@ impl Library {
     }
 }
 
+/// How well a lowercase query matches a book (higher is better)
+fn match_tier(query: &str, metadata: &BookMetadata) -> Option<u8> {
+    let title = metadata.title.to_lowercase();
+    let id = metadata.id.to_lowercase();
+    let author = metadata.author.as_deref().unwrap_or("").to_lowercase();
+
+    if title.starts_with(query) || id.starts_with(query) {
+        return Some(4);
+    }
+    if title.contains(query) || id.contains(query) {
+        return Some(3);
+    }
+    let haystack = format!("{} {} {}", title, id, author);
+    if query.split_whitespace().all(|word| haystack.contains(word)) {
+        return Some(2);
+    }
+    if is_subsequence(query, &title) || is_subsequence(query, &id) {
+        return Some(1);
+    }
+    None
+}
+
+/// Whether all characters of `needle` appear in order in `haystack`
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().filter(|c| !c.is_whitespace()).all(|c| chars.any(|h| h == c))
+}
+
 /// Get the books directory
 pub fn books_dir() -> Result<PathBuf> {
     let dir = Config::data_dir()?.join("books");
@@ -112,16 +283,64 @@ pub fn books_dir() -> Result<PathBuf> {
 }
 
 /// Get the cache directory for a specific book
-fn book_cache_dir(book_id: &str) -> Result<PathBuf> {
+pub(super) fn book_cache_dir(book_id: &str) -> Result<PathBuf> {
     let dir = books_dir()?.join(book_id);
     fs::create_dir_all(&dir)
         .with_context(|| format!("Failed to create book cache directory {:?}", dir))?;
     Ok(dir)
 }
 
-/// Get the path to the cached book JSON
+/// Get the path to the cached book outline
 fn book_cache_path(book_id: &str) -> Result<PathBuf> {
-    Ok(book_cache_dir(book_id)?.join("parsed.json"))
+    Ok(book_cache_dir(book_id)?.join("parsed.bin"))
+}
+
+/// Version of the cached book format, raised whenever `Book` changes shape so
+/// that older caches are parsed again rather than misread
+const BOOK_CACHE_VERSION: u32 = 2;
+
+/// What a cached book was made from, written ahead of it
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct CacheHeader {
+    /// `BOOK_CACHE_VERSION` when written
+    version: u32,
+    /// Content hash of the source parsed
+    source_hash: Option<String>,
+}
+
+/// A book's cache file: its header, then the book
+fn encode_cached(header: &CacheHeader, book: &Book) -> Result<Vec<u8>> {
+    let mut bytes = rmp_serde::to_vec_named(header).context("Failed to serialize cache header")?;
+    rmp_serde::encode::write_named(&mut bytes, book).context("Failed to serialize book")?;
+    Ok(bytes)
+}
+
+/// Read a book's cache file, refusing one written in another version of the format
+fn decode_cached(mut bytes: &[u8]) -> Result<(CacheHeader, Book)> {
+    let header: CacheHeader =
+        rmp_serde::from_read(&mut bytes).context("Failed to read cached book header")?;
+    if header.version != BOOK_CACHE_VERSION {
+        anyhow::bail!(
+            "Cached book is format version {}, not {}",
+            header.version,
+            BOOK_CACHE_VERSION
+        );
+    }
+    let book = rmp_serde::from_read(bytes).context("Failed to parse cached book")?;
+    Ok((header, book))
+}
+
+/// A book's cached outline and the header it was written with
+fn read_cached(book_id: &str) -> Result<(CacheHeader, Book)> {
+    let path = book_cache_path(book_id)?;
+    let bytes =
+        fs::read(&path).with_context(|| format!("Failed to read cached book from {:?}", path))?;
+    decode_cached(&bytes)
+}
+
+/// Path to a book's cached section content, read a section at a time
+fn content_cache_path(book_id: &str) -> Result<PathBuf> {
+    Ok(book_cache_dir(book_id)?.join("sections.jsonl"))
 }
 
 /// Check if cache is valid for a source path
@@ -167,84 +386,399 @@ fn get_source_mtime(path: &Path) -> Option<i64> {
     }
 }
 
+/// When a markdown book's files last changed: the newest modification time of
+/// its `.md` files and directories, skipping hidden ones such as `.git`
+pub fn markdown_modified(dir: &Path) -> Option<SystemTime> {
+    /// Directory levels searched, enough for mdbook's `src/<chapter>/` layout
+    const MAX_DEPTH: usize = 4;
+
+    fn newest(dir: &Path, depth: usize) -> Option<SystemTime> {
+        let mut latest = fs::metadata(dir).and_then(|m| m.modified()).ok();
+        for entry in fs::read_dir(dir).ok()?.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let modified = if path.is_dir() {
+                if depth == 0 {
+                    continue;
+                }
+                newest(&path, depth - 1)
+            } else if path.extension().is_some_and(|ext| ext == "md") {
+                entry.metadata().and_then(|m| m.modified()).ok()
+            } else {
+                continue;
+            };
+            latest = latest.max(modified);
+        }
+        latest
+    }
+
+    newest(dir, MAX_DEPTH)
+}
+
+/// Reparse a library book from its source, replacing its cache
+pub fn reparse_book(book_id: &str) -> Result<Book> {
+    if !refresh_book(book_id)? {
+        anyhow::bail!("Book not found in library");
+    }
+    let library = Library::load()?;
+    let entry =
+        library.find_by_id(book_id).ok_or_else(|| anyhow::anyhow!("Book not found in library"))?;
+    load_book(entry)
+}
+
 /// Load a book from cache if valid, otherwise parse and cache
 pub fn load_book(entry: &LibraryEntry) -> Result<Book> {
     let source_path = match &entry.metadata.source {
-        BookSource::Epub(p) => p.clone(),
-        BookSource::Markdown(p) => p.clone(),
+        BookSource::Epub(p)
+        | BookSource::Markdown(p)
+        | BookSource::Docx(p)
+        | BookSource::AsciiDoc(p) => p.clone(),
     };
 
-    // Check if cache is valid
+    // Check if cache is valid, including for the book's transform rules
     let cache_path = book_cache_path(&entry.metadata.id)?;
-    if cache_path.exists() && is_cache_valid(entry.source_mtime, &source_path) {
-        // Load from cache
-        let contents = fs::read_to_string(&cache_path)
-            .with_context(|| format!("Failed to read cached book from {:?}", cache_path))?;
-        return serde_json::from_str(&contents).with_context(|| "Failed to parse cached book");
+    let content_path = content_cache_path(&entry.metadata.id)?;
+    let cached_at = fs::metadata(&cache_path).and_then(|m| m.modified()).ok();
+    let rules_changed = Transforms::modified(&entry.metadata.id)
+        .is_some_and(|changed| cached_at.is_none_or(|cached| changed > cached));
+    // Caches from before section content was split out are parsed again
+    let cached = if cached_at.is_some() && content_path.exists() && !rules_changed {
+        read_cached(&entry.metadata.id)
+            .inspect_err(|e| tracing::info!("Parsing {} again: {:#}", entry.metadata.id, e))
+            .ok()
+    } else {
+        None
+    };
+    if let Some((header, mut book)) = cached {
+        // A source touched but not changed (e.g. checked out again) still matches its hash
+        let unchanged = is_cache_valid(entry.source_mtime, &source_path)
+            || header
+                .source_hash
+                .is_some_and(|hash| content_hash(&source_path).is_ok_and(|h| h == hash));
+        if unchanged {
+            // Section content is read when used
+            lazy::attach(&mut book, &content_path);
+            return Ok(book);
+        }
     }
 
     // Parse the book
-    let book = match &entry.metadata.source {
+    let mut book = match &entry.metadata.source {
         BookSource::Epub(path) => parse_epub_file(path)?,
         BookSource::Markdown(path) => parse_markdown_directory(path)?,
+        BookSource::Docx(path) => parse_docx_file(path)?,
+        BookSource::AsciiDoc(path) => parse_asciidoc_file(path)?,
     };
+    Transforms::load(&book.metadata.id)?.apply(&mut book);
 
     // Cache the parsed book
-    let contents =
-        serde_json::to_string_pretty(&book).with_context(|| "Failed to serialize book")?;
-    fs::write(&cache_path, contents)
-        .with_context(|| format!("Failed to write book cache to {:?}", cache_path))?;
+    cache_book(&entry.metadata.id, &mut book)?;
 
     Ok(book)
 }
 
 /// Add a book to the library from a source path
-pub fn add_book(source_path: &Path) -> Result<LibraryEntry> {
+///
+/// A source with the same content as a library book (under any name) isn't
+/// added again; the book it duplicates is returned instead.
+pub fn add_book(source_path: &Path) -> Result<Added> {
+    // A missing or unreadable source is reported by the parse
+    if let Some(existing) = find_duplicate(source_path).ok().flatten() {
+        return Ok(Added::Duplicate(existing));
+    }
+
+    let (entry, mut book) = parse_source(source_path, false, None)?;
+    cache_book(&entry.metadata.id, &mut book)?;
+
+    // Update library
+    let mut library = Library::load()?;
+    library.upsert(entry.clone());
+    library.save()?;
+
+    Ok(Added::New(entry))
+}
+
+/// What adding a book did
+#[derive(Debug)]
+pub enum Added {
+    /// The book was added
+    New(LibraryEntry),
+    /// The library already has the book, perhaps from another path
+    Duplicate(LibraryEntry),
+}
+
+/// The library book with the same content as a source, if it has been added
+/// before (under any name)
+///
+/// Entries added before content hashes were kept get theirs filled in.
+fn find_duplicate(source_path: &Path) -> Result<Option<LibraryEntry>> {
+    let source_path =
+        source_path.canonicalize().with_context(|| format!("Invalid path: {:?}", source_path))?;
+    let hash = content_hash(&source_path)?;
+
+    let mut library = Library::load()?;
+    let mut filled = false;
+    for entry in library.entries.iter_mut().filter(|e| e.content_hash.is_none()) {
+        entry.content_hash = content_hash(entry.metadata.source.path()).ok();
+        filled |= entry.content_hash.is_some();
+    }
+    if filled {
+        library.save()?;
+    }
+    Ok(library.find_by_hash(&hash).cloned())
+}
+
+/// A library book refreshed from a newer source file
+#[derive(Debug)]
+pub struct BookUpdate {
+    /// The updated entry, under the book's existing ID
+    pub entry: LibraryEntry,
+    /// How the sections differ from the previous version, if it was cached
+    pub changes: Option<Vec<SectionChange>>,
+}
+
+/// Refresh a library book in place from a newer source
+///
+/// The book keeps its ID and settings, so progress and notes on sections
+/// whose paths are unchanged carry over. Without `book_id`, the book is the
+/// one the source would be added as, else the one from the same path.
+pub fn update_book(source_path: &Path, book_id: Option<&str>) -> Result<BookUpdate> {
+    let library = Library::load()?;
+    let (parsed, _) = parse_source(source_path, false, None)?;
+    let existing = match book_id {
+        Some(id) => library.find_by_id(id),
+        None => library.find_by_id(&parsed.metadata.id).or_else(|| {
+            let source = parsed.metadata.source.path();
+            library.entries.iter().find(|e| e.metadata.source.path() == source)
+        }),
+    };
+    let Some(existing) = existing else {
+        anyhow::bail!("No book in the library matches {:?}; add it without --update", source_path);
+    };
+    if existing.shared {
+        anyhow::bail!("{} is in the shared library and can't be updated", existing.metadata.id);
+    }
+
+    let book_id = existing.metadata.id.clone();
+    let previous = cached_book(&book_id);
+    let (mut entry, mut book) = parse_source(source_path, false, Some(&book_id))?;
+    entry.metadata.added_at = existing.metadata.added_at;
+    entry.metadata.last_accessed = existing.metadata.last_accessed;
+    cache_book(&book_id, &mut book)?;
+
+    let mut library = library;
+    library.upsert(entry.clone());
+    library.save()?;
+
+    let changes = previous.map(|previous| compare_editions(&previous, &book));
+    Ok(BookUpdate { entry, changes })
+}
+
+/// A book's cached parse, whatever the state of its source
+///
+/// The content is read in full, as the cache is about to be replaced.
+fn cached_book(book_id: &str) -> Option<Book> {
+    let (_, mut book) = read_cached(book_id).ok()?;
+    lazy::attach(&mut book, &content_cache_path(book_id).ok()?);
+    lazy::detach(&mut book);
+    Some(book)
+}
+
+/// Stable hash (FNV-1a) of a source's content: a book file's bytes, or a
+/// markdown directory's `.md` files and their paths
+pub fn content_hash(source_path: &Path) -> Result<String> {
+    /// Directory levels hashed, as for `markdown_modified`
+    const MAX_DEPTH: usize = 4;
+
+    fn markdown_files(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else { return };
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')) {
+                continue;
+            }
+            if path.is_dir() && depth < MAX_DEPTH {
+                markdown_files(&path, depth + 1, files);
+            } else if path.extension().is_some_and(|e| e == "md") {
+                files.push(path);
+            }
+        }
+    }
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    let read = |path: &Path| fs::read(path).with_context(|| format!("Failed to read {:?}", path));
+
+    if source_path.is_dir() {
+        let mut files = Vec::new();
+        markdown_files(source_path, 0, &mut files);
+        files.sort();
+        for file in files {
+            let relative = file.strip_prefix(source_path).unwrap_or(&file);
+            feed(relative.to_string_lossy().as_bytes());
+            feed(&[0]);
+            feed(&read(&file)?);
+        }
+    } else {
+        feed(&read(source_path)?);
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+/// Parse a book source into a library entry and the parsed book, under
+/// `book_id` if given rather than the ID the source implies
+fn parse_source(
+    source_path: &Path,
+    shared: bool,
+    book_id: Option<&str>,
+) -> Result<(LibraryEntry, Book)> {
     let source_path =
         source_path.canonicalize().with_context(|| format!("Invalid path: {:?}", source_path))?;
 
     // Determine source type and parse
-    let book = if source_path.is_dir() {
+    let mut book = if source_path.is_dir() {
         parse_markdown_directory(&source_path)?
-    } else if source_path.extension().is_some_and(|ext| ext == "epub") {
-        parse_epub_file(&source_path)?
     } else {
-        anyhow::bail!(
-            "Unsupported source type. Expected directory (markdown) or .epub file: {:?}",
-            source_path
-        );
+        match source_path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+            Some("epub") => parse_epub_file(&source_path)?,
+            Some("docx") => parse_docx_file(&source_path)?,
+            Some("adoc" | "asciidoc") => parse_asciidoc_file(&source_path)?,
+            _ => anyhow::bail!(
+                "Unsupported source type. Expected directory (markdown), .epub, .docx or .adoc \
+                 file: {:?}",
+                source_path
+            ),
+        }
     };
+    if let Some(id) = book_id {
+        book.metadata.id = id.to_string();
+    }
+    Transforms::load(&book.metadata.id)?.apply(&mut book);
 
     // Create library entry
-    let now =
-        SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
-
     let entry = LibraryEntry {
         metadata: book.metadata.clone(),
-        cached_at: now,
+        cached_at: datetime::now(),
         source_mtime: get_source_mtime(&source_path),
+        shared,
+        settings: BookSettings::default(),
+        content_hash: content_hash(&source_path).ok(),
     };
 
-    // Cache the parsed book
-    let cache_path = book_cache_path(&entry.metadata.id)?;
-    // skipcq: RS-E1015 - false positive, no unit value is passed here
-    let contents =
-        serde_json::to_string_pretty(&book).with_context(|| "Failed to serialize book")?;
-    fs::write(&cache_path, contents)
+    Ok((entry, book))
+}
+
+/// Write a parsed book to its cache and bring its search index up to date
+///
+/// Section content goes in a file of its own, and the book's sections are
+/// pointed at it so they can be unloaded.
+fn cache_book(book_id: &str, book: &mut Book) -> Result<()> {
+    lazy::store(book, &content_cache_path(book_id)?)?;
+    let cache_path = book_cache_path(book_id)?;
+    let header = CacheHeader {
+        version: BOOK_CACHE_VERSION,
+        source_hash: content_hash(book.metadata.source.path()).ok(),
+    };
+    persist::write_atomic(&cache_path, encode_cached(&header, book)?)
         .with_context(|| format!("Failed to write book cache to {:?}", cache_path))?;
+    // Left by versions that cached books as JSON
+    let _ = fs::remove_file(book_cache_dir(book_id)?.join("parsed.json"));
+    update_index(book_id, book)
+}
 
-    // Update library
+/// File extensions of single-file books picked up from a shared library, in any case
+const SHARED_EXTENSIONS: &[&str] = &["epub", "docx", "adoc", "asciidoc"];
+
+/// Book sources (book files and markdown directories) in a shared library directory
+pub fn shared_sources(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("Failed to read shared library {:?}", dir))?;
+
+    let mut sources: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|path| !path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')))
+        .filter(|path| {
+            if path.is_dir() {
+                // A markdown book has markdown files at its top level
+                fs::read_dir(path).is_ok_and(|mut files| {
+                    files.any(|f| f.is_ok_and(|f| f.path().extension().is_some_and(|e| e == "md")))
+                })
+            } else {
+                path.extension().is_some_and(|ext| {
+                    SHARED_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e))
+                })
+            }
+        })
+        .collect();
+    sources.sort();
+    Ok(sources)
+}
+
+/// Bring the library's shared entries in line with a shared library directory
+///
+/// Shared books are parsed into the local cache but never written to, so the
+/// directory can be a read-only mount. New sources are added, and shared
+/// entries whose source is gone (or when `dir` is None) are dropped. Books
+/// already added locally under the same ID are left alone. Returns the number
+/// of books added.
+pub fn sync_shared_library(dir: Option<&Path>) -> Result<usize> {
     let mut library = Library::load()?;
-    library.upsert(entry.clone());
-    library.save()?;
+    let before = library.entries.len();
+
+    let sources = match dir {
+        Some(dir) => {
+            let dir = dir.canonicalize().with_context(|| format!("Invalid path: {:?}", dir))?;
+            shared_sources(&dir)?
+        }
+        None => Vec::new(),
+    };
+    let source_of = |entry: &LibraryEntry| match &entry.metadata.source {
+        BookSource::Epub(p)
+        | BookSource::Markdown(p)
+        | BookSource::Docx(p)
+        | BookSource::AsciiDoc(p) => p.clone(),
+    };
+
+    library.entries.retain(|e| !e.shared || sources.contains(&source_of(e)));
+    let removed = before - library.entries.len();
+
+    let mut added = 0;
+    for source in &sources {
+        if library.entries.iter().any(|e| e.shared && source_of(e) == *source) {
+            continue;
+        }
+        match parse_source(source, true, None) {
+            Ok((entry, mut book)) if library.find_by_id(&entry.metadata.id).is_none() => {
+                cache_book(&entry.metadata.id, &mut book)?;
+                library.entries.push(entry);
+                added += 1;
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Skipping shared book {:?}: {}", source, e),
+        }
+    }
 
-    Ok(entry)
+    if added > 0 || removed > 0 {
+        library.save()?;
+    }
+    Ok(added)
 }
 
 /// Remove a book from the library
 pub fn remove_book(book_id: &str) -> Result<bool> {
     let mut library = Library::load()?;
 
+    if library.find_by_id(book_id).is_some_and(|e| e.shared) {
+        anyhow::bail!("{} is in the shared library and can't be removed", book_id);
+    }
+
     if library.remove(book_id) {
         library.save()?;
 
@@ -294,19 +828,12 @@ mod tests {
     fn library_find_by_id() {
         let mut library = Library::default();
         library.entries.push(LibraryEntry {
-            metadata: BookMetadata {
-                id: "test-book".into(),
-                title: "Test Book".into(),
-                author: None,
-                source: BookSource::Markdown(PathBuf::from("/test")),
-                language: None,
-                description: None,
-                cover_image: None,
-                added_at: 0,
-                last_accessed: None,
-            },
+            metadata: BookMetadata::for_test("test-book", "Test Book"),
             cached_at: 0,
             source_mtime: None,
+            shared: false,
+            settings: BookSettings::default(),
+            content_hash: None,
         });
 
         assert!(library.find_by_id("test-book").is_some());
@@ -317,19 +844,12 @@ mod tests {
     fn library_find_by_title() {
         let mut library = Library::default();
         library.entries.push(LibraryEntry {
-            metadata: BookMetadata {
-                id: "test-book".into(),
-                title: "The Rust Programming Language".into(),
-                author: None,
-                source: BookSource::Markdown(PathBuf::from("/test")),
-                language: None,
-                description: None,
-                cover_image: None,
-                added_at: 0,
-                last_accessed: None,
-            },
+            metadata: BookMetadata::for_test("test-book", "The Rust Programming Language"),
             cached_at: 0,
             source_mtime: None,
+            shared: false,
+            settings: BookSettings::default(),
+            content_hash: None,
         });
 
         assert!(library.find_by_title("rust").is_some());
@@ -337,68 +857,115 @@ mod tests {
         assert!(library.find_by_title("python").is_none());
     }
 
+    fn entry(id: &str, title: &str, author: Option<&str>) -> LibraryEntry {
+        LibraryEntry {
+            metadata: BookMetadata {
+                author: author.map(Into::into),
+                ..BookMetadata::for_test(id, title)
+            },
+            cached_at: 0,
+            source_mtime: None,
+            shared: false,
+            settings: BookSettings::default(),
+            content_hash: None,
+        }
+    }
+
+    #[test]
+    fn library_resolve_tiers() {
+        let library = Library {
+            entries: vec![
+                entry("trpl", "The Rust Programming Language", Some("Klabnik")),
+                entry("rust-in-action", "Rust in Action", Some("McNamara")),
+                entry("go", "The Go Programming Language", None),
+            ],
+        };
+
+        // Exact ID and title win outright
+        assert!(matches!(library.resolve("trpl"), BookMatch::Found(e) if e.metadata.id == "trpl"));
+        assert!(matches!(library.resolve("rust in action"), BookMatch::Found(_)));
+
+        // A prefix match beats substring matches elsewhere
+        assert!(
+            matches!(library.resolve("rust"), BookMatch::Likely(e) if e.metadata.id == "rust-in-action")
+        );
+
+        // Equal substring matches are ambiguous
+        match library.resolve("programming") {
+            BookMatch::Ambiguous(matches) => assert_eq!(matches.len(), 2),
+            other => panic!("Expected ambiguous match, got {:?}", other),
+        }
+
+        // Words in any order, including the author
+        assert!(
+            matches!(library.resolve("klabnik rust"), BookMatch::Likely(e) if e.metadata.id == "trpl")
+        );
+
+        // Subsequence of the title
+        assert!(matches!(library.resolve("gopl"), BookMatch::Likely(e) if e.metadata.id == "go"));
+        assert!(matches!(library.resolve("python"), BookMatch::NotFound));
+    }
+
     #[test]
     fn library_upsert() {
         let mut library = Library::default();
 
         let entry1 = LibraryEntry {
-            metadata: BookMetadata {
-                id: "test".into(),
-                title: "Version 1".into(),
-                author: None,
-                source: BookSource::Markdown(PathBuf::from("/test")),
-                language: None,
-                description: None,
-                cover_image: None,
-                added_at: 0,
-                last_accessed: None,
-            },
+            metadata: BookMetadata::for_test("test", "Version 1"),
             cached_at: 1,
             source_mtime: None,
+            shared: false,
+            settings: BookSettings::default(),
+            content_hash: None,
         };
 
         library.upsert(entry1);
+        library.entries[0].settings.set("tw", Some("100".into()));
         assert_eq!(library.entries.len(), 1);
         assert_eq!(library.entries[0].metadata.title, "Version 1");
 
         let entry2 = LibraryEntry {
-            metadata: BookMetadata {
-                id: "test".into(),
-                title: "Version 2".into(),
-                author: None,
-                source: BookSource::Markdown(PathBuf::from("/test")),
-                language: None,
-                description: None,
-                cover_image: None,
-                added_at: 0,
-                last_accessed: None,
-            },
+            metadata: BookMetadata::for_test("test", "Version 2"),
             cached_at: 2,
             source_mtime: None,
+            shared: false,
+            settings: BookSettings::default(),
+            content_hash: None,
         };
 
         library.upsert(entry2);
         assert_eq!(library.entries.len(), 1);
         assert_eq!(library.entries[0].metadata.title, "Version 2");
+        assert_eq!(library.entries[0].settings.text_width, Some(100));
+    }
+
+    #[test]
+    fn book_settings_get_set_and_clear() {
+        let mut settings = BookSettings::default();
+        settings.set("model", Some("opus".into()));
+        settings.set("textwidth", Some("110".into()));
+        assert_eq!(settings.get("claude-model").as_deref(), Some("opus"));
+        assert_eq!(settings.get("tw").as_deref(), Some("110"));
+        assert_eq!(BookSettings::key("colour"), None);
+
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(json, r#"{"text_width":110,"model":"opus"}"#);
+
+        settings.set("model", None);
+        settings.set("tw", None);
+        assert!(settings.is_empty());
     }
 
     #[test]
     fn library_remove() {
         let mut library = Library::default();
         library.entries.push(LibraryEntry {
-            metadata: BookMetadata {
-                id: "test".into(),
-                title: "Test".into(),
-                author: None,
-                source: BookSource::Markdown(PathBuf::from("/test")),
-                language: None,
-                description: None,
-                cover_image: None,
-                added_at: 0,
-                last_accessed: None,
-            },
+            metadata: BookMetadata::for_test("test", "Test"),
             cached_at: 0,
             source_mtime: None,
+            shared: false,
+            settings: BookSettings::default(),
+            content_hash: None,
         });
 
         assert!(library.remove("test"));
@@ -406,6 +973,98 @@ mod tests {
         assert!(!library.remove("nonexistent"));
     }
 
+    #[test]
+    fn library_entry_defaults_to_local() {
+        let json = r#"{
+            "metadata": {
+                "id": "test",
+                "title": "Test",
+                "author": null,
+                "source": {"Markdown": "/test"},
+                "language": null,
+                "description": null,
+                "cover_image": null,
+                "added_at": 0,
+                "last_accessed": null
+            },
+            "cached_at": 0,
+            "source_mtime": null
+        }"#;
+        let entry: LibraryEntry = serde_json::from_str(json).unwrap();
+        assert!(!entry.shared);
+    }
+
+    #[test]
+    fn shared_sources_finds_epubs_and_markdown_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("course.epub"), b"").unwrap();
+        fs::write(root.join("HANDBOOK.DOCX"), b"").unwrap();
+        fs::write(root.join("notes.txt"), b"").unwrap();
+        fs::create_dir(root.join("guide")).unwrap();
+        fs::write(root.join("guide").join("README.md"), "# Guide").unwrap();
+        fs::create_dir(root.join("assets")).unwrap();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join(".git").join("HEAD.md"), "").unwrap();
+
+        let sources = shared_sources(root).unwrap();
+
+        assert_eq!(
+            sources,
+            vec![root.join("HANDBOOK.DOCX"), root.join("course.epub"), root.join("guide")]
+        );
+    }
+
+    #[test]
+    fn content_hash_follows_content_not_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("book.epub"), b"same bytes").unwrap();
+        fs::write(root.join("copy.epub"), b"same bytes").unwrap();
+        fs::write(root.join("newer.epub"), b"other bytes").unwrap();
+        let hash = |name: &str| content_hash(&root.join(name)).unwrap();
+        assert_eq!(hash("book.epub"), hash("copy.epub"));
+        assert_ne!(hash("book.epub"), hash("newer.epub"));
+
+        fs::create_dir(root.join("guide")).unwrap();
+        fs::write(root.join("guide").join("README.md"), "# Guide").unwrap();
+        let before = hash("guide");
+        fs::write(root.join("guide").join("notes.txt"), "ignored").unwrap();
+        assert_eq!(hash("guide"), before);
+        fs::write(root.join("guide").join("ch01.md"), "# One").unwrap();
+        assert_ne!(hash("guide"), before);
+
+        let mut library = Library::default();
+        let mut book = entry("book", "Book", None);
+        book.content_hash = Some(hash("book.epub"));
+        library.upsert(book);
+        assert!(library.find_by_hash(&hash("copy.epub")).is_some());
+        assert!(library.find_by_hash(&hash("newer.epub")).is_none());
+    }
+
+    #[test]
+    fn cached_book_round_trips_and_checks_its_version() {
+        use crate::book::{Chapter, ContentBlock, Section};
+
+        let mut book = Book::new(BookMetadata::for_test("rust", "Rust"));
+        let mut chapter = Chapter::new("Ownership", 1, "ch04");
+        let mut section = Section::new("Moves", 1, "ch04/moves");
+        section.content = vec![ContentBlock::Paragraph("A value has one owner".into())].into();
+        chapter.sections.push(section);
+        book.chapters.push(chapter);
+
+        let header = CacheHeader { version: BOOK_CACHE_VERSION, source_hash: Some("ab12".into()) };
+        let bytes = encode_cached(&header, &book).unwrap();
+        let (read_header, cached) = decode_cached(&bytes).unwrap();
+        assert_eq!(read_header, header);
+        assert_eq!(cached.metadata.title, "Rust");
+        assert_eq!(cached.chapters[0].sections[0].plain_text(), "A value has one owner");
+
+        let older = CacheHeader { version: BOOK_CACHE_VERSION + 1, source_hash: None };
+        assert!(decode_cached(&encode_cached(&older, &book).unwrap()).is_err());
+        assert!(decode_cached(b"{\"metadata\": {}}").is_err());
+    }
+
     #[test]
     fn get_source_mtime_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -436,4 +1095,22 @@ mod tests {
         // No cached mtime should be invalid
         assert!(!is_cache_valid(None, &file_path));
     }
+
+    #[test]
+    fn markdown_modified_finds_newest_nested_markdown() {
+        let temp_dir = TempDir::new().unwrap();
+        let touch = |path: &str, secs_ahead: u64| {
+            let path = temp_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let at = SystemTime::now() + std::time::Duration::from_secs(secs_ahead);
+            fs::File::create(&path).unwrap().set_modified(at).unwrap();
+            fs::metadata(&path).unwrap().modified().unwrap()
+        };
+
+        let chapter = touch("src/ch01/intro.md", 1000);
+        touch(".git/notes.md", 2000);
+        touch("src/build.log", 3000);
+
+        assert_eq!(markdown_modified(temp_dir.path()), Some(chapter));
+    }
 }
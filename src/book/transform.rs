@@ -0,0 +1,231 @@
+//! Per-book clean-up rules applied after parsing
+//!
+//! Badly converted EPUBs often carry page numbers, ligatures, stray running
+//! headers or headings at the wrong level. Rather than editing the source, a
+//! reader can put rules in `books/<book-id>.toml` in the config directory:
+//!
+//! ```toml
+//! # Remove text matching these patterns; blocks left empty are dropped
+//! strip = ['^Page \d+$', 'Copyright © \d{4}.*']
+//!
+//! # Replace text, in order ($1 etc. refer to capture groups)
+//! [[replace]]
+//! pattern = 'ﬁ'
+//! with = 'fi'
+//!
+//! # Change heading levels: every h1 becomes an h2
+//! [headings]
+//! h1 = 2
+//! ```
+//!
+//! Rules apply to prose (headings, paragraphs, lists, quotes, tables and
+//! footnotes), never to code. The parsed cache holds the transformed book and
+//! is rebuilt when the rules file changes.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use serde::Deserialize;
+
+use super::model::{Book, ContentBlock};
+use crate::config::Config;
+
+/// Rules as written in the TOML file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RulesFile {
+    strip: Vec<String>,
+    replace: Vec<ReplaceRule>,
+    headings: BTreeMap<String, u8>,
+}
+
+/// A regex replacement as written in the TOML file
+#[derive(Debug, Deserialize)]
+struct ReplaceRule {
+    pattern: String,
+    #[serde(default)]
+    with: String,
+}
+
+/// A book's compiled transformation pipeline
+#[derive(Debug, Default)]
+pub struct Transforms {
+    /// Patterns whose matches are removed
+    strip: Vec<Regex>,
+    /// Patterns replaced, in order
+    replace: Vec<(Regex, String)>,
+    /// New level for each heading level
+    headings: BTreeMap<u8, u8>,
+}
+
+impl Transforms {
+    /// Path of a book's rules file
+    pub fn path(book_id: &str) -> Result<PathBuf> {
+        let config_path = Config::config_path()?;
+        let config_dir = config_path.parent().context("Failed to determine config directory")?;
+        Ok(config_dir.join("books").join(format!("{}.toml", book_id)))
+    }
+
+    /// When a book's rules file was last changed; None if it has none
+    pub fn modified(book_id: &str) -> Option<SystemTime> {
+        std::fs::metadata(Self::path(book_id).ok()?).ok()?.modified().ok()
+    }
+
+    /// Load a book's rules; empty if it has no rules file
+    pub fn load(book_id: &str) -> Result<Self> {
+        let path = Self::path(book_id)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read transforms from {:?}", path))?;
+        Self::parse(&contents).with_context(|| format!("Invalid transforms in {:?}", path))
+    }
+
+    /// Compile rules from TOML
+    pub fn parse(contents: &str) -> Result<Self> {
+        let rules: RulesFile = toml::from_str(contents)?;
+        let compile = |pattern: &str| {
+            Regex::new(&format!("(?m){}", pattern))
+                .with_context(|| format!("Invalid pattern {:?}", pattern))
+        };
+
+        let strip = rules.strip.iter().map(|p| compile(p)).collect::<Result<_>>()?;
+        let replace = rules
+            .replace
+            .iter()
+            .map(|rule| Ok((compile(&rule.pattern)?, rule.with.clone())))
+            .collect::<Result<_>>()?;
+        let headings = rules
+            .headings
+            .iter()
+            .map(|(key, &to)| match key.trim_start_matches(['h', 'H']).parse::<u8>() {
+                Ok(from @ 1..=6) if (1..=6).contains(&to) => Ok((from, to)),
+                _ => bail!("Invalid heading mapping {} = {} (levels are 1-6)", key, to),
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { strip, replace, headings })
+    }
+
+    /// Whether there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.strip.is_empty() && self.replace.is_empty() && self.headings.is_empty()
+    }
+
+    /// Apply the rules to every section of a book
+    pub fn apply(&self, book: &mut Book) {
+        if self.is_empty() {
+            return;
+        }
+        for section in book.chapters.iter_mut().flat_map(|c| &mut c.sections) {
+            let title = self.apply_text(&section.title);
+            if !title.trim().is_empty() {
+                section.title = title;
+            }
+            let content = std::mem::take(&mut *section.content);
+            *section.content = content.into_iter().filter_map(|b| self.apply_block(b)).collect();
+            section.calculate_reading_time();
+        }
+    }
+
+    /// Transform one block; None if nothing is left of it
+    fn apply_block(&self, block: ContentBlock) -> Option<ContentBlock> {
+        let text = |text: String| Some(self.apply_text(&text)).filter(|t| !t.trim().is_empty());
+        let items = |items: Vec<String>| {
+            let items: Vec<String> = items.into_iter().filter_map(text).collect();
+            (!items.is_empty()).then_some(items)
+        };
+        match block {
+            ContentBlock::Heading { level, text: heading } => Some(ContentBlock::Heading {
+                level: self.headings.get(&level).copied().unwrap_or(level),
+                text: text(heading)?,
+            }),
+            ContentBlock::Paragraph(paragraph) => Some(ContentBlock::Paragraph(text(paragraph)?)),
+            ContentBlock::Blockquote(quote) => Some(ContentBlock::Blockquote(text(quote)?)),
+            ContentBlock::UnorderedList(list) => Some(ContentBlock::UnorderedList(items(list)?)),
+            ContentBlock::OrderedList(list) => Some(ContentBlock::OrderedList(items(list)?)),
+            ContentBlock::Footnote { label, text: note } => {
+                Some(ContentBlock::Footnote { label, text: text(note)? })
+            }
+            ContentBlock::Table(mut table) => {
+                let cells = table.headers.iter_mut().chain(table.rows.iter_mut().flatten());
+                for cell in cells {
+                    *cell = self.apply_text(cell);
+                }
+                Some(ContentBlock::Table(table))
+            }
+            block @ (ContentBlock::Code(_)
+            | ContentBlock::Image { .. }
+            | ContentBlock::HorizontalRule) => Some(block),
+        }
+    }
+
+    /// Strip then replace within a piece of text
+    fn apply_text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for pattern in &self.strip {
+            text = pattern.replace_all(&text, "").into_owned();
+        }
+        for (pattern, with) in &self.replace {
+            text = pattern.replace_all(&text, with.as_str()).into_owned();
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::{CodeBlock, Section};
+
+    const RULES: &str = r#"
+        strip = ['^Page \d+$']
+
+        [[replace]]
+        pattern = 'ﬁ'
+        with = 'fi'
+
+        [[replace]]
+        pattern = '(\w+)--(\w+)'
+        with = '$1 — $2'
+
+        [headings]
+        h1 = 2
+    "#;
+
+    #[test]
+    fn apply_cleans_prose_and_remaps_headings() {
+        let transforms = Transforms::parse(RULES).unwrap();
+        let mut section = Section::new("Intro", 1, "ch01/s01");
+        section.content = vec![
+            ContentBlock::Heading { level: 1, text: "Deﬁnitions".into() },
+            ContentBlock::Paragraph("Page 12".into()),
+            ContentBlock::Paragraph("Traits--mostly".into()),
+            ContentBlock::UnorderedList(vec!["Page 13".into(), "ﬁrst".into()]),
+            ContentBlock::Code(CodeBlock::new("// Page 14\nlet ﬁ = 1;")),
+        ]
+        .into();
+
+        let out: Vec<_> =
+            section.content.iter().cloned().filter_map(|b| transforms.apply_block(b)).collect();
+
+        assert!(
+            matches!(&out[0], ContentBlock::Heading { level: 2, text } if text == "Definitions")
+        );
+        assert!(matches!(&out[1], ContentBlock::Paragraph(text) if text == "Traits — mostly"));
+        assert!(matches!(&out[2], ContentBlock::UnorderedList(items) if items == &["first"]));
+        assert!(matches!(&out[3], ContentBlock::Code(code) if code.code.contains("Page 14")));
+        assert_eq!(out.len(), 4);
+    }
+
+    #[test]
+    fn parse_rejects_bad_rules() {
+        assert!(Transforms::parse("").unwrap().is_empty());
+        assert!(Transforms::parse("strip = ['(']").is_err());
+        assert!(Transforms::parse("[headings]\nh7 = 1").is_err());
+        assert!(Transforms::parse("[headings]\n2 = 9").is_err());
+    }
+}
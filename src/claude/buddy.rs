@@ -0,0 +1,231 @@
+//! Study buddy mode
+//!
+//! `:compare` asks the same question of two models and shows their answers
+//! side by side, with the key claims each makes matched up so disagreements
+//! on subtle topics stand out.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use super::models::ClaudeModel;
+use crate::book::search::tokenize;
+
+/// Heading each answer ends with, listing its key claims
+const CLAIMS_HEADING: &str = "## Key claims";
+
+/// Share of words two claims must have in common to count as the same claim
+const SAME_CLAIM_OVERLAP: f64 = 0.5;
+
+/// One model's side of a comparison
+#[derive(Debug, Clone)]
+pub struct BuddyAnswer {
+    /// Model asked
+    pub model: ClaudeModel,
+    /// Answer text, or the error that prevented it (None while waiting)
+    pub result: Option<Result<String, String>>,
+}
+
+impl BuddyAnswer {
+    /// Answer text, or a placeholder while waiting or after an error
+    pub fn text(&self) -> String {
+        match &self.result {
+            Some(Ok(text)) => text.clone(),
+            Some(Err(e)) => format!("Error: {}", e),
+            None => "Waiting for answer...".to_string(),
+        }
+    }
+
+    /// Key claims listed at the end of the answer
+    pub fn claims(&self) -> Vec<String> {
+        match &self.result {
+            Some(Ok(text)) => key_claims(text),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The same question put to two models
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// Question asked
+    pub question: String,
+    /// Answers, in the order the models were given
+    pub answers: [BuddyAnswer; 2],
+}
+
+impl Comparison {
+    /// Start a comparison awaiting both answers
+    pub fn new(question: &str, models: [ClaudeModel; 2]) -> Self {
+        Self {
+            question: question.to_string(),
+            answers: models.map(|model| BuddyAnswer { model, result: None }),
+        }
+    }
+
+    /// Whether both models have answered (or failed)
+    pub fn is_complete(&self) -> bool {
+        self.answers.iter().all(|a| a.result.is_some())
+    }
+
+    /// Match up the key claims of both answers
+    pub fn diff(&self) -> ClaimDiff {
+        diff_claims(&self.answers[0].claims(), &self.answers[1].claims())
+    }
+
+    /// Markdown version of the comparison, one answer after the other
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("**Question:** {}\n\n", self.question);
+        for answer in &self.answers {
+            let _ =
+                writeln!(out, "# {}\n\n{}\n", answer.model.display_name(), answer.text().trim());
+        }
+        if self.is_complete() {
+            out.push_str(&self.diff().to_markdown(self.answers.each_ref().map(|a| &a.model)));
+        }
+        out
+    }
+}
+
+/// Key claims of two answers, matched up
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClaimDiff {
+    /// Claims both answers make: (first answer's wording, second answer's wording)
+    pub shared: Vec<(String, String)>,
+    /// Claims only the first answer makes
+    pub only_first: Vec<String>,
+    /// Claims only the second answer makes
+    pub only_second: Vec<String>,
+}
+
+impl ClaimDiff {
+    /// Markdown listing of shared and differing claims
+    pub fn to_markdown(&self, models: [&ClaudeModel; 2]) -> String {
+        let mut out = String::from("# Key claims\n\n");
+        let mut section = |title: String, claims: Vec<&str>| {
+            let _ = writeln!(out, "## {}\n", title);
+            if claims.is_empty() {
+                out.push_str("- None\n");
+            }
+            for claim in claims {
+                let _ = writeln!(out, "- {}", claim);
+            }
+            out.push('\n');
+        };
+        section("Both agree".to_string(), self.shared.iter().map(|(a, _)| a.as_str()).collect());
+        section(
+            format!("Only {}", models[0].display_name()),
+            self.only_first.iter().map(String::as_str).collect(),
+        );
+        section(
+            format!("Only {}", models[1].display_name()),
+            self.only_second.iter().map(String::as_str).collect(),
+        );
+        out
+    }
+}
+
+/// Build the prompt each model answers
+pub fn answer_prompt(section_title: &str, content: &str, question: &str) -> String {
+    format!(
+        "I'm reading a section titled \"{}\" from a technical book:\n\n{}\n\n\
+         My question: {}\n\n\
+         Answer clearly and concisely in markdown. Then end with a \"{}\" heading \
+         followed by a bulleted list of the distinct factual claims your answer makes, \
+         one short sentence each.",
+        section_title, content, question, CLAIMS_HEADING
+    )
+}
+
+/// Bulleted claims under the key claims heading of an answer
+pub fn key_claims(answer: &str) -> Vec<String> {
+    answer
+        .lines()
+        .skip_while(|line| !line.trim().eq_ignore_ascii_case(CLAIMS_HEADING))
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            let line = line.trim();
+            line.strip_prefix("- ").or_else(|| line.strip_prefix("* "))
+        })
+        .map(|claim| claim.trim().to_string())
+        .filter(|claim| !claim.is_empty())
+        .collect()
+}
+
+/// Match claims that share most of their words
+///
+/// Each claim matches at most one claim of the other answer, greedily in order.
+pub fn diff_claims(first: &[String], second: &[String]) -> ClaimDiff {
+    let second_words: Vec<HashSet<String>> = second.iter().map(|c| claim_words(c)).collect();
+    let mut matched = vec![false; second.len()];
+    let mut diff = ClaimDiff::default();
+
+    for claim in first {
+        let words = claim_words(claim);
+        let best = second_words
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !matched[*i])
+            .map(|(i, other)| (i, overlap(&words, other)))
+            .filter(|&(_, score)| score >= SAME_CLAIM_OVERLAP)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match best {
+            Some((i, _)) => {
+                matched[i] = true;
+                diff.shared.push((claim.clone(), second[i].clone()));
+            }
+            None => diff.only_first.push(claim.clone()),
+        }
+    }
+    diff.only_second =
+        second.iter().zip(&matched).filter(|(_, m)| !**m).map(|(c, _)| c.clone()).collect();
+    diff
+}
+
+/// Significant words of a claim (short words like "a" and "is" are skipped)
+fn claim_words(claim: &str) -> HashSet<String> {
+    tokenize(claim).filter(|word| word.chars().count() > 2).collect()
+}
+
+/// Jaccard similarity of two word sets
+fn overlap(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_claims_reads_bullets_under_heading() {
+        let answer = "Moves transfer ownership.\n\n- not a claim\n\n## Key claims\n\
+                      - Assignment moves a `String`.\n* The old binding is invalid.\n\n## Notes\n- ignored";
+        assert_eq!(
+            key_claims(answer),
+            vec!["Assignment moves a `String`.", "The old binding is invalid."]
+        );
+        assert!(key_claims("No claims here").is_empty());
+    }
+
+    #[test]
+    fn diff_matches_claims_by_shared_words() {
+        let first = vec![
+            "Assignment moves ownership of the String".to_string(),
+            "Integers implement Copy".to_string(),
+        ];
+        let second = vec![
+            "Heap data is freed when the owner goes out of scope".to_string(),
+            "Assigning a String moves ownership".to_string(),
+        ];
+
+        let diff = diff_claims(&first, &second);
+
+        assert_eq!(diff.shared, vec![(first[0].clone(), second[1].clone())]);
+        assert_eq!(diff.only_first, vec![first[1].clone()]);
+        assert_eq!(diff.only_second, vec![second[0].clone()]);
+    }
+}
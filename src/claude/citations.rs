@@ -0,0 +1,196 @@
+//! Inline citations
+//!
+//! Claude is asked to back its claims with exact quotes from the section,
+//! written as `[[quoted text]]`, or with the numbers of the paragraphs it
+//! used, written as `[¶3]` (the section is sent with its blocks numbered).
+//! The response panel shows both as numbered references, and each can be
+//! located in the section to jump to its passage.
+
+use crate::book::Section;
+
+/// Appended to prompts about a section so answers quote their sources
+pub const CITATION_INSTRUCTION: &str = "Each paragraph of the section starts with its number, \
+     like [¶3]. After each claim, cite the paragraphs it comes from the same way, like [¶3] \
+     or [¶3][¶5]. Where one sentence backs up a claim directly, you may instead copy it \
+     verbatim inside double square brackets, like [[The quoted sentence.]]. Only cite \
+     paragraphs and quote text that appear in the section.";
+
+/// Marks a paragraph citation, before its number
+const PARAGRAPH_MARK: char = '¶';
+
+/// Words of a quote matched when the whole quote can't be found
+const PARTIAL_MATCH_WORDS: usize = 6;
+
+/// A piece of a response line: plain text, or a quote numbered by its citation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// Text outside any quote
+    Text(&'a str),
+    /// Quoted text with its citation index
+    Quote(&'a str, usize),
+}
+
+/// Quotes cited in a response, in order of first appearance
+pub fn quotes(text: &str) -> Vec<String> {
+    let mut quotes: Vec<String> = Vec::new();
+    for line in text.lines() {
+        for quote in raw_quotes(line) {
+            if !quotes.iter().any(|q| q == quote) {
+                quotes.push(quote.to_string());
+            }
+        }
+    }
+    quotes
+}
+
+/// Split a line into plain text and quotes, numbering quotes by their index in `quotes`
+pub fn segments<'a>(line: &'a str, quotes: &[String]) -> Vec<Segment<'a>> {
+    let mut segments = Vec::new();
+    let mut rest = line;
+    while let Some((start, end, quote)) = next_citation(rest) {
+        if !rest[..start].is_empty() {
+            segments.push(Segment::Text(&rest[..start]));
+        }
+        match quotes.iter().position(|q| q == quote) {
+            Some(index) => segments.push(Segment::Quote(quote, index)),
+            None => segments.push(Segment::Text(&rest[start..end])),
+        }
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    segments
+}
+
+/// Index of the block in a section containing a quote, or cited by number
+///
+/// Matching ignores case, whitespace and punctuation. If the full quote isn't
+/// found (Claude may trim or paraphrase slightly), its first few words are tried.
+pub fn locate(quote: &str, section: &Section) -> Option<usize> {
+    if let Some(number) = paragraph_number(quote) {
+        let index = number.checked_sub(1)?;
+        return section.content.get(index)?.plain_text().map(|_| index);
+    }
+    let words = normalized_words(quote);
+    if words.is_empty() {
+        return None;
+    }
+    let blocks: Vec<(usize, String)> = section
+        .content
+        .iter()
+        .enumerate()
+        .filter_map(|(i, block)| block.plain_text().map(|text| (i, normalized_words(&text))))
+        .map(|(i, words)| (i, format!(" {} ", words.join(" "))))
+        .collect();
+
+    let find = |words: &[String]| {
+        let needle = format!(" {} ", words.join(" "));
+        blocks.iter().find(|(_, text)| text.contains(&needle)).map(|(i, _)| *i)
+    };
+    find(&words).or_else(|| {
+        (words.len() > PARTIAL_MATCH_WORDS).then(|| find(&words[..PARTIAL_MATCH_WORDS])).flatten()
+    })
+}
+
+/// The number in a paragraph citation such as "¶3"
+fn paragraph_number(citation: &str) -> Option<usize> {
+    citation.strip_prefix(PARAGRAPH_MARK)?.trim().parse().ok()
+}
+
+/// The first citation in `text`, as the byte range of its markers and the
+/// citation inside them: a quote in `[[...]]` or a paragraph as in `[¶3]`
+fn next_citation(text: &str) -> Option<(usize, usize, &str)> {
+    let quote = text.find("[[").and_then(|start| {
+        let len = text[start + 2..].find("]]")?;
+        Some((start, start + len + 4, text[start + 2..start + 2 + len].trim()))
+    });
+    let paragraph = text.match_indices("[¶").find_map(|(start, _)| {
+        let len = text[start + 1..].find(']')?;
+        let citation = &text[start + 1..start + 1 + len];
+        paragraph_number(citation).map(|_| (start, start + len + 2, citation))
+    });
+    match (quote, paragraph) {
+        (Some(quote), Some(paragraph)) if paragraph.0 < quote.0 => Some(paragraph),
+        (None, paragraph) => paragraph,
+        (quote, _) => quote,
+    }
+}
+
+/// Citations in a line, in order
+fn raw_quotes(line: &str) -> impl Iterator<Item = &str> {
+    let mut rest = line;
+    std::iter::from_fn(move || {
+        let (_, end, quote) = next_citation(rest)?;
+        rest = &rest[end..];
+        Some(quote)
+    })
+    .filter(|quote| !quote.is_empty())
+}
+
+/// Lowercase words of a text with punctuation removed
+fn normalized_words(text: &str) -> Vec<String> {
+    crate::book::search::tokenize(text).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::ContentBlock;
+
+    #[test]
+    fn quotes_are_numbered_in_order_of_first_use() {
+        let text = "Moves [[Each value has an owner.]] and [[There can only be one owner.]]\n\
+                    Again: [[Each value has an owner.]] [[unclosed";
+        let quotes = quotes(text);
+        assert_eq!(quotes, vec!["Each value has an owner.", "There can only be one owner."]);
+
+        assert_eq!(
+            segments("Again: [[ There can only be one owner. ]] ok [[x", &quotes),
+            vec![
+                Segment::Text("Again: "),
+                Segment::Quote("There can only be one owner.", 1),
+                Segment::Text(" ok [[x"),
+            ]
+        );
+    }
+
+    #[test]
+    fn paragraph_citations_are_numbered_with_quotes() {
+        let text = "Owners drop values [¶2][¶4], see [[Each value has an owner.]] [¶2] [¶x]";
+        let quotes = quotes(text);
+        assert_eq!(quotes, vec!["¶2", "¶4", "Each value has an owner."]);
+        assert_eq!(
+            segments("drop [¶4]. [¶x]", &quotes),
+            vec![Segment::Text("drop "), Segment::Quote("¶4", 1), Segment::Text(". [¶x]")]
+        );
+
+        let mut section = Section::new("Ownership", 1, "ch04/s01");
+        section.content =
+            vec![ContentBlock::HorizontalRule, ContentBlock::Paragraph("Values move.".into())]
+                .into();
+        assert_eq!(locate("¶2", &section), Some(1));
+        assert_eq!(locate("¶1", &section), None);
+        assert_eq!(locate("¶9", &section), None);
+    }
+
+    #[test]
+    fn locate_ignores_case_and_punctuation() {
+        let mut section = Section::new("Ownership", 1, "ch04/s01");
+        section.content = vec![
+            ContentBlock::Paragraph("Rust has no garbage collector.".into()),
+            ContentBlock::Paragraph(
+                "Each value in Rust has an owner. There can only be one owner at a time.".into(),
+            ),
+        ]
+        .into();
+
+        assert_eq!(locate("each value in Rust has an owner", &section), Some(1));
+        assert_eq!(
+            locate("There can only be one owner at a time, always and forever.", &section),
+            Some(1)
+        );
+        assert_eq!(locate("Borrowing is checked at compile time.", &section), None);
+        assert_eq!(locate("...", &section), None);
+    }
+}
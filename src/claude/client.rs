@@ -1,24 +1,73 @@
 //! HTTP client for Claude API
 
-use reqwest::Client;
+use std::time::Duration;
+
+use reqwest::{Client, Response};
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
 use super::error::ClaudeError;
 use super::models::{CreateMessageRequest, MessageResponse, StreamEvent};
+use super::registry::{ListedModel, ModelsPage};
 use super::streaming;
 
+/// Retries for rate-limited (429) and server error (5xx) responses
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Delay before the first retry; doubled for each one after
+const BASE_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between retries
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// A failed request about to be retried
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryStatus {
+    /// HTTP status of the failed attempt
+    pub status: u16,
+    /// Retry number, starting at 1
+    pub attempt: u32,
+    /// Retries allowed in total
+    pub max_retries: u32,
+    /// Wait before retrying
+    pub delay: Duration,
+}
+
+impl RetryStatus {
+    /// Status line for the UI, e.g. "Rate limited, retrying in 4s… (2/3)"
+    pub fn message(&self) -> String {
+        let reason = if self.status == 429 {
+            "Rate limited".to_string()
+        } else {
+            format!("Server error ({})", self.status)
+        };
+        format!(
+            "{}, retrying in {}s… ({}/{})",
+            reason,
+            self.delay.as_secs().max(1),
+            self.attempt,
+            self.max_retries
+        )
+    }
+}
+
 /// Claude API client
 pub struct ClaudeClient {
     /// HTTP client
     client: Client,
     /// API key for authentication
     api_key: String,
+    /// Retries for rate-limited and server error responses
+    max_retries: u32,
+    /// Where to report retries, so the UI can show them
+    retry_tx: Option<mpsc::UnboundedSender<RetryStatus>>,
 }
 
 impl ClaudeClient {
     /// Claude API base URL
     const API_URL: &'static str = "https://api.anthropic.com/v1/messages";
+    /// Models endpoint URL
+    const MODELS_URL: &'static str = "https://api.anthropic.com/v1/models";
     /// API version header value
     const API_VERSION: &'static str = "2023-06-01";
 
@@ -29,54 +78,118 @@ impl ClaudeClient {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client, api_key }
+        Self { client, api_key, max_retries: DEFAULT_MAX_RETRIES, retry_tx: None }
     }
 
-    /// Send a streaming message request
+    /// Set how many times rate-limited and server error responses are retried
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Report retries through a channel
+    pub fn with_retry_notifier(mut self, tx: mpsc::UnboundedSender<RetryStatus>) -> Self {
+        self.retry_tx = Some(tx);
+        self
+    }
+
+    /// Post a request, retrying 429 and 5xx responses with exponential backoff
     ///
-    /// Spawns a background task that streams responses through the channel.
-    /// Use the cancellation token to interrupt the request.
-    pub async fn send_streaming(
+    /// Waiting between retries stops early if the token is cancelled.
+    async fn post(
         &self,
-        request: CreateMessageRequest,
-        tx: mpsc::Sender<StreamEvent>,
-        cancel_token: CancellationToken,
-    ) -> Result<(), ClaudeError> {
-        let response = self
-            .client
-            .post(Self::API_URL)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", Self::API_VERSION)
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-
-        // Handle HTTP errors
-        let status = response.status();
+        request: &CreateMessageRequest,
+        cancel_token: Option<&CancellationToken>,
+    ) -> Result<Response, ClaudeError> {
+        let mut attempt = 0;
+        loop {
+            let response = self
+                .client
+                .post(Self::API_URL)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", Self::API_VERSION)
+                .header("content-type", "application/json")
+                .json(request)
+                .send()
+                .await?;
+
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
 
-        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
             let retry_after = response
                 .headers()
                 .get("retry-after")
                 .and_then(|v| v.to_str().ok())
                 .and_then(|s| s.parse().ok())
-                .unwrap_or(60);
-            return Err(ClaudeError::RateLimited { retry_after_seconds: retry_after });
+                .map(Duration::from_secs);
+
+            if attempt >= self.max_retries || !is_retryable(status.as_u16()) {
+                return Err(Self::status_error(status, retry_after, response).await);
+            }
+
+            let delay = retry_after.unwrap_or_else(|| backoff_delay(attempt)).min(MAX_RETRY_DELAY);
+            attempt += 1;
+            tracing::warn!("Claude API returned {}; retry {} in {:?}", status, attempt, delay);
+            if let Some(tx) = &self.retry_tx {
+                let _ = tx.send(RetryStatus {
+                    status: status.as_u16(),
+                    attempt,
+                    max_retries: self.max_retries,
+                    delay,
+                });
+            }
+
+            match cancel_token {
+                Some(token) => tokio::select! {
+                    _ = token.cancelled() => return Err(ClaudeError::Cancelled),
+                    _ = tokio::time::sleep(delay) => {}
+                },
+                None => tokio::time::sleep(delay).await,
+            }
         }
+    }
 
-        if status == reqwest::StatusCode::UNAUTHORIZED {
-            return Err(ClaudeError::ApiError {
-                status: 401,
-                message: "Invalid API key".to_string(),
-            });
+    /// Error for an unsuccessful response
+    async fn status_error(
+        status: reqwest::StatusCode,
+        retry_after: Option<Duration>,
+        response: Response,
+    ) -> ClaudeError {
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after_seconds = retry_after.map_or(60, |d| d.as_secs());
+            return ClaudeError::RateLimited { retry_after_seconds };
         }
 
-        if !status.is_success() {
-            let message = response.text().await.unwrap_or_default();
-            return Err(ClaudeError::ApiError { status: status.as_u16(), message });
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return ClaudeError::ApiError { status: 401, message: "Invalid API key".to_string() };
         }
 
+        let message = response.text().await.unwrap_or_default();
+        ClaudeError::ApiError { status: status.as_u16(), message }
+    }
+
+    /// Send a streaming message request
+    ///
+    /// Spawns a background task that streams responses through the channel.
+    /// Use the cancellation token to interrupt the request. If the request
+    /// fails after any retries, the error is also sent as a stream event.
+    pub async fn send_streaming(
+        &self,
+        request: CreateMessageRequest,
+        tx: mpsc::Sender<StreamEvent>,
+        cancel_token: CancellationToken,
+    ) -> Result<(), ClaudeError> {
+        let response = match self.post(&request, Some(&cancel_token)).await {
+            Ok(response) => response,
+            Err(ClaudeError::Cancelled) => return Err(ClaudeError::Cancelled),
+            Err(e) => {
+                let _ = tx.send(StreamEvent::Error { message: e.to_string() }).await;
+                return Err(e);
+            }
+        };
+
         // Process the streaming response
         streaming::process_stream(response, tx, cancel_token).await
     }
@@ -91,50 +204,47 @@ impl ClaudeClient {
         // Disable streaming for this request
         request.stream = false;
 
-        let response = self
-            .client
-            .post(Self::API_URL)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", Self::API_VERSION)
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-
-        let status = response.status();
-
-        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-            let retry_after = response
-                .headers()
-                .get("retry-after")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(60);
-            return Err(ClaudeError::RateLimited { retry_after_seconds: retry_after });
-        }
-
-        if status == reqwest::StatusCode::UNAUTHORIZED {
-            return Err(ClaudeError::ApiError {
-                status: 401,
-                message: "Invalid API key".to_string(),
-            });
-        }
-
-        if !status.is_success() {
-            let message = response.text().await.unwrap_or_default();
-            return Err(ClaudeError::ApiError { status: status.as_u16(), message });
-        }
-
+        let response = self.post(&request, None).await?;
         let body = response.text().await?;
         let message_response: MessageResponse = serde_json::from_str(&body)?;
         Ok(message_response)
     }
 
+    /// Every model available to the API key, newest first
+    pub async fn list_models(&self) -> Result<Vec<ListedModel>, ClaudeError> {
+        let mut models = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let mut query = vec![("limit", "1000".to_string())];
+            if let Some(id) = after.take() {
+                query.push(("after_id", id));
+            }
+            let response = self
+                .client
+                .get(Self::MODELS_URL)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", Self::API_VERSION)
+                .query(&query)
+                .send()
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(Self::status_error(status, None, response).await);
+            }
+            let page: ModelsPage = serde_json::from_str(&response.text().await?)?;
+            models.extend(page.data);
+            match page.last_id {
+                Some(last) if page.has_more => after = Some(last),
+                _ => return Ok(models),
+            }
+        }
+    }
+
     /// Test the API key by sending a minimal request
     pub async fn test_connection(&self) -> Result<(), ClaudeError> {
         use super::models::{ClaudeModel, Message};
 
-        let request = CreateMessageRequest::new(ClaudeModel::Haiku45, vec![Message::user("Hi")])
+        let request = CreateMessageRequest::new(&ClaudeModel::Haiku45, vec![Message::user("Hi")])
             .with_max_tokens(10)
             .without_streaming();
 
@@ -143,6 +253,16 @@ impl ClaudeClient {
     }
 }
 
+/// Whether a failed request is worth retrying (rate limited or server error)
+fn is_retryable(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
+
+/// Exponential backoff before a retry: 1s, 2s, 4s, ...
+fn backoff_delay(attempt: u32) -> Duration {
+    BASE_RETRY_DELAY.saturating_mul(2u32.saturating_pow(attempt)).min(MAX_RETRY_DELAY)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,5 +271,28 @@ mod tests {
     fn client_creation() {
         let client = ClaudeClient::new("sk-ant-test-key".to_string());
         assert_eq!(client.api_key, "sk-ant-test-key");
+        assert_eq!(client.max_retries, DEFAULT_MAX_RETRIES);
+    }
+
+    #[test]
+    fn retries_rate_limits_and_server_errors_with_backoff() {
+        assert!(is_retryable(429));
+        assert!(is_retryable(529));
+        assert!(!is_retryable(400));
+        assert!(!is_retryable(401));
+
+        assert_eq!(backoff_delay(0), Duration::from_secs(1));
+        assert_eq!(backoff_delay(2), Duration::from_secs(4));
+        assert_eq!(backoff_delay(40), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn retry_status_message() {
+        let status =
+            RetryStatus { status: 429, attempt: 2, max_retries: 3, delay: Duration::from_secs(4) };
+        assert_eq!(status.message(), "Rate limited, retrying in 4s… (2/3)");
+
+        let status = RetryStatus { status: 503, ..status };
+        assert_eq!(status.message(), "Server error (503), retrying in 4s… (2/3)");
     }
 }
@@ -4,13 +4,22 @@
 //! for interacting with Claude's messages API.
 
 pub mod auth;
+pub mod buddy;
+pub mod citations;
 pub mod client;
 pub mod error;
 pub mod models;
+pub mod recap;
+pub mod registry;
 pub mod streaming;
+pub mod summary;
+pub mod teachback;
+pub mod usage;
 
 // Re-export commonly used types
 pub use auth::ApiKeyManager;
-pub use client::ClaudeClient;
+pub use client::{ClaudeClient, RetryStatus};
 pub use error::ClaudeError;
 pub use models::{ClaudeModel, CreateMessageRequest, Message, Role, StreamEvent};
+pub use registry::ModelList;
+pub use usage::UsageLog;
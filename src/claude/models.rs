@@ -1,9 +1,11 @@
 //! Data models for Claude API requests and responses
 
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
-/// Available Claude models
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+/// Claude models: the built-in defaults, or any model listed by the API
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub enum ClaudeModel {
     /// Claude Haiku 4.5 - fast and cost-effective ($1/$5 per MTok)
     #[default]
@@ -16,28 +18,89 @@ pub enum ClaudeModel {
     Sonnet45,
     /// Claude Opus 4.5 - most capable ($5/$25 per MTok)
     Opus45,
+    /// A model from the API's model list (`:claude-models`), by ID
+    Listed(Arc<str>),
+}
+
+/// How a model is saved: built-in models by name, as before listed models
+#[derive(Serialize, Deserialize)]
+enum StoredModel {
+    Haiku45,
+    Haiku3,
+    Sonnet4,
+    Sonnet45,
+    Opus45,
+    Listed(String),
+}
+
+impl Serialize for ClaudeModel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        StoredModel::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ClaudeModel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        StoredModel::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl From<StoredModel> for ClaudeModel {
+    fn from(stored: StoredModel) -> Self {
+        match stored {
+            StoredModel::Haiku45 => Self::Haiku45,
+            StoredModel::Haiku3 => Self::Haiku3,
+            StoredModel::Sonnet4 => Self::Sonnet4,
+            StoredModel::Sonnet45 => Self::Sonnet45,
+            StoredModel::Opus45 => Self::Opus45,
+            StoredModel::Listed(id) => Self::listed(&id),
+        }
+    }
+}
+
+impl From<&ClaudeModel> for StoredModel {
+    fn from(model: &ClaudeModel) -> Self {
+        match model {
+            ClaudeModel::Haiku45 => Self::Haiku45,
+            ClaudeModel::Haiku3 => Self::Haiku3,
+            ClaudeModel::Sonnet4 => Self::Sonnet4,
+            ClaudeModel::Sonnet45 => Self::Sonnet45,
+            ClaudeModel::Opus45 => Self::Opus45,
+            ClaudeModel::Listed(id) => Self::Listed(id.to_string()),
+        }
+    }
 }
 
 impl ClaudeModel {
     /// Get the API model identifier
-    pub fn model_id(&self) -> &'static str {
+    pub fn model_id(&self) -> &str {
         match self {
             Self::Haiku45 => "claude-haiku-4-5-20251001",
             Self::Haiku3 => "claude-3-haiku-20240307",
             Self::Sonnet4 => "claude-sonnet-4-20250514",
             Self::Sonnet45 => "claude-sonnet-4-5-20250929",
             Self::Opus45 => "claude-opus-4-5-20251101",
+            Self::Listed(id) => id,
         }
     }
 
     /// Get a human-readable display name
-    pub fn display_name(&self) -> &'static str {
+    pub fn display_name(&self) -> &str {
         match self {
             Self::Haiku45 => "Claude Haiku 4.5",
             Self::Haiku3 => "Claude Haiku 3",
             Self::Sonnet4 => "Claude Sonnet 4",
             Self::Sonnet45 => "Claude Sonnet 4.5",
             Self::Opus45 => "Claude Opus 4.5",
+            Self::Listed(id) => id,
+        }
+    }
+
+    /// The model with an API model ID: a built-in one if it has that ID
+    pub fn listed(id: &str) -> Self {
+        match Self::all().iter().find(|m| m.model_id() == id) {
+            Some(model) => model.clone(),
+            None => Self::Listed(id.into()),
         }
     }
 
@@ -63,7 +126,28 @@ impl ClaudeModel {
         }
     }
 
-    /// List all available models
+    /// Price per million tokens in USD as (input, output)
+    ///
+    /// The model list carries no prices, so a listed model is priced as the
+    /// latest built-in model of its family.
+    pub fn pricing(&self) -> (f64, f64) {
+        match self {
+            Self::Haiku45 => (1.0, 5.0),
+            Self::Haiku3 => (0.25, 1.25),
+            Self::Sonnet4 | Self::Sonnet45 => (3.0, 15.0),
+            Self::Opus45 => (5.0, 25.0),
+            Self::Listed(id) if id.contains("haiku") => Self::Haiku45.pricing(),
+            Self::Listed(id) if id.contains("opus") => Self::Opus45.pricing(),
+            Self::Listed(_) => Self::Sonnet45.pricing(),
+        }
+    }
+
+    /// Whether `pricing` is the model's own, rather than its family's
+    pub fn has_own_pricing(&self) -> bool {
+        !matches!(self, Self::Listed(_))
+    }
+
+    /// List the built-in models
     pub fn all() -> &'static [ClaudeModel] {
         &[Self::Haiku45, Self::Haiku3, Self::Sonnet4, Self::Sonnet45, Self::Opus45]
     }
@@ -89,24 +173,106 @@ pub enum Role {
     Assistant,
 }
 
+/// Prompt caching directive on a content block
+///
+/// The API caches the request prefix up to and including the marked block, so
+/// later requests starting with the same text are cheaper and faster.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheControl {
+    /// Cache type (only "ephemeral" is supported)
+    #[serde(rename = "type")]
+    pub cache_type: String,
+}
+
+impl CacheControl {
+    /// Short-lived cache entry, refreshed each time it is read
+    pub fn ephemeral() -> Self {
+        Self { cache_type: "ephemeral".to_string() }
+    }
+}
+
+/// A text content block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextBlock {
+    /// Block type (always "text")
+    #[serde(rename = "type")]
+    pub block_type: String,
+    /// Text content
+    pub text: String,
+    /// Marks the end of a cacheable prefix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
+}
+
+impl TextBlock {
+    /// Create a plain text block
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { block_type: "text".to_string(), text: text.into(), cache_control: None }
+    }
+
+    /// Create a text block ending a cacheable prefix
+    pub fn cached(text: impl Into<String>) -> Self {
+        Self { cache_control: Some(CacheControl::ephemeral()), ..Self::new(text) }
+    }
+}
+
+/// Message or system prompt content: a plain string, or blocks when parts are cached
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    /// Plain text
+    Text(String),
+    /// Text blocks
+    Blocks(Vec<TextBlock>),
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
 /// A single message in the conversation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     /// Role of the message sender
     pub role: Role,
     /// Message content
-    pub content: String,
+    pub content: MessageContent,
 }
 
 impl Message {
     /// Create a new user message
     pub fn user(content: impl Into<String>) -> Self {
-        Self { role: Role::User, content: content.into() }
+        Self { role: Role::User, content: MessageContent::Text(content.into()) }
+    }
+
+    /// Create a user message whose context (e.g. section text) is cached
+    ///
+    /// Repeated questions about the same context, with the same system prompt,
+    /// read the context from the cache instead of paying for it again.
+    pub fn user_with_cached_context(
+        context: impl Into<String>,
+        question: impl Into<String>,
+    ) -> Self {
+        Self {
+            role: Role::User,
+            content: MessageContent::Blocks(vec![
+                TextBlock::cached(context),
+                TextBlock::new(question),
+            ]),
+        }
     }
 
     /// Create a new assistant message
     pub fn assistant(content: impl Into<String>) -> Self {
-        Self { role: Role::Assistant, content: content.into() }
+        Self { role: Role::Assistant, content: MessageContent::Text(content.into()) }
     }
 }
 
@@ -121,26 +287,36 @@ pub struct CreateMessageRequest {
     pub messages: Vec<Message>,
     /// Optional system prompt
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub system: Option<String>,
+    pub system: Option<MessageContent>,
+    /// Sampling temperature (0-1); the API default when None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
     /// Whether to stream the response
     pub stream: bool,
 }
 
 impl CreateMessageRequest {
     /// Create a new request with default settings
-    pub fn new(model: ClaudeModel, messages: Vec<Message>) -> Self {
+    pub fn new(model: &ClaudeModel, messages: Vec<Message>) -> Self {
         Self {
             model: model.model_id().to_string(),
             max_tokens: 4096,
             messages,
             system: None,
+            temperature: None,
             stream: true,
         }
     }
 
     /// Set the system prompt
     pub fn with_system(mut self, system: impl Into<String>) -> Self {
-        self.system = Some(system.into());
+        self.system = Some(MessageContent::Text(system.into()));
+        self
+    }
+
+    /// Set a system prompt that is cached with the request prefix
+    pub fn with_cached_system(mut self, system: impl Into<String>) -> Self {
+        self.system = Some(MessageContent::Blocks(vec![TextBlock::cached(system)]));
         self
     }
 
@@ -150,6 +326,12 @@ impl CreateMessageRequest {
         self
     }
 
+    /// Set the sampling temperature, or None for the API default
+    pub fn with_temperature(mut self, temperature: Option<f32>) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
     /// Disable streaming
     pub fn without_streaming(mut self) -> Self {
         self.stream = false;
@@ -164,6 +346,12 @@ pub enum StreamEvent {
     MessageStart {
         /// Unique message identifier
         id: String,
+        /// Input tokens used by the request (excluding cached tokens)
+        input_tokens: u32,
+        /// Input tokens written to the prompt cache
+        cache_creation_input_tokens: u32,
+        /// Input tokens read from the prompt cache
+        cache_read_input_tokens: u32,
     },
     /// Content block started
     ContentBlockStart,
@@ -178,6 +366,8 @@ pub enum StreamEvent {
     MessageDelta {
         /// Stop reason (if finished)
         stop_reason: Option<String>,
+        /// Cumulative output tokens generated
+        output_tokens: u32,
     },
     /// Message finished
     MessageStop,
@@ -216,10 +406,16 @@ pub struct ContentBlock {
 /// Token usage statistics
 #[derive(Debug, Clone, Deserialize)]
 pub struct Usage {
-    /// Input tokens used
+    /// Input tokens used (excluding cached tokens)
     pub input_tokens: u32,
     /// Output tokens generated
     pub output_tokens: u32,
+    /// Input tokens written to the prompt cache
+    #[serde(default)]
+    pub cache_creation_input_tokens: u32,
+    /// Input tokens read from the prompt cache
+    #[serde(default)]
+    pub cache_read_input_tokens: u32,
 }
 
 #[cfg(test)]
@@ -238,16 +434,67 @@ mod tests {
         assert_eq!(ClaudeModel::parse("unknown"), None);
     }
 
+    #[test]
+    fn listed_models_keep_their_id() {
+        let model = ClaudeModel::listed("claude-opus-9-20300101");
+        assert_eq!(model, ClaudeModel::listed("claude-opus-9-20300101"));
+        assert_eq!(model.model_id(), "claude-opus-9-20300101");
+        assert_eq!(model.pricing(), ClaudeModel::Opus45.pricing());
+        assert!(!model.has_own_pricing());
+        assert_eq!(ClaudeModel::listed("claude-3-haiku-20240307"), ClaudeModel::Haiku3);
+
+        let json = serde_json::to_string(&[ClaudeModel::Sonnet45, model.clone()]).unwrap();
+        assert_eq!(json, r#"["Sonnet45",{"Listed":"claude-opus-9-20300101"}]"#);
+        let read: Vec<ClaudeModel> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, [ClaudeModel::Sonnet45, model]);
+    }
+
     #[test]
     fn create_message_request() {
         let messages = vec![Message::user("Hello")];
-        let request = CreateMessageRequest::new(ClaudeModel::Haiku45, messages)
+        let request = CreateMessageRequest::new(&ClaudeModel::Haiku45, messages)
             .with_system("You are helpful")
             .with_max_tokens(1000);
 
         assert_eq!(request.model, "claude-haiku-4-5-20251001");
         assert_eq!(request.max_tokens, 1000);
-        assert_eq!(request.system, Some("You are helpful".to_string()));
+        assert_eq!(request.system, Some(MessageContent::from("You are helpful")));
         assert!(request.stream);
     }
+
+    #[test]
+    fn temperature_is_sent_only_when_set() {
+        let request = CreateMessageRequest::new(&ClaudeModel::Haiku45, vec![Message::user("Hi")]);
+        assert!(serde_json::to_value(&request).unwrap().get("temperature").is_none());
+
+        let json = serde_json::to_value(request.with_temperature(Some(0.5))).unwrap();
+        assert_eq!(json["temperature"], 0.5);
+    }
+
+    #[test]
+    fn cached_context_serializes_cache_control() {
+        let request = CreateMessageRequest::new(
+            &ClaudeModel::Haiku45,
+            vec![Message::user_with_cached_context("Section text", "Question?")],
+        )
+        .with_cached_system("Tutor");
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json["system"],
+            serde_json::json!([
+                { "type": "text", "text": "Tutor", "cache_control": { "type": "ephemeral" } }
+            ])
+        );
+        assert_eq!(
+            json["messages"][0]["content"],
+            serde_json::json!([
+                { "type": "text", "text": "Section text", "cache_control": { "type": "ephemeral" } },
+                { "type": "text", "text": "Question?" }
+            ])
+        );
+
+        let plain = serde_json::to_value(Message::user("Hi")).unwrap();
+        assert_eq!(plain["content"], "Hi");
+    }
 }
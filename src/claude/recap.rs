@@ -0,0 +1,100 @@
+//! Chapter office hours
+//!
+//! After finishing a chapter, `:recap` has Claude ask the reader to explain the
+//! chapter's key ideas in their own words (the Feynman technique). The reader's
+//! explanation is then critiqued, and the whole exchange is kept as a note.
+
+/// An office hours exchange in progress
+#[derive(Debug, Clone, Default)]
+pub struct RecapSession {
+    /// Book the chapter belongs to
+    pub book_id: String,
+    /// Chapter path
+    pub chapter_path: String,
+    /// Chapter title
+    pub chapter_title: String,
+    /// Section the resulting note is attached to (the chapter's first section)
+    pub note_section_path: String,
+    /// Claude's opening questions, once received
+    pub questions: String,
+    /// The reader's explanation, while it is being critiqued
+    pub explanation: Option<String>,
+}
+
+/// Build the prompt asking Claude to open office hours for a chapter
+pub fn opening_prompt(chapter_title: &str, content: &str) -> String {
+    format!(
+        "Here is a chapter titled \"{}\" from a technical book:\n\n{}\n\n\
+         I have just finished reading it. Run office hours using the Feynman technique: \
+         pick the two or three most important ideas of the chapter and ask me to explain \
+         them back in my own words, as if teaching someone new to the subject. \
+         Ask only the questions, as a short numbered list. Do not answer them or \
+         summarize the chapter.",
+        chapter_title, content
+    )
+}
+
+/// Build the prompt asking Claude to critique the reader's explanation
+pub fn critique_prompt(
+    chapter_title: &str,
+    content: &str,
+    questions: &str,
+    explanation: &str,
+) -> String {
+    format!(
+        "Here is a chapter titled \"{}\" from a technical book:\n\n{}\n\n\
+         You asked me to explain its key ideas in my own words:\n\n{}\n\n\
+         My explanation:\n\n{}\n\n\
+         Critique my explanation in markdown with exactly these sections:\n\
+         ## What you got right\nA bulleted list.\n\
+         ## Gaps and misconceptions\nA bulleted list of anything missing, vague or wrong, \
+         citing what the chapter actually says.\n\
+         ## Try again\nOne follow-up question targeting the weakest part.\n\
+         Be direct and concise and do not add any other sections.",
+        chapter_title,
+        content,
+        questions.trim(),
+        explanation.trim()
+    )
+}
+
+/// Note content recording a finished exchange
+pub fn recap_note(
+    chapter_title: &str,
+    questions: &str,
+    explanation: &str,
+    critique: &str,
+) -> String {
+    format!(
+        "**Office hours: {}**\n\n{}\n\n**My explanation:**\n\n{}\n\n**Critique:**\n\n{}",
+        chapter_title,
+        questions.trim(),
+        explanation.trim(),
+        critique.trim()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn critique_prompt_includes_exchange() {
+        let prompt =
+            critique_prompt("Ownership", "Values have owners.", "1. What is a move?", " Copies ");
+        assert!(prompt.contains("\"Ownership\""));
+        assert!(prompt.contains("1. What is a move?"));
+        assert!(prompt.contains("My explanation:\n\nCopies\n\n"));
+        assert!(prompt.contains("## Gaps and misconceptions"));
+    }
+
+    #[test]
+    fn recap_note_keeps_questions_explanation_and_critique() {
+        let note = recap_note("Ownership", "1. Moves?\n", "They copy.", "\nNot quite.\n");
+        assert_eq!(
+            note,
+            "**Office hours: Ownership**\n\n1. Moves?\n\n**My explanation:**\n\nThey copy.\n\n\
+             **Critique:**\n\nNot quite."
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Models available to the API key
+//!
+//! `:claude-models` fetches the list from the API's models endpoint and
+//! caches it in the data directory, so `:claude-model` accepts any listed
+//! model as well as the built-in names, without waiting for a release.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::models::ClaudeModel;
+use crate::config::{Config, datetime, persist};
+
+/// A model from the API's model list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListedModel {
+    /// API model identifier
+    pub id: String,
+    /// Human-readable name
+    pub display_name: String,
+    /// When the model was released (RFC 3339)
+    #[serde(default)]
+    pub created_at: String,
+}
+
+/// A page of the models endpoint's response
+#[derive(Debug, Deserialize)]
+pub(super) struct ModelsPage {
+    pub data: Vec<ListedModel>,
+    #[serde(default)]
+    pub has_more: bool,
+    pub last_id: Option<String>,
+}
+
+/// The models last listed by the API
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelList {
+    /// Models in the order the API listed them (newest first)
+    pub models: Vec<ListedModel>,
+    /// Unix timestamp when fetched
+    pub fetched_at: i64,
+}
+
+impl ModelList {
+    /// Load the cached model list; empty if it has never been fetched
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        let loaded = persist::load(&path, |path| {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read model list from {:?}", path))?;
+            serde_json::from_str(&contents).with_context(|| "Failed to parse model list")
+        })?;
+        Ok(loaded.unwrap_or_default())
+    }
+
+    /// Save the model list
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create data directory {:?}", parent))?;
+        }
+        let contents =
+            serde_json::to_string_pretty(self).with_context(|| "Failed to serialize model list")?;
+        persist::write(&path, contents)
+            .with_context(|| format!("Failed to write model list to {:?}", path))
+    }
+
+    fn path() -> Result<PathBuf> {
+        Ok(Config::data_dir()?.join("models.json"))
+    }
+
+    /// A model list fetched now
+    pub fn fetched(models: Vec<ListedModel>) -> Self {
+        Self { models, fetched_at: datetime::now() }
+    }
+
+    /// The model a name refers to: a built-in name or ID, or a listed model's ID
+    pub fn resolve(&self, name: &str) -> Option<ClaudeModel> {
+        ClaudeModel::parse(name).or_else(|| {
+            self.models.iter().find(|m| m.id == name).map(|m| ClaudeModel::listed(&m.id))
+        })
+    }
+
+    /// A model's name: the API's display name if it's listed
+    pub fn display_name<'a>(&'a self, model: &'a ClaudeModel) -> &'a str {
+        match model {
+            ClaudeModel::Listed(id) => {
+                self.models.iter().find(|m| *m.id == **id).map_or(id, |m| &m.display_name)
+            }
+            _ => model.display_name(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_accepts_built_in_names_and_listed_ids() {
+        let page: ModelsPage = serde_json::from_str(
+            r#"{"data": [
+                {"type": "model", "id": "claude-sonnet-9-20300101", "display_name": "Claude Sonnet 9",
+                 "created_at": "2030-01-01T00:00:00Z"},
+                {"type": "model", "id": "claude-opus-4-5-20251101", "display_name": "Claude Opus 4.5",
+                 "created_at": "2025-11-01T00:00:00Z"}
+            ], "has_more": false, "first_id": "claude-sonnet-9-20300101", "last_id": null}"#,
+        )
+        .unwrap();
+        let list = ModelList::fetched(page.data);
+
+        let listed = list.resolve("claude-sonnet-9-20300101").unwrap();
+        assert_eq!(listed.model_id(), "claude-sonnet-9-20300101");
+        assert_eq!(list.display_name(&listed), "Claude Sonnet 9");
+        assert_eq!(list.resolve("claude-opus-4-5-20251101"), Some(ClaudeModel::Opus45));
+        assert_eq!(list.resolve("haiku"), Some(ClaudeModel::Haiku45));
+        assert_eq!(list.resolve("claude-sonnet-10"), None);
+    }
+}
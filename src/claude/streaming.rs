@@ -75,7 +75,14 @@ fn parse_event(event_type: &str, data: &str) -> Option<StreamEvent> {
         "message_start" => {
             let parsed: serde_json::Value = serde_json::from_str(data).ok()?;
             let id = parsed["message"]["id"].as_str()?.to_string();
-            Some(StreamEvent::MessageStart { id })
+            let usage = &parsed["message"]["usage"];
+            let tokens = |field: &str| usage[field].as_u64().unwrap_or(0) as u32;
+            Some(StreamEvent::MessageStart {
+                id,
+                input_tokens: tokens("input_tokens"),
+                cache_creation_input_tokens: tokens("cache_creation_input_tokens"),
+                cache_read_input_tokens: tokens("cache_read_input_tokens"),
+            })
         }
 
         "content_block_start" => Some(StreamEvent::ContentBlockStart),
@@ -91,7 +98,8 @@ fn parse_event(event_type: &str, data: &str) -> Option<StreamEvent> {
         "message_delta" => {
             let parsed: serde_json::Value = serde_json::from_str(data).ok()?;
             let stop_reason = parsed["delta"]["stop_reason"].as_str().map(|s| s.to_string());
-            Some(StreamEvent::MessageDelta { stop_reason })
+            let output_tokens = parsed["usage"]["output_tokens"].as_u64().unwrap_or(0);
+            Some(StreamEvent::MessageDelta { stop_reason, output_tokens: output_tokens as u32 })
         }
 
         "message_stop" => Some(StreamEvent::MessageStop),
@@ -132,7 +140,33 @@ mod tests {
         let event = parse_event("message_start", data);
         assert!(matches!(
             event,
-            Some(StreamEvent::MessageStart { id }) if id == "msg_123"
+            Some(StreamEvent::MessageStart { id, input_tokens: 10, .. }) if id == "msg_123"
+        ));
+    }
+
+    #[test]
+    fn parse_message_start_cache_usage() {
+        let data = r#"{"type":"message_start","message":{"id":"msg_1","usage":{"input_tokens":5,"cache_creation_input_tokens":2000,"cache_read_input_tokens":0,"output_tokens":1}}}"#;
+        let event = parse_event("message_start", data);
+        assert!(matches!(
+            event,
+            Some(StreamEvent::MessageStart {
+                input_tokens: 5,
+                cache_creation_input_tokens: 2000,
+                cache_read_input_tokens: 0,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_message_delta_usage() {
+        let data = r#"{"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":15}}"#;
+        let event = parse_event("message_delta", data);
+        assert!(matches!(
+            event,
+            Some(StreamEvent::MessageDelta { stop_reason: Some(reason), output_tokens: 15 })
+                if reason == "end_turn"
         ));
     }
 
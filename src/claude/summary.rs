@@ -0,0 +1,126 @@
+//! Chapter summaries
+//!
+//! Caches Claude-generated chapter summaries per book so `:summarize` can show
+//! them again without another request, and the CLAUDE.md export can include them.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, datetime, encryption, persist};
+
+/// A generated summary of one chapter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChapterSummary {
+    /// Chapter title when the summary was generated
+    pub title: String,
+    /// Summary text (markdown)
+    pub text: String,
+    /// Unix timestamp when generated
+    pub created_at: i64,
+}
+
+/// Cached chapter summaries for a single book
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SummaryCache {
+    /// Summaries keyed by chapter path
+    pub chapters: BTreeMap<String, ChapterSummary>,
+}
+
+impl SummaryCache {
+    /// Load the summaries cached for a book
+    pub fn load(book_id: &str) -> Result<Self> {
+        let path = Self::cache_path(book_id)?;
+
+        let loaded = persist::load(&path, |path| {
+            let contents = encryption::read(path)
+                .with_context(|| format!("Failed to read summaries from {:?}", path))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse summaries for {}", book_id))
+        })?;
+        Ok(loaded.unwrap_or_default())
+    }
+
+    /// Save the summaries for a book
+    pub fn save(&self, book_id: &str) -> Result<()> {
+        let path = Self::cache_path(book_id)?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create summaries directory {:?}", parent))?;
+        }
+
+        let contents =
+            serde_json::to_string_pretty(self).with_context(|| "Failed to serialize summaries")?;
+
+        encryption::write(&path, &contents)
+            .with_context(|| format!("Failed to write summaries to {:?}", path))?;
+
+        Ok(())
+    }
+
+    /// Get summaries cache path for a book
+    fn cache_path(book_id: &str) -> Result<PathBuf> {
+        Ok(Self::cache_dir()?.join(format!("{}.json", book_id)))
+    }
+
+    /// Directory holding every book's summaries
+    pub fn cache_dir() -> Result<PathBuf> {
+        Ok(Config::data_dir()?.join("summaries"))
+    }
+
+    /// Cached summary for a chapter
+    pub fn get(&self, chapter_path: &str) -> Option<&ChapterSummary> {
+        self.chapters.get(chapter_path)
+    }
+
+    /// Store (or replace) a chapter's summary
+    pub fn insert(&mut self, chapter_path: &str, title: &str, text: &str) {
+        let created_at = datetime::now();
+        self.chapters.insert(
+            chapter_path.to_string(),
+            ChapterSummary { title: title.to_string(), text: text.to_string(), created_at },
+        );
+    }
+}
+
+/// Build the prompt asking Claude to summarize a chapter
+pub fn summary_prompt(chapter_title: &str, content: &str) -> String {
+    format!(
+        "Here is a chapter titled \"{}\" from a technical book:\n\n{}\n\n\
+         Summarize this chapter in markdown with exactly these sections:\n\
+         ## Key concepts\nA bulleted list of the main ideas, one line each.\n\
+         ## Definitions\nA bulleted list of important terms as `term`: definition.\n\
+         ## Code takeaways\nA bulleted list of practical lessons from the code examples \
+         (write \"None\" if the chapter has no code).\n\
+         Be concise and do not add any other sections.",
+        chapter_title, content
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_replaces_existing_summary() {
+        let mut cache = SummaryCache::default();
+        cache.insert("ch01", "Ownership", "old");
+        cache.insert("ch01", "Ownership", "new");
+
+        assert_eq!(cache.chapters.len(), 1);
+        assert_eq!(cache.get("ch01").map(|s| s.text.as_str()), Some("new"));
+        assert!(cache.get("ch02").is_none());
+    }
+
+    #[test]
+    fn prompt_requests_structured_sections() {
+        let prompt = summary_prompt("Ownership", "Values have owners.");
+        assert!(prompt.contains("\"Ownership\""));
+        assert!(prompt.contains("## Key concepts"));
+        assert!(prompt.contains("## Definitions"));
+        assert!(prompt.contains("## Code takeaways"));
+    }
+}
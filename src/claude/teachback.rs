@@ -0,0 +1,82 @@
+//! Teach-back
+//!
+//! After finishing a section, `:teach` has the reader explain it in their own
+//! words. Claude grades the explanation against the section, pointing out
+//! gaps and misconceptions; the feedback is kept as a note, and a passing
+//! grade counts toward the reader's mastery of the book.
+
+/// Lowest grade, out of 5, that counts as a successful teach-back
+pub const PASS_GRADE: u8 = 4;
+
+/// An explanation waiting for Claude's grade
+#[derive(Debug, Clone, Default)]
+pub struct TeachBack {
+    /// Book the section belongs to
+    pub book_id: String,
+    /// Section explained
+    pub section_path: String,
+    /// Section title
+    pub section_title: String,
+    /// The reader's explanation
+    pub explanation: String,
+}
+
+/// Build the prompt asking Claude to grade the reader's explanation of a section
+pub fn grading_prompt(section_title: &str, content: &str, explanation: &str) -> String {
+    format!(
+        "Here is a section titled \"{}\" from a technical book:\n\n{}\n\n\
+         I have just finished reading it. Here is my explanation of it in my own words, \
+         as if teaching someone new to the subject:\n\n{}\n\n\
+         Grade my explanation against the section in markdown with exactly these sections:\n\
+         ## What you got right\nA bulleted list.\n\
+         ## Gaps and misconceptions\nA bulleted list of anything missing, vague or wrong, \
+         citing what the section actually says.\n\
+         Then end with a single line `Grade: N/5`, where 5 means I could teach the section \
+         and 1 means I missed its point. Be direct and concise.",
+        section_title,
+        content,
+        explanation.trim()
+    )
+}
+
+/// The grade out of 5 at the end of Claude's feedback, if it gave one
+pub fn grade(feedback: &str) -> Option<u8> {
+    feedback.lines().rev().find_map(|line| {
+        let line = line.trim().trim_matches(['*', '`', '_']).trim();
+        let rest = line.strip_prefix("Grade:").or_else(|| line.strip_prefix("grade:"))?;
+        let (grade, _) = rest.trim().trim_matches(['*', '`']).split_once('/')?;
+        grade.trim().parse().ok().filter(|g| (1..=5).contains(g))
+    })
+}
+
+/// Note content recording a graded teach-back
+pub fn teach_back_note(section_title: &str, explanation: &str, feedback: &str) -> String {
+    format!(
+        "**Teach-back: {}**\n\n**My explanation:**\n\n{}\n\n**Feedback:**\n\n{}",
+        section_title,
+        explanation.trim(),
+        feedback.trim()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grade_reads_the_last_grade_line() {
+        assert_eq!(grade("## Gaps\n- none\n\nGrade: 4/5"), Some(4));
+        assert_eq!(grade("**Grade: 2/5**\n"), Some(2));
+        assert_eq!(grade("Grade: 9/5"), None);
+        assert_eq!(grade("Looks fine"), None);
+    }
+
+    #[test]
+    fn grading_prompt_includes_section_and_explanation() {
+        let prompt = grading_prompt("Moves", "Values have owners.", " A move copies ");
+        assert!(prompt.contains("\"Moves\""));
+        assert!(prompt.contains("Values have owners."));
+        assert!(prompt.contains("\n\nA move copies\n\n"));
+        assert!(prompt.contains("Grade: N/5"));
+    }
+}
@@ -0,0 +1,365 @@
+//! API usage and cost tracking
+//!
+//! Records token counts for each Claude request and aggregates estimated
+//! costs per day, per book, and per model. Records older than
+//! `ROLL_UP_AFTER_DAYS` are rolled up into one per day, model and book, so
+//! the log stays small.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::models::ClaudeModel;
+use crate::config::{Config, datetime, persist};
+
+/// Price of writing a token to the prompt cache, relative to a regular input token
+const CACHE_WRITE_PRICE: f64 = 1.25;
+
+/// Price of reading a token from the prompt cache, relative to a regular input token
+const CACHE_READ_PRICE: f64 = 0.1;
+
+/// Days after which records are rolled up into daily totals
+const ROLL_UP_AFTER_DAYS: i64 = 30;
+
+/// Token usage for a single request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Unix timestamp when the request completed
+    pub timestamp: i64,
+    /// Model used for the request
+    pub model: ClaudeModel,
+    /// Book open when the request was made (if any)
+    #[serde(default)]
+    pub book_id: Option<String>,
+    /// Input tokens used
+    pub input_tokens: u32,
+    /// Output tokens generated
+    pub output_tokens: u32,
+    /// Input tokens written to the prompt cache
+    #[serde(default)]
+    pub cache_write_tokens: u32,
+    /// Input tokens read from the prompt cache
+    #[serde(default)]
+    pub cache_read_tokens: u32,
+    /// Requests the record covers: one, or a day's once rolled up
+    #[serde(default = "one_request", skip_serializing_if = "is_one_request")]
+    pub requests: u32,
+}
+
+fn one_request() -> u32 {
+    1
+}
+
+fn is_one_request(requests: &u32) -> bool {
+    *requests == 1
+}
+
+impl UsageRecord {
+    /// Estimated cost of this request in USD
+    pub fn cost(&self) -> f64 {
+        let (input_price, output_price) = self.model.pricing();
+        let input = self.input_tokens as f64
+            + self.cache_write_tokens as f64 * CACHE_WRITE_PRICE
+            + self.cache_read_tokens as f64 * CACHE_READ_PRICE;
+        (input * input_price + self.output_tokens as f64 * output_price) / 1_000_000.0
+    }
+
+    /// Local day number (days since the Unix epoch) this record falls on
+    fn day(&self) -> i64 {
+        datetime::day_number(self.timestamp)
+    }
+
+    /// Add another record's requests and tokens to this one
+    fn absorb(&mut self, other: &UsageRecord) {
+        self.requests = self.requests.saturating_add(other.requests);
+        self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
+        self.cache_write_tokens = self.cache_write_tokens.saturating_add(other.cache_write_tokens);
+        self.cache_read_tokens = self.cache_read_tokens.saturating_add(other.cache_read_tokens);
+    }
+}
+
+/// Aggregated totals for a group of records
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UsageTotals {
+    /// Number of requests
+    pub requests: usize,
+    /// Total input tokens, including cached ones
+    pub input_tokens: u64,
+    /// Input tokens read from the prompt cache
+    pub cache_read_tokens: u64,
+    /// Total output tokens
+    pub output_tokens: u64,
+    /// Estimated cost in USD
+    pub cost: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, record: &UsageRecord) {
+        self.requests += record.requests as usize;
+        self.input_tokens += record.input_tokens as u64
+            + record.cache_write_tokens as u64
+            + record.cache_read_tokens as u64;
+        self.cache_read_tokens += record.cache_read_tokens as u64;
+        self.output_tokens += record.output_tokens as u64;
+        self.cost += record.cost();
+    }
+}
+
+/// Persistent log of API usage
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageLog {
+    /// All recorded requests, oldest first; old ones rolled up by day
+    pub records: Vec<UsageRecord>,
+}
+
+impl UsageLog {
+    /// Load usage log from disk
+    pub fn load() -> Result<Self> {
+        let path = Self::usage_path()?;
+
+        let loaded = persist::load(&path, |path| {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read usage from {:?}", path))?;
+            serde_json::from_str(&contents).with_context(|| "Failed to parse usage.json")
+        })?;
+        let mut log: Self = loaded.unwrap_or_default();
+        log.roll_up(datetime::today());
+        Ok(log)
+    }
+
+    /// Save usage log to disk
+    pub fn save(&self) -> Result<()> {
+        let path = Self::usage_path()?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create data directory {:?}", parent))?;
+        }
+
+        let contents =
+            serde_json::to_string_pretty(self).with_context(|| "Failed to serialize usage")?;
+
+        persist::write(&path, contents)
+            .with_context(|| format!("Failed to write usage to {:?}", path))?;
+
+        Ok(())
+    }
+
+    /// Get usage log path
+    fn usage_path() -> Result<PathBuf> {
+        Ok(Config::data_dir()?.join("usage.json"))
+    }
+
+    /// Record a completed request
+    pub fn record(
+        &mut self,
+        model: ClaudeModel,
+        book_id: Option<&str>,
+        input_tokens: u32,
+        output_tokens: u32,
+    ) {
+        self.record_cached(model, book_id, input_tokens, output_tokens, (0, 0));
+    }
+
+    /// Record a completed request that used the prompt cache
+    ///
+    /// `cache_tokens` are the input tokens (written to, read from) the cache.
+    pub fn record_cached(
+        &mut self,
+        model: ClaudeModel,
+        book_id: Option<&str>,
+        input_tokens: u32,
+        output_tokens: u32,
+        cache_tokens: (u32, u32),
+    ) {
+        self.records.push(UsageRecord {
+            timestamp: datetime::now(),
+            model,
+            book_id: book_id.map(str::to_string),
+            input_tokens,
+            output_tokens,
+            cache_write_tokens: cache_tokens.0,
+            cache_read_tokens: cache_tokens.1,
+            requests: 1,
+        });
+    }
+
+    /// Merge records from before `ROLL_UP_AFTER_DAYS` ago into one per day,
+    /// model and book, which keeps every total the same
+    fn roll_up(&mut self, today: i64) {
+        let cutoff = today - ROLL_UP_AFTER_DAYS;
+        let (old, recent): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.records).into_iter().partition(|r| r.day() < cutoff);
+        let mut rolled: Vec<UsageRecord> = Vec::new();
+        for record in old {
+            let same = |r: &&mut UsageRecord| {
+                r.day() == record.day() && r.model == record.model && r.book_id == record.book_id
+            };
+            match rolled.iter_mut().find(same) {
+                Some(day) => day.absorb(&record),
+                None => rolled.push(record),
+            }
+        }
+        rolled.extend(recent);
+        self.records = rolled;
+    }
+
+    /// Totals across all records
+    pub fn total(&self) -> UsageTotals {
+        self.totals_where(|_| true)
+    }
+
+    /// Totals for today
+    pub fn today(&self) -> UsageTotals {
+        let today = datetime::today();
+        self.totals_where(|r| r.day() == today)
+    }
+
+    /// Totals for a single book
+    pub fn for_book(&self, book_id: &str) -> UsageTotals {
+        self.totals_where(|r| r.book_id.as_deref() == Some(book_id))
+    }
+
+    /// Totals per model: the built-in ones in order, then others as first used
+    pub fn by_model(&self) -> Vec<(ClaudeModel, UsageTotals)> {
+        let mut models = ClaudeModel::all().to_vec();
+        for record in &self.records {
+            if !models.contains(&record.model) {
+                models.push(record.model.clone());
+            }
+        }
+        models
+            .into_iter()
+            .map(|model| {
+                let totals = self.totals_where(|r| r.model == model);
+                (model, totals)
+            })
+            .filter(|(_, totals)| totals.requests > 0)
+            .collect()
+    }
+
+    /// Totals per day as ("YYYY-MM-DD", totals), oldest first
+    pub fn by_day(&self) -> Vec<(String, UsageTotals)> {
+        let mut days: BTreeMap<i64, UsageTotals> = BTreeMap::new();
+        for record in &self.records {
+            days.entry(record.day()).or_default().add(record);
+        }
+        days.into_iter()
+            .map(|(day, totals)| (datetime::day_date(day).to_string(), totals))
+            .collect()
+    }
+
+    fn totals_where(&self, filter: impl Fn(&UsageRecord) -> bool) -> UsageTotals {
+        let mut totals = UsageTotals::default();
+        for record in self.records.iter().filter(|r| filter(r)) {
+            totals.add(record);
+        }
+        totals
+    }
+}
+
+/// Format a cost in USD for display
+pub fn format_cost(cost: f64) -> String {
+    if cost < 0.01 { format!("${:.4}", cost) } else { format!("${:.2}", cost) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(model: ClaudeModel, book: Option<&str>, timestamp: i64) -> UsageRecord {
+        UsageRecord {
+            timestamp,
+            model,
+            book_id: book.map(str::to_string),
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_write_tokens: 0,
+            cache_read_tokens: 0,
+            requests: 1,
+        }
+    }
+
+    #[test]
+    fn record_cost_uses_model_pricing() {
+        let haiku = record(ClaudeModel::Haiku45, None, 0);
+        assert!((haiku.cost() - 1.5).abs() < 1e-9);
+
+        let opus = record(ClaudeModel::Opus45, None, 0);
+        assert!((opus.cost() - 7.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cached_tokens_are_priced_relative_to_input() {
+        let cached = UsageRecord {
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_write_tokens: 1_000_000,
+            cache_read_tokens: 2_000_000,
+            ..record(ClaudeModel::Haiku45, None, 0)
+        };
+        assert!((cached.cost() - 1.45).abs() < 1e-9);
+
+        let log = UsageLog { records: vec![cached] };
+        assert_eq!(log.total().input_tokens, 3_000_000);
+        assert_eq!(log.total().cache_read_tokens, 2_000_000);
+    }
+
+    #[test]
+    fn aggregates_by_book_model_and_day() {
+        let log = UsageLog {
+            records: vec![
+                // Midday, so the local date is the same in any time zone
+                record(ClaudeModel::Haiku45, Some("rust"), 43_200),
+                record(ClaudeModel::Sonnet45, Some("rust"), 86_400 + 43_200),
+                record(ClaudeModel::Haiku45, Some("go"), 86_400 + 43_210),
+            ],
+        };
+
+        assert_eq!(log.total().requests, 3);
+        assert_eq!(log.for_book("rust").requests, 2);
+        assert_eq!(log.for_book("go").input_tokens, 1_000_000);
+
+        let models = log.by_model();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].0, ClaudeModel::Haiku45);
+        assert_eq!(models[0].1.requests, 2);
+
+        let days = log.by_day();
+        assert_eq!(days[0].0, "1970-01-01");
+        assert_eq!(days[1].0, "1970-01-02");
+        assert_eq!(days[1].1.requests, 2);
+    }
+
+    #[test]
+    fn old_records_roll_up_into_daily_totals() {
+        let mut log = UsageLog {
+            records: vec![
+                record(ClaudeModel::Haiku45, Some("rust"), 43_200),
+                record(ClaudeModel::Haiku45, Some("rust"), 43_300),
+                record(ClaudeModel::Haiku45, Some("go"), 43_400),
+                record(ClaudeModel::Haiku45, Some("rust"), 86_400 * 40 + 43_200),
+                record(ClaudeModel::Haiku45, Some("rust"), 86_400 * 40 + 43_300),
+            ],
+        };
+        let (total, days) = (log.total(), log.by_day());
+
+        log.roll_up(45);
+        log.roll_up(45);
+
+        assert_eq!(log.records.len(), 4);
+        assert_eq!(log.records[0].requests, 2);
+        assert_eq!(log.total(), total);
+        assert_eq!(log.by_day(), days);
+        assert_eq!(log.for_book("rust").requests, 4);
+    }
+
+    #[test]
+    fn format_cost_small_amounts() {
+        assert_eq!(format_cost(0.00123), "$0.0012");
+        assert_eq!(format_cost(1.5), "$1.50");
+    }
+}
@@ -0,0 +1,254 @@
+//! Date and time display
+//!
+//! Timestamps are stored as Unix seconds. This module turns them into local
+//! dates and times for display, bucketing by the local calendar day, and
+//! decides where weeks begin. Each setting in `DateTimeConfig` that is left
+//! unset follows the locale from `LC_ALL`, `LC_TIME` or `LANG`.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Seconds in a day
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Order and separators of dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DateFormat {
+    /// 2026-10-16
+    Iso,
+    /// 10/16/2026
+    MonthFirst,
+    /// 16/10/2026
+    DayFirst,
+    /// 16.10.2026
+    DayFirstDots,
+}
+
+/// 12 or 24-hour clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Clock {
+    /// 3:05 PM
+    #[serde(rename = "12h")]
+    TwelveHour,
+    /// 15:05
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+}
+
+/// Date and time display settings (unset fields follow the locale)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DateTimeConfig {
+    /// How dates are written
+    pub date_format: Option<DateFormat>,
+    /// 12 or 24-hour clock
+    pub clock: Option<Clock>,
+    /// First day of the week, e.g. "monday" or "sun"
+    pub week_start: Option<Weekday>,
+}
+
+/// Resolved date and time display settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTimeFormat {
+    /// How dates are written
+    pub date_format: DateFormat,
+    /// 12 or 24-hour clock
+    pub clock: Clock,
+    /// First day of the week
+    pub week_start: Weekday,
+}
+
+impl Default for DateTimeFormat {
+    fn default() -> Self {
+        Self::for_locale("")
+    }
+}
+
+impl DateTimeFormat {
+    /// Settings from config, filling unset fields from the environment's locale
+    pub fn from_config(config: &DateTimeConfig) -> Self {
+        let locale = ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        Self::for_locale(&locale).with_overrides(config)
+    }
+
+    /// Conventional settings for a locale such as "en_US.UTF-8"
+    pub fn for_locale(locale: &str) -> Self {
+        let name = locale.split(['.', '@']).next().unwrap_or_default();
+        let (language, region) = name.split_once(['_', '-']).unwrap_or((name, ""));
+
+        let date_format = match (language, region) {
+            ("en", "US" | "PH") => DateFormat::MonthFirst,
+            ("" | "C" | "POSIX" | "ja" | "zh" | "ko" | "sv" | "lt" | "hu", _) | ("en", "CA") => {
+                DateFormat::Iso
+            }
+            ("de" | "ru" | "pl" | "fi" | "cs" | "da" | "nb" | "nn" | "tr" | "uk" | "sk", _) => {
+                DateFormat::DayFirstDots
+            }
+            _ => DateFormat::DayFirst,
+        };
+        let clock = match (language, region) {
+            ("en", "US" | "CA" | "AU" | "NZ" | "PH" | "IN") | ("hi" | "ar", _) => Clock::TwelveHour,
+            _ => Clock::TwentyFourHour,
+        };
+        let week_start = match region {
+            "US" | "CA" | "MX" | "BR" | "JP" | "KR" | "TW" | "HK" | "IL" | "PH" | "IN" | "ZA" => {
+                Weekday::Sun
+            }
+            _ => Weekday::Mon,
+        };
+        Self { date_format, clock, week_start }
+    }
+
+    /// Replace settings with those given in config
+    pub fn with_overrides(self, config: &DateTimeConfig) -> Self {
+        Self {
+            date_format: config.date_format.unwrap_or(self.date_format),
+            clock: config.clock.unwrap_or(self.clock),
+            week_start: config.week_start.unwrap_or(self.week_start),
+        }
+    }
+
+    /// A date, e.g. "16/10/2026"
+    pub fn date(&self, date: NaiveDate) -> String {
+        let pattern = match self.date_format {
+            DateFormat::Iso => "%Y-%m-%d",
+            DateFormat::MonthFirst => "%m/%d/%Y",
+            DateFormat::DayFirst => "%d/%m/%Y",
+            DateFormat::DayFirstDots => "%d.%m.%Y",
+        };
+        date.format(pattern).to_string()
+    }
+
+    /// A date with its weekday, e.g. "Fri 16/10/2026"
+    pub fn weekday_date(&self, date: NaiveDate) -> String {
+        format!("{} {}", date.format("%a"), self.date(date))
+    }
+
+    /// A time of day, e.g. "15:05" or "3:05 PM"
+    pub fn time(&self, time: NaiveDateTime) -> String {
+        match self.clock {
+            Clock::TwelveHour => time.format("%-I:%M %p").to_string(),
+            Clock::TwentyFourHour => time.format("%H:%M").to_string(),
+        }
+    }
+
+    /// A date and time of day, e.g. "16/10/2026 15:05"
+    pub fn date_time(&self, time: NaiveDateTime) -> String {
+        format!("{} {}", self.date(time.date()), self.time(time))
+    }
+
+    /// A Unix timestamp as a local date and time
+    pub fn timestamp(&self, timestamp: i64) -> String {
+        self.date_time(local_time(timestamp))
+    }
+
+    /// How long ago a timestamp was, falling back to its date after a week
+    pub fn relative(&self, timestamp: i64, now: i64) -> String {
+        let elapsed = (now - timestamp).max(0);
+        if elapsed < 60 {
+            "just now".to_string()
+        } else if elapsed < 3600 {
+            format!("{}m ago", elapsed / 60)
+        } else if elapsed < SECONDS_PER_DAY {
+            format!("{}h ago", elapsed / 3600)
+        } else if elapsed < 7 * SECONDS_PER_DAY {
+            format!("{}d ago", elapsed / SECONDS_PER_DAY)
+        } else {
+            self.date(local_time(timestamp).date())
+        }
+    }
+
+    /// First day of the week containing a date
+    pub fn start_of_week(&self, date: NaiveDate) -> NaiveDate {
+        date.week(self.week_start).first_day()
+    }
+}
+
+/// Local date and time of a Unix timestamp
+pub fn local_time(timestamp: i64) -> NaiveDateTime {
+    match Local.timestamp_opt(timestamp, 0).earliest() {
+        Some(time) => time.naive_local(),
+        None => DateTime::from_timestamp(timestamp, 0).unwrap_or_default().naive_utc(),
+    }
+}
+
+/// Local calendar day of a timestamp, as days since 1970-01-01
+pub fn day_number(timestamp: i64) -> i64 {
+    let epoch = NaiveDate::default();
+    (local_time(timestamp).date() - epoch).num_days()
+}
+
+/// Date of a day number from `day_number`
+pub fn day_date(day: i64) -> NaiveDate {
+    NaiveDate::default() + chrono::TimeDelta::days(day)
+}
+
+/// Day number of a date
+pub fn date_day(date: NaiveDate) -> i64 {
+    (date - NaiveDate::default()).num_days()
+}
+
+/// Current Unix timestamp in seconds
+pub fn now() -> i64 {
+    Local::now().timestamp()
+}
+
+/// Today's local day number
+pub fn today() -> i64 {
+    day_number(now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn locale_conventions() {
+        let us = DateTimeFormat::for_locale("en_US.UTF-8");
+        let de = DateTimeFormat::for_locale("de_DE.UTF-8");
+        let posix = DateTimeFormat::for_locale("C");
+        let time = date(2026, 10, 16).and_hms_opt(15, 5, 0).unwrap();
+
+        assert_eq!(us.date_time(time), "10/16/2026 3:05 PM");
+        assert_eq!(de.date_time(time), "16.10.2026 15:05");
+        assert_eq!(posix.weekday_date(time.date()), "Fri 2026-10-16");
+        assert_eq!(DateTimeFormat::for_locale("en_GB").date(time.date()), "16/10/2026");
+        assert_eq!((us.week_start, de.week_start), (Weekday::Sun, Weekday::Mon));
+    }
+
+    #[test]
+    fn config_overrides_locale_and_sets_week_start() {
+        let config: DateTimeConfig =
+            serde_json::from_str(r#"{"date_format":"iso","clock":"24h","week_start":"sunday"}"#)
+                .unwrap();
+        let format = DateTimeFormat::for_locale("de_DE").with_overrides(&config);
+
+        assert_eq!(format.date(date(2026, 10, 16)), "2026-10-16");
+        assert_eq!(format.start_of_week(date(2026, 10, 16)), date(2026, 10, 11));
+        assert_eq!(
+            DateTimeFormat::for_locale("de_DE").start_of_week(date(2026, 10, 16)),
+            date(2026, 10, 12)
+        );
+    }
+
+    #[test]
+    fn relative_times_and_day_numbers() {
+        let format = DateTimeFormat::for_locale("C");
+        let now = 1_000_000;
+        assert_eq!(format.relative(now - 30, now), "just now");
+        assert_eq!(format.relative(now - 7200, now), "2h ago");
+        assert_eq!(format.relative(now - 3 * SECONDS_PER_DAY, now), "3d ago");
+
+        assert_eq!(day_date(20_742), date(2026, 10, 16));
+        assert_eq!(date_day(date(2024, 2, 29)), 19_782);
+    }
+}
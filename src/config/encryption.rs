@@ -0,0 +1,229 @@
+//! Encryption at rest for notes and Claude output
+//!
+//! With `encrypt_notes` on, the notes store and cached Claude summaries are
+//! written as age-encrypted files. The age key is generated on first use and
+//! kept in the system keyring (or taken from `SENSEI_NOTES_KEY` on machines
+//! without one), so nothing needs to be typed to unlock them.
+//!
+//! Reading is transparent: encrypted and plain files are both accepted
+//! whatever the setting, so turning encryption on or off never loses data.
+//! Changing the setting rewrites each file's backup too, so no plaintext copy
+//! is left behind.
+
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use age::secrecy::ExposeSecret;
+use age::x25519::Identity;
+use anyhow::{Context, Result, anyhow};
+
+/// Service name for keyring storage
+const SERVICE_NAME: &str = "sensei-tui";
+/// Entry name for the notes key
+const KEY_ENTRY: &str = "notes-key";
+/// Environment variable overriding the keyring
+const KEY_ENV: &str = "SENSEI_NOTES_KEY";
+/// Start of every age-encrypted file
+const AGE_HEADER: &[u8] = b"age-encryption.org/";
+
+/// Whether writes are encrypted
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Key loaded (or created) for this process
+static IDENTITY: OnceLock<Identity> = OnceLock::new();
+
+/// Turn encryption of newly written files on or off
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether newly written files are encrypted
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Whether file contents are age-encrypted
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(AGE_HEADER)
+}
+
+/// Read a protected file, decrypting it if needed
+pub fn read(path: &Path) -> Result<String> {
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    if !is_encrypted(&bytes) {
+        return String::from_utf8(bytes).with_context(|| format!("{:?} is not valid UTF-8", path));
+    }
+    let plaintext = decrypt(&bytes, identity(false)?)
+        .with_context(|| format!("Failed to decrypt {:?}", path))?;
+    String::from_utf8(plaintext).with_context(|| format!("{:?} is not valid UTF-8", path))
+}
+
+/// Write a protected file, encrypting it when encryption is on
+pub fn write(path: &Path, contents: &str) -> Result<()> {
+    super::persist::write(path, encode(contents, write_key()?)?)
+}
+
+/// Rewrite a protected file and its backup so they match the current setting
+pub fn rewrite(path: &Path) -> Result<()> {
+    rewrite_with(path, write_key()?)
+}
+
+fn rewrite_with(path: &Path, key: Option<&Identity>) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    super::persist::write(path, encode(&read(path)?, key)?)?;
+    // The backup now holds the version just replaced
+    let backup = super::persist::backup_path(path);
+    if backup.exists() {
+        super::persist::write_atomic(&backup, encode(&read(&backup)?, key)?)?;
+    }
+    Ok(())
+}
+
+/// The key new files are encrypted to, if encryption is on
+fn write_key() -> Result<Option<&'static Identity>> {
+    if is_enabled() { identity(true).map(Some) } else { Ok(None) }
+}
+
+/// File contents, encrypted to `key` if there is one
+fn encode(contents: &str, key: Option<&Identity>) -> Result<Vec<u8>> {
+    match key {
+        Some(identity) => encrypt(contents.as_bytes(), identity),
+        None => Ok(contents.as_bytes().to_vec()),
+    }
+}
+
+/// Encrypt bytes to a key
+pub fn encrypt(plaintext: &[u8], identity: &Identity) -> Result<Vec<u8>> {
+    age::encrypt(&identity.to_public(), plaintext).map_err(|e| anyhow!("Encryption failed: {}", e))
+}
+
+/// Decrypt bytes encrypted to a key
+pub fn decrypt(ciphertext: &[u8], identity: &Identity) -> Result<Vec<u8>> {
+    age::decrypt(identity, ciphertext).map_err(|e| anyhow!("{}", e))
+}
+
+/// The notes key, loading it once per process
+///
+/// With `create`, a new key is generated and stored when there is none yet.
+fn identity(create: bool) -> Result<&'static Identity> {
+    if let Some(identity) = IDENTITY.get() {
+        return Ok(identity);
+    }
+
+    let stored = match std::env::var(KEY_ENV) {
+        Ok(key) => Some(key),
+        Err(_) => get_stored_key()?,
+    };
+    let identity = match stored {
+        Some(key) => {
+            Identity::from_str(key.trim()).map_err(|e| anyhow!("Invalid notes key: {}", e))?
+        }
+        None if create => {
+            let identity = Identity::generate();
+            store_key(identity.to_string().expose_secret())?;
+            identity
+        }
+        None => {
+            return Err(anyhow!(
+                "No notes key in the keyring (set {} to the key used to encrypt)",
+                KEY_ENV
+            ));
+        }
+    };
+    Ok(IDENTITY.get_or_init(|| identity))
+}
+
+/// Get the notes key from the system keyring
+#[cfg(target_os = "macos")]
+fn get_stored_key() -> Result<Option<String>> {
+    let output = std::process::Command::new("security")
+        .args(["find-generic-password", "-s", SERVICE_NAME, "-a", KEY_ENTRY, "-w"])
+        .output()
+        .context("Failed to query keyring")?;
+
+    let key = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((output.status.success() && !key.is_empty()).then_some(key))
+}
+
+/// Get the notes key from the system keyring (non-macOS fallback using keyring crate)
+#[cfg(not(target_os = "macos"))]
+fn get_stored_key() -> Result<Option<String>> {
+    let entry = keyring::Entry::new(SERVICE_NAME, KEY_ENTRY).context("Failed to open keyring")?;
+    match entry.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(anyhow!("Failed to read notes key from keyring: {}", e)),
+    }
+}
+
+/// Store the notes key in the system keyring
+#[cfg(target_os = "macos")]
+fn store_key(key: &str) -> Result<()> {
+    let output = std::process::Command::new("security")
+        .args(["add-generic-password", "-s", SERVICE_NAME, "-a", KEY_ENTRY, "-w", key])
+        .output()
+        .context("Failed to store notes key in keyring")?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(anyhow!("Failed to store notes key in keyring: {}", stderr))
+    }
+}
+
+/// Store the notes key in the system keyring (non-macOS fallback)
+#[cfg(not(target_os = "macos"))]
+fn store_key(key: &str) -> Result<()> {
+    let entry = keyring::Entry::new(SERVICE_NAME, KEY_ENTRY).context("Failed to open keyring")?;
+    entry.set_password(key).map_err(|e| anyhow!("Failed to store notes key in keyring: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::persist;
+
+    #[test]
+    fn encrypt_round_trips_and_rejects_other_keys() {
+        let identity = Identity::generate();
+        let ciphertext = encrypt(b"{\"books\":{}}", &identity).unwrap();
+
+        assert!(is_encrypted(&ciphertext));
+        assert!(!is_encrypted(b"{\"books\":{}}"));
+        assert_eq!(decrypt(&ciphertext, &identity).unwrap(), b"{\"books\":{}}");
+        assert!(decrypt(&ciphertext, &Identity::generate()).is_err());
+    }
+
+    #[test]
+    fn read_accepts_plain_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.json");
+        std::fs::write(&path, "{}").unwrap();
+
+        assert_eq!(read(&path).unwrap(), "{}");
+    }
+
+    #[test]
+    fn rewrite_leaves_no_plaintext_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.json");
+        persist::write(&path, "{\"v\":1}").unwrap();
+        persist::write(&path, "{\"v\":2}").unwrap();
+        let identity = Identity::generate();
+
+        rewrite_with(&path, Some(&identity)).unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(dir.path()).unwrap().flatten().collect();
+        assert_eq!(files.len(), 2);
+        for file in files {
+            let bytes = std::fs::read(file.path()).unwrap();
+            assert!(is_encrypted(&bytes), "{:?} is plaintext", file.path());
+            assert_eq!(decrypt(&bytes, &identity).unwrap(), b"{\"v\":2}");
+        }
+    }
+}
@@ -1,14 +1,20 @@
 //! Configuration management for Sensei
 
+pub mod datetime;
+pub mod encryption;
+pub mod persist;
 pub mod progress;
 pub mod session;
 
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 
+use crate::learning::plan::StudyPlan;
+use crate::learning::schedule::{self, Fsrs, Scheduler, Sm2};
 use crate::theme::Theme;
 
 /// Application configuration
@@ -26,6 +32,461 @@ pub struct Config {
 
     /// Vim mode enabled
     pub vim_mode: bool,
+
+    /// What to mark when a section has been scrolled through to the end
+    #[serde(default)]
+    pub auto_mark: AutoMarkMode,
+
+    /// Percentage of a section's lines (0-100) that must have been on screen
+    /// before scrolling to the end triggers `auto_mark`
+    #[serde(default = "default_auto_mark_threshold")]
+    pub auto_mark_threshold: u8,
+
+    /// Speed-up for held j/k navigation keys
+    #[serde(default)]
+    pub key_acceleration: KeyAcceleration,
+
+    /// Text-to-speech command used by `:speak`
+    #[serde(default)]
+    pub tts: TtsConfig,
+
+    /// Claude assistant settings
+    #[serde(default)]
+    pub claude: ClaudeConfig,
+
+    /// Maximum width of the content text in columns, centered in the panel
+    /// (None fills the panel)
+    #[serde(default)]
+    pub text_width: Option<usize>,
+
+    /// Justification and hyphenation of wrapped text
+    #[serde(default)]
+    pub typography: Typography,
+
+    /// Read-only directory of books shared with a team (EPUB files and markdown
+    /// directories). Progress and notes stay in the local data directory.
+    #[serde(default)]
+    pub shared_library: Option<PathBuf>,
+
+    /// How highlights are styled in exported notes
+    #[serde(default)]
+    pub export: ExportConfig,
+
+    /// How images are drawn when the terminal has no graphics protocol
+    #[serde(default)]
+    pub image_fallback: ImageFallback,
+
+    /// Encrypt notes and cached Claude summaries at rest, with a key kept in
+    /// the system keyring
+    #[serde(default)]
+    pub encrypt_notes: bool,
+
+    /// How dates and times are shown, and where weeks start
+    #[serde(default)]
+    pub datetime: datetime::DateTimeConfig,
+
+    /// Study plans across books, in the order they were added
+    #[serde(default)]
+    pub plans: Vec<StudyPlan>,
+
+    /// When sections come up for review after their quiz
+    #[serde(default)]
+    pub review: ReviewConfig,
+
+    /// Minutes between automatic saves of the reading position (0 saves only
+    /// on changing section and on exit)
+    #[serde(default = "default_autosave_minutes")]
+    pub autosave_minutes: u32,
+
+    /// Programs that run code blocks, by language (e.g. "python"); the code
+    /// is written to the program's stdin
+    #[serde(default)]
+    pub code_runners: BTreeMap<String, CodeRunner>,
+
+    /// Language of the interface, e.g. "en" or "eo" (None follows the locale)
+    #[serde(default)]
+    pub ui_language: Option<String>,
+
+    /// Segments of the status bar above the command line
+    #[serde(default)]
+    pub statusbar: StatusBarConfig,
+}
+
+/// Claude assistant settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClaudeConfig {
+    /// Named personas mapping to the system prompt that sets Claude's voice
+    pub system_prompts: BTreeMap<String, String>,
+    /// Persona used when a book has no override
+    pub persona: String,
+    /// Per-book persona overrides (book ID -> persona name)
+    pub book_personas: HashMap<String, String>,
+    /// Language for generated explanations, quizzes and summaries (None follows the book)
+    pub language: Option<String>,
+    /// Times per second the response panel updates while Claude streams (0 = on every delta)
+    pub stream_fps: u32,
+    /// Second model asked by `:compare`, alongside the current one (e.g. "sonnet", "opus")
+    pub buddy_model: String,
+    /// Times a rate-limited (429) or failed (5xx) request is retried, with exponential backoff
+    pub max_retries: u32,
+    /// Longest answer, explanation or summary Claude may write, in tokens
+    pub max_tokens: u32,
+    /// Longest quiz or quiz explanation Claude may write, in tokens
+    pub quiz_max_tokens: u32,
+    /// Sampling temperature from 0 (focused) to 1 (varied); None uses the API default
+    pub temperature: Option<f32>,
+    /// Characters of a section sent with questions, explanations and quizzes
+    pub context_chars: usize,
+    /// Characters of a chapter sent for summaries, glossaries and office hours
+    pub chapter_context_chars: usize,
+    /// Ask for learning objectives when a section is first opened
+    pub auto_objectives: bool,
+    /// Near the end of a section, fetch the next section's quiz and its
+    /// chapter's summary in the background
+    pub prefetch: bool,
+    /// Spending today, in USD, beyond which nothing is prefetched and no
+    /// objectives are written automatically (0 = no limit)
+    pub prefetch_budget: f64,
+}
+
+impl Default for ClaudeConfig {
+    fn default() -> Self {
+        let system_prompts = [
+            (
+                "tutor",
+                "You are an expert tutor helping someone understand technical content from a \
+                 book. Explain concepts clearly and concisely.",
+            ),
+            (
+                "eli5",
+                "You are a patient teacher explaining technical content from a book to a \
+                 complete beginner. Use simple words, everyday analogies and short sentences, \
+                 and define any jargon you can't avoid.",
+            ),
+            (
+                "exam-prep",
+                "You are an exam coach helping someone prepare to be tested on technical content \
+                 from a book. Focus on key definitions, facts and common pitfalls, and point out \
+                 what is most likely to be examined.",
+            ),
+        ]
+        .into_iter()
+        .map(|(name, prompt)| (name.to_string(), prompt.to_string()))
+        .collect();
+
+        Self {
+            system_prompts,
+            persona: "tutor".to_string(),
+            book_personas: HashMap::new(),
+            language: None,
+            stream_fps: 10,
+            buddy_model: "sonnet".to_string(),
+            max_retries: crate::claude::client::DEFAULT_MAX_RETRIES,
+            max_tokens: 4096,
+            quiz_max_tokens: 2000,
+            temperature: None,
+            context_chars: 8000,
+            chapter_context_chars: 24_000,
+            auto_objectives: false,
+            prefetch: false,
+            prefetch_budget: 1.0,
+        }
+    }
+}
+
+impl ClaudeConfig {
+    /// Name of the persona to use for a book
+    pub fn persona_name(&self, book_id: Option<&str>) -> &str {
+        book_id.and_then(|id| self.book_personas.get(id)).unwrap_or(&self.persona)
+    }
+
+    /// System prompt for the persona used with a book
+    ///
+    /// Falls back to the built-in tutor prompt if the persona isn't defined.
+    pub fn system_prompt(&self, book_id: Option<&str>) -> String {
+        self.persona_prompt(self.persona_name(book_id))
+    }
+
+    /// System prompt for a named persona, or the built-in tutor prompt
+    pub fn persona_prompt(&self, name: &str) -> String {
+        self.system_prompts
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| Self::default().system_prompts.remove("tutor").unwrap_or_default())
+    }
+
+    /// Instruction added to prompts so responses use the configured language
+    pub fn language_instruction(&self) -> Option<String> {
+        self.language.as_deref().map(|language| {
+            format!(
+                "Write all of your responses in {}, whatever the language of the source \
+                 material. Keep code, identifiers and JSON keys unchanged.",
+                language
+            )
+        })
+    }
+
+    /// Time between response panel updates while streaming
+    pub fn stream_interval(&self) -> std::time::Duration {
+        if self.stream_fps == 0 {
+            std::time::Duration::ZERO
+        } else {
+            std::time::Duration::from_secs(1) / self.stream_fps
+        }
+    }
+}
+
+/// Export styling for each kind of highlight
+///
+/// Highlights are keyed by where their note came from ("user", "claude" or
+/// "quiz"); kinds missing from the table use the built-in style.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportConfig {
+    /// Markdown syntax for highlighted passages
+    pub markdown_highlight: MarkdownHighlight,
+    /// Style for each highlight kind
+    pub highlights: BTreeMap<String, HighlightStyle>,
+}
+
+/// How one kind of highlight looks in exports
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighlightStyle {
+    /// Prefix for the passage in markdown exports (empty for none)
+    pub emoji: String,
+    /// CSS class of the highlight in HTML exports
+    pub css_class: String,
+    /// Background color of the highlight in HTML exports
+    pub color: String,
+}
+
+/// Markdown syntax for highlighted passages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkdownHighlight {
+    /// A blockquote (`> passage`)
+    #[default]
+    Quote,
+    /// Obsidian highlight syntax (`==passage==`)
+    Obsidian,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        let highlights =
+            [("user", "🖍️", "#fff1a8"), ("claude", "🤖", "#d6e8fb"), ("quiz", "🎯", "#d4f2dc")]
+                .into_iter()
+                .map(|(kind, emoji, color)| {
+                    let style = HighlightStyle {
+                        emoji: emoji.to_string(),
+                        css_class: kind.to_string(),
+                        color: color.to_string(),
+                    };
+                    (kind.to_string(), style)
+                })
+                .collect();
+
+        Self { markdown_highlight: MarkdownHighlight::Quote, highlights }
+    }
+}
+
+impl ExportConfig {
+    /// Style for a highlight kind, falling back to the built-in style
+    pub fn highlight_style(&self, kind: &str) -> HighlightStyle {
+        self.highlights
+            .get(kind)
+            .cloned()
+            .unwrap_or_else(|| Self::default().highlights.remove(kind).unwrap_or_default())
+    }
+}
+
+/// Acceleration applied while a navigation key is held down
+///
+/// Held keys arrive as a rapid series of presses; once presses keep coming
+/// within `repeat_window_ms` of each other, each step scrolls further.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyAcceleration {
+    /// Whether held keys speed up (false = constant speed)
+    pub enabled: bool,
+    /// Maximum gap between presses (ms) for them to count as a held key
+    pub repeat_window_ms: u64,
+    /// Number of repeats before the speed steps up again
+    pub repeats_per_step: u32,
+    /// Upper bound on the speed multiplier
+    pub max_multiplier: u32,
+}
+
+/// Text-to-speech backend configuration
+///
+/// Each sentence is passed as the final argument to `command`, so any
+/// program (or script wrapping a TTS API) that speaks its argument works.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TtsConfig {
+    /// Program to run (e.g. "say" or "espeak")
+    pub command: String,
+    /// Extra arguments placed before the text (e.g. ["-r", "200"])
+    pub args: Vec<String>,
+}
+
+/// A program that runs a code block
+///
+/// The code is written to its stdin, so interpreters take it directly
+/// (`python3 -`) and a script can wrap a compiler or post the code to an
+/// online playground.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeRunner {
+    /// Program to run (e.g. "python3")
+    pub command: String,
+    /// Arguments to pass (e.g. ["-"])
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        let command = if cfg!(target_os = "macos") { "say" } else { "espeak" };
+        Self { command: command.to_string(), args: Vec::new() }
+    }
+}
+
+impl Default for KeyAcceleration {
+    fn default() -> Self {
+        Self { enabled: true, repeat_window_ms: 120, repeats_per_step: 8, max_multiplier: 6 }
+    }
+}
+
+/// Progress to record automatically when a section is scrolled through
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoMarkMode {
+    /// Never mark sections automatically
+    #[default]
+    Off,
+    /// Mark the section as viewed
+    Viewed,
+    /// Mark the section as complete
+    Complete,
+}
+
+/// How wrapped text is laid out in the content panel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Typography {
+    /// Stretch the spaces between words so wrapped lines end flush right
+    pub justify: bool,
+    /// Break words that don't fit at the end of a line: identifiers after
+    /// `::`, `_`, `.` or `/` and at camelCase humps, other words between
+    /// syllables with a hyphen
+    pub hyphenate: bool,
+}
+
+/// Spaced-repetition review settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewConfig {
+    /// Scheduling algorithm
+    pub algorithm: ReviewAlgorithm,
+    /// Probability of recall FSRS schedules reviews at (0.5-0.99)
+    pub desired_retention: f64,
+    /// FSRS weights, set by `:review-tune` (None uses the defaults)
+    pub fsrs_weights: Option<Vec<f64>>,
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self { algorithm: ReviewAlgorithm::default(), desired_retention: 0.9, fsrs_weights: None }
+    }
+}
+
+impl ReviewConfig {
+    /// FSRS with the configured weights and retention
+    ///
+    /// Weights of the wrong length (e.g. from another FSRS version) are ignored.
+    pub fn fsrs(&self) -> Fsrs {
+        let weights = self
+            .fsrs_weights
+            .as_deref()
+            .and_then(|w| w.try_into().ok())
+            .unwrap_or(schedule::FSRS_DEFAULT_WEIGHTS);
+        Fsrs { weights, desired_retention: self.desired_retention }
+    }
+
+    /// The configured scheduler
+    pub fn scheduler(&self) -> Box<dyn Scheduler> {
+        match self.algorithm {
+            ReviewAlgorithm::Sm2 => Box::new(Sm2),
+            ReviewAlgorithm::Fsrs => Box::new(self.fsrs()),
+        }
+    }
+}
+
+/// Spaced-repetition algorithm
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewAlgorithm {
+    /// SuperMemo 2
+    #[default]
+    Sm2,
+    /// Free Spaced Repetition Scheduler
+    Fsrs,
+}
+
+/// Status bar segments, each shown only when turned on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusBarConfig {
+    /// Show the status bar at all (off by default, as it takes a line from
+    /// the content)
+    pub enabled: bool,
+    /// Chapter and section being read
+    pub section: bool,
+    /// Percentage of the book's sections completed
+    pub progress: bool,
+    /// Claude model questions go to
+    pub model: bool,
+    /// Whether Claude is writing a response
+    pub streaming: bool,
+    /// Time of day
+    pub time: bool,
+    /// Progress through the study plan step covering the book
+    pub goal: bool,
+}
+
+impl Default for StatusBarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            section: true,
+            progress: true,
+            model: true,
+            streaming: true,
+            time: false,
+            goal: true,
+        }
+    }
+}
+
+/// Text rendering of images for terminals without Kitty, Sixel or iTerm2 graphics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFallback {
+    /// Colored half-block characters, two pixels per cell
+    #[default]
+    Halfblocks,
+    /// Plain ASCII characters by ink density, for terminals without color
+    Ascii,
+}
+
+fn default_auto_mark_threshold() -> u8 {
+    80
+}
+
+fn default_autosave_minutes() -> u32 {
+    2
 }
 
 impl Default for Config {
@@ -35,15 +496,41 @@ impl Default for Config {
             custom_theme: None,
             animation_speed: 1.0,
             vim_mode: true,
+            auto_mark: AutoMarkMode::Off,
+            auto_mark_threshold: default_auto_mark_threshold(),
+            key_acceleration: KeyAcceleration::default(),
+            tts: TtsConfig::default(),
+            claude: ClaudeConfig::default(),
+            text_width: None,
+            typography: Typography::default(),
+            shared_library: None,
+            export: ExportConfig::default(),
+            image_fallback: ImageFallback::default(),
+            encrypt_notes: false,
+            datetime: datetime::DateTimeConfig::default(),
+            plans: Vec::new(),
+            review: ReviewConfig::default(),
+            autosave_minutes: default_autosave_minutes(),
+            code_runners: BTreeMap::new(),
+            ui_language: None,
+            statusbar: StatusBarConfig::default(),
         }
     }
 }
 
 impl Config {
+    /// Time between automatic session saves; None when turned off
+    pub fn autosave_interval(&self) -> Option<std::time::Duration> {
+        (self.autosave_minutes > 0)
+            .then(|| std::time::Duration::from_secs(u64::from(self.autosave_minutes) * 60))
+    }
+
     /// Load configuration from disk, or create default if not exists
     pub fn load() -> Result<Self> {
         let config_path = Self::config_path()?;
 
+        // No fallback to the backup: a mistake in a hand-edited config is
+        // reported rather than quietly replaced by older settings
         if config_path.exists() {
             let contents = std::fs::read_to_string(&config_path)
                 .with_context(|| format!("Failed to read config from {:?}", config_path))?;
@@ -67,7 +554,7 @@ impl Config {
         let contents =
             serde_json::to_string_pretty(self).with_context(|| "Failed to serialize config")?;
 
-        std::fs::write(&config_path, contents)
+        persist::write(&config_path, contents)
             .with_context(|| format!("Failed to write config to {:?}", config_path))?;
 
         Ok(())
@@ -101,6 +588,15 @@ impl Config {
     pub fn active_theme(&self) -> Theme {
         self.custom_theme.clone().unwrap_or_else(Theme::tokyo_night)
     }
+
+    /// A theme by name, ignoring case: a built-in one or the custom theme
+    pub fn theme_named(&self, name: &str) -> Option<Theme> {
+        self.custom_theme
+            .iter()
+            .cloned()
+            .chain([Theme::tokyo_night()])
+            .find(|theme| theme.name.eq_ignore_ascii_case(name.trim()))
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +609,16 @@ mod tests {
         assert_eq!(config.theme, "Tokyo Night");
     }
 
+    #[test]
+    fn theme_named_finds_built_in_and_custom_themes() {
+        let mut config = Config::default();
+        assert_eq!(config.theme_named("tokyo night").unwrap().name, "Tokyo Night");
+        assert!(config.theme_named("Paper").is_none());
+
+        config.custom_theme = Some(Theme { name: "Paper".into(), ..Theme::default() });
+        assert_eq!(config.theme_named("paper").unwrap().name, "Paper");
+    }
+
     #[test]
     fn default_config_has_vim_mode_enabled() {
         let config = Config::default();
@@ -132,5 +638,74 @@ mod tests {
         let config: Config = serde_json::from_str(json).unwrap();
         assert_eq!(config.theme, "Custom");
         assert!(!config.vim_mode);
+        assert_eq!(config.auto_mark, AutoMarkMode::Off);
+        assert_eq!(config.auto_mark_threshold, 80);
+        assert_eq!(config.key_acceleration, KeyAcceleration::default());
+    }
+
+    #[test]
+    fn claude_config_resolves_book_persona() {
+        let mut claude = ClaudeConfig::default();
+        claude.book_personas.insert("rust-book".into(), "eli5".into());
+
+        assert_eq!(claude.persona_name(None), "tutor");
+        assert_eq!(claude.persona_name(Some("other")), "tutor");
+        assert_eq!(claude.persona_name(Some("rust-book")), "eli5");
+        assert!(claude.system_prompt(Some("rust-book")).contains("beginner"));
+
+        claude.persona = "missing".into();
+        assert!(claude.system_prompt(None).contains("expert tutor"));
+    }
+
+    #[test]
+    fn claude_config_language_instruction() {
+        let mut claude = ClaudeConfig::default();
+        assert_eq!(claude.language_instruction(), None);
+
+        claude.language = Some("German".into());
+        let instruction = claude.language_instruction().unwrap();
+        assert!(instruction.contains("in German"));
+        assert!(instruction.contains("JSON keys"));
+    }
+
+    #[test]
+    fn claude_config_deserializes_request_limits() {
+        let json = r#"{"persona":"tutor","max_tokens":8000,"temperature":0.3}"#;
+        let claude: ClaudeConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(claude.max_tokens, 8000);
+        assert_eq!(claude.temperature, Some(0.3));
+        assert_eq!(claude.quiz_max_tokens, 2000);
+        assert_eq!(claude.context_chars, 8000);
+    }
+
+    #[test]
+    fn export_config_falls_back_to_builtin_styles() {
+        let json = r#"{"markdown_highlight":"obsidian",
+            "highlights":{"claude":{"emoji":"✨","css_class":"ai"}}}"#;
+        let export: ExportConfig = serde_json::from_str(json).unwrap();
+
+        assert_eq!(export.markdown_highlight, MarkdownHighlight::Obsidian);
+        let claude = export.highlight_style("claude");
+        assert_eq!((claude.emoji.as_str(), claude.css_class.as_str()), ("✨", "ai"));
+        assert_eq!(export.highlight_style("quiz").css_class, "quiz");
+        assert_eq!(export.highlight_style("unknown"), HighlightStyle::default());
+    }
+
+    #[test]
+    fn config_deserializes_partial_key_acceleration() {
+        let json = r#"{"theme":"Custom","animation_speed":1.0,"vim_mode":true,
+            "key_acceleration":{"enabled":false}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert!(!config.key_acceleration.enabled);
+        assert_eq!(config.key_acceleration.max_multiplier, 6);
+    }
+
+    #[test]
+    fn config_deserializes_auto_mark() {
+        let json = r#"{"theme":"Custom","animation_speed":1.0,"vim_mode":true,
+            "auto_mark":"complete","auto_mark_threshold":60}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.auto_mark, AutoMarkMode::Complete);
+        assert_eq!(config.auto_mark_threshold, 60);
     }
 }
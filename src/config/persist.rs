@@ -0,0 +1,181 @@
+//! Crash-safe saving of state files
+//!
+//! A state file is written to a temporary file beside it and renamed into
+//! place, so a crash mid-save leaves the previous version rather than a
+//! truncated one. The version replaced is kept as `<name>.bak` (the live file
+//! stays in place while it is copied), and loading
+//! falls back to it, with a warning, when the file is missing or unreadable.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Context, Result};
+
+/// Files restored from their backups since last asked
+static RESTORED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Temporary files written by this process, to keep their names unique
+static TEMP_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Write a file atomically, keeping the version it replaces as a backup
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let temp = write_temp(path, contents.as_ref())?;
+    if path.exists() {
+        let backup = backup_path(path);
+        back_up(path, &backup)
+            .with_context(|| format!("Failed to back up {:?} to {:?}", path, backup))?;
+    }
+    fs::rename(&temp, path).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Link (or, where links aren't supported, copy) a file to its backup,
+/// leaving the file itself in place
+fn back_up(path: &Path, backup: &Path) -> std::io::Result<()> {
+    match fs::remove_file(backup) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    fs::hard_link(path, backup).or_else(|_| fs::copy(path, backup).map(|_| ()))
+}
+
+/// Write a file atomically, without a backup, for files that can be rebuilt
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let temp = write_temp(path, contents.as_ref())?;
+    fs::rename(&temp, path).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Write contents to a temporary file beside `path`, flushed to disk
+///
+/// The name is unique to this process and call, so concurrent saves of the
+/// same file (from another instance or a background task) never share one.
+fn write_temp(path: &Path, contents: &[u8]) -> Result<PathBuf> {
+    let count = TEMP_COUNT.fetch_add(1, Ordering::Relaxed);
+    let temp = sibling(path, &format!("{}-{}.tmp", std::process::id(), count));
+    let written = File::create(&temp)
+        .with_context(|| format!("Failed to create {:?}", temp))
+        .and_then(|mut file| {
+            file.write_all(contents).with_context(|| format!("Failed to write {:?}", temp))?;
+            file.sync_all().with_context(|| format!("Failed to flush {:?}", temp))
+        });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    Ok(temp)
+}
+
+/// Path of the backup kept for a file
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, "bak")
+}
+
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+/// Read a file saved with [`write`] using `read`, falling back to its backup
+/// when the file is missing or `read` fails on it; None if neither exists
+///
+/// When both fail, the error is the file's own.
+pub fn load<T>(path: &Path, read: impl Fn(&Path) -> Result<T>) -> Result<Option<T>> {
+    let error = match path.exists() {
+        true => match read(path) {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) => Some(e),
+        },
+        false => None,
+    };
+    let backup = backup_path(path);
+    if !backup.exists() {
+        return error.map_or(Ok(None), Err);
+    }
+    match read(&backup) {
+        Ok(value) => {
+            match &error {
+                Some(e) => tracing::warn!("{:#}; restored it from {:?}", e, backup),
+                None => tracing::warn!("{:?} is missing; restored it from {:?}", path, backup),
+            }
+            if let Ok(mut restored) = RESTORED.lock() {
+                restored.push(path.to_path_buf());
+            }
+            Ok(Some(value))
+        }
+        Err(backup_error) => Err(error.unwrap_or(backup_error)),
+    }
+}
+
+/// Files restored from their backups since the last call
+pub fn take_restored() -> Vec<PathBuf> {
+    RESTORED.lock().map(|mut restored| std::mem::take(&mut *restored)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(path: &Path) -> Result<u32> {
+        Ok(fs::read_to_string(path)?.trim().parse()?)
+    }
+
+    #[test]
+    fn write_keeps_the_previous_version_as_a_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        assert_eq!(load(&path, read).unwrap(), None);
+
+        write(&path, "1").unwrap();
+        write(&path, "2").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "2");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "1");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+        assert_eq!(load(&path, read).unwrap(), Some(2));
+    }
+
+    #[test]
+    fn concurrent_writes_leave_a_whole_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        write(&path, "0").unwrap();
+
+        std::thread::scope(|scope| {
+            for n in 1..=8 {
+                let path = &path;
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        write(path, n.to_string()).unwrap();
+                        assert!(path.exists());
+                    }
+                });
+            }
+        });
+
+        let last = load(&path, read).unwrap().unwrap();
+        assert!((1..=8).contains(&last));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn load_falls_back_to_the_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        write(&path, "1").unwrap();
+        write(&path, "2").unwrap();
+
+        // Truncated by a crash
+        fs::write(&path, "").unwrap();
+        assert_eq!(load(&path, read).unwrap(), Some(1));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(load(&path, read).unwrap(), Some(1));
+        assert!(take_restored().contains(&path));
+
+        fs::write(backup_path(&path), "corrupt").unwrap();
+        fs::write(&path, "also corrupt").unwrap();
+        assert!(load(&path, read).is_err());
+    }
+}
@@ -1,12 +1,15 @@
 //! Progress tracking for book learning
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
-use super::Config;
+use super::{Config, persist};
+use crate::learning::rating::QuizRatings;
+use crate::learning::schedule::Review;
 
 /// Progress data for a single section
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -25,6 +28,110 @@ pub struct SectionProgress {
 
     /// Timestamp of last access
     pub last_accessed: Option<i64>,
+
+    /// First-attempt quiz results, oldest first, for scheduling reviews
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reviews: Vec<Review>,
+
+    /// Every quiz attempt, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quiz_history: Vec<QuizAttempt>,
+
+    /// Graded teach-backs of the section, oldest first (see `:teach`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub teach_backs: Vec<TeachBackGrade>,
+
+    /// Learning objectives checked off, by their text (see `:objective`)
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub objectives_met: BTreeSet<String>,
+}
+
+/// Claude's grade for one explanation of a section in the reader's own words
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TeachBackGrade {
+    /// When it was graded (Unix seconds)
+    pub at: i64,
+    /// Grade out of 5
+    pub grade: u8,
+}
+
+/// One attempt at a section's quiz
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuizAttempt {
+    /// When the attempt finished (Unix seconds)
+    pub at: i64,
+    /// Attempt number within the quiz (1 = first, then retries of missed questions)
+    pub attempt: u32,
+    /// Seconds from the first question appearing to the last answer
+    pub duration_secs: u64,
+    /// Whether each question was answered correctly, in question order
+    pub results: Vec<bool>,
+}
+
+impl QuizAttempt {
+    /// Questions answered correctly
+    pub fn correct(&self) -> usize {
+        self.results.iter().filter(|&&r| r).count()
+    }
+
+    /// Score as a percentage (0-100)
+    pub fn percent(&self) -> u8 {
+        (self.correct() * 100).checked_div(self.results.len()).unwrap_or(0) as u8
+    }
+}
+
+impl SectionProgress {
+    /// Fold in progress on the same section from elsewhere (another edition)
+    pub fn merge(&mut self, other: &SectionProgress) {
+        self.viewed |= other.viewed;
+        self.completed |= other.completed;
+        self.quiz_score = self.quiz_score.max(other.quiz_score);
+        self.questions_asked = self.questions_asked.max(other.questions_asked);
+        self.last_accessed = self.last_accessed.max(other.last_accessed);
+        self.merge_histories(other);
+    }
+
+    /// Add the other side's quiz, review and teach-back histories and met
+    /// objectives to these; merging the same progress twice adds nothing
+    fn merge_histories(&mut self, other: &SectionProgress) {
+        merge_history(&mut self.reviews, &other.reviews, |r| r.at);
+        merge_history(&mut self.quiz_history, &other.quiz_history, |a| a.at);
+        merge_history(&mut self.teach_backs, &other.teach_backs, |t| t.at);
+        self.objectives_met.extend(other.objectives_met.iter().cloned());
+    }
+
+    /// When the section was last read, quizzed, reviewed or taught back
+    pub fn updated_at(&self) -> Option<i64> {
+        let quizzed = self.quiz_history.iter().map(|a| a.at).max();
+        let reviewed = self.reviews.iter().map(|r| r.at).max();
+        let taught = self.teach_backs.iter().map(|t| t.at).max();
+        self.last_accessed.max(quizzed).max(reviewed).max(taught)
+    }
+
+    /// Fold in progress on the same section from another machine: the newer
+    /// side's state wins, and the quiz, review and teach-back histories and
+    /// met objectives of both are kept
+    pub fn merge_newest(&mut self, other: &SectionProgress) {
+        if other.updated_at() > self.updated_at() {
+            let older = std::mem::replace(self, other.clone());
+            self.merge_histories(&older);
+        } else {
+            self.merge_histories(other);
+        }
+    }
+
+    /// Mean score across quiz attempts; None if the quiz hasn't been taken
+    pub fn average_quiz_percent(&self) -> Option<f32> {
+        let total: u32 = self.quiz_history.iter().map(|a| u32::from(a.percent())).sum();
+        (!self.quiz_history.is_empty()).then(|| total as f32 / self.quiz_history.len() as f32)
+    }
+}
+
+/// Add entries to a history kept oldest first, dropping duplicates
+fn merge_history<T: Clone + PartialEq>(history: &mut Vec<T>, other: &[T], at: fn(&T) -> i64) {
+    history.extend_from_slice(other);
+    history.sort_by_key(at);
+    history.dedup();
 }
 
 /// Progress data for an entire book
@@ -41,6 +148,69 @@ pub struct BookProgress {
 
     /// Total time spent (seconds)
     pub total_time_seconds: u64,
+
+    /// Quiz question difficulty and the reader's mastery rating
+    #[serde(default, skip_serializing_if = "QuizRatings::is_empty")]
+    pub quiz_ratings: QuizRatings,
+}
+
+impl BookProgress {
+    /// Sections with quiz attempts, lowest average score first, as
+    /// (section path, progress)
+    pub fn weakest_sections(&self) -> Vec<(&str, &SectionProgress)> {
+        let mut sections: Vec<_> = self
+            .sections
+            .iter()
+            .filter(|(_, s)| !s.quiz_history.is_empty())
+            .map(|(path, s)| (path.as_str(), s))
+            .collect();
+        sections.sort_by(|a, b| {
+            let average = |s: &SectionProgress| s.average_quiz_percent().unwrap_or_default();
+            average(a.1).total_cmp(&average(b.1)).then(a.0.cmp(b.0))
+        });
+        sections
+    }
+
+    /// Move progress to the new paths of sections that moved, given as
+    /// old path -> new path; returns the number of sections moved
+    pub fn move_sections(&mut self, moves: &HashMap<&str, &str>) -> usize {
+        let mut moved = 0;
+        let mut sections: HashMap<String, SectionProgress> = HashMap::new();
+        for (path, section) in std::mem::take(&mut self.sections) {
+            let path = match moves.get(path.as_str()) {
+                Some(new_path) => {
+                    moved += 1;
+                    new_path.to_string()
+                }
+                None => path,
+            };
+            match sections.entry(path) {
+                Entry::Occupied(mut existing) => existing.get_mut().merge(&section),
+                Entry::Vacant(vacant) => {
+                    vacant.insert(section);
+                }
+            }
+        }
+        self.sections = sections;
+        moved
+    }
+
+    /// Fold in a book's progress from another machine, section by section
+    pub fn merge_newest(&mut self, other: &BookProgress) {
+        for (path, section) in &other.sections {
+            self.sections.entry(path.clone()).or_default().merge_newest(section);
+        }
+        self.total_time_seconds = self.total_time_seconds.max(other.total_time_seconds);
+        let rated = |r: &QuizRatings| r.answered + r.teach_backs + r.objectives_met;
+        if rated(&other.quiz_ratings) > rated(&self.quiz_ratings) {
+            self.quiz_ratings = other.quiz_ratings.clone();
+        }
+        let averages: Vec<f32> =
+            self.sections.values().filter_map(SectionProgress::average_quiz_percent).collect();
+        if !averages.is_empty() {
+            self.overall_quiz_average = Some(averages.iter().sum::<f32>() / averages.len() as f32);
+        }
+    }
 }
 
 /// All progress data
@@ -55,13 +225,12 @@ impl Progress {
     pub fn load() -> Result<Self> {
         let path = Self::progress_path()?;
 
-        if path.exists() {
-            let contents = std::fs::read_to_string(&path)
+        let loaded = persist::load(&path, |path| {
+            let contents = std::fs::read_to_string(path)
                 .with_context(|| format!("Failed to read progress from {:?}", path))?;
             serde_json::from_str(&contents).with_context(|| "Failed to parse progress.json")
-        } else {
-            Ok(Self::default())
-        }
+        })?;
+        Ok(loaded.unwrap_or_default())
     }
 
     /// Save progress to disk
@@ -76,12 +245,39 @@ impl Progress {
         let contents =
             serde_json::to_string_pretty(self).with_context(|| "Failed to serialize progress")?;
 
-        std::fs::write(&path, contents)
+        persist::write(&path, contents)
             .with_context(|| format!("Failed to write progress to {:?}", path))?;
 
         Ok(())
     }
 
+    /// Write progress to a file, to import on another machine
+    pub fn export(&self, path: &Path) -> Result<()> {
+        let contents =
+            serde_json::to_string_pretty(self).with_context(|| "Failed to serialize progress")?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write progress to {:?}", path))
+    }
+
+    /// Read progress exported with `export` (or a copy of progress.json)
+    pub fn import(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read progress from {:?}", path))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse progress from {:?}", path))
+    }
+
+    /// Fold in progress from another machine, keeping the newer state of each
+    /// section and every quiz attempt; returns the number of sections merged
+    pub fn merge(&mut self, other: &Progress) -> usize {
+        let mut sections = 0;
+        for (book_id, book) in &other.books {
+            self.book_mut(book_id).merge_newest(book);
+            sections += book.sections.len();
+        }
+        sections
+    }
+
     /// Get progress path
     fn progress_path() -> Result<PathBuf> {
         Ok(Config::data_dir()?.join("progress.json"))
@@ -149,4 +345,113 @@ mod tests {
         assert_eq!(weak.len(), 1);
         assert_eq!(weak[0].2, 50);
     }
+
+    #[test]
+    fn weakest_sections_orders_by_average_attempt_score() {
+        let attempt = |results: &[bool]| QuizAttempt {
+            at: 0,
+            attempt: 1,
+            duration_secs: 60,
+            results: results.to_vec(),
+        };
+        let mut book = BookProgress::default();
+        book.sections.insert(
+            "ch01".to_string(),
+            SectionProgress {
+                quiz_history: vec![attempt(&[true, false]), attempt(&[true, true])],
+                ..Default::default()
+            },
+        );
+        book.sections.insert(
+            "ch02".to_string(),
+            SectionProgress {
+                quiz_history: vec![attempt(&[false, true, false, false])],
+                ..Default::default()
+            },
+        );
+        book.sections.insert("ch03".to_string(), SectionProgress::default());
+
+        assert_eq!(attempt(&[true, true, false, true]).percent(), 75);
+        assert_eq!(book.sections["ch01"].average_quiz_percent(), Some(75.0));
+        let weakest: Vec<&str> = book.weakest_sections().iter().map(|(path, _)| *path).collect();
+        assert_eq!(weakest, ["ch02", "ch01"]);
+    }
+
+    #[test]
+    fn move_sections_carries_progress_to_new_paths() {
+        let mut book = BookProgress::default();
+        book.sections.insert(
+            "ch01/old".to_string(),
+            SectionProgress { completed: true, ..Default::default() },
+        );
+        book.sections.insert("ch02/s01".to_string(), SectionProgress::default());
+
+        let moves = HashMap::from([("ch01/old", "ch01/new")]);
+        assert_eq!(book.move_sections(&moves), 1);
+
+        assert!(!book.sections.contains_key("ch01/old"));
+        assert!(book.sections["ch01/new"].completed);
+        assert!(book.sections.contains_key("ch02/s01"));
+    }
+
+    #[test]
+    fn merge_keeps_the_furthest_progress() {
+        let mut new = SectionProgress { viewed: true, quiz_score: Some(40), ..Default::default() };
+        let old = SectionProgress {
+            completed: true,
+            quiz_score: Some(80),
+            questions_asked: 2,
+            ..Default::default()
+        };
+
+        new.merge(&old);
+        new.merge(&old);
+
+        assert!(new.viewed && new.completed);
+        assert_eq!((new.quiz_score, new.questions_asked), (Some(80), 2));
+    }
+
+    #[test]
+    fn merge_newest_takes_the_newer_section_and_keeps_both_histories() {
+        let attempt =
+            |at: i64| QuizAttempt { at, attempt: 1, duration_secs: 30, results: vec![true] };
+        let mut here = Progress::default();
+        here.book_mut("rust").sections.insert(
+            "ch01".to_string(),
+            SectionProgress {
+                completed: true,
+                quiz_score: Some(90),
+                last_accessed: Some(100),
+                quiz_history: vec![attempt(100)],
+                objectives_met: BTreeSet::from(["Explain moves".to_string()]),
+                ..Default::default()
+            },
+        );
+        let mut there = Progress::default();
+        there.book_mut("rust").sections.insert(
+            "ch01".to_string(),
+            SectionProgress {
+                viewed: true,
+                quiz_score: Some(40),
+                last_accessed: Some(50),
+                quiz_history: vec![attempt(50), attempt(200)],
+                ..Default::default()
+            },
+        );
+        there.book_mut("go").sections.insert("ch02".to_string(), SectionProgress::default());
+
+        assert_eq!(here.merge(&there), 2);
+        here.merge(&there);
+
+        // The attempt at 200 makes the other machine's section newer
+        let section = &here.books["rust"].sections["ch01"];
+        assert_eq!(
+            (section.viewed, section.completed, section.quiz_score),
+            (true, false, Some(40))
+        );
+        let attempts: Vec<i64> = section.quiz_history.iter().map(|a| a.at).collect();
+        assert_eq!(attempts, [50, 100, 200]);
+        assert_eq!(section.objectives_met, BTreeSet::from(["Explain moves".to_string()]));
+        assert!(here.books.contains_key("go"));
+    }
 }
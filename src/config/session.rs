@@ -2,13 +2,13 @@
 //!
 //! Stores UI state between sessions so users can resume where they left off.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
-use super::Config;
+use super::{Config, persist};
 
 /// Session state for a specific book
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -25,6 +25,63 @@ pub struct BookSession {
     pub content_scroll_offset: usize,
     /// Scroll offset in curriculum view
     pub curriculum_scroll_offset: usize,
+    /// Scroll offset each section was left at, by section path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub section_scroll: BTreeMap<String, usize>,
+    /// Marks set with `M{a-z}`: section path -> mark letter -> scroll offset
+    #[serde(default)]
+    pub marks: BTreeMap<String, BTreeMap<char, usize>>,
+    /// Where reading last stopped, for the last-read marker and `:resume`
+    #[serde(default)]
+    pub last_read: Option<LastRead>,
+}
+
+impl BookSession {
+    /// Move state kept by section path to the new paths of sections that
+    /// moved, given as old path -> new path
+    pub fn move_sections(&mut self, moves: &HashMap<&str, &str>) {
+        self.section_scroll = move_keys(std::mem::take(&mut self.section_scroll), moves);
+        self.marks = move_keys(std::mem::take(&mut self.marks), moves);
+        if let Some(last_read) = &mut self.last_read {
+            if let Some(new_path) = moves.get(last_read.section_path.as_str()) {
+                last_read.section_path = new_path.to_string();
+            }
+        }
+    }
+}
+
+/// A map keyed by section path with moved sections' entries under their new
+/// paths; where two land on one path, the section that moved there wins
+fn move_keys<V>(map: BTreeMap<String, V>, moves: &HashMap<&str, &str>) -> BTreeMap<String, V> {
+    let mut moved = BTreeMap::new();
+    let mut kept = Vec::new();
+    for (path, value) in map {
+        match moves.get(path.as_str()) {
+            Some(new_path) => {
+                moved.insert(new_path.to_string(), value);
+            }
+            None => kept.push((path, value)),
+        }
+    }
+    for (path, value) in kept {
+        moved.entry(path).or_insert(value);
+    }
+    moved
+}
+
+/// Line where reading stopped, anchored to a block so it survives reflow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastRead {
+    /// Section path
+    pub section_path: String,
+    /// Index of the block containing the line
+    pub block: usize,
+    /// Line within the block
+    pub line: usize,
+    /// Height of the block in lines when recorded
+    pub block_height: usize,
+    /// Content line when recorded, used before the section is laid out
+    pub offset: usize,
 }
 
 /// All session state
@@ -58,13 +115,12 @@ impl Session {
     pub fn load() -> Result<Self> {
         let path = Self::session_path()?;
 
-        if path.exists() {
-            let contents = std::fs::read_to_string(&path)
+        let loaded = persist::load(&path, |path| {
+            let contents = std::fs::read_to_string(path)
                 .with_context(|| format!("Failed to read session from {:?}", path))?;
             serde_json::from_str(&contents).with_context(|| "Failed to parse session.json")
-        } else {
-            Ok(Self::default())
-        }
+        })?;
+        Ok(loaded.unwrap_or_default())
     }
 
     /// Save session to disk
@@ -79,7 +135,7 @@ impl Session {
         let contents =
             serde_json::to_string_pretty(self).with_context(|| "Failed to serialize session")?;
 
-        std::fs::write(&path, contents)
+        persist::write(&path, contents)
             .with_context(|| format!("Failed to write session to {:?}", path))?;
 
         Ok(())
@@ -155,5 +211,37 @@ mod tests {
         let book = session.book("test").unwrap();
         assert_eq!(book.selected_index, 5);
         assert_eq!(book.content_scroll_offset, 100);
+        assert!(book.section_scroll.is_empty());
+    }
+
+    #[test]
+    fn section_scroll_round_trips() {
+        let mut session = Session::default();
+        session.book_mut("book").section_scroll.insert("ch01/s02".into(), 42);
+
+        let json = serde_json::to_string(&session).unwrap();
+        let session: Session = serde_json::from_str(&json).unwrap();
+        assert_eq!(session.book("book").unwrap().section_scroll["ch01/s02"], 42);
+    }
+
+    #[test]
+    fn move_sections_carries_session_state_to_new_paths() {
+        let mut book = BookSession::default();
+        book.section_scroll.insert("ch01/old".into(), 12);
+        book.section_scroll.insert("ch01/new".into(), 3);
+        book.marks.insert("ch01/old".into(), BTreeMap::from([('a', 7)]));
+        book.last_read = Some(LastRead {
+            section_path: "ch01/old".into(),
+            block: 2,
+            line: 0,
+            block_height: 1,
+            offset: 12,
+        });
+
+        book.move_sections(&HashMap::from([("ch01/old", "ch01/new")]));
+
+        assert_eq!(book.section_scroll, BTreeMap::from([("ch01/new".to_string(), 12)]));
+        assert_eq!(book.marks["ch01/new"][&'a'], 7);
+        assert_eq!(book.last_read.unwrap().section_path, "ch01/new");
     }
 }
@@ -0,0 +1,191 @@
+//! Anki flashcard export
+//!
+//! Turns notes and the reader's own quiz questions into Basic cards in Anki's
+//! tab-separated import format, with header lines that set the deck, note
+//! type and tag column so the file imports without further options:
+//!
+//! - Claude Q&A notes ask the question and answer with Claude's reply.
+//! - Highlight notes show the passage and answer with the note.
+//! - Section notes name the section and answer with the note.
+//! - Quiz questions from the question bank ask the question (with its
+//!   options, for multiple choice) and answer with the correct answer.
+//!
+//! Every card is tagged with the book ID and its section path.
+
+use std::fmt::Write as _;
+
+use anyhow::Result;
+
+use super::{ExportContext, Exporter};
+use crate::app::state::QuizQuestion;
+use crate::book::Book;
+use crate::learning::bank::QuestionBank;
+use crate::notes::{Note, NoteAnchor, NoteSource};
+
+/// Notes as an Anki deck
+pub struct AnkiExporter;
+
+impl Exporter for AnkiExporter {
+    fn name(&self) -> &'static str {
+        "anki"
+    }
+
+    fn extension(&self) -> &'static str {
+        "tsv"
+    }
+
+    fn description(&self) -> &'static str {
+        "Anki flashcards from notes and your quiz questions (File > Import)"
+    }
+
+    fn render(&self, ctx: &ExportContext) -> Result<Vec<u8>> {
+        Ok(generate(ctx.book, ctx.notes, ctx.questions).into_bytes())
+    }
+}
+
+/// One flashcard
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Card {
+    front: String,
+    back: String,
+}
+
+/// Generate the tab-separated deck for a book's notes and quiz questions,
+/// in book order
+pub fn generate(book: &Book, notes: &[Note], questions: &QuestionBank) -> String {
+    let mut out = String::from("#separator:tab\n#html:true\n#notetype:Basic\n");
+    let _ = writeln!(out, "#deck:{}", field(&book.metadata.title));
+    out.push_str("#tags column:3\n");
+
+    for chapter in &book.chapters {
+        for section in &chapter.sections {
+            let context = format!("{} › {}", chapter.title, section.title);
+            let tags = tags(&book.metadata.id, &section.path);
+            // A highlight without a note has nothing to ask
+            let cards = notes.iter().filter(|n| !n.is_highlight() || !n.content.trim().is_empty());
+            let cards = cards
+                .filter(|n| n.section_path == section.path)
+                .map(|note| card(note, &context))
+                .chain(questions.custom(&section.path).iter().map(|q| question_card(q, &context)));
+            for card in cards {
+                let _ = writeln!(out, "{}\t{}\t{}", card.front, card.back, tags);
+            }
+        }
+    }
+    out
+}
+
+/// The card for a note, its fields escaped for import
+fn card(note: &Note, context: &str) -> Card {
+    let passage = match &note.anchor {
+        NoteAnchor::TextRange { selected_text, .. } => {
+            Some(format!("<blockquote>{}</blockquote>", field(selected_text)))
+        }
+        NoteAnchor::Section => None,
+    };
+    let qa = (note.source == NoteSource::Claude).then(|| split_qa(&note.content)).flatten();
+
+    let (front, back) = match qa {
+        Some((question, answer)) => {
+            (format!("{}{}", passage.unwrap_or_default(), field(question)), field(answer))
+        }
+        None => (passage.unwrap_or_else(|| field(context)), field(note.content.trim())),
+    };
+    Card { front, back: back + &source(context) }
+}
+
+/// The card for one of the reader's quiz questions
+fn question_card(question: &QuizQuestion, context: &str) -> Card {
+    let mut front = field(question.question());
+    // The options of true or false go without saying
+    if let QuizQuestion::MultipleChoice { options, .. } = question {
+        for (letter, option) in ('a'..='z').zip(options) {
+            let _ = write!(front, "<br>{}) {}", letter, field(option));
+        }
+    }
+    Card { front, back: field(&question.answer_text()) + &source(context) }
+}
+
+/// The section a card comes from, shown under its answer
+fn source(context: &str) -> String {
+    format!("<br><small>{}</small>", field(context))
+}
+
+/// Question and answer of a Claude note ("**Q:** ...\n\n**A:** ...")
+fn split_qa(content: &str) -> Option<(&str, &str)> {
+    let rest = content.strip_prefix("**Q:**")?;
+    let (question, answer) = rest.split_once("\n\n**A:**")?;
+    Some((question.trim(), answer.trim()))
+}
+
+/// Text as an HTML field: escaped, with tabs removed and line breaks kept
+fn field(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\t', " ")
+        .replace('\n', "<br>")
+}
+
+/// Anki tags for a section: the book, and the section nested under it
+fn tags(book_id: &str, section_path: &str) -> String {
+    let clean = |s: &str| s.replace(char::is_whitespace, "_");
+    format!("{} {}::{}", clean(book_id), clean(book_id), clean(section_path).replace('/', "::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_writes_header_and_tagged_cards() {
+        let notes = vec![
+            Note::new_claude_note(
+                "test",
+                "ch01/s01",
+                "Why <T>?",
+                "Line one\nline two",
+                Some(0),
+                Some(0),
+                Some("let s2 = s1;"),
+            ),
+            Note::new_section_note("test", "ch01/s01", "Moves\tinvalidate"),
+            Note::new_section_note("test", "elsewhere", "Not in the book"),
+        ];
+
+        let mut questions = QuestionBank::default();
+        questions.save_question(
+            "ch01/s01",
+            None,
+            QuizQuestion::MultipleChoice {
+                question: "What does a move leave behind?".into(),
+                options: vec!["A copy".into(), "An invalid binding".into()],
+                correct_index: 1,
+            },
+        );
+        questions.save_question(
+            "ch01/s01",
+            None,
+            QuizQuestion::TrueFalse { question: "Moves copy the heap data".into(), answer: false },
+        );
+
+        let deck = generate(&Book::for_test(Vec::new()), &notes, &questions);
+        let lines: Vec<&str> = deck.lines().collect();
+
+        assert_eq!(lines[3], "#deck:Test Book");
+        assert_eq!(lines.len(), 9);
+        assert_eq!(
+            lines[5],
+            "<blockquote>let s2 = s1;</blockquote>Why &lt;T&gt;?\t\
+             Line one<br>line two<br><small>Ownership › Moves</small>\t\
+             test test::ch01::s01"
+        );
+        assert!(lines[6].starts_with("Ownership › Moves\tMoves invalidate<br>"));
+        assert_eq!(
+            lines[7],
+            "What does a move leave behind?<br>a) A copy<br>b) An invalid binding\t\
+             An invalid binding<br><small>Ownership › Moves</small>\ttest test::ch01::s01"
+        );
+        assert!(lines[8].starts_with("Moves copy the heap data\tFalse<br>"));
+    }
+}
@@ -1,3 +1,263 @@
 //! CLAUDE.md generation
+//!
+//! Produces a markdown study file for a book: reading progress, cached chapter
+//! summaries and notes, for use as context in other tools.
+//!
+//! The generated content lives between `<!-- sensei:begin -->` and
+//! `<!-- sensei:end -->` markers. Re-exporting into an existing file replaces
+//! only that region, so hand-written content around it is preserved.
 
-// TODO: Implement CLAUDE.md export
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::{ExportContext, Exporter};
+use crate::book::Book;
+use crate::claude::summary::SummaryCache;
+use crate::config::progress::BookProgress;
+use crate::config::{ExportConfig, MarkdownHighlight};
+use crate::notes::{Note, NoteAnchor};
+
+/// Generate CLAUDE.md content for a book
+pub fn generate(
+    book: &Book,
+    progress: Option<&BookProgress>,
+    notes: &[Note],
+    summaries: &SummaryCache,
+    styles: &ExportConfig,
+) -> String {
+    let mut out = format!("# {}\n\n", book.metadata.title);
+    if let Some(author) = &book.metadata.author {
+        let _ = writeln!(out, "By {}\n", author);
+    }
+    out.push_str("Study notes exported from sensei.\n\n");
+
+    write_progress(&mut out, book, progress);
+    write_summaries(&mut out, book, summaries);
+    write_notes(&mut out, book, notes, styles);
+
+    out
+}
+
+/// Start of the region managed by sensei
+pub const BEGIN_MARKER: &str = "<!-- sensei:begin -->";
+
+/// End of the region managed by sensei
+pub const END_MARKER: &str = "<!-- sensei:end -->";
+
+/// Write generated content to a CLAUDE.md file, keeping anything outside the managed region
+pub fn write(path: &Path, generated: &str) -> Result<()> {
+    let existing = if path.exists() {
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?
+    } else {
+        String::new()
+    };
+
+    std::fs::write(path, merge_managed(&existing, generated))
+        .with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Replace the managed region of `existing` with `generated`
+///
+/// If `existing` has no (well-formed) managed region, the region is appended
+/// after its content.
+pub fn merge_managed(existing: &str, generated: &str) -> String {
+    let region = format!("{}\n{}\n{}", BEGIN_MARKER, generated.trim_end(), END_MARKER);
+
+    let begin = existing.find(BEGIN_MARKER);
+    let end = begin.and_then(|b| existing[b..].find(END_MARKER).map(|e| b + e));
+    match (begin, end) {
+        (Some(begin), Some(end)) => {
+            let after = &existing[end + END_MARKER.len()..];
+            format!("{}{}{}", &existing[..begin], region, after)
+        }
+        _ if existing.trim().is_empty() => format!("{}\n", region),
+        _ => format!("{}\n\n{}\n", existing.trim_end(), region),
+    }
+}
+
+/// The CLAUDE.md study file as an export format
+pub struct ClaudeMdExporter;
+
+impl Exporter for ClaudeMdExporter {
+    fn name(&self) -> &'static str {
+        "claude-md"
+    }
+
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn description(&self) -> &'static str {
+        "CLAUDE.md study file with progress, summaries and notes"
+    }
+
+    fn render(&self, ctx: &ExportContext) -> Result<Vec<u8>> {
+        Ok(generate(ctx.book, ctx.progress, ctx.notes, ctx.summaries, ctx.styles).into_bytes())
+    }
+
+    fn default_file_name(&self, _ctx: &ExportContext) -> String {
+        "CLAUDE.md".to_string()
+    }
+
+    fn write(&self, path: &Path, rendered: &[u8]) -> Result<()> {
+        write(path, &String::from_utf8_lossy(rendered))
+    }
+}
+
+/// Completed sections per chapter
+fn write_progress(out: &mut String, book: &Book, progress: Option<&BookProgress>) {
+    let is_complete =
+        |path: &str| progress.and_then(|p| p.sections.get(path)).is_some_and(|s| s.completed);
+
+    let total = book.section_count();
+    let completed: usize =
+        book.chapters.iter().flat_map(|c| &c.sections).filter(|s| is_complete(&s.path)).count();
+
+    out.push_str("## Progress\n\n");
+    let _ = writeln!(out, "Completed {} of {} sections.\n", completed, total);
+    for chapter in &book.chapters {
+        let done = chapter.sections.iter().filter(|s| is_complete(&s.path)).count();
+        let mark = if done == chapter.sections.len() { "x" } else { " " };
+        let _ =
+            writeln!(out, "- [{}] {} ({}/{})", mark, chapter.title, done, chapter.sections.len());
+    }
+    out.push('\n');
+}
+
+/// Cached chapter summaries, in book order
+fn write_summaries(out: &mut String, book: &Book, summaries: &SummaryCache) {
+    let chapters: Vec<_> =
+        book.chapters.iter().filter_map(|c| summaries.get(&c.path).map(|s| (c, s))).collect();
+    if chapters.is_empty() {
+        return;
+    }
+
+    out.push_str("## Chapter summaries\n\n");
+    for (chapter, summary) in chapters {
+        let _ = writeln!(out, "### {}\n", chapter.title);
+        // Demote the summary's own headings below the chapter heading
+        for line in summary.text.trim().lines() {
+            if line.starts_with('#') {
+                let _ = writeln!(out, "##{}", line);
+            } else {
+                let _ = writeln!(out, "{}", line);
+            }
+        }
+        out.push('\n');
+    }
+}
+
+/// Notes grouped by section, in book order
+fn write_notes(out: &mut String, book: &Book, notes: &[Note], styles: &ExportConfig) {
+    if notes.is_empty() {
+        return;
+    }
+
+    out.push_str("## Notes\n\n");
+    for section in book.chapters.iter().flat_map(|c| &c.sections) {
+        let section_notes: Vec<&Note> =
+            notes.iter().filter(|n| n.section_path == section.path).collect();
+        if section_notes.is_empty() {
+            continue;
+        }
+
+        let _ = writeln!(out, "### {}\n", section.title);
+        for note in section_notes {
+            if let NoteAnchor::TextRange { selected_text, .. } = &note.anchor {
+                let style = styles.highlight_style(note.source.name());
+                let _ = writeln!(out, "{}\n", highlight(selected_text, &style.emoji, styles));
+            }
+            let _ = writeln!(out, "{}\n", note.content.trim());
+        }
+    }
+}
+
+/// A highlighted passage in the configured markdown syntax, prefixed by its emoji
+fn highlight(text: &str, emoji: &str, styles: &ExportConfig) -> String {
+    let text = text.replace('\n', " ");
+    let prefix = if emoji.is_empty() { String::new() } else { format!("{} ", emoji) };
+    match styles.markdown_highlight {
+        MarkdownHighlight::Quote => format!("> {}{}", prefix, text),
+        MarkdownHighlight::Obsidian => format!("{}=={}==", prefix, text.trim()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::Section;
+    use crate::config::progress::SectionProgress;
+
+    fn sample_book() -> Book {
+        let mut book = Book::for_test(Vec::new());
+        book.metadata.author = Some("Ferris".into());
+        book.chapters[0].sections.push(Section::new("Borrows", 2, "ch01/s02"));
+        book
+    }
+
+    #[test]
+    fn generate_includes_progress_summaries_and_notes() {
+        let book = sample_book();
+        let mut progress = BookProgress::default();
+        progress
+            .sections
+            .insert("ch01/s01".into(), SectionProgress { completed: true, ..Default::default() });
+        let mut summaries = SummaryCache::default();
+        summaries.insert("ch01", "Ownership", "## Key concepts\n- Moves");
+        let notes = vec![Note::new_selection_note("test", "ch01/s02", "Ask later", 0, 0, "&T")];
+
+        let md = generate(&book, Some(&progress), &notes, &summaries, &ExportConfig::default());
+
+        assert!(md.starts_with("# Test Book\n\nBy Ferris"));
+        assert!(md.contains("Completed 1 of 2 sections."));
+        assert!(md.contains("- [ ] Ownership (1/2)"));
+        assert!(md.contains("### Ownership\n\n#### Key concepts\n- Moves"));
+        assert!(md.contains("### Borrows\n\n> 🖍️ &T\n\nAsk later"));
+    }
+
+    #[test]
+    fn highlight_uses_configured_syntax() {
+        let mut styles = ExportConfig::default();
+        assert_eq!(highlight("a\nb", "", &styles), "> a b");
+
+        styles.markdown_highlight = MarkdownHighlight::Obsidian;
+        assert_eq!(highlight(" moves ", "🤖", &styles), "🤖 ==moves==");
+    }
+
+    #[test]
+    fn merge_replaces_only_managed_region() {
+        let existing = format!(
+            "# My notes\n\nKeep me.\n\n{}\nold\n{}\n\nAlso keep me.\n",
+            BEGIN_MARKER, END_MARKER
+        );
+
+        let merged = merge_managed(&existing, "new\n");
+
+        assert_eq!(
+            merged,
+            format!(
+                "# My notes\n\nKeep me.\n\n{}\nnew\n{}\n\nAlso keep me.\n",
+                BEGIN_MARKER, END_MARKER
+            )
+        );
+    }
+
+    #[test]
+    fn merge_appends_region_without_markers() {
+        assert_eq!(merge_managed("", "new"), format!("{}\nnew\n{}\n", BEGIN_MARKER, END_MARKER));
+        assert_eq!(
+            merge_managed("# Mine\n", "new"),
+            format!("# Mine\n\n{}\nnew\n{}\n", BEGIN_MARKER, END_MARKER)
+        );
+    }
+
+    #[test]
+    fn generate_skips_empty_sections() {
+        let md =
+            generate(&sample_book(), None, &[], &SummaryCache::default(), &ExportConfig::default());
+        assert!(!md.contains("## Chapter summaries"));
+        assert!(!md.contains("## Notes"));
+    }
+}
@@ -0,0 +1,763 @@
+//! HTML annotation export
+//!
+//! Writes one HTML page per chapter containing the book text with highlights
+//! rendered as colored spans and the notes shown alongside in the margin.
+//! As an [`Exporter`], the same content is rendered as a single page in the
+//! active theme's colors, opening with a report of reading progress, notes
+//! and quiz results for sharing with a mentor or study group. Highlight
+//! classes and colors come from `ExportConfig`.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ratatui::style::Color;
+
+use super::{ExportContext, Exporter};
+use crate::book::{Book, Chapter, ContentBlock, Section};
+use crate::config::ExportConfig;
+use crate::config::progress::BookProgress;
+use crate::notes::{Note, NoteAnchor};
+use crate::theme::Theme;
+
+/// Weakest quizzed sections listed in the report
+const WEAKEST_SECTIONS: usize = 3;
+
+/// Stylesheet shared by all exported pages
+const STYLE: &str = r#"
+body { font-family: Georgia, serif; margin: 0; background: #fafaf7; color: #222; }
+main { max-width: 1100px; margin: 0 auto; padding: 2rem; }
+nav a { margin-right: 1rem; }
+.block { display: grid; grid-template-columns: minmax(0, 1fr) 280px; gap: 2rem; }
+.margin { font-family: sans-serif; font-size: 0.85rem; }
+.note { border-left: 3px solid #e0b400; padding: 0.25rem 0.75rem; margin-bottom: 0.75rem; background: #fff; }
+.note.claude { border-color: #4a90d9; }
+.note.quiz { border-color: #3aa655; }
+.note .quote { color: #666; font-style: italic; }
+mark { background: #fff1a8; padding: 0 1px; }
+mark sup, .note sup { font-family: sans-serif; font-size: 0.7rem; color: #555; }
+pre { background: #f0f0ec; padding: 0.75rem; overflow-x: auto; }
+blockquote { border-left: 3px solid #ccc; margin-left: 0; padding-left: 1rem; color: #555; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 0.25rem 0.5rem; }
+.footnote { font-size: 0.85rem; color: #555; }
+"#;
+
+/// Export a book's annotations as HTML pages into `out_dir`
+///
+/// Writes `index.html` plus one page per chapter and returns the index path.
+pub fn export_book(
+    book: &Book,
+    notes: &[Note],
+    styles: &ExportConfig,
+    out_dir: &Path,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create export directory {:?}", out_dir))?;
+
+    for chapter in &book.chapters {
+        let path = out_dir.join(chapter_file_name(chapter));
+        std::fs::write(&path, render_chapter(book, chapter, notes, styles))
+            .with_context(|| format!("Failed to write {:?}", path))?;
+    }
+
+    let index_path = out_dir.join("index.html");
+    std::fs::write(&index_path, render_index(book, notes, styles))
+        .with_context(|| format!("Failed to write {:?}", index_path))?;
+
+    Ok(index_path)
+}
+
+/// The annotated book as a single HTML page
+pub struct HtmlExporter;
+
+impl Exporter for HtmlExporter {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn description(&self) -> &'static str {
+        "Themed HTML report: progress, quiz stats, highlights and margin notes"
+    }
+
+    fn render(&self, ctx: &ExportContext) -> Result<Vec<u8>> {
+        Ok(render_book(ctx).into_bytes())
+    }
+}
+
+/// File name for a chapter page
+fn chapter_file_name(chapter: &Chapter) -> String {
+    let slug: String = chapter
+        .path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}.html", slug)
+}
+
+/// Render the table of contents page with note counts per chapter
+fn render_index(book: &Book, notes: &[Note], styles: &ExportConfig) -> String {
+    let mut body = format!("<h1>{}</h1>\n<ul>\n", escape(&book.metadata.title));
+    for chapter in &book.chapters {
+        let count = notes
+            .iter()
+            .filter(|n| chapter.sections.iter().any(|s| s.path == n.section_path))
+            .count();
+        let _ = writeln!(
+            body,
+            "<li><a href=\"{}\">{}</a> ({} notes)</li>",
+            chapter_file_name(chapter),
+            escape(&chapter.title),
+            count
+        );
+    }
+    body.push_str("</ul>\n");
+    page(&book.metadata.title, &body, styles, "")
+}
+
+/// Render a chapter page
+fn render_chapter(book: &Book, chapter: &Chapter, notes: &[Note], styles: &ExportConfig) -> String {
+    let mut body = String::new();
+
+    // Previous/next navigation
+    let index = book.chapters.iter().position(|c| c.path == chapter.path).unwrap_or(0);
+    body.push_str("<nav><a href=\"index.html\">Contents</a>");
+    if let Some(prev) = index.checked_sub(1).and_then(|i| book.chapters.get(i)) {
+        let _ = write!(body, "<a href=\"{}\">&larr; Previous</a>", chapter_file_name(prev));
+    }
+    if let Some(next) = book.chapters.get(index + 1) {
+        let _ = write!(body, "<a href=\"{}\">Next &rarr;</a>", chapter_file_name(next));
+    }
+    body.push_str("</nav>\n");
+
+    // Number notes through the chapter so highlights and margin notes match up
+    render_chapter_body(&mut body, chapter, notes, styles, &mut 0);
+
+    page(&format!("{} - {}", chapter.title, book.metadata.title), &body, styles, "")
+}
+
+/// Render a whole book as a single page, with a report and a table of contents
+///
+/// Notes are numbered through the whole book so highlight ids stay unique.
+fn render_book(ctx: &ExportContext) -> String {
+    let book = ctx.book;
+    let mut body = format!("<h1>{}</h1>\n", escape(&book.metadata.title));
+    render_report(&mut body, ctx);
+
+    body.push_str("<nav><h2>Contents</h2><ul>\n");
+    for chapter in &book.chapters {
+        let _ = writeln!(
+            body,
+            "<li><a href=\"#{}\">{}</a></li>",
+            escape(&chapter.path),
+            escape(&chapter.title)
+        );
+    }
+    body.push_str("</ul></nav>\n");
+
+    let mut number = 0;
+    for chapter in &book.chapters {
+        let _ = writeln!(body, "<article id=\"{}\">", escape(&chapter.path));
+        render_chapter_body(&mut body, chapter, ctx.notes, ctx.styles, &mut number);
+        body.push_str("</article>\n");
+    }
+
+    page(&book.metadata.title, &body, ctx.styles, &theme_css(ctx.theme))
+}
+
+/// Render the summary of progress, notes and quiz results
+fn render_report(body: &mut String, ctx: &ExportContext) {
+    let sections: Vec<&Section> = ctx.book.chapters.iter().flat_map(|c| &c.sections).collect();
+    let progress = ctx.progress.cloned().unwrap_or_default();
+    let completed = sections
+        .iter()
+        .filter(|s| progress.sections.get(&s.path).is_some_and(|p| p.completed))
+        .count();
+    let highlights = ctx.notes.iter().filter(|n| n.anchor.char_range().is_some()).count();
+    let minutes = progress.total_time_seconds / 60;
+
+    body.push_str("<section class=\"report\">\n<h2>Study report</h2>\n<ul class=\"stats\">\n");
+    let _ = writeln!(
+        body,
+        "<li><strong>{}%</strong> read ({} of {} sections)</li>",
+        (completed * 100).checked_div(sections.len()).unwrap_or(0),
+        completed,
+        sections.len()
+    );
+    let _ = writeln!(
+        body,
+        "<li><strong>{}h {:02}m</strong> spent reading</li>",
+        minutes / 60,
+        minutes % 60
+    );
+    let _ = writeln!(
+        body,
+        "<li><strong>{}</strong> notes, {} on highlighted passages</li>",
+        ctx.notes.len(),
+        highlights
+    );
+    render_quiz_stats(body, &progress);
+    body.push_str("</ul>\n");
+
+    render_chapter_table(body, ctx.book, &progress);
+    render_weakest_sections(body, ctx.book, &progress);
+    body.push_str("</section>\n");
+}
+
+/// Render quiz totals as items of the report's stats list
+fn render_quiz_stats(body: &mut String, progress: &BookProgress) {
+    let attempts: Vec<_> = progress.sections.values().flat_map(|s| &s.quiz_history).collect();
+    if attempts.is_empty() {
+        body.push_str("<li>No quizzes taken yet</li>\n");
+        return;
+    }
+    let answered: usize = attempts.iter().map(|a| a.results.len()).sum();
+    let correct: usize = attempts.iter().map(|a| a.correct()).sum();
+    let quizzed = progress.sections.values().filter(|s| !s.quiz_history.is_empty()).count();
+    let _ = writeln!(
+        body,
+        "<li><strong>{}%</strong> of {} quiz answers correct ({} attempts over {} sections)</li>",
+        (correct * 100).checked_div(answered).unwrap_or(0),
+        answered,
+        attempts.len(),
+        quizzed
+    );
+}
+
+/// Render a table of sections completed and quiz average per chapter
+fn render_chapter_table(body: &mut String, book: &Book, progress: &BookProgress) {
+    body.push_str("<table class=\"chapters\">\n<tr><th>Chapter</th><th>Completed</th><th>Quiz average</th></tr>\n");
+    for chapter in &book.chapters {
+        let section_progress: Vec<_> =
+            chapter.sections.iter().filter_map(|s| progress.sections.get(&s.path)).collect();
+        let completed = section_progress.iter().filter(|p| p.completed).count();
+        let averages: Vec<f32> =
+            section_progress.iter().filter_map(|p| p.average_quiz_percent()).collect();
+        let average = if averages.is_empty() {
+            "&ndash;".to_string()
+        } else {
+            format!("{:.0}%", averages.iter().sum::<f32>() / averages.len() as f32)
+        };
+        let _ = writeln!(
+            body,
+            "<tr><td><a href=\"#{}\">{}</a></td><td>{} / {}</td><td>{}</td></tr>",
+            escape(&chapter.path),
+            escape(&chapter.title),
+            completed,
+            chapter.sections.len(),
+            average
+        );
+    }
+    body.push_str("</table>\n");
+}
+
+/// Render the sections with the lowest quiz averages, worth revisiting
+fn render_weakest_sections(body: &mut String, book: &Book, progress: &BookProgress) {
+    let weakest: Vec<_> = progress
+        .weakest_sections()
+        .into_iter()
+        .filter_map(|(path, p)| Some((book.find_section_by_path(path)?, p.average_quiz_percent()?)))
+        .take(WEAKEST_SECTIONS)
+        .collect();
+    if weakest.is_empty() {
+        return;
+    }
+    body.push_str("<h3>Worth revisiting</h3>\n<ul>\n");
+    for (section, average) in weakest {
+        let _ = writeln!(
+            body,
+            "<li><a href=\"#{}\">{}</a> ({:.0}% quiz average)</li>",
+            escape(&section.path),
+            escape(&section.title),
+            average
+        );
+    }
+    body.push_str("</ul>\n");
+}
+
+/// Render a chapter's title and sections, continuing note numbering from `number`
+fn render_chapter_body(
+    body: &mut String,
+    chapter: &Chapter,
+    notes: &[Note],
+    styles: &ExportConfig,
+    number: &mut usize,
+) {
+    let _ = writeln!(body, "<h1>{}</h1>", escape(&chapter.title));
+    for section in &chapter.sections {
+        let section_notes: Vec<&Note> =
+            notes.iter().filter(|n| n.section_path == section.path).collect();
+        render_section(body, section, &section_notes, styles, number);
+    }
+}
+
+/// Render a section's blocks with their highlights and margin notes
+fn render_section(
+    body: &mut String,
+    section: &Section,
+    notes: &[&Note],
+    styles: &ExportConfig,
+    number: &mut usize,
+) {
+    let _ = writeln!(body, "<section id=\"{}\">", escape(&section.path));
+    let _ = writeln!(body, "<h2>{}</h2>", escape(&section.title));
+
+    let section_notes: Vec<&Note> = notes.iter().copied().filter(|n| n.is_section_note()).collect();
+    if !section_notes.is_empty() {
+        body.push_str("<div class=\"block\"><div></div><div class=\"margin\">\n");
+        for note in section_notes {
+            render_margin_note(body, note, styles, None);
+        }
+        body.push_str("</div></div>\n");
+    }
+
+    for (block_index, block) in section.content.iter().enumerate() {
+        let mut highlights: Vec<(usize, usize, usize, &Note)> = notes
+            .iter()
+            .filter(|n| n.anchor.block_index() == Some(block_index))
+            .filter_map(|n| n.anchor.char_range().map(|(start, end)| (start, end, n)))
+            .map(|(start, end, note)| (start, end, 0, *note))
+            .collect();
+        highlights.sort_by_key(|(start, ..)| *start);
+        for highlight in &mut highlights {
+            *number += 1;
+            highlight.2 = *number;
+        }
+
+        body.push_str("<div class=\"block\"><div>\n");
+        render_block(body, block, &highlights, styles);
+        body.push_str("</div><div class=\"margin\">\n");
+        for (_, _, n, note) in &highlights {
+            render_margin_note(body, note, styles, Some(*n));
+        }
+        body.push_str("</div></div>\n");
+    }
+
+    body.push_str("</section>\n");
+}
+
+/// A highlight over block characters: (start, end, note number, note)
+type Highlight<'a> = (usize, usize, usize, &'a Note);
+
+/// Render a content block, marking highlighted ranges
+fn render_block(
+    body: &mut String,
+    block: &ContentBlock,
+    highlights: &[Highlight],
+    styles: &ExportConfig,
+) {
+    match block {
+        ContentBlock::Heading { level, text } => {
+            let level = (*level).clamp(3, 6);
+            let _ = writeln!(
+                body,
+                "<h{0}>{1}</h{0}>",
+                level,
+                highlight_text(text, 0, highlights, styles)
+            );
+        }
+        ContentBlock::Paragraph(text) => {
+            let _ = writeln!(body, "<p>{}</p>", highlight_text(text, 0, highlights, styles));
+        }
+        ContentBlock::Blockquote(text) => {
+            let _ = writeln!(
+                body,
+                "<blockquote>{}</blockquote>",
+                highlight_text(text, 0, highlights, styles)
+            );
+        }
+        ContentBlock::UnorderedList(items) | ContentBlock::OrderedList(items) => {
+            let tag = if matches!(block, ContentBlock::OrderedList(_)) { "ol" } else { "ul" };
+            let _ = writeln!(body, "<{}>", tag);
+            // Note offsets are relative to the items joined with newlines
+            let mut offset = 0;
+            for item in items {
+                let _ =
+                    writeln!(body, "<li>{}</li>", highlight_text(item, offset, highlights, styles));
+                offset += item.chars().count() + 1;
+            }
+            let _ = writeln!(body, "</{}>", tag);
+        }
+        ContentBlock::Code(code) => {
+            let _ = writeln!(
+                body,
+                "<pre><code>{}</code></pre>",
+                highlight_text(&code.code, 0, highlights, styles)
+            );
+        }
+        ContentBlock::Table(table) => {
+            body.push_str("<table>\n<tr>");
+            for header in &table.headers {
+                let _ = write!(body, "<th>{}</th>", escape(header));
+            }
+            body.push_str("</tr>\n");
+            for row in &table.rows {
+                body.push_str("<tr>");
+                for cell in row {
+                    let _ = write!(body, "<td>{}</td>", escape(cell));
+                }
+                body.push_str("</tr>\n");
+            }
+            body.push_str("</table>\n");
+        }
+        ContentBlock::Image { alt, .. } => {
+            if !alt.is_empty() {
+                let _ = writeln!(body, "<p><em>[Image: {}]</em></p>", escape(alt));
+            }
+        }
+        ContentBlock::HorizontalRule => body.push_str("<hr>\n"),
+        ContentBlock::Footnote { label, text } => {
+            let _ = writeln!(
+                body,
+                "<p class=\"footnote\"><sup>{}</sup> {}</p>",
+                escape(label),
+                escape(text)
+            );
+        }
+    }
+}
+
+/// Escape `text` and wrap highlighted characters in `<mark>` spans
+///
+/// `offset` is the position of `text` within the block's character indices.
+/// Where highlights overlap, the earliest one wins.
+fn highlight_text(
+    text: &str,
+    offset: usize,
+    highlights: &[Highlight],
+    styles: &ExportConfig,
+) -> String {
+    let mut out = String::new();
+    let mut open: Option<usize> = None;
+
+    let close = |out: &mut String, open: &mut Option<usize>| {
+        if let Some(n) = open.take() {
+            let _ = write!(out, "<sup>{}</sup></mark>", n);
+        }
+    };
+
+    for (i, c) in text.chars().enumerate() {
+        let pos = offset + i;
+        let covering = highlights.iter().find(|(start, end, ..)| pos >= *start && pos < *end);
+        let number = covering.map(|(_, _, n, _)| *n);
+
+        if number != open {
+            close(&mut out, &mut open);
+            if let Some((_, _, n, note)) = covering {
+                let _ =
+                    write!(out, "<mark class=\"{}\" id=\"hl-{}\">", source_class(note, styles), n);
+                open = Some(*n);
+            }
+        }
+        push_escaped(&mut out, c);
+    }
+    close(&mut out, &mut open);
+    out
+}
+
+/// Render a note in the margin, rendering its markdown content
+fn render_margin_note(
+    body: &mut String,
+    note: &Note,
+    styles: &ExportConfig,
+    number: Option<usize>,
+) {
+    let _ = write!(body, "<div class=\"note {}\">", source_class(note, styles));
+    if let Some(n) = number {
+        let _ = write!(body, "<sup><a href=\"#hl-{0}\">{0}</a></sup> ", n);
+    }
+    if let NoteAnchor::TextRange { selected_text, .. } = &note.anchor {
+        let _ = write!(body, "<div class=\"quote\">&ldquo;{}&rdquo;</div>", escape(selected_text));
+    }
+    pulldown_cmark::html::push_html(body, markdown_without_html(&note.content));
+    body.push_str("</div>\n");
+}
+
+/// Markdown events for note text, with any raw HTML in it shown as text
+/// rather than added to the page
+fn markdown_without_html(text: &str) -> impl Iterator<Item = pulldown_cmark::Event<'_>> {
+    use pulldown_cmark::Event;
+
+    pulldown_cmark::Parser::new(text).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        event => event,
+    })
+}
+
+/// CSS class for a note's highlight kind
+fn source_class(note: &Note, styles: &ExportConfig) -> String {
+    escape(&styles.highlight_style(note.source.name()).css_class)
+}
+
+/// Highlight colors for each configured CSS class
+fn highlight_css(styles: &ExportConfig) -> String {
+    let mut css = String::new();
+    for style in styles.highlights.values() {
+        let class: String = style
+            .css_class
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+            .collect();
+        if !class.is_empty() && is_css_color(&style.color) {
+            let _ = writeln!(css, "mark.{} {{ background: {}; }}", class, style.color);
+        }
+    }
+    css
+}
+
+/// Whether a configured color is a hex color, `rgb()`/`rgba()` or a named
+/// color, and so safe to write into the stylesheet as is
+fn is_css_color(color: &str) -> bool {
+    if let Some(hex) = color.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    let args = color.strip_prefix("rgba(").or_else(|| color.strip_prefix("rgb("));
+    if let Some(args) = args.and_then(|rest| rest.strip_suffix(')')) {
+        return args.chars().all(|c| c.is_ascii_digit() || matches!(c, ',' | '.' | '%' | ' '));
+    }
+    !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Stylesheet overrides taking the page's colors from `theme`
+///
+/// Highlights keep their configured colors, so their text stays dark.
+fn theme_css(theme: &Theme) -> String {
+    let rules = [
+        ("body", "background", theme.bg_primary),
+        ("body", "color", theme.fg_primary),
+        ("a", "color", theme.accent_primary),
+        ("h1, h2, h3", "color", theme.accent_secondary),
+        (".note, .report", "background", theme.bg_secondary),
+        (".note .quote, blockquote, .footnote", "color", theme.fg_secondary),
+        ("mark sup, .note sup", "color", theme.fg_muted),
+        ("pre", "background", theme.bg_tertiary),
+        ("blockquote, td, th", "border-color", theme.border),
+        (".note.claude", "border-color", theme.info),
+        (".note.quiz", "border-color", theme.success),
+    ];
+    let mut css = String::from(
+        ".report { padding: 0.5rem 1.5rem 1rem; margin-bottom: 2rem; }\nmark { color: #222; }\n",
+    );
+    for (selector, property, color) in rules {
+        if let Some(color) = css_color(color) {
+            let _ = writeln!(css, "{} {{ {}: {}; }}", selector, property, color);
+        }
+    }
+    css
+}
+
+/// A theme color as CSS; None for terminal palette colors, which have no
+/// fixed value
+fn css_color(color: Color) -> Option<String> {
+    match color {
+        Color::Rgb(r, g, b) => Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        _ => None,
+    }
+}
+
+/// Wrap body HTML in a complete page, with `css` added to the stylesheet
+fn page(title: &str, body: &str, styles: &ExportConfig, css: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}{}{}</style>\n</head>\n<body>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        highlight_css(styles),
+        css,
+        body
+    )
+}
+
+/// Escape text for HTML
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_escaped(&mut out, c);
+    }
+    out
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        _ => out.push(c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning::bank::QuestionBank;
+
+    fn sample_book() -> Book {
+        Book::for_test(vec![
+            ContentBlock::Paragraph("Values <move> by default.".into()),
+            ContentBlock::UnorderedList(vec!["Copy types".into(), "Clone types".into()]),
+        ])
+    }
+
+    #[test]
+    fn highlight_text_marks_ranges_and_escapes() {
+        let note = Note::new_selection_note("test", "ch01/s01", "Why?", 0, 7, "<move>");
+        let html = highlight_text(
+            "Values <move> by default.",
+            0,
+            &[(7, 13, 1, &note)],
+            &ExportConfig::default(),
+        );
+        assert_eq!(
+            html,
+            "Values <mark class=\"user\" id=\"hl-1\">&lt;move&gt;<sup>1</sup></mark> by default."
+        );
+    }
+
+    #[test]
+    fn margin_notes_escape_raw_html() {
+        let note = Note::new_selection_note(
+            "test",
+            "ch01/s01",
+            "**Careful** <script>alert(1)</script>\n\n<img src=x onerror=alert(2)>",
+            0,
+            5,
+            "Moves",
+        );
+        let mut body = String::new();
+        render_margin_note(&mut body, &note, &ExportConfig::default(), None);
+
+        assert!(body.contains("<strong>Careful</strong>"));
+        assert!(body.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!body.contains("<script>") && !body.contains("<img"));
+    }
+
+    #[test]
+    fn highlight_text_uses_list_item_offsets() {
+        let note = Note::new_selection_note("test", "ch01/s01", "", 1, 11, "Clone");
+        let highlights = [(11, 16, 1, &note)];
+        let styles = ExportConfig::default();
+        assert_eq!(highlight_text("Copy types", 0, &highlights, &styles), "Copy types");
+        assert!(highlight_text("Clone types", 11, &highlights, &styles).starts_with("<mark"));
+    }
+
+    #[test]
+    fn export_writes_index_and_chapter_pages() {
+        let book = sample_book();
+        let notes = vec![
+            Note::new_selection_note("test", "ch01/s01", "**Key** point", 0, 7, "<move>"),
+            Note::new_section_note("test", "ch01/s01", "Reread this"),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+
+        let index = export_book(&book, &notes, &ExportConfig::default(), dir.path()).unwrap();
+        assert!(std::fs::read_to_string(index).unwrap().contains("(2 notes)"));
+
+        let chapter = std::fs::read_to_string(dir.path().join("ch01.html")).unwrap();
+        assert!(chapter.contains("<mark class=\"user\" id=\"hl-1\">"));
+        assert!(chapter.contains("<strong>Key</strong> point"));
+        assert!(chapter.contains("Reread this"));
+        assert!(chapter.contains("mark.claude { background: #d6e8fb; }"));
+    }
+
+    #[test]
+    fn highlight_text_uses_configured_class() {
+        let note = Note::new_selection_note("test", "ch01/s01", "", 0, 0, "Values");
+        let mut styles = ExportConfig::default();
+        styles.highlights.get_mut("user").unwrap().css_class = "mine".into();
+
+        let html = highlight_text("Values", 0, &[(0, 6, 1, &note)], &styles);
+        assert!(html.starts_with("<mark class=\"mine\""));
+        assert!(highlight_css(&styles).contains("mark.mine { background: #fff1a8; }"));
+    }
+
+    #[test]
+    fn highlight_css_skips_colors_that_are_not_colors() {
+        for color in ["#fff1a8", "rgb(255, 241, 168)", "rgba(0,0,0,0.5)", "gold"] {
+            assert!(is_css_color(color), "{}", color);
+        }
+        for color in ["", "#ggg", "red</style><script>", "url(x)", "rgb(1,2,3);x"] {
+            assert!(!is_css_color(color), "{}", color);
+        }
+    }
+
+    #[test]
+    fn exporter_renders_single_page_with_unique_highlight_ids() {
+        let mut book = sample_book();
+        let mut chapter = Chapter::new("Borrowing", 2, "ch02");
+        let mut section = Section::new("References", 1, "ch02/s01");
+        section.content = vec![ContentBlock::Paragraph("References borrow values.".into())].into();
+        chapter.sections.push(section);
+        book.chapters.push(chapter);
+        let notes = vec![
+            Note::new_selection_note("test", "ch01/s01", "", 0, 0, "Values"),
+            Note::new_selection_note("test", "ch02/s01", "", 0, 0, "References"),
+        ];
+        let summaries = crate::claude::summary::SummaryCache::default();
+        let styles = ExportConfig::default();
+        let theme = Theme::default();
+        let ctx = ExportContext {
+            book: &book,
+            notes: &notes,
+            questions: &QuestionBank::default(),
+            progress: None,
+            summaries: &summaries,
+            styles: &styles,
+            theme: &theme,
+        };
+
+        let html = String::from_utf8(HtmlExporter.render(&ctx).unwrap()).unwrap();
+        assert!(html.contains("<li><a href=\"#ch02\">Borrowing</a></li>"));
+        assert!(html.contains("<article id=\"ch01\">\n<h1>Ownership</h1>"));
+        assert!(html.contains("id=\"hl-1\">Values"));
+        assert!(html.contains("id=\"hl-2\">References"));
+        assert_eq!(HtmlExporter.default_file_name(&ctx), "test.html");
+    }
+
+    #[test]
+    fn exporter_report_summarizes_progress_and_quizzes_in_theme_colors() {
+        use crate::config::progress::{QuizAttempt, SectionProgress};
+
+        let book = sample_book();
+        let notes = vec![
+            Note::new_selection_note("test", "ch01/s01", "Why?", 0, 7, "<move>"),
+            Note::new_section_note("test", "ch01/s01", "Reread this"),
+        ];
+        let mut progress = BookProgress::default();
+        let attempt =
+            QuizAttempt { at: 0, attempt: 1, duration_secs: 60, results: vec![true, false] };
+        progress.sections.insert(
+            "ch01/s01".into(),
+            SectionProgress { completed: true, quiz_history: vec![attempt], ..Default::default() },
+        );
+        progress.total_time_seconds = 3_900;
+        let summaries = crate::claude::summary::SummaryCache::default();
+        let styles = ExportConfig::default();
+        let theme =
+            Theme { bg_primary: Color::Rgb(1, 2, 3), fg_primary: Color::Reset, ..Theme::default() };
+        let ctx = ExportContext {
+            book: &book,
+            notes: &notes,
+            questions: &QuestionBank::default(),
+            progress: Some(&progress),
+            summaries: &summaries,
+            styles: &styles,
+            theme: &theme,
+        };
+
+        let html = String::from_utf8(HtmlExporter.render(&ctx).unwrap()).unwrap();
+        assert!(html.contains("<strong>100%</strong> read (1 of 1 sections)"));
+        assert!(html.contains("<strong>1h 05m</strong> spent reading"));
+        assert!(html.contains("<strong>2</strong> notes, 1 on highlighted passages"));
+        assert!(html.contains("<strong>50%</strong> of 2 quiz answers correct"));
+        assert!(html.contains("<td>1 / 1</td><td>50%</td>"));
+        assert!(html.contains("<a href=\"#ch01/s01\">Moves</a> (50% quiz average)"));
+        assert!(html.contains("body { background: #010203; }"));
+        assert!(!html.contains("body { color"));
+        assert!(html.contains("&ldquo;&lt;move&gt;&rdquo;"));
+    }
+}
@@ -1,5 +1,170 @@
 //! Export features
+//!
+//! Each export format implements [`Exporter`], turning a book and the reader's
+//! notes, progress and summaries into the bytes of one file. Formats are
+//! looked up by name in an [`ExporterRegistry`], so adding a format (Anki,
+//! ICS, Obsidian, ...) only takes a new implementation registered alongside
+//! the built-in ones.
 
+pub mod anki;
 pub mod claude_md;
+pub mod html;
+pub mod pdf;
 
-// Re-exports will be added as modules are implemented
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::book::Book;
+use crate::claude::summary::SummaryCache;
+use crate::config::ExportConfig;
+use crate::config::progress::BookProgress;
+use crate::learning::bank::QuestionBank;
+use crate::notes::Note;
+use crate::theme::Theme;
+
+pub use anki::AnkiExporter;
+pub use claude_md::ClaudeMdExporter;
+pub use html::HtmlExporter;
+pub use pdf::PdfExporter;
+
+/// Everything an exporter can draw on for one book
+#[derive(Debug, Clone, Copy)]
+pub struct ExportContext<'a> {
+    /// Book being exported
+    pub book: &'a Book,
+    /// Notes on the book
+    pub notes: &'a [Note],
+    /// Quiz questions the reader wrote for the book
+    pub questions: &'a QuestionBank,
+    /// Reading progress, if the book has been opened
+    pub progress: Option<&'a BookProgress>,
+    /// Cached chapter summaries
+    pub summaries: &'a SummaryCache,
+    /// Highlight styles
+    pub styles: &'a ExportConfig,
+    /// Colors for formats that are styled, from the active theme
+    pub theme: &'a Theme,
+}
+
+/// An export format
+pub trait Exporter: Send + Sync {
+    /// Name used to pick the format, e.g. "html"
+    fn name(&self) -> &'static str;
+
+    /// File extension of the output, without the dot
+    fn extension(&self) -> &'static str;
+
+    /// One-line description shown when listing formats
+    fn description(&self) -> &'static str;
+
+    /// Render the export
+    fn render(&self, ctx: &ExportContext) -> Result<Vec<u8>>;
+
+    /// File name used when no output path is given
+    fn default_file_name(&self, ctx: &ExportContext) -> String {
+        format!("{}.{}", ctx.book.metadata.id, self.extension())
+    }
+
+    /// Write rendered output to `path`
+    ///
+    /// Formats that merge into an existing file override this.
+    fn write(&self, path: &Path, rendered: &[u8]) -> Result<()> {
+        std::fs::write(path, rendered).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    /// Render the export and write it to `path`
+    fn export(&self, ctx: &ExportContext, path: &Path) -> Result<()> {
+        let rendered = self.render(ctx)?;
+        self.write(path, &rendered)
+    }
+}
+
+/// Export formats available by name
+pub struct ExporterRegistry {
+    exporters: Vec<Box<dyn Exporter>>,
+}
+
+impl ExporterRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        Self { exporters: Vec::new() }
+    }
+
+    /// A registry with the formats built into sensei
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(ClaudeMdExporter));
+        registry.register(Box::new(HtmlExporter));
+        registry.register(Box::new(AnkiExporter));
+        registry.register(Box::new(PdfExporter));
+        registry
+    }
+
+    /// Add a format, replacing any registered under the same name
+    pub fn register(&mut self, exporter: Box<dyn Exporter>) {
+        match self.exporters.iter().position(|e| e.name() == exporter.name()) {
+            Some(i) => self.exporters[i] = exporter,
+            None => self.exporters.push(exporter),
+        }
+    }
+
+    /// Look up a format by name (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&dyn Exporter> {
+        self.exporters.iter().find(|e| e.name().eq_ignore_ascii_case(name)).map(|e| e.as_ref())
+    }
+
+    /// Registered formats, in registration order
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Exporter> {
+        self.exporters.iter().map(|e| e.as_ref())
+    }
+
+    /// Names of the registered formats, comma separated
+    pub fn names(&self) -> String {
+        self.iter().map(|e| e.name()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+impl Default for ExporterRegistry {
+    fn default() -> Self {
+        Self::with_builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct PlainExporter;
+
+    impl Exporter for PlainExporter {
+        fn name(&self) -> &'static str {
+            "html"
+        }
+
+        fn extension(&self) -> &'static str {
+            "txt"
+        }
+
+        fn description(&self) -> &'static str {
+            "Chapter titles"
+        }
+
+        fn render(&self, ctx: &ExportContext) -> Result<Vec<u8>> {
+            let titles: Vec<&str> = ctx.book.chapters.iter().map(|c| c.title.as_str()).collect();
+            Ok(titles.join("\n").into_bytes())
+        }
+    }
+
+    #[test]
+    fn registry_looks_up_and_replaces_formats_by_name() {
+        let mut registry = ExporterRegistry::with_builtin();
+        assert_eq!(registry.names(), "claude-md, html, anki, pdf");
+        assert_eq!(registry.get("HTML").map(|e| e.extension()), Some("html"));
+        assert!(registry.get("ics").is_none());
+
+        registry.register(Box::new(PlainExporter));
+        assert_eq!(registry.names(), "claude-md, html, anki, pdf");
+        assert_eq!(registry.get("html").map(|e| e.extension()), Some("txt"));
+    }
+}
@@ -0,0 +1,690 @@
+//! PDF export of notes and highlights
+//!
+//! Lays out the notes on a book chapter by chapter, each under the passage it
+//! highlights, as a printable PDF. The file is written directly rather than
+//! through a PDF library: text is set in the standard Helvetica and Courier
+//! fonts every reader provides, so nothing is embedded, and characters outside
+//! their Latin-1 (WinAnsi) repertoire print as `?`. Code keeps its syntax
+//! highlighting, darkened to read on paper.
+
+use std::io::Write as _;
+
+use anyhow::Result;
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Parser, Tag, TagEnd};
+use ratatui::style::Color;
+
+use super::{ExportContext, Exporter};
+use crate::book::{ContentBlock, Section};
+use crate::notes::{Note, NoteAnchor};
+use crate::theme::Theme;
+
+/// A4, in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+
+/// Font sizes, in points
+const TITLE_SIZE: f32 = 20.0;
+const CHAPTER_SIZE: f32 = 15.0;
+const SECTION_SIZE: f32 = 12.0;
+const BODY_SIZE: f32 = 10.5;
+const CODE_SIZE: f32 = 8.5;
+
+/// Line height as a multiple of the font size
+const LEADING: f32 = 1.35;
+
+/// Indent of quotes and note text under a section heading
+const INDENT: f32 = 12.0;
+
+type Rgb = (f32, f32, f32);
+
+const TEXT: Rgb = (0.13, 0.13, 0.13);
+const MUTED: Rgb = (0.42, 0.42, 0.42);
+const CODE_BACKGROUND: Rgb = (0.95, 0.95, 0.93);
+/// Bar beside a quote whose highlight color can't be read
+const QUOTE_BAR: Rgb = (0.88, 0.71, 0.0);
+
+/// Notes and highlights as a printable PDF
+pub struct PdfExporter;
+
+impl Exporter for PdfExporter {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn extension(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn description(&self) -> &'static str {
+        "Printable PDF of notes by chapter, with quoted passages and highlighted code"
+    }
+
+    fn render(&self, ctx: &ExportContext) -> Result<Vec<u8>> {
+        Ok(render_notes(ctx))
+    }
+}
+
+/// Lay out the book's notes, grouped by chapter and section
+fn render_notes(ctx: &ExportContext) -> Vec<u8> {
+    let book = ctx.book;
+    let mut layout = Layout::new();
+
+    layout.paragraph(&[run(&book.metadata.title, Font::Bold, TEXT)], TITLE_SIZE, 0.0, None);
+    if let Some(author) = &book.metadata.author {
+        layout.paragraph(
+            &[run(&format!("By {}", author), Font::Italic, MUTED)],
+            BODY_SIZE,
+            0.0,
+            None,
+        );
+    }
+    let count = ctx.notes.len();
+    let summary =
+        format!("{} note{} exported from sensei", count, if count == 1 { "" } else { "s" });
+    layout.paragraph(&[run(&summary, Font::Regular, MUTED)], BODY_SIZE, 0.0, None);
+    layout.space(BODY_SIZE);
+
+    let accent = printable(ctx.theme.accent_primary).unwrap_or(TEXT);
+    for chapter in &book.chapters {
+        let sections: Vec<(&Section, Vec<&Note>)> = chapter
+            .sections
+            .iter()
+            .map(|s| (s, ctx.notes.iter().filter(|n| n.section_path == s.path).collect()))
+            .filter(|(_, notes): &(_, Vec<_>)| !notes.is_empty())
+            .collect();
+        if sections.is_empty() {
+            continue;
+        }
+
+        layout.space(CHAPTER_SIZE * 0.5);
+        layout.keep(CHAPTER_SIZE * 4.0);
+        layout.paragraph(&[run(&chapter.title, Font::Bold, accent)], CHAPTER_SIZE, 0.0, None);
+        for (section, notes) in sections {
+            layout.space(SECTION_SIZE * 0.5);
+            layout.keep(SECTION_SIZE * 4.0);
+            layout.paragraph(&[run(&section.title, Font::Bold, TEXT)], SECTION_SIZE, 0.0, None);
+            for note in notes {
+                render_note(&mut layout, section, note, ctx);
+                layout.space(BODY_SIZE * 0.6);
+            }
+        }
+    }
+    if ctx.notes.is_empty() {
+        layout.paragraph(&[run("No notes yet.", Font::Italic, MUTED)], BODY_SIZE, 0.0, None);
+    }
+
+    layout.finish(&book.metadata.title)
+}
+
+/// A note: the passage it highlights, if any, then its content
+fn render_note(layout: &mut Layout, section: &Section, note: &Note, ctx: &ExportContext) {
+    if let NoteAnchor::TextRange { block_index, selected_text, .. } = &note.anchor {
+        match section.content.get(*block_index) {
+            Some(ContentBlock::Code(code)) => {
+                layout.code(selected_text, code.language.as_deref(), INDENT, ctx.theme);
+            }
+            _ => {
+                let style = ctx.styles.highlight_style(note.source.name());
+                let bar = hex_color(&style.color).unwrap_or(QUOTE_BAR);
+                let quote = format!("\u{201c}{}\u{201d}", selected_text.trim());
+                layout.paragraph(&[run(&quote, Font::Italic, MUTED)], BODY_SIZE, INDENT, Some(bar));
+            }
+        }
+        layout.space(BODY_SIZE * 0.3);
+    }
+    render_markdown(layout, &note.content, ctx.theme);
+}
+
+/// A note's markdown: paragraphs, headings and lists with bold, italic and
+/// inline code, and fenced code with its highlighting
+fn render_markdown(layout: &mut Layout, markdown: &str, theme: &Theme) {
+    let mut runs: Vec<Run> = Vec::new();
+    let (mut bold, mut italic) = (false, false);
+    let mut list_depth = 0usize;
+    let mut code: Option<(Option<String>, String)> = None;
+
+    let flush = |layout: &mut Layout, runs: &mut Vec<Run>, indent: f32| {
+        if runs.iter().any(|r| !r.text.trim().is_empty()) {
+            layout.paragraph(runs, BODY_SIZE, indent, None);
+            layout.space(BODY_SIZE * 0.3);
+        }
+        runs.clear();
+    };
+    let indent = |depth: usize| INDENT + 10.0 * depth.saturating_sub(1) as f32;
+
+    for event in Parser::new(markdown) {
+        let font = match (bold, italic) {
+            (true, _) => Font::Bold,
+            (false, true) => Font::Italic,
+            (false, false) => Font::Regular,
+        };
+        match event {
+            Event::Start(Tag::Strong | Tag::Heading { .. }) => bold = true,
+            Event::End(TagEnd::Strong) => bold = false,
+            Event::End(TagEnd::Heading(level)) => {
+                bold = false;
+                if level <= HeadingLevel::H2 {
+                    layout.keep(BODY_SIZE * 4.0);
+                }
+                flush(layout, &mut runs, INDENT);
+            }
+            Event::Start(Tag::Emphasis) => italic = true,
+            Event::End(TagEnd::Emphasis) => italic = false,
+            Event::Start(Tag::List(_)) => {
+                flush(layout, &mut runs, indent(list_depth));
+                list_depth += 1;
+            }
+            Event::End(TagEnd::List(_)) => list_depth = list_depth.saturating_sub(1),
+            Event::Start(Tag::Item) => {
+                flush(layout, &mut runs, indent(list_depth));
+                runs.push(run("\u{2022} ", Font::Regular, TEXT));
+            }
+            Event::End(TagEnd::Item | TagEnd::Paragraph | TagEnd::BlockQuote(_)) => {
+                flush(layout, &mut runs, indent(list_depth))
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                flush(layout, &mut runs, indent(list_depth));
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().map(str::to_string)
+                    }
+                    CodeBlockKind::Indented => None,
+                };
+                code = Some((language, String::new()));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, text)) = code.take() {
+                    layout.code(&text, language.as_deref(), indent(list_depth), theme);
+                    layout.space(BODY_SIZE * 0.3);
+                }
+            }
+            Event::Text(text) => match &mut code {
+                Some((_, code)) => code.push_str(&text),
+                None => runs.push(run(&text, font, TEXT)),
+            },
+            Event::Code(text) => runs.push(run(&text, Font::Mono, TEXT)),
+            Event::SoftBreak | Event::HardBreak => runs.push(run(" ", font, TEXT)),
+            _ => {}
+        }
+    }
+    flush(layout, &mut runs, INDENT);
+}
+
+/// Fonts every PDF reader has built in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Font {
+    Regular,
+    Bold,
+    Italic,
+    Mono,
+}
+
+impl Font {
+    const ALL: [Font; 4] = [Font::Regular, Font::Bold, Font::Italic, Font::Mono];
+
+    /// Name of the font in page resources
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Italic => "F3",
+            Font::Mono => "F4",
+        }
+    }
+
+    fn base_font(self) -> &'static str {
+        match self {
+            Font::Regular => "Helvetica",
+            Font::Bold => "Helvetica-Bold",
+            Font::Italic => "Helvetica-Oblique",
+            Font::Mono => "Courier",
+        }
+    }
+
+    /// Width of a character at `size`, from the fonts' metrics; characters
+    /// beyond ASCII are taken to be as wide as a digit
+    fn width(self, c: char, size: f32) -> f32 {
+        let widths = match self {
+            Font::Mono => return 0.6 * size,
+            Font::Bold => &HELVETICA_BOLD_WIDTHS,
+            Font::Regular | Font::Italic => &HELVETICA_WIDTHS,
+        };
+        let units = (c as usize).checked_sub(32).and_then(|i| widths.get(i)).copied();
+        f32::from(units.unwrap_or(556)) * size / 1000.0
+    }
+
+    fn text_width(self, text: &str, size: f32) -> f32 {
+        text.chars().map(|c| self.width(c, size)).sum()
+    }
+}
+
+/// Helvetica advance widths of ASCII 32-126, in thousandths of the font size
+#[rustfmt::skip]
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Helvetica-Bold advance widths of ASCII 32-126
+#[rustfmt::skip]
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// Text in one font and color
+#[derive(Debug, Clone, PartialEq)]
+struct Run {
+    text: String,
+    font: Font,
+    color: Rgb,
+}
+
+fn run(text: &str, font: Font, color: Rgb) -> Run {
+    Run { text: text.to_string(), font, color }
+}
+
+/// Content laid out top to bottom onto pages
+struct Layout {
+    /// Content streams of the finished pages
+    pages: Vec<Vec<u8>>,
+    /// Content stream of the page being filled
+    page: Vec<u8>,
+    /// Baseline the next line sits under, from the bottom of the page
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Self { pages: Vec::new(), page: Vec::new(), y: PAGE_HEIGHT - MARGIN }
+    }
+
+    fn width() -> f32 {
+        PAGE_WIDTH - 2.0 * MARGIN
+    }
+
+    /// Start a new page unless `height` more points fit on this one
+    fn keep(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.pages.push(std::mem::take(&mut self.page));
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn space(&mut self, height: f32) {
+        if self.y < PAGE_HEIGHT - MARGIN {
+            self.y -= height;
+        }
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Rgb) {
+        let (r, g, b) = color;
+        let _ = writeln!(
+            self.page,
+            "{:.3} {:.3} {:.3} rg {:.2} {:.2} {:.2} {:.2} re f",
+            r, g, b, x, y, width, height
+        );
+    }
+
+    /// Lay out one line of runs at `x`, after moving down a line
+    fn line(&mut self, x: f32, runs: &[Run], size: f32) {
+        let height = size * LEADING;
+        self.keep(height);
+        self.y -= height;
+        let baseline = self.y + (height - size) / 2.0 + size * 0.2;
+        let mut x = x;
+        for run in runs.iter().filter(|r| !r.text.is_empty()) {
+            let (r, g, b) = run.color;
+            let _ = write!(
+                self.page,
+                "BT /{} {:.1} Tf {:.3} {:.3} {:.3} rg {:.2} {:.2} Td ",
+                run.font.resource(),
+                size,
+                r,
+                g,
+                b,
+                x,
+                baseline
+            );
+            self.page.extend(pdf_string(&run.text));
+            self.page.extend(b" Tj ET\n");
+            x += run.font.text_width(&run.text, size);
+        }
+    }
+
+    /// Wrap runs into lines at word boundaries, indented by `indent`, with a
+    /// colored bar to the left when given
+    fn paragraph(&mut self, runs: &[Run], size: f32, indent: f32, bar: Option<Rgb>) {
+        let text_x = MARGIN + indent + if bar.is_some() { 8.0 } else { 0.0 };
+        let width = MARGIN + Self::width() - text_x;
+        for line in wrap(runs, size, width) {
+            self.line(text_x, &line, size);
+            if let Some(bar) = bar {
+                self.rect(MARGIN + indent, self.y, 2.5, size * LEADING, bar);
+            }
+        }
+    }
+
+    /// Lay out code on a shaded background, highlighted for `language` and
+    /// broken wherever a line is too long
+    fn code(&mut self, code: &str, language: Option<&str>, indent: f32, theme: &Theme) {
+        let x = MARGIN + indent;
+        let width = Self::width() - indent;
+        let columns = ((width - 8.0) / Font::Mono.width(' ', CODE_SIZE)).max(1.0) as usize;
+        let height = CODE_SIZE * LEADING;
+
+        for source_line in code.trim_end_matches('\n').lines() {
+            let runs: Vec<Run> = crate::syntax::highlight_line(source_line, language, theme)
+                .into_iter()
+                .map(|span| {
+                    let color = span.style.fg.and_then(printable).unwrap_or(TEXT);
+                    run(&span.content.replace('\t', "    "), Font::Mono, color)
+                })
+                .collect();
+            for line in split_columns(&runs, columns) {
+                self.keep(height);
+                self.rect(x, self.y - height, width, height, CODE_BACKGROUND);
+                self.line(x + 4.0, &line, CODE_SIZE);
+            }
+        }
+    }
+
+    /// The finished document
+    fn finish(mut self, title: &str) -> Vec<u8> {
+        if !self.page.is_empty() || self.pages.is_empty() {
+            self.pages.push(std::mem::take(&mut self.page));
+        }
+
+        // Objects: catalog, page tree, info, fonts, then each page and its content
+        let fonts = 4;
+        let first_page = fonts + Font::ALL.len();
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        let kids: Vec<String> =
+            (0..self.pages.len()).map(|i| format!("{} 0 R", first_page + 2 * i)).collect();
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        objects.push(
+            format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), kids.len())
+                .into_bytes(),
+        );
+        let mut info = b"<< /Title ".to_vec();
+        info.extend(pdf_string(title));
+        info.extend(b" /Producer (sensei) >>");
+        objects.push(info);
+        for font in Font::ALL {
+            objects.push(
+                format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                    font.base_font()
+                )
+                .into_bytes(),
+            );
+        }
+        let font_resources: Vec<String> = Font::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, font)| format!("/{} {} 0 R", font.resource(), fonts + i))
+            .collect();
+        for (i, content) in self.pages.iter().enumerate() {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << {} >> >> /Contents {} 0 R >>",
+                    PAGE_WIDTH,
+                    PAGE_HEIGHT,
+                    font_resources.join(" "),
+                    first_page + 2 * i + 1
+                )
+                .into_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend(content);
+            stream.extend(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            let _ = writeln!(out, "{} 0 obj", i + 1);
+            out.extend(object);
+            out.extend(b"\nendobj\n");
+        }
+        let xref = out.len();
+        let _ = write!(out, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(out, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            out,
+            "trailer\n<< /Size {} /Root 1 0 R /Info 3 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        );
+        out
+    }
+}
+
+/// Break runs into lines no wider than `width`, at spaces where possible
+fn wrap(runs: &[Run], size: f32, width: f32) -> Vec<Vec<Run>> {
+    let mut lines: Vec<Vec<Run>> = Vec::new();
+    let mut line: Vec<Run> = Vec::new();
+    let mut line_width = 0.0;
+
+    // Words keep their trailing spaces, so each can be placed whole
+    let words = runs.iter().flat_map(|r| {
+        r.text.split_inclusive(' ').map(move |word| run(&word.replace('\n', " "), r.font, r.color))
+    });
+    for word in words {
+        let word_width = word.font.text_width(word.text.trim_end(), size);
+        if line_width + word_width > width && !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0.0;
+        }
+        if line.is_empty() && word.text.trim().is_empty() {
+            continue;
+        }
+        // A word wider than a whole line is broken where it overflows
+        let mut word = word;
+        while word.font.text_width(word.text.trim_end(), size) > width {
+            let mut taken = 0.0;
+            let split = word
+                .text
+                .char_indices()
+                .find(|(_, c)| {
+                    taken += word.font.width(*c, size);
+                    taken > width
+                })
+                .map_or(word.text.len(), |(i, _)| i.max(1));
+            let rest = word.text.split_off(split);
+            lines.push(vec![word.clone()]);
+            word.text = rest;
+        }
+        line_width += word.font.text_width(&word.text, size);
+        line.push(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Break a line of monospaced runs every `columns` characters
+fn split_columns(runs: &[Run], columns: usize) -> Vec<Vec<Run>> {
+    let mut lines = vec![Vec::new()];
+    let mut used = 0;
+    for run in runs {
+        let mut chars = run.text.chars().peekable();
+        while chars.peek().is_some() {
+            if used == columns {
+                lines.push(Vec::new());
+                used = 0;
+            }
+            let piece: String = chars.by_ref().take(columns - used).collect();
+            used += piece.chars().count();
+            lines.last_mut().unwrap().push(Run { text: piece, ..run.clone() });
+        }
+    }
+    lines
+}
+
+/// Text as a PDF string literal in WinAnsi encoding
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in text.chars() {
+        let byte = match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                c as u8
+            }
+            ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+            '\u{20ac}' => 0x80,
+            '\u{2026}' => 0x85,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            '\t' => b' ',
+            _ => b'?',
+        };
+        out.push(byte);
+    }
+    out.push(b')');
+    out
+}
+
+/// A theme color darkened to read on white paper; None for terminal palette
+/// colors, which have no fixed value
+fn printable(color: Color) -> Option<Rgb> {
+    match color {
+        Color::Rgb(r, g, b) => {
+            let scale = |c: u8| f32::from(c) / 255.0 * 0.7;
+            Some((scale(r), scale(g), scale(b)))
+        }
+        _ => None,
+    }
+}
+
+/// A CSS hex color (`#rgb` or `#rrggbb`), as configured for highlights
+fn hex_color(css: &str) -> Option<Rgb> {
+    // Slicing by byte below needs every character to be one byte
+    let hex = css.trim().strip_prefix('#').filter(|hex| hex.is_ascii())?;
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok().map(|v| f32::from(v) / 255.0);
+    match hex.len() {
+        6 => Some((channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?)),
+        3 => {
+            let double = |i: usize| channel(&hex[i..=i].repeat(2));
+            Some((double(0)?, double(1)?, double(2)?))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::{Book, CodeBlock};
+    use crate::claude::summary::SummaryCache;
+    use crate::config::ExportConfig;
+    use crate::learning::bank::QuestionBank;
+
+    fn sample_book() -> Book {
+        Book::for_test(vec![
+            ContentBlock::Paragraph("Values (and their owners) move.".into()),
+            ContentBlock::Code(CodeBlock::new("let s = String::new();")),
+        ])
+    }
+
+    fn render(book: &Book, notes: &[Note]) -> Vec<u8> {
+        let ctx = ExportContext {
+            book,
+            notes,
+            questions: &QuestionBank::default(),
+            progress: None,
+            summaries: &SummaryCache::default(),
+            styles: &ExportConfig::default(),
+            theme: &Theme::default(),
+        };
+        PdfExporter.render(&ctx).unwrap()
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[test]
+    fn xref_points_at_each_object() {
+        let book = sample_book();
+        let mut quoted = Note::new_section_note("test", "ch01/s01", "See **why** `moves` matter");
+        quoted.anchor = NoteAnchor::TextRange {
+            block_index: 0,
+            start_char: 0,
+            char_length: 6,
+            selected_text: "Values".into(),
+        };
+        let pdf = render(&book, &[quoted]);
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        let xref: usize =
+            text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with(b"xref\n"));
+        let table = String::from_utf8_lossy(&pdf[xref..]);
+        let offsets = table.lines().skip(3).take_while(|l| l.ends_with(" n "));
+        for (i, line) in offsets.enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj", i + 1).as_bytes()));
+        }
+        assert!(contains(&pdf, b"(\x93Values\x94)"));
+        assert!(contains(&pdf, b"/F2 10.5 Tf"));
+        assert!(contains(&pdf, b"(why)"));
+        assert!(contains(&pdf, b"(moves)"));
+    }
+
+    #[test]
+    fn long_notes_flow_onto_more_pages() {
+        let book = sample_book();
+        let long = "A sentence about ownership (and borrowing). ".repeat(400);
+        let pdf = render(&book, &[Note::new_section_note("test", "ch01/s01", &long)]);
+        let text = String::from_utf8_lossy(&pdf);
+        let count: usize = text
+            .split("/Count ")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|n| n.parse().ok())
+            .unwrap();
+        assert!(count > 2);
+        assert!(contains(&pdf, b"\\(and "));
+    }
+
+    #[test]
+    fn wrap_breaks_at_spaces_within_the_width() {
+        let runs = [run("one two three four", Font::Regular, TEXT)];
+        let lines = wrap(&runs, 10.0, Font::Regular.text_width("one two three", 10.0) + 1.0);
+        let text: Vec<String> =
+            lines.iter().map(|l| l.iter().map(|r| r.text.as_str()).collect()).collect();
+        assert_eq!(text, ["one two three ", "four"]);
+    }
+
+    #[test]
+    fn hex_color_reads_hex_and_rejects_the_rest() {
+        assert_eq!(hex_color("#fff1a8"), Some((1.0, 241.0 / 255.0, 168.0 / 255.0)));
+        assert_eq!(hex_color("#fff"), Some((1.0, 1.0, 1.0)));
+        assert_eq!(hex_color("#éé"), None);
+        assert_eq!(hex_color("#aé"), None);
+        assert_eq!(hex_color("yellow"), None);
+    }
+}
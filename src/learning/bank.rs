@@ -0,0 +1,240 @@
+//! Per-book banks of the reader's own quiz questions
+//!
+//! Questions written or edited in the question editor (`e` on the quiz
+//! results) are kept per section in the data directory and asked before
+//! Claude's, which only fill the rest of the quiz. Deleting one of Claude's
+//! questions remembers its ID so it isn't asked again.
+//!
+//! In the editor a question is plain text: the question, then its answer on
+//! lines starting with a marker. `*` marks the right option and `-` a wrong
+//! one; `=` gives `true` or `false`, or the word for a question with a
+//! `____`; `~` gives a model answer for Claude to grade against.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::app::state::{QuizQuestion, question_id};
+use crate::config::{Config, persist};
+
+/// Marks the blank of a fill-in-the-blank question
+const BLANK: &str = "____";
+
+/// The reader's questions for one section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SectionQuestions {
+    /// Questions written or edited by the reader, in the order added
+    pub custom: Vec<QuizQuestion>,
+    /// IDs of Claude's questions the reader deleted (see `QuizQuestion::id`)
+    pub removed: Vec<String>,
+}
+
+/// A book's question bank
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuestionBank {
+    /// Questions keyed by section path
+    pub sections: BTreeMap<String, SectionQuestions>,
+}
+
+impl QuestionBank {
+    /// Load a book's question bank; empty if it has none
+    pub fn load(book_id: &str) -> Result<Self> {
+        let path = Self::path(book_id)?;
+        let loaded = persist::load(&path, |path| {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read question bank from {:?}", path))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse question bank for {}", book_id))
+        })?;
+        Ok(loaded.unwrap_or_default())
+    }
+
+    /// Save a book's question bank
+    pub fn save(&self, book_id: &str) -> Result<()> {
+        let path = Self::path(book_id)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create question directory {:?}", parent))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .with_context(|| "Failed to serialize question bank")?;
+        persist::write(&path, contents)
+            .with_context(|| format!("Failed to write question bank to {:?}", path))
+    }
+
+    /// Path of a book's question bank
+    fn path(book_id: &str) -> Result<PathBuf> {
+        Ok(Config::data_dir()?.join("questions").join(format!("{}.json", book_id)))
+    }
+
+    /// The reader's questions for a section
+    pub fn custom(&self, section_path: &str) -> &[QuizQuestion] {
+        self.sections.get(section_path).map_or(&[], |s| &s.custom)
+    }
+
+    /// Whether a generated question was deleted by the reader
+    pub fn is_removed(&self, section_path: &str, question: &QuizQuestion) -> bool {
+        let id = question.id();
+        // Banks saved before IDs hold the question text, which maps to its ID
+        self.sections
+            .get(section_path)
+            .is_some_and(|s| s.removed.iter().any(|removed| question_id(removed) == id))
+    }
+
+    /// How many of Claude's questions for a section the reader deleted
+    pub fn removed_count(&self, section_path: &str) -> usize {
+        self.sections.get(section_path).map_or(0, |s| s.removed.len())
+    }
+
+    /// Store a question, replacing `original` if it's one of the reader's
+    pub fn save_question(
+        &mut self,
+        section_path: &str,
+        original: Option<&QuizQuestion>,
+        question: QuizQuestion,
+    ) {
+        let section = self.sections.entry(section_path.to_string()).or_default();
+        match original.and_then(|q| section.custom.iter().position(|c| c == q)) {
+            Some(index) => section.custom[index] = question,
+            None => section.custom.push(question),
+        }
+    }
+
+    /// Delete a question: the reader's own are dropped, Claude's remembered
+    /// so they aren't asked again
+    pub fn delete_question(&mut self, section_path: &str, question: &QuizQuestion) {
+        let section = self.sections.entry(section_path.to_string()).or_default();
+        match section.custom.iter().position(|c| c == question) {
+            Some(index) => {
+                section.custom.remove(index);
+            }
+            None => section.removed.push(question.id()),
+        }
+        if *section == SectionQuestions::default() {
+            self.sections.remove(section_path);
+        }
+    }
+}
+
+/// A question as editor text
+pub fn question_to_text(question: &QuizQuestion) -> String {
+    let answer = match question {
+        QuizQuestion::MultipleChoice { options, correct_index, .. } => options
+            .iter()
+            .enumerate()
+            .map(|(i, option)| {
+                format!("{} {}", if i == *correct_index { '*' } else { '-' }, option)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        QuizQuestion::TrueFalse { answer, .. } => format!("= {}", answer),
+        QuizQuestion::FillInBlank { answer, .. } => format!("= {}", answer),
+        QuizQuestion::ShortAnswer { reference_answer, .. } => format!("~ {}", reference_answer),
+    };
+    format!("{}\n{}", question.question(), answer)
+}
+
+/// Read a question from editor text
+pub fn parse_question(text: &str) -> Result<QuizQuestion, String> {
+    let mut question_lines = Vec::new();
+    let mut options: Vec<(bool, String)> = Vec::new();
+    let mut answer = None;
+    let mut reference = None;
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let (marker, rest) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
+        let rest = rest.trim().to_string();
+        match marker {
+            "*" | "-" => options.push((marker == "*", rest)),
+            "=" => answer = Some(rest),
+            "~" => reference = Some(rest),
+            _ => question_lines.push(line),
+        }
+    }
+    let question = question_lines.join(" ");
+    if question.is_empty() {
+        return Err("Write the question on the first line".to_string());
+    }
+
+    match (options.is_empty(), answer, reference) {
+        (false, None, None) => {
+            let correct: Vec<usize> = (0..options.len()).filter(|&i| options[i].0).collect();
+            if options.len() < 2 || correct.len() != 1 {
+                return Err("Give at least two options, one marked * as correct".to_string());
+            }
+            let options = options.into_iter().map(|(_, option)| option).collect();
+            Ok(QuizQuestion::MultipleChoice { question, options, correct_index: correct[0] })
+        }
+        (true, Some(answer), None) if question.contains(BLANK) && !answer.is_empty() => {
+            Ok(QuizQuestion::FillInBlank { question, answer })
+        }
+        (true, Some(answer), None) => match answer.to_lowercase().as_str() {
+            "true" => Ok(QuizQuestion::TrueFalse { question, answer: true }),
+            "false" => Ok(QuizQuestion::TrueFalse { question, answer: false }),
+            _ => Err(format!("= takes true or false, or the word for a {} blank", BLANK)),
+        },
+        (true, None, Some(reference_answer)) if !reference_answer.is_empty() => {
+            Ok(QuizQuestion::ShortAnswer { question, reference_answer })
+        }
+        (true, None, None) => Err("Add an answer: * and - options, = or ~".to_string()),
+        _ => Err("Use one kind of answer: options, = or ~".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn questions_round_trip_through_editor_text() {
+        let questions = [
+            QuizQuestion::MultipleChoice {
+                question: "Where does Box<T> store its value?".into(),
+                options: vec!["Stack".into(), "Heap".into(), "Registers".into()],
+                correct_index: 1,
+            },
+            QuizQuestion::TrueFalse { question: "Moves copy the heap data.".into(), answer: false },
+            QuizQuestion::FillInBlank {
+                question: "A value has one ____ at a time.".into(),
+                answer: "owner".into(),
+            },
+            QuizQuestion::ShortAnswer {
+                question: "Why can't two &mut borrows coexist?".into(),
+                reference_answer: "They could race on the same data.".into(),
+            },
+        ];
+        for question in questions {
+            assert_eq!(parse_question(&question_to_text(&question)), Ok(question));
+        }
+    }
+
+    #[test]
+    fn parse_question_explains_mistakes() {
+        assert!(parse_question("\n* yes\n- no").unwrap_err().contains("first line"));
+        assert!(parse_question("Pick one\n- a\n- b").unwrap_err().contains("marked *"));
+        assert!(parse_question("Is it?\n= maybe").unwrap_err().contains("true or false"));
+        assert!(parse_question("Is it?\n= true\n~ yes").unwrap_err().contains("one kind"));
+        assert!(parse_question("Is it?").unwrap_err().contains("Add an answer"));
+    }
+
+    #[test]
+    fn delete_drops_custom_and_remembers_generated() {
+        let mut bank = QuestionBank::default();
+        let mine = QuizQuestion::TrueFalse { question: "Mine".into(), answer: true };
+        let claudes = QuizQuestion::TrueFalse { question: "Generated".into(), answer: true };
+        bank.save_question("ch01/s01", None, mine.clone());
+        let edited = QuizQuestion::TrueFalse { question: "Mine, edited".into(), answer: false };
+        bank.save_question("ch01/s01", Some(&mine), edited.clone());
+        assert_eq!(bank.custom("ch01/s01"), std::slice::from_ref(&edited));
+
+        bank.delete_question("ch01/s01", &claudes);
+        assert!(bank.is_removed("ch01/s01", &claudes));
+        let reworded = QuizQuestion::TrueFalse { question: "generated?".into(), answer: false };
+        assert!(bank.is_removed("ch01/s01", &reworded));
+        bank.delete_question("ch01/s01", &edited);
+        assert!(bank.custom("ch01/s01").is_empty());
+        assert!(!bank.is_removed("ch01/s01", &edited));
+    }
+}
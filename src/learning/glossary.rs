@@ -0,0 +1,249 @@
+//! Per-book glossaries of key terms
+//!
+//! Claude extracts a chapter's key terms and their definitions when asked
+//! (`:glossary`). They're kept per book in the data directory, highlighted
+//! where they appear in the text, and defined in a popup when the cursor rests
+//! on one.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, datetime, persist};
+
+/// Separates a term from its definition in Claude's answer (spaced, so paths
+/// like `std::mem::swap` stay whole)
+const SEPARATOR: &str = " :: ";
+
+/// A key term and what it means
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Term {
+    /// The term as written in the book
+    pub term: String,
+    /// One-sentence definition
+    pub definition: String,
+}
+
+/// The terms extracted from one chapter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChapterTerms {
+    /// Chapter title when the terms were extracted
+    pub title: String,
+    /// Terms in the order Claude gave them
+    pub terms: Vec<Term>,
+    /// Unix timestamp when extracted
+    pub created_at: i64,
+}
+
+/// A book's glossary
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Glossary {
+    /// Terms keyed by chapter path
+    pub chapters: BTreeMap<String, ChapterTerms>,
+}
+
+impl Glossary {
+    /// Load a book's glossary; empty if none has been extracted
+    pub fn load(book_id: &str) -> Result<Self> {
+        let path = Self::path(book_id)?;
+        let loaded = persist::load(&path, |path| {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read glossary from {:?}", path))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse glossary for {}", book_id))
+        })?;
+        Ok(loaded.unwrap_or_default())
+    }
+
+    /// Save a book's glossary
+    pub fn save(&self, book_id: &str) -> Result<()> {
+        let path = Self::path(book_id)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create glossary directory {:?}", parent))?;
+        }
+        let contents =
+            serde_json::to_string_pretty(self).with_context(|| "Failed to serialize glossary")?;
+        persist::write(&path, contents)
+            .with_context(|| format!("Failed to write glossary to {:?}", path))
+    }
+
+    /// Path of a book's glossary
+    fn path(book_id: &str) -> Result<PathBuf> {
+        Ok(Config::data_dir()?.join("glossary").join(format!("{}.json", book_id)))
+    }
+
+    /// Store (or replace) a chapter's terms
+    pub fn insert(&mut self, chapter_path: &str, title: &str, terms: Vec<Term>) {
+        let created_at = datetime::now();
+        self.chapters.insert(
+            chapter_path.to_string(),
+            ChapterTerms { title: title.to_string(), terms, created_at },
+        );
+    }
+
+    /// Every term alphabetically with the title of the chapter defining it,
+    /// keeping the first definition of a term found in several chapters
+    pub fn terms(&self) -> Vec<(&Term, &str)> {
+        let mut terms: BTreeMap<String, (&Term, &str)> = BTreeMap::new();
+        for chapter in self.chapters.values() {
+            for term in &chapter.terms {
+                terms.entry(term.term.to_lowercase()).or_insert((term, &chapter.title));
+            }
+        }
+        terms.into_values().collect()
+    }
+
+    /// The glossary as markdown, for the response panel
+    pub fn to_markdown(&self, book_title: &str) -> String {
+        let terms = self.terms();
+        let mut out = format!(
+            "# Glossary: {}\n\n{} terms from {} chapter{}\n\n",
+            book_title,
+            terms.len(),
+            self.chapters.len(),
+            if self.chapters.len() == 1 { "" } else { "s" }
+        );
+        for (term, chapter) in terms {
+            out.push_str(&format!("- **{}**: {} _({})_\n", term.term, term.definition, chapter));
+        }
+        out.push_str("\nUse `:glossary` in another chapter to add its terms.\n");
+        out
+    }
+
+    /// A matcher for the glossary's terms; None if it has none
+    pub fn matcher(&self) -> Option<TermMatcher> {
+        TermMatcher::new(self.terms().into_iter().map(|(term, _)| term.clone()).collect())
+    }
+}
+
+/// Finds glossary terms in text
+#[derive(Debug, Clone)]
+pub struct TermMatcher {
+    /// Every term, longest first so "borrow checker" wins over "borrow"
+    pattern: Regex,
+    /// Terms keyed by their lowercased text
+    terms: BTreeMap<String, Term>,
+}
+
+impl TermMatcher {
+    /// Build a matcher; None if there are no terms
+    pub fn new(mut terms: Vec<Term>) -> Option<Self> {
+        terms.retain(|t| !t.term.trim().is_empty());
+        terms.sort_by_key(|t| std::cmp::Reverse(t.term.chars().count()));
+
+        // Word boundaries only where the term starts or ends with a word character
+        let alternatives: Vec<String> = terms
+            .iter()
+            .map(|t| {
+                let term = t.term.trim();
+                let boundary = |c: Option<char>| {
+                    if c.is_some_and(|c| c.is_alphanumeric() || c == '_') { r"\b" } else { "" }
+                };
+                let (start, end) = (boundary(term.chars().next()), boundary(term.chars().last()));
+                format!("{}{}{}", start, regex::escape(term), end)
+            })
+            .collect();
+        if alternatives.is_empty() {
+            return None;
+        }
+        let pattern = Regex::new(&format!("(?i)(?:{})", alternatives.join("|"))).ok()?;
+        let terms = terms.into_iter().map(|t| (t.term.trim().to_lowercase(), t)).collect();
+        Some(Self { pattern, terms })
+    }
+
+    /// Byte ranges of terms in `text`
+    pub fn ranges(&self, text: &str) -> Vec<Range<usize>> {
+        self.pattern.find_iter(text).map(|m| m.range()).collect()
+    }
+
+    /// The term covering the character at `char_index` in `text`
+    pub fn term_at(&self, text: &str, char_index: usize) -> Option<&Term> {
+        let byte = text.char_indices().nth(char_index)?.0;
+        let found = self.pattern.find_iter(text).find(|m| m.range().contains(&byte))?;
+        self.terms.get(&found.as_str().to_lowercase())
+    }
+}
+
+/// Build the prompt asking Claude for a chapter's key terms
+pub fn glossary_prompt(chapter_title: &str, content: &str) -> String {
+    format!(
+        "Here is a chapter titled \"{}\" from a technical book:\n\n{}\n\n\
+         List the key terms this chapter introduces or relies on, up to 25, each on \
+         its own line as:\nterm{}one-sentence definition\n\
+         Write each term exactly as it appears in the text. Output only the list.",
+        chapter_title, content, SEPARATOR
+    )
+}
+
+/// Read the terms from Claude's answer, skipping lines that aren't terms
+pub fn parse_terms(response: &str) -> Vec<Term> {
+    response
+        .lines()
+        .filter_map(|line| {
+            let (term, definition) = line.split_once(SEPARATOR)?;
+            let term =
+                term.trim().trim_start_matches(['-', '*', ' ']).trim_matches(['*', '`', ' ']);
+            let definition = definition.trim();
+            (!term.is_empty() && !definition.is_empty())
+                .then(|| Term { term: term.to_string(), definition: definition.to_string() })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(term: &str, definition: &str) -> Term {
+        Term { term: term.into(), definition: definition.into() }
+    }
+
+    #[test]
+    fn parse_terms_skips_other_lines() {
+        let response = "Here are the terms:\n\n- **Ownership** :: Each value has one owner.\n\
+                        `std::mem::swap` :: Swaps two values.\nLifetime ::\n";
+        assert_eq!(
+            parse_terms(response),
+            [
+                term("Ownership", "Each value has one owner."),
+                term("std::mem::swap", "Swaps two values.")
+            ]
+        );
+    }
+
+    #[test]
+    fn matcher_prefers_longer_terms_and_whole_words() {
+        let matcher = TermMatcher::new(vec![
+            term("borrow", "Take a reference."),
+            term("borrow checker", "Enforces borrowing rules."),
+            term("&mut", "An exclusive reference."),
+        ])
+        .unwrap();
+
+        let text = "The Borrow checker rejects &mut x but borrowing is fine";
+        assert_eq!(matcher.ranges(text), [4..18, 27..31]);
+        assert_eq!(matcher.term_at(text, 10).unwrap().term, "borrow checker");
+        assert!(matcher.term_at(text, 40).is_none());
+    }
+
+    #[test]
+    fn terms_are_sorted_and_deduplicated() {
+        let mut glossary = Glossary::default();
+        glossary.insert(
+            "ch02",
+            "Traits",
+            vec![term("trait", "Shared behaviour."), term("Box", "A heap pointer.")],
+        );
+        glossary.insert("ch01", "Basics", vec![term("Trait", "Defined first.")]);
+
+        let terms: Vec<(&str, &str)> =
+            glossary.terms().iter().map(|(t, chapter)| (t.definition.as_str(), *chapter)).collect();
+        assert_eq!(terms, [("A heap pointer.", "Traits"), ("Defined first.", "Basics")]);
+        assert!(glossary.to_markdown("Rust").contains("- **Box**: A heap pointer. _(Traits)_"));
+    }
+}
@@ -1,6 +1,12 @@
 //! AI learning features
 
+pub mod bank;
 pub mod claude;
+pub mod glossary;
+pub mod objectives;
+pub mod plan;
 pub mod quiz;
+pub mod rating;
+pub mod schedule;
 
 // Re-exports will be added as modules are implemented
@@ -0,0 +1,133 @@
+//! Learning objectives for sections
+//!
+//! Claude writes a few objectives for a section before it's read
+//! (`:objectives`, or on first opening it with `claude.auto_objectives`).
+//! They're kept per book in the data directory and shown above the section,
+//! where `:objective N` checks them off; each one met counts toward the
+//! reader's mastery of the book.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, datetime, persist};
+
+/// Most objectives kept for a section
+pub const MAX_OBJECTIVES: usize = 5;
+
+/// The objectives written for one section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionObjectives {
+    /// Section title when the objectives were written
+    pub title: String,
+    /// Objectives in the order Claude gave them
+    pub objectives: Vec<String>,
+    /// Unix timestamp when written
+    pub created_at: i64,
+}
+
+/// A book's learning objectives
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Objectives {
+    /// Objectives keyed by section path
+    pub sections: BTreeMap<String, SectionObjectives>,
+}
+
+impl Objectives {
+    /// Load a book's objectives; empty if none have been written
+    pub fn load(book_id: &str) -> Result<Self> {
+        let path = Self::path(book_id)?;
+        let loaded = persist::load(&path, |path| {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read objectives from {:?}", path))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse objectives for {}", book_id))
+        })?;
+        Ok(loaded.unwrap_or_default())
+    }
+
+    /// Save a book's objectives
+    pub fn save(&self, book_id: &str) -> Result<()> {
+        let path = Self::path(book_id)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create objectives directory {:?}", parent))?;
+        }
+        let contents =
+            serde_json::to_string_pretty(self).with_context(|| "Failed to serialize objectives")?;
+        persist::write(&path, contents)
+            .with_context(|| format!("Failed to write objectives to {:?}", path))
+    }
+
+    /// Path of a book's objectives
+    fn path(book_id: &str) -> Result<PathBuf> {
+        Ok(Config::data_dir()?.join("objectives").join(format!("{}.json", book_id)))
+    }
+
+    /// Objectives written for a section
+    pub fn get(&self, section_path: &str) -> Option<&SectionObjectives> {
+        self.sections.get(section_path)
+    }
+
+    /// Store (or replace) a section's objectives
+    pub fn insert(&mut self, section_path: &str, title: &str, objectives: Vec<String>) {
+        let created_at = datetime::now();
+        self.sections.insert(
+            section_path.to_string(),
+            SectionObjectives { title: title.to_string(), objectives, created_at },
+        );
+    }
+}
+
+/// Build the prompt asking Claude for a section's learning objectives
+pub fn objectives_prompt(section_title: &str, content: &str) -> String {
+    format!(
+        "Here is a section titled \"{}\" from a technical book:\n\n{}\n\n\
+         Write 3 to {} learning objectives for a reader about to study it: what they \
+         should be able to do or explain once they have. Start each with a verb, keep \
+         each to one line, and output only the objectives, one per line as a bulleted list.",
+        section_title, content, MAX_OBJECTIVES
+    )
+}
+
+/// Read the objectives from Claude's answer: its bulleted or numbered lines
+pub fn parse_objectives(response: &str) -> Vec<String> {
+    response
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let item = line.strip_prefix(['-', '*', '•']).or_else(|| {
+                let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+                (rest.len() < line.len()).then(|| rest.strip_prefix(['.', ')'])).flatten()
+            })?;
+            // Shown as plain text, so bold markers would show
+            let item = item.replace("**", "");
+            let item = item.trim();
+            (!item.is_empty()).then(|| item.to_string())
+        })
+        .take(MAX_OBJECTIVES)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_objectives_takes_list_items() {
+        let response = "Objectives:\n\n- Explain what a move is\n2. **Predict** when a value \
+                        is dropped\n* Use `clone` deliberately\n2024 was a year\n-\n";
+        assert_eq!(
+            parse_objectives(response),
+            [
+                "Explain what a move is",
+                "Predict when a value is dropped",
+                "Use `clone` deliberately"
+            ]
+        );
+        let many: String = (1..=8).map(|i| format!("- Objective {}\n", i)).collect();
+        assert_eq!(parse_objectives(&many).len(), MAX_OBJECTIVES);
+    }
+}
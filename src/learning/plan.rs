@@ -0,0 +1,185 @@
+//! Study plans across books
+//!
+//! A plan is an ordered list of steps, each a book or a range of its
+//! chapters, e.g. "rust-book ch1-10, then rust-for-rustaceans ch1-4". Progress
+//! is never stored separately: a step is done when every section in it is
+//! marked complete, and the next section to read is the first one that isn't.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::book::{Book, Section};
+use crate::config::progress::BookProgress;
+
+/// An ordered study plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StudyPlan {
+    /// Plan name, e.g. "Rust path"
+    pub name: String,
+    /// Steps in reading order
+    pub steps: Vec<PlanStep>,
+}
+
+/// A book, or a range of its numbered chapters, in a plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanStep {
+    /// Book ID (or the name it was given as, if it wasn't in the library)
+    pub book: String,
+    /// First and last chapter number, inclusive; None for the whole book
+    #[serde(default)]
+    pub chapters: Option<(usize, usize)>,
+}
+
+/// How far through a plan step the reader is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepProgress {
+    /// Sections completed
+    pub done: usize,
+    /// Sections in the step
+    pub total: usize,
+    /// First section not yet completed, as (path, title)
+    pub next: Option<(String, String)>,
+}
+
+impl StepProgress {
+    /// Whether every section in the step is complete
+    pub fn is_complete(&self) -> bool {
+        self.next.is_none()
+    }
+}
+
+impl StudyPlan {
+    /// Parse steps written as "<book> [ch<a>[-<b>]]", separated by commas or "then"
+    pub fn parse(name: &str, spec: &str) -> Result<Self> {
+        let name = name.trim();
+        if name.is_empty() {
+            bail!("A plan needs a name, e.g. \"Rust path: rust-book ch1-10, then ...\"");
+        }
+        let steps = spec
+            .split(',')
+            .flat_map(|part| part.split(" then "))
+            .map(|part| part.trim().trim_start_matches("then ").trim())
+            .filter(|part| !part.is_empty())
+            .map(PlanStep::parse)
+            .collect::<Result<Vec<_>>>()?;
+        if steps.is_empty() {
+            bail!("Plan \"{}\" has no steps", name);
+        }
+        Ok(Self { name: name.to_string(), steps })
+    }
+}
+
+impl PlanStep {
+    /// Parse "<book> [ch<a>[-<b>]]"
+    pub fn parse(text: &str) -> Result<Self> {
+        let (book, last) = text.rsplit_once(' ').unwrap_or((text, ""));
+        let Some(range) = last.strip_prefix("ch") else {
+            return Ok(Self { book: text.trim().to_string(), chapters: None });
+        };
+        let (first, end) = range.split_once('-').unwrap_or((range, range));
+        let (Ok(first), Ok(end)) = (first.parse::<usize>(), end.parse::<usize>()) else {
+            bail!("Invalid chapter range \"{}\" (expected e.g. ch1-10)", last);
+        };
+        if first > end || book.trim().is_empty() {
+            bail!("Invalid plan step \"{}\"", text);
+        }
+        Ok(Self { book: book.trim().to_string(), chapters: Some((first, end)) })
+    }
+
+    /// The step as written, e.g. "rust-book ch1-10"
+    pub fn label(&self) -> String {
+        self.describe(&self.book)
+    }
+
+    /// The step with the book called by `title`, e.g. "The Rust Book ch1-10"
+    pub fn describe(&self, title: &str) -> String {
+        match self.chapters {
+            Some((first, last)) if first == last => format!("{} ch{}", title, first),
+            Some((first, last)) => format!("{} ch{}-{}", title, first, last),
+            None => title.to_string(),
+        }
+    }
+
+    /// Sections of the book covered by this step, in order
+    pub fn sections<'a>(&self, book: &'a Book) -> Vec<&'a Section> {
+        book.chapters
+            .iter()
+            .filter(|c| match (self.chapters, c.number) {
+                (None, _) => true,
+                (Some((first, last)), Some(number)) => (first..=last).contains(&number),
+                (Some(_), None) => false,
+            })
+            .flat_map(|c| &c.sections)
+            .collect()
+    }
+
+    /// Progress through this step of a book
+    pub fn progress(&self, book: &Book, progress: Option<&BookProgress>) -> StepProgress {
+        let sections = self.sections(book);
+        let completed = |s: &Section| {
+            progress.and_then(|p| p.sections.get(&s.path)).is_some_and(|s| s.completed)
+        };
+        StepProgress {
+            done: sections.iter().filter(|s| completed(s)).count(),
+            total: sections.len(),
+            next: sections
+                .iter()
+                .find(|s| !completed(s))
+                .map(|s| (s.path.clone(), s.title.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::{BookMetadata, Chapter};
+    use crate::config::progress::SectionProgress;
+
+    fn sample_book() -> Book {
+        let mut book = Book::new(BookMetadata::for_test("rust-book", "The Rust Book"));
+        book.chapters.push(Chapter::new_unnumbered("Foreword", "foreword"));
+        for number in 1..=3 {
+            let mut chapter =
+                Chapter::new(format!("Chapter {}", number), number, format!("ch{:02}", number));
+            for s in 1..=2 {
+                let path = format!("ch{:02}/s{:02}", number, s);
+                chapter.sections.push(Section::new(format!("{}.{}", number, s), s, path));
+            }
+            book.chapters.push(chapter);
+        }
+        book
+    }
+
+    #[test]
+    fn parse_reads_steps_and_ranges() {
+        let plan = StudyPlan::parse("Rust path", "rust-book ch1-10, then Rust for Rustaceans ch4")
+            .unwrap();
+
+        assert_eq!(plan.steps[0], PlanStep { book: "rust-book".into(), chapters: Some((1, 10)) });
+        assert_eq!(plan.steps[1].label(), "Rust for Rustaceans ch4");
+        assert_eq!(StudyPlan::parse("x", "a then b").unwrap().steps.len(), 2);
+        assert_eq!(PlanStep::parse("Zero To Production").unwrap().chapters, None);
+        assert!(PlanStep::parse("rust-book ch9-2").is_err());
+        assert!(StudyPlan::parse("", "rust-book").is_err());
+    }
+
+    #[test]
+    fn progress_counts_sections_in_range() {
+        let book = sample_book();
+        let step = PlanStep { book: "rust-book".into(), chapters: Some((2, 3)) };
+        let mut progress = BookProgress::default();
+        for path in ["ch01/s01", "ch02/s01"] {
+            progress
+                .sections
+                .insert(path.into(), SectionProgress { completed: true, ..Default::default() });
+        }
+
+        let status = step.progress(&book, Some(&progress));
+        assert_eq!((status.done, status.total), (1, 4));
+        assert_eq!(status.next, Some(("ch02/s02".into(), "2.2".into())));
+
+        let whole = PlanStep { book: "rust-book".into(), chapters: None };
+        assert_eq!(whole.progress(&book, None).total, 6);
+    }
+}
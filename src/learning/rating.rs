@@ -0,0 +1,189 @@
+//! Elo ratings for quiz questions and the reader
+//!
+//! Every graded answer is a match between the reader and the question: a
+//! correct answer is a win for the reader. Both ratings move by how surprising
+//! the result was, so a question most readers miss climbs towards "hard" and
+//! the reader's per-book mastery rises by answering hard questions correctly.
+//! Questions keep their rating across retakes, keyed by their text.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Rating of a new reader and a new question
+pub const INITIAL_RATING: f64 = 1200.0;
+
+/// Largest change to a rating from one answer
+const K_FACTOR: f64 = 32.0;
+
+/// Rating of a successful teach-back as a won match: explaining a section
+/// back counts like answering a hard question
+const TEACH_BACK_RATING: f64 = INITIAL_RATING + 200.0;
+
+/// Rating of a learning objective as a match: meeting one counts like
+/// answering an average question
+const OBJECTIVE_RATING: f64 = INITIAL_RATING;
+
+/// Probability that a reader rated `reader` answers a question rated `question`
+pub fn expected(reader: f64, question: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((question - reader) / 400.0))
+}
+
+/// A quiz question's difficulty rating and record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestionRating {
+    /// Section the question was asked about
+    pub section_path: String,
+    /// Elo rating; higher is harder
+    pub rating: f64,
+    /// Times answered
+    pub attempts: u32,
+    /// Times answered correctly
+    pub correct: u32,
+}
+
+/// Quiz ratings for one book: the reader's mastery and each question's difficulty
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuizRatings {
+    /// Reader's mastery rating for the book
+    pub mastery: f64,
+    /// Graded answers behind the mastery rating
+    pub answered: u32,
+    /// Successful teach-backs behind the mastery rating (see `:teach`)
+    pub teach_backs: u32,
+    /// Learning objectives checked off behind the mastery rating (see `:objective`)
+    pub objectives_met: u32,
+    /// Ratings by question text
+    pub questions: HashMap<String, QuestionRating>,
+}
+
+impl Default for QuizRatings {
+    fn default() -> Self {
+        Self {
+            mastery: INITIAL_RATING,
+            answered: 0,
+            teach_backs: 0,
+            objectives_met: 0,
+            questions: HashMap::new(),
+        }
+    }
+}
+
+impl QuizRatings {
+    /// Whether no answer, teach-back or objective has been rated yet
+    pub fn is_empty(&self) -> bool {
+        self.answered == 0 && self.teach_backs == 0 && self.objectives_met == 0
+    }
+
+    /// Raise the reader's mastery after a successful teach-back
+    pub fn record_teach_back(&mut self) {
+        self.mastery += K_FACTOR * (1.0 - expected(self.mastery, TEACH_BACK_RATING));
+        self.teach_backs += 1;
+    }
+
+    /// Move the reader's mastery as a learning objective is checked off
+    /// (`met`), or unchecked again
+    pub fn record_objective(&mut self, met: bool) {
+        let score = if met { 1.0 } else { 0.0 };
+        self.mastery += K_FACTOR * (score - expected(self.mastery, OBJECTIVE_RATING));
+        if met {
+            self.objectives_met += 1;
+        } else {
+            self.objectives_met = self.objectives_met.saturating_sub(1);
+        }
+    }
+
+    /// Update the reader's and the question's ratings after an answer
+    pub fn record(&mut self, section_path: &str, question: &str, correct: bool) {
+        let entry = self.questions.entry(question.to_string()).or_insert_with(|| QuestionRating {
+            section_path: section_path.to_string(),
+            rating: INITIAL_RATING,
+            attempts: 0,
+            correct: 0,
+        });
+        let score = if correct { 1.0 } else { 0.0 };
+        let change = K_FACTOR * (score - expected(self.mastery, entry.rating));
+
+        self.mastery += change;
+        self.answered += 1;
+        entry.rating -= change;
+        entry.attempts += 1;
+        entry.correct += u32::from(correct);
+    }
+
+    /// Questions about a section, hardest for the reader first
+    pub fn hardest_in(&self, section_path: &str) -> Vec<(&str, &QuestionRating)> {
+        let mut questions: Vec<_> = self
+            .questions
+            .iter()
+            .filter(|(_, q)| q.section_path == section_path)
+            .map(|(text, q)| (text.as_str(), q))
+            .collect();
+        questions.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating).then(a.0.cmp(b.0)));
+        questions
+    }
+
+    /// Questions across the book, hardest first
+    pub fn hardest(&self) -> Vec<(&str, &QuestionRating)> {
+        let mut questions: Vec<_> =
+            self.questions.iter().map(|(text, q)| (text.as_str(), q)).collect();
+        questions.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating).then(a.0.cmp(b.0)));
+        questions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratings_move_by_surprise() {
+        let mut ratings = QuizRatings::default();
+        ratings.record("ch01/s01", "What moves?", false);
+
+        // An even match moves both ratings by half the K-factor
+        assert_eq!(ratings.mastery, INITIAL_RATING - 16.0);
+        assert_eq!(ratings.questions["What moves?"].rating, INITIAL_RATING + 16.0);
+
+        // Answering the now-harder question right gains more than was lost
+        ratings.record("ch01/s01", "What moves?", true);
+        assert!(ratings.mastery > INITIAL_RATING);
+        assert_eq!(ratings.answered, 2);
+        assert_eq!(ratings.questions["What moves?"].attempts, 2);
+        assert_eq!(ratings.questions["What moves?"].correct, 1);
+    }
+
+    #[test]
+    fn teach_backs_raise_mastery_like_hard_questions() {
+        let mut ratings = QuizRatings::default();
+        ratings.record_teach_back();
+        assert!(ratings.mastery > INITIAL_RATING + 16.0);
+        assert_eq!((ratings.answered, ratings.teach_backs), (0, 1));
+        assert!(!ratings.is_empty());
+        assert!(ratings.questions.is_empty());
+    }
+
+    #[test]
+    fn unchecking_an_objective_takes_back_its_gain() {
+        let mut ratings = QuizRatings::default();
+        ratings.record_objective(true);
+        assert_eq!((ratings.mastery, ratings.objectives_met), (INITIAL_RATING + 16.0, 1));
+        ratings.record_objective(false);
+        assert!(ratings.mastery < INITIAL_RATING);
+        assert_eq!(ratings.objectives_met, 0);
+    }
+
+    #[test]
+    fn hardest_in_orders_by_rating() {
+        let mut ratings = QuizRatings::default();
+        ratings.record("ch01/s01", "Easy", true);
+        ratings.record("ch01/s01", "Hard", false);
+        ratings.record("ch02/s01", "Elsewhere", false);
+
+        let hardest: Vec<&str> = ratings.hardest_in("ch01/s01").iter().map(|(t, _)| *t).collect();
+        assert_eq!(hardest, ["Hard", "Easy"]);
+        assert_eq!(ratings.hardest().len(), 3);
+        assert!((expected(1400.0, 1200.0) - 0.76).abs() < 0.01);
+    }
+}
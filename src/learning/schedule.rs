@@ -0,0 +1,331 @@
+//! Spaced-repetition scheduling of section reviews
+//!
+//! Each first attempt at a section's quiz is recorded as a review, graded by
+//! the score. A [`Scheduler`] turns a section's review history into the date
+//! its next review is due. Schedulers keep no state of their own: they replay
+//! the history, so switching algorithm reschedules every section at once.
+//!
+//! Two algorithms are built in:
+//!
+//! - [`Sm2`], the classic SuperMemo 2 ease-factor scheme.
+//! - [`Fsrs`], the Free Spaced Repetition Scheduler (v4.5), which models
+//!   memory stability and difficulty and schedules for a target retention.
+
+use serde::{Deserialize, Serialize};
+
+/// Seconds in a day
+const DAY: i64 = 24 * 60 * 60;
+
+/// How well a section was recalled at a review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Grade {
+    /// Forgotten
+    Again,
+    /// Recalled with difficulty
+    Hard,
+    /// Recalled
+    Good,
+    /// Recalled easily
+    Easy,
+}
+
+impl Grade {
+    /// Grade for a quiz score: every answer right is Easy, most is Good
+    pub fn from_score(correct: usize, total: usize) -> Self {
+        match (correct * 100).checked_div(total).unwrap_or(0) {
+            100.. => Self::Easy,
+            80.. => Self::Good,
+            50.. => Self::Hard,
+            _ => Self::Again,
+        }
+    }
+
+    /// FSRS rating, 1 (Again) to 4 (Easy)
+    fn rating(self) -> f64 {
+        match self {
+            Self::Again => 1.0,
+            Self::Hard => 2.0,
+            Self::Good => 3.0,
+            Self::Easy => 4.0,
+        }
+    }
+}
+
+/// One review of a section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Review {
+    /// When the review happened (Unix seconds)
+    pub at: i64,
+    /// How well it went
+    pub grade: Grade,
+}
+
+/// A spaced-repetition algorithm
+pub trait Scheduler {
+    /// Name shown in the review dashboard
+    fn name(&self) -> &'static str;
+
+    /// Days from the last review until the next, given the history oldest first
+    fn interval_days(&self, history: &[Review]) -> f64;
+
+    /// When the next review is due (Unix seconds); None before the first review
+    fn due(&self, history: &[Review]) -> Option<i64> {
+        let last = history.last()?;
+        Some(last.at + (self.interval_days(history) * DAY as f64).round() as i64)
+    }
+}
+
+/// SuperMemo 2
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sm2;
+
+impl Scheduler for Sm2 {
+    fn name(&self) -> &'static str {
+        "SM-2"
+    }
+
+    fn interval_days(&self, history: &[Review]) -> f64 {
+        let mut ease: f64 = 2.5;
+        let mut repetitions = 0;
+        let mut interval: f64 = 0.0;
+        for review in history {
+            // SM-2 quality on its 0-5 scale; below 3 is a lapse
+            let quality = match review.grade {
+                Grade::Again => 1.0,
+                Grade::Hard => 3.0,
+                Grade::Good => 4.0,
+                Grade::Easy => 5.0,
+            };
+            if quality < 3.0 {
+                repetitions = 0;
+                interval = 1.0;
+            } else {
+                repetitions += 1;
+                interval = match repetitions {
+                    1 => 1.0,
+                    2 => 6.0,
+                    _ => (interval * ease).round(),
+                };
+            }
+            ease = (ease + 0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02)).max(1.3);
+        }
+        interval
+    }
+}
+
+/// FSRS v4.5 default weights, fitted by its authors on a large review dataset
+pub const FSRS_DEFAULT_WEIGHTS: [f64; 17] = [
+    0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367, 1.0461, 2.1072,
+    0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+];
+
+/// Decay of the FSRS forgetting curve
+const DECAY: f64 = -0.5;
+/// Chosen so recall probability is 90% when elapsed days equal stability
+const FACTOR: f64 = 19.0 / 81.0;
+
+/// Reviews of a first grade needed before its initial stability is tuned
+const MIN_TUNING_SAMPLES: usize = 5;
+
+/// Free Spaced Repetition Scheduler
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fsrs {
+    /// Model weights
+    pub weights: [f64; 17],
+    /// Probability of recall to schedule the next review at (0-1)
+    pub desired_retention: f64,
+}
+
+impl Default for Fsrs {
+    fn default() -> Self {
+        Self { weights: FSRS_DEFAULT_WEIGHTS, desired_retention: 0.9 }
+    }
+}
+
+/// FSRS memory state after a review
+#[derive(Debug, Clone, Copy)]
+struct Memory {
+    stability: f64,
+    difficulty: f64,
+}
+
+impl Fsrs {
+    /// Memory state after replaying a history
+    fn memory(&self, history: &[Review]) -> Option<Memory> {
+        let w = &self.weights;
+        let (first, rest) = history.split_first()?;
+        let mut memory = Memory {
+            stability: self.initial_stability(first.grade),
+            difficulty: self.initial_difficulty(first.grade.rating()),
+        };
+        let mut last = first.at;
+        for review in rest {
+            let elapsed = (review.at - last).max(0) as f64 / DAY as f64;
+            let recall = retrievability(elapsed, memory.stability);
+            let g = review.grade.rating();
+            let Memory { stability: s, difficulty: d } = memory;
+
+            let stability = if review.grade == Grade::Again {
+                w[11]
+                    * d.powf(-w[12])
+                    * ((s + 1.0).powf(w[13]) - 1.0)
+                    * (w[14] * (1.0 - recall)).exp()
+            } else {
+                let hard = if review.grade == Grade::Hard { w[15] } else { 1.0 };
+                let easy = if review.grade == Grade::Easy { w[16] } else { 1.0 };
+                s * (1.0
+                    + w[8].exp()
+                        * (11.0 - d)
+                        * s.powf(-w[9])
+                        * ((w[10] * (1.0 - recall)).exp() - 1.0)
+                        * hard
+                        * easy)
+            };
+            let difficulty = d - w[6] * (g - 3.0);
+            let difficulty = w[7] * self.initial_difficulty(3.0) + (1.0 - w[7]) * difficulty;
+
+            memory =
+                Memory { stability: stability.max(0.1), difficulty: difficulty.clamp(1.0, 10.0) };
+            last = review.at;
+        }
+        Some(memory)
+    }
+
+    fn initial_stability(&self, grade: Grade) -> f64 {
+        self.weights[grade.rating() as usize - 1].max(0.1)
+    }
+
+    fn initial_difficulty(&self, rating: f64) -> f64 {
+        (self.weights[4] - (rating - 3.0) * self.weights[5]).clamp(1.0, 10.0)
+    }
+
+    /// Fit the initial stabilities to how well sections were recalled at their
+    /// second review, returning the tuned scheduler and the reviews used
+    ///
+    /// These four weights set the first interval after each grade, which is
+    /// where the defaults fit individual learners least well. A grade keeps
+    /// its default until it has been followed by enough second reviews.
+    pub fn tune<'a>(&self, histories: impl IntoIterator<Item = &'a [Review]>) -> (Self, usize) {
+        let mut samples: [Vec<(f64, bool)>; 4] = Default::default();
+        for history in histories {
+            if let [first, second, ..] = history {
+                let elapsed = (second.at - first.at).max(0) as f64 / DAY as f64;
+                let recalled = second.grade != Grade::Again;
+                samples[first.grade.rating() as usize - 1].push((elapsed, recalled));
+            }
+        }
+
+        let mut tuned = self.clone();
+        let mut used = 0;
+        for (weight, samples) in tuned.weights.iter_mut().zip(&samples) {
+            if samples.len() >= MIN_TUNING_SAMPLES {
+                *weight = fit_stability(samples);
+                used += samples.len();
+            }
+        }
+        (tuned, used)
+    }
+}
+
+impl Scheduler for Fsrs {
+    fn name(&self) -> &'static str {
+        "FSRS"
+    }
+
+    fn interval_days(&self, history: &[Review]) -> f64 {
+        let Some(memory) = self.memory(history) else { return 0.0 };
+        let retention = self.desired_retention.clamp(0.5, 0.99);
+        let interval = memory.stability / FACTOR * (retention.powf(1.0 / DECAY) - 1.0);
+        interval.round().max(1.0)
+    }
+}
+
+/// Probability of recall `elapsed` days after a review, given stability
+fn retrievability(elapsed: f64, stability: f64) -> f64 {
+    (1.0 + FACTOR * elapsed / stability).powf(DECAY)
+}
+
+/// Stability that best explains (elapsed days, recalled) outcomes, searched
+/// over a log-spaced grid from 0.1 to 100 days
+fn fit_stability(samples: &[(f64, bool)]) -> f64 {
+    let log_likelihood = |stability: f64| -> f64 {
+        samples
+            .iter()
+            .map(|&(elapsed, recalled)| {
+                let r = retrievability(elapsed, stability).clamp(1e-6, 1.0 - 1e-6);
+                if recalled { r.ln() } else { (1.0 - r).ln() }
+            })
+            .sum()
+    };
+    (0..=300)
+        .map(|i| 0.1 * 10f64.powf(f64::from(i) / 100.0))
+        .max_by(|a, b| log_likelihood(*a).total_cmp(&log_likelihood(*b)))
+        .unwrap_or(FSRS_DEFAULT_WEIGHTS[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(grades: &[(i64, Grade)]) -> Vec<Review> {
+        grades.iter().map(|&(day, grade)| Review { at: day * DAY, grade }).collect()
+    }
+
+    #[test]
+    fn sm2_grows_intervals_and_resets_on_lapse() {
+        use Grade::*;
+        assert_eq!(Sm2.interval_days(&history(&[(0, Good)])), 1.0);
+        assert_eq!(Sm2.interval_days(&history(&[(0, Good), (1, Good)])), 6.0);
+        assert_eq!(Sm2.interval_days(&history(&[(0, Good), (1, Good), (7, Good)])), 15.0);
+        assert_eq!(Sm2.interval_days(&history(&[(0, Good), (1, Good), (7, Again)])), 1.0);
+        assert_eq!(Sm2.due(&history(&[(0, Good), (1, Good)])), Some(7 * DAY));
+        assert_eq!(Sm2.due(&[]), None);
+    }
+
+    #[test]
+    fn fsrs_schedules_by_grade_and_retention() {
+        use Grade::*;
+        let fsrs = Fsrs::default();
+        // At 90% retention the first interval is the initial stability
+        assert_eq!(fsrs.interval_days(&history(&[(0, Good)])), 4.0);
+        assert!(
+            fsrs.interval_days(&history(&[(0, Easy)])) > fsrs.interval_days(&history(&[(0, Hard)]))
+        );
+
+        let passed = fsrs.interval_days(&history(&[(0, Good), (4, Good)]));
+        let lapsed = fsrs.interval_days(&history(&[(0, Good), (4, Again)]));
+        assert!(passed > 4.0 && lapsed < 4.0, "passed {passed}, lapsed {lapsed}");
+
+        let strict = Fsrs { desired_retention: 0.95, ..Fsrs::default() };
+        assert!(
+            strict.interval_days(&history(&[(0, Easy)]))
+                < fsrs.interval_days(&history(&[(0, Easy)]))
+        );
+    }
+
+    #[test]
+    fn grade_from_score() {
+        assert_eq!(Grade::from_score(5, 5), Grade::Easy);
+        assert_eq!(Grade::from_score(4, 5), Grade::Good);
+        assert_eq!(Grade::from_score(3, 5), Grade::Hard);
+        assert_eq!(Grade::from_score(1, 5), Grade::Again);
+        assert_eq!(Grade::from_score(0, 0), Grade::Again);
+    }
+
+    #[test]
+    fn tune_fits_initial_stability_from_second_reviews() {
+        use Grade::*;
+        // After a Good first review, sections are always forgotten within two days
+        let histories: Vec<Vec<Review>> = (0..8)
+            .map(|i| history(&[(0, Good), (2 + i % 2, Again)]))
+            .chain(std::iter::once(history(&[(0, Easy)])))
+            .collect();
+
+        let (tuned, used) = Fsrs::default().tune(histories.iter().map(Vec::as_slice));
+
+        assert_eq!(used, 8);
+        assert!(tuned.weights[2] < FSRS_DEFAULT_WEIGHTS[2]);
+        assert_eq!(tuned.weights[3], FSRS_DEFAULT_WEIGHTS[3]);
+    }
+}
@@ -10,6 +10,8 @@ pub mod config;
 pub mod export;
 pub mod learning;
 pub mod notes;
+pub mod runner;
+pub mod speech;
 pub mod syntax;
 pub mod theme;
 pub mod ui;
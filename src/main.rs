@@ -16,9 +16,16 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Add a book to your library
+    ///
+    /// Adding a book already in the library under another name offers to
+    /// update that entry instead of adding a second one.
     Add {
-        /// Path to EPUB file or markdown directory
+        /// Path to an EPUB, DOCX or AsciiDoc file, or a markdown directory
         path: String,
+        /// Refresh the matching library book from this newer file, keeping
+        /// its progress and notes
+        #[arg(long)]
+        update: bool,
     },
     /// List books in your library
     List,
@@ -28,10 +35,57 @@ enum Commands {
         name: String,
     },
     /// Export your progress and notes
+    ///
+    /// For the claude-md format, only the region between sensei's marker
+    /// comments is replaced in an existing file; content outside it is kept.
+    /// The html format is a standalone report in the active theme's colors.
+    /// The pdf format lays out notes by chapter for printing, with quoted
+    /// passages and highlighted code.
     Export {
-        /// Output path for CLAUDE.md
-        #[arg(short, long, default_value = "CLAUDE.md")]
-        output: String,
+        /// Export format (claude-md, html, anki, pdf)
+        #[arg(default_value = "claude-md")]
+        format: String,
+        /// Output path (defaults to CLAUDE.md or <book-id>.<extension>)
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Name or ID of the book to export (defaults to the last opened book)
+        #[arg(short, long)]
+        book: Option<String>,
+    },
+    /// Export or import reading and quiz progress, to move it between machines
+    Progress {
+        #[command(subcommand)]
+        action: ProgressAction,
+    },
+    /// Check that parsing, rendering, storage and the Claude API work
+    ///
+    /// Prints a pass/fail report and exits with status 1 if any step fails.
+    /// The API step runs only when an API key is stored.
+    Selftest {
+        /// Skip the Claude API request
+        #[arg(long)]
+        offline: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProgressAction {
+    /// Write all progress to a file
+    Export {
+        /// File to write
+        file: String,
+    },
+    /// Read progress from an exported file
+    ///
+    /// Without --merge the file replaces your progress. With it, each section
+    /// keeps whichever side was used most recently, and quiz history from
+    /// both is kept.
+    Import {
+        /// File to read
+        file: String,
+        /// Merge with existing progress instead of replacing it
+        #[arg(long)]
+        merge: bool,
     },
 }
 
@@ -49,18 +103,41 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Some(Commands::Add { path }) => {
+        Some(Commands::Add { path, update }) => {
+            load_config(true)?;
             let path = PathBuf::from(&path);
+            if update {
+                update_book(&path, None);
+                return Ok(());
+            }
+
             println!("Adding book from: {}", path.display());
 
             match book::add_book(&path) {
-                Ok(entry) => {
+                Ok(book::Added::New(entry)) => {
                     println!("Successfully added: {}", entry.metadata.title);
                     println!("  ID: {}", entry.metadata.id);
                     if let Some(author) = &entry.metadata.author {
                         println!("  Author: {}", author);
                     }
                 }
+                Ok(book::Added::Duplicate(existing)) => {
+                    let title = &existing.metadata.title;
+                    if existing.shared {
+                        println!("{} is already in the shared library", title);
+                        return Ok(());
+                    }
+                    if path.canonicalize().is_ok_and(|p| p == existing.metadata.source.path()) {
+                        println!("{} is already in your library ({})", title, existing.metadata.id);
+                        return Ok(());
+                    }
+                    println!("This is the same book as {} ({})", title, existing.metadata.id);
+                    if !confirm("Update that entry to read from this file instead?")? {
+                        eprintln!("Cancelled");
+                        std::process::exit(1);
+                    }
+                    update_book(&path, Some(&existing.metadata.id));
+                }
                 Err(e) => {
                     eprintln!("Failed to add book: {}", e);
                     std::process::exit(1);
@@ -68,6 +145,7 @@ async fn main() -> Result<()> {
             }
         }
         Some(Commands::List) => {
+            load_config(true)?;
             let library = book::Library::load()?;
 
             if library.list().is_empty() {
@@ -76,7 +154,8 @@ async fn main() -> Result<()> {
                 println!("Books in library:");
                 println!();
                 for entry in library.list() {
-                    println!("  {} - {}", entry.metadata.id, entry.metadata.title);
+                    let shared = if entry.shared { " [shared]" } else { "" };
+                    println!("  {} - {}{}", entry.metadata.id, entry.metadata.title, shared);
                     if let Some(author) = &entry.metadata.author {
                         println!("    Author: {}", author);
                     }
@@ -84,16 +163,33 @@ async fn main() -> Result<()> {
             }
         }
         Some(Commands::Remove { name }) => {
-            // Try to find by ID first, then by title
+            load_config(true)?;
+            // Find by ID, title, or fuzzy title match
             let library = book::Library::load()?;
 
-            let id_to_remove = if library.find_by_id(&name).is_some() {
-                name.clone()
-            } else if let Some(entry) = library.find_by_title(&name) {
-                entry.metadata.id.clone()
-            } else {
-                eprintln!("Book not found: {}", name);
-                std::process::exit(1);
+            let id_to_remove = match library.resolve(&name) {
+                book::BookMatch::Found(entry) => entry.metadata.id.clone(),
+                // Only a loose match, so ask before removing it
+                book::BookMatch::Likely(entry) => {
+                    let question =
+                        format!("Remove {} ({})?", entry.metadata.title, entry.metadata.id);
+                    if !confirm(&question)? {
+                        eprintln!("Cancelled");
+                        std::process::exit(1);
+                    }
+                    entry.metadata.id.clone()
+                }
+                book::BookMatch::Ambiguous(matches) => match choose_book(&name, &matches)? {
+                    Some(id) => id,
+                    None => {
+                        eprintln!("Cancelled");
+                        std::process::exit(1);
+                    }
+                },
+                book::BookMatch::NotFound => {
+                    eprintln!("Book not found: {}", name);
+                    std::process::exit(1);
+                }
             };
 
             match book::remove_book(&id_to_remove) {
@@ -110,17 +206,197 @@ async fn main() -> Result<()> {
                 }
             }
         }
-        Some(Commands::Export { output }) => {
-            // TODO: Implement full export
-            println!("Export to {} - Coming soon!", output);
+        Some(Commands::Export { format, output, book: query }) => {
+            let config = load_config(false)?;
+            let registry = sensei::export::ExporterRegistry::with_builtin();
+            let Some(exporter) = registry.get(&format) else {
+                eprintln!("Unknown export format: {} (available: {})", format, registry.names());
+                std::process::exit(1);
+            };
+            let library = book::Library::load()?;
+
+            let entry = match &query {
+                Some(name) => match library.resolve(name) {
+                    book::BookMatch::Found(entry) | book::BookMatch::Likely(entry) => entry,
+                    book::BookMatch::Ambiguous(matches) => {
+                        let Some(id) = choose_book(name, &matches)? else {
+                            eprintln!("Cancelled");
+                            std::process::exit(1);
+                        };
+                        library.find_by_id(&id).expect("chosen book is in library")
+                    }
+                    book::BookMatch::NotFound => {
+                        eprintln!("Book not found: {}", name);
+                        std::process::exit(1);
+                    }
+                },
+                None => {
+                    let session = sensei::config::session::Session::load()?;
+                    match session.current_book_id.as_deref().and_then(|id| library.find_by_id(id)) {
+                        Some(entry) => entry,
+                        None => {
+                            eprintln!("No book to export. Use --book <name>");
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            };
+
+            let loaded = book::load_book(entry)?;
+            let book_id = &entry.metadata.id;
+            let progress = sensei::config::progress::Progress::load()?;
+            let notes = sensei::notes::NotesStore::load()?;
+            let summaries = sensei::claude::summary::SummaryCache::load(book_id)?;
+            let questions = sensei::learning::bank::QuestionBank::load(book_id)?;
+            let theme = entry
+                .settings
+                .theme
+                .as_deref()
+                .and_then(|name| config.theme_named(name))
+                .unwrap_or_else(|| config.active_theme());
+
+            let ctx = sensei::export::ExportContext {
+                book: &loaded,
+                notes: notes.get_book_notes(book_id),
+                questions: &questions,
+                progress: progress.books.get(book_id),
+                summaries: &summaries,
+                styles: &config.export,
+                theme: &theme,
+            };
+            let output = output.unwrap_or_else(|| exporter.default_file_name(&ctx));
+            exporter.export(&ctx, std::path::Path::new(&output))?;
+            println!("Exported {} to {}", entry.metadata.title, output);
+        }
+        Some(Commands::Progress { action: ProgressAction::Export { file } }) => {
+            let progress = sensei::config::progress::Progress::load()?;
+            progress.export(std::path::Path::new(&file))?;
+            println!("Exported progress for {} books to {}", progress.books.len(), file);
+        }
+        Some(Commands::Progress { action: ProgressAction::Import { file, merge } }) => {
+            let imported = sensei::config::progress::Progress::import(std::path::Path::new(&file))?;
+            if merge {
+                let mut progress = sensei::config::progress::Progress::load()?;
+                let sections = progress.merge(&imported);
+                progress.save()?;
+                println!("Merged {} sections from {}", sections, file);
+            } else {
+                imported.save()?;
+                println!("Imported progress for {} books from {}", imported.books.len(), file);
+            }
+        }
+        Some(Commands::Selftest { offline }) => {
+            let config = load_config(false)?;
+            let steps = sensei::app::selftest::run(&config, !offline).await;
+            print!("{}", sensei::app::selftest::report(&steps));
+            if !sensei::app::selftest::passed(&steps) {
+                std::process::exit(1);
+            }
         }
         None => {
             // Launch TUI
-            let config = Config::load()?;
-            let mut app = App::new(config)?;
+            let mut app = App::new(load_config(true)?)?;
             app.run().await?;
         }
     }
 
     Ok(())
 }
+
+/// Load the config and apply its process-wide settings, first picking up new
+/// (or dropping removed) books from the shared library if `sync_library`
+fn load_config(sync_library: bool) -> Result<Config> {
+    let config = Config::load()?;
+    sensei::config::encryption::set_enabled(config.encrypt_notes);
+    sensei::ui::locale::set_language(sensei::ui::locale::Language::resolve(
+        config.ui_language.as_deref(),
+    ));
+
+    if sync_library && let Err(e) = book::sync_shared_library(config.shared_library.as_deref()) {
+        tracing::warn!("Failed to sync shared library: {}", e);
+    }
+    Ok(config)
+}
+
+/// Refresh a library book in place from a newer file, reporting what changed
+fn update_book(path: &std::path::Path, book_id: Option<&str>) {
+    use sensei::book::compare::SectionChange;
+
+    println!("Updating book from: {}", path.display());
+    let update = match book::update_book(path, book_id) {
+        Ok(update) => update,
+        Err(e) => {
+            eprintln!("Failed to update book: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("Successfully updated: {}", update.entry.metadata.title);
+    println!("  ID: {}", update.entry.metadata.id);
+    let Some(changes) = update.changes else { return };
+    let changed = changes.iter().filter(|c| c.is_changed()).count();
+    let removed = changes.iter().filter(|c| matches!(c, SectionChange::Removed { .. })).count();
+    println!("  {} of {} sections changed, {} removed", changed, changes.len(), removed);
+
+    // Progress and notes are keyed by section path, so follow sections that moved
+    let moves: std::collections::HashMap<&str, &str> = changes
+        .iter()
+        .filter_map(|change| match change {
+            SectionChange::Matched { old_path, new_path, .. } if old_path != new_path => {
+                Some((old_path.as_str(), new_path.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+    if moves.is_empty() {
+        return;
+    }
+    let book_id = &update.entry.metadata.id;
+    let moved = (|| -> Result<(usize, usize)> {
+        let mut progress = sensei::config::progress::Progress::load()?;
+        let sections = progress.book_mut(book_id).move_sections(&moves);
+        progress.save()?;
+        let mut session = sensei::config::session::Session::load()?;
+        if let Some(book) = session.books.get_mut(book_id) {
+            book.move_sections(&moves);
+            session.save()?;
+        }
+        let mut notes = sensei::notes::NotesStore::load()?;
+        let notes_moved = notes.move_sections(book_id, &moves);
+        notes.save()?;
+        Ok((sections, notes_moved))
+    })();
+    match moved {
+        Ok((sections, notes)) => {
+            println!(
+                "  Moved progress on {} sections and {} notes to their new paths",
+                sections, notes
+            )
+        }
+        Err(e) => eprintln!("Failed to move progress and notes to the new section paths: {}", e),
+    }
+}
+
+/// Ask the user a yes/no question, defaulting to no
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Ask the user to pick one of several matching books
+fn choose_book(query: &str, matches: &[&book::LibraryEntry]) -> Result<Option<String>> {
+    println!("Multiple books match \"{}\":", query);
+    for (i, entry) in matches.iter().enumerate() {
+        println!("  {}) {} ({})", i + 1, entry.metadata.title, entry.metadata.id);
+    }
+    print!("Choose [1-{}] or press Enter to cancel: ", matches.len());
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    let choice = input.trim().parse::<usize>().ok().and_then(|n| n.checked_sub(1));
+    Ok(choice.and_then(|i| matches.get(i)).map(|e| e.metadata.id.clone()))
+}
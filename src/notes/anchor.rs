@@ -0,0 +1,198 @@
+//! Re-anchoring notes after a book changes
+//!
+//! Text-selection notes point at a block index and character offset, which
+//! shift when a book is re-parsed from an edited source. Each anchor also keeps
+//! the selected text, so on load the text is looked up again: first where the
+//! anchor says, then in the blocks nearest to it. Matching ignores case,
+//! runs of whitespace and inline markdown markers so small edits don't lose
+//! the note. Notes whose text can't be found are flagged rather than dropped.
+
+use super::model::{Note, NoteAnchor};
+use crate::book::{Book, ContentBlock, Section};
+
+/// Characters of the selection used to find it again
+const NEEDLE_CHARS: usize = 80;
+
+/// What re-anchoring a book's notes changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReanchorReport {
+    /// Notes whose anchor moved to a new position
+    pub moved: usize,
+    /// Notes whose text could not be found
+    pub lost: usize,
+    /// Lost notes whose text was found again
+    pub recovered: usize,
+}
+
+impl ReanchorReport {
+    /// Whether any note was updated
+    pub fn changed(&self) -> bool {
+        self.moved + self.recovered > 0 || self.lost > 0
+    }
+}
+
+/// Re-anchor a book's text-selection notes against its current content
+///
+/// Notes changed are touched, so that saving keeps the new anchors over
+/// copies of the notes on disk.
+pub fn reanchor(notes: &mut [Note], book: &Book) -> ReanchorReport {
+    let mut report = ReanchorReport::default();
+    for note in notes.iter_mut().filter(|n| n.is_selection_note()) {
+        let was_lost = note.anchor_lost;
+        let found =
+            book.find_section_by_path(&note.section_path).and_then(|s| locate(&note.anchor, s));
+
+        match found {
+            Some((block, start)) => {
+                note.anchor_lost = false;
+                if let NoteAnchor::TextRange { block_index, start_char, .. } = &mut note.anchor
+                    && (*block_index, *start_char) != (block, start)
+                {
+                    (*block_index, *start_char) = (block, start);
+                    report.moved += 1;
+                    note.touch();
+                }
+                if was_lost {
+                    report.recovered += 1;
+                    note.touch();
+                }
+            }
+            None if !was_lost => {
+                note.anchor_lost = true;
+                report.lost += 1;
+                note.touch();
+            }
+            None => {}
+        }
+    }
+    report
+}
+
+/// Current block index and character offset of an anchor's text in a section
+///
+/// Section anchors, and selections too short to search for, stay where they are.
+pub fn locate(anchor: &NoteAnchor, section: &Section) -> Option<(usize, usize)> {
+    let NoteAnchor::TextRange { block_index, start_char, selected_text, .. } = anchor else {
+        return None;
+    };
+    let first_line = selected_text.lines().find(|l| !l.trim().is_empty()).unwrap_or_default();
+    let needle: String = first_line.trim().chars().take(NEEDLE_CHARS).collect();
+    let (needle, _) = normalize(&needle);
+    if needle.is_empty() {
+        return Some((*block_index, *start_char));
+    }
+
+    section
+        .content
+        .iter()
+        .enumerate()
+        .filter_map(|(i, block)| Some((i, block_text(block)?)))
+        .flat_map(|(i, text)| matches(&text, &needle).into_iter().map(move |start| (i, start)))
+        .min_by_key(|&(i, start)| (i.abs_diff(*block_index), start.abs_diff(*start_char)))
+}
+
+/// Text of a block as selections index into it
+pub fn block_text(block: &ContentBlock) -> Option<String> {
+    match block {
+        ContentBlock::Paragraph(text)
+        | ContentBlock::Blockquote(text)
+        | ContentBlock::Heading { text, .. } => Some(text.clone()),
+        ContentBlock::Code(code) => Some(code.code.clone()),
+        ContentBlock::UnorderedList(items) | ContentBlock::OrderedList(items) => {
+            Some(items.join("\n"))
+        }
+        _ => None,
+    }
+}
+
+/// Character offsets in `text` where the normalized `needle` starts
+fn matches(text: &str, needle: &[char]) -> Vec<usize> {
+    let (haystack, offsets) = normalize(text);
+    if haystack.len() < needle.len() {
+        return Vec::new();
+    }
+    (0..=haystack.len() - needle.len())
+        .filter(|&i| haystack[i..i + needle.len()] == *needle)
+        .map(|i| offsets[i])
+        .collect()
+}
+
+/// Lowercased text without inline markers and with whitespace runs collapsed,
+/// alongside the original character offset of each kept character
+fn normalize(text: &str) -> (Vec<char>, Vec<usize>) {
+    let mut chars = Vec::new();
+    let mut offsets = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        if matches!(c, '*' | '_' | '`') {
+            continue;
+        }
+        if c.is_whitespace() {
+            if chars.last().is_some_and(|&last| last != ' ') {
+                chars.push(' ');
+                offsets.push(i);
+            }
+            continue;
+        }
+        chars.extend(c.to_lowercase());
+        offsets.resize(chars.len(), i);
+    }
+    (chars, offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::{BookMetadata, Chapter};
+
+    fn book(blocks: &[&str]) -> Book {
+        let mut book = Book::new(BookMetadata::for_test("test", "Test"));
+        let mut section = Section::new("Moves", 1, "ch01/s01");
+        section.content = blocks.iter().map(|b| ContentBlock::Paragraph(b.to_string())).collect();
+        let mut chapter = Chapter::new("Ownership", 1, "ch01");
+        chapter.sections.push(section);
+        book.chapters.push(chapter);
+        book
+    }
+
+    fn anchor_of(note: &Note) -> (usize, usize) {
+        match note.anchor {
+            NoteAnchor::TextRange { block_index, start_char, .. } => (block_index, start_char),
+            NoteAnchor::Section => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn reanchor_follows_moved_and_reworded_text() {
+        let mut notes = vec![
+            Note::new_selection_note("test", "ch01/s01", "a", 0, 4, "values have owners"),
+            Note::new_selection_note("test", "ch01/s01", "b", 1, 0, "The owner drops it"),
+        ];
+        notes[0].updated_at = 0;
+        let edited = book(&[
+            "Intro added before.",
+            "All **values**  have\nowners here.",
+            "When the owner drops it, memory is freed.",
+        ]);
+
+        let report = reanchor(&mut notes, &edited);
+
+        assert_eq!(report, ReanchorReport { moved: 2, lost: 0, recovered: 0 });
+        assert_eq!(anchor_of(&notes[0]), (1, 6));
+        assert_eq!(anchor_of(&notes[1]), (2, 5));
+        assert!(notes[0].updated_at > 0);
+    }
+
+    #[test]
+    fn reanchor_flags_and_recovers_lost_notes() {
+        let mut notes = vec![Note::new_selection_note("test", "ch01/s01", "a", 0, 0, "borrow")];
+
+        let report = reanchor(&mut notes, &book(&["Nothing relevant."]));
+        assert_eq!(report.lost, 1);
+        assert!(notes[0].anchor_lost);
+
+        let report = reanchor(&mut notes, &book(&["You may borrow it."]));
+        assert_eq!(report, ReanchorReport { moved: 1, lost: 0, recovered: 1 });
+        assert!(!notes[0].anchor_lost);
+        assert_eq!(anchor_of(&notes[0]), (0, 8));
+    }
+}
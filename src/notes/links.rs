@@ -0,0 +1,113 @@
+//! Links between notes and sections
+//!
+//! A note links to another note with `[[note:ID]]` (short or full ID) and to
+//! a section of its own book with `[[section:path]]`. The store keeps an
+//! index of section links so a section can list the notes that reference it.
+
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::model::Note;
+
+static LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[(note|section):\s*([^\]\s]+)\s*\]\]").unwrap());
+
+/// Where a link in a note goes
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NoteLink {
+    /// A note, by short or full ID
+    Note(String),
+    /// A section of the note's book, by path
+    Section(String),
+}
+
+impl NoteLink {
+    /// How the link is described to the reader
+    pub fn label(&self) -> String {
+        match self {
+            NoteLink::Note(id) => format!("note #{}", id),
+            NoteLink::Section(path) => format!("section {}", path),
+        }
+    }
+}
+
+/// Links in a note's content, with their byte ranges, in order
+pub fn find(content: &str) -> Vec<(Range<usize>, NoteLink)> {
+    LINK.captures_iter(content)
+        .map(|caps| {
+            let target = caps[2].to_string();
+            let link = match &caps[1] {
+                "note" => NoteLink::Note(target.trim_start_matches('#').to_lowercase()),
+                _ => NoteLink::Section(target),
+            };
+            (caps.get(0).map_or(0..0, |m| m.range()), link)
+        })
+        .collect()
+}
+
+/// Notes linking to each section
+#[derive(Debug, Clone, Default)]
+pub struct Backlinks {
+    /// IDs of the notes linking to each (book ID, section path)
+    linked_from: HashMap<(String, String), BTreeSet<String>>,
+}
+
+impl Backlinks {
+    /// Record a note's section links
+    pub fn add(&mut self, note: &Note) {
+        for (_, link) in find(&note.content) {
+            if let NoteLink::Section(path) = link {
+                let target = (note.book_id.clone(), path);
+                self.linked_from.entry(target).or_default().insert(note.id.clone());
+            }
+        }
+    }
+
+    /// Forget a note's links
+    pub fn remove(&mut self, note_id: &str) {
+        self.linked_from.retain(|_, from| {
+            from.remove(note_id);
+            !from.is_empty()
+        });
+    }
+
+    /// IDs of the notes linking to a section
+    pub fn to_section(&self, book_id: &str, path: &str) -> Vec<&str> {
+        let target = (book_id.to_string(), path.to_string());
+        self.linked_from.get(&target).into_iter().flatten().map(String::as_str).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_parses_note_and_section_links() {
+        let content = "See [[note:#3FA2C1]] and [[section: ch03/s02 ]], not [[ch01]].";
+        let links = find(content);
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].1, NoteLink::Note("3fa2c1".to_string()));
+        assert_eq!(&content[links[0].0.clone()], "[[note:#3FA2C1]]");
+        assert_eq!(links[1].1, NoteLink::Section("ch03/s02".to_string()));
+    }
+
+    #[test]
+    fn backlinks_follow_edits() {
+        let mut linking =
+            Note::new_section_note("rust", "ch04/s01", "Builds on [[section:ch02/s01]]");
+        let mut backlinks = Backlinks::default();
+        backlinks.add(&linking);
+
+        assert_eq!(backlinks.to_section("rust", "ch02/s01"), [linking.id.as_str()]);
+        assert!(backlinks.to_section("go", "ch02/s01").is_empty());
+
+        linking.content = "Nothing linked now".to_string();
+        backlinks.remove(&linking.id);
+        backlinks.add(&linking);
+        assert!(backlinks.to_section("rust", "ch02/s01").is_empty());
+    }
+}
@@ -3,9 +3,16 @@
 //! This module provides note storage and management for book content.
 //! Notes can be attached at the section level or to specific text selections.
 
+pub mod anchor;
+pub mod links;
 pub mod model;
+pub mod replace;
+pub mod search;
+pub mod stats;
 pub mod storage;
 
 // Re-exports
-pub use model::{Note, NoteAnchor, NoteSource};
+pub use model::{HighlightColor, Note, NoteAnchor, NoteSource};
+pub use search::NoteQuery;
+pub use stats::WritingLog;
 pub use storage::NotesStore;
@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::config::datetime;
+
 /// A note attached to book content
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Note {
@@ -21,14 +23,18 @@ pub struct Note {
     pub source: NoteSource,
     /// How the note is anchored to content
     pub anchor: NoteAnchor,
+    /// The anchored text could not be found after the book changed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub anchor_lost: bool,
+    /// Color of a highlight; highlights mark text without needing any content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<HighlightColor>,
 }
 
 impl Note {
     /// Create a new section-level note
     pub fn new_section_note(book_id: &str, section_path: &str, content: &str) -> Self {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_secs() as i64);
+        let now = datetime::now();
 
         Self {
             id: generate_id(),
@@ -39,6 +45,8 @@ impl Note {
             updated_at: now,
             source: NoteSource::User,
             anchor: NoteAnchor::Section,
+            anchor_lost: false,
+            highlight: None,
         }
     }
 
@@ -51,9 +59,7 @@ impl Note {
         start_char: usize,
         selected_text: &str,
     ) -> Self {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_secs() as i64);
+        let now = datetime::now();
 
         Self {
             id: generate_id(),
@@ -69,15 +75,57 @@ impl Note {
                 char_length: selected_text.chars().count(),
                 selected_text: selected_text.to_string(),
             },
+            anchor_lost: false,
+            highlight: None,
+        }
+    }
+
+    /// Create a highlight of selected text, with no content
+    pub fn new_highlight(
+        book_id: &str,
+        section_path: &str,
+        color: HighlightColor,
+        block_index: usize,
+        start_char: usize,
+        selected_text: &str,
+    ) -> Self {
+        Self {
+            highlight: Some(color),
+            ..Self::new_selection_note(
+                book_id,
+                section_path,
+                "",
+                block_index,
+                start_char,
+                selected_text,
+            )
         }
     }
 
     /// Update the note content
     pub fn update_content(&mut self, content: &str) {
         self.content = content.to_string();
-        self.updated_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_secs() as i64);
+        self.touch();
+    }
+
+    /// Mark the note as changed now
+    pub fn touch(&mut self) {
+        self.updated_at = datetime::now();
+    }
+
+    /// A copy of this note, with a new ID, attached to another book's section
+    pub fn copy_to(&self, book_id: &str, section_path: &str) -> Self {
+        Self {
+            id: generate_id(),
+            book_id: book_id.to_string(),
+            section_path: section_path.to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// Short, stable ID shown in the notes panel and accepted by note commands
+    pub fn short_id(&self) -> String {
+        short_id(&self.id)
     }
 
     /// Check if this is a section-level note
@@ -100,9 +148,7 @@ impl Note {
         start_char: Option<usize>,
         selected_text: Option<&str>,
     ) -> Self {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_or(0, |d| d.as_secs() as i64);
+        let now = datetime::now();
 
         // Format content as Q&A
         let content = format!("**Q:** {}\n\n**A:** {}", question, answer);
@@ -127,6 +173,8 @@ impl Note {
             updated_at: now,
             source: NoteSource::Claude,
             anchor,
+            anchor_lost: false,
+            highlight: None,
         }
     }
 
@@ -134,6 +182,56 @@ impl Note {
     pub fn is_claude_note(&self) -> bool {
         matches!(self.source, NoteSource::Claude)
     }
+
+    /// Check if this is a highlight
+    pub fn is_highlight(&self) -> bool {
+        self.highlight.is_some()
+    }
+}
+
+/// Color category of a highlight
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HighlightColor {
+    #[default]
+    Yellow,
+    Green,
+    Blue,
+    Pink,
+    Purple,
+}
+
+impl HighlightColor {
+    /// Every color, in the order `next` cycles through them
+    pub const ALL: [Self; 5] = [Self::Yellow, Self::Green, Self::Blue, Self::Pink, Self::Purple];
+
+    /// Lowercase name, as typed in commands
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Yellow => "yellow",
+            Self::Green => "green",
+            Self::Blue => "blue",
+            Self::Pink => "pink",
+            Self::Purple => "purple",
+        }
+    }
+
+    /// Parse a color name or an unambiguous start of one, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        let mut matches =
+            Self::ALL.into_iter().filter(|c| !name.is_empty() && c.name().starts_with(&name));
+        match (matches.next(), matches.next()) {
+            (Some(color), None) => Some(color),
+            _ => None,
+        }
+    }
+
+    /// The color after this one, wrapping around
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&c| c == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
 }
 
 /// Where a note originated from
@@ -148,6 +246,17 @@ pub enum NoteSource {
     Quiz,
 }
 
+impl NoteSource {
+    /// Lowercase name, used as the highlight kind in exports
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Claude => "claude",
+            Self::Quiz => "quiz",
+        }
+    }
+}
+
 /// How a note is anchored to content
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub enum NoteAnchor {
@@ -195,6 +304,22 @@ impl NoteAnchor {
     }
 }
 
+/// Number of hex digits in a short note ID
+pub const SHORT_ID_LEN: usize = 6;
+
+/// Derive a short ID from a full note ID
+///
+/// Full IDs share a long timestamp prefix, so the short ID is a hash (FNV-1a)
+/// of the whole ID rather than a prefix of it.
+pub fn short_id(id: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in id.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)[..SHORT_ID_LEN].to_string()
+}
+
 /// Generate a unique ID for a note
 fn generate_id() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -224,6 +349,13 @@ fn rand_u16() -> u16 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn short_id_is_stable_and_short() {
+        assert_eq!(short_id("note_1_0001"), short_id("note_1_0001"));
+        assert_ne!(short_id("note_1_0001"), short_id("note_1_0002"));
+        assert_eq!(short_id("note_1_0001").len(), SHORT_ID_LEN);
+    }
+
     #[test]
     fn create_section_note() {
         let note = Note::new_section_note("book1", "ch01/s01", "My note");
@@ -298,6 +430,27 @@ mod tests {
         assert_ne!(note1.id, note2.id);
     }
 
+    #[test]
+    fn highlights_have_a_color_and_no_content() {
+        let note = Note::new_highlight("book1", "ch01/s01", HighlightColor::Green, 1, 4, "text");
+        assert!(note.is_highlight() && note.is_selection_note());
+        assert!(note.content.is_empty());
+        assert_eq!(note.anchor.char_range(), Some((4, 8)));
+
+        let json = serde_json::to_string(&note).unwrap();
+        assert!(json.contains("\"highlight\":\"green\""));
+        assert!(
+            !serde_json::to_string(&Note::new_section_note("b", "s", "n"))
+                .unwrap()
+                .contains("highlight")
+        );
+
+        assert_eq!(HighlightColor::from_name("PI"), Some(HighlightColor::Pink));
+        assert_eq!(HighlightColor::from_name("p"), None);
+        assert_eq!(HighlightColor::from_name("Blue"), Some(HighlightColor::Blue));
+        assert_eq!(HighlightColor::Purple.next(), HighlightColor::Yellow);
+    }
+
     #[test]
     fn note_source_default() {
         assert_eq!(NoteSource::default(), NoteSource::User);
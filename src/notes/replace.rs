@@ -0,0 +1,175 @@
+//! Search and replace across a book's notes
+//!
+//! `:notes s/old/new/` replaces text the way vim's `:s` does, but literally
+//! rather than as a pattern: the first match in each note, every match with
+//! the `g` flag, ignoring case with `i`. Any punctuation can delimit the
+//! parts, and a backslash escapes it. Edits are planned first so they can be
+//! previewed, then applied together.
+
+use regex::{NoExpand, Regex, RegexBuilder};
+
+use super::model::{Note, short_id};
+use super::storage::NotesStore;
+
+/// Changed lines shown for each note in a preview
+const PREVIEW_LINES: usize = 3;
+
+/// A parsed `s/old/new/flags` substitution
+#[derive(Debug, Clone)]
+pub struct Substitution {
+    /// Text replaced
+    pub from: String,
+    /// Text it is replaced with
+    pub to: String,
+    /// Replace every match in a note, not only the first
+    pub global: bool,
+    /// `from`, matched literally
+    pattern: Regex,
+}
+
+impl Substitution {
+    /// Parse `s/old/new/[gi]`; the error says what's wrong
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let usage = "Usage: :notes s/old/new/[g][i]";
+        let mut chars = expr.strip_prefix('s').ok_or(usage)?.chars();
+        let delimiter =
+            chars.next().filter(|c| !c.is_alphanumeric() && !c.is_whitespace()).ok_or(usage)?;
+
+        // Split on unescaped delimiters
+        let mut parts = vec![String::new()];
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(next) if next == delimiter => parts.last_mut().unwrap().push(next),
+                    Some(next) => parts.last_mut().unwrap().extend(['\\', next]),
+                    None => parts.last_mut().unwrap().push('\\'),
+                },
+                c if c == delimiter => parts.push(String::new()),
+                c => parts.last_mut().unwrap().push(c),
+            }
+        }
+        if parts.len() < 2 || parts.len() > 3 {
+            return Err(usage.to_string());
+        }
+        let flags = parts.get(2).cloned().unwrap_or_default();
+        if let Some(flag) = flags.chars().find(|c| !matches!(c, 'g' | 'i')) {
+            return Err(format!("Unknown flag '{}' ({})", flag, usage));
+        }
+        let (from, to) = (parts[0].clone(), parts[1].clone());
+        if from.is_empty() {
+            return Err("Nothing to replace".to_string());
+        }
+
+        let pattern = RegexBuilder::new(&regex::escape(&from))
+            .case_insensitive(flags.contains('i'))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self { from, to, global: flags.contains('g'), pattern })
+    }
+
+    /// `text` with the substitution made and how many matches were replaced;
+    /// None if nothing matches
+    pub fn apply(&self, text: &str) -> Option<(String, usize)> {
+        let matches = self.pattern.find_iter(text).count();
+        if matches == 0 {
+            return None;
+        }
+        let limit = if self.global { 0 } else { 1 };
+        let replaced = self.pattern.replacen(text, limit, NoExpand(&self.to)).into_owned();
+        Some((replaced, if self.global { matches } else { 1 }))
+    }
+}
+
+/// A planned change to one note
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteEdit {
+    /// Note changed
+    pub note_id: String,
+    /// Its content before the change
+    pub before: String,
+    /// Its content after the change
+    pub after: String,
+    /// Matches replaced
+    pub count: usize,
+}
+
+/// The edits a substitution makes to a book's notes, in the book's order
+pub fn plan(store: &NotesStore, book_id: &str, substitution: &Substitution) -> Vec<NoteEdit> {
+    store
+        .get_book_notes(book_id)
+        .iter()
+        .filter_map(|note: &Note| {
+            let (after, count) = substitution.apply(&note.content)?;
+            Some(NoteEdit { note_id: note.id.clone(), before: note.content.clone(), after, count })
+        })
+        .collect()
+}
+
+/// Markdown previewing planned edits, with a few changed lines of each note
+pub fn preview(substitution: &Substitution, edits: &[NoteEdit]) -> String {
+    let count: usize = edits.iter().map(|e| e.count).sum();
+    let mut out = format!(
+        "# Replace \"{}\" with \"{}\"\n\n{} replacement{} in {} note{}\n\n",
+        substitution.from,
+        substitution.to,
+        count,
+        if count == 1 { "" } else { "s" },
+        edits.len(),
+        if edits.len() == 1 { "" } else { "s" }
+    );
+    for edit in edits {
+        out.push_str(&format!("## Note {}\n\n", short_id(&edit.note_id)));
+        let changed: Vec<(&str, &str)> =
+            edit.before.lines().zip(edit.after.lines()).filter(|(a, b)| a != b).collect();
+        for (before, after) in changed.iter().take(PREVIEW_LINES) {
+            out.push_str(&format!("- {}\n+ {}\n", before.trim(), after.trim()));
+        }
+        if changed.len() > PREVIEW_LINES {
+            out.push_str(&format!("(and {} more lines)\n", changed.len() - PREVIEW_LINES));
+        }
+        out.push('\n');
+    }
+    out.push_str("Press y to apply, any other key to cancel.\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_handles_delimiters_escapes_and_flags() {
+        let sub = Substitution::parse("s|a/b|c\\|d|gi").unwrap();
+        assert_eq!((sub.from.as_str(), sub.to.as_str(), sub.global), ("a/b", "c|d", true));
+        assert!(Substitution::parse("s/teh/the").is_ok());
+        assert!(Substitution::parse("s/teh/the/x").unwrap_err().contains("Unknown flag 'x'"));
+        assert!(Substitution::parse("s//the/").is_err());
+        assert!(Substitution::parse("search").is_err());
+    }
+
+    #[test]
+    fn apply_replaces_first_or_every_match_literally() {
+        let once = Substitution::parse("s/a.b/$1/").unwrap();
+        assert_eq!(once.apply("a.b a.b axb"), Some(("$1 a.b axb".to_string(), 1)));
+        let every = Substitution::parse("s/Teh/the/gi").unwrap();
+        assert_eq!(every.apply("teh cat, TEH hat"), Some(("the cat, the hat".to_string(), 2)));
+        assert_eq!(every.apply("nothing"), None);
+    }
+
+    #[test]
+    fn plan_covers_only_the_books_matching_notes() {
+        let mut store = NotesStore::default();
+        store.add_note(Note::new_section_note("rust", "ch01/s01", "teh borrow checker"));
+        store.add_note(Note::new_section_note("rust", "ch01/s02", "fine"));
+        store.add_note(Note::new_section_note("go", "ch01/s01", "teh goroutine"));
+
+        let sub = Substitution::parse("s/teh/the/").unwrap();
+        let edits = plan(&store, "rust", &sub);
+
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].after, "the borrow checker");
+        let preview = preview(&sub, &edits);
+        assert!(preview.contains("1 replacement in 1 note"));
+        assert!(preview.contains("- teh borrow checker\n+ the borrow checker"));
+    }
+}
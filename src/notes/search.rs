@@ -0,0 +1,119 @@
+//! Full-text search across every book's notes
+//!
+//! Each word of the query must appear somewhere in a note, ignoring case.
+//! Notes are ranked by where their matches are: a match in the note's first
+//! line (its title, or the question of a Q&A note) counts most, then one in
+//! the quoted passage, then one in the rest of the note. Ties go to the note
+//! edited most recently.
+
+use std::ops::Range;
+
+use regex::Regex;
+
+use super::model::Note;
+use super::storage::NotesStore;
+
+/// Score for each match in a note's first line
+const TITLE_WEIGHT: usize = 3;
+/// Score for each match in the passage a note is anchored to
+const QUOTE_WEIGHT: usize = 2;
+/// Score for each match in the rest of the note
+const CONTENT_WEIGHT: usize = 1;
+
+/// A compiled notes query
+#[derive(Debug, Clone)]
+pub struct NoteQuery {
+    /// Lowercased words, all of which must match
+    words: Vec<String>,
+    /// Any of the words, ignoring case
+    pattern: Regex,
+}
+
+impl NoteQuery {
+    /// Compile a query; None if it has no words
+    pub fn new(query: &str) -> Option<Self> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if words.is_empty() {
+            return None;
+        }
+        let alternatives: Vec<String> = words.iter().map(|w| regex::escape(w)).collect();
+        let pattern = Regex::new(&format!("(?i){}", alternatives.join("|"))).ok()?;
+        Some(Self { words, pattern })
+    }
+
+    /// Byte ranges of the query's words in `text`, for highlighting
+    pub fn ranges(&self, text: &str) -> Vec<Range<usize>> {
+        self.pattern.find_iter(text).map(|m| m.range()).collect()
+    }
+
+    /// Ranking score for a note; None if some word isn't in it
+    pub fn score(&self, note: &Note) -> Option<usize> {
+        let quote = note.anchor.selected_text().unwrap_or("");
+        let (title, body) =
+            note.content.trim_start().split_once('\n').unwrap_or((&note.content, ""));
+
+        let haystack = format!("{}\n{}", quote, note.content).to_lowercase();
+        if !self.words.iter().all(|w| haystack.contains(w.as_str())) {
+            return None;
+        }
+        let count = |text: &str| self.pattern.find_iter(text).count();
+        Some(
+            count(title) * TITLE_WEIGHT
+                + count(quote) * QUOTE_WEIGHT
+                + count(body) * CONTENT_WEIGHT,
+        )
+    }
+}
+
+/// Notes in every book matching `query`, best first
+pub fn search<'a>(store: &'a NotesStore, query: &NoteQuery) -> Vec<&'a Note> {
+    let mut hits: Vec<(usize, &Note)> = store
+        .books
+        .values()
+        .flatten()
+        .filter_map(|note| query.score(note).map(|score| (score, note)))
+        .collect();
+    hits.sort_by(|a, b| {
+        b.0.cmp(&a.0).then(b.1.updated_at.cmp(&a.1.updated_at)).then(a.1.id.cmp(&b.1.id))
+    });
+    hits.into_iter().map(|(_, note)| note).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_title_then_quote_then_content() {
+        let mut store = NotesStore::default();
+        let mut body = Note::new_section_note("a", "ch01/s01", "Aside\nlifetimes come later");
+        body.id = "body".into();
+        let mut quote =
+            Note::new_selection_note("a", "ch01/s02", "Aside", 0, 0, "Lifetimes are generic");
+        quote.id = "quote".into();
+        let mut title = Note::new_section_note("b", "ch02/s01", "Lifetimes\nsee the appendix");
+        title.id = "title".into();
+        let unrelated = Note::new_section_note("b", "ch02/s01", "Traits");
+        for note in [body, quote, title, unrelated] {
+            store.add_note(note);
+        }
+
+        let query = NoteQuery::new("LIFETIMES").unwrap();
+        let ids: Vec<&str> = search(&store, &query).iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["title", "quote", "body"]);
+    }
+
+    #[test]
+    fn every_word_must_match() {
+        let note = Note::new_section_note("a", "ch01/s01", "Borrowing rules for references");
+        assert!(NoteQuery::new("borrow refer").unwrap().score(&note).is_some());
+        assert!(NoteQuery::new("borrow traits").unwrap().score(&note).is_none());
+        assert!(NoteQuery::new("  ").is_none());
+    }
+
+    #[test]
+    fn ranges_ignore_case() {
+        let query = NoteQuery::new("mut (a)").unwrap();
+        assert_eq!(query.ranges("Mut and &MUT, (a)"), [0..3, 9..12, 14..17]);
+    }
+}
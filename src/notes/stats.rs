@@ -0,0 +1,205 @@
+//! Writing statistics
+//!
+//! Records how many words are written in notes each day and per book, to
+//! show writing totals and streaks.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, datetime, persist};
+
+/// Words written in one note save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WritingRecord {
+    /// Unix timestamp when the note was saved
+    pub timestamp: i64,
+    /// Book the note belongs to
+    pub book_id: String,
+    /// Words added
+    pub words: usize,
+}
+
+impl WritingRecord {
+    /// Local day number (days since the Unix epoch) this record falls on
+    fn day(&self) -> i64 {
+        datetime::day_number(self.timestamp)
+    }
+}
+
+/// Persistent log of words written in notes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WritingLog {
+    /// All recorded saves, oldest first
+    pub records: Vec<WritingRecord>,
+}
+
+impl WritingLog {
+    /// Load writing log from disk
+    pub fn load() -> Result<Self> {
+        let path = Self::log_path()?;
+
+        let loaded = persist::load(&path, |path| {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read writing stats from {:?}", path))?;
+            serde_json::from_str(&contents).with_context(|| "Failed to parse writing.json")
+        })?;
+        Ok(loaded.unwrap_or_default())
+    }
+
+    /// Save writing log to disk
+    pub fn save(&self) -> Result<()> {
+        let path = Self::log_path()?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create data directory {:?}", parent))?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .with_context(|| "Failed to serialize writing stats")?;
+
+        persist::write(&path, contents)
+            .with_context(|| format!("Failed to write writing stats to {:?}", path))?;
+
+        Ok(())
+    }
+
+    /// Get writing log path
+    fn log_path() -> Result<PathBuf> {
+        Ok(Config::data_dir()?.join("writing.json"))
+    }
+
+    /// Record words written for a book (ignored if zero)
+    pub fn record(&mut self, book_id: &str, words: usize) {
+        if words == 0 {
+            return;
+        }
+        let timestamp = datetime::now();
+        self.records.push(WritingRecord { timestamp, book_id: book_id.to_string(), words });
+    }
+
+    /// Words written across all books
+    pub fn total(&self) -> usize {
+        self.records.iter().map(|r| r.words).sum()
+    }
+
+    /// Words written today
+    pub fn today(&self) -> usize {
+        self.since_day(datetime::today())
+    }
+
+    /// Words written on or after a day, e.g. the start of the week
+    pub fn since_day(&self, day: i64) -> usize {
+        self.records.iter().filter(|r| r.day() >= day).map(|r| r.words).sum()
+    }
+
+    /// Words written for a single book
+    pub fn for_book(&self, book_id: &str) -> usize {
+        self.records.iter().filter(|r| r.book_id == book_id).map(|r| r.words).sum()
+    }
+
+    /// Words per day for the `days` days ending today, oldest first
+    pub fn recent_days(&self, days: i64) -> Vec<(i64, usize)> {
+        let per_day = self.words_per_day();
+        let today = datetime::today();
+        (today - days + 1..=today)
+            .map(|day| (day, per_day.get(&day).copied().unwrap_or(0)))
+            .collect()
+    }
+
+    /// Consecutive days with writing, ending today or yesterday
+    ///
+    /// A streak isn't broken until a whole day passes without writing.
+    pub fn current_streak(&self) -> usize {
+        self.streak_ending(datetime::today())
+    }
+
+    /// Longest run of consecutive days with writing
+    pub fn longest_streak(&self) -> usize {
+        let mut longest = 0;
+        let mut run = 0;
+        let mut previous: Option<i64> = None;
+        for &day in self.words_per_day().keys() {
+            run = if previous == Some(day - 1) { run + 1 } else { 1 };
+            longest = longest.max(run);
+            previous = Some(day);
+        }
+        longest
+    }
+
+    fn streak_ending(&self, today: i64) -> usize {
+        let per_day = self.words_per_day();
+        let mut day = if per_day.contains_key(&today) { today } else { today - 1 };
+        let mut streak = 0;
+        while per_day.contains_key(&day) {
+            streak += 1;
+            day -= 1;
+        }
+        streak
+    }
+
+    fn words_per_day(&self) -> BTreeMap<i64, usize> {
+        let mut days = BTreeMap::new();
+        for record in &self.records {
+            *days.entry(record.day()).or_default() += record.words;
+        }
+        days
+    }
+}
+
+/// Number of words in a piece of text
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(book: &str, day: i64, words: usize) -> WritingRecord {
+        // Midday, so the local date is the same in any time zone
+        WritingRecord { timestamp: day * 86_400 + 43_200, book_id: book.into(), words }
+    }
+
+    #[test]
+    fn totals_by_book() {
+        let log = WritingLog {
+            records: vec![record("rust", 1, 10), record("go", 1, 5), record("rust", 2, 7)],
+        };
+
+        assert_eq!(log.total(), 22);
+        assert_eq!(log.for_book("rust"), 17);
+        assert_eq!(log.for_book("zig"), 0);
+    }
+
+    #[test]
+    fn streaks_count_consecutive_days() {
+        let log = WritingLog {
+            records: vec![
+                record("rust", 1, 10),
+                record("rust", 2, 10),
+                record("rust", 3, 10),
+                record("rust", 7, 10),
+                record("rust", 8, 10),
+            ],
+        };
+
+        assert_eq!(log.longest_streak(), 3);
+        // Writing yesterday keeps the streak alive today
+        assert_eq!(log.streak_ending(8), 2);
+        assert_eq!(log.streak_ending(9), 2);
+        assert_eq!(log.streak_ending(10), 0);
+    }
+
+    #[test]
+    fn record_ignores_zero_words() {
+        let mut log = WritingLog::default();
+        log.record("rust", 0);
+        assert!(log.records.is_empty());
+        log.record("rust", word_count("  two words "));
+        assert_eq!(log.today(), 2);
+    }
+}
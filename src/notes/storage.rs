@@ -1,55 +1,133 @@
 //! Note persistence
+//!
+//! More than one sensei can have the notes open at once, so saving merges
+//! rather than overwrites: under a lock on `notes.json.lock`, the file is read
+//! back and the notes another instance saved since this one loaded them are
+//! kept alongside this one's changes.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use fs4::FileExt;
 use serde::{Deserialize, Serialize};
 
-use super::model::{Note, NoteAnchor};
-use crate::config::Config;
+use super::anchor::{self, ReanchorReport};
+use super::links::Backlinks;
+use super::model::{HighlightColor, Note, NoteAnchor};
+use crate::book::Book;
+use crate::config::{Config, encryption, persist};
 
 /// All notes organized by book
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NotesStore {
     /// Notes per book (book_id -> list of notes)
     pub books: HashMap<String, Vec<Note>>,
+    /// Which notes link to each section, kept up to date as notes change
+    #[serde(skip)]
+    backlinks: Backlinks,
+    /// When each note was last changed as of the last load or save, to tell
+    /// notes deleted here from notes another instance added
+    #[serde(skip)]
+    saved: HashMap<String, i64>,
 }
 
 impl NotesStore {
     /// Load notes from disk
     pub fn load() -> Result<Self> {
-        let path = Self::notes_path()?;
+        Self::load_from(&Self::notes_path()?)
+    }
 
-        if path.exists() {
-            let contents = std::fs::read_to_string(&path)
-                .with_context(|| format!("Failed to read notes from {:?}", path))?;
-            serde_json::from_str(&contents).with_context(|| "Failed to parse notes.json")
-        } else {
-            Ok(Self::default())
+    fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() && !persist::backup_path(path).exists() {
+            return Ok(Self::default());
         }
+        let lock = lock(path)?;
+        FileExt::lock_shared(&lock).with_context(|| format!("Failed to lock {:?}", path))?;
+        let mut store = persist::load(path, Self::read)?.unwrap_or_default();
+        store.loaded();
+        Ok(store)
     }
 
-    /// Save notes to disk
-    pub fn save(&self) -> Result<()> {
-        let path = Self::notes_path()?;
+    /// Save notes to disk, merged with any another instance saved since they
+    /// were loaded
+    pub fn save(&mut self) -> Result<()> {
+        self.save_to(&Self::notes_path()?)
+    }
 
+    fn save_to(&mut self, path: &Path) -> Result<()> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)
                 .with_context(|| format!("Failed to create data directory {:?}", parent))?;
         }
+        let lock = lock(path)?;
+        FileExt::lock(&lock).with_context(|| format!("Failed to lock {:?}", path))?;
 
+        if let Some(disk) = persist::load(path, Self::read)? {
+            self.merge(disk);
+        }
         let contents =
             serde_json::to_string_pretty(self).with_context(|| "Failed to serialize notes")?;
 
-        std::fs::write(&path, contents)
+        encryption::write(path, &contents)
             .with_context(|| format!("Failed to write notes to {:?}", path))?;
+        self.loaded();
 
         Ok(())
     }
 
+    fn read(path: &Path) -> Result<Self> {
+        let contents = encryption::read(path)
+            .with_context(|| format!("Failed to read notes from {:?}", path))?;
+        serde_json::from_str(&contents).with_context(|| "Failed to parse notes.json")
+    }
+
+    /// Index the notes as they now are on disk
+    fn loaded(&mut self) {
+        self.backlinks = Backlinks::default();
+        for note in self.books.values().flatten() {
+            self.backlinks.add(note);
+        }
+        self.saved = self.books.values().flatten().map(|n| (n.id.clone(), n.updated_at)).collect();
+    }
+
+    /// Take in the notes on disk: a note changed on both sides keeps the
+    /// later change, and a note deleted on one side stays deleted unless the
+    /// other changed it since
+    ///
+    /// Changes are ordered by `updated_at`, which is in whole seconds: of two
+    /// changes to a note within the same second, the one here is kept, and a
+    /// change made in the second it was loaded in doesn't count as one when
+    /// the note was deleted on disk.
+    fn merge(&mut self, disk: Self) {
+        let mut theirs: HashMap<String, Note> =
+            disk.books.into_values().flatten().map(|n| (n.id.clone(), n)).collect();
+        for notes in self.books.values_mut() {
+            notes.retain_mut(|note| match theirs.remove(&note.id) {
+                Some(their) => {
+                    if their.updated_at > note.updated_at {
+                        *note = their;
+                    }
+                    true
+                }
+                None => self.saved.get(&note.id).is_none_or(|&saved| note.updated_at > saved),
+            });
+        }
+
+        let mut added: Vec<Note> = theirs
+            .into_values()
+            .filter(|n| self.saved.get(&n.id).is_none_or(|&saved| n.updated_at > saved))
+            .collect();
+        added.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        for note in added {
+            self.books.entry(note.book_id.clone()).or_default().push(note);
+        }
+        self.books.retain(|_, notes| !notes.is_empty());
+    }
+
     /// Get path to notes.json
-    fn notes_path() -> Result<PathBuf> {
+    pub fn notes_path() -> Result<PathBuf> {
         Ok(Config::data_dir()?.join("notes.json"))
     }
 
@@ -82,13 +160,55 @@ impl NotesStore {
             .collect()
     }
 
-    /// Get note anchors for highlighting (text ranges only)
-    pub fn get_note_anchors(&self, book_id: &str, section_path: &str) -> Vec<&NoteAnchor> {
-        self.get_selection_notes(book_id, section_path).into_iter().map(|n| &n.anchor).collect()
+    /// Get note anchors for highlighting (text ranges that still match the book),
+    /// with the color of those that are highlights
+    pub fn get_note_anchors(
+        &self,
+        book_id: &str,
+        section_path: &str,
+    ) -> Vec<(&NoteAnchor, Option<HighlightColor>)> {
+        self.get_selection_notes(book_id, section_path)
+            .into_iter()
+            .filter(|n| !n.anchor_lost)
+            .map(|n| (&n.anchor, n.highlight))
+            .collect()
+    }
+
+    /// Get every highlight in a book
+    pub fn get_highlights(&self, book_id: &str) -> Vec<&Note> {
+        self.get_book_notes(book_id).iter().filter(|n| n.is_highlight()).collect()
+    }
+
+    /// Change a highlight's color by ID
+    pub fn set_highlight_color(&mut self, note_id: &str, color: HighlightColor) -> bool {
+        let note = self.books.values_mut().flatten().find(|n| n.id == note_id);
+        match note {
+            Some(note) if note.is_highlight() => {
+                note.highlight = Some(color);
+                note.touch();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Move a book's text-selection notes to where their text now is
+    pub fn reanchor_book(&mut self, book: &Book) -> ReanchorReport {
+        match self.books.get_mut(&book.metadata.id) {
+            Some(notes) => anchor::reanchor(notes, book),
+            None => ReanchorReport::default(),
+        }
+    }
+
+    /// Notes in a book linking to one of its sections with `[[section:path]]`
+    pub fn backlinks_to_section(&self, book_id: &str, section_path: &str) -> Vec<&Note> {
+        let ids = self.backlinks.to_section(book_id, section_path);
+        self.get_book_notes(book_id).iter().filter(|n| ids.contains(&n.id.as_str())).collect()
     }
 
     /// Add a note
     pub fn add_note(&mut self, note: Note) {
+        self.backlinks.add(&note);
         self.books.entry(note.book_id.clone()).or_default().push(note);
     }
 
@@ -97,18 +217,36 @@ impl NotesStore {
         for notes in self.books.values_mut() {
             if let Some(note) = notes.iter_mut().find(|n| n.id == note_id) {
                 note.update_content(new_content);
+                self.backlinks.remove(note_id);
+                self.backlinks.add(note);
                 return true;
             }
         }
         false
     }
 
+    /// Add text to the end of an existing note, after a blank line
+    pub fn append_to_note(&mut self, note_id: &str, text: &str) -> bool {
+        let Some(note) = self.get_note_mut(note_id) else { return false };
+        let content = if note.content.trim().is_empty() {
+            text.to_string()
+        } else {
+            format!("{}\n\n{}", note.content.trim_end(), text)
+        };
+        note.update_content(&content);
+        let note = note.clone();
+        self.backlinks.remove(note_id);
+        self.backlinks.add(&note);
+        true
+    }
+
     /// Delete a note by ID
     pub fn delete_note(&mut self, note_id: &str) -> bool {
         for notes in self.books.values_mut() {
             let len_before = notes.len();
             notes.retain(|n| n.id != note_id);
             if notes.len() < len_before {
+                self.backlinks.remove(note_id);
                 return true;
             }
         }
@@ -125,6 +263,20 @@ impl NotesStore {
         None
     }
 
+    /// Find notes matching a full ID or a short ID prefix (with or without `#`)
+    ///
+    /// More than one result means the prefix is ambiguous.
+    pub fn find_by_id(&self, query: &str) -> Vec<&Note> {
+        let query = query.trim().trim_start_matches('#').to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        if let Some(note) = self.get_note(&query) {
+            return vec![note];
+        }
+        self.books.values().flatten().filter(|n| n.short_id().starts_with(&query)).collect()
+    }
+
     /// Get a mutable reference to a note by ID
     pub fn get_note_mut(&mut self, note_id: &str) -> Option<&mut Note> {
         for notes in self.books.values_mut() {
@@ -135,6 +287,21 @@ impl NotesStore {
         None
     }
 
+    /// Move a book's notes to the new paths of sections that moved, given as
+    /// old path -> new path; returns the number of notes moved
+    pub fn move_sections(&mut self, book_id: &str, moves: &HashMap<&str, &str>) -> usize {
+        let Some(notes) = self.books.get_mut(book_id) else { return 0 };
+        let mut moved = 0;
+        for note in notes.iter_mut() {
+            if let Some(path) = moves.get(note.section_path.as_str()) {
+                note.section_path = path.to_string();
+                note.touch();
+                moved += 1;
+            }
+        }
+        moved
+    }
+
     /// Count total notes
     pub fn total_count(&self) -> usize {
         self.books.values().map(|v| v.len()).sum()
@@ -146,6 +313,17 @@ impl NotesStore {
     }
 }
 
+/// The lock file kept beside the notes, held while they're read or written
+fn lock(path: &Path) -> Result<File> {
+    let lock_path = path.with_extension("json.lock");
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open {:?}", lock_path))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,6 +344,21 @@ mod tests {
         assert!(store.get_note(&note_id).is_some());
     }
 
+    #[test]
+    fn move_sections_swaps_paths_at_once() {
+        let mut store = NotesStore::default();
+        store.add_note(create_test_note("book1", "ch01/s01", "First"));
+        store.add_note(create_test_note("book1", "ch01/s02", "Second"));
+        store.add_note(create_test_note("book2", "ch01/s01", "Other book"));
+
+        let moves = HashMap::from([("ch01/s01", "ch01/s02"), ("ch01/s02", "ch01/s01")]);
+        assert_eq!(store.move_sections("book1", &moves), 2);
+
+        assert_eq!(store.get_section_notes("book1", "ch01/s02")[0].content, "First");
+        assert_eq!(store.get_section_notes("book1", "ch01/s01")[0].content, "Second");
+        assert_eq!(store.get_section_notes("book2", "ch01/s01").len(), 1);
+    }
+
     #[test]
     fn get_section_notes() {
         let mut store = NotesStore::default();
@@ -198,6 +391,18 @@ mod tests {
         assert_eq!(updated.content, "Updated");
     }
 
+    #[test]
+    fn append_to_note() {
+        let mut store = NotesStore::default();
+        let note = create_test_note("book1", "ch01/s01", "Original\n");
+        let note_id = note.id.clone();
+
+        store.add_note(note);
+        assert!(store.append_to_note(&note_id, "More"));
+        assert_eq!(store.get_note(&note_id).unwrap().content, "Original\n\nMore");
+        assert!(!store.append_to_note("nonexistent", "More"));
+    }
+
     #[test]
     fn delete_note() {
         let mut store = NotesStore::default();
@@ -254,6 +459,25 @@ mod tests {
         assert_eq!(anchors.len(), 2);
     }
 
+    #[test]
+    fn highlights_keep_their_color() {
+        use crate::notes::HighlightColor;
+
+        let mut store = NotesStore::default();
+        let note = Note::new_highlight("book1", "ch01/s01", HighlightColor::Yellow, 0, 0, "text");
+        let id = note.id.clone();
+        store.add_note(note);
+        store.add_note(Note::new_selection_note("book1", "ch01/s01", "Note", 1, 0, "more"));
+
+        assert!(store.set_highlight_color(&id, HighlightColor::Blue));
+        let colors: Vec<_> =
+            store.get_note_anchors("book1", "ch01/s01").iter().map(|(_, c)| *c).collect();
+        assert_eq!(colors, [Some(HighlightColor::Blue), None]);
+        assert_eq!(store.get_highlights("book1").len(), 1);
+        let note_id = store.get_book_notes("book1")[1].id.clone();
+        assert!(!store.set_highlight_color(&note_id, HighlightColor::Pink));
+    }
+
     #[test]
     fn book_count() {
         let mut store = NotesStore::default();
@@ -266,4 +490,70 @@ mod tests {
         assert_eq!(store.book_count("book2"), 1);
         assert_eq!(store.book_count("book3"), 0);
     }
+
+    #[test]
+    fn find_by_full_or_short_id() {
+        let mut store = NotesStore::default();
+        let note = create_test_note("book1", "ch01/s01", "Note 1");
+        let id = note.id.clone();
+        let short = note.short_id();
+        store.add_note(note);
+        store.add_note(create_test_note("book2", "ch01/s01", "Note 2"));
+
+        assert_eq!(store.find_by_id(&id)[0].content, "Note 1");
+        assert_eq!(store.find_by_id(&format!("#{}", short))[0].content, "Note 1");
+        assert_eq!(store.find_by_id(&short.to_uppercase()).len(), 1);
+        // An empty prefix doesn't match everything
+        assert!(store.find_by_id("#").is_empty());
+    }
+
+    #[test]
+    fn save_keeps_notes_saved_by_another_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.json");
+        let mut setup = NotesStore::default();
+        let kept = create_test_note("book1", "ch01/s01", "Kept");
+        let deleted = create_test_note("book1", "ch01/s01", "Deleted");
+        let (kept_id, deleted_id) = (kept.id.clone(), deleted.id.clone());
+        setup.add_note(kept);
+        setup.add_note(deleted);
+        setup.save_to(&path).unwrap();
+
+        let mut first = NotesStore::load_from(&path).unwrap();
+        let mut second = NotesStore::load_from(&path).unwrap();
+        first.add_note(create_test_note("book1", "ch01/s02", "From the first"));
+        first.get_note_mut(&kept_id).unwrap().updated_at += 10;
+        first.get_note_mut(&kept_id).unwrap().content = "Edited".into();
+        first.save_to(&path).unwrap();
+        second.add_note(create_test_note("book2", "ch01/s01", "From the second"));
+        second.delete_note(&deleted_id);
+        second.save_to(&path).unwrap();
+
+        let merged = NotesStore::load_from(&path).unwrap();
+        let mut contents: Vec<&str> =
+            merged.books.values().flatten().map(|n| n.content.as_str()).collect();
+        contents.sort_unstable();
+        assert_eq!(contents, ["Edited", "From the first", "From the second"]);
+        assert_eq!(second.total_count(), 3);
+
+        // A note deleted elsewhere stays deleted here
+        first.save_to(&path).unwrap();
+        assert!(first.get_note(&deleted_id).is_none());
+    }
+
+    #[test]
+    fn backlinks_track_note_changes() {
+        let mut store = NotesStore::default();
+        let note = create_test_note("book1", "ch02/s01", "See [[section:ch01/s01]]");
+        let id = note.id.clone();
+        store.add_note(note);
+        assert_eq!(store.backlinks_to_section("book1", "ch01/s01")[0].id, id);
+
+        store.update_note(&id, "No links");
+        assert!(store.backlinks_to_section("book1", "ch01/s01").is_empty());
+        store.append_to_note(&id, "Also [[section:ch01/s01]]");
+        assert_eq!(store.backlinks_to_section("book1", "ch01/s01").len(), 1);
+        store.delete_note(&id);
+        assert!(store.backlinks_to_section("book1", "ch01/s01").is_empty());
+    }
 }
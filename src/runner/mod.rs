@@ -0,0 +1,115 @@
+//! Running code blocks
+//!
+//! Each language can have a [`CodeRunner`] in the config. The block's code is
+//! written to the runner's stdin and what it prints is shown as the result.
+//! Runs are killed if they take longer than [`RUN_TIMEOUT`].
+
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::io::AsyncWriteExt;
+
+use crate::book::CodeBlock;
+use crate::config::CodeRunner;
+
+/// Longest a run may take before it is stopped
+pub const RUN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What a finished run printed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunOutput {
+    /// Exit code (None if killed by a signal)
+    pub status: Option<i32>,
+    /// Standard output
+    pub stdout: String,
+    /// Standard error
+    pub stderr: String,
+}
+
+impl RunOutput {
+    /// The output as markdown for the response panel
+    pub fn to_markdown(&self, language: &str) -> String {
+        let mut out = format!("# Run: {}\n\n", language);
+        match self.status {
+            Some(0) => out.push_str("Finished successfully.\n"),
+            Some(code) => out.push_str(&format!("Exited with status {}.\n", code)),
+            None => out.push_str("Stopped by a signal.\n"),
+        }
+        for (title, text) in [("Output", &self.stdout), ("Errors", &self.stderr)] {
+            if !text.trim().is_empty() {
+                out.push_str(&format!("\n## {}\n\n```\n{}\n```\n", title, text.trim_end()));
+            }
+        }
+        if self.stdout.trim().is_empty() && self.stderr.trim().is_empty() {
+            out.push_str("\nNo output.\n");
+        }
+        out
+    }
+}
+
+/// The runner configured for a code block's language, matched ignoring case
+pub fn runner_for<'a>(
+    runners: &'a BTreeMap<String, CodeRunner>,
+    code: &CodeBlock,
+) -> Option<&'a CodeRunner> {
+    let language = code.language_name()?;
+    runners.iter().find(|(name, _)| name.eq_ignore_ascii_case(language)).map(|(_, r)| r)
+}
+
+/// Run code through a runner, waiting up to [`RUN_TIMEOUT`]
+pub async fn run(runner: &CodeRunner, code: &str) -> Result<RunOutput> {
+    let mut child = tokio::process::Command::new(&runner.command)
+        .args(&runner.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", runner.command))?;
+
+    // Sending the code counts against the timeout too, as a runner that
+    // never reads its stdin blocks the write once the pipe fills
+    let stdin = child.stdin.take();
+    let finished = async move {
+        if let Some(mut stdin) = stdin {
+            stdin.write_all(code.as_bytes()).await.context("Failed to send code to the runner")?;
+        }
+        Ok::<_, anyhow::Error>(child.wait_with_output().await?)
+    };
+    let output = tokio::time::timeout(RUN_TIMEOUT, finished)
+        .await
+        .with_context(|| format!("Stopped after {}s", RUN_TIMEOUT.as_secs()))??;
+    Ok(RunOutput {
+        status: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runner_for_matches_language_name() {
+        let mut runners = BTreeMap::new();
+        let python = CodeRunner { command: "python3".into(), args: vec!["-".into()] };
+        runners.insert("Python".to_string(), python.clone());
+
+        let code = CodeBlock::new("print(1)").with_language("python,ignore");
+        assert_eq!(runner_for(&runners, &code), Some(&python));
+        assert_eq!(runner_for(&runners, &CodeBlock::new("x").with_language("rust")), None);
+    }
+
+    #[tokio::test]
+    async fn run_pipes_code_to_stdin() {
+        let cat = CodeRunner { command: "cat".into(), args: Vec::new() };
+        let output = run(&cat, "hello\n").await.unwrap();
+
+        assert_eq!(output.status, Some(0));
+        assert_eq!(output.stdout, "hello\n");
+        assert!(output.to_markdown("text").contains("## Output\n\n```\nhello\n```"));
+    }
+}
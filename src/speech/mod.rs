@@ -0,0 +1,254 @@
+//! Text-to-speech read-aloud
+//!
+//! Speaks a section one sentence at a time through an external command
+//! (e.g. `say` or `espeak`), so playback can be paused between sentences
+//! and the reader can follow along in the content panel.
+
+use std::io;
+use std::process::{Child, Command, Stdio};
+
+use crate::book::{ContentBlock, Section};
+use crate::config::TtsConfig;
+
+/// A sentence to speak, with the content block it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Utterance {
+    /// Index of the source block in the section
+    pub block_index: usize,
+    /// Sentence text
+    pub text: String,
+}
+
+/// Progress reported by [`Speaker::poll`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeechEvent {
+    /// Nothing changed since the last poll
+    Idle,
+    /// Started speaking the utterance at this position
+    Started(usize),
+    /// All utterances have been spoken
+    Finished,
+}
+
+/// Plays a queue of utterances through the configured TTS command
+#[derive(Debug)]
+pub struct Speaker {
+    command: String,
+    args: Vec<String>,
+    utterances: Vec<Utterance>,
+    position: usize,
+    child: Option<Child>,
+    paused: bool,
+    /// Section being read
+    section_path: String,
+}
+
+impl Speaker {
+    /// Create a speaker for a section (does not start speaking)
+    pub fn new(config: &TtsConfig, section: &Section) -> Self {
+        Self {
+            command: config.command.clone(),
+            args: config.args.clone(),
+            utterances: utterances(section),
+            position: 0,
+            child: None,
+            paused: false,
+            section_path: section.path.clone(),
+        }
+    }
+
+    /// Section path this speaker is reading
+    pub fn section_path(&self) -> &str {
+        &self.section_path
+    }
+
+    /// Whether there is anything to speak
+    pub fn is_empty(&self) -> bool {
+        self.utterances.is_empty()
+    }
+
+    /// Whether playback is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Current position and total number of utterances
+    pub fn progress(&self) -> (usize, usize) {
+        (self.position, self.utterances.len())
+    }
+
+    /// Utterance currently being spoken (or next to be spoken when paused)
+    pub fn current(&self) -> Option<&Utterance> {
+        self.utterances.get(self.position)
+    }
+
+    /// Start (or resume) speaking from the current position
+    pub fn play(&mut self) -> io::Result<SpeechEvent> {
+        self.paused = false;
+        if self.child.is_some() {
+            return Ok(SpeechEvent::Idle);
+        }
+        self.spawn_current()
+    }
+
+    /// Pause playback; the interrupted sentence is repeated on resume
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.kill_child();
+    }
+
+    /// Check on the TTS process, moving to the next sentence when one finishes
+    pub fn poll(&mut self) -> io::Result<SpeechEvent> {
+        if self.paused {
+            return Ok(SpeechEvent::Idle);
+        }
+        let Some(child) = &mut self.child else {
+            return Ok(if self.position >= self.utterances.len() {
+                SpeechEvent::Finished
+            } else {
+                SpeechEvent::Idle
+            });
+        };
+        if child.try_wait()?.is_none() {
+            return Ok(SpeechEvent::Idle);
+        }
+
+        self.child = None;
+        self.position += 1;
+        self.spawn_current()
+    }
+
+    /// Spawn the TTS command for the current utterance
+    fn spawn_current(&mut self) -> io::Result<SpeechEvent> {
+        let Some(utterance) = self.utterances.get(self.position) else {
+            return Ok(SpeechEvent::Finished);
+        };
+        let child = Command::new(&self.command)
+            .args(&self.args)
+            .arg(&utterance.text)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        self.child = Some(child);
+        Ok(SpeechEvent::Started(self.position))
+    }
+
+    fn kill_child(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        self.kill_child();
+    }
+}
+
+/// Break a section into speakable sentences
+///
+/// Code blocks, tables, images and footnotes are skipped since they don't read
+/// well aloud.
+pub fn utterances(section: &Section) -> Vec<Utterance> {
+    let mut result = Vec::new();
+    for (block_index, block) in section.content.iter().enumerate() {
+        let texts: Vec<&str> = match block {
+            ContentBlock::Heading { text, .. }
+            | ContentBlock::Paragraph(text)
+            | ContentBlock::Blockquote(text) => vec![text],
+            ContentBlock::UnorderedList(items) | ContentBlock::OrderedList(items) => {
+                items.iter().map(String::as_str).collect()
+            }
+            ContentBlock::Code(_)
+            | ContentBlock::Image { .. }
+            | ContentBlock::HorizontalRule
+            | ContentBlock::Table(_)
+            | ContentBlock::Footnote { .. } => continue,
+        };
+        for text in texts {
+            result.extend(
+                split_sentences(&strip_markup(text))
+                    .into_iter()
+                    .map(|text| Utterance { block_index, text }),
+            );
+        }
+    }
+    result
+}
+
+/// Remove inline markdown markers that would otherwise be read out
+fn strip_markup(text: &str) -> String {
+    text.chars().filter(|c| !matches!(c, '*' | '`')).collect()
+}
+
+/// Split text into sentences at `.`, `!` or `?` followed by whitespace
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        current.push(c);
+        let at_boundary =
+            matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|next| next.is_whitespace());
+        if at_boundary {
+            let sentence = current.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !sentence.is_empty() {
+                sentences.push(sentence);
+            }
+            current.clear();
+        }
+    }
+
+    let rest = current.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+    sentences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::CodeBlock;
+
+    #[test]
+    fn split_sentences_on_terminators() {
+        let sentences = split_sentences("Ownership is key. Is it hard?  Not really!\nTry v1.2 now");
+        assert_eq!(
+            sentences,
+            vec!["Ownership is key.", "Is it hard?", "Not really!", "Try v1.2 now"]
+        );
+    }
+
+    #[test]
+    fn utterances_skip_code_and_track_blocks() {
+        let mut section = Section::new("Intro", 1, "ch01/s01");
+        section.content = vec![
+            ContentBlock::Heading { level: 2, text: "Intro".into() },
+            ContentBlock::Code(CodeBlock::new("fn main() {}")),
+            ContentBlock::Paragraph("Uses **bold** text. Then `code`.".into()),
+            ContentBlock::UnorderedList(vec!["One".into(), "Two".into()]),
+        ]
+        .into();
+
+        let spoken = utterances(&section);
+        let blocks: Vec<usize> = spoken.iter().map(|u| u.block_index).collect();
+        assert_eq!(blocks, vec![0, 2, 2, 3, 3]);
+        assert_eq!(spoken[1].text, "Uses bold text.");
+        assert_eq!(spoken[2].text, "Then code.");
+    }
+
+    #[test]
+    fn speaker_reports_missing_command() {
+        let config = TtsConfig { command: "sensei-no-such-tts-command".into(), args: Vec::new() };
+        let mut section = Section::new("Intro", 1, "ch01/s01");
+        section.content = vec![ContentBlock::Paragraph("Hello.".into())].into();
+
+        let mut speaker = Speaker::new(&config, &section);
+        assert!(speaker.play().is_err());
+    }
+}
@@ -8,7 +8,12 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 
+use unicode_width::UnicodeWidthStr;
+
+use super::locale::{self, Text};
 use crate::app::state::AppState;
+use crate::claude::buddy::Comparison;
+use crate::claude::citations::{Segment, quotes, segments};
 use crate::theme::Theme;
 
 /// Draw the Claude response panel as a centered overlay
@@ -25,12 +30,24 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &mut AppState, theme: &Theme)
     frame.render_widget(Clear, overlay_area);
 
     // Create the panel block
-    let title =
-        if state.claude.streaming { " Claude (streaming...) " } else { " Claude Response " };
+    let title = if state.claude.streaming {
+        Text::ClaudeStreaming
+    } else if state.claude.comparison.is_some() {
+        Text::StudyBuddy
+    } else {
+        Text::ClaudeResponse
+    };
+    let title = format!(" {} ", locale::text(title));
+
+    let text =
+        if state.claude.streaming { &state.claude.stream_buffer } else { &state.claude.response };
+    let quotes = if state.claude.comparison.is_some() { Vec::new() } else { quotes(text) };
+    let help = if quotes.is_empty() { Text::ResponseHelp } else { Text::CitedResponseHelp };
+    let help = format!(" {} ", locale::text(help));
 
     let block = Block::default()
         .title(title)
-        .title_bottom(Line::from(" [c] toggle  [j/k] scroll  [Esc] close ").centered())
+        .title_bottom(Line::from(help).centered())
         .borders(Borders::ALL)
         .border_style(Style::default().fg(theme.border_focused))
         .style(Style::default().bg(theme.bg_secondary));
@@ -38,24 +55,24 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &mut AppState, theme: &Theme)
     let inner = block.inner(overlay_area);
     frame.render_widget(block, overlay_area);
 
-    // Get the response text
-    let text =
-        if state.claude.streaming { &state.claude.stream_buffer } else { &state.claude.response };
-
     if text.is_empty() {
-        let empty = Paragraph::new("No response yet...").style(Style::default().fg(theme.fg_muted));
+        let empty = Paragraph::new(locale::text(Text::NoResponseYet))
+            .style(Style::default().fg(theme.fg_muted));
         frame.render_widget(empty, inner);
         return;
     }
 
     // Wrap text and create lines
     let width = inner.width.saturating_sub(2) as usize;
-    let lines: Vec<Line> = text
-        .lines()
-        .flat_map(|line| {
-            if line.is_empty() { vec![Line::from("")] } else { wrap_line(line, width, theme) }
-        })
-        .collect();
+    let mut citations = Vec::new();
+    let lines: Vec<Line> = match &state.claude.comparison {
+        Some(comparison) => comparison_lines(comparison, width, theme),
+        None if quotes.is_empty() => wrap_text(text, width, theme),
+        None => {
+            let selected = state.claude.selected_citation;
+            cited_lines(text, &quotes, selected, width, theme, &mut citations)
+        }
+    };
 
     // Calculate max scroll
     let visible_lines = inner.height as usize;
@@ -66,6 +83,20 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &mut AppState, theme: &Theme)
     let scroll = (state.claude.response_scroll as usize).min(max_scroll);
     state.claude.response_scroll = scroll as u16;
 
+    // Remember where citations are, to scroll to them and find them under the mouse
+    state.claude.citation_rows = (0..quotes.len())
+        .map(|index| citations.iter().find(|c| c.index == index).map_or(0, |c| c.row))
+        .collect();
+    state.claude.citation_hits = citations
+        .iter()
+        .filter(|c| c.row >= scroll && c.row < scroll + visible_lines)
+        .map(|c| {
+            let x = inner.x + c.col as u16;
+            let y = inner.y + (c.row - scroll) as u16;
+            (x, y, c.width as u16, c.index)
+        })
+        .collect();
+
     // Create scrollable paragraph
     let para = Paragraph::new(lines)
         .style(Style::default().fg(theme.fg_primary))
@@ -80,6 +111,214 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &mut AppState, theme: &Theme)
     }
 }
 
+/// Wrap multi-line text
+pub(super) fn wrap_text(text: &str, width: usize, theme: &Theme) -> Vec<Line<'static>> {
+    let mut in_fence = false;
+    text.lines()
+        .flat_map(|line| {
+            if let Some(line) = preformatted_line(line, &mut in_fence, theme) {
+                vec![line]
+            } else if line.is_empty() {
+                vec![Line::from("")]
+            } else {
+                wrap_line(line, width, theme)
+            }
+        })
+        .collect()
+}
+
+/// A fenced code block line, kept exactly as written so diagrams line up;
+/// None outside a fence
+fn preformatted_line(line: &str, in_fence: &mut bool, theme: &Theme) -> Option<Line<'static>> {
+    if line.trim_start().starts_with("```") {
+        *in_fence = !*in_fence;
+        return Some(Line::from(Span::styled(
+            line.to_string(),
+            Style::default().fg(theme.fg_muted),
+        )));
+    }
+    in_fence.then(|| {
+        Line::from(Span::styled(line.to_string(), Style::default().fg(theme.syntax_function)))
+    })
+}
+
+/// Where a citation was drawn, in panel lines and columns
+struct CitationSpan {
+    row: usize,
+    col: usize,
+    width: usize,
+    index: usize,
+}
+
+/// Wrap text whose quotes are shown as numbered, highlighted citations
+fn cited_lines(
+    text: &str,
+    quotes: &[String],
+    selected: Option<usize>,
+    width: usize,
+    theme: &Theme,
+    citations: &mut Vec<CitationSpan>,
+) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        if let Some(line) = preformatted_line(line, &mut in_fence, theme) {
+            lines.push(line);
+        } else if line.is_empty() {
+            lines.push(Line::from(""));
+        } else if line.contains("[[") {
+            lines.extend(wrap_cited_line(
+                line,
+                quotes,
+                selected,
+                width,
+                theme,
+                lines.len(),
+                citations,
+            ));
+        } else {
+            lines.extend(wrap_line(line, width, theme));
+        }
+    }
+    lines
+}
+
+/// Wrap a line containing quotes, recording where each citation lands
+fn wrap_cited_line(
+    line: &str,
+    quotes: &[String],
+    selected: Option<usize>,
+    width: usize,
+    theme: &Theme,
+    first_row: usize,
+    citations: &mut Vec<CitationSpan>,
+) -> Vec<Line<'static>> {
+    // Words tagged with the citation they belong to; quotes end with their number
+    let mut words: Vec<(String, Option<usize>)> = Vec::new();
+    for segment in segments(line, quotes) {
+        match segment {
+            Segment::Text(text) => {
+                words.extend(text.split_whitespace().map(|w| (w.to_string(), None)))
+            }
+            Segment::Quote(quote, index) => {
+                let quoted = format!("❝{}❞[{}]", quote, index + 1);
+                words.extend(quoted.split_whitespace().map(|w| (w.to_string(), Some(index))));
+            }
+        }
+    }
+
+    // Greedy wrap into rows of words
+    let mut rows: Vec<Vec<(String, Option<usize>)>> = vec![Vec::new()];
+    let mut row_width = 0;
+    for (word, index) in words {
+        let word_width = word.width();
+        let last = rows.last_mut().expect("rows is never empty");
+        if !last.is_empty() && row_width + 1 + word_width > width {
+            rows.push(Vec::new());
+            row_width = 0;
+        }
+        let last = rows.last_mut().expect("rows is never empty");
+        if !last.is_empty() {
+            row_width += 1;
+        }
+        row_width += word_width;
+        last.push((word, index));
+    }
+
+    let citation_style = |index: usize| {
+        let style = Style::default()
+            .fg(theme.accent_secondary)
+            .bg(theme.bg_tertiary)
+            .add_modifier(Modifier::UNDERLINED);
+        if selected == Some(index) { style.bg(theme.selection) } else { style }
+    };
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(row, words)| {
+            let mut spans: Vec<Span<'static>> = Vec::new();
+            let mut col = 0;
+            let mut previous: Option<usize> = None;
+            for (i, (word, index)) in words.into_iter().enumerate() {
+                if i > 0 {
+                    // Spaces inside a quote are part of the citation
+                    let joined = index.is_some() && index == previous;
+                    let style = if joined {
+                        citation_style(index.unwrap_or_default())
+                    } else {
+                        Style::default()
+                    };
+                    spans.push(Span::styled(" ", style));
+                    col += 1;
+                    if let Some(span) = citations.last_mut().filter(|_| joined) {
+                        span.width += 1;
+                    }
+                }
+                let word_width = word.width();
+                match index {
+                    Some(index) => {
+                        match citations.last_mut() {
+                            Some(span)
+                                if previous == Some(index) && span.row == first_row + row =>
+                            {
+                                span.width += word_width
+                            }
+                            _ => citations.push(CitationSpan {
+                                row: first_row + row,
+                                col,
+                                width: word_width,
+                                index,
+                            }),
+                        }
+                        spans.push(Span::styled(word, citation_style(index)));
+                    }
+                    None => spans.push(Span::raw(word)),
+                }
+                col += word_width;
+                previous = index;
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Two answers in columns, followed by the diff of their key claims
+fn comparison_lines(comparison: &Comparison, width: usize, theme: &Theme) -> Vec<Line<'static>> {
+    const DIVIDER: &str = " │ ";
+    let column_width = width.saturating_sub(DIVIDER.width()) / 2;
+    let heading = Style::default().fg(theme.accent_primary).add_modifier(Modifier::BOLD);
+
+    let columns = comparison.answers.each_ref().map(|answer| {
+        let mut lines =
+            vec![Line::from(Span::styled(answer.model.display_name().to_string(), heading))];
+        lines.push(Line::from(""));
+        lines.extend(wrap_text(&answer.text(), column_width, theme));
+        lines
+    });
+
+    let mut lines = wrap_text(&format!("Question: {}", comparison.question), width, theme);
+    lines.push(Line::from(""));
+
+    let rows = columns[0].len().max(columns[1].len());
+    for row in 0..rows {
+        let left = columns[0].get(row).cloned().unwrap_or_default();
+        let right = columns[1].get(row).cloned().unwrap_or_default();
+        let padding = " ".repeat(column_width.saturating_sub(left.width()));
+        let mut spans = left.spans;
+        spans.push(Span::raw(padding));
+        spans.push(Span::styled(DIVIDER, Style::default().fg(theme.fg_muted)));
+        spans.extend(right.spans);
+        lines.push(Line::from(spans));
+    }
+
+    if comparison.is_complete() {
+        let models = comparison.answers.each_ref().map(|a| &a.model);
+        lines.push(Line::from(""));
+        lines.extend(wrap_text(&comparison.diff().to_markdown(models), width, theme));
+    }
+    lines
+}
+
 /// Wrap a single line of text
 fn wrap_line(line: &str, width: usize, theme: &Theme) -> Vec<Line<'static>> {
     if width == 0 {
@@ -162,7 +401,7 @@ fn style_line(text: &str, theme: &Theme) -> Line<'static> {
 }
 
 /// Draw scroll indicator on the right side
-fn draw_scroll_indicator(
+pub(super) fn draw_scroll_indicator(
     frame: &mut Frame,
     area: Rect,
     scroll: usize,
@@ -191,7 +430,7 @@ fn draw_scroll_indicator(
 }
 
 /// Create a centered rectangle with the given percentage of width and height
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+pub(super) fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::vertical([
         Constraint::Percentage((100 - percent_y) / 2),
         Constraint::Percentage(percent_y),
@@ -206,3 +445,47 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     ])
     .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cited_line_records_citation_positions() {
+        let theme = Theme::default();
+        let text = "See [[Each value has an owner.]] here";
+        let quotes = quotes(text);
+        let mut citations = Vec::new();
+
+        let lines = cited_lines(text, &quotes, None, 20, &theme, &mut citations);
+
+        let rendered: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        assert_eq!(rendered, vec!["See ❝Each value has", "an owner.❞[1] here"]);
+        let spans: Vec<_> = citations.iter().map(|c| (c.row, c.col, c.width, c.index)).collect();
+        assert_eq!(spans, vec![(0, 4, 15, 0), (1, 0, 13, 0)]);
+    }
+
+    #[test]
+    fn fenced_blocks_keep_their_layout() {
+        let theme = Theme::default();
+        let text = "Ownership moves:\n```\n s1 ──┐\n      ▼\n    heap\n```\nthen s1 is invalid";
+
+        let lines: Vec<String> =
+            wrap_text(text, 12, &theme).iter().map(|l| l.to_string()).collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "Ownership",
+                "moves:",
+                "```",
+                " s1 ──┐",
+                "      ▼",
+                "    heap",
+                "```",
+                "then s1 is",
+                "invalid"
+            ]
+        );
+    }
+}
@@ -8,11 +8,23 @@ use ratatui::{
     widgets::Paragraph,
 };
 
+use unicode_width::UnicodeWidthStr;
+
 use crate::app::state::{CommandLineState, CommandMode};
 use crate::theme::Theme;
 
 /// Draw the command line at the bottom of the screen
 pub fn draw(frame: &mut Frame, area: Rect, state: &CommandLineState, theme: &Theme) {
+    if state.mode == CommandMode::Normal && state.message.is_none() && !state.hints.is_empty() {
+        let status_width = state.status.as_ref().map_or(0, |s| s.width() as u16 + 1);
+        let line =
+            hints_line(&state.hints, area.width.saturating_sub(status_width) as usize, theme);
+        let text_width = line.width() as u16;
+        frame.render_widget(Paragraph::new(line), area);
+        draw_status(frame, area, state, text_width, theme);
+        return;
+    }
+
     let (text, style) = match state.mode {
         CommandMode::Normal => {
             // Show message or empty
@@ -41,6 +53,8 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &CommandLineState, theme: &The
         }
     };
 
+    let text_width = text.width() as u16;
+
     // Build the line with cursor if in input mode
     let line = if state.is_input_mode() {
         build_line_with_cursor(&text, state.cursor + 1, style, theme) // +1 for prefix
@@ -50,6 +64,47 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &CommandLineState, theme: &The
 
     let paragraph = Paragraph::new(line);
     frame.render_widget(paragraph, area);
+    draw_status(frame, area, state, text_width, theme);
+}
+
+/// Right-aligned status (e.g. running API cost) when not typing
+fn draw_status(
+    frame: &mut Frame,
+    area: Rect,
+    state: &CommandLineState,
+    text_width: u16,
+    theme: &Theme,
+) {
+    if let Some(ref status) = state.status {
+        let status_width = status.width() as u16 + 1;
+        if !state.is_input_mode() && text_width + status_width < area.width {
+            let status_area =
+                Rect { x: area.x + area.width - status_width, width: status_width, ..area };
+            let status_line = Paragraph::new(Line::from(Span::styled(
+                status.as_str(),
+                Style::default().fg(theme.fg_muted),
+            )));
+            frame.render_widget(status_line, status_area);
+        }
+    }
+}
+
+/// Key hints as keys and what they do, as many as fit in `width` columns
+fn hints_line(hints: &[(String, &'static str)], width: usize, theme: &Theme) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut used = 0;
+    for (keys, what) in hints {
+        let separator = if spans.is_empty() { "" } else { "  " };
+        let hint_width = separator.len() + keys.width() + 1 + what.width();
+        if used + hint_width > width {
+            break;
+        }
+        used += hint_width;
+        spans.push(Span::styled(separator, Style::default()));
+        spans.push(Span::styled(keys.clone(), Style::default().fg(theme.accent_primary)));
+        spans.push(Span::styled(format!(" {}", what), Style::default().fg(theme.fg_muted)));
+    }
+    Line::from(spans)
 }
 
 /// Build a line with a visible cursor
@@ -87,6 +142,14 @@ fn build_line_with_cursor(
 mod tests {
     use super::*;
 
+    #[test]
+    fn hints_stop_at_width() {
+        let theme = Theme::default();
+        let hints = vec![("j/k".to_string(), "move"), ("Enter".to_string(), "open")];
+        assert_eq!(hints_line(&hints, 80, &theme).to_string(), "j/k move  Enter open");
+        assert_eq!(hints_line(&hints, 12, &theme).to_string(), "j/k move");
+    }
+
     #[test]
     fn build_cursor_at_start() {
         let theme = Theme::default();
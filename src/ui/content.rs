@@ -1,20 +1,29 @@
 //! Content block renderer
 
+use std::collections::BTreeSet;
+
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
-
-use crate::app::state::{AppState, VisualModeState};
-use crate::book::ContentBlock;
-use crate::notes::{NoteAnchor, NotesStore};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::app::state::{AppState, ContentState, ReadingMode, VisualModeState};
+use crate::book::model::{footnote_marker, format_reading_time};
+use crate::book::{ContentBlock, Section, Table};
+use crate::config::Typography;
+use crate::learning::glossary::TermMatcher;
+use crate::notes::{HighlightColor, NoteAnchor, NotesStore};
 use crate::syntax;
 use crate::theme::Theme;
 
+use super::hyperlink;
+use super::hyphenate;
 use super::image::ImageCache;
+use super::locale::{self, Text};
 use super::section_footer;
 
 /// Draw the content panel with section content
@@ -35,12 +44,12 @@ pub fn draw_with_notes(
 
     let title = if let Some(book) = &state.book {
         if let Some(section) = book.get_section(state.current_chapter, state.current_section) {
-            format!(" {} ", section.title)
+            panel_title(section)
         } else {
-            " Content ".to_string()
+            format!(" {} ", locale::text(Text::Content))
         }
     } else {
-        " Content ".to_string()
+        format!(" {} ", locale::text(Text::Content))
     };
 
     let block = Block::default()
@@ -67,7 +76,7 @@ pub fn draw_with_notes(
     };
 
     // Get note anchors for this section
-    let note_anchors: Vec<&NoteAnchor> = notes_store
+    let note_anchors: Vec<NoteMark> = notes_store
         .map(|store| store.get_note_anchors(&book.metadata.id, &section.path))
         .unwrap_or_default();
 
@@ -81,29 +90,32 @@ pub fn draw_with_notes(
             cursor_char: state.content.cursor_char,
             cursor_mode: state.content.cursor_mode,
             selection_active: state.visual_mode.active,
+            table_scroll: state.content.table_scroll_at(state.content.cursor_block),
         })
     } else {
         None
     };
 
-    // Reserve 1 column for scrollbar
-    let content_width = inner.width.saturating_sub(2) as usize;
-    let content_area =
-        Rect { x: inner.x, y: inner.y, width: inner.width.saturating_sub(1), height: inner.height };
+    // Reserve 1 column for scrollbar; text is centered when narrower than the panel
+    let text_area = centered_text_area(inner, state.content.max_width);
+    let content_width = text_area.width.saturating_sub(2) as usize;
+    let content_area = Rect { width: text_area.width.saturating_sub(1), ..text_area };
     let scrollbar_x = inner.x + inner.width.saturating_sub(1);
 
     // Render content blocks with note underlining and selection highlighting
     // (no image heights for non-image rendering path)
     let empty_heights = std::collections::HashMap::new();
-    let (lines, block_offsets) = render_content_blocks_with_offsets(
-        &section.content,
+    let context = RenderContext {
         theme,
-        content_width,
-        &note_anchors,
+        width: content_width,
+        typography: state.content.typography,
+        review: false,
+        note_anchors: &note_anchors,
         visual_mode,
-        cursor_state.as_ref(),
-        &empty_heights,
-    );
+        cursor_state: cursor_state.as_ref(),
+        image_heights: &empty_heights,
+    };
+    let (lines, block_offsets) = render_content_blocks_with_offsets(&section.content, &context);
     let total_lines = lines.len();
     let visible_height = inner.height as usize;
 
@@ -143,12 +155,12 @@ pub fn draw_with_images(
 
     let title = if let Some(book) = &state.book {
         if let Some(section) = book.get_section(state.current_chapter, state.current_section) {
-            format!(" {} ", section.title)
+            panel_title(section)
         } else {
-            " Content ".to_string()
+            format!(" {} ", locale::text(Text::Content))
         }
     } else {
-        " Content ".to_string()
+        format!(" {} ", locale::text(Text::Content))
     };
 
     let block = Block::default()
@@ -174,8 +186,27 @@ pub fn draw_with_images(
         return;
     };
 
+    // The section's learning objectives sit above its text
+    let inner = draw_objectives(frame, inner, &state.content, theme);
+
+    // Skimming lays out a shortened copy of the blocks, one for one, with
+    // the blocks the reader expanded kept whole
+    let reading_mode = state.content.reading_mode;
+    let skim = (reading_mode == ReadingMode::Skim).then(|| {
+        let key = (book.metadata.id.clone(), section.path.clone());
+        state.content.skim_expanded.get(&key).cloned().unwrap_or_default()
+    });
+    let skimmed;
+    let blocks: &[ContentBlock] = match &skim {
+        Some(expanded) => {
+            skimmed = skim_blocks(&section.content, expanded);
+            &skimmed
+        }
+        None => &section.content,
+    };
+
     // Get note anchors for this section
-    let note_anchors: Vec<&NoteAnchor> = notes_store
+    let note_anchors: Vec<NoteMark> = notes_store
         .map(|store| store.get_note_anchors(&book.metadata.id, &section.path))
         .unwrap_or_default();
 
@@ -189,22 +220,23 @@ pub fn draw_with_images(
             cursor_char: state.content.cursor_char,
             cursor_mode: state.content.cursor_mode,
             selection_active: state.visual_mode.active,
+            table_scroll: state.content.table_scroll_at(state.content.cursor_block),
         })
     } else {
         None
     };
 
-    // Reserve 1 column for scrollbar
-    let content_width = inner.width.saturating_sub(2) as usize;
-    let content_area =
-        Rect { x: inner.x, y: inner.y, width: inner.width.saturating_sub(1), height: inner.height };
+    // Reserve 1 column for scrollbar; text is centered when narrower than the panel
+    let text_area = centered_text_area(inner, state.content.max_width);
+    let content_width = text_area.width.saturating_sub(2) as usize;
+    let content_area = Rect { width: text_area.width.saturating_sub(1), ..text_area };
     let scrollbar_x = inner.x + inner.width.saturating_sub(1);
 
     // Collect image info for later rendering, calculating dynamic heights
     let mut image_info: Vec<ImageRenderInfo> = Vec::new();
     let mut image_heights: std::collections::HashMap<usize, usize> =
         std::collections::HashMap::new();
-    for (block_idx, content_block) in section.content.iter().enumerate() {
+    for (block_idx, content_block) in blocks.iter().enumerate() {
         if let ContentBlock::Image { src, .. } = content_block {
             // Calculate dynamic height based on image aspect ratio
             let height = image_cache
@@ -215,31 +247,70 @@ pub fn draw_with_images(
         }
     }
 
-    // Render content blocks with note underlining and selection highlighting
-    let (mut lines, block_offsets) = render_content_blocks_with_offsets(
-        &section.content,
+    // Render content blocks with note underlining and selection highlighting, reusing
+    // the previous frame's lines unless something that affects the layout changed
+    let visible_height = inner.height as usize;
+    let key = LayoutKey {
+        book_id: book.metadata.id.clone(),
+        section_path: section.path.clone(),
+        theme: theme.name.clone(),
+        width: content_width,
+        typography: state.content.typography,
+        skim,
+        review: reading_mode == ReadingMode::Review,
+        note_anchors: note_anchors
+            .iter()
+            .map(|(anchor, color)| ((*anchor).clone(), *color))
+            .collect(),
+        image_heights,
+        cursor: cursor_state,
+        visual_mode: visual_mode.cloned(),
+    };
+    let scroll_offset = state.content.scroll_offset;
+    let cached = state.content.layout_cache.take();
+    // A width change (panel resized or toggled) reflows the section; keep the
+    // text that was at the top of the viewport there
+    let anchor = cached
+        .as_ref()
+        .filter(|cache| cache.key.width != key.width && cache.key.same_section(&key))
+        .and_then(|cache| cache.window.anchor(scroll_offset));
+    let context = RenderContext {
         theme,
-        content_width,
-        &note_anchors,
+        width: content_width,
+        typography: key.typography,
+        review: key.review,
+        note_anchors: &note_anchors,
         visual_mode,
-        cursor_state.as_ref(),
-        &image_heights,
-    );
+        cursor_state: cursor_state.as_ref(),
+        image_heights: &key.image_heights,
+    };
+    let render =
+        |scroll_offset| render_content_window(blocks, &context, scroll_offset, visible_height);
+    let mut window = match cached {
+        Some(cache) if cache.key == key && cache.window.covers(scroll_offset, visible_height) => {
+            cache.window
+        }
+        _ => render(scroll_offset),
+    };
+    if let Some(anchor) = anchor {
+        let offset = window.offset_of(anchor);
+        if !window.covers(offset, visible_height) {
+            window = render(offset);
+        }
+        state.content.scroll_offset = offset;
+    }
 
-    // Add blank lines before footer for spacing
-    let footer_start_line = lines.len();
-    lines.push(Line::from(""));
-    lines.push(Line::from(""));
+    // Two blank lines before footer for spacing
+    let footer_start_line = window.content_lines;
 
     // Add footer height to total lines (footer renders separately but affects scroll)
     let footer_height = section_footer::FOOTER_HEIGHT as usize;
-    let total_lines = lines.len() + footer_height;
-    let visible_height = inner.height as usize;
+    let total_lines = footer_start_line + 2 + footer_height;
 
     // Update state with content metrics for scroll clamping
     state.content.total_lines = total_lines;
     state.content.visible_height = visible_height;
-    state.content.block_line_offsets = block_offsets.clone();
+    state.content.block_line_offsets = window.block_offsets.clone();
     state.content.content_width = content_width;
     state.content.content_area =
         (content_area.x, content_area.y, content_area.width, content_area.height);
@@ -248,16 +319,44 @@ pub fn draw_with_images(
     state.content.clamp_scroll();
     let scroll_offset = state.content.scroll_offset;
     let end = (scroll_offset + visible_height).min(total_lines);
-    let visible_lines: Vec<Line> =
-        lines.into_iter().skip(scroll_offset).take(end - scroll_offset).collect();
+    // Lines outside the rendered window (footer spacing) are blank
+    let mut visible_lines: Vec<Line> = (scroll_offset..end)
+        .map(|line| {
+            line.checked_sub(window.first_line)
+                .and_then(|i| window.lines.get(i))
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect();
+    let cursor_line = window.cursor_line;
+    state.content.layout_cache = Some(LayoutCache { key, window });
+    if let Some(glossary) = &state.content.glossary {
+        let offsets = &state.content.block_line_offsets;
+        mark_glossary_terms(&mut visible_lines, scroll_offset, blocks, offsets, glossary);
+    }
+    state.content.link_hits = hyperlink::visible_links(
+        &visible_lines,
+        scroll_offset,
+        blocks,
+        &state.content.block_line_offsets,
+        (content_area.x, content_area.y),
+        theme,
+    );
 
+    let cursor_row = cursor_line
+        .and_then(|line| line.checked_sub(scroll_offset))
+        .filter(|&row| row < visible_lines.len());
     let content = Paragraph::new(visible_lines);
     frame.render_widget(content, content_area);
+    draw_last_read_marker(frame, &state.content, &section.path, content_area, theme);
+    if let Some(row) = cursor_row.filter(|_| !state.visual_mode.active) {
+        draw_glossary_popup(frame, &state.content, section, content_area, row, theme);
+    }
 
     // Render images at their positions
     for img_info in image_info {
         // Get the line offset for this image block
-        if let Some(&line_offset) = block_offsets.get(img_info.block_index) {
+        if let Some(&line_offset) = state.content.block_line_offsets.get(img_info.block_index) {
             let image_height = img_info.height;
             let image_end = line_offset + image_height;
             let viewport_end = scroll_offset + visible_height;
@@ -272,9 +371,9 @@ pub fn draw_with_images(
                     if available_height >= 1 {
                         let render_height = available_height.min(image_height as u16);
                         let image_area = Rect {
-                            x: inner.x + 2,
+                            x: text_area.x + 2,
                             y: inner.y + relative_y,
-                            width: inner.width.saturating_sub(4),
+                            width: text_area.width.saturating_sub(4),
                             height: render_height,
                         };
 
@@ -293,9 +392,9 @@ pub fn draw_with_images(
                     if visible_lines >= 1 {
                         let render_height = (visible_lines as u16).min(inner.height);
                         let image_area = Rect {
-                            x: inner.x + 2,
+                            x: text_area.x + 2,
                             y: inner.y,
-                            width: inner.width.saturating_sub(4),
+                            width: text_area.width.saturating_sub(4),
                             height: render_height,
                         };
 
@@ -332,7 +431,7 @@ pub fn draw_with_images(
         let available_height = inner.height.saturating_sub(footer_y - inner.y);
         if available_height >= section_footer::FOOTER_HEIGHT {
             let footer_area = Rect {
-                x: inner.x,
+                x: text_area.x,
                 y: footer_y,
                 width: content_area.width,
                 height: section_footer::FOOTER_HEIGHT,
@@ -345,6 +444,90 @@ pub fn draw_with_images(
     draw_scrollbar(frame, scrollbar_x, inner.y, inner.height, scroll_offset, total_lines, theme);
 }
 
+/// Narrow the content area to `max_width` text columns, centered in the panel
+fn centered_text_area(inner: Rect, max_width: Option<usize>) -> Rect {
+    // Content is rendered 2 columns narrower than its area (see `content_width`)
+    let Some(area_width) = max_width.map(|w| (w + 2).min(u16::MAX as usize) as u16) else {
+        return inner;
+    };
+    if inner.width <= area_width {
+        return inner;
+    }
+    let margin = (inner.width - area_width) / 2;
+    Rect { x: inner.x + margin, width: area_width, ..inner }
+}
+
+/// Draw the section's learning objectives across the top of `area`, ruled
+/// off from the text; returns the area left for the text
+fn draw_objectives(frame: &mut Frame, area: Rect, content: &ContentState, theme: &Theme) -> Rect {
+    if content.objectives.is_empty() || area.height < 8 {
+        return area;
+    }
+    let text_area = centered_text_area(area, content.max_width);
+    let width = text_area.width.saturating_sub(1) as usize;
+    let mut lines =
+        objective_lines(&content.objectives, content.objectives_collapsed, width, theme);
+    // The text keeps at least two thirds of the panel
+    lines.truncate((area.height / 3) as usize);
+    lines.push(Line::from(Span::styled(
+        "\u{2500}".repeat(width),
+        Style::default().fg(theme.border),
+    )));
+
+    let height = lines.len() as u16;
+    frame.render_widget(Paragraph::new(lines), Rect { height, ..text_area });
+    Rect { y: area.y + height, height: area.height - height, ..area }
+}
+
+/// The learning objectives header: how many are met, then (unless
+/// collapsed) each objective, numbered for `:objective`
+fn objective_lines(
+    objectives: &[(String, bool)],
+    collapsed: bool,
+    width: usize,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let met = objectives.iter().filter(|(_, met)| *met).count();
+    let summary = format!(
+        "  {} Objectives: {}/{} met",
+        if collapsed { '\u{25b8}' } else { '\u{25be}' },
+        met,
+        objectives.len()
+    );
+    let mut lines = vec![Line::from(Span::styled(
+        summary,
+        Style::default().fg(theme.accent_primary).add_modifier(Modifier::BOLD),
+    ))];
+    if collapsed {
+        return lines;
+    }
+
+    for (i, (objective, met)) in objectives.iter().enumerate() {
+        let (mark, mark_color, text_color) = if *met {
+            ("[x]", theme.success, theme.fg_muted)
+        } else {
+            ("[ ]", theme.fg_secondary, theme.fg_primary)
+        };
+        let number = format!(" {}. ", i + 1);
+        let room = width.saturating_sub(2 + mark.len() + number.len());
+        let mut text = String::new();
+        for c in objective.chars() {
+            if text.width() + c.width().unwrap_or(0) + 1 > room {
+                text.push('\u{2026}');
+                break;
+            }
+            text.push(c);
+        }
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            Span::styled(mark, Style::default().fg(mark_color)),
+            Span::styled(number, Style::default().fg(theme.fg_secondary)),
+            Span::styled(text, Style::default().fg(text_color)),
+        ]));
+    }
+    lines
+}
+
 /// Information about an image to render
 struct ImageRenderInfo {
     block_index: usize,
@@ -353,6 +536,180 @@ struct ImageRenderInfo {
     height: usize,
 }
 
+/// Mark the last-read line in the spare column right of the text
+fn draw_last_read_marker(
+    frame: &mut Frame,
+    content: &ContentState,
+    section_path: &str,
+    area: Rect,
+    theme: &Theme,
+) {
+    let Some(last_read) = content.last_read.as_ref().filter(|l| l.section_path == section_path)
+    else {
+        return;
+    };
+    let Some(cache) = &content.layout_cache else { return };
+    let anchor = ScrollAnchor {
+        block: last_read.block,
+        line: last_read.line,
+        height: last_read.block_height,
+    };
+    let line = cache.window.offset_of(anchor);
+    let Some(row) = line.checked_sub(content.scroll_offset).filter(|&r| r < area.height as usize)
+    else {
+        return;
+    };
+    let position = (area.x + area.width.saturating_sub(1), area.y + row as u16);
+    if let Some(cell) = frame.buffer_mut().cell_mut(position) {
+        cell.set_symbol("◂").set_fg(theme.accent_secondary);
+    }
+}
+
+/// Dim every span of some lines, for text review mode doesn't emphasize
+fn mute_lines(lines: &mut [Line<'static>], theme: &Theme) {
+    for span in lines.iter_mut().flat_map(|line| line.spans.iter_mut()) {
+        span.style = span.style.fg(theme.fg_muted);
+    }
+}
+
+/// A section's blocks shortened for skimming, index for index: headings
+/// stay, prose keeps its first sentence, lists their first item, and code,
+/// tables and images become rules. Blocks in `expanded` are kept whole.
+fn skim_blocks(blocks: &[ContentBlock], expanded: &BTreeSet<usize>) -> Vec<ContentBlock> {
+    let first_item = |items: &[String]| items.iter().take(1).map(|i| first_sentence(i)).collect();
+    blocks
+        .iter()
+        .enumerate()
+        .map(|(i, block)| match block {
+            _ if expanded.contains(&i) => block.clone(),
+            ContentBlock::Heading { .. } => block.clone(),
+            ContentBlock::Paragraph(text) => ContentBlock::Paragraph(first_sentence(text)),
+            ContentBlock::Blockquote(text) => ContentBlock::Blockquote(first_sentence(text)),
+            ContentBlock::UnorderedList(items) => ContentBlock::UnorderedList(first_item(items)),
+            ContentBlock::OrderedList(items) => ContentBlock::OrderedList(first_item(items)),
+            _ => ContentBlock::HorizontalRule,
+        })
+        .collect()
+}
+
+/// Whether skimming shortens a block, so it can be expanded
+pub fn skim_shortens(block: &ContentBlock) -> bool {
+    let skimmed = skim_blocks(std::slice::from_ref(block), &BTreeSet::new());
+    skimmed[0].plain_text() != block.plain_text()
+}
+
+/// The first sentence of `text`, marked with an ellipsis if there was more
+fn first_sentence(text: &str) -> String {
+    let end = text.char_indices().find_map(|(i, c)| {
+        let end = i + c.len_utf8();
+        let next = text[end..].chars().next();
+        (matches!(c, '.' | '!' | '?') && next.is_some_and(char::is_whitespace)).then_some(end)
+    });
+    match end {
+        Some(end) if !text[end..].trim().is_empty() => format!("{} …", &text[..end]),
+        _ => text.to_string(),
+    }
+}
+
+/// Underline glossary terms in the visible lines of prose blocks
+fn mark_glossary_terms(
+    lines: &mut [Line<'static>],
+    first_line: usize,
+    blocks: &[ContentBlock],
+    block_offsets: &[usize],
+    glossary: &TermMatcher,
+) {
+    for (i, line) in lines.iter_mut().enumerate() {
+        let block = block_offsets.partition_point(|&offset| offset <= first_line + i);
+        let prose = block.checked_sub(1).and_then(|b| blocks.get(b)).is_some_and(|block| {
+            matches!(
+                block,
+                ContentBlock::Paragraph(_)
+                    | ContentBlock::Blockquote(_)
+                    | ContentBlock::UnorderedList(_)
+                    | ContentBlock::OrderedList(_)
+                    | ContentBlock::Footnote { .. }
+            )
+        });
+        if prose {
+            line.spans = mark_terms(std::mem::take(&mut line.spans), glossary);
+        }
+    }
+}
+
+/// Split spans around glossary terms, giving the terms a dim underline
+fn mark_terms(spans: Vec<Span<'static>>, glossary: &TermMatcher) -> Vec<Span<'static>> {
+    let mut marked = Vec::with_capacity(spans.len());
+    for span in spans {
+        let ranges = glossary.ranges(&span.content);
+        if ranges.is_empty() {
+            marked.push(span);
+            continue;
+        }
+        let text = span.content.as_ref();
+        let term_style = span.style.add_modifier(Modifier::UNDERLINED | Modifier::DIM);
+        let mut last = 0;
+        for range in ranges {
+            if range.start > last {
+                marked.push(Span::styled(text[last..range.start].to_string(), span.style));
+            }
+            marked.push(Span::styled(text[range.clone()].to_string(), term_style));
+            last = range.end;
+        }
+        if last < text.len() {
+            marked.push(Span::styled(text[last..].to_string(), span.style));
+        }
+    }
+    marked
+}
+
+/// Widest the glossary popup gets, in columns
+const GLOSSARY_POPUP_WIDTH: u16 = 60;
+
+/// Define the glossary term under the cursor in a popup below its line
+/// (above it near the bottom of the panel)
+fn draw_glossary_popup(
+    frame: &mut Frame,
+    content: &ContentState,
+    section: &Section,
+    area: Rect,
+    cursor_row: usize,
+    theme: &Theme,
+) {
+    let Some(glossary) = content.glossary.as_ref().filter(|_| content.cursor_mode) else {
+        return;
+    };
+    let text = match section.content.get(content.cursor_block) {
+        Some(ContentBlock::Paragraph(text) | ContentBlock::Blockquote(text)) => text.clone(),
+        Some(ContentBlock::Heading { text, .. }) => text.clone(),
+        Some(ContentBlock::UnorderedList(items) | ContentBlock::OrderedList(items)) => {
+            items.join("\n")
+        }
+        _ => return,
+    };
+    let Some(term) = glossary.term_at(&text, content.cursor_char) else { return };
+
+    let width = area.width.saturating_sub(4).min(GLOSSARY_POPUP_WIDTH);
+    let lines = textwrap::wrap(&term.definition, width.saturating_sub(2).max(1) as usize);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let below = area.y + cursor_row as u16 + 1;
+    let y = if below + height <= area.y + area.height {
+        below
+    } else {
+        (area.y + cursor_row as u16).saturating_sub(height).max(area.y)
+    };
+    let popup = Rect { x: area.x + 2, y, width, height };
+
+    let block = Block::default()
+        .title(format!(" {} ", term.term))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent_secondary))
+        .style(Style::default().bg(theme.bg_secondary).fg(theme.fg_primary));
+    let lines: Vec<Line> = lines.into_iter().map(|l| Line::from(l.into_owned())).collect();
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
 /// Draw a scrollbar indicator
 fn draw_scrollbar(
     frame: &mut Frame,
@@ -402,6 +759,14 @@ fn draw_scrollbar(
     }
 }
 
+/// Panel title for a section, with its estimated reading time
+pub fn panel_title(section: &Section) -> String {
+    match section.reading_time_minutes {
+        Some(minutes) => format!(" {} · {} ", section.title, format_reading_time(minutes)),
+        None => format!(" {} ", section.title),
+    }
+}
+
 /// Draw the welcome message when no book is loaded
 fn draw_welcome(frame: &mut Frame, area: Rect, theme: &Theme) {
     let welcome = vec![
@@ -462,18 +827,21 @@ pub fn render_content_blocks(
     render_content_blocks_with_notes(blocks, theme, width, &[])
 }
 
+/// A note's anchor, with its color if the note is a highlight
+pub type NoteMark<'a> = (&'a NoteAnchor, Option<HighlightColor>);
+
 /// Render content blocks with note underlines
 pub fn render_content_blocks_with_notes(
     blocks: &[ContentBlock],
     theme: &Theme,
     width: usize,
-    note_anchors: &[&NoteAnchor],
+    note_anchors: &[NoteMark],
 ) -> Vec<Line<'static>> {
     render_content_blocks_with_visual_mode(blocks, theme, width, note_anchors, None, None)
 }
 
-/// Render content blocks with note underlines and visual mode selection
 /// Cursor and selection state for rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CursorState {
     /// Cursor block index
     pub cursor_block: usize,
@@ -483,50 +851,287 @@ pub struct CursorState {
     pub cursor_mode: bool,
     /// Whether selection mode is active (for different cursor color)
     pub selection_active: bool,
+    /// Columns the table under the cursor is scrolled sideways
+    pub table_scroll: usize,
+}
+
+/// What content blocks are laid out with, besides the blocks themselves
+#[derive(Clone, Copy)]
+pub struct RenderContext<'a> {
+    /// Colors to render with
+    pub theme: &'a Theme,
+    /// Columns available for text
+    pub width: usize,
+    /// Justification and hyphenation of wrapped text
+    pub typography: Typography,
+    /// Whether to bold marked text and dim the rest of the prose
+    pub review: bool,
+    /// Notes to underline or highlight
+    pub note_anchors: &'a [NoteMark<'a>],
+    /// Visual mode selection, if any
+    pub visual_mode: Option<&'a VisualModeState>,
+    /// Cursor and selection state, if any
+    pub cursor_state: Option<&'a CursorState>,
+    /// Lines reserved for each image block, by block index
+    pub image_heights: &'a std::collections::HashMap<usize, usize>,
 }
 
+/// Render content blocks with note underlines and visual mode selection
 pub fn render_content_blocks_with_visual_mode(
     blocks: &[ContentBlock],
     theme: &Theme,
     width: usize,
-    note_anchors: &[&NoteAnchor],
+    note_anchors: &[NoteMark],
     visual_mode: Option<&VisualModeState>,
     cursor_state: Option<&CursorState>,
 ) -> Vec<Line<'static>> {
     let empty_heights = std::collections::HashMap::new();
-    let (lines, _offsets) = render_content_blocks_with_offsets(
-        blocks,
+    let context = RenderContext {
         theme,
         width,
+        typography: Typography::default(),
+        review: false,
         note_anchors,
         visual_mode,
         cursor_state,
-        &empty_heights,
-    );
+        image_heights: &empty_heights,
+    };
+    let (lines, _offsets) = render_content_blocks_with_offsets(blocks, &context);
     lines
 }
 
 /// Render content blocks and track starting line offset for each block
 pub fn render_content_blocks_with_offsets(
     blocks: &[ContentBlock],
-    theme: &Theme,
-    width: usize,
-    note_anchors: &[&NoteAnchor],
-    visual_mode: Option<&VisualModeState>,
-    cursor_state: Option<&CursorState>,
-    image_heights: &std::collections::HashMap<usize, usize>,
+    context: &RenderContext,
 ) -> (Vec<Line<'static>>, Vec<usize>) {
+    let (lines, block_offsets, _) = render_blocks(blocks, 0, context);
+    (lines, block_offsets)
+}
+
+/// Columns between table cells, and either side of a table
+const TABLE_GAP: usize = 2;
+
+/// Narrowest a table column is squeezed to before the table scrolls sideways
+const MIN_TABLE_COLUMN: usize = 8;
+
+/// Sections estimated to be taller than this are rendered a window at a time
+const WINDOWED_LINE_THRESHOLD: usize = 5_000;
+
+/// Lines rendered above and below the viewport in windowed sections
+const WINDOW_MARGIN_LINES: usize = 300;
+
+/// Lines laid out for all or part of a section
+#[derive(Debug, Clone)]
+pub struct RenderedWindow {
+    /// Rendered lines, starting at section line `first_line`
+    pub lines: Vec<Line<'static>>,
+    /// Section line of the first rendered line
+    pub first_line: usize,
+    /// Starting line of every block (estimated outside the rendered window)
+    pub block_offsets: Vec<usize>,
+    /// Height of all the section's blocks in lines
+    pub content_lines: usize,
+    /// Section line holding the cursor, if it is in the rendered window
+    pub cursor_line: Option<usize>,
+}
+
+impl RenderedWindow {
+    /// Whether the rendered lines include every content line in the viewport
+    pub fn covers(&self, scroll_offset: usize, visible_height: usize) -> bool {
+        let end = (scroll_offset + visible_height).min(self.content_lines);
+        self.first_line <= scroll_offset.min(end) && end <= self.first_line + self.lines.len()
+    }
+
+    /// Position of a scroll offset within the blocks, or None past the last block
+    pub fn anchor(&self, scroll_offset: usize) -> Option<ScrollAnchor> {
+        if scroll_offset >= self.content_lines {
+            return None;
+        }
+        let block =
+            self.block_offsets.partition_point(|&start| start <= scroll_offset).checked_sub(1)?;
+        let (start, end) = self.block_span(block)?;
+        Some(ScrollAnchor { block, line: scroll_offset - start, height: end - start })
+    }
+
+    /// Scroll offset of an anchor in this layout, scaling its line to the block's new height
+    pub fn offset_of(&self, anchor: ScrollAnchor) -> usize {
+        let Some((start, end)) = self.block_span(anchor.block) else {
+            return self.content_lines;
+        };
+        let height = end - start;
+        let line = (anchor.line * height).checked_div(anchor.height).unwrap_or(0);
+        start + line.min(height.saturating_sub(1))
+    }
+
+    /// First line and end of a block
+    fn block_span(&self, block: usize) -> Option<(usize, usize)> {
+        let start = *self.block_offsets.get(block)?;
+        let end = self.block_offsets.get(block + 1).copied().unwrap_or(self.content_lines);
+        Some((start, end.max(start)))
+    }
+}
+
+/// Reading position that survives reflow: a block and a line within it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollAnchor {
+    /// Index of the block at the top of the viewport
+    pub block: usize,
+    /// Line within the block at the top of the viewport
+    pub line: usize,
+    /// Height of the block in lines when anchored
+    pub height: usize,
+}
+
+/// Everything a section's layout depends on
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutKey {
+    /// Book the section belongs to
+    pub book_id: String,
+    /// Section path within the book
+    pub section_path: String,
+    /// Name of the theme the lines are styled with
+    pub theme: String,
+    /// Content width in columns
+    pub width: usize,
+    /// Justification and hyphenation
+    pub typography: Typography,
+    /// Blocks expanded while skimming; None when not skimming
+    pub skim: Option<BTreeSet<usize>>,
+    /// Whether marked text is emphasized and the rest dimmed (review mode)
+    pub review: bool,
+    /// Note anchors underlined (or highlights colored) in the section
+    pub note_anchors: Vec<(NoteAnchor, Option<HighlightColor>)>,
+    /// Rows reserved for each image block
+    pub image_heights: std::collections::HashMap<usize, usize>,
+    /// Cursor position, if cursor mode is on
+    pub cursor: Option<CursorState>,
+    /// Selection anchor, if visual mode is on
+    pub visual_mode: Option<VisualModeState>,
+}
+
+impl LayoutKey {
+    /// Whether two layouts are of the same section
+    pub fn same_section(&self, other: &Self) -> bool {
+        self.book_id == other.book_id && self.section_path == other.section_path
+    }
+}
+
+/// Lines laid out for the current section, reused until its layout inputs change
+#[derive(Debug, Clone)]
+pub struct LayoutCache {
+    /// Inputs the lines were laid out with
+    pub key: LayoutKey,
+    /// The laid-out lines
+    pub window: RenderedWindow,
+}
+
+/// Render the blocks of a section around the viewport
+///
+/// Most sections are rendered in full. Giant ones (appendices, API dumps) only
+/// render blocks within `WINDOW_MARGIN_LINES` of the viewport, and the offsets
+/// of the rest are estimated from their length, so every frame stays cheap.
+pub fn render_content_window(
+    blocks: &[ContentBlock],
+    context: &RenderContext,
+    scroll_offset: usize,
+    visible_height: usize,
+) -> RenderedWindow {
+    let RenderContext { width, image_heights, .. } = *context;
+    let heights: Vec<usize> = blocks
+        .iter()
+        .enumerate()
+        .map(|(i, block)| estimate_block_height(block, width, image_heights.get(&i).copied()))
+        .collect();
+    let estimated_total: usize = heights.iter().sum();
+
+    if estimated_total <= WINDOWED_LINE_THRESHOLD {
+        let (lines, block_offsets, cursor_line) = render_blocks(blocks, 0, context);
+        let content_lines = lines.len();
+        return RenderedWindow { lines, first_line: 0, block_offsets, content_lines, cursor_line };
+    }
+
+    let mut estimated_offsets = Vec::with_capacity(blocks.len());
+    let mut line = 0;
+    for height in &heights {
+        estimated_offsets.push(line);
+        line += height;
+    }
+
+    // Blocks overlapping the viewport plus margin
+    let scroll = scroll_offset.min(estimated_total.saturating_sub(visible_height / 2));
+    let window_start = scroll.saturating_sub(WINDOW_MARGIN_LINES);
+    let window_end = scroll + visible_height + WINDOW_MARGIN_LINES;
+    let first = (0..blocks.len())
+        .find(|&i| estimated_offsets[i] + heights[i] > window_start)
+        .unwrap_or(blocks.len() - 1);
+    let last = (first..blocks.len()).rfind(|&i| estimated_offsets[i] < window_end).unwrap_or(first);
+
+    let (lines, window_offsets, cursor_line) = render_blocks(&blocks[first..=last], first, context);
+
+    // Exact offsets inside the window, estimates before and after it
+    let first_line = estimated_offsets[first];
+    let mut block_offsets = estimated_offsets[..first].to_vec();
+    block_offsets.extend(window_offsets.iter().map(|offset| first_line + offset));
+    let mut line = first_line + lines.len();
+    for height in &heights[last + 1..] {
+        block_offsets.push(line);
+        line += height;
+    }
+
+    let cursor_line = cursor_line.map(|cursor| first_line + cursor);
+    RenderedWindow { lines, first_line, block_offsets, content_lines: line, cursor_line }
+}
+
+/// Approximate height of a block in lines, without laying it out
+fn estimate_block_height(block: &ContentBlock, width: usize, image_height: Option<usize>) -> usize {
+    let wrapped =
+        |text: &str, indent: usize| text.width() / width.saturating_sub(indent).max(1) + 1;
+    match block {
+        ContentBlock::Heading { .. } => 3,
+        ContentBlock::Paragraph(text) | ContentBlock::Blockquote(text) => wrapped(text, 4) + 1,
+        ContentBlock::Footnote { text, .. } => wrapped(text, 6) + 1,
+        ContentBlock::Code(code) => code.code.lines().count().max(1) + 3,
+        ContentBlock::UnorderedList(items) | ContentBlock::OrderedList(items) => {
+            items.iter().map(|item| wrapped(item, 6)).sum::<usize>() + 1
+        }
+        ContentBlock::HorizontalRule => 1,
+        ContentBlock::Image { .. } => image_height.unwrap_or(IMAGE_RESERVED_HEIGHT),
+        ContentBlock::Table(table) => table.rows.len() + 5,
+    }
+}
+
+/// Render blocks that start at `first_index` within their section, along
+/// with each block's first line and the line holding the cursor
+fn render_blocks(
+    blocks: &[ContentBlock],
+    first_index: usize,
+    context: &RenderContext,
+) -> (Vec<Line<'static>>, Vec<usize>, Option<usize>) {
+    let RenderContext {
+        theme,
+        width,
+        typography,
+        review,
+        note_anchors,
+        visual_mode,
+        cursor_state,
+        image_heights,
+    } = *context;
     let mut lines: Vec<Line<'static>> = Vec::new();
     let mut block_offsets: Vec<usize> = Vec::with_capacity(blocks.len());
+    let mut cursor_line = None;
 
-    for (block_index, block) in blocks.iter().enumerate() {
+    for (i, block) in blocks.iter().enumerate() {
+        let block_index = first_index + i;
         // Record the starting line for this block
         block_offsets.push(lines.len());
         // Find note anchors for this block
-        let block_anchors: Vec<(usize, usize)> = note_anchors
+        let block_anchors: Vec<(usize, usize, Option<HighlightColor>)> = note_anchors
             .iter()
-            .filter_map(|anchor| {
-                if anchor.block_index() == Some(block_index) { anchor.char_range() } else { None }
+            .filter(|(anchor, _)| anchor.block_index() == Some(block_index))
+            .filter_map(|(anchor, color)| {
+                anchor.char_range().map(|(start, end)| (start, end, *color))
             })
             .collect();
 
@@ -574,19 +1179,23 @@ pub fn render_content_blocks_with_offsets(
             }
             ContentBlock::Paragraph(text) => {
                 if let Some((start, end)) = selection_range {
-                    render_paragraph_with_selection(&mut lines, text, theme, width, start, end);
+                    render_paragraph_with_selection(
+                        &mut lines, text, theme, width, typography, start, end,
+                    );
                 } else if let Some(pos) = cursor_pos {
                     // Show cursor without selection
-                    render_paragraph_with_cursor(&mut lines, text, theme, width, pos);
+                    render_paragraph_with_cursor(&mut lines, text, theme, width, typography, pos);
                 } else if block_anchors.is_empty() {
-                    render_paragraph(&mut lines, text, theme, width);
+                    render_paragraph(&mut lines, text, theme, width, typography);
                 } else {
                     render_paragraph_with_underlines(
                         &mut lines,
                         text,
                         theme,
                         width,
+                        typography,
                         &block_anchors,
+                        review,
                     );
                 }
             }
@@ -602,30 +1211,38 @@ pub fn render_content_blocks_with_offsets(
             ContentBlock::UnorderedList(items) => {
                 if let Some((start, end)) = selection_range {
                     render_unordered_list_with_selection(
-                        &mut lines, items, theme, width, start, end,
+                        &mut lines, items, theme, width, typography, start, end,
                     );
                 } else if let Some(pos) = cursor_pos {
-                    render_unordered_list_with_cursor(&mut lines, items, theme, width, pos);
+                    render_unordered_list_with_cursor(
+                        &mut lines, items, theme, width, typography, pos,
+                    );
                 } else {
-                    render_unordered_list(&mut lines, items, theme, width);
+                    render_unordered_list(&mut lines, items, theme, width, typography);
                 }
             }
             ContentBlock::OrderedList(items) => {
                 if let Some((start, end)) = selection_range {
-                    render_ordered_list_with_selection(&mut lines, items, theme, width, start, end);
+                    render_ordered_list_with_selection(
+                        &mut lines, items, theme, width, typography, start, end,
+                    );
                 } else if let Some(pos) = cursor_pos {
-                    render_ordered_list_with_cursor(&mut lines, items, theme, width, pos);
+                    render_ordered_list_with_cursor(
+                        &mut lines, items, theme, width, typography, pos,
+                    );
                 } else {
-                    render_ordered_list(&mut lines, items, theme, width);
+                    render_ordered_list(&mut lines, items, theme, width, typography);
                 }
             }
             ContentBlock::Blockquote(text) => {
                 if let Some((start, end)) = selection_range {
-                    render_blockquote_with_selection(&mut lines, text, theme, width, start, end);
+                    render_blockquote_with_selection(
+                        &mut lines, text, theme, width, typography, start, end,
+                    );
                 } else if let Some(pos) = cursor_pos {
-                    render_blockquote_with_cursor(&mut lines, text, theme, width, pos);
+                    render_blockquote_with_cursor(&mut lines, text, theme, width, typography, pos);
                 } else {
-                    render_blockquote(&mut lines, text, theme, width);
+                    render_blockquote(&mut lines, text, theme, width, typography);
                 }
             }
             ContentBlock::HorizontalRule => {
@@ -637,12 +1254,71 @@ pub fn render_content_blocks_with_offsets(
                 render_image(&mut lines, alt, theme, height);
             }
             ContentBlock::Table(table) => {
-                render_table(&mut lines, table, theme);
+                let scroll = cursor_state
+                    .filter(|cs| cs.cursor_block == block_index)
+                    .map_or(0, |cs| cs.table_scroll);
+                render_table(&mut lines, table, theme, width, scroll);
             }
+            ContentBlock::Footnote { label, text } => {
+                render_footnote(&mut lines, label, text, theme, width, typography);
+            }
+        }
+
+        // Review mode dims the text without notes or highlights; code, tables
+        // and images keep their colors, and marked paragraphs emphasize their marks
+        let marked = matches!(block, ContentBlock::Paragraph(_)) && !block_anchors.is_empty();
+        let prose = !matches!(
+            block,
+            ContentBlock::Code(_) | ContentBlock::Table(_) | ContentBlock::Image { .. }
+        );
+        if review && prose && !marked && cursor_pos.is_none() && selection_range.is_none() {
+            let first = block_offsets[i];
+            mute_lines(&mut lines[first..], theme);
+        }
+
+        // The render_*_with_cursor functions drew the cursor cell; mark its line too
+        if cursor_pos.is_some() && selection_range.is_none() {
+            let first = block_offsets[i];
+            cursor_line =
+                highlight_cursor_line(&mut lines[first..], theme, width).map(|line| first + line);
         }
     }
 
-    (lines, block_offsets)
+    (lines, block_offsets, cursor_line)
+}
+
+/// Give the wrapped line holding the cursor cell a subtle full-width
+/// background, so it is easy to find within long paragraphs, returning
+/// which of the block's lines it is
+fn highlight_cursor_line(
+    lines: &mut [Line<'static>],
+    theme: &Theme,
+    width: usize,
+) -> Option<usize> {
+    let is_cursor = |span: &Span| {
+        span.style.fg == Some(theme.bg_primary) && span.style.bg == Some(theme.accent_primary)
+    };
+    let row = lines.iter().position(|line| line.spans.iter().any(is_cursor))?;
+    let line = &mut lines[row];
+
+    // Code blocks keep to their box; prose lines are filled out to the width
+    let prose = line.spans.first().is_some_and(|span| span.style.bg.is_none());
+    // Code and inline code already sit on the secondary background
+    for span in line.spans.iter_mut().filter(|span| !is_cursor(span)) {
+        match span.style.bg {
+            None => span.style.bg = Some(theme.bg_secondary),
+            Some(bg) if bg == theme.bg_secondary => span.style.bg = Some(theme.bg_tertiary),
+            Some(_) => {}
+        }
+    }
+    let filled = line.width();
+    if prose && filled < width {
+        line.spans.push(Span::styled(
+            " ".repeat(width - filled),
+            Style::default().bg(theme.bg_secondary),
+        ));
+    }
+    Some(row)
 }
 
 fn render_heading(lines: &mut Vec<Line<'static>>, level: u8, text: &str, theme: &Theme) {
@@ -837,11 +1513,17 @@ fn render_heading_with_cursor(
     lines.push(Line::from(""));
 }
 
-fn render_paragraph(lines: &mut Vec<Line<'static>>, text: &str, theme: &Theme, width: usize) {
+fn render_paragraph(
+    lines: &mut Vec<Line<'static>>,
+    text: &str,
+    theme: &Theme,
+    width: usize,
+    typography: Typography,
+) {
     // Parse inline formatting and word-wrap
     let padding = "  "; // Left padding for paragraph text
     let spans = parse_inline_formatting(text, theme);
-    let wrapped_lines = wrap_spans(spans, width.saturating_sub(4)); // Account for padding
+    let wrapped_lines = wrap_spans(spans, width.saturating_sub(4), typography); // Account for padding
 
     for line in wrapped_lines {
         let mut padded_spans = vec![Span::raw(padding)];
@@ -857,12 +1539,13 @@ fn render_paragraph_with_selection(
     text: &str,
     theme: &Theme,
     width: usize,
+    typography: Typography,
     start: usize,
     end: usize,
 ) {
     let padding = "  "; // Left padding for paragraph text
     let spans = parse_text_with_selection(text, theme, start, end);
-    let wrapped_lines = wrap_spans(spans, width.saturating_sub(4)); // Account for padding
+    let wrapped_lines = wrap_spans(spans, width.saturating_sub(4), typography); // Account for padding
 
     for line in wrapped_lines {
         let mut padded_spans = vec![Span::raw(padding)];
@@ -878,11 +1561,12 @@ fn render_paragraph_with_cursor(
     text: &str,
     theme: &Theme,
     width: usize,
+    typography: Typography,
     cursor_pos: usize,
 ) {
     let padding = "  "; // Left padding for paragraph text
     let spans = parse_text_with_cursor(text, theme, cursor_pos);
-    let wrapped_lines = wrap_spans(spans, width.saturating_sub(4)); // Account for padding
+    let wrapped_lines = wrap_spans(spans, width.saturating_sub(4), typography); // Account for padding
 
     for line in wrapped_lines {
         let mut padded_spans = vec![Span::raw(padding)];
@@ -1121,18 +1805,21 @@ fn parse_text_with_selection(
     spans
 }
 
-/// Render a paragraph with underlined note ranges
+/// Render a paragraph with underlined note ranges and colored highlights,
+/// bolded with the rest of the text dimmed in review mode
 fn render_paragraph_with_underlines(
     lines: &mut Vec<Line<'static>>,
     text: &str,
     theme: &Theme,
     width: usize,
-    underline_ranges: &[(usize, usize)],
+    typography: Typography,
+    underline_ranges: &[(usize, usize, Option<HighlightColor>)],
+    review: bool,
 ) {
     // First, apply underlines to the raw text spans, then parse inline formatting
     let padding = "  "; // Left padding for paragraph text
-    let spans = parse_inline_formatting_with_underlines(text, theme, underline_ranges);
-    let wrapped_lines = wrap_spans(spans, width.saturating_sub(4)); // Account for padding
+    let spans = parse_inline_formatting_with_underlines(text, theme, underline_ranges, review);
+    let wrapped_lines = wrap_spans(spans, width.saturating_sub(4), typography); // Account for padding
 
     for line in wrapped_lines {
         let mut padded_spans = vec![Span::raw(padding)];
@@ -1142,128 +1829,97 @@ fn render_paragraph_with_underlines(
     lines.push(Line::from(""));
 }
 
-/// Parse inline markdown formatting with underlines for note ranges
+/// Background of a highlight color, taken from the theme's palette
+pub fn highlight_background(color: HighlightColor, theme: &Theme) -> Color {
+    match color {
+        HighlightColor::Yellow => theme.warning,
+        HighlightColor::Green => theme.success,
+        HighlightColor::Blue => theme.info,
+        HighlightColor::Pink => theme.error,
+        HighlightColor::Purple => theme.accent_secondary,
+    }
+}
+
+/// Parse inline markdown formatting, underlining note ranges and giving
+/// highlights their background color; `emphasize` bolds them and dims the rest
 fn parse_inline_formatting_with_underlines(
     text: &str,
     theme: &Theme,
-    underline_ranges: &[(usize, usize)],
+    underline_ranges: &[(usize, usize, Option<HighlightColor>)],
+    emphasize: bool,
 ) -> Vec<Span<'static>> {
-    // Check if a character index falls within any underline range
-    let should_underline = |char_idx: usize| -> bool {
-        underline_ranges.iter().any(|(start, end)| char_idx >= *start && char_idx < *end)
-    };
-
     // Use accent color for underlined noted text to make it visually distinct
     let underline_color = theme.accent_secondary;
+    let plain = Style::default().fg(if emphasize { theme.fg_muted } else { theme.fg_primary });
+    let marked = if emphasize { plain.add_modifier(Modifier::BOLD) } else { plain };
+
+    // Style of the character at an index; a highlight's background shows
+    // under any note's underline
+    let style_at = |char_idx: usize| -> Style {
+        let mut ranges = underline_ranges
+            .iter()
+            .filter(|(start, end, _)| char_idx >= *start && char_idx < *end)
+            .peekable();
+        if ranges.peek().is_none() {
+            return plain;
+        }
+        ranges.fold(marked, |style, (_, _, color)| match color {
+            Some(color) => style.fg(theme.bg_primary).bg(highlight_background(*color, theme)),
+            None if style.bg.is_none() => {
+                style.fg(underline_color).add_modifier(Modifier::UNDERLINED)
+            }
+            None => style.add_modifier(Modifier::UNDERLINED),
+        })
+    };
 
     let mut spans = Vec::new();
     let mut chars = text.chars().peekable();
     let mut current = String::new();
-    let mut current_underlined = false;
+    let mut current_style = plain;
     let mut char_idx = 0;
 
     while let Some(c) = chars.next() {
-        let is_underlined = should_underline(char_idx);
-
-        match c {
-            '`' => {
-                // Flush current text
-                if !current.is_empty() {
-                    let style = if current_underlined {
-                        Style::default().fg(underline_color).add_modifier(Modifier::UNDERLINED)
-                    } else {
-                        Style::default().fg(theme.fg_primary)
-                    };
-                    spans.push(Span::styled(current.clone(), style));
-                    current.clear();
-                }
-
-                // Inline code
-                let mut code = String::new();
-                let code_start_idx = char_idx + 1;
-                let mut code_char_idx = code_start_idx;
-                while let Some(&next) = chars.peek() {
-                    if next == '`' {
-                        chars.next();
-                        char_idx += 1;
-                        break;
-                    }
-                    code.push(chars.next().unwrap());
-                    char_idx += 1;
-                    code_char_idx += 1;
-                }
-
-                // Check if any part of code should be underlined
-                let code_underlined = (code_start_idx..code_char_idx).any(&should_underline);
-                let style = if code_underlined {
-                    Style::default()
-                        .fg(underline_color)
-                        .bg(theme.bg_secondary)
-                        .add_modifier(Modifier::UNDERLINED | Modifier::BOLD)
-                } else {
-                    Style::default()
-                        .fg(theme.syntax_string)
-                        .bg(theme.bg_secondary)
-                        .add_modifier(Modifier::BOLD)
-                };
-                spans.push(Span::styled(code, style));
-                current_underlined = is_underlined;
-            }
-            '*' | '_' => {
-                // Simplified: for bold/italic, just add the characters
-                // Full parsing would require tracking nested formatting
-                if current_underlined != is_underlined && !current.is_empty() {
-                    let style = if current_underlined {
-                        Style::default().fg(underline_color).add_modifier(Modifier::UNDERLINED)
-                    } else {
-                        Style::default().fg(theme.fg_primary)
-                    };
-                    spans.push(Span::styled(current.clone(), style));
-                    current.clear();
-                }
-                current.push(c);
-                current_underlined = is_underlined;
+        if c == '`' {
+            // Flush current text
+            if !current.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut current), current_style));
             }
-            '[' => {
-                // Simplified: just add the character
-                if current_underlined != is_underlined && !current.is_empty() {
-                    let style = if current_underlined {
-                        Style::default().fg(underline_color).add_modifier(Modifier::UNDERLINED)
-                    } else {
-                        Style::default().fg(theme.fg_primary)
-                    };
-                    spans.push(Span::styled(current.clone(), style));
-                    current.clear();
+
+            // Inline code
+            let mut code = String::new();
+            let code_start_idx = char_idx + 1;
+            while let Some(&next) = chars.peek() {
+                chars.next();
+                char_idx += 1;
+                if next == '`' {
+                    break;
                 }
-                current.push(c);
-                current_underlined = is_underlined;
+                code.push(next);
             }
-            _ => {
-                // Regular character - check if underline state changed
-                if current_underlined != is_underlined && !current.is_empty() {
-                    let style = if current_underlined {
-                        Style::default().fg(underline_color).add_modifier(Modifier::UNDERLINED)
-                    } else {
-                        Style::default().fg(theme.fg_primary)
-                    };
-                    spans.push(Span::styled(current.clone(), style));
-                    current.clear();
-                }
-                current.push(c);
-                current_underlined = is_underlined;
+
+            // Marked if any part of the code is
+            let code_end_idx = code_start_idx + code.chars().count();
+            let style = match (code_start_idx..code_end_idx).map(style_at).find(|s| *s != plain) {
+                Some(marked) if marked.bg.is_some() => marked,
+                Some(marked) => marked.bg(theme.bg_secondary),
+                None => Style::default().fg(theme.syntax_string).bg(theme.bg_secondary),
+            };
+            spans.push(Span::styled(code, style.add_modifier(Modifier::BOLD)));
+        } else {
+            // Bold/italic markers and links are kept as typed
+            let style = style_at(char_idx);
+            if style != current_style && !current.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut current), current_style));
             }
+            current.push(c);
+            current_style = style;
         }
         char_idx += 1;
     }
 
     // Flush remaining text
     if !current.is_empty() {
-        let style = if current_underlined {
-            Style::default().fg(underline_color).add_modifier(Modifier::UNDERLINED)
-        } else {
-            Style::default().fg(theme.fg_primary)
-        };
-        spans.push(Span::styled(current, style));
+        spans.push(Span::styled(current, current_style));
     }
 
     if spans.is_empty() {
@@ -1486,7 +2142,15 @@ fn parse_inline_formatting(text: &str, theme: &Theme) -> Vec<Span<'static>> {
 }
 
 /// Wrap styled spans into lines while preserving formatting
-fn wrap_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Line<'static>> {
+///
+/// With hyphenation on, a word that doesn't fit is broken to fill the line
+/// and words wider than a line are split rather than overflowing. With
+/// justification on, every line but the last is stretched to `width`.
+fn wrap_spans(
+    spans: Vec<Span<'static>>,
+    width: usize,
+    typography: Typography,
+) -> Vec<Line<'static>> {
     if width == 0 {
         return vec![Line::from(spans)];
     }
@@ -1499,18 +2163,30 @@ fn wrap_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Line<'static>> {
         let text = span.content.to_string();
         let style = span.style;
 
-        for word in text.split_inclusive(char::is_whitespace) {
-            let word_len = word.chars().count();
-
-            if current_width + word_len > width && current_width > 0 {
+        for word in hyphenate::words(&text) {
+            let mut word = word.to_string();
+            while current_width + word.width() > width {
+                let room = width.saturating_sub(current_width);
+                let split = typography
+                    .hyphenate
+                    .then(|| hyphenate::split(&word, room, current_width == 0))
+                    .flatten();
+                match split {
+                    Some((head, tail)) => {
+                        current_line.push(Span::styled(head, style));
+                        word = tail;
+                    }
+                    // Too long to split further: let it overflow
+                    None if current_width == 0 => break,
+                    None => {}
+                }
                 // Start new line
-                lines.push(Line::from(current_line.clone()));
-                current_line.clear();
+                lines.push(Line::from(std::mem::take(&mut current_line)));
                 current_width = 0;
             }
 
-            current_line.push(Span::styled(word.to_string(), style));
-            current_width += word_len;
+            current_width += word.width();
+            current_line.push(Span::styled(word, style));
         }
     }
 
@@ -1522,9 +2198,91 @@ fn wrap_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Line<'static>> {
         lines.push(Line::from(""));
     }
 
+    if typography.justify {
+        let last = lines.len() - 1;
+        for line in &mut lines[..last] {
+            justify_line(line, width);
+        }
+    }
+
     lines
 }
 
+/// Character of `text` under column `col` of wrapped line `line`, wrapped as
+/// the renderer wraps it with `typography`: spaces added by justification
+/// count as the word after them, and an added hyphen or a column past the
+/// line's end as the line's last character
+pub fn wrapped_char_at(
+    text: &str,
+    width: usize,
+    typography: Typography,
+    line: usize,
+    col: usize,
+) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let last = chars.len().saturating_sub(1);
+    let lines = wrap_spans(vec![Span::raw(text.to_string())], width, typography);
+    let Some(target) = lines.get(line) else { return last };
+
+    let line_start = lines[..line].iter().fold(0, |start, l| shown_until(&chars, start, l, None));
+    let line_end = shown_until(&chars, line_start, target, None);
+    let under = shown_until(&chars, line_start, target, Some(col));
+    under.min(line_end.saturating_sub(1)).max(line_start).min(last)
+}
+
+/// Index in `chars` past the text shown on a wrapped line starting at `start`,
+/// or only its first `columns`; rendered characters that don't match the
+/// text's were added by typography
+fn shown_until(chars: &[char], start: usize, line: &Line, columns: Option<usize>) -> usize {
+    let mut next = start;
+    let mut width = 0;
+    for c in line.spans.iter().flat_map(|s| s.content.chars()) {
+        width += c.width().unwrap_or(0);
+        if columns.is_some_and(|columns| width > columns) {
+            break;
+        }
+        if chars.get(next) == Some(&c) {
+            next += 1;
+        }
+    }
+    next
+}
+
+/// Widen the spaces between words so a wrapped line ends at `width`
+///
+/// Lines much shorter than the width (before a word too long to share a
+/// line) are left ragged rather than opened up into rivers of space.
+fn justify_line(line: &mut Line<'static>, width: usize) {
+    const MAX_STRETCH: usize = 3;
+
+    let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+    let chars: Vec<char> = text.chars().collect();
+    let end = text.trim_end().chars().count();
+    let start = chars.iter().take_while(|c| c.is_whitespace()).count();
+    let gaps: Vec<usize> =
+        (start..end).filter(|&i| chars[i] == ' ' && !chars[i + 1].is_whitespace()).collect();
+    let extra = width.saturating_sub(text.trim_end().width());
+    if gaps.is_empty() || extra == 0 || extra > gaps.len() * MAX_STRETCH {
+        return;
+    }
+
+    // Spread the extra spaces evenly, the first gaps taking any remainder
+    let mut widen = vec![0; chars.len()];
+    for (n, &gap) in gaps.iter().enumerate() {
+        widen[gap] = extra / gaps.len() + usize::from(n < extra % gaps.len());
+    }
+    let mut index = 0;
+    for span in &mut line.spans {
+        let mut content = String::new();
+        for c in span.content.chars() {
+            content.push(c);
+            content.extend(std::iter::repeat_n(' ', widen[index]));
+            index += 1;
+        }
+        span.content = content.into();
+    }
+}
+
 /// Clean up language label by removing attributes like "ignore", "noplayground", etc.
 fn clean_language_label(lang: Option<&str>) -> String {
     match lang {
@@ -1555,11 +2313,11 @@ fn render_code_block(
     let lang_label = clean_language_label(code.language.as_deref());
 
     // Calculate the max line width for consistent shading
-    let code_width = code.code.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    let code_width = code.code.lines().map(|l| l.width()).max().unwrap_or(0);
     let block_width = width.saturating_sub(4).max(code_width + 2);
 
     // Language label header with background - full width
-    let header_padding = block_width.saturating_sub(lang_label.len() + 4);
+    let header_padding = block_width.saturating_sub(lang_label.width() + 4);
     lines.push(Line::from(vec![
         Span::styled("┌─ ", border_style),
         Span::styled(lang_label, Style::default().fg(theme.info).bg(theme.bg_secondary)),
@@ -1577,8 +2335,7 @@ fn render_code_block(
             .map(|span| Span::styled(span.content.to_string(), span.style.bg(theme.bg_secondary)))
             .collect();
 
-        let line_char_count: usize =
-            highlighted_with_bg.iter().map(|s| s.content.chars().count()).sum();
+        let line_char_count: usize = highlighted_with_bg.iter().map(|s| s.content.width()).sum();
 
         line_spans.extend(highlighted_with_bg);
 
@@ -1621,11 +2378,11 @@ fn render_code_block_with_cursor(
 
     let lang_label = clean_language_label(code.language.as_deref());
 
-    let code_width = code.code.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    let code_width = code.code.lines().map(|l| l.width()).max().unwrap_or(0);
     let block_width = width.saturating_sub(4).max(code_width + 2);
 
     // Header - full width
-    let header_padding = block_width.saturating_sub(lang_label.len() + 4);
+    let header_padding = block_width.saturating_sub(lang_label.width() + 4);
     lines.push(Line::from(vec![
         Span::styled("┌─ ", border_style),
         Span::styled(lang_label, Style::default().fg(theme.info).bg(theme.bg_secondary)),
@@ -1690,8 +2447,7 @@ fn render_code_block_with_cursor(
         }
 
         // Pad to fill width
-        let line_char_count: usize =
-            line_spans.iter().skip(1).map(|s| s.content.chars().count()).sum();
+        let line_char_count: usize = line_spans.iter().skip(1).map(|s| s.content.width()).sum();
         let padding_needed = block_width.saturating_sub(line_char_count + 2);
         if padding_needed > 0 {
             line_spans.push(Span::styled(" ".repeat(padding_needed), bg_style));
@@ -1726,11 +2482,11 @@ fn render_code_block_with_selection(
 
     let lang_label = clean_language_label(code.language.as_deref());
 
-    let code_width = code.code.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    let code_width = code.code.lines().map(|l| l.width()).max().unwrap_or(0);
     let block_width = width.saturating_sub(4).max(code_width + 2);
 
     // Header - full width
-    let header_padding = block_width.saturating_sub(lang_label.len() + 4);
+    let header_padding = block_width.saturating_sub(lang_label.width() + 4);
     lines.push(Line::from(vec![
         Span::styled("┌─ ", border_style),
         Span::styled(lang_label, Style::default().fg(theme.info).bg(theme.bg_secondary)),
@@ -1803,8 +2559,7 @@ fn render_code_block_with_selection(
         }
 
         // Pad to fill width
-        let line_char_count: usize =
-            line_spans.iter().skip(1).map(|s| s.content.chars().count()).sum();
+        let line_char_count: usize = line_spans.iter().skip(1).map(|s| s.content.width()).sum();
         let padding_needed = block_width.saturating_sub(line_char_count + 2);
         if padding_needed > 0 {
             line_spans.push(Span::styled(" ".repeat(padding_needed), bg_style));
@@ -1836,6 +2591,7 @@ fn render_unordered_list(
     items: &[String],
     theme: &Theme,
     width: usize,
+    typography: Typography,
 ) {
     let bullet = "  • ";
     let indent = "    "; // Same width as bullet for continuation lines
@@ -1843,7 +2599,7 @@ fn render_unordered_list(
 
     for item in items {
         let spans = parse_inline_formatting(item, theme);
-        let wrapped = wrap_spans(spans, content_width);
+        let wrapped = wrap_spans(spans, content_width, typography);
 
         for (i, line) in wrapped.into_iter().enumerate() {
             let prefix = if i == 0 {
@@ -1864,6 +2620,7 @@ fn render_ordered_list(
     items: &[String],
     theme: &Theme,
     width: usize,
+    typography: Typography,
 ) {
     let content_width = width.saturating_sub(6); // Account for "  X. " prefix
 
@@ -1871,7 +2628,7 @@ fn render_ordered_list(
         let prefix = format!("  {}. ", i + 1);
         let indent = "     "; // Same width for continuation lines
         let spans = parse_inline_formatting(item, theme);
-        let wrapped = wrap_spans(spans, content_width);
+        let wrapped = wrap_spans(spans, content_width, typography);
 
         for (j, line) in wrapped.into_iter().enumerate() {
             let prefix_span = if j == 0 {
@@ -1893,6 +2650,7 @@ fn render_unordered_list_with_cursor(
     items: &[String],
     theme: &Theme,
     width: usize,
+    typography: Typography,
     cursor_pos: usize,
 ) {
     let bullet = "  • ";
@@ -1920,7 +2678,7 @@ fn render_unordered_list_with_cursor(
             parse_inline_formatting(item, theme)
         };
 
-        let wrapped = wrap_spans(item_spans, content_width);
+        let wrapped = wrap_spans(item_spans, content_width, typography);
 
         for (j, line) in wrapped.into_iter().enumerate() {
             let prefix_span = if j == 0 {
@@ -1946,6 +2704,7 @@ fn render_unordered_list_with_selection(
     items: &[String],
     theme: &Theme,
     width: usize,
+    typography: Typography,
     start: usize,
     end: usize,
 ) {
@@ -1981,7 +2740,7 @@ fn render_unordered_list_with_selection(
             parse_inline_formatting(item, theme)
         };
 
-        let wrapped = wrap_spans(item_spans, content_width);
+        let wrapped = wrap_spans(item_spans, content_width, typography);
 
         for (j, line) in wrapped.into_iter().enumerate() {
             let prefix_span = if j == 0 {
@@ -2006,6 +2765,7 @@ fn render_ordered_list_with_cursor(
     items: &[String],
     theme: &Theme,
     width: usize,
+    typography: Typography,
     cursor_pos: usize,
 ) {
     let indent = "     ";
@@ -2030,7 +2790,7 @@ fn render_ordered_list_with_cursor(
             parse_inline_formatting(item, theme)
         };
 
-        let wrapped = wrap_spans(item_spans, content_width);
+        let wrapped = wrap_spans(item_spans, content_width, typography);
 
         for (j, line) in wrapped.into_iter().enumerate() {
             let prefix_span = if j == 0 {
@@ -2055,6 +2815,7 @@ fn render_ordered_list_with_selection(
     items: &[String],
     theme: &Theme,
     width: usize,
+    typography: Typography,
     start: usize,
     end: usize,
 ) {
@@ -2087,7 +2848,7 @@ fn render_ordered_list_with_selection(
             parse_inline_formatting(item, theme)
         };
 
-        let wrapped = wrap_spans(item_spans, content_width);
+        let wrapped = wrap_spans(item_spans, content_width, typography);
 
         for (j, line) in wrapped.into_iter().enumerate() {
             let prefix_span = if j == 0 {
@@ -2106,7 +2867,43 @@ fn render_ordered_list_with_selection(
     lines.push(Line::from(""));
 }
 
-fn render_blockquote(lines: &mut Vec<Line<'static>>, text: &str, theme: &Theme, width: usize) {
+/// Render a footnote as muted text hanging from its marker
+fn render_footnote(
+    lines: &mut Vec<Line<'static>>,
+    label: &str,
+    text: &str,
+    theme: &Theme,
+    width: usize,
+    typography: Typography,
+) {
+    let marker = format!("  {} ", footnote_marker(label));
+    let indent = " ".repeat(marker.width());
+    let spans: Vec<Span<'static>> = parse_inline_formatting(text, theme)
+        .into_iter()
+        .map(|s| Span::styled(s.content.to_string(), s.style.fg(theme.fg_muted)))
+        .collect();
+    let wrapped = wrap_spans(spans, width.saturating_sub(indent.len() + 2), typography);
+
+    for (i, line) in wrapped.into_iter().enumerate() {
+        let prefix = if i == 0 {
+            Span::styled(marker.clone(), Style::default().fg(theme.accent_secondary))
+        } else {
+            Span::raw(indent.clone())
+        };
+        let mut spans = vec![prefix];
+        spans.extend(line.spans);
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(""));
+}
+
+fn render_blockquote(
+    lines: &mut Vec<Line<'static>>,
+    text: &str,
+    theme: &Theme,
+    width: usize,
+    typography: Typography,
+) {
     let prefix = "  │ ";
     let content_width = width.saturating_sub(4); // Account for prefix
 
@@ -2136,7 +2933,7 @@ fn render_blockquote(lines: &mut Vec<Line<'static>>, text: &str, theme: &Theme,
                 .into_iter()
                 .map(|s| Span::styled(s.content.to_string(), s.style.fg(theme.fg_muted)))
                 .collect();
-            let wrapped = wrap_spans(muted_spans, content_width);
+            let wrapped = wrap_spans(muted_spans, content_width, typography);
 
             for wrapped_line in wrapped {
                 let mut line_spans =
@@ -2160,6 +2957,7 @@ fn render_blockquote_with_selection(
     text: &str,
     theme: &Theme,
     width: usize,
+    typography: Typography,
     start: usize,
     end: usize,
 ) {
@@ -2193,7 +2991,7 @@ fn render_blockquote_with_selection(
         spans.push(Span::styled("", muted_style));
     }
 
-    let wrapped = wrap_spans(spans, content_width);
+    let wrapped = wrap_spans(spans, content_width, typography);
 
     for wrapped_line in wrapped {
         let mut line_spans = vec![Span::styled(prefix, Style::default().fg(theme.accent_primary))];
@@ -2210,6 +3008,7 @@ fn render_blockquote_with_cursor(
     text: &str,
     theme: &Theme,
     width: usize,
+    typography: Typography,
     cursor_pos: usize,
 ) {
     let prefix = "  │ ";
@@ -2242,7 +3041,7 @@ fn render_blockquote_with_cursor(
         }
     }
 
-    let wrapped = wrap_spans(spans, content_width);
+    let wrapped = wrap_spans(spans, content_width, typography);
 
     for wrapped_line in wrapped {
         let mut line_spans = vec![Span::styled(prefix, Style::default().fg(theme.accent_primary))];
@@ -2271,24 +3070,24 @@ fn render_image(lines: &mut Vec<Line<'static>>, _alt: &str, _theme: &Theme, heig
     }
 }
 
-fn render_table(lines: &mut Vec<Line<'static>>, table: &crate::book::Table, theme: &Theme) {
-    let num_cols = table.headers.len().max(table.rows.iter().map(|r| r.len()).max().unwrap_or(0));
+/// Render a table laid out in `width` columns, scrolled `scroll` columns
+/// sideways if it is wider than that
+fn render_table(
+    lines: &mut Vec<Line<'static>>,
+    table: &Table,
+    theme: &Theme,
+    width: usize,
+    scroll: usize,
+) {
+    let col_widths = table_column_widths(table, width);
+    let num_cols = col_widths.len();
     if num_cols == 0 {
         return;
     }
 
-    // Fixed column widths based on number of columns
-    let col_widths: Vec<usize> = match num_cols {
-        1 => vec![70],
-        2 => vec![30, 40],
-        3 => vec![20, 25, 30],
-        4 => vec![10, 18, 28, 14],
-        5 => vec![8, 15, 22, 15, 10],
-        _ => vec![12; num_cols],
-    };
-
     // Add spacing before table
     lines.push(Line::from(""));
+    let first_row = lines.len();
 
     // Header row with background color
     if !table.headers.is_empty() {
@@ -2299,10 +3098,7 @@ fn render_table(lines: &mut Vec<Line<'static>>, table: &crate::book::Table, them
         header_spans.push(Span::raw("  "));
 
         for (i, header) in table.headers.iter().enumerate() {
-            let width = col_widths.get(i).copied().unwrap_or(15);
-            // Strip backticks from headers
-            let clean_header = header.replace('`', "");
-            let padded = pad_or_truncate(&clean_header, width);
+            let padded = pad_or_truncate(&clean_cell(header), col_widths[i]);
 
             // Add spacing between columns
             if i > 0 {
@@ -2332,10 +3128,7 @@ fn render_table(lines: &mut Vec<Line<'static>>, table: &crate::book::Table, them
         let mut max_lines = 1;
 
         for (i, cell) in row.iter().enumerate() {
-            let width = col_widths.get(i).copied().unwrap_or(15);
-            // Strip backticks from cell content
-            let clean_cell = cell.replace('`', "");
-            let wrapped = wrap_cell_text(&clean_cell, width);
+            let wrapped = wrap_cell_text(&clean_cell(cell), col_widths[i]);
             max_lines = max_lines.max(wrapped.len());
             wrapped_cells.push(wrapped);
         }
@@ -2353,9 +3146,8 @@ fn render_table(lines: &mut Vec<Line<'static>>, table: &crate::book::Table, them
             row_spans.push(Span::raw("  "));
 
             for (col_idx, wrapped) in wrapped_cells.iter().enumerate() {
-                let width = col_widths.get(col_idx).copied().unwrap_or(15);
                 let cell_line = wrapped.get(line_idx).map(|s| s.as_str()).unwrap_or("");
-                let padded = pad_or_truncate(cell_line, width);
+                let padded = pad_or_truncate(cell_line, col_widths[col_idx]);
 
                 // Add spacing between columns
                 if col_idx > 0 {
@@ -2381,20 +3173,112 @@ fn render_table(lines: &mut Vec<Line<'static>>, table: &crate::book::Table, them
         }
     }
 
+    // Wider than the panel: show the scrolled-to part and where it is
+    let total = table_width(&col_widths);
+    if total > width {
+        let scroll = scroll.min(total - width);
+        for line in &mut lines[first_row..] {
+            *line = slice_line(std::mem::take(line), scroll, width);
+        }
+        lines.push(Line::from(Span::styled(
+            format!("  ◂ columns {}-{} of {} (h/l) ▸", scroll + 1, scroll + width, total),
+            Style::default().fg(theme.fg_muted),
+        )));
+    }
+
     lines.push(Line::from(""));
 }
 
+/// Table cell text as shown, without code backticks
+fn clean_cell(cell: &str) -> String {
+    cell.replace('`', "").trim().to_string()
+}
+
+/// Width of each column of a table laid out in `width` columns
+///
+/// Columns take their natural width when the table fits. Otherwise each
+/// keeps up to `MIN_TABLE_COLUMN` and the room left is shared in proportion
+/// to how much more each needs, so the widest columns wrap most. If even
+/// that doesn't fit, the table is wider than the panel and scrolls sideways.
+fn table_column_widths(table: &Table, width: usize) -> Vec<usize> {
+    let num_cols = table.headers.len().max(table.rows.iter().map(|r| r.len()).max().unwrap_or(0));
+    let mut natural = vec![1; num_cols];
+    for row in std::iter::once(&table.headers).chain(&table.rows) {
+        for (i, cell) in row.iter().enumerate() {
+            natural[i] = natural[i].max(clean_cell(cell).width());
+        }
+    }
+
+    let room = width.saturating_sub(TABLE_GAP * (num_cols + 1));
+    if natural.iter().sum::<usize>() <= room {
+        return natural;
+    }
+    let mut widths: Vec<usize> = natural.iter().map(|&n| n.min(MIN_TABLE_COLUMN)).collect();
+    let spare = room.saturating_sub(widths.iter().sum());
+    let wanted: usize = natural.iter().zip(&widths).map(|(n, w)| n - w).sum();
+    for (w, n) in widths.iter_mut().zip(&natural) {
+        *w += ((n - *w) * spare).checked_div(wanted).unwrap_or(0);
+    }
+    widths
+}
+
+/// Columns a table with these column widths takes, with its padding
+fn table_width(col_widths: &[usize]) -> usize {
+    col_widths.iter().sum::<usize>() + TABLE_GAP * (col_widths.len() + 1)
+}
+
+/// Furthest a table laid out in `width` columns can scroll sideways
+pub fn table_scroll_limit(table: &Table, width: usize) -> usize {
+    table_width(&table_column_widths(table, width)).saturating_sub(width)
+}
+
+/// The part of a line from column `skip`, at most `width` columns wide, with
+/// spaces for the visible half of a wide character cut at either edge
+fn slice_line(line: Line<'static>, skip: usize, width: usize) -> Line<'static> {
+    let end = skip + width;
+    let mut column = 0;
+    let mut spans = Vec::new();
+    for span in line.spans {
+        let mut content = String::new();
+        for c in span.content.chars() {
+            let start = column;
+            column += c.width().unwrap_or(0);
+            if start >= skip && column <= end {
+                content.push(c);
+            } else if start < end && column > skip {
+                content.extend(std::iter::repeat_n(' ', column.min(end) - start.max(skip)));
+            }
+        }
+        if !content.is_empty() {
+            spans.push(Span::styled(content, span.style));
+        }
+    }
+    Line::from(spans)
+}
+
 /// Pad string to width or truncate with ellipsis
 fn pad_or_truncate(s: &str, width: usize) -> String {
-    let char_count = s.chars().count();
-    if char_count > width {
-        let truncated: String = s.chars().take(width.saturating_sub(1)).collect();
-        format!("{}…", truncated)
+    let text_width = s.width();
+    if text_width > width {
+        let truncated = take_width(s, width.saturating_sub(1));
+        let padding = width.saturating_sub(truncated.width() + 1);
+        format!("{}…{}", truncated, " ".repeat(padding))
     } else {
-        format!("{:width$}", s, width = width)
+        format!("{}{}", s, " ".repeat(width - text_width))
     }
 }
 
+/// The longest start of `s` that fits in `width` columns
+fn take_width(s: &str, width: usize) -> String {
+    let mut used = 0;
+    s.chars()
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+            used <= width
+        })
+        .collect()
+}
+
 /// Wrap text to fit within a given width
 fn wrap_cell_text(text: &str, width: usize) -> Vec<String> {
     if width == 0 {
@@ -2405,13 +3289,13 @@ fn wrap_cell_text(text: &str, width: usize) -> Vec<String> {
     let mut current_line = String::new();
 
     for word in text.split_whitespace() {
-        let word_len = word.chars().count();
-        let current_len = current_line.chars().count();
+        let word_len = word.width();
+        let current_len = current_line.width();
 
         if current_line.is_empty() {
             // First word on line - might need to truncate if too long
             if word_len > width {
-                current_line = word.chars().take(width).collect();
+                current_line = take_width(word, width);
             } else {
                 current_line = word.to_string();
             }
@@ -2423,7 +3307,7 @@ fn wrap_cell_text(text: &str, width: usize) -> Vec<String> {
             // Start new line
             result.push(current_line);
             if word_len > width {
-                current_line = word.chars().take(width).collect();
+                current_line = take_width(word, width);
             } else {
                 current_line = word.to_string();
             }
@@ -2454,7 +3338,7 @@ fn wrap_text(text: &str, width: usize) -> Vec<String> {
     for word in text.split_whitespace() {
         if current_line.is_empty() {
             current_line = word.to_string();
-        } else if current_line.len() + 1 + word.len() <= width {
+        } else if current_line.width() + 1 + word.width() <= width {
             current_line.push(' ');
             current_line.push_str(word);
         } else {
@@ -2477,8 +3361,66 @@ fn wrap_text(text: &str, width: usize) -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn centered_text_area_limits_and_centers() {
+        let inner = Rect { x: 10, y: 1, width: 200, height: 40 };
+        assert_eq!(centered_text_area(inner, None), inner);
+
+        let narrowed = centered_text_area(inner, Some(80));
+        assert_eq!(narrowed.width, 82);
+        assert_eq!(narrowed.x, 10 + 59);
+        assert_eq!(narrowed.height, 40);
+
+        // Panels narrower than the limit are left alone
+        assert_eq!(centered_text_area(Rect { width: 60, ..inner }, Some(80)).width, 60);
+    }
     use crate::theme::Theme;
 
+    #[test]
+    fn cursor_line_gets_a_full_width_background() {
+        let theme = Theme::default();
+        let blocks = vec![ContentBlock::Paragraph("one two three four five six seven".into())];
+        let cursor = CursorState {
+            cursor_block: 0,
+            cursor_char: 14,
+            cursor_mode: true,
+            selection_active: false,
+            table_scroll: 0,
+        };
+        let lines =
+            render_content_blocks_with_visual_mode(&blocks, &theme, 16, &[], None, Some(&cursor));
+
+        let marked: Vec<bool> = lines
+            .iter()
+            .map(|line| line.spans.first().is_some_and(|s| s.style.bg == Some(theme.bg_secondary)))
+            .collect();
+        assert_eq!(marked.iter().filter(|m| **m).count(), 1);
+        let line = lines.iter().zip(&marked).find(|(_, m)| **m).unwrap().0;
+        assert!(line.to_string().contains("four"));
+        assert_eq!(line.width(), 16);
+    }
+
+    #[test]
+    fn objective_lines_number_and_shorten_objectives() {
+        let theme = Theme::default();
+        let objectives = vec![
+            ("Explain moves".to_string(), true),
+            ("Predict when a value is dropped at the end of its scope".to_string(), false),
+        ];
+
+        let lines = objective_lines(&objectives, false, 30, &theme);
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(text[0], "  \u{25be} Objectives: 1/2 met");
+        assert_eq!(text[1], "  [x] 1. Explain moves");
+        assert_eq!(text[2], "  [ ] 2. Predict when a value\u{2026}");
+        assert!(lines.iter().all(|line| line.width() <= 30));
+
+        let folded = objective_lines(&objectives, true, 30, &theme);
+        assert_eq!(folded.len(), 1);
+        assert!(folded[0].to_string().starts_with("  \u{25b8}"));
+    }
+
     #[test]
     fn wrap_text_short() {
         let result = wrap_text("hello world", 20);
@@ -2600,6 +3542,221 @@ mod tests {
         assert_eq!(spans[0].content, "plain text only");
     }
 
+    fn plain_context<'a>(
+        theme: &'a Theme,
+        width: usize,
+        image_heights: &'a std::collections::HashMap<usize, usize>,
+    ) -> RenderContext<'a> {
+        RenderContext {
+            theme,
+            width,
+            typography: Typography::default(),
+            review: false,
+            note_anchors: &[],
+            visual_mode: None,
+            cursor_state: None,
+            image_heights,
+        }
+    }
+
+    #[test]
+    fn render_content_window_renders_small_sections_in_full() {
+        use crate::book::ContentBlock;
+        let theme = Theme::default();
+        let blocks = vec![
+            ContentBlock::Heading { level: 1, text: "Title".into() },
+            ContentBlock::Paragraph("Hello world".into()),
+        ];
+        let heights = std::collections::HashMap::new();
+        let context = plain_context(&theme, 80, &heights);
+        let window = render_content_window(&blocks, &context, 0, 40);
+        let (lines, offsets) = render_content_blocks_with_offsets(&blocks, &context);
+
+        assert_eq!(window.first_line, 0);
+        assert_eq!(window.lines.len(), lines.len());
+        assert_eq!(window.block_offsets, offsets);
+        assert_eq!(window.content_lines, lines.len());
+    }
+
+    #[test]
+    fn render_content_window_renders_giant_sections_near_viewport() {
+        use crate::book::ContentBlock;
+        let theme = Theme::default();
+        let blocks: Vec<_> =
+            (0..20_000).map(|i| ContentBlock::Paragraph(format!("Line {}", i))).collect();
+        let heights = std::collections::HashMap::new();
+        let window =
+            render_content_window(&blocks, &plain_context(&theme, 80, &heights), 20_000, 40);
+
+        assert!(window.lines.len() < 1_000);
+        assert!(window.first_line <= 20_000 && 20_040 <= window.first_line + window.lines.len());
+        assert_eq!(window.block_offsets.len(), blocks.len());
+        assert!(window.block_offsets.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(window.content_lines, 40_000);
+    }
+
+    #[test]
+    fn render_content_window_records_the_cursor_line() {
+        use crate::book::ContentBlock;
+        let theme = Theme::default();
+        let blocks = vec![
+            ContentBlock::Heading { level: 1, text: "Title".into() },
+            ContentBlock::Paragraph(format!("{} end", "word ".repeat(40))),
+        ];
+        let cursor = CursorState {
+            cursor_block: 1,
+            cursor_char: 201,
+            cursor_mode: true,
+            selection_active: false,
+            table_scroll: 0,
+        };
+        let heights = std::collections::HashMap::new();
+        let context =
+            RenderContext { cursor_state: Some(&cursor), ..plain_context(&theme, 40, &heights) };
+        let window = render_content_window(&blocks, &context, 0, 40);
+
+        let line = window.cursor_line.unwrap();
+        assert!(line > window.block_offsets[1]);
+        assert!(window.lines[line].to_string().contains("end"));
+    }
+
+    #[test]
+    fn rendered_window_covers_viewport() {
+        let window = RenderedWindow {
+            lines: vec![Line::from(""); 100],
+            first_line: 50,
+            block_offsets: Vec::new(),
+            content_lines: 1_000,
+            cursor_line: None,
+        };
+
+        assert!(window.covers(50, 40));
+        assert!(window.covers(110, 40));
+        assert!(!window.covers(20, 40));
+        assert!(!window.covers(130, 40));
+
+        // The footer below the last content line needs no rendered lines
+        let window = RenderedWindow { content_lines: 150, ..window };
+        assert!(window.covers(130, 40));
+    }
+
+    #[test]
+    fn scroll_anchor_keeps_position_across_reflow() {
+        use crate::book::ContentBlock;
+        let theme = Theme::default();
+        let heights = std::collections::HashMap::new();
+        let blocks: Vec<_> = (0..6)
+            .map(|i| ContentBlock::Paragraph(format!("Paragraph {} {}", i, "word ".repeat(60))))
+            .collect();
+        let wide = render_content_window(&blocks, &plain_context(&theme, 100, &heights), 0, 40);
+        let narrow = render_content_window(&blocks, &plain_context(&theme, 40, &heights), 0, 40);
+
+        // Halfway into the fourth paragraph stays halfway into it
+        let start = wide.block_offsets[3];
+        let anchor = wide.anchor(start + 1).unwrap();
+        assert_eq!((anchor.block, anchor.line), (3, 1));
+        let offset = narrow.offset_of(anchor);
+        assert!(offset > narrow.block_offsets[3] && offset < narrow.block_offsets[4]);
+
+        assert_eq!(narrow.offset_of(wide.anchor(start).unwrap()), narrow.block_offsets[3]);
+        assert!(wide.anchor(wide.content_lines).is_none());
+    }
+
+    #[test]
+    fn wrap_spans_hyphenates_and_justifies() {
+        let wrap = |typography| -> Vec<String> {
+            let spans =
+                vec![Span::raw("Ownership rules for borrowing in std::collections::HashMap")];
+            wrap_spans(spans, 16, typography)
+                .iter()
+                .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+                .collect()
+        };
+
+        assert_eq!(
+            wrap(Typography::default()),
+            ["Ownership rules ", "for borrowing ", "in ", "std::collections::HashMap"]
+        );
+        assert_eq!(
+            wrap(Typography { hyphenate: true, justify: false }),
+            ["Ownership rules ", "for borrowing ", "in std::collec-", "tions::HashMap"]
+        );
+        assert_eq!(
+            wrap(Typography { hyphenate: true, justify: true }),
+            ["Ownership  rules ", "for    borrowing ", "in  std::collec-", "tions::HashMap"]
+        );
+    }
+
+    #[test]
+    fn wrapped_char_at_follows_hyphenation_and_justification() {
+        let text = "Ownership rules for borrowing in std::collections::HashMap";
+        let at = |typography, line, col| {
+            text.chars().nth(wrapped_char_at(text, 16, typography, line, col))
+        };
+
+        let plain = Typography::default();
+        assert_eq!(at(plain, 1, 4), Some('b'));
+        assert_eq!(at(plain, 3, 5), Some('c'));
+
+        let hyphenated = Typography { hyphenate: true, justify: false };
+        // "in std::collec-" then "tions::HashMap"
+        assert_eq!(at(hyphenated, 2, 3), Some('s'));
+        assert_eq!(at(hyphenated, 3, 0), Some('t'));
+        assert_eq!(at(hyphenated, 3, 7), Some('H'));
+        assert_eq!(at(hyphenated, 2, 14), Some('c'));
+
+        let justified = Typography { hyphenate: true, justify: true };
+        // "for    borrowing "
+        assert_eq!(at(justified, 1, 7), Some('b'));
+        assert_eq!(at(justified, 1, 5), Some('b'));
+        assert_eq!(at(justified, 1, 3), Some(' '));
+        assert_eq!(wrapped_char_at(text, 16, justified, 9, 0), text.chars().count() - 1);
+    }
+
+    #[test]
+    fn skimming_keeps_headings_and_first_sentences_block_for_block() {
+        let blocks = vec![
+            ContentBlock::Heading { level: 2, text: "Moves".into() },
+            ContentBlock::Paragraph("Values move. Version 1.2 keeps them! Later.".into()),
+            ContentBlock::Paragraph("One sentence only.".into()),
+            ContentBlock::UnorderedList(vec!["First. More".into(), "Second".into()]),
+            ContentBlock::HorizontalRule,
+        ];
+
+        let skimmed = skim_blocks(&blocks, &BTreeSet::new());
+
+        let texts: Vec<Option<String>> = skimmed.iter().map(ContentBlock::plain_text).collect();
+        assert_eq!(
+            texts,
+            [
+                Some("Moves".into()),
+                Some("Values move. …".into()),
+                Some("One sentence only.".into()),
+                Some("First. …".into()),
+                None
+            ]
+        );
+
+        let expanded = skim_blocks(&blocks, &BTreeSet::from([1]));
+        assert_eq!(expanded[1].plain_text(), blocks[1].plain_text());
+        let shortened: Vec<bool> = blocks.iter().map(skim_shortens).collect();
+        assert_eq!(shortened, [false, true, false, true, false]);
+    }
+
+    #[test]
+    fn wide_text_wraps_and_pads_by_columns() {
+        let lines: Vec<String> =
+            wrap_spans(vec![Span::raw("借用检查器 checks")], 6, Typography::default())
+                .iter()
+                .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+                .collect();
+        assert_eq!(lines, ["借用检", "查器 ", "checks"]);
+
+        assert_eq!(pad_or_truncate("借用", 6), "借用  ");
+        assert_eq!(pad_or_truncate("借用检查器", 6), "借用… ");
+        assert_eq!(wrap_cell_text("所有权 规则", 6), ["所有权", "规则"]);
+    }
+
     #[test]
     fn render_content_blocks_empty() {
         let theme = Theme::default();
@@ -2700,6 +3857,113 @@ mod tests {
         assert!(!lines.is_empty());
     }
 
+    #[test]
+    fn table_columns_fit_their_content_then_share_the_width() {
+        use crate::book::Table;
+        let table = Table {
+            headers: vec!["Type".into(), "Description".into()],
+            rows: vec![vec!["`Box<T>`".into(), "A pointer to a value on the heap ".repeat(3)]],
+            alignments: Vec::new(),
+        };
+
+        // Natural widths when there is room, long columns squeezed when not
+        assert_eq!(table_column_widths(&table, 200), [6, 98]);
+        assert_eq!(table_column_widths(&table, 60), [6, 48]);
+        assert_eq!(table_scroll_limit(&table, 60), 0);
+
+        // Below the minimum column widths the table scrolls sideways
+        assert_eq!(table_column_widths(&table, 12), [6, 8]);
+        assert_eq!(table_scroll_limit(&table, 12), 8);
+
+        let theme = Theme::default();
+        let mut lines = Vec::new();
+        render_table(&mut lines, &table, &theme, 12, 100);
+        let text: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        assert_eq!(text[1], "  Descrip…  ");
+        assert!(text[text.len() - 2].contains("columns 9-20 of 20"));
+    }
+
+    #[test]
+    fn slice_line_cuts_by_columns() {
+        let line = Line::from(vec![Span::raw("ab"), Span::raw("借用cd")]);
+        assert_eq!(slice_line(line.clone(), 1, 4).to_string(), "b借 ");
+        assert_eq!(slice_line(line, 3, 4).to_string(), " 用c");
+    }
+
+    #[test]
+    fn highlights_get_a_background_and_notes_an_underline() {
+        let theme = Theme::default();
+        let ranges = [(0, 4, Some(HighlightColor::Green)), (5, 10, None)];
+        let spans =
+            parse_inline_formatting_with_underlines("Move `self` out", &theme, &ranges, false);
+
+        let texts: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(texts, ["Move", " ", "self", " out"]);
+        assert_eq!(spans[0].style.bg, Some(theme.success));
+        assert!(!spans[0].style.add_modifier.contains(Modifier::UNDERLINED));
+        assert_eq!(spans[1].style, Style::default().fg(theme.fg_primary));
+        assert!(spans[2].style.add_modifier.contains(Modifier::UNDERLINED | Modifier::BOLD));
+        assert_eq!(spans[2].style.fg, Some(theme.accent_secondary));
+    }
+
+    #[test]
+    fn review_mode_bolds_marks_and_dims_other_prose_only() {
+        use crate::book::{CodeBlock, ContentBlock};
+        let theme = Theme::default();
+        let blocks = vec![
+            ContentBlock::Paragraph("Owners drop values".into()),
+            ContentBlock::Paragraph("Nothing noted here".into()),
+            ContentBlock::Code(CodeBlock::new("let x = 1;").with_language("rust")),
+        ];
+        let anchor = NoteAnchor::TextRange {
+            block_index: 0,
+            start_char: 0,
+            char_length: 6,
+            selected_text: "Owners".into(),
+        };
+        let heights = std::collections::HashMap::new();
+        let context = RenderContext {
+            review: true,
+            note_anchors: &[(&anchor, None)],
+            ..plain_context(&theme, 80, &heights)
+        };
+        let (lines, offsets) = render_content_blocks_with_offsets(&blocks, &context);
+
+        let spans = &lines[offsets[0]].spans;
+        assert!(spans[1].style.add_modifier.contains(Modifier::BOLD | Modifier::UNDERLINED));
+        assert_eq!(spans[2].style.fg, Some(theme.fg_muted));
+        assert!(lines[offsets[1]].spans.iter().all(|s| s.style.fg == Some(theme.fg_muted)));
+        let code = lines[offsets[2]..].iter().flat_map(|line| line.spans.iter());
+        assert!(code.clone().any(|s| s.style.fg != Some(theme.fg_muted)));
+    }
+
+    #[test]
+    fn glossary_terms_are_marked_in_prose_only() {
+        use crate::learning::glossary::Term;
+
+        let glossary = TermMatcher::new(vec![Term {
+            term: "trait object".into(),
+            definition: "A value of type dyn Trait.".into(),
+        }])
+        .unwrap();
+        let blocks = vec![
+            ContentBlock::Paragraph("A trait object".into()),
+            ContentBlock::Code(crate::book::CodeBlock::new("// trait object")),
+        ];
+        let mut lines = vec![
+            Line::from(vec![Span::raw("  "), Span::raw("A trait object is dynamic")]),
+            Line::from(""),
+            Line::from("// trait object"),
+        ];
+
+        mark_glossary_terms(&mut lines, 0, &blocks, &[0, 2], &glossary);
+
+        let marked: Vec<&str> = lines[0].spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(marked, ["  ", "A ", "trait object", " is dynamic"]);
+        assert!(lines[0].spans[2].style.add_modifier.contains(Modifier::UNDERLINED));
+        assert_eq!(lines[2].spans.len(), 1);
+    }
+
     #[test]
     fn highlight_code_line_rust() {
         let theme = Theme::default();
@@ -9,7 +9,10 @@ use ratatui::{
 };
 use textwrap::{Options, wrap};
 
+use super::locale::{self, Text};
 use crate::app::state::AppState;
+use crate::book::Chapter;
+use crate::book::model::format_reading_time;
 use crate::config::progress::Progress;
 use crate::theme::Theme;
 
@@ -34,8 +37,16 @@ pub fn draw_with_progress(
 ) {
     let border_color = if focused { theme.border_focused } else { theme.border };
 
+    let name = locale::text(Text::Curriculum);
+    let title = if state.curriculum.filter_editing {
+        format!(" {} /{}▏", name, state.curriculum.filter)
+    } else if state.curriculum.is_filtered() {
+        format!(" {} /{} ", name, state.curriculum.filter)
+    } else {
+        format!(" {} ", name)
+    };
     let block = Block::default()
-        .title(" Curriculum ")
+        .title(title)
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color))
         .style(Style::default().bg(theme.bg_primary));
@@ -48,7 +59,7 @@ pub fn draw_with_progress(
 
     // If no book loaded, show message
     let Some(book) = &state.book else {
-        let msg = Paragraph::new("No book loaded\n\nAdd a book with:\nsensei add <path>")
+        let msg = Paragraph::new(locale::text(Text::NoBookLoaded))
             .style(Style::default().fg(theme.fg_muted));
         frame.render_widget(msg, inner);
         return;
@@ -63,7 +74,11 @@ pub fn draw_with_progress(
     let mut flat_index = 0;
 
     for (chapter_idx, chapter) in book.chapters.iter().enumerate() {
-        let is_expanded = state.curriculum.expanded_chapters.contains(&chapter_idx);
+        let shown_sections = shown_sections(state, chapter_idx, chapter);
+        if !chapter_shown(state, chapter, &shown_sections) {
+            continue;
+        }
+        let is_expanded = !shown_sections.is_empty();
         let expand_icon = if is_expanded { "▼" } else { "▶" };
 
         // Check if this chapter row is selected
@@ -111,9 +126,10 @@ pub fn draw_with_progress(
         }
         flat_index += 1;
 
-        // Sections (if expanded)
+        // Sections (if expanded, or matching the filter)
         if is_expanded {
-            for (section_idx, section) in chapter.sections.iter().enumerate() {
+            for &section_idx in &shown_sections {
+                let section = &chapter.sections[section_idx];
                 let is_section_selected = flat_index == state.curriculum.selected_index;
 
                 // Get status from progress if available
@@ -152,7 +168,15 @@ pub fn draw_with_progress(
                 } else {
                     Style::default().fg(theme.accent_primary).add_modifier(Modifier::BOLD)
                 };
-                let wrapped_lines = wrap_with_indent(&section.title, width, section_prefix.len());
+                let reading_time = section
+                    .reading_time_minutes
+                    .map(|minutes| format!(" {}", format_reading_time(minutes)))
+                    .unwrap_or_default();
+                let wrapped_lines = wrap_with_indent(
+                    &section.title,
+                    width.saturating_sub(reading_time.len()),
+                    section_prefix.len(),
+                );
 
                 let mut in_code = false;
                 for (i, line_text) in wrapped_lines.iter().enumerate() {
@@ -172,6 +196,9 @@ pub fn draw_with_progress(
                     in_code = new_in_code;
                     lines.push(Line::from(line_spans));
                 }
+                if let Some(last) = lines.last_mut() {
+                    last.push_span(Span::styled(reading_time, Style::default().fg(theme.fg_muted)));
+                }
                 flat_index += 1;
             }
         }
@@ -284,41 +311,53 @@ fn get_section_status(
     }
 }
 
-/// Calculate total visible items in curriculum
-pub fn calculate_visible_items(state: &AppState) -> usize {
-    let Some(book) = &state.book else { return 0 };
+/// Indices of a chapter's sections shown in the tree
+///
+/// An expanded chapter shows all its sections. While filtering, chapters are
+/// also expanded to show any sections whose titles match.
+fn shown_sections(state: &AppState, chapter_idx: usize, chapter: &Chapter) -> Vec<usize> {
+    let curriculum = &state.curriculum;
+    let expanded = curriculum.expanded_chapters.contains(&chapter_idx);
+    (0..chapter.sections.len())
+        .filter(|&i| {
+            expanded
+                || (curriculum.is_filtered()
+                    && curriculum.matches_filter(&chapter.sections[i].title))
+        })
+        .collect()
+}
 
-    let mut count = 0;
+/// Whether a chapter appears in the tree (always, unless filtered out)
+fn chapter_shown(state: &AppState, chapter: &Chapter, shown_sections: &[usize]) -> bool {
+    let curriculum = &state.curriculum;
+    !curriculum.is_filtered()
+        || curriculum.matches_filter(&chapter.title)
+        || shown_sections.iter().any(|&i| curriculum.matches_filter(&chapter.sections[i].title))
+}
+
+/// Items shown in the curriculum tree, in display order
+pub fn visible_items(state: &AppState) -> Vec<CurriculumItem> {
+    let Some(book) = &state.book else { return Vec::new() };
+
+    let mut items = Vec::new();
     for (chapter_idx, chapter) in book.chapters.iter().enumerate() {
-        count += 1; // Chapter itself
-        if state.curriculum.expanded_chapters.contains(&chapter_idx) {
-            count += chapter.sections.len();
+        let sections = shown_sections(state, chapter_idx, chapter);
+        if chapter_shown(state, chapter, &sections) {
+            items.push(CurriculumItem::Chapter(chapter_idx));
+            items.extend(sections.into_iter().map(|i| CurriculumItem::Section(chapter_idx, i)));
         }
     }
-    count
+    items
+}
+
+/// Calculate total visible items in curriculum
+pub fn calculate_visible_items(state: &AppState) -> usize {
+    visible_items(state).len()
 }
 
 /// Get the chapter/section at a given flat index
 pub fn get_item_at_index(state: &AppState, target_index: usize) -> Option<CurriculumItem> {
-    let book = state.book.as_ref()?;
-
-    let mut current_idx = 0;
-    for (chapter_idx, chapter) in book.chapters.iter().enumerate() {
-        if current_idx == target_index {
-            return Some(CurriculumItem::Chapter(chapter_idx));
-        }
-        current_idx += 1;
-
-        if state.curriculum.expanded_chapters.contains(&chapter_idx) {
-            for (section_idx, _section) in chapter.sections.iter().enumerate() {
-                if current_idx == target_index {
-                    return Some(CurriculumItem::Section(chapter_idx, section_idx));
-                }
-                current_idx += 1;
-            }
-        }
-    }
-    None
+    visible_items(state).get(target_index).copied()
 }
 
 /// Represents an item in the curriculum tree
@@ -331,21 +370,10 @@ pub enum CurriculumItem {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::book::{Book, BookMetadata, BookSource, Chapter, Section};
-    use std::path::PathBuf;
+    use crate::book::{Book, BookMetadata, Chapter, Section};
 
     fn create_test_book() -> Book {
-        let mut book = Book::new(BookMetadata {
-            id: "test".into(),
-            title: "Test Book".into(),
-            author: None,
-            source: BookSource::Markdown(PathBuf::from("/test")),
-            language: None,
-            description: None,
-            cover_image: None,
-            added_at: 0,
-            last_accessed: None,
-        });
+        let mut book = Book::new(BookMetadata::for_test("test", "Test Book"));
 
         let mut ch1 = Chapter::new("Getting Started", 1, "ch01");
         ch1.sections.push(Section::new("Installation", 1, "ch01/s01"));
@@ -401,6 +429,26 @@ mod tests {
         assert_eq!(get_item_at_index(&state, 3), Some(CurriculumItem::Chapter(1)));
     }
 
+    #[test]
+    fn filter_narrows_tree_and_expands_matching_sections() {
+        let mut state = AppState { book: Some(create_test_book()), ..Default::default() };
+        state.curriculum.filter = "hello".into();
+
+        assert_eq!(
+            visible_items(&state),
+            vec![CurriculumItem::Chapter(0), CurriculumItem::Section(0, 1)]
+        );
+
+        // A matching chapter is listed collapsed; expanding shows all its sections
+        state.curriculum.filter = "BASICS".into();
+        assert_eq!(visible_items(&state), vec![CurriculumItem::Chapter(1)]);
+        state.curriculum.expanded_chapters.insert(1);
+        assert_eq!(get_item_at_index(&state, 1), Some(CurriculumItem::Section(1, 0)));
+
+        state.curriculum.clear_filter();
+        assert_eq!(calculate_visible_items(&state), 3);
+    }
+
     #[test]
     fn status_not_started_without_progress() {
         let status = get_section_status(None, "book", "ch01/s01");
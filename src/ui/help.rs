@@ -0,0 +1,120 @@
+//! Help screen: every key, grouped by where it works
+//!
+//! The keys come from the keymap (`input::help_groups`), so the screen never
+//! disagrees with what the keys do. `/` filters it.
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::input::{Action, HelpGroup, help_groups, key_for};
+use crate::app::state::HelpState;
+use crate::theme::Theme;
+
+/// Draw the help screen, scrolled to `state.scroll`
+pub fn draw(frame: &mut Frame, state: &mut HelpState, theme: &Theme) {
+    let area = frame.area();
+    frame.render_widget(Block::default().style(Style::default().bg(theme.bg_primary)), area);
+    if area.height < 4 {
+        return;
+    }
+
+    let title = Line::from(Span::styled(
+        " Keys",
+        Style::default().fg(theme.accent_primary).add_modifier(Modifier::BOLD),
+    ));
+    frame.render_widget(Paragraph::new(title), Rect::new(area.x, area.y, area.width, 1));
+
+    let groups = help_groups(&state.search);
+    let lines = if groups.is_empty() {
+        vec![Line::from(Span::styled(
+            format!("   No keys match \"{}\"", state.search),
+            Style::default().fg(theme.fg_muted),
+        ))]
+    } else {
+        help_lines(&groups, theme)
+    };
+
+    let body = Rect::new(area.x, area.y + 2, area.width, area.height - 3);
+    state.height = body.height as usize;
+    state.scroll = state.scroll.min(lines.len().saturating_sub(state.height));
+    let shown: Vec<Line> = lines.into_iter().skip(state.scroll).take(state.height).collect();
+    frame.render_widget(Paragraph::new(shown), body);
+
+    let footer = if state.search_editing {
+        Line::from(vec![
+            Span::styled(" /", Style::default().fg(theme.accent_primary)),
+            Span::styled(state.search.clone(), Style::default().fg(theme.fg_primary)),
+            Span::styled("█", Style::default().fg(theme.cursor)),
+        ])
+    } else {
+        let key = |action| key_for(action).unwrap_or_default();
+        let scroll = format!("{}/{} scroll", key(Action::Down), key(Action::Up));
+        let hint = if state.search.is_empty() {
+            format!(" {}  {} search  {} back", scroll, key(Action::Search), key(Action::Back))
+        } else {
+            format!(" /{}  {} clear  {}", state.search, key(Action::Back), scroll)
+        };
+        Line::from(Span::styled(hint, Style::default().fg(theme.fg_muted)))
+    };
+    let footer_area = Rect::new(area.x, area.y + area.height - 1, area.width, 1);
+    frame.render_widget(Paragraph::new(footer), footer_area);
+}
+
+/// Lines of the help screen: each group's title, then its keys in a column
+/// wide enough for the longest
+pub fn help_lines(groups: &[HelpGroup], theme: &Theme) -> Vec<Line<'static>> {
+    let width = groups
+        .iter()
+        .flat_map(|g| g.entries.iter().map(|(keys, _)| keys.width()))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (i, group) in groups.iter().enumerate() {
+        if i > 0 {
+            lines.push(Line::default());
+        }
+        lines.push(Line::from(Span::styled(
+            format!("  {}", group.title),
+            Style::default().fg(theme.fg_secondary).add_modifier(Modifier::BOLD),
+        )));
+        for (keys, what) in &group.entries {
+            let padding = " ".repeat(width.saturating_sub(keys.width()));
+            lines.push(Line::from(vec![
+                Span::styled(format!("    {}{}  ", keys, padding), Style::default().fg(theme.info)),
+                Span::styled(what.to_string(), Style::default().fg(theme.fg_primary)),
+            ]));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn help_lines_align_descriptions() {
+        let groups = vec![
+            HelpGroup {
+                title: "Quiz",
+                entries: vec![("n / p".into(), "next"), ("↓".into(), "down")],
+            },
+            HelpGroup { title: "Notes", entries: vec![("e".into(), "edit")] },
+        ];
+        let text: Vec<String> = help_lines(&groups, &Theme::default())
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(
+            text,
+            ["  Quiz", "    n / p  next", "    ↓      down", "", "  Notes", "    e      edit"]
+        );
+    }
+}
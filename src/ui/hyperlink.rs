@@ -0,0 +1,359 @@
+//! Clickable links via OSC 8 terminal hyperlinks
+//!
+//! ratatui draws cells, not escape sequences, so links are found in the
+//! content panel's visible lines while drawing and written over themselves
+//! afterwards, wrapped in OSC 8, in terminals known to support it.
+
+use std::io::{self, Write};
+use std::ops::Range;
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+use crossterm::cursor::{MoveTo, RestorePosition, SavePosition};
+use crossterm::queue;
+use crossterm::style::{
+    Attribute, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use ratatui::buffer::Buffer;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use regex::Regex;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::book::ContentBlock;
+use crate::theme::Theme;
+
+/// Markdown links, `[text](url)`, and bare URLs
+static LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[([^\]]*)\]\(([^)\s]*)\)|(?:https?://|mailto:)[^\s<>()\[\]]+").unwrap()
+});
+
+/// A link in a block's text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    /// Byte range of the whole link in the text
+    pub range: Range<usize>,
+    /// Text shown for it
+    pub text: String,
+    /// Where it goes
+    pub url: String,
+}
+
+/// Whether a URL leaves the book (rather than pointing inside it)
+pub fn is_external(url: &str) -> bool {
+    ["http://", "https://", "mailto:"].iter().any(|scheme| url.starts_with(scheme))
+}
+
+/// External links in a block's raw text, in order
+pub fn find_links(text: &str) -> Vec<Link> {
+    LINK.captures_iter(text)
+        .filter_map(|caps| {
+            let whole = caps.get(0)?;
+            let (text, url) = match (caps.get(1), caps.get(2)) {
+                (Some(text), Some(url)) => (text.as_str(), url.as_str()),
+                // A sentence ending in a bare URL doesn't end the URL
+                _ => {
+                    let url = whole.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+                    (url, url)
+                }
+            };
+            let end = if caps.get(1).is_some() { whole.end() } else { whole.start() + url.len() };
+            is_external(url).then(|| Link {
+                range: whole.start()..end,
+                text: text.to_string(),
+                url: url.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The link at a byte offset in a block's text, else the next one after it
+pub fn link_at(text: &str, offset: usize) -> Option<Link> {
+    find_links(text).into_iter().find(|link| offset < link.range.end)
+}
+
+/// A link drawn on screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkHit {
+    pub x: u16,
+    pub y: u16,
+    /// Text drawn, and its style
+    pub text: String,
+    pub style: Style,
+    pub url: String,
+}
+
+impl LinkHit {
+    /// The style the buffer shows this link in; None if something was drawn
+    /// over it
+    fn drawn_style(&self, buffer: &Buffer) -> Option<Style> {
+        let mut x = self.x;
+        let mut style = None;
+        for c in self.text.chars() {
+            let cell = buffer.cell((x, self.y)).filter(|cell| cell.symbol().starts_with(c))?;
+            style.get_or_insert(cell.style());
+            x += c.width().unwrap_or(0) as u16;
+        }
+        style
+    }
+}
+
+/// Drop links something was drawn over, and take the others' full style
+/// (background included) from the buffer
+pub fn keep_drawn(hits: &mut Vec<LinkHit>, buffer: &Buffer) {
+    hits.retain_mut(|hit| match hit.drawn_style(buffer) {
+        Some(style) => {
+            hit.style = style;
+            true
+        }
+        None => false,
+    });
+}
+
+/// Links in the content panel's visible lines, placed from `(x, y)`
+///
+/// Wrapping splits a link's text across spans and lines, so each link-styled
+/// span is matched against what remains of its block's links in order.
+pub fn visible_links(
+    lines: &[Line<'_>],
+    first_line: usize,
+    blocks: &[ContentBlock],
+    block_offsets: &[usize],
+    (x, y): (u16, u16),
+    theme: &Theme,
+) -> Vec<LinkHit> {
+    let mut hits = Vec::new();
+    let mut current_block = None;
+    let mut links = Vec::new();
+    // Link being matched, and how much of its text is already on screen
+    let (mut index, mut matched) = (0, 0);
+
+    for (row, line) in lines.iter().enumerate() {
+        let block = block_offsets.partition_point(|&offset| offset <= first_line + row);
+        let Some(block) = block.checked_sub(1) else { continue };
+        if current_block != Some(block) {
+            current_block = Some(block);
+            links = blocks
+                .get(block)
+                .filter(|block| !matches!(block, ContentBlock::Code(_)))
+                .and_then(ContentBlock::plain_text)
+                .map(|text| find_links(&text))
+                .unwrap_or_default();
+            (index, matched) = (0, 0);
+        }
+
+        let mut column = 0;
+        for span in &line.spans {
+            let width = span.content.width() as u16;
+            let word = span.content.trim();
+            let linked = span.style.fg == Some(theme.accent_secondary)
+                && span.style.add_modifier.contains(Modifier::UNDERLINED);
+            // Bare URLs are drawn as plain words, maybe ending a sentence
+            let bare = word.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            let link = if linked {
+                link_of_word(&links, word, &mut index, &mut matched)
+            } else {
+                links.iter().find(|link| link.text == link.url && link.url == bare)
+            };
+            if let Some(link) = link.filter(|_| !word.is_empty()) {
+                let lead = (span.content.len() - span.content.trim_start().len()) as u16;
+                hits.push(LinkHit {
+                    x: x + column + lead,
+                    y: y + row as u16,
+                    text: if linked { word } else { bare }.to_string(),
+                    style: span.style,
+                    url: link.url.clone(),
+                });
+            }
+            column += width;
+        }
+    }
+    hits
+}
+
+/// The link a word of link text belongs to, moving on through `links` as
+/// their text is used up
+fn link_of_word<'a>(
+    links: &'a [Link],
+    word: &str,
+    index: &mut usize,
+    matched: &mut usize,
+) -> Option<&'a Link> {
+    for (i, link) in links.iter().enumerate().skip(*index) {
+        let from = if i == *index { *matched } else { 0 };
+        if let Some(at) = link.text.get(from..).and_then(|rest| rest.find(word)) {
+            *index = i;
+            *matched = from + at + word.len();
+            return Some(link);
+        }
+    }
+    None
+}
+
+/// Whether the terminal understands OSC 8 hyperlinks
+pub fn supported() -> bool {
+    supported_in(&|name| std::env::var(name).ok())
+}
+
+fn supported_in(env: &impl Fn(&str) -> Option<String>) -> bool {
+    // Sequences can't be passed through multiplexers reliably
+    if env("TMUX").is_some() || env("TERM").is_some_and(|term| term.starts_with("screen")) {
+        return false;
+    }
+    let term_program = env("TERM_PROGRAM").unwrap_or_default();
+    ["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper", "rio"]
+        .iter()
+        .any(|program| term_program.eq_ignore_ascii_case(program))
+        || env("TERM").is_some_and(|term| {
+            ["kitty", "foot", "alacritty", "ghostty"].iter().any(|name| term.contains(name))
+        })
+        || env("VTE_VERSION").and_then(|v| v.parse::<u32>().ok()).is_some_and(|v| v >= 5000)
+        || env("WT_SESSION").is_some()
+        || env("KONSOLE_VERSION").is_some()
+}
+
+/// Write the links over themselves as OSC 8 hyperlinks, leaving the cursor
+/// where it was
+pub fn write(out: &mut impl Write, hits: &[LinkHit]) -> io::Result<()> {
+    if hits.is_empty() {
+        return Ok(());
+    }
+    queue!(out, SavePosition)?;
+    for hit in hits {
+        queue!(out, MoveTo(hit.x, hit.y))?;
+        if let Some(fg) = hit.style.fg {
+            queue!(out, SetForegroundColor(fg.into()))?;
+        }
+        if let Some(bg) = hit.style.bg {
+            queue!(out, SetBackgroundColor(bg.into()))?;
+        }
+        for (modifier, attribute) in [
+            (Modifier::BOLD, Attribute::Bold),
+            (Modifier::DIM, Attribute::Dim),
+            (Modifier::ITALIC, Attribute::Italic),
+            (Modifier::UNDERLINED, Attribute::Underlined),
+        ] {
+            if hit.style.add_modifier.contains(modifier) {
+                queue!(out, SetAttribute(attribute))?;
+            }
+        }
+        // The book supplies both, so neither may end the sequence early
+        let linked = format!(
+            "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
+            encode_controls(&hit.url),
+            without_controls(&hit.text)
+        );
+        queue!(out, Print(linked), SetAttribute(Attribute::Reset), ResetColor)?;
+    }
+    queue!(out, RestorePosition)?;
+    out.flush()
+}
+
+/// A URL with its control characters (C0, DEL and C1) percent-encoded
+fn encode_controls(url: &str) -> String {
+    let mut encoded = String::with_capacity(url.len());
+    for c in url.chars() {
+        if c.is_control() {
+            for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            encoded.push(c);
+        }
+    }
+    encoded
+}
+
+/// Text without its control characters
+fn without_controls(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Open a URL in the default browser
+pub fn open(url: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        // Not `cmd /C start`, which would run whatever follows a `&` in the URL
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(url).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::text::Span;
+
+    #[test]
+    fn write_keeps_control_characters_out_of_the_sequence() {
+        let hit = LinkHit {
+            x: 0,
+            y: 0,
+            text: "click\x1b]0;owned\x07 me".into(),
+            style: Style::default(),
+            url: "https://example.com/\x1b]8;;\x07\u{9b}x".into(),
+        };
+        let mut out = Vec::new();
+        write(&mut out, &[hit]).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains(
+            "\x1b]8;;https://example.com/%1B]8;;%07%C2%9Bx\x1b\\click]0;owned me\x1b]8;;\x1b\\"
+        ));
+        assert!(!out.contains('\x07'));
+    }
+
+    #[test]
+    fn find_links_takes_markdown_and_bare_urls() {
+        let text = "See [the docs](https://doc.rust-lang.org), https://crates.io. \
+                    Or [chapter 2](ch02.html).";
+        let links = find_links(text);
+        assert_eq!(links.len(), 2);
+        assert_eq!(
+            (links[0].text.as_str(), links[0].url.as_str()),
+            ("the docs", "https://doc.rust-lang.org")
+        );
+        assert_eq!(links[1].url, "https://crates.io");
+        assert_eq!(&text[links[1].range.clone()], "https://crates.io");
+        assert_eq!(link_at(text, 0).unwrap().text, "the docs");
+        assert_eq!(link_at(text, 45).unwrap().url, "https://crates.io");
+        assert_eq!(link_at(text, 70), None);
+    }
+
+    #[test]
+    fn visible_links_follow_wrapped_link_text() {
+        let theme = Theme::default();
+        let blocks =
+            vec![ContentBlock::Paragraph("Read [the whole book](https://x.dev) now".to_string())];
+        let link = Style::default().fg(theme.accent_secondary).add_modifier(Modifier::UNDERLINED);
+        let lines = vec![
+            Line::from(vec![Span::raw("  Read "), Span::styled("the ", link)]),
+            Line::from(vec![
+                Span::raw("  "),
+                Span::styled("whole ", link),
+                Span::styled("book", link),
+            ]),
+        ];
+        let hits = visible_links(&lines, 0, &blocks, &[0], (10, 5), &theme);
+        let placed: Vec<_> = hits.iter().map(|h| (h.x, h.y, h.text.as_str())).collect();
+        assert_eq!(placed, [(17, 5, "the"), (12, 6, "whole"), (18, 6, "book")]);
+        assert!(hits.iter().all(|h| h.url == "https://x.dev"));
+    }
+
+    #[test]
+    fn multiplexers_turn_hyperlinks_off() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        assert!(supported_in(&env(&[("TERM_PROGRAM", "WezTerm")])));
+        assert!(supported_in(&env(&[("VTE_VERSION", "7600")])));
+        assert!(!supported_in(&env(&[("TERM_PROGRAM", "WezTerm"), ("TMUX", "/tmp/t")])));
+        assert!(!supported_in(&env(&[("TERM", "xterm-256color")])));
+    }
+}
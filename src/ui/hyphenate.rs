@@ -0,0 +1,174 @@
+//! Where words may break across lines
+//!
+//! There's no dictionary: technical identifiers break after their separators
+//! (`::`, `_`, `.`, `/`, `-`) and at camelCase humps, where no hyphen is
+//! added, and ordinary words break between two consonants with vowels on
+//! either side ("com-pilation", "bor-rowing"), always leaving at least
+//! three letters each side. Lines may also break either side of a wide
+//! (CJK) character, since those scripts don't separate words with spaces.
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Letters each side of a syllable break
+const MIN_SYLLABLE_PART: usize = 3;
+
+/// Letter pairs that sound as one and aren't split
+const DIGRAPHS: [&str; 8] = ["ch", "ck", "gh", "ng", "ph", "sh", "th", "wh"];
+
+/// A place a word may break
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Break {
+    /// Character index of the second part
+    pub at: usize,
+    /// Whether a hyphen ends the first part
+    pub hyphen: bool,
+}
+
+/// Places `word` may break, in order
+pub fn breaks(word: &str) -> Vec<Break> {
+    let chars: Vec<char> = word.chars().collect();
+    (1..chars.len())
+        .filter_map(|at| {
+            let (prev, next) = (chars[at - 1], chars[at]);
+            let identifier = matches!(prev, '_' | '.' | '/' | '-')
+                || (prev == ':' && at >= 2 && chars[at - 2] == ':')
+                || (prev.is_lowercase() && next.is_uppercase());
+            if identifier && next.is_alphanumeric() {
+                return Some(Break { at, hyphen: false });
+            }
+            syllable_break(&chars, at).then_some(Break { at, hyphen: true })
+        })
+        .collect()
+}
+
+/// `text` split into the pieces wrapping keeps together: words with their
+/// trailing whitespace, and each wide character on its own
+pub fn words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    for (i, c) in text.char_indices() {
+        if let Some(prev) = prev {
+            if !c.is_whitespace() && (prev.is_whitespace() || is_wide(prev) || is_wide(c)) {
+                words.push(&text[start..i]);
+                start = i;
+            }
+        }
+        prev = Some(c);
+    }
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+    words
+}
+
+/// Whether a character takes two columns
+pub fn is_wide(c: char) -> bool {
+    c.width().is_some_and(|width| width > 1)
+}
+
+/// Split `word` so its first part, with any hyphen, fits in `room` columns
+///
+/// Trailing whitespace stays with the second part. With `force`, a word
+/// with no break that fits is cut at `room` (for words wider than a line).
+/// Returns None if the word can't or needn't be split.
+pub fn split(word: &str, room: usize, force: bool) -> Option<(String, String)> {
+    let body = word.trim_end();
+    if body.width() <= room {
+        return None;
+    }
+    // Columns taken by the first n characters
+    let columns: Vec<usize> = std::iter::once(0)
+        .chain(body.chars().scan(0, |columns, c| {
+            *columns += c.width().unwrap_or(0);
+            Some(*columns)
+        }))
+        .collect();
+    let chosen = breaks(body)
+        .into_iter()
+        .rfind(|b| columns[b.at] + usize::from(b.hyphen) <= room)
+        .or_else(|| {
+            let at = columns.iter().rposition(|&width| width <= room)?;
+            (force && at > 0).then_some(Break { at, hyphen: false })
+        })?;
+
+    let head: String = word.chars().take(chosen.at).collect();
+    let tail: String = word.chars().skip(chosen.at).collect();
+    Some((if chosen.hyphen { head + "-" } else { head }, tail))
+}
+
+/// Whether a word may break between syllables before `chars[at]`
+fn syllable_break(chars: &[char], at: usize) -> bool {
+    let is_vowel = |c: char| matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let is_consonant = |c: char| c.is_alphabetic() && !is_vowel(c);
+
+    let (prev, next) = (chars[at - 1], chars[at]);
+    if !is_consonant(prev) || !is_consonant(next) {
+        return false;
+    }
+    let pair: String = [prev, next].iter().flat_map(|c| c.to_lowercase()).collect();
+    if DIGRAPHS.contains(&pair.as_str()) {
+        return false;
+    }
+
+    // Whole letters each side, each part with a vowel
+    let head = chars[..at].iter().rev().take_while(|c| c.is_alphabetic());
+    let tail = chars[at..].iter().take_while(|c| c.is_alphabetic());
+    let part_ok =
+        |part: Vec<char>| part.len() >= MIN_SYLLABLE_PART && part.iter().any(|&c| is_vowel(c));
+    part_ok(head.copied().collect()) && part_ok(tail.copied().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(word: &str) -> Vec<String> {
+        let mut parts = Vec::new();
+        let mut last = 0;
+        let chars: Vec<char> = word.chars().collect();
+        for b in breaks(word) {
+            parts.push(
+                chars[last..b.at].iter().collect::<String>() + if b.hyphen { "-" } else { "" },
+            );
+            last = b.at;
+        }
+        parts.push(chars[last..].iter().collect());
+        parts
+    }
+
+    #[test]
+    fn breaks_identifiers_at_separators() {
+        assert_eq!(parts("std::HashMap"), ["std::", "Hash", "Map"]);
+        assert_eq!(parts("MAX_RETRY_COUNT"), ["MAX_", "RETRY_", "COUNT"]);
+        assert_eq!(parts("src/ui/content.rs"), ["src/", "ui/", "con-", "tent.", "rs"]);
+    }
+
+    #[test]
+    fn breaks_words_between_consonants() {
+        assert_eq!(parts("compilation"), ["com-", "pilation"]);
+        assert_eq!(parts("collections"), ["col-", "lec-", "tions"]);
+        assert_eq!(parts("borrowing"), ["bor-", "rowing"]);
+        assert_eq!(parts("nothing"), ["nothing"]);
+        assert_eq!(parts("lifetimes,"), ["lifetimes,"]);
+    }
+
+    #[test]
+    fn split_fits_room_or_forces() {
+        assert_eq!(
+            split("HashMap::new() ", 10, false),
+            Some(("HashMap::".into(), "new() ".into()))
+        );
+        assert_eq!(split("borrowing ", 5, false), Some(("bor-".into(), "rowing ".into())));
+        assert_eq!(split("borrowing ", 3, false), None);
+        assert_eq!(split("xxxxxxxx", 3, true), Some(("xxx".into(), "xxxxx".into())));
+        assert_eq!(split("fits ", 4, true), None);
+        assert_eq!(split("漢字漢字", 5, true), Some(("漢字".into(), "漢字".into())));
+    }
+
+    #[test]
+    fn words_keep_wide_characters_apart() {
+        assert_eq!(words("Rust 是一门 language"), ["Rust ", "是", "一", "门 ", "language"]);
+        assert_eq!(words("  two  spaces"), ["  ", "two  ", "spaces"]);
+    }
+}
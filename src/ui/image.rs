@@ -4,32 +4,97 @@
 //! - Sixel (iTerm2, mlterm, foot, xterm)
 //! - Kitty graphics protocol
 //! - iTerm2 inline images
-//! - Unicode half-blocks (fallback)
+//!
+//! Terminals without any of these get a text rendering instead: colored
+//! Unicode half-blocks, or plain ASCII art (see `ImageFallback`).
 //!
 //! SVG images are rendered using resvg.
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
 
-use image::{DynamicImage, RgbaImage};
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgba, RgbaImage};
 use ratatui::Frame;
+use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
 use ratatui_image::StatefulImage;
 use ratatui_image::picker::{Picker, ProtocolType};
 use ratatui_image::protocol::StatefulProtocol;
 
+use crate::config::ImageFallback;
+
+/// Characters for ASCII art, from no ink to full ink
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Pixels with less alpha than this are drawn as background
+const OPAQUE_ALPHA: u8 = 128;
+
 /// Cache for loaded and encoded images
+///
+/// Images are decoded and downscaled to the width they are drawn at on a
+/// background thread, so scrolling onto a large image doesn't stall a frame;
+/// a placeholder is drawn until it is ready.
 pub struct ImageCache {
     /// The picker for creating protocol instances
     picker: Picker,
-    /// Map from image path to loaded image data
-    images: HashMap<PathBuf, DynamicImage>,
-    /// Map from (path, height) to protocol state for cropped renders
-    protocols: HashMap<(PathBuf, u16), StatefulProtocol>,
+    /// Decoding thread, started when the first image is needed
+    decoder: Option<Decoder>,
+    /// Map from (path, columns) to the image downscaled to that width
+    images: HashMap<(PathBuf, u16), Decoded>,
+    /// Map from image path to its size in pixels
+    dimensions: HashMap<PathBuf, (u32, u32)>,
+    /// Map from (path, columns, height) to protocol state for cropped renders
+    protocols: HashMap<(PathBuf, u16, u16), StatefulProtocol>,
+    /// Map from (path, columns, rows) to images downscaled for text rendering
+    thumbnails: HashMap<(PathBuf, u16, u16), RgbaImage>,
+    /// Text rendering used when there is no graphics protocol
+    fallback: ImageFallback,
     /// Base path for resolving relative image paths
     base_path: Option<PathBuf>,
 }
 
+/// An image being decoded on the background thread, or the result
+enum Decoded {
+    Pending,
+    Ready(DynamicImage),
+    Failed,
+}
+
+/// An image to decode, and the width in pixels to downscale it to
+struct DecodeJob {
+    path: PathBuf,
+    columns: u16,
+    max_width: u32,
+}
+
+/// A decoded image: its original size and its downscaled copy
+type DecodeResult = (PathBuf, u16, Option<((u32, u32), DynamicImage)>);
+
+/// Channels to the background decoding thread
+struct Decoder {
+    jobs: Sender<DecodeJob>,
+    results: Receiver<DecodeResult>,
+}
+
+impl Decoder {
+    fn start() -> Self {
+        let (jobs, queue) = mpsc::channel::<DecodeJob>();
+        let (done, results) = mpsc::channel();
+        std::thread::spawn(move || {
+            for job in queue {
+                let image = decode(&job.path, job.max_width);
+                if done.send((job.path, job.columns, image)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { jobs, results }
+    }
+}
+
 impl ImageCache {
     /// Create a new image cache, detecting terminal capabilities
     pub fn new() -> Self {
@@ -54,25 +119,42 @@ impl ImageCache {
             picker.set_protocol_type(ProtocolType::Kitty);
         }
 
-        Self { picker, images: HashMap::new(), protocols: HashMap::new(), base_path: None }
+        Self::with_picker(picker)
     }
 
     /// Create a new image cache with halfblocks only (safe fallback)
     pub fn new_halfblocks() -> Self {
+        Self::with_picker(Picker::from_fontsize((8, 16)))
+    }
+
+    fn with_picker(picker: Picker) -> Self {
         Self {
-            picker: Picker::from_fontsize((8, 16)),
+            picker,
+            decoder: None,
             images: HashMap::new(),
+            dimensions: HashMap::new(),
             protocols: HashMap::new(),
+            thumbnails: HashMap::new(),
+            fallback: ImageFallback::default(),
             base_path: None,
         }
     }
 
+    /// Set how images are drawn when the terminal has no graphics protocol
+    pub fn set_fallback(&mut self, fallback: ImageFallback) {
+        self.fallback = fallback;
+    }
+
+    /// Whether images are drawn as text because no graphics protocol is available
+    fn uses_text_art(&self) -> bool {
+        self.picker.protocol_type() == ProtocolType::Halfblocks
+    }
+
     /// Set the base path for resolving relative image paths
     pub fn set_base_path(&mut self, path: PathBuf) {
         // If base path changed, clear cache
         if self.base_path.as_ref() != Some(&path) {
-            self.images.clear();
-            self.protocols.clear();
+            self.clear();
             self.base_path = Some(path);
         }
     }
@@ -82,17 +164,34 @@ impl ImageCache {
         self.base_path.as_deref()
     }
 
+    /// Graphics protocol used to draw images
+    pub fn protocol_type(&self) -> ProtocolType {
+        self.picker.protocol_type()
+    }
+
     /// Get the font size (pixels per cell)
     pub fn font_size(&self) -> (u16, u16) {
         self.picker.font_size()
     }
 
+    /// Whether images are still being decoded in the background
+    pub fn is_decoding(&self) -> bool {
+        self.images.values().any(|image| matches!(image, Decoded::Pending))
+    }
+
     /// Calculate the recommended row height for an image given available width in columns
     /// Returns the number of terminal rows needed to display the image at proper aspect ratio
+    ///
+    /// Raster sizes are read from the file header; an SVG's is known once it
+    /// has been decoded, until then this is None.
     pub fn recommended_rows(&mut self, src: &str, available_cols: u16) -> Option<usize> {
-        // Load the image to get dimensions
-        let path = self.load_image(src)?;
-        let img = self.images.get(&path)?;
+        self.receive();
+        let path = self.resolve_path(src)?;
+        if !self.dimensions.contains_key(&path) && !is_svg(&path) {
+            let dimensions = image::image_dimensions(&path).ok()?;
+            self.dimensions.insert(path.clone(), dimensions);
+        }
+        let &(width, height) = self.dimensions.get(&path)?;
 
         let (font_width, font_height) = self.picker.font_size();
 
@@ -100,7 +199,7 @@ impl ImageCache {
         let available_width_px = available_cols as u32 * font_width as u32;
 
         // Calculate height needed to maintain aspect ratio
-        let img_aspect = img.height() as f32 / img.width() as f32;
+        let img_aspect = height as f32 / width as f32;
         let needed_height_px = (available_width_px as f32 * img_aspect) as u32;
 
         // Convert to rows, with min/max bounds
@@ -141,36 +240,60 @@ impl ImageCache {
         None
     }
 
-    /// Load an image, returning the path key
-    fn load_image(&mut self, src: &str) -> Option<PathBuf> {
-        let path = self.resolve_path(src)?;
-
-        // Check if already cached
-        if self.images.contains_key(&path) {
-            return Some(path);
+    /// Take decoded images from the background thread
+    fn receive(&mut self) {
+        let Some(decoder) = &self.decoder else { return };
+        for (path, columns, image) in decoder.results.try_iter() {
+            // Dropped by clear() while decoding
+            let Some(slot) = self.images.get_mut(&(path.clone(), columns)) else { continue };
+            *slot = match image {
+                Some((dimensions, image)) => {
+                    self.dimensions.insert(path, dimensions);
+                    Decoded::Ready(image)
+                }
+                None => Decoded::Failed,
+            };
         }
+    }
 
-        // Load the image (handle SVG specially)
-        let img = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("svg")) {
-            match load_svg(&path) {
-                Ok(img) => img,
-                Err(e) => {
-                    tracing::warn!("Failed to load SVG {}: {}", path.display(), e);
-                    return None;
-                }
-            }
-        } else {
-            match image::open(&path) {
-                Ok(img) => img,
-                Err(e) => {
-                    tracing::warn!("Failed to load image {}: {}", path.display(), e);
+    /// Load an image downscaled to `columns`, returning its cache key once it
+    /// is decoded; until then a placeholder is drawn in `area`
+    ///
+    /// The outer None means the image can't be drawn at all.
+    fn load_image(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        src: &str,
+        columns: u16,
+    ) -> Option<Option<(PathBuf, u16)>> {
+        self.receive();
+        let path = self.resolve_path(src)?;
+        let key = (path, columns);
+        match self.images.get(&key) {
+            Some(Decoded::Ready(_)) => return Some(Some(key)),
+            Some(Decoded::Failed) => return None,
+            Some(Decoded::Pending) => {}
+            None => {
+                let max_width = u32::from(columns) * u32::from(self.picker.font_size().0);
+                let job = DecodeJob { path: key.0.clone(), columns, max_width };
+                let decoder = self.decoder.get_or_insert_with(Decoder::start);
+                if decoder.jobs.send(job).is_err() {
                     return None;
                 }
+                self.images.insert(key, Decoded::Pending);
             }
-        };
+        }
+        draw_placeholder(frame.buffer_mut(), area);
+        Some(None)
+    }
 
-        self.images.insert(path.clone(), img);
-        Some(path)
+    /// A decoded image
+    fn image(&self, key: &(PathBuf, u16)) -> Option<&DynamicImage> {
+        match self.images.get(key)? {
+            Decoded::Ready(image) => Some(image),
+            _ => None,
+        }
     }
 
     /// Render an image to the frame with proper clipping
@@ -185,15 +308,22 @@ impl ImageCache {
         full_height: u16,
     ) -> bool {
         // Try to load/get cached image
-        let Some(path) = self.load_image(src) else {
+        let Some(loaded) = self.load_image(frame, area, src, area.width) else {
             return false;
         };
+        let Some(key) = loaded else {
+            return true;
+        };
+
+        if self.uses_text_art() {
+            return self.render_text_art(frame.buffer_mut(), area, key, full_height, 0);
+        }
 
         // If fully visible, use full-size cached protocol
         if area.height >= full_height {
-            let cache_key = (path.clone(), full_height);
+            let cache_key = (key.0.clone(), key.1, full_height);
             if !self.protocols.contains_key(&cache_key) {
-                let Some(img) = self.images.get(&path) else {
+                let Some(img) = self.image(&key) else {
                     return false;
                 };
                 let protocol = self.picker.new_resize_protocol(img.clone());
@@ -209,10 +339,10 @@ impl ImageCache {
 
         // Partially visible - need to crop. Quantize to reduce cache entries.
         let quantized = (area.height / 3) * 3 + 3; // Round up to nearest 3
-        let cache_key = (path.clone(), quantized);
+        let cache_key = (key.0.clone(), key.1, quantized);
 
         if !self.protocols.contains_key(&cache_key) {
-            let Some(img) = self.images.get(&path) else {
+            let Some(img) = self.image(&key) else {
                 return false;
             };
 
@@ -252,23 +382,31 @@ impl ImageCache {
         src: &str,
         full_height: u16,
     ) -> bool {
+        // If mostly visible, just use the full image
+        if area.height >= full_height {
+            return self.render_cropped(frame, area, src, full_height);
+        }
+
         // Try to load/get cached image
-        let Some(path) = self.load_image(src) else {
+        let Some(loaded) = self.load_image(frame, area, src, area.width) else {
             return false;
         };
+        let Some(key) = loaded else {
+            return true;
+        };
 
-        // If mostly visible, just use the full image
-        if area.height >= full_height {
-            return self.render_cropped(frame, area, src, full_height);
+        if self.uses_text_art() {
+            let skip_rows = full_height - area.height;
+            return self.render_text_art(frame.buffer_mut(), area, key, full_height, skip_rows);
         }
 
         // Crop from bottom of source image. Quantize to reduce cache entries.
         // Use negative values in cache key to distinguish from top crops
         let quantized = (area.height / 3) * 3 + 3;
-        let cache_key = (path.clone(), quantized.wrapping_neg());
+        let cache_key = (key.0.clone(), key.1, quantized.wrapping_neg());
 
         if !self.protocols.contains_key(&cache_key) {
-            let Some(img) = self.images.get(&path) else {
+            let Some(img) = self.image(&key) else {
                 return false;
             };
 
@@ -293,6 +431,30 @@ impl ImageCache {
         true
     }
 
+    /// Draw an image as text, skipping `skip_rows` rows of its full-height rendering
+    fn render_text_art(
+        &mut self,
+        buf: &mut Buffer,
+        area: Rect,
+        key: (PathBuf, u16),
+        full_height: u16,
+        skip_rows: u16,
+    ) -> bool {
+        let thumb_key = (key.0.clone(), area.width, full_height);
+        if !self.thumbnails.contains_key(&thumb_key) {
+            let Some(img) = self.image(&key) else {
+                return false;
+            };
+            self.thumbnails.insert(thumb_key.clone(), thumbnail(img, area.width, full_height));
+        }
+        let Some(thumb) = self.thumbnails.get(&thumb_key) else {
+            return false;
+        };
+
+        draw_text_art(buf, area, thumb, skip_rows, self.fallback);
+        true
+    }
+
     /// Check if an image can be rendered (exists and is loadable)
     pub fn can_render(&self, src: &str) -> bool {
         self.resolve_path(src).is_some()
@@ -301,7 +463,9 @@ impl ImageCache {
     /// Clear the image cache
     pub fn clear(&mut self) {
         self.images.clear();
+        self.dimensions.clear();
         self.protocols.clear();
+        self.thumbnails.clear();
     }
 
     /// Get the number of cached images
@@ -322,6 +486,78 @@ impl Default for ImageCache {
     }
 }
 
+/// Downscale an image to fit `cols` x `rows` cells at two pixels per cell vertically
+fn thumbnail(img: &DynamicImage, cols: u16, rows: u16) -> RgbaImage {
+    let (width, height) = (u32::from(cols).max(1), u32::from(rows).max(1) * 2);
+    img.resize(width, height, FilterType::Triangle).to_rgba8()
+}
+
+/// Draw a thumbnail into `area` as half-blocks or ASCII art, centered horizontally
+///
+/// Each cell covers two thumbnail rows; the first `skip_rows` cells' worth are
+/// skipped so the bottom of a partly scrolled-off image can be shown.
+/// Transparent pixels are left as the terminal background.
+pub fn draw_text_art(
+    buf: &mut Buffer,
+    area: Rect,
+    thumb: &RgbaImage,
+    skip_rows: u16,
+    fallback: ImageFallback,
+) {
+    let width = (thumb.width() as u16).min(area.width);
+    let x_offset = (area.width - width) / 2;
+    let pixel = |x: u32, y: u32| thumb.get_pixel_checked(x, y).copied().unwrap_or(Rgba([0; 4]));
+
+    for row in 0..area.height {
+        let y = u32::from(row + skip_rows) * 2;
+        if y >= thumb.height() {
+            break;
+        }
+        for col in 0..width {
+            let (top, bottom) = (pixel(u32::from(col), y), pixel(u32::from(col), y + 1));
+            let Some(cell) = buf.cell_mut((area.x + x_offset + col, area.y + row)) else {
+                continue;
+            };
+            match fallback {
+                ImageFallback::Halfblocks => {
+                    match (top[3] >= OPAQUE_ALPHA, bottom[3] >= OPAQUE_ALPHA) {
+                        (true, true) => {
+                            cell.set_char('▀').set_fg(rgb(top)).set_bg(rgb(bottom));
+                        }
+                        (true, false) => {
+                            cell.set_char('▀').set_fg(rgb(top));
+                        }
+                        (false, true) => {
+                            cell.set_char('▄').set_fg(rgb(bottom));
+                        }
+                        (false, false) => {}
+                    }
+                }
+                ImageFallback::Ascii => {
+                    let ink = (ink(top) + ink(bottom)) / 2.0;
+                    let index = (ink * (ASCII_RAMP.len() - 1) as f32).round() as usize;
+                    cell.set_char(ASCII_RAMP[index.min(ASCII_RAMP.len() - 1)] as char);
+                }
+            }
+        }
+    }
+}
+
+/// Terminal color of a pixel
+fn rgb(pixel: Rgba<u8>) -> Color {
+    Color::Rgb(pixel[0], pixel[1], pixel[2])
+}
+
+/// How much ink a pixel puts down, from 0.0 (transparent or white) to 1.0 (black)
+///
+/// Dark strokes on a light or transparent background, as in most diagrams,
+/// come out as dense characters on any terminal theme.
+fn ink(pixel: Rgba<u8>) -> f32 {
+    let luma =
+        0.299 * f32::from(pixel[0]) + 0.587 * f32::from(pixel[1]) + 0.114 * f32::from(pixel[2]);
+    (1.0 - luma / 255.0) * f32::from(pixel[3]) / 255.0
+}
+
 /// Check if the terminal likely supports graphics protocols
 pub fn terminal_supports_graphics() -> bool {
     // Check common environment variables
@@ -344,6 +580,41 @@ pub fn is_ghostty() -> bool {
         || std::env::var("GHOSTTY_RESOURCES_DIR").is_ok()
 }
 
+/// Draw a dim "loading" note centered in the area an image will take
+fn draw_placeholder(buf: &mut Buffer, area: Rect) {
+    let label = "Loading image…";
+    let width = label.chars().count() as u16;
+    if area.height == 0 || area.width < width {
+        return;
+    }
+    let x = area.x + (area.width - width) / 2;
+    let y = area.y + area.height / 2;
+    buf.set_string(x, y, label, Style::default().add_modifier(Modifier::DIM));
+}
+
+fn is_svg(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+}
+
+/// Decode an image, downscaled to at most `max_width` pixels wide; returns
+/// its size before downscaling too
+fn decode(path: &Path, max_width: u32) -> Option<((u32, u32), DynamicImage)> {
+    // Load the image (handle SVG specially)
+    let img = if is_svg(path) {
+        load_svg(path).map_err(|e| tracing::warn!("Failed to load SVG {}: {}", path.display(), e))
+    } else {
+        image::open(path)
+            .map_err(|e| tracing::warn!("Failed to load image {}: {}", path.display(), e))
+    }
+    .ok()?;
+
+    let dimensions = (img.width(), img.height());
+    if img.width() <= max_width.max(1) {
+        return Some((dimensions, img));
+    }
+    Some((dimensions, img.resize(max_width.max(1), u32::MAX, FilterType::Triangle)))
+}
+
 /// Load an SVG file and render it to a raster image
 fn load_svg(path: &Path) -> Result<DynamicImage, String> {
     // Read the SVG file
@@ -435,4 +706,88 @@ mod tests {
         let cache = ImageCache::default();
         assert!(!cache.can_render("/nonexistent/image.png"));
     }
+
+    #[test]
+    fn decode_downscales_to_the_drawn_width() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wide.png");
+        RgbaImage::from_pixel(400, 200, Rgba([0, 0, 0, 255])).save(&path).unwrap();
+
+        let ((width, height), img) = decode(&path, 100).unwrap();
+        assert_eq!((width, height), (400, 200));
+        assert_eq!((img.width(), img.height()), (100, 50));
+        assert_eq!(decode(&path, 800).unwrap().1.width(), 400);
+        assert!(decode(&dir.path().join("missing.png"), 100).is_none());
+    }
+
+    #[test]
+    fn images_decode_in_the_background() {
+        let dir = tempfile::tempdir().unwrap();
+        RgbaImage::from_pixel(64, 32, Rgba([0, 0, 0, 255])).save(dir.path().join("a.png")).unwrap();
+        let mut cache = ImageCache::default();
+        cache.set_base_path(dir.path().to_path_buf());
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(20, 8)).unwrap();
+        let mut draw = |cache: &mut ImageCache| {
+            let mut drawn = false;
+            terminal
+                .draw(|frame| drawn = cache.render_cropped(frame, frame.area(), "a.png", 8))
+                .unwrap();
+            drawn
+        };
+
+        assert!(draw(&mut cache));
+        assert!(cache.is_decoding());
+        let start = std::time::Instant::now();
+        while cache.is_decoding() && start.elapsed() < std::time::Duration::from_secs(5) {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            cache.receive();
+        }
+        assert!(!cache.is_decoding());
+        assert_eq!(cache.recommended_rows("a.png", 20), Some(8));
+        assert!(draw(&mut cache));
+        assert_eq!(cache.thumbnails.len(), 1);
+    }
+
+    #[test]
+    fn draw_text_art_uses_half_blocks_and_keeps_transparency() {
+        let black = Rgba([0, 0, 0, 255]);
+        let white = Rgba([255, 255, 255, 255]);
+        let clear = Rgba([0, 0, 0, 0]);
+        let thumb = RgbaImage::from_fn(2, 4, |x, y| match (x, y) {
+            (0, 0) | (0, 2) => black,
+            (0, _) => white,
+            (1, 3) => black,
+            _ => clear,
+        });
+        let area = Rect::new(0, 0, 4, 2);
+        let mut buf = Buffer::empty(area);
+
+        draw_text_art(&mut buf, area, &thumb, 0, ImageFallback::Halfblocks);
+
+        // Centered: 2 pixel columns in 4 cells
+        let cell = &buf[(1, 0)];
+        assert_eq!(cell.symbol(), "▀");
+        assert_eq!((cell.fg, cell.bg), (Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255)));
+        assert_eq!(buf[(2, 0)].symbol(), " ");
+        assert_eq!(buf[(2, 1)].symbol(), "▄");
+        assert_eq!(buf[(0, 0)].symbol(), " ");
+    }
+
+    #[test]
+    fn draw_text_art_ascii_maps_ink_and_skips_rows() {
+        let thumb = RgbaImage::from_fn(3, 4, |x, y| match (x, y) {
+            (0, 2..) => Rgba([0, 0, 0, 255]),
+            (1, 2) => Rgba([0, 0, 0, 255]),
+            _ => Rgba([255, 255, 255, 255]),
+        });
+        let area = Rect::new(0, 0, 3, 2);
+        let mut buf = Buffer::empty(area);
+
+        draw_text_art(&mut buf, area, &thumb, 1, ImageFallback::Ascii);
+
+        let row: String = (0..3).map(|x| buf[(x, 0)].symbol().to_string()).collect();
+        assert_eq!(row, "@+ ");
+        assert_eq!(buf[(0, 1)].symbol(), " ");
+    }
 }
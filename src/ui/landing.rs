@@ -7,6 +7,7 @@ use ratatui::{
     widgets::Paragraph,
 };
 
+use super::locale::{self, Text};
 use crate::app::state::LandingAnimation;
 use crate::theme::Theme;
 
@@ -89,8 +90,6 @@ const ENSO_ROWS: usize = 10;
 const ENSO_COLS: usize = 24;
 
 const TITLE: &str = "SENSEI";
-const TAGLINE: &str = "Your AI-powered guide to mastering technical books";
-const PROMPT: &str = "Press any key to begin...";
 
 /// Build the ensō string based on animation progress
 fn build_enso(progress: f32) -> String {
@@ -112,7 +111,9 @@ fn build_enso(progress: f32) -> String {
 }
 
 /// Draw the landing screen with ensō animation
-pub fn draw(frame: &mut Frame, animation: &LandingAnimation, theme: &Theme) {
+///
+/// `next_up` is the next section of the reader's study plans, if any.
+pub fn draw(frame: &mut Frame, animation: &LandingAnimation, next_up: Option<&str>, theme: &Theme) {
     let area = frame.area();
 
     // Fill background
@@ -170,9 +171,21 @@ pub fn draw(frame: &mut Frame, animation: &LandingAnimation, theme: &Theme) {
                 width: area.width,
                 height: 1.min(area.height.saturating_sub(tagline_y)),
             };
-            let tagline = Paragraph::new(TAGLINE).style(tagline_style).alignment(Alignment::Center);
+            let tagline = Paragraph::new(locale::text(Text::Tagline))
+                .style(tagline_style)
+                .alignment(Alignment::Center);
             frame.render_widget(tagline, tagline_area);
         }
+
+        let next_y = tagline_y + 2;
+        if let Some(next_up) = next_up.filter(|_| next_y < area.height) {
+            let next_style = Style::default().fg(theme.accent_secondary).bg(theme.bg_primary);
+            let next_area = Rect { x: area.x, y: next_y, width: area.width, height: 1 };
+            let next = Paragraph::new(format!("{} {}", locale::text(Text::NextUp), next_up))
+                .style(next_style)
+                .alignment(Alignment::Center);
+            frame.render_widget(next, next_area);
+        }
     }
 
     // Blinking prompt
@@ -189,8 +202,9 @@ pub fn draw(frame: &mut Frame, animation: &LandingAnimation, theme: &Theme) {
                     width: area.width,
                     height: 1.min(area.height.saturating_sub(prompt_y)),
                 };
-                let prompt =
-                    Paragraph::new(PROMPT).style(prompt_style).alignment(Alignment::Center);
+                let prompt = Paragraph::new(locale::text(Text::PressAnyKey))
+                    .style(prompt_style)
+                    .alignment(Alignment::Center);
                 frame.render_widget(prompt, prompt_area);
             }
         }
@@ -0,0 +1,186 @@
+//! Interface text in the reader's language
+//!
+//! Fixed interface text (panel titles, key help, placeholders) is looked up
+//! by key in the bundle for the interface language. Each bundle is a match
+//! over every key, so text can't be added without translating it. The
+//! language comes from `ui_language`, else the locale in `LC_ALL`,
+//! `LC_MESSAGES` or `LANG`, falling back to English.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Interface language (index into `Language::ALL`)
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+/// A language the interface is translated into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Esperanto,
+}
+
+impl Language {
+    /// Every language, in the order `:set uilang` lists them
+    pub const ALL: [Language; 2] = [Language::English, Language::Esperanto];
+
+    /// Two-letter code, as in `ui_language`
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::Esperanto => "eo",
+        }
+    }
+
+    /// Language for a code or locale such as "eo" or "en_GB.UTF-8"
+    pub fn from_code(code: &str) -> Option<Self> {
+        let name = code.trim().split(['.', '@', '_', '-']).next().unwrap_or_default();
+        Self::ALL.into_iter().find(|language| name.eq_ignore_ascii_case(language.code()))
+    }
+
+    /// The configured language, else the one the environment's locale names
+    pub fn resolve(configured: Option<&str>) -> Self {
+        configured
+            .and_then(Self::from_code)
+            .or_else(|| {
+                ["LC_ALL", "LC_MESSAGES", "LANG"]
+                    .iter()
+                    .filter_map(|var| std::env::var(var).ok())
+                    .find(|value| !value.is_empty())
+                    .and_then(|locale| Self::from_code(&locale))
+            })
+            .unwrap_or(Self::English)
+    }
+}
+
+/// Change the interface language
+pub fn set_language(language: Language) {
+    let index = Language::ALL.iter().position(|&l| l == language).unwrap_or_default();
+    LANGUAGE.store(index as u8, Ordering::Relaxed);
+}
+
+/// The interface language
+pub fn language() -> Language {
+    Language::ALL
+        .get(LANGUAGE.load(Ordering::Relaxed) as usize)
+        .copied()
+        .unwrap_or(Language::English)
+}
+
+/// A piece of interface text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    Curriculum,
+    Content,
+    Notes,
+    Highlights,
+    ClaudeResponse,
+    ClaudeStreaming,
+    StudyBuddy,
+    Quiz,
+    QuizGenerating,
+    QuizReview,
+    QuizResults,
+    QuizError,
+    QuestionBank,
+    /// Key help under a response
+    ResponseHelp,
+    /// Key help under a response with citations
+    CitedResponseHelp,
+    NoResponseYet,
+    NoBookLoaded,
+    Tagline,
+    PressAnyKey,
+    /// Before the next section of a study plan on the landing screen
+    NextUp,
+    /// Before the title of a book loading in the background
+    Loading,
+}
+
+/// `text` in the interface language
+pub fn text(text: Text) -> &'static str {
+    match language() {
+        Language::English => english(text),
+        Language::Esperanto => esperanto(text),
+    }
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::Curriculum => "Curriculum",
+        Text::Content => "Content",
+        Text::Notes => "Notes",
+        Text::Highlights => "Highlights",
+        Text::ClaudeResponse => "Claude Response",
+        Text::ClaudeStreaming => "Claude (streaming...)",
+        Text::StudyBuddy => "Study Buddy",
+        Text::Quiz => "Quiz",
+        Text::QuizGenerating => "Generating Quiz...",
+        Text::QuizReview => "Quiz Review",
+        Text::QuizResults => "Quiz Results",
+        Text::QuizError => "Quiz Error",
+        Text::QuestionBank => "Question Bank",
+        Text::ResponseHelp => {
+            "[y] copy  [s] save note  [a] add to note  [c] toggle  [j/k] scroll  [Esc] close"
+        }
+        Text::CitedResponseHelp => {
+            "[Tab] citation  [Enter] go to passage  [y] copy  [s] save note  [a] add to note  \
+             [c] toggle  [Esc] close"
+        }
+        Text::NoResponseYet => "No response yet...",
+        Text::NoBookLoaded => "No book loaded\n\nAdd a book with:\nsensei add <path>",
+        Text::Tagline => "Your AI-powered guide to mastering technical books",
+        Text::PressAnyKey => "Press any key to begin...",
+        Text::NextUp => "Next up:",
+        Text::Loading => "Loading",
+    }
+}
+
+fn esperanto(text: Text) -> &'static str {
+    match text {
+        Text::Curriculum => "Instruplano",
+        Text::Content => "Enhavo",
+        Text::Notes => "Notoj",
+        Text::Highlights => "Markaĵoj",
+        Text::ClaudeResponse => "Respondo de Claude",
+        Text::ClaudeStreaming => "Claude (fluas...)",
+        Text::StudyBuddy => "Studkamarado",
+        Text::Quiz => "Kvizo",
+        Text::QuizGenerating => "Kreante kvizon...",
+        Text::QuizReview => "Revizio de kvizo",
+        Text::QuizResults => "Rezultoj de kvizo",
+        Text::QuizError => "Eraro de kvizo",
+        Text::QuestionBank => "Demandaro",
+        Text::ResponseHelp => {
+            "[y] kopii  [s] konservi noton  [a] aldoni al noto  [c] baskuligi  [j/k] rulumi  \
+             [Esc] fermi"
+        }
+        Text::CitedResponseHelp => {
+            "[Tab] citaĵo  [Enter] al la fonto  [y] kopii  [s] konservi noton  \
+             [a] aldoni al noto  [c] baskuligi  [Esc] fermi"
+        }
+        Text::NoResponseYet => "Ankoraŭ neniu respondo...",
+        Text::NoBookLoaded => "Neniu libro ŝargita\n\nAldonu libron per:\nsensei add <vojo>",
+        Text::Tagline => "Via AI-gvidanto por majstri teknikajn librojn",
+        Text::PressAnyKey => "Premu ajnan klavon por komenci...",
+        Text::NextUp => "Sekva:",
+        Text::Loading => "Ŝargante",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages_from_codes_and_locales() {
+        assert_eq!(Language::from_code("eo"), Some(Language::Esperanto));
+        assert_eq!(Language::from_code("en_GB.UTF-8"), Some(Language::English));
+        assert_eq!(Language::from_code("fr_FR"), None);
+        assert_eq!(Language::resolve(Some("EO")), Language::Esperanto);
+    }
+
+    #[test]
+    fn bundles_translate_text() {
+        assert_eq!(english(Text::Notes), "Notes");
+        assert_eq!(esperanto(Text::Notes), "Notoj");
+    }
+}
@@ -2,11 +2,17 @@
 
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
 };
+use unicode_width::UnicodeWidthStr;
 
+use super::locale::{self, Text};
 use super::{
     claude_panel, command_line, content, curriculum, image::ImageCache, notes_panel, quiz_panel,
+    report, split_pane, status_bar,
 };
 use crate::app::state::{AppState, Panel};
 use crate::config::progress::Progress;
@@ -27,14 +33,15 @@ pub fn draw(
 ) {
     let area = frame.area();
 
-    // Split vertically: main area and command line
+    // Split vertically: main area, status bar (if any) and command line
+    let status_height = u16::from(!state.status_bar.is_empty());
     let vertical_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .constraints([Constraint::Min(3), Constraint::Length(status_height), Constraint::Length(1)])
         .split(area);
 
     let main_area = vertical_chunks[0];
-    let command_area = vertical_chunks[1];
+    let command_area = vertical_chunks[2];
 
     // Calculate panel layout for main area
     let chunks = create_layout(main_area, state);
@@ -54,15 +61,32 @@ pub fn draw(
         panel_index += 1;
     }
 
+    // With a split open the content area is shared between the two panes
+    let content_focused = state.focused_panel == Panel::Content;
+    let split_focused = state.split.as_ref().map(|split| split.focused);
+    let content_area = chunks[panel_index];
+    let (content_area, split_area) = match split_focused {
+        Some(_) => {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(content_area);
+            (halves[0], Some(halves[1]))
+        }
+        None => (content_area, None),
+    };
     draw_content_panel(
         frame,
-        chunks[panel_index],
+        content_area,
         state,
         theme,
-        state.focused_panel == Panel::Content,
+        content_focused && split_focused != Some(true),
         notes_store,
         image_cache,
     );
+    if let Some(area) = split_area {
+        split_pane::draw(frame, area, state, theme, content_focused && split_focused == Some(true));
+    }
     panel_index += 1;
 
     if state.panel_visibility.notes {
@@ -76,6 +100,10 @@ pub fn draw(
         );
     }
 
+    if status_height > 0 {
+        status_bar::draw(frame, vertical_chunks[1], &state.status_bar, theme);
+    }
+
     // Draw command line at bottom
     command_line::draw(frame, command_area, &state.command_line, theme);
 
@@ -84,6 +112,39 @@ pub fn draw(
 
     // Draw quiz panel as overlay (if active)
     quiz_panel::draw(frame, area, state, theme);
+
+    // Draw a report over both (if any)
+    report::draw(frame, area, state, theme);
+
+    // Draw book loading indicator (if a book is being parsed)
+    draw_book_loading(frame, main_area, state, theme);
+}
+
+/// Draw a small spinner box while a book loads in the background
+fn draw_book_loading(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(loading) = &state.book_loading else { return };
+
+    let text =
+        format!("{} {} {}...", loading.spinner(), locale::text(Text::Loading), loading.title);
+    let width = (text.width() as u16 + 6).min(area.width);
+    let height = 3.min(area.height);
+    let overlay_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, overlay_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_focused))
+        .style(Style::default().bg(theme.bg_secondary));
+    let para =
+        Paragraph::new(Line::from(Span::styled(text, Style::default().fg(theme.accent_primary))))
+            .alignment(Alignment::Center)
+            .block(block);
+    frame.render_widget(para, overlay_area);
 }
 
 /// Create the layout constraints based on visible panels
@@ -4,13 +4,20 @@ pub mod claude_panel;
 pub mod command_line;
 pub mod content;
 pub mod curriculum;
+pub mod help;
+pub mod hyperlink;
+pub mod hyphenate;
 pub mod image;
 pub mod landing;
 pub mod layout;
+pub mod locale;
 pub mod main_screen;
 pub mod notes_panel;
 pub mod quiz_panel;
+pub mod report;
 pub mod section_footer;
+pub mod split_pane;
+pub mod status_bar;
 
 use ratatui::Frame;
 
@@ -30,23 +37,20 @@ pub fn draw(
     notes_store: &NotesStore,
     image_cache: &mut ImageCache,
 ) {
-    let theme = config.active_theme();
+    let theme = state.book_theme.clone().unwrap_or_else(|| config.active_theme());
+    state.content.link_hits.clear();
 
     match &state.screen {
         Screen::Landing => {
-            landing::draw(frame, &state.landing_animation, &theme);
+            landing::draw(frame, &state.landing_animation, state.next_up.as_deref(), &theme);
         }
         Screen::Main => {
             main_screen::draw(frame, state, &theme, progress, notes_store, image_cache);
-        }
-        Screen::Quiz => {
-            layout::draw_placeholder(frame, "Quiz - Coming Soon\n\nPress Esc to return", &theme);
+            hyperlink::keep_drawn(&mut state.content.link_hits, frame.buffer_mut());
         }
         Screen::Notes => {
             layout::draw_placeholder(frame, "Notes - Coming Soon\n\nPress Esc to return", &theme);
         }
-        Screen::Help => {
-            layout::draw_placeholder(frame, "Help - Coming Soon\n\nPress Esc to return", &theme);
-        }
+        Screen::Help => help::draw(frame, &mut state.help, &theme),
     }
 }
@@ -9,10 +9,19 @@ use ratatui::{
 };
 use textwrap::{Options, wrap};
 
+use super::content::highlight_background;
+use super::locale::{self, Text};
 use crate::app::state::AppState;
-use crate::notes::{Note, NotesStore};
+use crate::config::datetime::{self, DateTimeFormat};
+use crate::notes::{Note, NoteQuery, NoteSource, NotesStore, links, search};
 use crate::theme::Theme;
 
+/// Content lines shown for a collapsed note
+const PREVIEW_LINES: usize = 4;
+
+/// Latest answer lines shown while a Q&A note is being written
+const PENDING_LINES: usize = 8;
+
 /// Draw the notes panel
 pub fn draw(
     frame: &mut Frame,
@@ -24,8 +33,21 @@ pub fn draw(
 ) {
     let border_color = if focused { theme.border_focused } else { theme.border };
 
+    let name = locale::text(Text::Notes);
+    let title = if state.notes.search_editing {
+        format!(" {} /{}▏", name, state.notes.search)
+    } else if state.notes.is_searching() {
+        format!(" {} /{} ", name, state.notes.search)
+    } else if let Some(color) = state.notes.highlights.then_some(state.notes.highlight_filter) {
+        let highlights = locale::text(Text::Highlights);
+        color.map_or(format!(" {} ", highlights), |c| format!(" {}: {} ", highlights, c.name()))
+    } else if state.notes.follow {
+        format!(" {} \u{00B7} in view ", name)
+    } else {
+        format!(" {} ", name)
+    };
     let block = Block::default()
-        .title(" Notes ")
+        .title(title)
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color))
         .style(Style::default().bg(theme.bg_primary));
@@ -37,6 +59,16 @@ pub fn draw(
     state.notes.visible_height = inner.height as usize;
 
     let width = inner.width as usize;
+    let layout = NoteLayout { theme, width, dates: &state.datetime, query: None };
+
+    if state.notes.is_searching() {
+        draw_search_results(frame, inner, state, theme, focused, notes_store);
+        return;
+    }
+    if state.notes.highlights {
+        draw_highlights(frame, inner, state, theme, focused, notes_store);
+        return;
+    }
 
     // Get current section path from book if loaded
     let (book_id, section_path) = match &state.book {
@@ -58,8 +90,13 @@ pub fn draw(
     };
 
     // Get notes for current section
-    let section_notes = notes_store.get_section_level_notes(book_id, section_path);
-    let selection_notes = notes_store.get_selection_notes(book_id, section_path);
+    let (section_notes, selection_notes, backlinks) =
+        section_lists(state, notes_store, book_id, section_path);
+    if state.notes.follow {
+        // The list changes as the text scrolls
+        let count = section_notes.len() + selection_notes.len() + backlinks.len();
+        state.notes.selected_index = state.notes.selected_index.min(count.saturating_sub(1));
+    }
 
     // If we're creating a new note, show the input area
     if state.notes.creating {
@@ -77,8 +114,8 @@ pub fn draw(
         return;
     }
 
-    // Build notes list
-    let mut lines: Vec<Line> = Vec::new();
+    // Build notes list, led by the Q&A note Claude is answering here
+    let mut lines: Vec<Line> = pending_note_lines(state, book_id, section_path, theme, width);
     let mut note_indices: Vec<&Note> = Vec::new();
 
     // Section-level notes first
@@ -89,9 +126,10 @@ pub fn draw(
         )));
         lines.push(Line::from(""));
 
-        for (idx, note) in section_notes.iter().enumerate() {
+        for note in &section_notes {
             let is_selected = note_indices.len() == state.notes.selected_index && focused;
-            add_note_lines(&mut lines, note, is_selected, theme, width, idx);
+            let expanded = state.notes.expanded.contains(&note.id);
+            add_note_lines(&mut lines, note, is_selected, expanded, &layout);
             note_indices.push(note);
         }
     }
@@ -110,17 +148,55 @@ pub fn draw(
         for (idx, note) in selection_notes.iter().enumerate() {
             let note_list_idx = section_notes.len() + idx;
             let is_selected = note_list_idx == state.notes.selected_index && focused;
-            add_note_lines(&mut lines, note, is_selected, theme, width, idx);
+            let expanded = state.notes.expanded.contains(&note.id);
+            add_note_lines(&mut lines, note, is_selected, expanded, &layout);
             note_indices.push(note);
         }
     }
 
+    // Notes elsewhere in the book linking here with [[section:path]]
+    if !backlinks.is_empty() {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            "─ Linked from ─",
+            Style::default().fg(theme.fg_muted).add_modifier(Modifier::BOLD),
+        )));
+        lines.push(Line::from(""));
+
+        for note in backlinks {
+            let title = state
+                .book
+                .as_ref()
+                .and_then(|book| book.find_section_by_path(&note.section_path))
+                .map_or(note.section_path.as_str(), |s| s.title.as_str());
+            lines.push(Line::from(Span::styled(
+                truncate_str(title, width),
+                Style::default().fg(theme.accent_secondary),
+            )));
+            let is_selected = note_indices.len() == state.notes.selected_index && focused;
+            let expanded = state.notes.expanded.contains(&note.id);
+            add_note_lines(&mut lines, note, is_selected, expanded, &layout);
+            note_indices.push(note);
+        }
+    }
+
+    if lines.is_empty() && state.notes.follow {
+        draw_empty_message(
+            frame,
+            inner,
+            theme,
+            "No annotations on the text in view\n\n:notes follow to list the whole section",
+        );
+        return;
+    }
     if lines.is_empty() {
         draw_empty_message(
             frame,
             inner,
             theme,
-            "No notes yet\n\nPress 'n' to add a note\nSelect text with 'v' to annotate",
+            "No notes yet\n\nPress 'n' to add a note\nSelect text with 'v' to annotate\nPress '/' to search all notes",
         );
         return;
     }
@@ -135,6 +211,136 @@ pub fn draw(
     frame.render_widget(notes_widget, inner);
 }
 
+/// Draw notes from every book matching the search, best first
+fn draw_search_results(
+    frame: &mut Frame,
+    area: Rect,
+    state: &AppState,
+    theme: &Theme,
+    focused: bool,
+    notes_store: &NotesStore,
+) {
+    let Some(query) = NoteQuery::new(&state.notes.search) else {
+        draw_empty_message(frame, area, theme, "Type to search notes in every book");
+        return;
+    };
+    let results = search::search(notes_store, &query);
+    if results.is_empty() {
+        draw_empty_message(frame, area, theme, "No notes match\n\nEsc to clear the search");
+        return;
+    }
+
+    let width = area.width as usize;
+    let layout = NoteLayout { theme, width, dates: &state.datetime, query: Some(&query) };
+    let mut lines: Vec<Line> = vec![
+        Line::from(Span::styled(
+            format!("─ {} match{} ─", results.len(), if results.len() == 1 { "" } else { "es" }),
+            Style::default().fg(theme.fg_muted).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    for (idx, note) in results.iter().enumerate() {
+        let is_selected = idx == state.notes.selected_index && focused;
+        let location = format!("{} · {}", note.book_id, note.section_path);
+        lines.push(Line::from(Span::styled(
+            truncate_str(&location, width),
+            Style::default().fg(theme.accent_secondary),
+        )));
+        let expanded = state.notes.expanded.contains(&note.id);
+        add_note_lines(&mut lines, note, is_selected, expanded, &layout);
+    }
+
+    let visible: Vec<Line> =
+        lines.into_iter().skip(state.notes.scroll_offset).take(area.height as usize).collect();
+    frame.render_widget(Paragraph::new(visible).wrap(Wrap { trim: false }), area);
+}
+
+/// Draw the book's highlights in reading order, each under its section title
+fn draw_highlights(
+    frame: &mut Frame,
+    area: Rect,
+    state: &AppState,
+    theme: &Theme,
+    focused: bool,
+    notes_store: &NotesStore,
+) {
+    let Some(book) = &state.book else {
+        draw_empty_message(frame, area, theme, "No book loaded");
+        return;
+    };
+    let highlights = highlight_list(state, notes_store);
+    if highlights.is_empty() {
+        draw_empty_message(
+            frame,
+            area,
+            theme,
+            "No highlights yet\n\nSelect text with 'v' and press 'H'\nEsc to go back",
+        );
+        return;
+    }
+
+    let width = area.width as usize;
+    let mut lines: Vec<Line> = vec![
+        Line::from(Span::styled(
+            format!(
+                "─ {} highlight{} · c color · x delete ─",
+                highlights.len(),
+                if highlights.len() == 1 { "" } else { "s" }
+            ),
+            Style::default().fg(theme.fg_muted).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    let mut last_section = None;
+    for (idx, note) in highlights.iter().enumerate() {
+        if last_section != Some(&note.section_path) {
+            let title = book
+                .find_section_by_path(&note.section_path)
+                .map_or(note.section_path.as_str(), |s| s.title.as_str());
+            lines.push(Line::from(Span::styled(
+                truncate_str(title, width),
+                Style::default().fg(theme.accent_secondary),
+            )));
+            last_section = Some(&note.section_path);
+        }
+
+        let is_selected = idx == state.notes.selected_index && focused;
+        let color = note.highlight.unwrap_or_default();
+        let swatch = Style::default().fg(highlight_background(color, theme));
+        let text_style = if is_selected {
+            Style::default().fg(theme.bg_primary).bg(theme.accent_primary)
+        } else {
+            Style::default().fg(theme.fg_primary)
+        };
+        let quote = note.anchor.selected_text().unwrap_or_default();
+        let quote = quote.split_whitespace().collect::<Vec<_>>().join(" ");
+        let quote_lines = if state.notes.expanded.contains(&note.id) {
+            wrap_text(&quote, width.saturating_sub(2))
+        } else {
+            vec![truncate_str(&quote, width.saturating_sub(2))]
+        };
+        for line_text in quote_lines {
+            lines.push(Line::from(vec![
+                Span::styled("▌ ", swatch),
+                Span::styled(line_text, text_style),
+            ]));
+        }
+        if !note.content.trim().is_empty() {
+            for line_text in wrap_text(&note.content, width.saturating_sub(2)) {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", line_text),
+                    Style::default().fg(theme.fg_muted),
+                )));
+            }
+        }
+        lines.push(Line::from(""));
+    }
+
+    let visible: Vec<Line> =
+        lines.into_iter().skip(state.notes.scroll_offset).take(area.height as usize).collect();
+    frame.render_widget(Paragraph::new(visible).wrap(Wrap { trim: false }), area);
+}
+
 /// Draw an empty message centered in the area
 fn draw_empty_message(frame: &mut Frame, area: Rect, theme: &Theme, msg: &str) {
     let msg_widget =
@@ -220,15 +426,29 @@ fn draw_note_input(
     frame.render_widget(widget, area);
 }
 
+/// How the notes of a list are laid out
+#[derive(Clone, Copy)]
+struct NoteLayout<'a> {
+    theme: &'a Theme,
+    width: usize,
+    dates: &'a DateTimeFormat,
+    /// Search whose words are highlighted
+    query: Option<&'a NoteQuery>,
+}
+
 /// Add lines for a single note to the display
+///
+/// Collapsed notes show a one-line quote and the first few lines of content;
+/// expanded notes show the whole quote and note. Words of a search `query`
+/// are highlighted.
 fn add_note_lines(
     lines: &mut Vec<Line>,
     note: &Note,
     is_selected: bool,
-    theme: &Theme,
-    width: usize,
-    _idx: usize,
+    expanded: bool,
+    layout: &NoteLayout,
 ) {
+    let NoteLayout { theme, width, dates, query } = *layout;
     let base_style = if is_selected {
         Style::default().fg(theme.bg_primary).bg(theme.accent_primary)
     } else {
@@ -241,76 +461,278 @@ fn add_note_lines(
         Style::default().fg(theme.fg_muted)
     };
 
-    // For selection notes, show the selected text first
+    // For selection notes, show the anchored text first
     if let Some(selected_text) = note.anchor.selected_text() {
-        let quote = format!("\"{}\"", truncate_str(selected_text, width.saturating_sub(4)));
-        lines.push(Line::from(Span::styled(quote, muted_style.add_modifier(Modifier::ITALIC))));
+        let quote_style = muted_style.add_modifier(Modifier::ITALIC | Modifier::DIM);
+        let selected_text = selected_text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let quote_lines = if expanded {
+            wrap_text(&selected_text, width.saturating_sub(2))
+        } else {
+            vec![truncate_str(&selected_text, width.saturating_sub(2))]
+        };
+        for line_text in quote_lines {
+            lines.push(highlighted(format!("▎ {}", line_text), quote_style, query));
+        }
     }
 
-    // Note content (wrapped)
-    let wrapped = if width > 2 {
-        let opts = Options::new(width - 2);
-        wrap(&note.content, opts).into_iter().map(|s| s.to_string()).collect::<Vec<_>>()
+    // Note content (wrapped), led by an icon for where the note came from;
+    // a highlight without content is named by its color
+    let content = match note.highlight {
+        Some(color) if note.content.trim().is_empty() => format!("{} highlight", color.name()),
+        _ => note.content.clone(),
+    };
+    let icon = if note.is_highlight() { "▌" } else { source_icon(note.source) };
+    let wrapped = wrap_text(&content, width.saturating_sub(2));
+    let hidden = if expanded { 0 } else { wrapped.len().saturating_sub(PREVIEW_LINES) };
+    let link_style = if is_selected {
+        base_style.add_modifier(Modifier::UNDERLINED)
     } else {
-        vec![note.content.clone()]
+        Style::default().fg(theme.accent_secondary).add_modifier(Modifier::UNDERLINED)
     };
+    for (i, line_text) in wrapped.iter().take(wrapped.len() - hidden).enumerate() {
+        let prefix = if i == 0 { icon } else { " " };
+        let text = format!("{} {}", prefix, line_text);
+        lines.push(match query {
+            Some(_) => highlighted(text, base_style, query),
+            None => linked(text, base_style, link_style),
+        });
+    }
+    if hidden > 0 {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  ▸ {} more line{} (Enter to expand)",
+                hidden,
+                if hidden == 1 { "" } else { "s" }
+            ),
+            muted_style,
+        )));
+    }
 
-    for line_text in wrapped {
-        lines.push(Line::from(Span::styled(format!("  {}", line_text), base_style)));
+    // Short ID (for :note/:note-delete) and timestamp, in full when expanded
+    let created = if expanded {
+        dates.timestamp(note.created_at)
+    } else {
+        format_timestamp(dates, note.created_at)
+    };
+    let mut details = format!("  #{} · {}", note.short_id(), created);
+    if note.anchor_lost {
+        details.push_str(" · ⚠ text not found");
+    }
+    if expanded {
+        details.push_str(&format!(" · {}", source_label(note.source)));
+        if note.updated_at > note.created_at {
+            details.push_str(&format!(" · edited {}", format_timestamp(dates, note.updated_at)));
+        }
     }
+    lines.push(Line::from(Span::styled(details, muted_style)));
+
+    lines.push(Line::from(""));
+}
 
-    // Timestamp
-    let timestamp = format_timestamp(note.created_at);
-    lines.push(Line::from(Span::styled(format!("  {}", timestamp), muted_style)));
+/// Preview of the Q&A note being written for a section: the question and the
+/// latest lines of Claude's answer as it streams in
+fn pending_note_lines(
+    state: &AppState,
+    book_id: &str,
+    section_path: &str,
+    theme: &Theme,
+    width: usize,
+) -> Vec<Line<'static>> {
+    let claude = &state.claude;
+    let Some(question) = &claude.pending_question else { return Vec::new() };
+    if claude.pending_book_id.as_deref() != Some(book_id)
+        || claude.pending_section_path.as_deref() != Some(section_path)
+    {
+        return Vec::new();
+    }
 
+    let muted = Style::default().fg(theme.fg_muted);
+    let mut lines = vec![
+        Line::from(Span::styled("─ Answering ─", muted.add_modifier(Modifier::BOLD))),
+        Line::from(""),
+    ];
+    let question = wrap_text(&format!("Q: {}", question), width.saturating_sub(2));
+    for (i, text) in question.into_iter().enumerate() {
+        let prefix = if i == 0 { source_icon(NoteSource::Claude) } else { " " };
+        lines.push(Line::from(Span::styled(
+            format!("{} {}", prefix, text),
+            Style::default().fg(theme.fg_primary).add_modifier(Modifier::BOLD),
+        )));
+    }
+
+    // The answer arrives in the stream buffer; `response` is the previous one until it ends
+    let text = if claude.streaming { &claude.stream_buffer } else { &claude.response };
+    let answer: Vec<String> =
+        text.lines().flat_map(|line| wrap_text(line, width.saturating_sub(2))).collect();
+    let hidden = answer.len().saturating_sub(PENDING_LINES);
+    if hidden > 0 {
+        lines.push(Line::from(Span::styled(format!("  ⋮ {} lines above", hidden), muted)));
+    }
+    for text in &answer[hidden..] {
+        lines.push(Line::from(Span::styled(
+            format!("  {}", text),
+            Style::default().fg(theme.fg_secondary),
+        )));
+    }
+    let status = if claude.streaming { "  ▍ writing…" } else { "  saving…" };
+    lines.push(Line::from(Span::styled(status, muted.add_modifier(Modifier::ITALIC))));
     lines.push(Line::from(""));
+    lines
+}
+
+/// A line in `style` with the words of a search query in bold and underlined
+fn highlighted(text: String, style: Style, query: Option<&NoteQuery>) -> Line<'static> {
+    let Some(query) = query else { return Line::from(Span::styled(text, style)) };
+    let hit = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    let ranges = query.ranges(&text);
+    styled_ranges(&text, style, &ranges, hit)
+}
+
+/// A line in `style` with `[[note:ID]]` and `[[section:path]]` links in `link_style`
+fn linked(text: String, style: Style, link_style: Style) -> Line<'static> {
+    let ranges: Vec<_> = links::find(&text).into_iter().map(|(range, _)| range).collect();
+    styled_ranges(&text, style, &ranges, link_style)
+}
+
+/// A line in `style` with byte ranges of it in `hit`
+fn styled_ranges(
+    text: &str,
+    style: Style,
+    ranges: &[std::ops::Range<usize>],
+    hit: Style,
+) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for range in ranges {
+        if range.start > last {
+            spans.push(Span::styled(text[last..range.start].to_string(), style));
+        }
+        spans.push(Span::styled(text[range.clone()].to_string(), hit));
+        last = range.end;
+    }
+    if last < text.len() || spans.is_empty() {
+        spans.push(Span::styled(text[last..].to_string(), style));
+    }
+    Line::from(spans)
+}
+
+/// Wrap text to a width, keeping at least one (possibly empty) line
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    if width == 0 {
+        return vec![text.to_string()];
+    }
+    let wrapped: Vec<String> =
+        wrap(text, Options::new(width)).into_iter().map(|s| s.to_string()).collect();
+    if wrapped.is_empty() { vec![String::new()] } else { wrapped }
 }
 
-/// Truncate a string to a maximum length with ellipsis
+/// Icon shown before a note to tell manual notes from generated ones
+fn source_icon(source: NoteSource) -> &'static str {
+    match source {
+        NoteSource::User => "✎",
+        NoteSource::Claude => "✦",
+        NoteSource::Quiz => "?",
+    }
+}
+
+/// Name of where a note came from, for the expanded view
+fn source_label(source: NoteSource) -> &'static str {
+    match source {
+        NoteSource::User => "written by you",
+        NoteSource::Claude => "Claude Q&A",
+        NoteSource::Quiz => "quiz explanation",
+    }
+}
+
+/// Truncate a string to a maximum length (in characters) with ellipsis
 fn truncate_str(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
+    if s.chars().count() <= max_len {
         s.to_string()
     } else if max_len <= 3 {
         "...".to_string()
     } else {
-        format!("{}...", &s[..max_len - 3])
+        let kept: String = s.chars().take(max_len - 3).collect();
+        format!("{}...", kept.trim_end())
     }
 }
 
-/// Format a Unix timestamp as a relative time or date
-fn format_timestamp(timestamp: i64) -> String {
-    use std::time::{Duration, SystemTime, UNIX_EPOCH};
-
-    let note_time = UNIX_EPOCH + Duration::from_secs(timestamp as u64);
-    let now = SystemTime::now();
-
-    let elapsed = now.duration_since(note_time).unwrap_or(Duration::ZERO);
-
-    if elapsed.as_secs() < 60 {
-        "just now".to_string()
-    } else if elapsed.as_secs() < 3600 {
-        let mins = elapsed.as_secs() / 60;
-        format!("{}m ago", mins)
-    } else if elapsed.as_secs() < 86400 {
-        let hours = elapsed.as_secs() / 3600;
-        format!("{}h ago", hours)
-    } else if elapsed.as_secs() < 604800 {
-        let days = elapsed.as_secs() / 86400;
-        format!("{}d ago", days)
-    } else {
-        // More than a week, show date
-        let days_since_epoch = timestamp / 86400;
-        // Simple date calculation (approximate)
-        let years = 1970 + (days_since_epoch / 365);
-        let remaining_days = days_since_epoch % 365;
-        let month = remaining_days / 30 + 1;
-        let day = remaining_days % 30 + 1;
-        format!("{:04}-{:02}-{:02}", years, month, day)
+/// Format a Unix timestamp as a relative time, or a local date after a week
+fn format_timestamp(dates: &DateTimeFormat, timestamp: i64) -> String {
+    dates.relative(timestamp, datetime::now())
+}
+
+/// Notes matching the notes panel's search, best first
+fn search_results<'a>(state: &AppState, notes_store: &'a NotesStore) -> Vec<&'a Note> {
+    NoteQuery::new(&state.notes.search)
+        .map(|query| search::search(notes_store, &query))
+        .unwrap_or_default()
+}
+
+/// The book's highlights listed in the notes panel, in reading order
+fn highlight_list<'a>(state: &AppState, notes_store: &'a NotesStore) -> Vec<&'a Note> {
+    let Some(book) = &state.book else { return Vec::new() };
+    let order: std::collections::HashMap<&str, usize> = book
+        .chapters
+        .iter()
+        .flat_map(|c| &c.sections)
+        .enumerate()
+        .map(|(i, s)| (s.path.as_str(), i))
+        .collect();
+    let mut highlights: Vec<&Note> = notes_store
+        .get_highlights(&book.metadata.id)
+        .into_iter()
+        .filter(|n| state.notes.highlight_filter.is_none_or(|color| n.highlight == Some(color)))
+        .collect();
+    highlights.sort_by_key(|n| {
+        let section = order.get(n.section_path.as_str()).copied().unwrap_or(usize::MAX);
+        (section, n.anchor.block_index(), n.anchor.char_range())
+    });
+    highlights
+}
+
+/// Notes in other sections of the book linking to the current one
+pub fn backlink_list<'a>(state: &AppState, notes_store: &'a NotesStore) -> Vec<&'a Note> {
+    let Some(book) = &state.book else { return Vec::new() };
+    let Some(section) = book.get_section(state.current_chapter, state.current_section) else {
+        return Vec::new();
+    };
+    notes_store
+        .backlinks_to_section(&book.metadata.id, &section.path)
+        .into_iter()
+        .filter(|n| n.section_path != section.path)
+        .collect()
+}
+
+/// A section's notes as the panel lists them: section notes, annotations, and
+/// notes linking here; when following the scroll, only the annotations on
+/// blocks in view
+fn section_lists<'a>(
+    state: &AppState,
+    notes_store: &'a NotesStore,
+    book_id: &str,
+    section_path: &str,
+) -> (Vec<&'a Note>, Vec<&'a Note>, Vec<&'a Note>) {
+    let selection_notes = notes_store.get_selection_notes(book_id, section_path);
+    if !state.notes.follow {
+        let section_notes = notes_store.get_section_level_notes(book_id, section_path);
+        return (section_notes, selection_notes, backlink_list(state, notes_store));
     }
+    let in_view = state.content.visible_blocks();
+    let selection_notes = selection_notes
+        .into_iter()
+        .filter(|n| !n.anchor_lost && n.anchor.block_index().is_some_and(|b| in_view.contains(&b)))
+        .collect();
+    (Vec::new(), selection_notes, Vec::new())
 }
 
 /// Get the note at the current selection index
 pub fn get_selected_note<'a>(state: &AppState, notes_store: &'a NotesStore) -> Option<&'a Note> {
+    if state.notes.is_searching() {
+        return search_results(state, notes_store).get(state.notes.selected_index).copied();
+    }
+    if state.notes.highlights {
+        return highlight_list(state, notes_store).get(state.notes.selected_index).copied();
+    }
     let book = state.book.as_ref()?;
     let chapter = book.chapters.get(state.current_chapter)?;
     let section = chapter.sections.get(state.current_section)?;
@@ -318,27 +740,38 @@ pub fn get_selected_note<'a>(state: &AppState, notes_store: &'a NotesStore) -> O
     let book_id = &book.metadata.id;
     let section_path = &section.path;
 
-    let section_notes = notes_store.get_section_level_notes(book_id, section_path);
-    let selection_notes = notes_store.get_selection_notes(book_id, section_path);
-
+    let (section_notes, selection_notes, backlinks) =
+        section_lists(state, notes_store, book_id, section_path);
     let idx = state.notes.selected_index;
-    if idx < section_notes.len() {
-        section_notes.get(idx).copied()
-    } else {
-        selection_notes.get(idx - section_notes.len()).copied()
-    }
+    section_notes.into_iter().chain(selection_notes).chain(backlinks).nth(idx)
 }
 
-/// Get total note count for current section
+/// Get total note count for current section, or of search results
 pub fn get_note_count(state: &AppState, notes_store: &NotesStore) -> usize {
+    if state.notes.is_searching() {
+        return search_results(state, notes_store).len();
+    }
+    if state.notes.highlights {
+        return highlight_list(state, notes_store).len();
+    }
     let Some(book) = &state.book else { return 0 };
     let Some(chapter) = book.chapters.get(state.current_chapter) else { return 0 };
     let Some(section) = chapter.sections.get(state.current_section) else { return 0 };
 
-    let section_notes = notes_store.get_section_level_notes(&book.metadata.id, &section.path);
-    let selection_notes = notes_store.get_selection_notes(&book.metadata.id, &section.path);
+    let (section_notes, selection_notes, backlinks) =
+        section_lists(state, notes_store, &book.metadata.id, &section.path);
+    section_notes.len() + selection_notes.len() + backlinks.len()
+}
 
-    section_notes.len() + selection_notes.len()
+/// Whether the selected note is listed as linking to the current section,
+/// rather than belonging to it
+pub fn backlink_selected(state: &AppState, notes_store: &NotesStore) -> bool {
+    if state.notes.is_searching() || state.notes.highlights {
+        return false;
+    }
+    let backlinks = if state.notes.follow { 0 } else { backlink_list(state, notes_store).len() };
+    let count = get_note_count(state, notes_store);
+    state.notes.selected_index < count && state.notes.selected_index >= count - backlinks
 }
 
 #[cfg(test)]
@@ -365,26 +798,87 @@ mod tests {
         assert_eq!(truncate_str("hello", 2), "...");
     }
 
+    #[test]
+    fn truncate_str_counts_characters() {
+        assert_eq!(truncate_str("héllo wörld", 8), "héllo...");
+    }
+
+    #[test]
+    fn long_notes_collapse_until_expanded() {
+        let theme = Theme::default();
+        let mut note = Note::new_selection_note("b", "s", "", 0, 0, "the quoted passage");
+        note.content = (1..=10).map(|i| format!("line{}", i)).collect::<Vec<_>>().join(" ");
+
+        let mut collapsed = Vec::new();
+        let dates = DateTimeFormat::default();
+        let layout = NoteLayout { theme: &theme, width: 9, dates: &dates, query: None };
+        add_note_lines(&mut collapsed, &note, false, false, &layout);
+        let text: Vec<String> = collapsed.iter().map(|l| l.to_string()).collect();
+        assert_eq!(text[0], "▎ the...");
+        assert_eq!(text[1], "✎ line1");
+        assert_eq!(text[5], "  ▸ 6 more lines (Enter to expand)");
+
+        let mut expanded = Vec::new();
+        add_note_lines(&mut expanded, &note, false, true, &layout);
+        let text: Vec<String> = expanded.iter().map(|l| l.to_string()).collect();
+        assert_eq!(&text[..3], ["▎ the", "▎ quoted", "▎ passage"]);
+        assert_eq!(text[12], "  line10");
+        assert!(text[13].ends_with("written by you"));
+    }
+
+    #[test]
+    fn pending_note_shows_the_latest_answer_lines() {
+        let theme = Theme::default();
+        let mut state = AppState::default();
+        assert!(pending_note_lines(&state, "b", "s", &theme, 40).is_empty());
+
+        state.claude.set_pending_note("Why Rc?", "b", "s", None, None, None);
+        state.claude.streaming = true;
+        state.claude.response = "the previous answer".into();
+        state.claude.stream_buffer =
+            (1..=10).map(|i| format!("line{}", i)).collect::<Vec<_>>().join("\n");
+        assert!(pending_note_lines(&state, "b", "other", &theme, 40).is_empty());
+
+        let text: Vec<String> = pending_note_lines(&state, "b", "s", &theme, 40)
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(text[2], "✦ Q: Why Rc?");
+        assert_eq!(text[3], "  ⋮ 2 lines above");
+        assert_eq!(text[4], "  line3");
+        assert_eq!(text[11], "  line10");
+        assert_eq!(text[12], "  ▍ writing…");
+    }
+
+    #[test]
+    fn search_matches_are_highlighted() {
+        let query = NoteQuery::new("borrow").unwrap();
+        let line = highlighted("✎ Borrowing ends".into(), Style::default(), Some(&query));
+        let parts: Vec<(&str, bool)> = line
+            .spans
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style.add_modifier.contains(Modifier::UNDERLINED)))
+            .collect();
+        assert_eq!(parts, [("✎ ", false), ("Borrow", true), ("ing ends", false)]);
+    }
+
     #[test]
     fn format_timestamp_just_now() {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
-        assert_eq!(format_timestamp(now), "just now");
+        let now = crate::config::datetime::now();
+        assert_eq!(format_timestamp(&DateTimeFormat::default(), now), "just now");
     }
 
     #[test]
     fn format_timestamp_minutes_ago() {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        let now = crate::config::datetime::now();
         let five_mins_ago = now - 300;
-        assert_eq!(format_timestamp(five_mins_ago), "5m ago");
+        assert_eq!(format_timestamp(&DateTimeFormat::default(), five_mins_ago), "5m ago");
     }
 
     #[test]
     fn format_timestamp_hours_ago() {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        let now = crate::config::datetime::now();
         let two_hours_ago = now - 7200;
-        assert_eq!(format_timestamp(two_hours_ago), "2h ago");
+        assert_eq!(format_timestamp(&DateTimeFormat::default(), two_hours_ago), "2h ago");
     }
 }
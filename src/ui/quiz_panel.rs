@@ -1,14 +1,19 @@
 //! Quiz panel overlay component
+//!
+//! The quiz is a modal over the section: a row of markers tracks progress
+//! through the questions, and answers are only marked right or wrong once the
+//! quiz is done, in the results and the review of each question.
 
 use ratatui::{
     Frame,
     layout::{Constraint, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
 };
 
-use crate::app::state::AppState;
+use super::locale::{self, Text};
+use crate::app::state::{AppState, QuestionEditor, QuizAnswer, QuizState};
 use crate::theme::Theme;
 
 /// Draw the quiz panel as a centered overlay
@@ -25,19 +30,25 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     frame.render_widget(Clear, overlay_area);
 
     // Determine title based on state
-    let title = if state.quiz.loading {
-        " Generating Quiz... "
+    let title = if state.quiz.editor.is_some() {
+        Text::QuestionBank
+    } else if state.quiz.loading {
+        Text::QuizGenerating
+    } else if state.quiz.reviewing {
+        Text::QuizReview
     } else if state.quiz.completed {
-        " Quiz Results "
+        Text::QuizResults
     } else if state.quiz.error.is_some() {
-        " Quiz Error "
+        Text::QuizError
     } else {
-        " Quiz "
+        Text::Quiz
     };
+    let title = format!(" {} ", locale::text(title));
 
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(theme.border_focused))
         .style(Style::default().bg(theme.bg_secondary));
 
@@ -45,26 +56,34 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     frame.render_widget(block, overlay_area);
 
     // Draw content based on state
-    if state.quiz.loading {
-        draw_loading(frame, inner, theme);
+    if let Some(editor) = &state.quiz.editor {
+        draw_editor(frame, inner, editor, theme);
+    } else if state.quiz.loading {
+        draw_loading(frame, inner, state.quiz.is_regenerating(), theme);
     } else if let Some(ref error) = state.quiz.error {
         draw_error(frame, inner, error, theme);
+    } else if state.quiz.reviewing {
+        draw_review(frame, inner, state, theme);
     } else if state.quiz.completed {
         draw_results(frame, inner, state, theme);
+    } else if state.quiz.awaiting_question() {
+        draw_awaiting(frame, inner, state, theme);
     } else {
         draw_question(frame, inner, state, theme);
     }
 }
 
 /// Draw loading state
-fn draw_loading(frame: &mut Frame, area: Rect, theme: &Theme) {
+fn draw_loading(frame: &mut Frame, area: Rect, regenerating: bool, theme: &Theme) {
+    let message = if regenerating {
+        "Generating follow-up questions on what you missed..."
+    } else {
+        "Generating quiz questions..."
+    };
     let text = vec![
         Line::from(""),
         Line::from(""),
-        Line::from(Span::styled(
-            "Generating quiz questions...",
-            Style::default().fg(theme.fg_primary),
-        )),
+        Line::from(Span::styled(message, Style::default().fg(theme.fg_primary))),
         Line::from(""),
         Line::from(Span::styled(
             "Please wait while Claude creates your quiz.",
@@ -76,6 +95,28 @@ fn draw_loading(frame: &mut Frame, area: Rect, theme: &Theme) {
     frame.render_widget(para, area);
 }
 
+/// Draw the wait for the next question to stream in
+fn draw_awaiting(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let text = vec![
+        Line::from(""),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Waiting for the next question...",
+            Style::default().fg(theme.fg_primary),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(generated_label(state), Style::default().fg(theme.fg_muted))),
+    ];
+
+    let para = Paragraph::new(text).alignment(ratatui::layout::Alignment::Center);
+    frame.render_widget(para, area);
+}
+
+/// Progress of questions streaming in, e.g. "Generated 2/5 questions..."
+fn generated_label(state: &AppState) -> String {
+    format!("Generated {}/{} questions...", state.quiz.questions.len(), state.quiz.total())
+}
+
 /// Draw error state
 fn draw_error(frame: &mut Frame, area: Rect, error: &str, theme: &Theme) {
     let text = vec![
@@ -108,7 +149,12 @@ fn draw_results(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme)
     let mut lines = vec![Line::from(""), Line::from("")];
 
     // Result header
-    if passed {
+    if state.quiz.grading {
+        lines.push(Line::from(Span::styled(
+            "Grading short answers...",
+            Style::default().fg(theme.accent_primary).add_modifier(Modifier::BOLD),
+        )));
+    } else if passed {
         lines.push(Line::from(vec![
             Span::styled("  ", Style::default()),
             Span::styled(
@@ -126,20 +172,30 @@ fn draw_results(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme)
         ]));
     }
 
+    if !state.quiz.grading && total > 0 {
+        lines.push(Line::from(""));
+        lines.push(score_bar(correct, total, passed, theme));
+        let mut details = format!("Attempt {}", state.quiz.attempt.max(1));
+        if let Some(elapsed) = state.quiz.elapsed() {
+            let elapsed = elapsed.as_secs();
+            details.push_str(&format!(" \u{00B7} {}:{:02}", elapsed / 60, elapsed % 60));
+        }
+        lines.push(Line::from(Span::styled(details, Style::default().fg(theme.fg_muted))));
+    }
+
     lines.push(Line::from(""));
     lines.push(Line::from(""));
 
     // Question results
     let mut result_spans = vec![Span::raw("  ")];
-    for (i, question) in state.quiz.questions.iter().enumerate() {
-        let user_answer = state.quiz.answers.get(i).copied().flatten();
-        let is_correct = user_answer == Some(question.correct_index);
-
-        let marker = if is_correct { " \u{2713} " } else { " \u{2717} " }; // ✓ or ✗
-        let style = if is_correct {
-            Style::default().fg(theme.success)
-        } else {
-            Style::default().fg(theme.error)
+    for i in 0..state.quiz.questions.len() {
+        let grade = state.quiz.grades.get(i).copied().flatten();
+
+        let (marker, style) = match grade {
+            Some(true) => (" \u{2713} ", Style::default().fg(theme.success)), // ✓
+            Some(false) => (" \u{2717} ", Style::default().fg(theme.error)),  // ✗
+            None if state.quiz.grading => (" \u{2026} ", Style::default().fg(theme.fg_muted)), // …
+            None => (" \u{2717} ", Style::default().fg(theme.error)),
         };
 
         result_spans.push(Span::styled(format!("Q{}{}", i + 1, marker), style));
@@ -150,14 +206,16 @@ fn draw_results(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme)
     lines.push(Line::from(""));
 
     // Action hint
-    if passed {
+    if state.quiz.grading {
+        lines.push(Line::from(Span::styled("[Esc] Cancel", Style::default().fg(theme.fg_muted))));
+    } else if passed {
         lines.push(Line::from(Span::styled(
-            "[Enter] Continue to Next Section",
+            "[j/k] Review Answers    [e] Edit Questions    [Enter] Continue to Next Section",
             Style::default().fg(theme.fg_muted),
         )));
     } else {
         lines.push(Line::from(Span::styled(
-            "[Enter] Retry    [Esc] Back to Section",
+            "[j/k] Review    [e] Edit Questions    [Enter] Retry Missed    [Esc] Back to Section",
             Style::default().fg(theme.fg_muted),
         )));
     }
@@ -166,10 +224,163 @@ fn draw_results(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme)
     frame.render_widget(para, area);
 }
 
+/// Draw one question's answers and Claude's explanation
+fn draw_review(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let quiz = &state.quiz;
+    let index = quiz.review_index;
+    let Some(question) = quiz.questions.get(index) else {
+        return;
+    };
+    let correct = quiz.grades.get(index).copied().flatten() == Some(true);
+
+    let mut lines = vec![];
+
+    let (verdict, verdict_style) = if correct {
+        ("\u{2713} Correct", Style::default().fg(theme.success)) // ✓
+    } else {
+        ("\u{2717} Incorrect", Style::default().fg(theme.error)) // ✗
+    };
+    lines.push(Line::from(vec![
+        Span::styled(
+            format!("Question {} of {} \u{00B7} ", index + 1, quiz.questions.len()),
+            Style::default().fg(theme.fg_muted),
+        ),
+        Span::styled(verdict, verdict_style.add_modifier(Modifier::BOLD)),
+    ]));
+    lines.push(Line::from(""));
+
+    lines.push(Line::from(Span::styled(
+        question.question(),
+        Style::default().fg(theme.fg_primary).add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::from(""));
+
+    let answer = quiz.answers.get(index).and_then(Option::as_ref);
+    let label = Style::default().fg(theme.fg_muted);
+    if let Some(correct_option) = question.correct_option() {
+        let chosen = match answer {
+            Some(QuizAnswer::Choice(i)) => Some(*i),
+            _ => None,
+        };
+        lines.extend(feedback_lines(question.options(), correct_option, chosen, theme));
+    } else {
+        let given = answer
+            .map(|a| question.describe_answer(a))
+            .unwrap_or_else(|| "(no answer)".to_string());
+        lines.push(Line::from(vec![
+            Span::styled("Your answer:    ", label),
+            Span::styled(given, verdict_style),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("Correct answer: ", label),
+            Span::styled(question.answer_text(), Style::default().fg(theme.success)),
+        ]));
+        lines.push(Line::from(""));
+    }
+
+    if let Some(explanation) = quiz.explanation(index) {
+        lines.push(Line::from(Span::styled("Why", label.add_modifier(Modifier::BOLD))));
+        lines.push(Line::from(Span::styled(explanation, Style::default().fg(theme.fg_secondary))));
+    } else if quiz.explaining {
+        lines.push(Line::from(Span::styled(
+            "Asking Claude to explain the answers...",
+            Style::default().fg(theme.accent_primary),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(""));
+
+    let next = if quiz.passed() { "Continue" } else { "Retry Missed" };
+    lines.push(Line::from(Span::styled(
+        format!("[j/k] Previous/Next    [e] Edit    [Enter] {}    [Esc] Back to Results", next),
+        Style::default().fg(theme.fg_muted),
+    )));
+
+    let para = Paragraph::new(lines).wrap(Wrap { trim: true });
+    frame.render_widget(para, area);
+}
+
+/// Draw the question bank editor: the section's questions, or the one being written
+fn draw_editor(frame: &mut Frame, area: Rect, editor: &QuestionEditor, theme: &Theme) {
+    let muted = Style::default().fg(theme.fg_muted);
+    let mut lines = vec![];
+
+    if let Some(form) = &editor.form {
+        let heading = if form.index.is_some() { "Edit question" } else { "New question" };
+        lines.push(Line::from(Span::styled(heading, muted.add_modifier(Modifier::BOLD))));
+        lines.push(Line::from(""));
+        let text_style = Style::default().fg(theme.fg_primary);
+        for (i, line) in form.lines.iter().enumerate() {
+            if i != form.line {
+                lines.push(Line::from(Span::styled(line.clone(), text_style)));
+                continue;
+            }
+            let before: String = line.chars().take(form.col).collect();
+            let at: String = line.chars().nth(form.col).map_or(" ".to_string(), String::from);
+            let after: String = line.chars().skip(form.col + 1).collect();
+            lines.push(Line::from(vec![
+                Span::styled(before, text_style),
+                Span::styled(at, text_style.add_modifier(Modifier::REVERSED)),
+                Span::styled(after, text_style),
+            ]));
+        }
+        lines.push(Line::from(""));
+        if let Some(error) = &editor.error {
+            lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(theme.error))));
+            lines.push(Line::from(""));
+        }
+        for help in [
+            "The question, then its answer:",
+            "  * right option   - wrong option",
+            "  = true/false, or the word for a ____ blank",
+            "  ~ model answer for Claude to grade against",
+        ] {
+            lines.push(Line::from(Span::styled(help, muted)));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("[Ctrl+S] Save    [Esc] Cancel", muted)));
+    } else {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{} questions \u{00B7} yours are asked before Claude's",
+                editor.questions.len()
+            ),
+            muted,
+        )));
+        lines.push(Line::from(""));
+        for (i, banked) in editor.questions.iter().enumerate() {
+            let selected = i == editor.selected;
+            let marker = if selected { "> " } else { "  " };
+            let source = if banked.custom { "yours " } else { "Claude" };
+            let style = if selected {
+                Style::default().fg(theme.accent_primary).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.fg_primary)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(marker, style),
+                Span::styled(format!("[{}] ", source), muted),
+                Span::styled(banked.question.question().to_string(), style),
+            ]));
+        }
+        if editor.questions.is_empty() {
+            lines.push(Line::from(Span::styled("No questions yet", muted)));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "[j/k] Select    [e] Edit    [a] Add    [x] Delete    [Esc] Back to Results",
+            muted,
+        )));
+    }
+
+    let para = Paragraph::new(lines).wrap(Wrap { trim: false });
+    frame.render_widget(para, area);
+}
+
 /// Draw current question
 fn draw_question(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let current = state.quiz.current_question;
-    let total = state.quiz.questions.len();
+    let total = state.quiz.total();
 
     let Some(question) = state.quiz.questions.get(current) else {
         return;
@@ -177,23 +388,56 @@ fn draw_question(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme)
 
     let mut lines = vec![];
 
-    // Question number
+    // Question number and type
     lines.push(Line::from(Span::styled(
-        format!("Question {} of {}", current + 1, total),
+        format!("Question {} of {} \u{00B7} {}", current + 1, total, question.kind_label()),
         Style::default().fg(theme.fg_muted),
     )));
+    lines.push(progress_markers(&state.quiz, theme));
+    if state.quiz.generating {
+        lines.push(Line::from(Span::styled(
+            generated_label(state),
+            Style::default().fg(theme.accent_primary),
+        )));
+    }
     lines.push(Line::from(""));
 
     // Question text
     lines.push(Line::from(Span::styled(
-        &question.question,
+        question.question(),
         Style::default().fg(theme.fg_primary).add_modifier(Modifier::BOLD),
     )));
     lines.push(Line::from(""));
     lines.push(Line::from(""));
 
-    // Options
-    for (i, option) in question.options.iter().enumerate() {
+    if question.takes_text() {
+        draw_text_answer(&mut lines, state, theme);
+    } else {
+        draw_options(&mut lines, question.options(), state, theme);
+    }
+
+    lines.push(Line::from(""));
+
+    // Hint
+    let hint = if question.takes_text() {
+        "[Type] Answer    [Enter] Confirm    [\u{2191}/\u{2193}] Previous/Next    [Esc] Cancel"
+    } else {
+        "[j/k/1-4/a-d] Select    [Enter] Confirm    [p/n] Previous/Next    [Esc] Cancel"
+    };
+    lines.push(Line::from(Span::styled(hint, Style::default().fg(theme.fg_muted))));
+
+    let para = Paragraph::new(lines).wrap(Wrap { trim: true });
+    frame.render_widget(para, area);
+}
+
+/// Add the answer options for a multiple choice or true/false question
+fn draw_options<'a>(
+    lines: &mut Vec<Line<'a>>,
+    options: Vec<&'a str>,
+    state: &AppState,
+    theme: &Theme,
+) {
+    for (i, option) in options.into_iter().enumerate() {
         let is_selected = i == state.quiz.selected_option;
         let prefix = if is_selected { "\u{25CF}" } else { "\u{25CB}" }; // ● or ○
         let letter = (b'A' + i as u8) as char;
@@ -207,17 +451,86 @@ fn draw_question(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme)
         lines.push(Line::from(Span::styled(format!("  {} {}) {}", prefix, letter, option), style)));
         lines.push(Line::from(""));
     }
+}
+
+/// One marker per question: answered, the current one, not yet answered, and
+/// still being written by Claude
+fn progress_markers(quiz: &QuizState, theme: &Theme) -> Line<'static> {
+    let spans = (0..quiz.total()).flat_map(|i| {
+        let answered = quiz.answers.get(i).is_some_and(Option::is_some);
+        let (marker, style) = if i == quiz.current_question {
+            ("\u{25C9}", Style::default().fg(theme.accent_primary).add_modifier(Modifier::BOLD)) // ◉
+        } else if i >= quiz.questions.len() {
+            ("\u{00B7}", Style::default().fg(theme.fg_muted)) // ·
+        } else if answered {
+            ("\u{25CF}", Style::default().fg(theme.accent_secondary)) // ●
+        } else {
+            ("\u{25CB}", Style::default().fg(theme.fg_muted)) // ○
+        };
+        [Span::styled(marker, style), Span::raw(" ")]
+    });
+    Line::from(spans.collect::<Vec<_>>())
+}
 
+/// A choice question's options marked after the quiz: the correct one in the
+/// success color, and the one chosen, if wrong, in the error color
+fn feedback_lines(
+    options: Vec<&str>,
+    correct: usize,
+    chosen: Option<usize>,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for (i, option) in options.into_iter().enumerate() {
+        let letter = (b'A' + i as u8) as char;
+        let (marker, style) = if i == correct {
+            ("\u{2713}", Style::default().fg(theme.success).add_modifier(Modifier::BOLD)) // ✓
+        } else if Some(i) == chosen {
+            ("\u{2717}", Style::default().fg(theme.error).add_modifier(Modifier::BOLD)) // ✗
+        } else {
+            (" ", Style::default().fg(theme.fg_muted))
+        };
+        let yours = if Some(i) == chosen { "  (your answer)" } else { "" };
+        lines.push(Line::from(Span::styled(
+            format!("  {} {}) {}{}", marker, letter, option, yours),
+            style,
+        )));
+    }
     lines.push(Line::from(""));
+    lines
+}
 
-    // Hint
-    lines.push(Line::from(Span::styled(
-        "[j/k] Select    [Enter] Confirm    [Esc] Cancel",
-        Style::default().fg(theme.fg_muted),
-    )));
+/// A bar filled in proportion to the score, with the percentage
+fn score_bar(correct: usize, total: usize, passed: bool, theme: &Theme) -> Line<'static> {
+    const WIDTH: usize = 20;
+    let filled = (correct * WIDTH).div_ceil(total.max(1)).min(WIDTH);
+    let color = if passed { theme.success } else { theme.warning };
+    Line::from(vec![
+        Span::styled("\u{2588}".repeat(filled), Style::default().fg(color)), // █
+        Span::styled("\u{2591}".repeat(WIDTH - filled), Style::default().fg(theme.border)), // ░
+        Span::styled(
+            format!(" {}%", correct * 100 / total.max(1)),
+            Style::default().fg(theme.fg_secondary),
+        ),
+    ])
+}
 
-    let para = Paragraph::new(lines).wrap(Wrap { trim: true });
-    frame.render_widget(para, area);
+/// Add the text box for a typed answer, with a block cursor
+fn draw_text_answer(lines: &mut Vec<Line<'_>>, state: &AppState, theme: &Theme) {
+    let input = &state.quiz.text_input;
+    let cursor = state.quiz.text_cursor;
+    let before: String = input.chars().take(cursor).collect();
+    let at: String = input.chars().nth(cursor).map_or(" ".to_string(), String::from);
+    let after: String = input.chars().skip(cursor + 1).collect();
+
+    let text_style = Style::default().fg(theme.fg_primary);
+    lines.push(Line::from(vec![
+        Span::styled("  > ", Style::default().fg(theme.accent_primary)),
+        Span::styled(before, text_style),
+        Span::styled(at, text_style.add_modifier(Modifier::REVERSED)),
+        Span::styled(after, text_style),
+    ]));
+    lines.push(Line::from(""));
 }
 
 /// Create a centered rectangle with the given percentage of width and height
@@ -236,3 +549,50 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     ])
     .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::state::QuizQuestion;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn progress_markers_show_answered_current_and_pending() {
+        let question = QuizQuestion::TrueFalse { question: "Moves copy".into(), answer: false };
+        let mut quiz = QuizState {
+            questions: vec![question.clone(), question.clone(), question],
+            answers: vec![Some(QuizAnswer::Choice(1)), None, None],
+            current_question: 1,
+            generating: true,
+            expected: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            text(&progress_markers(&quiz, &Theme::default())),
+            "\u{25CF} \u{25C9} \u{25CB} \u{00B7} "
+        );
+        quiz.generating = false;
+        assert_eq!(progress_markers(&quiz, &Theme::default()).spans.len(), 6);
+    }
+
+    #[test]
+    fn feedback_marks_the_correct_and_chosen_options() {
+        let theme = Theme::default();
+        let lines = feedback_lines(vec!["copy", "move", "borrow"], 1, Some(2), &theme);
+        let shown: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(
+            shown,
+            ["    A) copy", "  \u{2713} B) move", "  \u{2717} C) borrow  (your answer)", ""]
+        );
+        assert_eq!(lines[1].spans[0].style.fg, Some(theme.success));
+        assert_eq!(lines[2].spans[0].style.fg, Some(theme.error));
+
+        assert_eq!(
+            text(&score_bar(3, 4, false, &theme)).chars().filter(|&c| c == '\u{2588}').count(),
+            15
+        );
+    }
+}
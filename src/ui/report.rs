@@ -0,0 +1,46 @@
+//! Report overlay: text shown over the main screen until dismissed
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use super::claude_panel::{centered_rect, draw_scroll_indicator, wrap_text};
+use crate::app::state::AppState;
+use crate::theme::Theme;
+
+/// Draw the report (if any) as a centered overlay
+pub fn draw(frame: &mut Frame, area: Rect, state: &mut AppState, theme: &Theme) {
+    let Some(report) = &mut state.report else { return };
+
+    let overlay_area = centered_rect(80, 80, area);
+    frame.render_widget(Clear, overlay_area);
+
+    let block = Block::default()
+        .title(format!(" {} ", report.title))
+        .title_bottom(Line::from(" j/k scroll  Esc close ").centered())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_focused))
+        .style(Style::default().bg(theme.bg_secondary));
+    let inner = block.inner(overlay_area);
+    frame.render_widget(block, overlay_area);
+
+    let lines = wrap_text(&report.text, inner.width.saturating_sub(2) as usize, theme);
+    let visible_lines = inner.height as usize;
+    let max_scroll = lines.len().saturating_sub(visible_lines);
+    let scroll = (report.scroll as usize).min(max_scroll);
+    report.scroll = scroll as u16;
+
+    let para = Paragraph::new(lines)
+        .style(Style::default().fg(theme.fg_primary))
+        .scroll((scroll as u16, 0))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(para, inner);
+
+    if max_scroll > 0 {
+        draw_scroll_indicator(frame, inner, scroll, max_scroll, theme);
+    }
+}
@@ -1,4 +1,5 @@
-//! Section footer component with Quiz and Complete buttons
+//! Section footer component with Quiz and Complete buttons, and the section's
+//! quiz history
 
 use ratatui::{
     Frame,
@@ -9,10 +10,15 @@ use ratatui::{
 };
 
 use crate::app::state::AppState;
+use crate::config::datetime::{self, DateTimeFormat};
+use crate::config::progress::QuizAttempt;
 use crate::theme::Theme;
 
 /// Height of the section footer in lines
-pub const FOOTER_HEIGHT: u16 = 3;
+pub const FOOTER_HEIGHT: u16 = 4;
+
+/// Most recent attempts whose scores are listed
+const HISTORY_SHOWN: usize = 5;
 
 /// Draw the section footer with two buttons
 pub fn draw(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
@@ -63,6 +69,13 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
         Rect::new(start_x + quiz_width as u16 + gap as u16, button_y, next_width as u16, 1);
     frame.render_widget(Paragraph::new(next_button), next_area);
 
+    // Draw quiz history line
+    let history = history_text(&state.content.quiz_history, &state.datetime, datetime::now());
+    let history_para =
+        Paragraph::new(Line::from(Span::styled(history, Style::default().fg(theme.fg_secondary))))
+            .centered();
+    frame.render_widget(history_para, Rect::new(area.x, area.y + 2, area.width, 1));
+
     // Draw hint line
     let hint = if footer_focused {
         Line::from(vec![
@@ -84,10 +97,38 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     };
 
     let hint_para = Paragraph::new(hint);
-    let hint_area = Rect::new(area.x, area.y + 2, area.width, 1);
+    let hint_area = Rect::new(area.x, area.y + 3, area.width, 1);
     frame.render_widget(hint_para, hint_area);
 }
 
+/// Recent quiz scores and how the last attempt went, e.g.
+/// "Quiz: 40% → 80%  ·  last 4/5 ✓✓✗✓✓ in 1m 30s, 2h ago"
+fn history_text(history: &[QuizAttempt], dates: &DateTimeFormat, now: i64) -> String {
+    let Some(last) = history.last() else {
+        return "Quiz not taken yet".to_string();
+    };
+    let scores: Vec<String> = history
+        .iter()
+        .skip(history.len().saturating_sub(HISTORY_SHOWN))
+        .map(|a| format!("{}%", a.percent()))
+        .collect();
+    let marks: String = last.results.iter().map(|&r| if r { '✓' } else { '✗' }).collect();
+    let duration = match last.duration_secs {
+        secs @ 0..60 => format!("{}s", secs),
+        secs => format!("{}m {}s", secs / 60, secs % 60),
+    };
+    format!(
+        "Quiz: {}{}  ·  last {}/{} {} in {}, {}",
+        if history.len() > HISTORY_SHOWN { "… " } else { "" },
+        scores.join(" → "),
+        last.correct(),
+        last.results.len(),
+        marks,
+        duration,
+        dates.relative(last.at, now)
+    )
+}
+
 /// Create a styled button
 fn create_button<'a>(text: &'a str, focused: bool, theme: &Theme) -> Line<'a> {
     let style = if focused {
@@ -103,3 +144,26 @@ fn create_button<'a>(text: &'a str, focused: bool, theme: &Theme) -> Line<'a> {
 pub fn footer_lines() -> usize {
     FOOTER_HEIGHT as usize
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_text_lists_scores_and_last_attempt() {
+        let attempt = |results: &[bool], duration_secs| QuizAttempt {
+            at: 0,
+            attempt: 1,
+            duration_secs,
+            results: results.to_vec(),
+        };
+        let dates = DateTimeFormat::default();
+
+        assert_eq!(history_text(&[], &dates, 0), "Quiz not taken yet");
+        let history = [attempt(&[true, false], 45), attempt(&[true, true], 90)];
+        assert_eq!(
+            history_text(&history, &dates, 7200),
+            "Quiz: 50% → 100%  ·  last 2/2 ✓✓ in 1m 30s, 2h ago"
+        );
+    }
+}
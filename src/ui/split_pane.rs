@@ -0,0 +1,136 @@
+//! Second content pane, for reading two sections side by side
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::app::state::{AppState, ContentState, SplitPane};
+use crate::book::{Book, Section};
+use crate::theme::Theme;
+
+use super::content::{LayoutCache, LayoutKey, RenderContext, panel_title, render_content_window};
+
+/// Draw the split pane beside the main content
+pub fn draw(frame: &mut Frame, area: Rect, state: &mut AppState, theme: &Theme, focused: bool) {
+    let AppState { split, book, content, .. } = state;
+    let Some(split) = split else { return };
+    // Out of the pane while drawing, so the pane can be updated
+    let other = split.book.take();
+    if let Some(book) = other.as_ref().or(book.as_ref()) {
+        let pane = Pane { split, book, other_book: other.is_some() };
+        pane.draw(frame, area, content, theme, focused);
+    }
+    split.book = other;
+}
+
+/// The split pane and the book it shows
+struct Pane<'a> {
+    split: &'a mut SplitPane,
+    book: &'a Book,
+    /// Whether the book isn't the main one, so is named in the title
+    other_book: bool,
+}
+
+impl Pane<'_> {
+    fn draw(
+        self,
+        frame: &mut Frame,
+        area: Rect,
+        content: &ContentState,
+        theme: &Theme,
+        focused: bool,
+    ) {
+        let border_color = if focused { theme.border_focused } else { theme.border };
+        let section = self.book.get_section(self.split.chapter, self.split.section);
+        let mut title = match section {
+            Some(section) if self.other_book => {
+                format!(" {} ·{}", self.book.metadata.title, panel_title(section))
+            }
+            Some(section) => panel_title(section),
+            None => " Split ".to_string(),
+        };
+        if self.split.scroll_bind.is_some() {
+            title.push_str("[scrollbind] ");
+        }
+
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color))
+            .style(Style::default().bg(theme.bg_primary));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let Some(section) = section else { return };
+        let key = LayoutKey {
+            book_id: self.book.metadata.id.clone(),
+            section_path: section.path.clone(),
+            theme: theme.name.clone(),
+            width: inner.width.saturating_sub(2) as usize,
+            typography: content.typography,
+            skim: None,
+            review: false,
+            note_anchors: Vec::new(),
+            image_heights: Default::default(),
+            cursor: None,
+            visual_mode: None,
+        };
+        let visible_height = inner.height as usize;
+        let lines =
+            visible_lines(self.split, section, &key, content.scroll_offset, visible_height, theme);
+        frame.render_widget(
+            Paragraph::new(lines),
+            Rect { width: inner.width.saturating_sub(1), ..inner },
+        );
+    }
+}
+
+/// The pane's lines in view, laying the section out again only when its
+/// layout inputs change or the view leaves the rendered window
+fn visible_lines(
+    split: &mut SplitPane,
+    section: &Section,
+    key: &LayoutKey,
+    main_offset: usize,
+    visible_height: usize,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let context = RenderContext {
+        theme,
+        width: key.width,
+        typography: key.typography,
+        review: key.review,
+        note_anchors: &[],
+        visual_mode: None,
+        cursor_state: None,
+        image_heights: &key.image_heights,
+    };
+    let render = |offset| render_content_window(&section.content, &context, offset, visible_height);
+
+    let offset = split.offset_for(main_offset);
+    let window = match split.layout_cache.take() {
+        Some(cache) if &cache.key == key && cache.window.covers(offset, visible_height) => {
+            cache.window
+        }
+        _ => render(offset),
+    };
+    split.max_scroll = window.content_lines.saturating_sub(visible_height);
+    split.scroll_offset = split.scroll_offset.min(split.max_scroll);
+    let offset = split.offset_for(main_offset);
+
+    let end = (offset + visible_height).min(window.content_lines);
+    let lines = (offset..end)
+        .map(|line| {
+            line.checked_sub(window.first_line)
+                .and_then(|i| window.lines.get(i))
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect();
+    split.layout_cache = Some(LayoutCache { key: key.clone(), window });
+    lines
+}
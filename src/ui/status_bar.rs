@@ -0,0 +1,89 @@
+//! Status bar above the command line
+//!
+//! The app decides which segments to show (`Config.statusbar`) and fills
+//! them in before each frame; this only lays them out, dropping segments from
+//! the right when they don't all fit and shortening the first if it alone
+//! doesn't.
+
+use std::borrow::Cow;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::theme::Theme;
+
+/// Between segments
+const SEPARATOR: &str = " │ ";
+
+/// Draw the segments, as many as fit
+pub fn draw(frame: &mut Frame, area: Rect, segments: &[String], theme: &Theme) {
+    let shown = fitting(segments, area.width.saturating_sub(2) as usize);
+    let mut spans = vec![Span::raw(" ")];
+    for (i, segment) in shown.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(SEPARATOR, Style::default().fg(theme.border)));
+        }
+        let color = if i == 0 { theme.fg_secondary } else { theme.fg_muted };
+        spans.push(Span::styled(segment.to_string(), Style::default().fg(color)));
+    }
+    let bar = Paragraph::new(Line::from(spans)).style(Style::default().bg(theme.bg_secondary));
+    frame.render_widget(bar, area);
+}
+
+/// The leading segments that fit in `width` columns, the first shortened
+/// with an ellipsis when even it is too wide
+fn fitting(segments: &[String], width: usize) -> Vec<Cow<'_, str>> {
+    let mut shown = Vec::new();
+    let mut used = 0;
+    for (i, segment) in segments.iter().enumerate() {
+        let separator = if i == 0 { 0 } else { SEPARATOR.width() };
+        used += separator + segment.width();
+        if used <= width {
+            shown.push(Cow::Borrowed(segment.as_str()));
+        } else {
+            if i == 0 && width > 0 {
+                shown.push(Cow::Owned(truncate(segment, width)));
+            }
+            break;
+        }
+    }
+    shown
+}
+
+/// Text cut to `width` columns, ending in an ellipsis
+fn truncate(text: &str, width: usize) -> String {
+    let mut cut = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width - 1 {
+            break;
+        }
+        cut.push(c);
+        used += w;
+    }
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_that_dont_fit_are_dropped() {
+        let segments: Vec<String> = ["Ch 2 › Traits", "40%", "Sonnet"].map(String::from).into();
+        assert_eq!(fitting(&segments, 80).len(), 3);
+        assert_eq!(fitting(&segments, 19), ["Ch 2 › Traits", "40%"]);
+
+        // The first segment is shortened rather than dropped
+        assert_eq!(fitting(&segments, 5), ["Ch 2…"]);
+        assert!(fitting(&segments, 0).is_empty());
+    }
+}